@@ -0,0 +1,103 @@
+/*!
+ * ClipVanish™ 拦截热路径基准
+ *
+ * 测量一次剪贴板拦截（加密、写入Base64、比较剪贴板中的内容是否为我们的密文）的耗时与堆分配次数
+ * 特点：
+ * - 自带计数分配器，只作用于本基准程序，不影响主程序与单元测试
+ * - 小内容快速路径的分配次数超出预算时以非零状态退出，可直接用于CI
+ * - 运行：cargo bench --bench interception
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::hint::black_box;
+use std::process;
+use std::time::Instant;
+use clipvanish::crypto::CryptoEngine;
+
+/// 按线程统计堆分配次数
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// 每个内容大小的计时轮数
+const ITERATIONS: u32 = 2_000;
+
+/// 小内容快速路径的上限（与crypto模块一致）
+const SMALL_CONTENT_LIMIT: usize = 4096;
+
+/// 快速路径每次拦截的分配预算：密文与Base64各一次
+///
+/// 内核密钥环模式下临时取出的密钥在栈上，擦除时同样不分配内存
+const FAST_PATH_ALLOCATIONS: usize = 2;
+
+/// 执行一次拦截
+fn intercept(engine: &CryptoEngine, content: &[u8]) {
+    let encrypted = engine.encrypt(content).expect("加密失败");
+    let base64_str = encrypted.to_base64();
+    assert!(encrypted.matches_base64(black_box(&base64_str)));
+}
+
+/// 一次拦截的堆分配次数
+fn allocations_per_interception(engine: &CryptoEngine, content: &[u8]) -> usize {
+    let before = ALLOCATIONS.with(|count| count.get());
+    intercept(engine, content);
+    ALLOCATIONS.with(|count| count.get()) - before
+}
+
+fn main() {
+    let engine = CryptoEngine::new().expect("初始化加密引擎失败");
+    let mut over_budget = false;
+
+    println!("{:>8}  {:>12}  {:>8}", "大小", "每次耗时", "分配次数");
+    for size in [16, 256, 1024, SMALL_CONTENT_LIMIT, 16 * 1024, 256 * 1024] {
+        let content = vec![b'x'; size];
+
+        // 预热一次，排除首次调用的惰性初始化
+        intercept(&engine, &content);
+        let allocations = allocations_per_interception(&engine, &content);
+
+        let started = Instant::now();
+        for _ in 0..ITERATIONS {
+            intercept(&engine, black_box(&content));
+        }
+        let per_iteration = started.elapsed() / ITERATIONS;
+
+        let fast_path = size <= SMALL_CONTENT_LIMIT;
+        let marker = if fast_path && allocations > FAST_PATH_ALLOCATIONS {
+            over_budget = true;
+            "  ❌ 超出预算"
+        } else {
+            ""
+        };
+        println!("{:>8}  {:>12?}  {:>8}{}", size, per_iteration, allocations, marker);
+    }
+
+    if over_budget {
+        eprintln!("快速路径每次拦截最多 {} 次分配", FAST_PATH_ALLOCATIONS);
+        process::exit(1);
+    }
+}
@@ -28,7 +28,67 @@ fn main() {
     // 设置版本信息
     println!("cargo:rustc-env=CARGO_PKG_VERSION={}", env!("CARGO_PKG_VERSION"));
     
+    // 构建元数据（供 --version --json 使用）
+    println!("cargo:rustc-env=CLIPVANISH_GIT_COMMIT={}", git_commit());
+    println!("cargo:rustc-env=CLIPVANISH_BUILD_DATE={}", build_date());
+    println!("cargo:rustc-env=CLIPVANISH_TARGET={}", std::env::var("TARGET").unwrap_or_default());
+    println!("cargo:rustc-env=CLIPVANISH_PROFILE={}", std::env::var("PROFILE").unwrap_or_default());
+    println!("cargo:rustc-env=CLIPVANISH_FEATURES={}", enabled_features().join(","));
+    
     // 重新运行条件
     println!("cargo:rerun-if-changed=build.rs");
     println!("cargo:rerun-if-changed=Cargo.toml");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+}
+
+/// 获取当前git提交（短哈希），不在git仓库中时返回unknown
+fn git_commit() -> String {
+    std::process::Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|commit| commit.trim().to_string())
+        .filter(|commit| !commit.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// 获取构建日期（UTC，YYYY-MM-DD），设置SOURCE_DATE_EPOCH时使用该时间以支持可复现构建
+fn build_date() -> String {
+    let seconds = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|value| value.parse::<i64>().ok())
+        .unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0)
+        });
+
+    // 将Unix天数转换为公历日期
+    let days = seconds.div_euclid(86400);
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// 收集已启用的cargo特性
+fn enabled_features() -> Vec<String> {
+    let mut features: Vec<String> = std::env::vars()
+        .filter_map(|(key, _)| key.strip_prefix("CARGO_FEATURE_").map(str::to_string))
+        .map(|name| name.to_lowercase().replace('_', "-"))
+        .filter(|name| name != "default")
+        .collect();
+    features.sort();
+    features
 }
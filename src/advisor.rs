@@ -0,0 +1,351 @@
+/*!
+ * ClipVanish™ 剪贴板卫生建议模块
+ *
+ * 根据审计日志中最近7天的保护、粘贴、延长、销毁与紧急销毁记录计算剪贴板卫生评分，并给出具体的配置建议
+ * 特点：
+ * - 评分0到100，由平均暴露时间、销毁前粘贴的比例、延长频率与紧急销毁频率加权得出
+ * - 建议指向具体设置与取值（如把默认倒计时缩短到多少秒），只在数据支持时给出
+ * - 结果缓存一小时，`status` 与菜单栏共用；条目太少时不评分
+ * - 只使用审计日志中的元数据，不读取任何内容
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::Serialize;
+use crate::audit::{AuditEntry, AuditLog};
+use crate::config::{Config, CountdownAnchor};
+use crate::stats;
+
+/// 评分缓存的有效时间
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// 统计区间（天）
+const PERIOD_DAYS: i64 = 7;
+
+/// 参与评分的最少条目数
+pub const MIN_ITEMS: usize = 5;
+
+/// 平均暴露时间不超过此值（秒）时该项满分
+const GOOD_EXPOSURE_SECONDS: f64 = 30.0;
+
+/// 平均暴露时间达到此值（秒）时该项为零分
+const POOR_EXPOSURE_SECONDS: f64 = 600.0;
+
+/// 平均每个条目的延长次数达到此值时该项为零分
+const POOR_EXTENSIONS_PER_ITEM: f64 = 0.5;
+
+/// 平均每个条目的紧急销毁次数达到此值时该项为零分
+const POOR_NUKES_PER_ITEM: f64 = 0.2;
+
+/// 各项权重：暴露时间、销毁前粘贴、延长、紧急销毁
+const WEIGHTS: [f64; 4] = [40.0, 25.0, 20.0, 15.0];
+
+/// 卫生评分所依据的指标
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HygieneMetrics {
+    /// 统计区间内的受保护条目数
+    pub items: usize,
+    /// 已销毁条目的平均暴露时间（秒）
+    pub average_exposure_seconds: Option<f64>,
+    /// 销毁前至少粘贴过一次的条目比例
+    pub pasted_ratio: f64,
+    /// 90%的已粘贴条目在复制后多少秒内首次粘贴
+    pub first_paste_p90_seconds: Option<f64>,
+    /// 已粘贴条目中只粘贴一次的比例
+    pub single_paste_ratio: Option<f64>,
+    /// 平均每个条目的延长次数
+    pub extensions_per_item: f64,
+    /// 统计区间内的紧急销毁次数
+    pub nukes: usize,
+}
+
+/// 剪贴板卫生评分与建议
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HygieneReport {
+    /// 评分（0到100）
+    pub score: u8,
+    /// 指标
+    pub metrics: HygieneMetrics,
+    /// 具体建议
+    pub recommendations: Vec<String>,
+}
+
+impl HygieneReport {
+    /// 评分等级
+    pub fn grade(&self) -> &'static str {
+        match self.score {
+            85.. => "优秀",
+            70..=84 => "良好",
+            50..=69 => "一般",
+            _ => "较差",
+        }
+    }
+}
+
+impl fmt::Display for HygieneReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/100（{}）", self.score, self.grade())
+    }
+}
+
+/// 单个条目在审计日志中的经过
+struct ItemHistory {
+    protected_at: DateTime<Utc>,
+    first_paste: Option<DateTime<Utc>>,
+    pastes: u32,
+    extensions: u32,
+    destroyed_at: Option<DateTime<Utc>>,
+}
+
+/// 按0到1截断
+fn clamp_unit(value: f64) -> f64 {
+    value.clamp(0.0, 1.0)
+}
+
+/// 从审计记录汇总指标（条目不足时返回None）
+///
+/// # 参数
+/// * `entries` - 按时间顺序排列的审计记录
+/// * `now` - 统计截止时间
+fn collect_metrics(entries: &[AuditEntry], now: DateTime<Utc>) -> Option<HygieneMetrics> {
+    let period_start = now - ChronoDuration::days(PERIOD_DAYS);
+    let mut items: HashMap<&str, ItemHistory> = HashMap::new();
+    let mut nukes = 0;
+
+    for entry in entries.iter().filter(|entry| entry.timestamp >= period_start) {
+        if entry.event == "nuke" {
+            nukes += 1;
+            continue;
+        }
+        let Some(id) = stats::item_id(&entry.detail) else { continue };
+        match entry.event.as_str() {
+            "protected" => {
+                items.insert(
+                    id,
+                    ItemHistory { protected_at: entry.timestamp, first_paste: None, pastes: 0, extensions: 0, destroyed_at: None },
+                );
+            }
+            "pasted" => {
+                if let Some(item) = items.get_mut(id).filter(|item| item.destroyed_at.is_none()) {
+                    item.first_paste.get_or_insert(entry.timestamp);
+                    item.pastes += 1;
+                }
+            }
+            "extended" => {
+                if let Some(item) = items.get_mut(id) {
+                    item.extensions += 1;
+                }
+            }
+            "destroyed" => {
+                if let Some(item) = items.get_mut(id) {
+                    item.destroyed_at.get_or_insert(entry.timestamp);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let items: Vec<ItemHistory> = items.into_values().collect();
+    if items.len() < MIN_ITEMS {
+        return None;
+    }
+    let count = items.len() as f64;
+    let seconds = |from: DateTime<Utc>, to: DateTime<Utc>| (to - from).num_milliseconds().max(0) as f64 / 1000.0;
+
+    let exposures: Vec<f64> = items
+        .iter()
+        .filter_map(|item| Some(seconds(item.protected_at, item.destroyed_at?)))
+        .collect();
+    let mut first_pastes: Vec<f64> = items
+        .iter()
+        .filter_map(|item| Some(seconds(item.protected_at, item.first_paste?)))
+        .collect();
+    first_pastes.sort_by(f64::total_cmp);
+    let pasted = first_pastes.len();
+
+    Some(HygieneMetrics {
+        items: items.len(),
+        average_exposure_seconds: (!exposures.is_empty()).then(|| exposures.iter().sum::<f64>() / exposures.len() as f64),
+        pasted_ratio: pasted as f64 / count,
+        first_paste_p90_seconds: (pasted > 0).then(|| first_pastes[((pasted as f64 * 0.9).ceil() as usize).clamp(1, pasted) - 1]),
+        single_paste_ratio: (pasted > 0)
+            .then(|| items.iter().filter(|item| item.pastes == 1).count() as f64 / pasted as f64),
+        extensions_per_item: items.iter().map(|item| item.extensions as f64).sum::<f64>() / count,
+        nukes,
+    })
+}
+
+/// 按指标计算评分（没有已销毁条目时暴露时间不参与评分，其余权重按比例放大）
+fn score(metrics: &HygieneMetrics) -> u8 {
+    let exposure = metrics.average_exposure_seconds.map(|average| {
+        clamp_unit((POOR_EXPOSURE_SECONDS - average) / (POOR_EXPOSURE_SECONDS - GOOD_EXPOSURE_SECONDS))
+    });
+    let parts = [
+        exposure,
+        Some(metrics.pasted_ratio),
+        Some(1.0 - clamp_unit(metrics.extensions_per_item / POOR_EXTENSIONS_PER_ITEM)),
+        Some(1.0 - clamp_unit(metrics.nukes as f64 / metrics.items as f64 / POOR_NUKES_PER_ITEM)),
+    ];
+
+    let (points, weights) = parts
+        .iter()
+        .zip(WEIGHTS)
+        .filter_map(|(part, weight)| part.map(|part| (part * weight, weight)))
+        .fold((0.0, 0.0), |(points, weights), (p, w)| (points + p, weights + w));
+    (points / weights * 100.0).round() as u8
+}
+
+/// 按指标与当前配置给出建议
+fn recommend(metrics: &HygieneMetrics, config: &Config) -> Vec<String> {
+    let mut recommendations = Vec::new();
+    let countdown = config.timer.default_countdown;
+
+    if let Some(p90) = metrics.first_paste_p90_seconds {
+        // 建议值取首次粘贴耗时的两倍，向上取整到5秒
+        let suggested = ((p90 * 2.0 / 5.0).ceil() as u64 * 5).max(config.timer.min_countdown);
+        if config.timer.anchor == CountdownAnchor::Copy && metrics.pasted_ratio >= 0.5 && suggested + 5 < countdown {
+            recommendations.push(format!(
+                "90%的条目在复制后 {:.0}秒内首次粘贴，默认倒计时 {}秒偏长，建议将 timer.default_countdown 缩短为 {}",
+                p90, countdown, suggested
+            ));
+        }
+    }
+
+    if metrics.extensions_per_item >= 0.3 {
+        if config.timer.anchor == CountdownAnchor::Copy {
+            recommendations.push(format!(
+                "平均每个条目延长 {:.1} 次，建议设置 timer.anchor = \"paste\"，首次粘贴时才开始倒计时",
+                metrics.extensions_per_item
+            ));
+        } else {
+            recommendations.push(format!(
+                "平均每个条目延长 {:.1} 次，建议将 timer.default_countdown 延长为 {}",
+                metrics.extensions_per_item,
+                (countdown * 2).min(config.timer.max_countdown)
+            ));
+        }
+    }
+
+    if !config.security.destroy_on_paste && metrics.single_paste_ratio.is_some_and(|ratio| ratio >= 0.8) {
+        recommendations.push(format!(
+            "{:.0}%的已粘贴条目只粘贴一次，建议启用 security.destroy_on_paste，粘贴后立即销毁",
+            metrics.single_paste_ratio.unwrap_or_default() * 100.0
+        ));
+    }
+
+    if metrics.pasted_ratio < 0.5 {
+        recommendations.push(format!(
+            "{:.0}%的条目直到销毁都未被粘贴，检查是否有规则误报，可在配置中关闭不需要的规则",
+            (1.0 - metrics.pasted_ratio) * 100.0
+        ));
+    }
+
+    if metrics.nukes >= 3 {
+        recommendations.push(format!(
+            "最近{}天紧急销毁 {} 次，如果是离开前清理，可以改用 nuke --in 定时销毁，或缩短默认倒计时",
+            PERIOD_DAYS, metrics.nukes
+        ));
+    }
+
+    recommendations
+}
+
+/// 根据审计记录计算卫生评分（条目不足时返回None）
+///
+/// # 参数
+/// * `entries` - 按时间顺序排列的审计记录
+/// * `now` - 统计截止时间
+/// * `config` - 当前配置（用于给出建议）
+pub fn evaluate(entries: &[AuditEntry], now: DateTime<Utc>, config: &Config) -> Option<HygieneReport> {
+    let metrics = collect_metrics(entries, now)?;
+    Some(HygieneReport {
+        score: score(&metrics),
+        recommendations: recommend(&metrics, config),
+        metrics,
+    })
+}
+
+/// 缓存的评分与计算时间
+static CACHE: Mutex<Option<(Instant, Option<HygieneReport>)>> = Mutex::new(None);
+
+/// 当前的卫生评分（缓存超过 `REFRESH_INTERVAL` 后重新读取审计日志计算）
+///
+/// # 参数
+/// * `config` - 当前配置
+pub fn current(config: &Config) -> Option<HygieneReport> {
+    let mut cache = CACHE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some((computed_at, report)) = cache.as_ref() {
+        if computed_at.elapsed() < REFRESH_INTERVAL {
+            return report.clone();
+        }
+    }
+    let report = AuditLog::open_default()
+        .and_then(|log| log.read_all())
+        .ok()
+        .and_then(|entries| evaluate(&entries, Utc::now(), config));
+    *cache = Some((Instant::now(), report.clone()));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(at: DateTime<Utc>, event: &str, detail: String) -> AuditEntry {
+        AuditEntry { timestamp: at, event: event.to_string(), detail, hash: None }
+    }
+
+    /// 生成若干条目：复制后 `paste_after` 秒粘贴，`destroy_after` 秒销毁，每条延长 `extensions` 次
+    fn items(count: usize, paste_after: Option<i64>, destroy_after: i64, extensions: usize) -> Vec<AuditEntry> {
+        let start = Utc::now() - ChronoDuration::hours(1);
+        let mut entries = Vec::new();
+        for index in 0..count {
+            let at = start + ChronoDuration::minutes(index as i64);
+            entries.push(entry(at, "protected", format!("条目 #{} 类别=sensitive_pattern/regex 长度=20", index)));
+            if let Some(after) = paste_after {
+                entries.push(entry(at + ChronoDuration::seconds(after), "pasted", format!("条目 #{}", index)));
+            }
+            for _ in 0..extensions {
+                entries.push(entry(at + ChronoDuration::seconds(1), "extended", format!("条目 #{} 延长=60秒", index)));
+            }
+            entries.push(entry(at + ChronoDuration::seconds(destroy_after), "destroyed", format!("条目 #{} 原因=倒计时到期", index)));
+        }
+        entries
+    }
+
+    #[test]
+    fn test_good_habits_score_high() {
+        let mut config = Config::default();
+        config.timer.default_countdown = 120;
+
+        let report = evaluate(&items(10, Some(5), 6, 0), Utc::now(), &config).unwrap();
+        assert_eq!(report.metrics.items, 10);
+        assert_eq!(report.metrics.pasted_ratio, 1.0);
+        assert_eq!(report.metrics.first_paste_p90_seconds, Some(5.0));
+        assert_eq!(report.score, 100);
+        assert_eq!(report.grade(), "优秀");
+        // 首次粘贴很快而默认倒计时较长时建议缩短
+        assert!(report.recommendations.iter().any(|r| r.contains("timer.default_countdown 缩短为 10")), "{:?}", report.recommendations);
+    }
+
+    #[test]
+    fn test_poor_habits_score_low_with_recommendations() {
+        let config = Config::default();
+        let report = evaluate(&items(10, None, 900, 1), Utc::now(), &config).unwrap();
+        assert_eq!(report.metrics.pasted_ratio, 0.0);
+        assert_eq!(report.metrics.extensions_per_item, 1.0);
+        // 只有紧急销毁一项满分
+        assert_eq!(report.score, 15);
+        assert_eq!(report.grade(), "较差");
+        assert!(report.recommendations.iter().any(|r| r.contains("timer.anchor")));
+        assert!(report.recommendations.iter().any(|r| r.contains("未被粘贴")));
+
+        // 条目太少时不评分
+        assert!(evaluate(&items(MIN_ITEMS - 1, Some(5), 6, 0), Utc::now(), &config).is_none());
+    }
+}
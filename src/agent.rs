@@ -0,0 +1,384 @@
+/*!
+ * ClipVanish™ 代理套接字模块
+ *
+ * 类似ssh-agent的本地套接字：预先批准的程序（如terraform、kubectl）可以直接取得当前受保护内容，
+ * 明文不会出现在普通剪贴板中
+ * 特点：
+ * - 按对端进程的可执行文件路径与SHA-256识别客户端，未登记或哈希不符的程序一律拒绝
+ * - 每个客户端单独配置审批策略（ask/allow/deny），ask时在服务终端询问，超时视为拒绝
+ * - 每个受保护条目只释放一次，释放视同一次粘贴：密钥重置，粘贴倒计时照常启动
+ * - 放行与拒绝都写入审计日志
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use zeroize::Zeroizing;
+use crate::audit;
+use crate::clipboard::ClipboardMonitor;
+use crate::config::{AgentApproval, AgentClient, AgentConfig};
+use crate::ipc::IpcResponse;
+use crate::ipc_auth;
+use crate::item_id::ItemId;
+use crate::prompt::ConfirmPrompt;
+
+/// 代理错误类型
+#[derive(Debug)]
+pub enum AgentError {
+    /// 无法确认对端进程
+    Unverifiable(String),
+    /// 对端程序未登记
+    UnknownClient(PathBuf),
+    /// 对端程序哈希与登记的不一致
+    HashMismatch(String),
+    /// 客户端策略或用户拒绝
+    Denied(String),
+    /// 没有受保护内容
+    NoContent,
+    /// 该条目已释放过
+    AlreadyReleased(ItemId),
+    /// 解密受保护内容失败
+    ReadFailed(String),
+}
+
+impl std::fmt::Display for AgentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AgentError::Unverifiable(msg) => write!(f, "无法确认客户端: {}", msg),
+            AgentError::UnknownClient(path) => write!(f, "未批准的客户端: {}", path.display()),
+            AgentError::HashMismatch(name) => write!(f, "客户端 {} 的可执行文件哈希与登记的不一致", name),
+            AgentError::Denied(name) => write!(f, "客户端 {} 的请求被拒绝", name),
+            AgentError::NoContent => write!(f, "没有受保护内容"),
+            AgentError::AlreadyReleased(id) => write!(f, "条目 {} 已交给代理客户端，不能再次读取", id),
+            AgentError::ReadFailed(msg) => write!(f, "读取受保护内容失败: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for AgentError {}
+
+/// 代理命令
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentCommand {
+    /// 读取当前受保护内容
+    Get,
+}
+
+/// 代理请求
+#[derive(Debug, Deserialize)]
+pub struct AgentRequest {
+    /// 命令
+    pub command: AgentCommand,
+}
+
+/// 释放给客户端的条目（借用明文，序列化时不产生额外副本）
+#[derive(Serialize)]
+struct Release<'a> {
+    /// 条目编号
+    item: ItemId,
+    /// 明文内容
+    content: &'a str,
+}
+
+/// 释放成功时的应答，字段与`IpcResponse`一致
+#[derive(Serialize)]
+struct ReleaseResponse<'a> {
+    ok: bool,
+    data: Release<'a>,
+}
+
+/// 按可执行文件路径与哈希查找已批准的客户端
+///
+/// # 参数
+/// * `clients` - 已批准的客户端
+/// * `executable` - 对端进程的可执行文件路径
+/// * `sha256` - 对端可执行文件的SHA-256（十六进制）
+pub fn match_client<'a>(clients: &'a [AgentClient], executable: &Path, sha256: &str) -> Result<&'a AgentClient, AgentError> {
+    let client = clients
+        .iter()
+        .find(|client| fs::canonicalize(&client.path).unwrap_or_else(|_| client.path.clone()) == executable)
+        .ok_or_else(|| AgentError::UnknownClient(executable.to_path_buf()))?;
+
+    if client.sha256.trim().eq_ignore_ascii_case(sha256) {
+        Ok(client)
+    } else {
+        Err(AgentError::HashMismatch(client.name.clone()))
+    }
+}
+
+/// 对端进程的可执行文件路径与SHA-256
+///
+/// 哈希直接读取 /proc/<pid>/exe，即正在运行的映像；路径上的文件被替换或删除时路径不再匹配
+#[cfg(target_os = "linux")]
+fn peer_executable(pid: i32) -> std::io::Result<(PathBuf, String)> {
+    let link = PathBuf::from(format!("/proc/{}/exe", pid));
+    let path = fs::read_link(&link)?;
+    let sha256 = sha256_file(&link)?;
+    Ok((path, sha256))
+}
+
+/// 对端进程的可执行文件路径与SHA-256
+#[cfg(target_os = "macos")]
+fn peer_executable(pid: i32) -> std::io::Result<(PathBuf, String)> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut buffer = vec![0u8; libc::PROC_PIDPATHINFO_MAXSIZE as usize];
+    let length = unsafe { libc::proc_pidpath(pid, buffer.as_mut_ptr() as *mut libc::c_void, buffer.len() as u32) };
+    if length <= 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    let path = PathBuf::from(std::ffi::OsStr::from_bytes(&buffer[..length as usize]));
+    let sha256 = sha256_file(&path)?;
+    Ok((path, sha256))
+}
+
+/// 对端进程的可执行文件路径与SHA-256
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn peer_executable(_pid: i32) -> std::io::Result<(PathBuf, String)> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "当前平台无法确认对端进程的可执行文件"))
+}
+
+/// 流式计算文件的SHA-256（十六进制小写），可执行文件无需整体读入内存
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn sha256_file(path: &Path) -> std::io::Result<String> {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(crate::encoding::encode_hex(&hasher.finalize()))
+}
+
+/// 代理套接字服务
+pub struct AgentServer {
+    /// 代理套接字路径
+    socket_path: PathBuf,
+    /// 代理配置（启动时读取，修改客户端后需重启服务）
+    config: AgentConfig,
+    /// 剪贴板监听器
+    monitor: Arc<ClipboardMonitor>,
+    /// 最近释放给代理客户端的条目
+    released: Mutex<Option<ItemId>>,
+    /// 审批提示（同一时间只询问一个请求）
+    approval: tokio::sync::Mutex<()>,
+}
+
+impl AgentServer {
+    /// 创建代理套接字服务
+    ///
+    /// # 参数
+    /// * `socket_path` - 代理套接字路径
+    /// * `config` - 代理配置
+    /// * `monitor` - 剪贴板监听器
+    pub fn new(socket_path: PathBuf, config: AgentConfig, monitor: Arc<ClipboardMonitor>) -> Self {
+        AgentServer {
+            socket_path,
+            config,
+            monitor,
+            released: Mutex::new(None),
+            approval: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// 代理套接字路径
+    pub fn socket_path(&self) -> &Path {
+        &self.socket_path
+    }
+
+    /// 绑定代理套接字（仅当前用户可访问）
+    pub fn bind(&self) -> std::io::Result<UnixListener> {
+        ipc_auth::prepare_socket_path(&self.socket_path)?;
+        let listener = UnixListener::bind(&self.socket_path)?;
+        ipc_auth::restrict_permissions(&self.socket_path, 0o600)?;
+        info!("代理套接字已启动: {}", self.socket_path.display());
+        Ok(listener)
+    }
+
+    /// 接受并处理连接，直到任务被取消
+    ///
+    /// # 参数
+    /// * `listener` - 已绑定的监听器
+    pub async fn serve(self: Arc<Self>, listener: UnixListener) {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let server = self.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = server.handle_connection(stream).await {
+                            debug!("代理连接结束: {}", e);
+                        }
+                    });
+                },
+                Err(e) => warn!("接受代理连接失败: {}", e),
+            }
+        }
+    }
+
+    /// 处理单个连接（每个连接一条请求）
+    async fn handle_connection(&self, stream: UnixStream) -> std::io::Result<()> {
+        let cred = stream.peer_cred();
+        let (reader, mut writer) = stream.into_split();
+
+        let mut line = String::new();
+        BufReader::new(reader).read_line(&mut line).await?;
+        let line = Zeroizing::new(line);
+
+        let result = match serde_json::from_str::<AgentRequest>(&line) {
+            Ok(request) => match cred {
+                Ok(cred) => self.handle_request(&request, cred.uid(), cred.pid()).await,
+                Err(e) => Err(AgentError::Unverifiable(e.to_string())),
+            },
+            Err(e) => {
+                let response = IpcResponse::failure(format!("无效请求: {}", e));
+                return writer.write_all(format!("{}\n", serde_json::to_string(&response)?).as_bytes()).await;
+            },
+        };
+
+        let text = match result {
+            Ok((item, content)) => {
+                // 按最坏的转义长度预留空间，避免扩容时在释放的旧缓冲区里留下明文
+                let mut text = Zeroizing::new(Vec::with_capacity(content.len() * 6 + 128));
+                let response = ReleaseResponse { ok: true, data: Release { item, content: &content } };
+                serde_json::to_writer(&mut *text, &response)?;
+                text.push(b'\n');
+                text
+            },
+            Err(e) => {
+                audit::record("agent_denied", &e.to_string());
+                let response = IpcResponse::failure(e);
+                Zeroizing::new(format!("{}\n", serde_json::to_string(&response)?).into_bytes())
+            },
+        };
+        writer.write_all(&text).await
+    }
+
+    /// 识别客户端、按策略审批并释放当前受保护内容
+    async fn handle_request(&self, request: &AgentRequest, uid: u32, pid: Option<i32>) -> Result<(ItemId, Zeroizing<String>), AgentError> {
+        let AgentCommand::Get = request.command;
+
+        ipc_auth::check_peer_uid(uid).map_err(|e| AgentError::Unverifiable(e.to_string()))?;
+        let pid = pid.ok_or_else(|| AgentError::Unverifiable("无法获取对端进程ID".to_string()))?;
+        let (executable, sha256) = peer_executable(pid)
+            .map_err(|e| AgentError::Unverifiable(format!("pid={} {}", pid, e)))?;
+        let client = match_client(&self.config.clients, &executable, &sha256)?;
+
+        let item_id = self.pending_item()?;
+        match client.approval {
+            AgentApproval::Deny => return Err(AgentError::Denied(client.name.clone())),
+            AgentApproval::Allow => {},
+            AgentApproval::Ask => {
+                let _guard = self.approval.lock().await;
+                let question = format!("🔐 允许 {}（{}，pid {}）读取条目 {}?", client.name, executable.display(), pid, item_id);
+                let timeout = Duration::from_secs(self.config.approval_timeout_seconds);
+                let approved = tokio::task::spawn_blocking(move || ConfirmPrompt::new(false, timeout).ask(&question).is_confirmed())
+                    .await
+                    .unwrap_or(false);
+                if !approved {
+                    return Err(AgentError::Denied(client.name.clone()));
+                }
+                // 等待审批期间条目可能已被替换或清除
+                if self.pending_item()? != item_id {
+                    return Err(AgentError::Denied(client.name.clone()));
+                }
+            },
+        }
+
+        // 与应用粘贴相同：解密一次并重置密钥，随后启动粘贴倒计时
+        let content = self.monitor.get_decrypted_content_for_paste()
+            .map_err(|e| AgentError::ReadFailed(e.to_string()))?
+            .map(Zeroizing::new)
+            .ok_or(AgentError::NoContent)?;
+        *self.released.lock().unwrap() = Some(item_id);
+        if let Err(e) = self.monitor.handle_paste(&content) {
+            warn!("代理释放后启动粘贴倒计时失败: {}", e);
+        }
+
+        audit::record("agent_release", &format!("条目 {} 已交给 {}（pid {}）", item_id, client.name, pid));
+        info!("条目 {} 已交给代理客户端 {}", item_id, client.name);
+        Ok((item_id, content))
+    }
+
+    /// 当前可释放的条目
+    fn pending_item(&self) -> Result<ItemId, AgentError> {
+        let item_id = self.monitor.current_item_id()
+            .filter(|_| self.monitor.has_protected_item())
+            .ok_or(AgentError::NoContent)?;
+        if *self.released.lock().unwrap() == Some(item_id) {
+            return Err(AgentError::AlreadyReleased(item_id));
+        }
+        Ok(item_id)
+    }
+}
+
+impl Drop for AgentServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.socket_path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(name: &str, path: &str, sha256: &str) -> AgentClient {
+        AgentClient {
+            name: name.to_string(),
+            path: PathBuf::from(path),
+            sha256: sha256.to_string(),
+            approval: AgentApproval::Ask,
+        }
+    }
+
+    #[test]
+    fn test_match_client_by_path_and_hash() {
+        let clients = vec![
+            client("terraform", "/nonexistent/bin/terraform", "AB12"),
+            client("kubectl", "/nonexistent/bin/kubectl", "cd34"),
+        ];
+
+        let matched = match_client(&clients, Path::new("/nonexistent/bin/terraform"), "ab12").unwrap();
+        assert_eq!(matched.name, "terraform");
+        assert!(matches!(
+            match_client(&clients, Path::new("/nonexistent/bin/kubectl"), "ab12"),
+            Err(AgentError::HashMismatch(name)) if name == "kubectl"
+        ));
+        assert!(matches!(
+            match_client(&clients, Path::new("/usr/bin/curl"), "cd34"),
+            Err(AgentError::UnknownClient(_))
+        ));
+    }
+
+    #[test]
+    fn test_request_parsing() {
+        let request: AgentRequest = serde_json::from_str(r#"{"command":"get"}"#).unwrap();
+        assert_eq!(request.command, AgentCommand::Get);
+        assert!(serde_json::from_str::<AgentRequest>(r#"{"command":"nuke"}"#).is_err());
+
+        let config: AgentConfig = serde_json::from_str(
+            r#"{"enabled":true,"clients":[{"name":"kubectl","path":"/usr/bin/kubectl","sha256":"00","approval":"allow"}]}"#,
+        ).unwrap();
+        assert_eq!(config.approval_timeout_seconds, 30);
+        assert_eq!(config.clients[0].approval, AgentApproval::Allow);
+    }
+
+    #[test]
+    fn test_release_response_format() {
+        let item = ItemId::generate();
+        let response = ReleaseResponse { ok: true, data: Release { item, content: "pa\"ss\nword" } };
+        let mut text = Zeroizing::new(Vec::new());
+        serde_json::to_writer(&mut *text, &response).unwrap();
+
+        let value: serde_json::Value = serde_json::from_slice(&text).unwrap();
+        assert_eq!(value["ok"], true);
+        assert_eq!(value["data"]["item"], item.to_string());
+        assert_eq!(value["data"]["content"], "pa\"ss\nword");
+        assert!(value.get("error").is_none());
+    }
+}
@@ -0,0 +1,658 @@
+/*!
+ * ClipVanish™ 审计日志模块
+ *
+ * 以追加方式记录安全相关事件，便于事后追溯
+ * 特点：
+ * - JSON Lines格式，每行一条记录
+ * - 只追加不修改
+ * - 从不记录明文内容
+ * - 每条记录带有哈希链（SHA-256），删改或插入记录都会使其后的链值对不上
+ * - 新记录同时发布到事件总线，供webhook等订阅者使用
+ * - 订阅时可按事件类型、规则标签和最低严重级别过滤，高频事件不会淹没下游
+ * - 可按时间范围导出为JSON Lines或CSV，供SIEM采集并在下游重新校验哈希链
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use crate::config::Config;
+use crate::encoding::encode_hex;
+use crate::duration;
+
+/// 哈希链起点（第一条记录或旧版本写入的无链记录之后）
+pub const GENESIS: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// 读取最后一条记录时从文件末尾读取的字节数
+const TAIL_BYTES: u64 = 64 * 1024;
+
+/// 审计日志错误类型
+#[derive(Debug)]
+pub enum AuditError {
+    /// 日志文件访问失败
+    IoError(std::io::Error),
+    /// 记录序列化或解析失败
+    FormatError(serde_json::Error),
+    /// 无法确定日志位置
+    LocationError(String),
+    /// 导出时间范围无效
+    InvalidRange(String),
+}
+
+impl std::fmt::Display for AuditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuditError::IoError(e) => write!(f, "审计日志访问失败: {}", e),
+            AuditError::FormatError(e) => write!(f, "审计记录格式错误: {}", e),
+            AuditError::LocationError(msg) => write!(f, "无法确定审计日志位置: {}", msg),
+            AuditError::InvalidRange(msg) => write!(f, "时间范围无效: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for AuditError {}
+
+/// 单条审计记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// 记录时间（UTC）
+    pub timestamp: DateTime<Utc>,
+    /// 事件类型
+    pub event: String,
+    /// 事件详情（不得包含明文）
+    pub detail: String,
+    /// 哈希链值（旧版本写入的记录没有）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+}
+
+impl AuditEntry {
+    /// 计算链值：SHA-256(上一条链值 + "\n" + 紧凑JSON {"detail","event","timestamp"})，十六进制小写
+    ///
+    /// JSON按键名排序、不含空白，非ASCII字符原样输出（UTF-8）
+    ///
+    /// # 参数
+    /// * `prev` - 上一条记录的链值（没有时为 `GENESIS`）
+    pub fn chain_hash(&self, prev: &str) -> String {
+        let body = serde_json::json!({ "timestamp": self.timestamp, "event": self.event, "detail": self.detail });
+        let mut hasher = Sha256::default();
+        hasher.update(prev.as_bytes());
+        hasher.update(b"\n");
+        hasher.update(body.to_string().as_bytes());
+        encode_hex(&hasher.finalize())
+    }
+}
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Default, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// JSON Lines（每行一条记录）
+    #[default]
+    Jsonl,
+    /// CSV（RFC 4180，首行为列名）
+    Csv,
+}
+
+impl std::fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportFormat::Jsonl => write!(f, "jsonl"),
+            ExportFormat::Csv => write!(f, "csv"),
+        }
+    }
+}
+
+/// 导出结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportSummary {
+    /// 导出的记录数
+    pub exported: usize,
+    /// 第一条导出记录之前的链值（下游校验的起点）
+    pub anchor: String,
+    /// 最后一条导出记录的链值
+    pub head: String,
+    /// 哈希链第一次对不上的记录序号（从1开始，按整个日志计）
+    pub broken_at: Option<usize>,
+}
+
+/// 导出的一行
+#[derive(Serialize)]
+struct ExportRecord<'a> {
+    timestamp: &'a DateTime<Utc>,
+    event: &'a str,
+    detail: &'a str,
+    prev_hash: &'a str,
+    hash: Option<&'a str>,
+}
+
+/// 解析导出时间范围的一端
+///
+/// 支持 `now`、相对时长（如 `7d` 表示7天前）、RFC 3339、`YYYY-MM-DD[ HH:MM[:SS]]`（本地时间）；
+/// 作为结束时间的纯日期包含当天全天
+///
+/// # 参数
+/// * `text` - 时间文本
+/// * `now` - 当前时间
+/// * `end` - 是否为结束时间
+pub fn parse_bound(text: &str, now: DateTime<Utc>, end: bool) -> Result<DateTime<Utc>, AuditError> {
+    let text = text.trim();
+    let invalid = || AuditError::InvalidRange(text.to_string());
+    if text == "now" {
+        return Ok(now);
+    }
+    if let Ok(at) = DateTime::parse_from_rfc3339(text) {
+        return Ok(at.with_timezone(&Utc));
+    }
+    let local = |naive: NaiveDateTime| Local.from_local_datetime(&naive).earliest().map(|at| at.with_timezone(&Utc));
+    for format in ["%Y-%m-%d %H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%dT%H:%M:%S"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(text, format) {
+            return local(naive).ok_or_else(invalid);
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        let date = if end { date.succ_opt().ok_or_else(invalid)? } else { date };
+        let at = local(date.and_hms_opt(0, 0, 0).ok_or_else(invalid)?).ok_or_else(invalid)?;
+        // 结束日期的次日零点不包含在内
+        return Ok(if end { at - chrono::Duration::nanoseconds(1) } else { at });
+    }
+    let ago = duration::parse(text).map_err(|_| invalid())?;
+    chrono::Duration::from_std(ago).ok().and_then(|ago| now.checked_sub_signed(ago)).ok_or_else(invalid)
+}
+
+/// 对日志文件加排他锁（文件关闭时释放）
+#[cfg(unix)]
+fn lock_exclusive(file: &File) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+    loop {
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } == 0 {
+            return Ok(());
+        }
+        let error = io::Error::last_os_error();
+        if error.kind() != io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+}
+
+/// 对日志文件加排他锁（其他平台只依靠进程内的互斥锁）
+#[cfg(not(unix))]
+fn lock_exclusive(_file: &File) -> io::Result<()> {
+    Ok(())
+}
+
+/// CSV字段（含逗号、引号或换行时加引号）
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 进程内串行化追加（不同进程之间由日志文件的排他锁串行化）
+static APPEND_LOCK: Mutex<()> = Mutex::new(());
+
+/// 审计日志
+pub struct AuditLog {
+    /// 日志文件路径
+    path: PathBuf,
+}
+
+impl AuditLog {
+    /// 使用指定文件创建审计日志
+    ///
+    /// # 参数
+    /// * `path` - 日志文件路径
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        AuditLog {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// 打开默认位置的审计日志（配置目录下的audit.log）
+    ///
+    /// # 返回值
+    /// * `Result<AuditLog, AuditError>` - 审计日志实例
+    pub fn open_default() -> Result<Self, AuditError> {
+        let dir = Config::get_config_directory()
+            .map_err(|e| AuditError::LocationError(e.to_string()))?;
+        Ok(Self::new(dir.join("audit.log")))
+    }
+
+    /// 日志文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 追加一条审计记录
+    ///
+    /// 读取上一条链值到写入新记录期间持有排他锁，并发追加不会基于同一条记录分叉哈希链
+    ///
+    /// # 参数
+    /// * `event` - 事件类型
+    /// * `detail` - 事件详情
+    ///
+    /// # 返回值
+    /// * `Result<(), AuditError>` - 操作结果
+    pub fn append(&self, event: &str, detail: &str) -> Result<AuditEntry, AuditError> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(AuditError::IoError)?;
+        }
+
+        let _guard = APPEND_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(AuditError::IoError)?;
+        lock_exclusive(&file).map_err(AuditError::IoError)?;
+
+        let mut entry = AuditEntry {
+            timestamp: Utc::now(),
+            event: event.to_string(),
+            detail: detail.to_string(),
+            hash: None,
+        };
+        entry.hash = Some(entry.chain_hash(&self.last_hash()?));
+        let line = serde_json::to_string(&entry).map_err(AuditError::FormatError)?;
+        writeln!(file, "{}", line).map_err(AuditError::IoError)?;
+
+        Ok(entry)
+    }
+
+    /// 最后一条记录的链值（日志为空或最后一条没有链值时为 `GENESIS`）
+    fn last_hash(&self) -> Result<String, AuditError> {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(GENESIS.to_string()),
+            Err(e) => return Err(AuditError::IoError(e)),
+        };
+        let length = file.metadata().map_err(AuditError::IoError)?.len();
+        file.seek(SeekFrom::Start(length.saturating_sub(TAIL_BYTES))).map_err(AuditError::IoError)?;
+        let mut tail = Vec::new();
+        file.read_to_end(&mut tail).map_err(AuditError::IoError)?;
+
+        let tail = String::from_utf8_lossy(&tail);
+        let Some(line) = tail.lines().rev().find(|line| !line.trim().is_empty()) else {
+            return Ok(GENESIS.to_string());
+        };
+        let entry: AuditEntry = serde_json::from_str(line).map_err(AuditError::FormatError)?;
+        Ok(entry.hash.unwrap_or_else(|| GENESIS.to_string()))
+    }
+
+    /// 按时间范围导出记录，并校验整个日志的哈希链
+    ///
+    /// # 参数
+    /// * `from` - 起始时间（含）
+    /// * `to` - 结束时间（含）
+    /// * `format` - 导出格式
+    /// * `writer` - 输出
+    pub fn export<W: Write>(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        format: ExportFormat,
+        mut writer: W,
+    ) -> Result<ExportSummary, AuditError> {
+        if from > to {
+            return Err(AuditError::InvalidRange(format!("起始时间 {} 晚于结束时间 {}", from.to_rfc3339(), to.to_rfc3339())));
+        }
+
+        let mut summary = ExportSummary { exported: 0, anchor: GENESIS.to_string(), head: GENESIS.to_string(), broken_at: None };
+        if format == ExportFormat::Csv {
+            writeln!(writer, "timestamp,event,detail,prev_hash,hash").map_err(AuditError::IoError)?;
+        }
+
+        let mut prev = GENESIS.to_string();
+        let mut chained = false;
+        for (index, entry) in self.read_all()?.iter().enumerate() {
+            let intact = match &entry.hash {
+                Some(hash) => *hash == entry.chain_hash(&prev),
+                // 旧版本的无链记录只能出现在链开始之前
+                None => !chained,
+            };
+            if !intact && summary.broken_at.is_none() {
+                summary.broken_at = Some(index + 1);
+            }
+
+            if entry.timestamp >= from && entry.timestamp <= to {
+                if summary.exported == 0 {
+                    summary.anchor = prev.clone();
+                }
+                let record = ExportRecord {
+                    timestamp: &entry.timestamp,
+                    event: &entry.event,
+                    detail: &entry.detail,
+                    prev_hash: &prev,
+                    hash: entry.hash.as_deref(),
+                };
+                match format {
+                    ExportFormat::Jsonl => {
+                        let line = serde_json::to_string(&record).map_err(AuditError::FormatError)?;
+                        writeln!(writer, "{}", line).map_err(AuditError::IoError)?;
+                    }
+                    ExportFormat::Csv => writeln!(
+                        writer,
+                        "{},{},{},{},{}",
+                        csv_field(&entry.timestamp.to_rfc3339()),
+                        csv_field(&entry.event),
+                        csv_field(&entry.detail),
+                        prev,
+                        record.hash.unwrap_or_default()
+                    )
+                    .map_err(AuditError::IoError)?,
+                }
+                summary.exported += 1;
+                summary.head = entry.hash.clone().unwrap_or_else(|| GENESIS.to_string());
+            }
+
+            chained |= entry.hash.is_some();
+            prev = entry.hash.clone().unwrap_or_else(|| GENESIS.to_string());
+        }
+
+        writer.flush().map_err(AuditError::IoError)?;
+        Ok(summary)
+    }
+
+    /// 读取全部审计记录
+    ///
+    /// # 返回值
+    /// * `Result<Vec<AuditEntry>, AuditError>` - 按时间顺序排列的记录
+    pub fn read_all(&self) -> Result<Vec<AuditEntry>, AuditError> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&self.path).map_err(AuditError::IoError)?;
+        content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(AuditError::FormatError))
+            .collect()
+    }
+
+    /// 读取审计日志原文（日志不存在时为空）
+    ///
+    /// # 返回值
+    /// * `Result<String, AuditError>` - 日志原文
+    pub fn read_text(&self) -> Result<String, AuditError> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => Ok(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+            Err(e) => Err(AuditError::IoError(e)),
+        }
+    }
+}
+
+/// 事件总线容量（订阅者落后超过该数量时丢弃最旧的记录）
+const BUS_CAPACITY: usize = 256;
+
+/// 审计事件总线（第一次订阅时创建）
+static BUS: OnceLock<broadcast::Sender<AuditEntry>> = OnceLock::new();
+
+/// 事件严重级别（从低到高）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventSeverity {
+    /// 日常记录（观察、延长、粘贴等）
+    #[default]
+    Info,
+    /// 值得留意的状态变化
+    Notice,
+    /// 策略拦截或运行异常
+    Warning,
+    /// 销毁、崩溃等需要立即处理的事件
+    Critical,
+}
+
+impl EventSeverity {
+    /// 事件类型对应的严重级别（未列出的类型为 `Info`）
+    ///
+    /// # 参数
+    /// * `event` - 事件类型
+    pub fn of(event: &str) -> Self {
+        match event {
+            "nuke" | "scheduled_nuke" | "canary_tripped" | "panic" | "health_exit" => EventSeverity::Critical,
+            "paste_blocked" | "ipc_denied" | "agent_denied" | "nuke_denied" | "suspicious_reader" | "health_check_failed"
+            | "monitor_degraded" | "lifetime_capped" | "safe_mode" | "clipboard_taken" | "elevation_mismatch" => EventSeverity::Warning,
+            "destroyed" | "unprotect" | "handoff" | "gui_handoff" | "primary_paste" | "rule_promoted" | "rule_pack_imported"
+            | "ipc_token" | "monitor_restart" | "health_recovered" | "escrow_recover" | "nuke_authorized" | "shutdown"
+            | "backup_created" | "backup_restored" => {
+                EventSeverity::Notice
+            }
+            _ => EventSeverity::Info,
+        }
+    }
+}
+
+impl std::fmt::Display for EventSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EventSeverity::Info => write!(f, "info"),
+            EventSeverity::Notice => write!(f, "notice"),
+            EventSeverity::Warning => write!(f, "warning"),
+            EventSeverity::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+/// 订阅过滤条件（列表为空表示不限制）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventFilter {
+    /// 只接收这些事件类型
+    pub events: Vec<String>,
+    /// 只接收详情中带这些规则标签（`规则=`或`类别=`）的事件，可写 `规则` 或 `规则/检测器`
+    pub rules: Vec<String>,
+    /// 最低严重级别
+    pub min_severity: EventSeverity,
+}
+
+impl EventFilter {
+    /// 只接收指定事件类型的过滤条件
+    ///
+    /// # 参数
+    /// * `events` - 事件类型
+    pub fn events(events: &[&str]) -> Self {
+        EventFilter { events: events.iter().map(|event| event.to_string()).collect(), ..Default::default() }
+    }
+
+    /// 记录是否满足全部条件
+    ///
+    /// # 参数
+    /// * `entry` - 审计记录
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        if !self.events.is_empty() && !self.events.contains(&entry.event) {
+            return false;
+        }
+        if EventSeverity::of(&entry.event) < self.min_severity {
+            return false;
+        }
+        if self.rules.is_empty() {
+            return true;
+        }
+        let Some(label) = rule_label(&entry.detail) else {
+            return false;
+        };
+        let rule = label.split('/').next().unwrap_or(label);
+        self.rules.iter().any(|wanted| wanted == label || wanted == rule)
+    }
+}
+
+/// 从详情中取出规则标签（`规则=` 或 `类别=` 之后到空白为止）
+fn rule_label(detail: &str) -> Option<&str> {
+    detail
+        .split_whitespace()
+        .find_map(|field| field.strip_prefix("规则=").or_else(|| field.strip_prefix("类别=")))
+        .filter(|label| !label.is_empty())
+}
+
+/// 带过滤条件的事件订阅
+pub struct Subscription {
+    receiver: broadcast::Receiver<AuditEntry>,
+    filter: EventFilter,
+}
+
+impl Subscription {
+    /// 等待下一条满足过滤条件的记录（不满足的直接丢弃）
+    ///
+    /// # 返回值
+    /// * `Result<AuditEntry, RecvError>` - 记录；订阅者落后时为 `Lagged`，总线关闭时为 `Closed`
+    pub async fn recv(&mut self) -> Result<AuditEntry, RecvError> {
+        loop {
+            let entry = self.receiver.recv().await?;
+            if self.filter.matches(&entry) {
+                return Ok(entry);
+            }
+        }
+    }
+}
+
+/// 订阅之后写入、满足过滤条件的审计记录
+///
+/// # 参数
+/// * `filter` - 过滤条件（`EventFilter::default()` 接收全部）
+pub fn subscribe(filter: EventFilter) -> Subscription {
+    let receiver = BUS.get_or_init(|| broadcast::channel(BUS_CAPACITY).0).subscribe();
+    Subscription { receiver, filter }
+}
+
+/// 向默认审计日志追加记录，失败时仅记录警告
+///
+/// 写入失败的记录同样发布到事件总线
+///
+/// # 参数
+/// * `event` - 事件类型
+/// * `detail` - 事件详情
+pub fn record(event: &str, detail: &str) {
+    let entry = match AuditLog::open_default().and_then(|log| log.append(event, detail)) {
+        Ok(entry) => entry,
+        Err(e) => {
+            log::warn!("写入审计日志失败: {}", e);
+            AuditEntry { timestamp: Utc::now(), event: event.to_string(), detail: detail.to_string(), hash: None }
+        }
+    };
+    if let Some(bus) = BUS.get() {
+        // 没有订阅者时发送失败，忽略即可
+        let _ = bus.send(entry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_append_and_read() {
+        let temp_dir = tempdir().unwrap();
+        let log = AuditLog::new(temp_dir.path().join("audit.log"));
+
+        log.append("nuke", "手动紧急销毁").unwrap();
+        log.append("panic", "崩溃清理完成").unwrap();
+
+        let entries = log.read_all().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].event, "nuke");
+        assert_eq!(entries[1].detail, "崩溃清理完成");
+    }
+
+    #[test]
+    fn test_export_range_and_chain() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("audit.log");
+        // 旧版本写入的无链记录
+        fs::write(&path, "{\"timestamp\":\"2024-01-01T12:00:00Z\",\"event\":\"nuke\",\"detail\":\"旧记录\"}\n").unwrap();
+        let log = AuditLog::new(&path);
+        let first = log.append("destroyed", "条目 A 原因=\"超时\",手动").unwrap();
+        let second = log.append("nuke", "完整销毁").unwrap();
+        assert_eq!(first.hash.as_deref(), Some(first.chain_hash(GENESIS).as_str()));
+        assert_eq!(second.hash.as_deref(), Some(second.chain_hash(first.hash.as_deref().unwrap()).as_str()));
+
+        let now = Utc::now();
+        let mut output = Vec::new();
+        let summary = log.export(parse_bound("1h", now, false).unwrap(), now, ExportFormat::Jsonl, &mut output).unwrap();
+        assert_eq!(summary.exported, 2);
+        assert_eq!(summary.anchor, GENESIS);
+        assert_eq!(summary.head, second.hash.clone().unwrap());
+        assert_eq!(summary.broken_at, None);
+        let lines: Vec<serde_json::Value> =
+            String::from_utf8(output).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines[1]["prev_hash"], first.hash.clone().unwrap());
+
+        let mut output = Vec::new();
+        let to = parse_bound("2024-01-01", now, true).unwrap();
+        let summary = log.export(parse_bound("2024-01-01", now, false).unwrap(), to, ExportFormat::Csv, &mut output).unwrap();
+        assert_eq!(summary.exported, 1);
+        let csv = String::from_utf8(output).unwrap();
+        assert!(csv.starts_with("timestamp,event,detail,prev_hash,hash\n"));
+        assert!(csv.contains(",旧记录,"));
+
+        // 篡改最后一条记录后，链从该处断开
+        let content = fs::read_to_string(&path).unwrap().replace("完整销毁", "部分销毁");
+        fs::write(&path, content).unwrap();
+        let summary = log.export(now - chrono::Duration::hours(1), now, ExportFormat::Jsonl, io::sink()).unwrap();
+        assert_eq!(summary.broken_at, Some(3));
+        assert!(log.export(now, now - chrono::Duration::hours(1), ExportFormat::Csv, io::sink()).is_err());
+    }
+
+    #[test]
+    fn test_concurrent_appends_keep_chain() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("audit.log");
+        let threads: Vec<_> = (0..4)
+            .map(|thread| {
+                let log = AuditLog::new(&path);
+                std::thread::spawn(move || {
+                    for index in 0..10 {
+                        log.append("observed", &format!("线程 {} 第 {} 条", thread, index)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let log = AuditLog::new(&path);
+        let now = Utc::now();
+        let summary = log.export(now - chrono::Duration::hours(1), now, ExportFormat::Jsonl, io::sink()).unwrap();
+        assert_eq!(summary.exported, 40);
+        assert_eq!(summary.broken_at, None);
+    }
+
+    #[test]
+    fn test_read_missing_log() {
+        let temp_dir = tempdir().unwrap();
+        let log = AuditLog::new(temp_dir.path().join("missing.log"));
+        assert!(log.read_all().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_event_filter() {
+        let entry = |event: &str, detail: &str| AuditEntry { timestamp: Utc::now(), event: event.to_string(), detail: detail.to_string(), hash: None };
+        let observed = entry("observed", "规则=api_key/aws 长度=40");
+        let capped = entry("lifetime_capped", "条目 7 类别=card/luhn 上限=30秒");
+
+        assert!(EventFilter::default().matches(&observed));
+        assert!(EventFilter::events(&["observed"]).matches(&observed));
+        assert!(!EventFilter::events(&["destroyed"]).matches(&observed));
+
+        let warnings = EventFilter { min_severity: EventSeverity::Warning, ..Default::default() };
+        assert!(!warnings.matches(&observed));
+        assert!(warnings.matches(&capped));
+        assert!(warnings.matches(&entry("nuke", "手动紧急销毁")));
+
+        let rules = EventFilter { rules: vec!["api_key".to_string(), "card/cvv".to_string()], ..Default::default() };
+        assert!(rules.matches(&observed));
+        assert!(!rules.matches(&capped));
+        assert!(!rules.matches(&entry("nuke", "手动紧急销毁")));
+    }
+}
@@ -0,0 +1,410 @@
+/*!
+ * ClipVanish™ 备份与恢复模块
+ *
+ * 将配置、规则、统计数据与（可选的）加密笔记打包成一个以口令加密的文件，迁移到新设备时原样恢复
+ * 特点：
+ * - 归档为tar（manifest.json + 各数据文件），整体以口令派生的密钥经AES-256-GCM-SIV加密
+ * - 口令经PBKDF2-HMAC-SHA256（随机盐，600000次迭代）派生密钥，参数记录在文件头中
+ * - manifest记录每个文件的SHA-256，恢复前全部校验，任何一项不符都不写入
+ * - Windows上以DPAPI保护的状态文件先解开再打包，恢复时用新设备的DPAPI重新保护
+ * - 不包含控制接口令牌、解锁失败计数、崩溃计数等与设备绑定的状态
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use aes_gcm_siv::aead::OsRng;
+use chrono::{DateTime, Utc};
+use pbkdf2::pbkdf2_hmac;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+use crate::config::Config;
+use crate::crypto::{CryptoEngine, EncryptedData, SecureKey};
+use crate::encoding::{decode_hex, encode_hex};
+use crate::platform;
+use crate::report::{self, TarWriter};
+
+/// 备份文件标识
+const BACKUP_FORMAT: &str = "clipvanish-backup";
+
+/// 当前备份文件修订号
+const BACKUP_REVISION: u32 = 1;
+
+/// 密钥派生算法
+const KDF: &str = "pbkdf2-sha256";
+
+/// 新备份使用的PBKDF2迭代次数（测试中降低，避免未优化构建过慢）
+const KDF_ITERATIONS: u32 = if cfg!(test) { 10 } else { 600_000 };
+
+/// 恢复时接受的迭代次数范围（备份文件不可信：过低时口令易被暴力破解，过高时恢复会长时间占满CPU）
+const KDF_ITERATIONS_RANGE: std::ops::RangeInclusive<u32> = (if cfg!(test) { 10 } else { 100_000 })..=10_000_000;
+
+/// 口令最短长度（字符）
+pub const MIN_PASSPHRASE_LENGTH: usize = 8;
+
+/// 归档内的清单文件名
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// 数据类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Component {
+    /// 配置与诱饵令牌
+    Config,
+    /// 学习到的偏好与规则试运行记录（规则本身在配置中）
+    Rules,
+    /// 使用统计的数据来源（审计日志）
+    Stats,
+    /// 加密笔记（仍需各自的读取凭据才能解密）
+    Notes,
+}
+
+impl fmt::Display for Component {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Component::Config => "配置",
+            Component::Rules => "规则",
+            Component::Stats => "统计",
+            Component::Notes => "加密笔记",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// 归档中的文件（文件名, 内容）
+type ArchiveFile = (String, Zeroizing<Vec<u8>>);
+
+/// 文件在磁盘上的保存方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Storage {
+    /// 明文文件
+    Plain,
+    /// 经 `platform::write_state` 保护的状态文件
+    State,
+}
+
+/// 可备份的文件（配置目录下的文件名, 类别, 保存方式）
+const FILES: &[(&str, Component, Storage)] = &[
+    ("config.json", Component::Config, Storage::Plain),
+    ("canaries.json", Component::Config, Storage::State),
+    ("learned_preferences.json", Component::Rules, Storage::State),
+    ("rule_trials.json", Component::Rules, Storage::State),
+    ("audit.log", Component::Stats, Storage::Plain),
+    ("notes.json", Component::Notes, Storage::State),
+];
+
+/// 备份错误类型
+#[derive(Debug)]
+pub enum BackupError {
+    /// 口令不正确（或文件被修改）
+    WrongPassphrase,
+    /// 口令过短
+    WeakPassphrase,
+    /// 不是备份文件或修订号不受支持
+    FormatError(String),
+    /// 归档内容与清单不符
+    Corrupt(String),
+    /// 归档中的配置无效
+    ConfigError(String),
+    /// 文件读写失败
+    IoError(io::Error),
+}
+
+impl fmt::Display for BackupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackupError::WrongPassphrase => write!(f, "口令不正确，或备份文件已被修改"),
+            BackupError::WeakPassphrase => write!(f, "口令至少需要 {} 个字符", MIN_PASSPHRASE_LENGTH),
+            BackupError::FormatError(msg) => write!(f, "备份文件格式错误: {}", msg),
+            BackupError::Corrupt(msg) => write!(f, "备份内容损坏: {}", msg),
+            BackupError::ConfigError(msg) => write!(f, "备份中的配置无效: {}", msg),
+            BackupError::IoError(e) => write!(f, "备份文件访问失败: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for BackupError {}
+
+/// 清单中的单个文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupEntry {
+    /// 文件名
+    pub name: String,
+    /// 类别
+    pub component: Component,
+    /// 字节数（明文）
+    pub length: usize,
+    /// 内容的SHA-256（十六进制）
+    pub sha256: String,
+}
+
+/// 备份清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    /// 备份时间（UTC）
+    pub created_at: DateTime<Utc>,
+    /// 备份程序版本
+    pub build: String,
+    /// 包含的文件
+    pub files: Vec<BackupEntry>,
+}
+
+/// 备份文件（JSON，只有密钥派生参数是明文）
+#[derive(Debug, Serialize, Deserialize)]
+struct Envelope {
+    /// 固定为 `clipvanish-backup`
+    format: String,
+    /// 修订号
+    revision: u32,
+    /// 密钥派生算法
+    kdf: String,
+    /// PBKDF2迭代次数
+    iterations: u32,
+    /// 盐（十六进制）
+    salt: String,
+    /// 加密的tar归档（Base64，含nonce）
+    ciphertext: String,
+}
+
+/// 备份配置目录中的数据并写入加密文件
+///
+/// # 参数
+/// * `dir` - 配置目录
+/// * `output` - 备份文件路径
+/// * `include_notes` - 是否包含加密笔记
+/// * `passphrase` - 口令
+///
+/// # 返回值
+/// * `Result<Manifest, BackupError>` - 写入的清单
+pub fn create(dir: &Path, output: &Path, include_notes: bool, passphrase: &str) -> Result<Manifest, BackupError> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LENGTH {
+        return Err(BackupError::WeakPassphrase);
+    }
+
+    let mut manifest = Manifest { created_at: Utc::now(), build: env!("CARGO_PKG_VERSION").to_string(), files: Vec::new() };
+    let mut contents = Vec::new();
+    for &(name, component, storage) in FILES {
+        if component == Component::Notes && !include_notes {
+            continue;
+        }
+        let path = dir.join(name);
+        let content = match storage {
+            Storage::Plain => fs::read(&path),
+            Storage::State => platform::read_state_to_string(&path).map(String::into_bytes),
+        };
+        let content = match content {
+            Ok(content) => Zeroizing::new(content),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(BackupError::IoError(e)),
+        };
+        manifest.files.push(BackupEntry {
+            name: name.to_string(),
+            component,
+            length: content.len(),
+            sha256: checksum(&content),
+        });
+        contents.push((name, content));
+    }
+
+    let mut tar = TarWriter::new();
+    let manifest_json = serde_json::to_vec_pretty(&manifest).map_err(|e| BackupError::FormatError(e.to_string()))?;
+    tar.append_file(MANIFEST_FILE, &manifest_json);
+    for (name, content) in &contents {
+        tar.append_file(name, content);
+    }
+    let archive = Zeroizing::new(tar.finish());
+
+    let envelope = seal(&archive, passphrase, KDF_ITERATIONS)?;
+    platform::write_private(output, &envelope).map_err(BackupError::IoError)?;
+    Ok(manifest)
+}
+
+/// 从加密文件恢复数据到配置目录
+///
+/// 全部文件校验通过后才开始写入；被替换的文件改名为 `<文件名>.pre-restore` 保留，
+/// 配置文件沿用配置备份机制（可用 `config restore` 撤销）
+///
+/// # 参数
+/// * `dir` - 配置目录
+/// * `input` - 备份文件路径
+/// * `passphrase` - 口令
+///
+/// # 返回值
+/// * `Result<Manifest, BackupError>` - 已恢复的清单
+pub fn restore(dir: &Path, input: &Path, passphrase: &str) -> Result<Manifest, BackupError> {
+    let data = fs::read(input).map_err(BackupError::IoError)?;
+    let (manifest, files) = open(&data, passphrase)?;
+
+    // 先校验配置，避免写入一半后才发现无效
+    let config = match files.iter().find(|(name, _)| name == "config.json") {
+        Some((_, content)) => {
+            let config: Config = serde_json::from_slice(content).map_err(|e| BackupError::ConfigError(e.to_string()))?;
+            config.validate().map_err(|e| BackupError::ConfigError(e.to_string()))?;
+            Some(config)
+        }
+        None => None,
+    };
+
+    fs::create_dir_all(dir).map_err(BackupError::IoError)?;
+    for (name, content) in &files {
+        let path = dir.join(name);
+        if let Some(config) = config.as_ref().filter(|_| name == "config.json") {
+            config.save_to_file(&path).map_err(|e| BackupError::ConfigError(e.to_string()))?;
+            continue;
+        }
+
+        if path.exists() {
+            fs::rename(&path, dir.join(format!("{}.pre-restore", name))).map_err(BackupError::IoError)?;
+        }
+        match storage_of(name) {
+            Some(Storage::State) => platform::write_state(&path, content),
+            _ => platform::write_private(&path, content),
+        }
+        .map_err(BackupError::IoError)?;
+    }
+    Ok(manifest)
+}
+
+/// 解密备份文件并校验内容
+///
+/// # 参数
+/// * `data` - 备份文件内容
+/// * `passphrase` - 口令
+///
+/// # 返回值
+/// * `Result<(Manifest, Vec<ArchiveFile>), BackupError>` - 清单与按清单顺序排列的文件
+pub fn open(data: &[u8], passphrase: &str) -> Result<(Manifest, Vec<ArchiveFile>), BackupError> {
+    let envelope: Envelope = serde_json::from_slice(data).map_err(|e| BackupError::FormatError(e.to_string()))?;
+    if envelope.format != BACKUP_FORMAT {
+        return Err(BackupError::FormatError("不是ClipVanish备份文件".to_string()));
+    }
+    if envelope.revision > BACKUP_REVISION || envelope.kdf != KDF {
+        return Err(BackupError::FormatError(format!(
+            "不支持的修订 {}（{}），请升级ClipVanish后再恢复",
+            envelope.revision, envelope.kdf
+        )));
+    }
+    if !KDF_ITERATIONS_RANGE.contains(&envelope.iterations) {
+        return Err(BackupError::FormatError(format!(
+            "迭代次数 {} 超出允许范围（{}到{}）",
+            envelope.iterations,
+            KDF_ITERATIONS_RANGE.start(),
+            KDF_ITERATIONS_RANGE.end()
+        )));
+    }
+    let salt = decode_hex(&envelope.salt).ok_or_else(|| BackupError::FormatError("盐不是有效的十六进制".to_string()))?;
+
+    let engine = engine(passphrase, &salt, envelope.iterations)?;
+    let archive = EncryptedData::from_base64(&envelope.ciphertext)
+        .and_then(|encrypted| engine.decrypt(&encrypted))
+        .map(Zeroizing::new)
+        .map_err(|_| BackupError::WrongPassphrase)?;
+    let mut entries = report::read_tar(&archive).ok_or_else(|| BackupError::Corrupt("无法解析归档".to_string()))?;
+
+    let manifest_index = entries
+        .iter()
+        .position(|(name, _)| name == MANIFEST_FILE)
+        .ok_or_else(|| BackupError::Corrupt("缺少manifest.json".to_string()))?;
+    let (_, manifest_json) = entries.remove(manifest_index);
+    let manifest: Manifest = serde_json::from_slice(&manifest_json).map_err(|e| BackupError::Corrupt(e.to_string()))?;
+
+    let mut files = Vec::with_capacity(manifest.files.len());
+    for entry in &manifest.files {
+        // 只接受已知的文件名，归档中的名称不会被当作路径使用
+        if storage_of(&entry.name).is_none() {
+            return Err(BackupError::Corrupt(format!("未知的文件 {}", entry.name)));
+        }
+        let index = entries
+            .iter()
+            .position(|(name, _)| *name == entry.name)
+            .ok_or_else(|| BackupError::Corrupt(format!("缺少 {}", entry.name)))?;
+        let (name, content) = entries.remove(index);
+        let content = Zeroizing::new(content);
+        if checksum(&content) != entry.sha256 {
+            return Err(BackupError::Corrupt(format!("{} 的校验值不符", name)));
+        }
+        files.push((name, content));
+    }
+    Ok((manifest, files))
+}
+
+/// 加密归档并生成备份文件内容
+fn seal(archive: &[u8], passphrase: &str, iterations: u32) -> Result<Vec<u8>, BackupError> {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let encrypted = engine(passphrase, &salt, iterations)?
+        .encrypt(archive)
+        .map_err(|e| BackupError::FormatError(e.to_string()))?;
+
+    let envelope = Envelope {
+        format: BACKUP_FORMAT.to_string(),
+        revision: BACKUP_REVISION,
+        kdf: KDF.to_string(),
+        iterations,
+        salt: encode_hex(&salt),
+        ciphertext: encrypted.to_base64(),
+    };
+    serde_json::to_vec_pretty(&envelope).map_err(|e| BackupError::FormatError(e.to_string()))
+}
+
+/// 由口令派生密钥并创建加密引擎
+fn engine(passphrase: &str, salt: &[u8], iterations: u32) -> Result<CryptoEngine, BackupError> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, iterations, key.as_mut());
+    CryptoEngine::from_key(SecureKey::from_bytes(*key)).map_err(|e| BackupError::FormatError(e.to_string()))
+}
+
+/// 内容的SHA-256（十六进制）
+fn checksum(content: &[u8]) -> String {
+    let mut hasher = Sha256::default();
+    hasher.update(content);
+    encode_hex(&hasher.finalize())
+}
+
+/// 已知文件的保存方式
+fn storage_of(name: &str) -> Option<Storage> {
+    FILES.iter().find(|(file, _, _)| *file == name).map(|&(_, _, storage)| storage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_round_trip() {
+        let source = tempdir().unwrap();
+        Config::default().save_to_file(source.path().join("config.json")).unwrap();
+        fs::write(source.path().join("audit.log"), "{\"timestamp\":\"2024-01-01T12:00:00Z\",\"event\":\"nuke\",\"detail\":\"热键\"}\n").unwrap();
+        platform::write_state(source.path().join("notes.json"), b"{\"notes\":[]}").unwrap();
+
+        let archive = seal(&TarWriter::new().finish(), "correct horse", KDF_ITERATIONS).unwrap();
+        assert!(matches!(open(&archive, "wrong horse"), Err(BackupError::WrongPassphrase)));
+        for iterations in [0, u32::MAX] {
+            let mut envelope: serde_json::Value = serde_json::from_slice(&archive).unwrap();
+            envelope["iterations"] = iterations.into();
+            let tampered = serde_json::to_vec(&envelope).unwrap();
+            assert!(matches!(open(&tampered, "correct horse"), Err(BackupError::FormatError(_))));
+        }
+
+        let file = source.path().join("backup.cvb");
+        assert!(matches!(create(source.path(), &file, false, "short"), Err(BackupError::WeakPassphrase)));
+        let manifest = create(source.path(), &file, false, "correct horse").unwrap();
+        let names: Vec<&str> = manifest.files.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, ["config.json", "audit.log"]);
+        assert!(!fs::read_to_string(&file).unwrap().contains("热键"));
+
+        let target = tempdir().unwrap();
+        fs::write(target.path().join("audit.log"), "本机原有内容\n").unwrap();
+        restore(target.path(), &file, "correct horse").unwrap();
+        assert!(fs::read_to_string(target.path().join("audit.log")).unwrap().contains("热键"));
+        assert_eq!(fs::read_to_string(target.path().join("audit.log.pre-restore")).unwrap(), "本机原有内容\n");
+        assert!(Config::load_from_file(target.path().join("config.json")).is_ok());
+        assert!(!target.path().join("notes.json").exists());
+    }
+}
@@ -0,0 +1,94 @@
+/*!
+ * ClipVanish™ 构建信息模块
+ *
+ * 提供构建脚本生成的版本与构建元数据
+ * 特点：
+ * - git提交、构建日期、目标平台、构建配置
+ * - 已启用的cargo特性列表
+ * - 支持文本与JSON两种输出格式
+ *
+ * 作者: ClipVanish Team
+ */
+
+use serde::Serialize;
+
+/// 构建信息
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildInfo {
+    /// 版本号
+    pub version: &'static str,
+    /// git提交（短哈希）
+    pub git_commit: &'static str,
+    /// 构建日期（UTC）
+    pub build_date: &'static str,
+    /// 目标平台三元组
+    pub target: &'static str,
+    /// 构建配置（debug/release）
+    pub profile: &'static str,
+    /// 已启用的cargo特性
+    pub features: Vec<&'static str>,
+}
+
+impl BuildInfo {
+    /// 获取当前二进制的构建信息
+    pub fn current() -> Self {
+        BuildInfo {
+            version: env!("CARGO_PKG_VERSION"),
+            git_commit: env!("CLIPVANISH_GIT_COMMIT"),
+            build_date: env!("CLIPVANISH_BUILD_DATE"),
+            target: env!("CLIPVANISH_TARGET"),
+            profile: env!("CLIPVANISH_PROFILE"),
+            features: parse_features(env!("CLIPVANISH_FEATURES")),
+        }
+    }
+
+    /// 以JSON格式输出
+    ///
+    /// # 返回值
+    /// * `String` - 格式化后的JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+impl std::fmt::Display for BuildInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "clipvanish {} ({} {}) {}",
+            self.version, self.git_commit, self.build_date, self.target
+        )?;
+        if !self.features.is_empty() {
+            write!(f, " [{}]", self.features.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// 解析逗号分隔的特性列表
+fn parse_features(list: &'static str) -> Vec<&'static str> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_features() {
+        assert!(parse_features("").is_empty());
+        assert_eq!(parse_features("api,tray"), vec!["api", "tray"]);
+    }
+
+    #[test]
+    fn test_json_fields() {
+        let json: serde_json::Value = serde_json::from_str(&BuildInfo::current().to_json()).unwrap();
+        for key in ["version", "git_commit", "build_date", "target", "profile", "features"] {
+            assert!(json.get(key).is_some(), "缺少字段: {}", key);
+        }
+        assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+    }
+}
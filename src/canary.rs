@@ -0,0 +1,329 @@
+/*!
+ * ClipVanish™ 剪贴板诱饵令牌模块
+ *
+ * 剪贴板空闲时放入一个可唯一识别的诱饵令牌（形似云服务访问密钥），用于发现窃取剪贴板的恶意程序
+ * 特点：
+ * - 只在剪贴板为空且已空闲一段时间、没有受保护条目时放置，不会覆盖用户的内容
+ * - 令牌按时间轮换，已轮换的令牌保留在本地记录中；它再次出现在剪贴板中说明有程序保存并写回了它
+ * - `canary check` 在日志、外发请求记录等文本中查找发出过的令牌，命中即说明剪贴板内容被带出
+ * - 替换受保护内容的诱饵文本也记入同一份记录，可用同样的方式追查
+ * - 令牌本身不是任何真实凭据，泄露没有风险；记录文件仅当前用户可读写
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use crate::config::Config;
+use crate::platform;
+
+/// `canary check` 发现令牌时使用的退出码（与 `classify` 命中时相同）
+pub const EXIT_FOUND: i32 = 3;
+
+/// 服务检查剪贴板空闲状态的间隔
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 令牌前缀（与AWS访问密钥ID的格式相同，容易被窃取程序当作凭据收集）
+const TOKEN_PREFIX: &str = "AKIA";
+
+/// 令牌随机部分的字符集与长度
+const TOKEN_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const TOKEN_RANDOM_LEN: usize = 16;
+
+/// 诱饵令牌配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CanaryConfig {
+    /// 是否在空闲时放置诱饵令牌
+    pub enabled: bool,
+    /// 剪贴板为空多久后放置（秒）
+    pub idle_seconds: u64,
+    /// 令牌轮换间隔（秒）
+    pub rotate_seconds: u64,
+    /// 保留的已轮换令牌数
+    pub retain: usize,
+}
+
+impl Default for CanaryConfig {
+    fn default() -> Self {
+        CanaryConfig {
+            enabled: false,
+            idle_seconds: 120,
+            rotate_seconds: 24 * 3600,
+            retain: 100,
+        }
+    }
+}
+
+/// 诱饵令牌错误类型
+#[derive(Debug)]
+pub enum CanaryError {
+    /// 记录文件访问失败
+    IoError(std::io::Error),
+    /// 记录文件格式错误
+    FormatError(serde_json::Error),
+    /// 无法确定记录文件位置
+    UnknownLocation,
+}
+
+impl std::fmt::Display for CanaryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CanaryError::IoError(e) => write!(f, "诱饵令牌记录访问失败: {}", e),
+            CanaryError::FormatError(e) => write!(f, "诱饵令牌记录格式错误: {}", e),
+            CanaryError::UnknownLocation => write!(f, "无法确定诱饵令牌记录位置"),
+        }
+    }
+}
+
+impl std::error::Error for CanaryError {}
+
+/// 发出过的诱饵令牌
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanaryToken {
+    /// 令牌
+    pub token: String,
+    /// 生成时间
+    pub created_at: DateTime<Utc>,
+    /// 轮换时间（仍在使用时为None）
+    #[serde(default)]
+    pub retired_at: Option<DateTime<Utc>>,
+    /// 最后一次放到剪贴板的时间
+    #[serde(default)]
+    pub placed_at: Option<DateTime<Utc>>,
+    /// 是否为替换受保护内容的诱饵文本（而非空闲时放置的令牌）
+    #[serde(default)]
+    pub decoy: bool,
+}
+
+impl CanaryToken {
+    /// 用于日志与审计的简短标识（令牌前8位）
+    pub fn label(&self) -> &str {
+        &self.token[..self.token.len().min(8)]
+    }
+}
+
+/// 生成一个新令牌
+fn generate_token() -> String {
+    let mut rng = rand::rngs::OsRng;
+    let random: String = (0..TOKEN_RANDOM_LEN)
+        .map(|_| TOKEN_ALPHABET[rng.gen_range(0..TOKEN_ALPHABET.len())] as char)
+        .collect();
+    format!("{}{}", TOKEN_PREFIX, random)
+}
+
+/// 诱饵令牌记录
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CanaryStore {
+    /// 发出过的令牌（按生成时间排序，最后一个可能仍在使用）
+    tokens: Vec<CanaryToken>,
+}
+
+impl CanaryStore {
+    /// 默认记录文件位置（配置目录下的canaries.json）
+    pub fn default_path() -> Result<PathBuf, CanaryError> {
+        Config::get_config_directory()
+            .map(|dir| dir.join("canaries.json"))
+            .map_err(|_| CanaryError::UnknownLocation)
+    }
+
+    /// 加载记录（文件不存在时返回空记录）
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, CanaryError> {
+        match platform::read_state_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).map_err(CanaryError::FormatError),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(CanaryStore::default()),
+            Err(e) => Err(CanaryError::IoError(e)),
+        }
+    }
+
+    /// 保存记录（仅当前用户可读写）
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), CanaryError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(CanaryError::IoError)?;
+        }
+
+        let content = serde_json::to_string_pretty(self).map_err(CanaryError::FormatError)?;
+        platform::write_state(path, content.as_bytes()).map_err(CanaryError::IoError)
+    }
+
+    /// 全部令牌
+    pub fn tokens(&self) -> &[CanaryToken] {
+        &self.tokens
+    }
+
+    /// 仍在使用的令牌
+    pub fn active(&self) -> Option<&CanaryToken> {
+        self.tokens.last().filter(|token| token.retired_at.is_none())
+    }
+
+    /// 轮换：停用当前令牌并生成新令牌，超出保留数量的旧令牌被删除
+    ///
+    /// # 参数
+    /// * `retain` - 保留的已轮换令牌数
+    /// * `now` - 当前时间
+    pub fn rotate(&mut self, retain: usize, now: DateTime<Utc>) -> &CanaryToken {
+        if let Some(token) = self.tokens.last_mut().filter(|token| token.retired_at.is_none()) {
+            token.retired_at = Some(now);
+        }
+        self.prune(retain);
+
+        self.tokens.push(CanaryToken { token: generate_token(), created_at: now, retired_at: None, placed_at: None, decoy: false });
+        self.tokens.last().expect("刚加入的令牌")
+    }
+
+    /// 记录一个已放到剪贴板的诱饵文本
+    ///
+    /// # 参数
+    /// * `decoy` - 诱饵文本
+    /// * `retain` - 保留的已停用令牌数
+    /// * `now` - 当前时间
+    pub fn record_decoy(&mut self, decoy: &str, retain: usize, now: DateTime<Utc>) {
+        let token = CanaryToken { token: decoy.to_string(), created_at: now, retired_at: Some(now), placed_at: Some(now), decoy: true };
+        // 仍在使用的令牌保持在最后
+        let at = self.tokens.len() - usize::from(self.active().is_some());
+        self.tokens.insert(at, token);
+        self.prune(retain);
+    }
+
+    /// 删除超出保留数量的最旧的已停用令牌
+    fn prune(&mut self, retain: usize) {
+        let retired = self.tokens.len() - usize::from(self.active().is_some());
+        self.tokens.drain(..retired.saturating_sub(retain));
+    }
+
+    /// 当前令牌（没有或已超过轮换间隔时先轮换）
+    ///
+    /// # 参数
+    /// * `config` - 诱饵令牌配置
+    /// * `now` - 当前时间
+    pub fn current(&mut self, config: &CanaryConfig, now: DateTime<Utc>) -> &CanaryToken {
+        let rotate_after = chrono::Duration::seconds(config.rotate_seconds.min(i64::MAX as u64) as i64);
+        let due = self.active().is_none_or(|token| now - token.created_at >= rotate_after);
+        if due {
+            self.rotate(config.retain, now);
+        }
+        self.tokens.last().expect("轮换后必有令牌")
+    }
+
+    /// 记录当前令牌已放到剪贴板
+    pub fn mark_placed(&mut self, now: DateTime<Utc>) {
+        if let Some(token) = self.tokens.last_mut().filter(|token| token.retired_at.is_none()) {
+            token.placed_at = Some(now);
+        }
+    }
+
+    /// 在文本中查找发出过的令牌
+    ///
+    /// # 参数
+    /// * `text` - 待检查的文本
+    pub fn find(&self, text: &str) -> Vec<&CanaryToken> {
+        self.tokens.iter().filter(|token| text.contains(&token.token)).collect()
+    }
+
+    /// 判断剪贴板中新出现的内容是否与诱饵令牌有关
+    ///
+    /// # 参数
+    /// * `content` - 剪贴板内容
+    pub fn sighting(&self, content: &str) -> Option<Sighting> {
+        if self.active().is_some_and(|token| content.trim() == token.token) {
+            return Some(Sighting::Placed);
+        }
+        self.find(content).first().map(|token| Sighting::Reappeared(token.label().to_string()))
+    }
+}
+
+/// 剪贴板中观察到的内容与诱饵令牌的关系
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sighting {
+    /// 我们放置的当前令牌
+    Placed,
+    /// 已轮换的令牌重新出现，或令牌被夹带在其他内容中（参数为令牌标识）
+    Reappeared(String),
+}
+
+/// 剪贴板空闲跟踪（由服务定时调用）
+#[derive(Debug, Default)]
+pub struct IdleTracker {
+    /// 剪贴板从何时起为空
+    empty_since: Option<Instant>,
+}
+
+impl IdleTracker {
+    /// 记录一次剪贴板状态，返回是否应当放置令牌
+    ///
+    /// # 参数
+    /// * `config` - 诱饵令牌配置
+    /// * `empty` - 剪贴板是否为空且没有受保护条目
+    /// * `now` - 当前时间
+    pub fn observe(&mut self, config: &CanaryConfig, empty: bool, now: Instant) -> bool {
+        if !empty {
+            self.empty_since = None;
+            return false;
+        }
+        let since = *self.empty_since.get_or_insert(now);
+        if now.duration_since(since) >= Duration::from_secs(config.idle_seconds) {
+            self.empty_since = None;
+            return true;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation_and_lookup() {
+        let config = CanaryConfig { retain: 2, rotate_seconds: 60, ..CanaryConfig::default() };
+        let now = Utc::now();
+        let mut store = CanaryStore::default();
+
+        let first = store.current(&config, now).token.clone();
+        assert!(first.starts_with(TOKEN_PREFIX) && first.len() == TOKEN_PREFIX.len() + TOKEN_RANDOM_LEN);
+        assert_eq!(store.current(&config, now + chrono::Duration::seconds(30)).token, first);
+
+        let second = store.current(&config, now + chrono::Duration::seconds(60)).token.clone();
+        assert_ne!(second, first);
+        assert!(store.tokens()[0].retired_at.is_some());
+
+        let text = format!("GET /upload?k={} HTTP/1.1", first);
+        assert_eq!(store.find(&text).len(), 1);
+        assert!(store.find("nothing here").is_empty());
+        assert_eq!(store.sighting(&second), Some(Sighting::Placed));
+        assert_eq!(store.sighting(&first), Some(Sighting::Reappeared(first[..8].to_string())));
+        assert_eq!(store.sighting("AKIA but not ours"), None);
+
+        // 超出保留数量的旧令牌被删除
+        store.rotate(config.retain, now);
+        store.rotate(config.retain, now);
+        assert_eq!(store.tokens().len(), 3);
+        assert!(store.find(&text).is_empty());
+
+        // 诱饵文本排在仍在使用的令牌之前，同样计入保留数量
+        let active = store.active().unwrap().token.clone();
+        store.record_decoy("ghp_decoy", config.retain, now);
+        assert_eq!(store.tokens().len(), 3);
+        assert_eq!(store.active().unwrap().token, active);
+        assert_eq!(store.sighting("token=ghp_decoy"), Some(Sighting::Reappeared("ghp_deco".to_string())));
+    }
+
+    #[test]
+    fn test_idle_tracker() {
+        let config = CanaryConfig { idle_seconds: 10, ..CanaryConfig::default() };
+        let mut tracker = IdleTracker::default();
+        let start = Instant::now();
+
+        assert!(!tracker.observe(&config, true, start));
+        assert!(!tracker.observe(&config, false, start + Duration::from_secs(5)));
+        assert!(!tracker.observe(&config, true, start + Duration::from_secs(6)));
+        assert!(tracker.observe(&config, true, start + Duration::from_secs(16)));
+        // 放置后重新计时
+        assert!(!tracker.observe(&config, true, start + Duration::from_secs(17)));
+    }
+}
@@ -0,0 +1,282 @@
+/*!
+ * ClipVanish™ 功能降级检测模块
+ *
+ * 启动时检测热键、键盘监听、内存锁定、原生清除、控制接口与管理员程序交互是否可用，
+ * 并记录每项功能处于启用、降级还是停用状态以及原因
+ * 特点：
+ * - 启动检测与运行时失败共用同一张状态表，`status` 与 `doctor` 直接展示
+ * - 同一功能的同一降级原因只警告一次，避免反复刷屏
+ * - 检测本身不修改系统状态
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use log::warn;
+use serde::Serialize;
+use crate::config::Config;
+use crate::doctor;
+#[cfg(target_os = "windows")]
+use crate::elevation;
+use crate::helpers;
+use crate::memory::SecureMemory;
+use crate::virtual_clipboard;
+
+/// 受检测的功能
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    /// 全局热键
+    GlobalHotkeys,
+    /// 键盘监听（粘贴时自动解密）
+    KeyboardHooks,
+    /// 内存锁定（mlock/VirtualLock）
+    MemoryLocking,
+    /// 原生剪贴板清除接口
+    NativeClear,
+    /// 本地控制接口
+    ControlApi,
+    /// 内核密钥环（Linux会话密钥保存）
+    KernelKeyring,
+    /// 与管理员权限程序交互（Windows界面特权隔离）
+    ElevatedTargets,
+}
+
+impl Feature {
+    /// 显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            Feature::GlobalHotkeys => "全局热键",
+            Feature::KeyboardHooks => "键盘监听",
+            Feature::MemoryLocking => "内存锁定",
+            Feature::NativeClear => "原生清除",
+            Feature::ControlApi => "控制接口",
+            Feature::KernelKeyring => "内核密钥环",
+            Feature::ElevatedTargets => "管理员程序",
+        }
+    }
+}
+
+/// 功能状态
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CapabilityState {
+    /// 正常工作
+    Active,
+    /// 可用但功能受限
+    Degraded,
+    /// 不可用或已关闭
+    Disabled,
+}
+
+impl CapabilityState {
+    /// 状态对应的显示符号
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            CapabilityState::Active => "✅",
+            CapabilityState::Degraded => "⚠️",
+            CapabilityState::Disabled => "⛔",
+        }
+    }
+
+    /// 状态名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            CapabilityState::Active => "启用",
+            CapabilityState::Degraded => "降级",
+            CapabilityState::Disabled => "停用",
+        }
+    }
+}
+
+/// 单项功能的状态与原因
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CapabilityStatus {
+    /// 功能
+    pub feature: Feature,
+    /// 状态
+    pub state: CapabilityState,
+    /// 原因或补充说明
+    pub reason: Option<String>,
+}
+
+impl CapabilityStatus {
+    fn new(feature: Feature, state: CapabilityState, reason: Option<&str>) -> Self {
+        CapabilityStatus {
+            feature,
+            state,
+            reason: reason.map(str::to_string),
+        }
+    }
+}
+
+impl std::fmt::Display for CapabilityStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}: {}", self.state.symbol(), self.feature.label(), self.state.label())?;
+        if let Some(reason) = &self.reason {
+            write!(f, "（{}）", reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// 当前进程的功能状态表
+static MATRIX: Mutex<BTreeMap<Feature, CapabilityStatus>> = Mutex::new(BTreeMap::new());
+
+/// 记录功能状态
+///
+/// 进入降级或停用状态、且与上一次记录不同时输出一次警告
+///
+/// # 参数
+/// * `feature` - 功能
+/// * `state` - 状态
+/// * `reason` - 原因（启用时可为补充说明）
+pub fn record(feature: Feature, state: CapabilityState, reason: Option<&str>) {
+    let status = CapabilityStatus::new(feature, state, reason);
+    let previous = MATRIX.lock().unwrap().insert(feature, status.clone());
+
+    if state != CapabilityState::Active && previous.as_ref() != Some(&status) {
+        warn!("{}已{}: {}", feature.label(), state.label(), reason.unwrap_or("原因未知"));
+    }
+}
+
+/// 记录功能降级（同一原因只警告一次）
+pub fn degrade(feature: Feature, reason: &str) {
+    record(feature, CapabilityState::Degraded, Some(reason));
+}
+
+/// 记录功能停用（同一原因只警告一次）
+pub fn disable(feature: Feature, reason: &str) {
+    record(feature, CapabilityState::Disabled, Some(reason));
+}
+
+/// 当前功能状态表
+pub fn snapshot() -> Vec<CapabilityStatus> {
+    MATRIX.lock().unwrap().values().cloned().collect()
+}
+
+/// 检测各项功能的状态（不记录、不输出警告）
+///
+/// # 参数
+/// * `config` - 当前配置
+pub fn probe(config: &Config) -> Vec<CapabilityStatus> {
+    use CapabilityState::*;
+
+    let headless = virtual_clipboard::is_headless();
+    let status = CapabilityStatus::new;
+
+    let hotkeys = if !config.hotkeys.enable_global_hotkeys {
+        status(Feature::GlobalHotkeys, Disabled, Some("配置中已关闭"))
+    } else if !cfg!(feature = "hotkeys") {
+        status(Feature::GlobalHotkeys, Disabled, Some("当前构建未包含hotkeys特性"))
+    } else if headless {
+        status(Feature::GlobalHotkeys, Disabled, Some("无头模式"))
+    } else {
+        status(Feature::GlobalHotkeys, Active, None)
+    };
+
+    let keyboard = if !cfg!(feature = "keyboard-hooks") {
+        status(Feature::KeyboardHooks, Disabled, Some("当前构建未包含keyboard-hooks特性，粘贴时不会自动解密"))
+    } else if headless {
+        status(Feature::KeyboardHooks, Disabled, Some("无头模式，使用 get 代替粘贴"))
+    } else {
+        status(Feature::KeyboardHooks, Active, None)
+    };
+
+    let memory = if !config.security.enable_memory_locking {
+        status(Feature::MemoryLocking, Disabled, Some("配置中已关闭"))
+    } else if !SecureMemory::supports_memory_locking() {
+        status(Feature::MemoryLocking, Degraded, Some("系统不支持mlock/VirtualLock，敏感内存可能被换出到磁盘"))
+    } else {
+        status(Feature::MemoryLocking, Active, None)
+    };
+
+    let native_clear = if headless {
+        status(Feature::NativeClear, Active, Some("虚拟剪贴板"))
+    } else if virtual_clipboard::uses_portal(config.clipboard.access) {
+        status(Feature::NativeClear, Active, Some("xdg-desktop-portal"))
+    } else {
+        probe_native_clear()
+    };
+
+    let control_api = if !config.ipc.enabled {
+        status(Feature::ControlApi, Disabled, Some("配置中未启用"))
+    } else if !cfg!(all(unix, feature = "api")) {
+        status(Feature::ControlApi, Disabled, Some("当前构建或平台不支持（需要Unix与api特性）"))
+    } else {
+        status(Feature::ControlApi, Active, None)
+    };
+
+    let statuses = vec![hotkeys, keyboard, memory, native_clear, control_api];
+    // 只有Windows存在界面特权隔离，前台进入管理员程序时由检测线程降级
+    #[cfg(target_os = "windows")]
+    let statuses = {
+        let mut statuses = statuses;
+        statuses.push(if elevation::is_elevated() {
+            status(Feature::ElevatedTargets, Active, Some("已以管理员身份运行"))
+        } else {
+            status(Feature::ElevatedTargets, Active, None)
+        });
+        statuses
+    };
+    statuses
+}
+
+/// 检测并记录各项功能的状态（服务启动时调用）
+///
+/// # 参数
+/// * `config` - 当前配置
+pub fn detect(config: &Config) {
+    for status in probe(config) {
+        record(status.feature, status.state, status.reason.as_deref());
+    }
+}
+
+/// 检测原生剪贴板清除接口
+fn probe_native_clear() -> CapabilityStatus {
+    // 原因与运行时回退时记录的一致，避免同一问题警告两次
+    let missing = if cfg!(target_os = "windows") {
+        None
+    } else if helpers::is_strict() {
+        Some("严格模式禁止辅助程序，回退为写入空内容")
+    } else if cfg!(target_os = "macos") {
+        doctor::find_in_path("osascript").is_none().then_some("osascript不可用，回退为写入空内容")
+    } else {
+        (doctor::find_in_path("xclip").is_none() && doctor::find_in_path("xsel").is_none())
+            .then_some("xclip和xsel都不可用，回退为写入空内容")
+    };
+
+    match missing {
+        Some(reason) => CapabilityStatus::new(Feature::NativeClear, CapabilityState::Degraded, Some(reason)),
+        None => CapabilityStatus::new(Feature::NativeClear, CapabilityState::Active, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_reports_config_disabled_features() {
+        let mut config = Config::default();
+        config.hotkeys.enable_global_hotkeys = false;
+        config.security.enable_memory_locking = false;
+
+        let statuses = probe(&config);
+        assert_eq!(statuses.len(), if cfg!(target_os = "windows") { 6 } else { 5 });
+        let hotkeys = statuses.iter().find(|s| s.feature == Feature::GlobalHotkeys).unwrap();
+        assert_eq!(hotkeys.state, CapabilityState::Disabled);
+        assert_eq!(hotkeys.to_string(), "⛔ 全局热键: 停用（配置中已关闭）");
+    }
+
+    #[test]
+    fn test_record_keeps_latest_state() {
+        // 使用测试中不会被其他路径修改的功能，避免与并行测试互相影响
+        disable(Feature::ControlApi, "测试原因");
+        disable(Feature::ControlApi, "测试原因");
+        let status = snapshot().into_iter().find(|s| s.feature == Feature::ControlApi).unwrap();
+        assert_eq!(status.state, CapabilityState::Disabled);
+        assert_eq!(status.reason.as_deref(), Some("测试原因"));
+    }
+}
@@ -12,18 +12,338 @@
  */
 
 use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 use tokio::signal;
 use tokio::time::sleep;
+use tokio::sync::Notify;
 use log::{info, warn, error, debug};
+use clipboard::ClipboardProvider;
+use zeroize::Zeroizing;
+#[cfg(feature = "hotkeys")]
 use global_hotkey::{GlobalHotKeyManager, HotKeyState, GlobalHotKeyEvent};
-use global_hotkey::hotkey::{HotKey, Modifiers, Code};
+#[cfg(feature = "hotkeys")]
+use global_hotkey::hotkey::HotKey;
+#[cfg(feature = "hotkeys")]
+use std::collections::HashMap;
 
-use crate::config::Config;
-use crate::clipboard::{ClipboardMonitor, ClipboardEvent, ClearReason, ClipboardOperation};
+use crate::config::{Config, CountdownAnchor, ClipboardHistoryPolicy, HandoffPolicy};
+#[cfg(any(feature = "hotkeys", all(target_os = "macos", feature = "menubar")))]
+use crate::config::HotkeyAction;
+use crate::prompt::ConfirmPrompt;
+use crate::clipboard_actor::Priority;
+use crate::clipboard::{ClipboardMonitor, ClipboardEvent, ClearReason, ClipboardOperation, ExtendOutcome, NukeOutcome, NukeScope};
 use crate::timer::{DestructTimer, TimerEvent, TimerState};
-use crate::memory::SecureMemory;
+use crate::erase_scheduler;
+use crate::supervisor::{self, MonitorHealth, RestartPolicy};
+#[cfg(feature = "keyboard-hooks")]
 use crate::keyboard::{KeyboardMonitor, KeyboardEvent};
+use crate::build_info::BuildInfo;
+use crate::doctor;
+use crate::shutdown::{self, Phase, Trigger};
+use crate::health;
+use crate::safe_mode::{self, SafeMode, Subsystem};
+use crate::audit;
+use crate::backup;
+use crate::formats;
+use crate::session::{self, SessionStats};
+use crate::encoding::encode_hex;
+use crate::forensics::{self, ForensicsKey};
+use crate::soak::{self, SoakOptions};
+use crate::redteam::{self, RedteamOptions};
+use crate::stats::{self, UsageReport};
+use crate::advisor;
+use crate::watch::{EventCounter, WatchLine};
+use crate::schedule::{self, NukeScheduler};
+use crate::duration;
+use crate::escrow::{self, EscrowPrivateKey};
+use crate::audit::{EventFilter, ExportFormat};
+use crate::canary::{self, CanaryStore, IdleTracker};
+#[cfg(target_os = "windows")]
+use crate::reader_watch;
+use crate::nuke_guard::{self, NukeGuardConfig};
+use crate::crypto::{CryptoEngine, EncryptedData};
+use crate::win_history;
+use crate::capabilities::{self, CapabilityStatus};
+#[cfg(target_os = "windows")]
+use crate::elevation;
+#[cfg(feature = "api")]
+use crate::ipc_security;
+use crate::virtual_clipboard::{self, ClipboardBackend};
+use crate::helpers;
+use crate::confinement;
+use crate::learning::{Bias, PreferenceStore};
+use crate::classifier;
+use crate::rule_trial::{self, RuleStage, TrialStore};
+use crate::rule_pack::{self, RulePack};
+#[cfg(all(target_os = "macos", feature = "menubar"))]
+use crate::menubar::{MenuBarService, MenuCommand};
+use crate::item_id::ItemId;
+use crate::countdown_display::{self, TerminalTitle};
+use crate::overlay::Overlay;
+#[cfg(feature = "keyboard-hooks")]
+use crate::paste_confirm::PasteDecision;
+#[cfg(all(any(feature = "hotkeys", all(target_os = "macos", feature = "menubar")), feature = "keyboard-hooks"))]
+use crate::paste_strategy::{PasteError, PasteStrategy};
+use crate::notifications::{self, Severity};
+#[cfg(all(target_os = "linux", feature = "keyboard-hooks"))]
+use crate::paste_gestures::{self, PasteGesture};
+#[cfg(feature = "keyboard-hooks")]
+use crate::source_app;
+use crate::notes::{NoteHandle, NoteStore};
+#[cfg(feature = "api")]
+use crate::ipc_auth::{Capability, TokenStore};
+#[cfg(all(unix, feature = "api"))]
+use crate::ipc::ControlServer;
+#[cfg(all(unix, feature = "api"))]
+use crate::editor;
+#[cfg(all(unix, feature = "api"))]
+use crate::agent::AgentServer;
+use crate::webhook::WebhookDispatcher;
+use crate::mqtt::MqttPublisher;
+use crate::status_page::StatusPage;
+use crate::handoff;
+#[cfg(target_os = "windows")]
+use crate::delayed_render::{DelayedRenderer, RenderProvider};
+#[cfg(target_os = "windows")]
+use crate::paste_context::PasteContext;
+use crate::report::{self, ReportSection};
+use crate::output::{self, notice, notice_inline, OutputFormat};
+
+/// 控制接口令牌操作
+#[cfg(feature = "api")]
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum TokenAction {
+    /// 创建令牌
+    Create {
+        /// 令牌名称（记录在审计日志中）
+        name: String,
+        /// 令牌能力：status（只读状态）或 control（控制）
+        #[arg(short, long, default_value = "status")]
+        capability: Capability,
+    },
+    /// 列出全部令牌
+    List,
+    /// 吊销令牌
+    Revoke {
+        /// 令牌名称
+        name: String,
+    },
+    /// 从stdin读取破坏性命令的口令，输出写入 api.security.passphrase_hash 的哈希
+    Passphrase,
+    /// 生成请求签名密钥对（私钥写入文件，公钥输出到stdout）
+    Keygen {
+        /// 私钥文件路径
+        #[arg(short, long, default_value = "clipvanish-ipc.key")]
+        output: String,
+    },
+    /// 为控制命令签名，输出需合并到请求中的字段（JSON）
+    Sign {
+        /// 命令名称（如 nuke、pause、schedule_nuke）
+        command: String,
+        /// 私钥文件
+        #[arg(short = 'k', long)]
+        key: String,
+        /// schedule_nuke 的 at 或 in 参数（需与请求中的一致）
+        #[arg(long, default_value = "")]
+        argument: String,
+    },
+}
+
+/// 阅后即焚笔记操作
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum NoteAction {
+    /// 创建笔记，读取凭据输出到stdout（内容未指定时从stdin读取）
+    Create {
+        /// 笔记内容
+        text: Option<String>,
+        /// 存活时间（如 1h、30m、1d），过期未读取的笔记自动销毁
+        #[arg(long, default_value = "1h", value_parser = duration::parse)]
+        ttl: Duration,
+    },
+    /// 读取笔记：内容放入受保护剪贴板，笔记随即销毁
+    Read {
+        /// 创建时输出的读取凭据
+        handle: NoteHandle,
+    },
+}
+
+/// 密钥托管操作
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum EscrowAction {
+    /// 生成管理员恢复密钥对（私钥写入文件，公钥输出到stdout）
+    Keygen {
+        /// 私钥文件路径
+        #[arg(short, long, default_value = "clipvanish-recovery.key")]
+        output: String,
+    },
+    /// 使用恢复私钥解密取证归档中的密文
+    Decrypt {
+        /// 取证归档路径
+        archive: String,
+        /// 恢复私钥文件
+        #[arg(short = 'k', long)]
+        private_key: String,
+    },
+}
+
+/// 诱饵令牌操作
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum CanaryAction {
+    /// 生成新令牌并输出（可手动放到其他位置，如 .env 文件）
+    Generate,
+    /// 停用当前令牌，下次放置时使用新令牌
+    Rotate,
+    /// 列出发出过的令牌
+    List,
+    /// 在文件或stdin中查找发出过的令牌
+    Check {
+        /// 要检查的文件（缺省读取stdin）
+        files: Vec<String>,
+    },
+}
+
+/// 紧急销毁双人确认操作
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum GuardAction {
+    /// 生成TOTP共享密钥，输出配置值与供验证器应用导入的otpauth链接
+    SetupTotp,
+    /// 登记YubiKey挑战-响应槽位（需已用 ykman 配置该槽位），输出配置值
+    #[cfg(feature = "hardware-keys")]
+    EnrollKey {
+        /// 挑战-响应槽位
+        #[arg(long, default_value = "2", value_parser = clap::value_parser!(u8).range(1..=2))]
+        slot: u8,
+    },
+}
+
+/// 审计日志操作
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum AuditAction {
+    /// 按时间范围导出审计日志（含哈希链值，供SIEM采集后重新校验）
+    Export {
+        /// 起始时间（如 2024-01-01、7d 表示7天前；缺省为最早的记录）
+        #[arg(long)]
+        from: Option<String>,
+        /// 结束时间（纯日期包含当天全天）
+        #[arg(long, default_value = "now")]
+        to: String,
+        /// 导出格式
+        #[arg(long, value_enum, default_value_t = ExportFormat::Jsonl)]
+        format: ExportFormat,
+        /// 输出文件（缺省输出到stdout）
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+}
+
+/// 持久化格式操作
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum FormatsAction {
+    /// 列出写入磁盘的数据格式及当前修订号
+    List,
+    /// 输出数据格式的JSON Schema（缺省输出全部格式）
+    DumpSchema {
+        /// 格式名称（audit、history、notes、canaries、backup）
+        format: Option<String>,
+    },
+}
+
+/// 用户数据备份操作
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum BackupAction {
+    /// 将配置、规则与统计数据备份到以口令加密的文件（口令从stdin读取一行）
+    Create {
+        /// 备份文件
+        file: String,
+        /// 同时备份加密笔记（恢复后仍需原来的读取凭据）
+        #[arg(long)]
+        include_notes: bool,
+    },
+    /// 从备份文件恢复到本机配置目录（被替换的文件保留为 *.pre-restore）
+    Restore {
+        /// 备份文件
+        file: String,
+    },
+}
+
+/// 配置备份操作
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum ConfigAction {
+    /// 列出配置备份（最新的为1）
+    Backups,
+    /// 将配置回滚到指定备份
+    Restore {
+        /// 备份序号（1为最新，见 config backups）
+        #[arg(long, default_value_t = 1)]
+        backup: usize,
+    },
+}
+
+/// 规则包操作
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum RulesAction {
+    /// 将当前的敏感模式与熵值规则导出为规则包
+    Export {
+        /// 规则包文件路径
+        file: String,
+        /// 规则包名称（默认为文件名）
+        #[arg(long)]
+        name: Option<String>,
+        /// 使用签名私钥签名（见 rules keygen）
+        #[arg(long)]
+        sign_key: Option<String>,
+    },
+    /// 验证规则包签名并合并进配置
+    Import {
+        /// 规则包文件路径或https地址
+        source: String,
+        /// 允许导入未签名的规则包
+        #[arg(long)]
+        allow_unsigned: bool,
+        /// 信任该签名公钥（64位十六进制），导入成功后加入 rule_packs.trusted_keys
+        #[arg(long)]
+        trust: Option<String>,
+        /// 用规则包的敏感模式替换现有的 sensitive_pattern，而不是合并
+        #[arg(long)]
+        replace: bool,
+    },
+    /// 生成规则包签名密钥对（私钥写入文件，公钥输出到stdout）
+    Keygen {
+        /// 私钥文件路径
+        #[arg(short, long, default_value = "clipvanish-rules.key")]
+        output: String,
+    },
+}
+
+/// 使用统计操作
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum StatsAction {
+    /// 根据审计日志生成最近7天的使用报告
+    Report {
+        /// 生成单文件HTML报告（默认保存到配置目录下的reports）
+        #[arg(long)]
+        html: bool,
+        /// HTML报告路径
+        #[arg(short, long, requires = "html")]
+        output: Option<String>,
+        /// 趋势包含的周数
+        #[arg(long, default_value_t = 8)]
+        weeks: u32,
+    },
+    /// 导出可共享的加噪统计（差分隐私，不含内容，时间只精确到天），帮助调整默认检测器
+    Share {
+        /// 隐私预算ε（越小噪声越大、隐私越强）
+        #[arg(long, default_value_t = stats::DEFAULT_EPSILON)]
+        epsilon: f64,
+        /// 统计最近多少天（含今天）
+        #[arg(long, default_value_t = 28)]
+        days: u32,
+        /// 保存路径（默认输出到stdout）
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+}
 
 /// CLI错误类型
 #[derive(Debug)]
@@ -42,6 +362,10 @@ pub enum CliError {
     OperationCancelled,
     /// 服务运行错误
     ServiceError(String),
+    /// 问题报告生成失败
+    ReportError(String),
+    /// 紧急销毁的第二因素验证失败
+    SecondFactorError(String),
 }
 
 impl std::fmt::Display for CliError {
@@ -54,6 +378,8 @@ impl std::fmt::Display for CliError {
             CliError::ServiceNotRunning => write!(f, "ClipVanish服务未运行"),
             CliError::OperationCancelled => write!(f, "操作被用户取消"),
             CliError::ServiceError(msg) => write!(f, "服务错误: {}", msg),
+            CliError::ReportError(msg) => write!(f, "报告错误: {}", msg),
+            CliError::SecondFactorError(msg) => write!(f, "双人确认未通过: {}", msg),
         }
     }
 }
@@ -88,13 +414,38 @@ pub struct CliHandler {
     /// 自毁定时器
     destruct_timer: Option<Arc<Mutex<DestructTimer>>>,
     /// 键盘监听器
+    #[cfg(feature = "keyboard-hooks")]
     keyboard_monitor: Option<Arc<KeyboardMonitor>>,
     /// 全局热键管理器
+    #[cfg(feature = "hotkeys")]
     hotkey_manager: Option<GlobalHotKeyManager>,
     /// 服务状态
     service_status: Arc<Mutex<ServiceStatus>>,
     /// 是否应该停止服务
     should_stop: Arc<Mutex<bool>>,
+    /// 是否跳过所有确认提示（--yes）
+    assume_yes: bool,
+    /// 机器输出格式（--output）
+    output_format: OutputFormat,
+    /// 当前会话统计
+    session: Arc<Mutex<Option<SessionStats>>>,
+    /// 控制接口任务
+    #[cfg(all(unix, feature = "api"))]
+    ipc_task: Mutex<Option<tokio::task::JoinHandle<()>>>,
+    /// 代理套接字任务
+    #[cfg(all(unix, feature = "api"))]
+    agent_task: Mutex<Option<tokio::task::JoinHandle<()>>>,
+    /// 状态页任务
+    status_page_task: Mutex<Option<tokio::task::JoinHandle<()>>>,
+    /// 健康检查任务
+    watchdog_task: Mutex<Option<tokio::task::JoinHandle<()>>>,
+    /// 定时紧急销毁调度器
+    nuke_scheduler: Option<Arc<NukeScheduler>>,
+    /// 倒计时悬浮窗
+    overlay: Option<Arc<Overlay>>,
+    /// macOS菜单栏连接（`clipvanish menubar`）
+    #[cfg(all(target_os = "macos", feature = "menubar"))]
+    menubar: Option<MenuBarService>,
 }
 
 impl CliHandler {
@@ -119,13 +470,53 @@ impl CliHandler {
             config,
             clipboard_monitor: None,
             destruct_timer: None,
+            #[cfg(feature = "keyboard-hooks")]
             keyboard_monitor: None,
+            #[cfg(feature = "hotkeys")]
             hotkey_manager: None,
             service_status: Arc::new(Mutex::new(service_status)),
             should_stop: Arc::new(Mutex::new(false)),
+            assume_yes: false,
+            output_format: OutputFormat::Text,
+            session: Arc::new(Mutex::new(None)),
+            #[cfg(all(unix, feature = "api"))]
+            ipc_task: Mutex::new(None),
+            #[cfg(all(unix, feature = "api"))]
+            agent_task: Mutex::new(None),
+            status_page_task: Mutex::new(None),
+            watchdog_task: Mutex::new(None),
+            nuke_scheduler: None,
+            overlay: None,
+            #[cfg(all(target_os = "macos", feature = "menubar"))]
+            menubar: None,
         }
     }
     
+    /// 设置是否跳过所有确认提示
+    /// 
+    /// # 参数
+    /// * `assume_yes` - 为true时所有确认提示视为已确认
+    pub fn set_assume_yes(&mut self, assume_yes: bool) {
+        self.assume_yes = assume_yes;
+    }
+    
+    /// 设置机器输出格式
+    /// 
+    /// # 参数
+    /// * `format` - 输出格式
+    pub fn set_output_format(&mut self, format: OutputFormat) {
+        self.output_format = format;
+    }
+    
+    /// 连接macOS菜单栏（在启动监听服务之前调用）
+    /// 
+    /// # 参数
+    /// * `service` - 菜单栏连接的服务一侧
+    #[cfg(all(target_os = "macos", feature = "menubar"))]
+    pub fn attach_menubar(&mut self, service: MenuBarService) {
+        self.menubar = Some(service);
+    }
+    
     /// 启动剪贴板监听服务
     /// 
     /// # 参数
@@ -141,44 +532,109 @@ impl CliHandler {
         {
             let is_running = self.service_status.lock().unwrap().is_running;
             if is_running {
-                println!("⚠️  ClipVanish服务已在运行");
+                notice!("⚠️  ClipVanish服务已在运行");
                 return Ok(());
             }
         }
         
+        // 反复崩溃后以安全模式启动，收紧配置后再创建各组件
+        let safe_mode = safe_mode::begin_run(&self.config.safe_mode);
+        let timer_duration = match &safe_mode {
+            Some(safe_mode) => {
+                safe_mode.apply(&mut self.config);
+                timer_duration.min(SafeMode::countdown_cap())
+            }
+            None => timer_duration,
+        };
+        
         // 显示启动信息
         if !daemon_mode {
             self.display_startup_info(timer_duration);
         }
         
+        // 登记新启用或修改过的规则（进入仅警告阶段）
+        rule_trial::sync(&self.config);
+        
         // 初始化剪贴板监听器
         let clipboard_monitor = Arc::new(
-            ClipboardMonitor::new(self.config.clone())
-                .map_err(|e| CliError::ClipboardError(e.to_string()))?
+            ClipboardMonitor::new(self.config.clone()).map_err(|e| {
+                safe_mode::record_crash(Subsystem::Clipboard, &format!("启动失败: {}", e));
+                CliError::ClipboardError(e.to_string())
+            })?
         );
         
         // 初始化定时器
         let destruct_timer = Arc::new(Mutex::new({
             let mut timer = DestructTimer::new();
-            timer.start_service().await
-                .map_err(|e| CliError::TimerError(e.to_string()))?;
+            timer.start_service().await.map_err(|e| {
+                safe_mode::record_crash(Subsystem::Timers, &format!("启动失败: {}", e));
+                CliError::TimerError(e.to_string())
+            })?;
             timer
         }));
 
-        // 初始化键盘监听器
-        let keyboard_monitor = Arc::new(KeyboardMonitor::new());
+        // 立即登记清理步骤，启动过程中崩溃同样会清除剪贴板与密钥
+        self.register_shutdown_teardown(&clipboard_monitor);
+        
+        // 无头模式下没有系统剪贴板与键盘，跳过所有依赖图形环境的组件
+        let headless = virtual_clipboard::is_headless();
+        
+        // 检测各项功能是否可用，降级或停用的功能在此统一警告一次
+        capabilities::detect(&self.config);
+        for confined in confinement::detect() {
+            warn!("运行于{}，剪贴板或键盘访问可能受限；{}", confined, confined.guidance());
+        }
+        if let Some(safe_mode) = &safe_mode {
+            Self::announce_safe_mode(safe_mode);
+        }
+        
+        // 会话期间禁用Windows剪贴板历史
+        if !headless && self.config.clipboard.history_policy == ClipboardHistoryPolicy::Suppress {
+            if let Err(e) = win_history::suppress_for_session() {
+                warn!("禁用Windows剪贴板历史失败: {}", e);
+            }
+        }
+        
+        if !headless {
+            // 经用户同意后，会话期间关闭Handoff
+            self.apply_handoff_policy();
+            
+            // Windows延迟渲染：剪贴板中只放承诺，粘贴请求时才解密
+            #[cfg(target_os = "windows")]
+            self.start_delayed_rendering(&clipboard_monitor);
+            #[cfg(not(target_os = "windows"))]
+            if self.config.clipboard.delayed_rendering {
+                warn!("延迟渲染仅支持Windows，受保护内容仍以密文写入剪贴板");
+            }
+            
+            // 倒计时悬浮窗（未启用时只在热键第一次显示时创建窗口）
+            self.overlay = Some(Arc::new(Overlay::new(self.config.overlay.clone())));
+        }
         
         // 保存组件引用（在注册热键之前）
         self.clipboard_monitor = Some(clipboard_monitor.clone());
         self.destruct_timer = Some(destruct_timer.clone());
-        self.keyboard_monitor = Some(keyboard_monitor.clone());
+        self.nuke_scheduler = Some(NukeScheduler::new(clipboard_monitor.clone(), destruct_timer.clone()));
+        
+        // 开始新的会话统计
+        *self.session.lock().unwrap() = Some(SessionStats::new());
         
         // 设置事件回调
-        self.setup_event_callbacks(&clipboard_monitor, &destruct_timer, &keyboard_monitor, timer_duration);
+        self.setup_event_callbacks(&clipboard_monitor, &destruct_timer, timer_duration);
         
         // 注册全局热键
-        if self.config.hotkeys.enable_global_hotkeys {
-            self.register_global_hotkeys(&clipboard_monitor, &destruct_timer)?;
+        #[cfg(feature = "hotkeys")]
+        if self.config.hotkeys.enable_global_hotkeys && !headless {
+            // 注册失败时服务照常运行，只停用热键
+            if let Err(e) = self.register_global_hotkeys(&clipboard_monitor, &destruct_timer) {
+                capabilities::disable(capabilities::Feature::GlobalHotkeys, &confinement::annotate(&e.to_string()));
+            }
+        }
+        
+        // 菜单栏命令与状态同步
+        #[cfg(all(target_os = "macos", feature = "menubar"))]
+        if let Some(service) = self.menubar.take() {
+            self.start_menubar_bridge(service, &clipboard_monitor, &destruct_timer);
         }
         
         // 更新服务状态
@@ -189,52 +645,157 @@ impl CliHandler {
             status.total_events = 0;
         }
         
-        // 启动监听循环（在后台）
+        // 启动信号处理（后台模式下不接管SIGINT/SIGTERM）
+        self.start_signal_handler(!daemon_mode).await;
+        
+        // 定期同步定时器状态，供 status 显示
+        self.start_status_update_task().await;
+        
+        // 启动监听循环（在后台，由监督器捕获panic并按退避重启）
         let poll_interval = self.config.get_poll_interval();
         let status_clone = self.service_status.clone();
+        let restart_monitor = Arc::new(Notify::new());
         let monitor_task = {
             let monitor = clipboard_monitor.clone();
+            let restart = restart_monitor.clone();
             tokio::spawn(async move {
-                if let Err(e) = monitor.start_monitoring(poll_interval).await {
-                    error!("剪贴板监听任务失败: {}", e);
-                    // 更新状态为非运行
-                    if let Ok(mut status) = status_clone.lock() {
-                        status.is_running = false;
-                    }
+                let stop_monitor = monitor.clone();
+                let health_monitor = monitor.clone();
+                supervisor::supervise(
+                    RestartPolicy::default(),
+                    move || {
+                        let monitor = monitor.clone();
+                        async move { monitor.start_monitoring(poll_interval).await.map_err(|e| e.to_string()) }
+                    },
+                    move || stop_monitor.stop_requested(),
+                    move |health| health_monitor.set_health(health),
+                    restart,
+                )
+                .await;
+                // 监督器只在监听停止后返回
+                if let Ok(mut status) = status_clone.lock() {
+                    status.is_running = false;
                 }
             })
         };
 
         // 启动键盘监听任务
-        let keyboard_task = {
-            let keyboard = keyboard_monitor.clone();
+        #[cfg(feature = "keyboard-hooks")]
+        if !headless && safe_mode.is_none() {
+            let keyboard_monitor = Arc::new(KeyboardMonitor::new());
+            self.keyboard_monitor = Some(keyboard_monitor.clone());
+            self.setup_keyboard_callback(&clipboard_monitor, &keyboard_monitor);
+            
             tokio::spawn(async move {
-                if let Err(e) = keyboard.start_monitoring().await {
-                    error!("键盘监听任务失败: {}", e);
+                if let Err(e) = keyboard_monitor.start_monitoring().await {
+                    capabilities::disable(
+                        capabilities::Feature::KeyboardHooks,
+                        &confinement::annotate(&format!("键盘监听任务失败: {}", e)),
+                    );
                 }
-            })
-        };
+            });
+        }
+        // 启动本地控制接口
+        #[cfg(all(unix, feature = "api"))]
+        if self.config.ipc.enabled {
+            self.start_control_server(&clipboard_monitor, &destruct_timer);
+        }
+        // 启动代理套接字
+        #[cfg(all(unix, feature = "api"))]
+        if self.config.agent.enabled {
+            self.start_agent_server(&clipboard_monitor);
+        }
+        // 启动Webhook推送
+        if let Some(dispatcher) = WebhookDispatcher::new(self.config.webhooks.clone()) {
+            if helpers::is_strict() {
+                warn!("严格模式下不能启动curl，Webhook推送已停用");
+            } else {
+                notice!("🪝 Webhook推送: {} 个端点", self.config.webhooks.endpoints.len());
+                let events = audit::subscribe(dispatcher.filter());
+                tokio::spawn(dispatcher.run(events));
+            }
+        }
+        // 空闲时放置诱饵令牌
+        if self.config.canary.enabled {
+            let monitor = clipboard_monitor.clone();
+            notice!("🐤 诱饵令牌: 剪贴板空闲 {} 秒后放置", self.config.canary.idle_seconds);
+            tokio::spawn(async move {
+                let mut idle = IdleTracker::default();
+                let mut interval = tokio::time::interval(canary::CHECK_INTERVAL);
+                while !monitor.stop_requested() {
+                    interval.tick().await;
+                    if let Err(e) = monitor.tend_canary(&mut idle) {
+                        warn!("放置诱饵令牌失败: {}", e);
+                    }
+                }
+            });
+        }
+        // 前台为管理员权限程序时提示（仅Windows）
+        #[cfg(target_os = "windows")]
+        if !headless {
+            elevation::spawn(clipboard_monitor.clone());
+        }
+        // 检测可疑的剪贴板读取者
+        if !headless && self.config.reader_watch.enabled {
+            #[cfg(target_os = "windows")]
+            reader_watch::spawn(self.config.reader_watch.clone(), clipboard_monitor.clone());
+            #[cfg(not(target_os = "windows"))]
+            warn!("可疑剪贴板读取者检测仅支持Windows，已跳过");
+        }
+        // 启动MQTT状态发布
+        if self.config.integrations.mqtt.enabled {
+            let publisher = MqttPublisher::new(self.config.integrations.mqtt.clone(), clipboard_monitor.clone());
+            notice!("📡 MQTT发布: {}", self.config.integrations.mqtt.broker);
+            tokio::spawn(publisher.run(audit::subscribe(EventFilter::events(&["destroyed"]))));
+        }
+        // 要求剪贴板管理器删除或不记录受保护内容
+        if !headless && self.config.integrations.clipboard_managers.enabled {
+            if helpers::is_strict() {
+                warn!("严格模式下不能启动copyq等辅助程序，剪贴板管理器协作已停用");
+            } else {
+                let bridge = clipboard_monitor.manager_bridge();
+                bridge.start();
+                tokio::spawn(bridge.run(audit::subscribe(EventFilter::events(&["destroyed"]))));
+            }
+        }
+        // 启动只读状态页
+        if self.config.status_page.enabled {
+            let page = Arc::new(StatusPage::new(self.config.status_page.clone(), clipboard_monitor.clone(), destruct_timer.clone()));
+            match page.bind().await {
+                Ok(listener) => {
+                    notice!("🖥️  状态页: http://127.0.0.1:{}/", self.config.status_page.port);
+                    *self.status_page_task.lock().unwrap() = Some(tokio::spawn(page.serve(listener, audit::subscribe(self.config.status_page.event_filter.clone()))));
+                }
+                Err(e) => warn!("启动状态页失败: {}", e),
+            }
+        }
+        
+        // 定期检查监听循环、倒计时与剪贴板连接
+        if self.config.health.enabled {
+            self.start_watchdog(&clipboard_monitor, &destruct_timer, restart_monitor);
+        }
+        
+        self.register_shutdown_services(&clipboard_monitor, &destruct_timer);
         
-        println!("✅ ClipVanish服务已启动");
-        println!("   自毁倒计时: {}秒", timer_duration);
-        println!("   紧急销毁热键: {}", self.config.hotkeys.emergency_nuke_key);
+        notice!("✅ ClipVanish服务已启动");
+        notice!("   自毁倒计时: {}", duration::format(Duration::from_secs(timer_duration)));
+        notice!("   紧急销毁热键: {}", self.config.hotkeys.emergency_nuke_key);
+        if let Ok(Some(recipient)) = self.config.escrow.recipient() {
+            notice!("🔑 密钥托管已启用：会话密钥同时封装给恢复公钥 {}，管理员可解密导出的密文", recipient.fingerprint());
+        }
         
         if daemon_mode {
             // 后台模式：启动后立即返回
-            println!("🔄 ClipVanish已在后台启动");
+            notice!("🔄 ClipVanish已在后台启动");
             Ok(())
         } else {
             // 前台模式：保持运行，但不等待监听任务完成
             // 这样可以避免因为监听任务结束而导致程序退出
-            println!("\n📊 实时状态 (按 Ctrl+C 停止监听):");
-            println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-            println!("🔄 监听服务已在后台运行，程序将持续运行直到手动停止");
-
-            // 使用一个无限循环来保持程序运行，并在监听任务结束时重新启动
-            let mut current_monitor_task = monitor_task;
-            let mut restart_count = 0;
-            const MAX_RESTART_ATTEMPTS: u32 = 5;
+            notice!("\n📊 实时状态 (按 Ctrl+C 停止监听):");
+            notice!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+            notice!("🔄 监听服务已在后台运行，程序将持续运行直到手动停止");
 
+            // 保持运行直到收到停止请求；监听循环的失败由监督器处理
             loop {
                 tokio::time::sleep(Duration::from_secs(1)).await;
 
@@ -243,38 +804,16 @@ impl CliHandler {
                     break;
                 }
 
-                // 检查监听任务是否还在运行
-                if current_monitor_task.is_finished() {
-                    if restart_count < MAX_RESTART_ATTEMPTS {
-                        restart_count += 1;
-                        warn!("监听任务意外结束，尝试重新启动... (第 {}/{} 次)", restart_count, MAX_RESTART_ATTEMPTS);
-
-                        // 重新启动监听任务
-                        let monitor = clipboard_monitor.clone();
-                        let status_clone = self.service_status.clone();
-                        current_monitor_task = tokio::spawn(async move {
-                            if let Err(e) = monitor.start_monitoring(poll_interval).await {
-                                error!("剪贴板监听任务失败: {}", e);
-                                // 更新状态为非运行
-                                if let Ok(mut status) = status_clone.lock() {
-                                    status.is_running = false;
-                                }
-                            }
-                        });
-
-                        // 等待一段时间再继续检查
-                        tokio::time::sleep(Duration::from_secs(2)).await;
-                    } else {
-                        error!("监听任务重启次数过多，停止尝试");
-                        break;
-                    }
-                } else {
-                    // 如果任务正常运行，重置重启计数器
-                    restart_count = 0;
+                // 监督器只在监听停止后结束
+                if monitor_task.is_finished() {
+                    break;
                 }
             }
 
-            println!("📴 ClipVanish监听已停止");
+            // 由信号触发时已经完成关闭，这里不会重复执行
+            Self::run_shutdown(Trigger::Stop).await;
+            notice!("📴 ClipVanish监听已停止");
+            self.finish_session();
             Ok(())
         }
     }
@@ -282,266 +821,2424 @@ impl CliHandler {
     /// 紧急销毁所有数据
     /// 
     /// # 参数
-    /// * `force` - 是否强制执行（跳过确认）
+    /// * `force` - 是否强制执行（跳过确认，不跳过双人确认）
+    /// * `code` - 双人确认的TOTP验证码
     /// 
     /// # 返回值
     /// * `Result<(), CliError>` - 操作结果
-    pub async fn emergency_nuke(&self, force: bool) -> Result<(), CliError> {
+    pub async fn emergency_nuke(&self, force: bool, scope: &NukeScope, code: Option<&str>) -> Result<(), CliError> {
         if !force {
-            println!("⚠️  紧急销毁操作");
-            println!("   这将立即清除所有剪贴板数据和内存中的敏感信息");
-            print!("   确认执行? (y/N): ");
-            
-            use std::io::{self, Write};
-            io::stdout().flush().unwrap();
-            
-            let mut input = String::new();
-            io::stdin().read_line(&mut input).unwrap();
+            notice!("⚠️  紧急销毁操作");
+            if scope.is_full() {
+                notice!("   这将立即清除所有剪贴板数据和内存中的敏感信息");
+            } else {
+                notice!("   这将立即销毁选定的部分: {}", Self::describe_nuke_scope(scope));
+            }
             
-            if !input.trim().to_lowercase().starts_with('y') {
-                println!("❌ 操作已取消");
+            if !self.confirm("确认执行?") {
+                notice!("❌ 操作已取消");
                 return Err(CliError::OperationCancelled);
             }
         }
+        self.authorize_nuke(code)?;
         
-        println!("🔥 执行紧急销毁...");
+        notice!("🔥 执行紧急销毁...");
         
-        // 如果有剪贴板监听器，执行紧急销毁
-        if let Some(monitor) = &self.clipboard_monitor {
-            monitor.emergency_nuke()
-                .map_err(|e| CliError::ClipboardError(e.to_string()))?;
+        // 如果有剪贴板监听器，按范围执行紧急销毁
+        let outcome = match &self.clipboard_monitor {
+            Some(monitor) => monitor.nuke_scoped(scope)
+                .map_err(|e| CliError::ClipboardError(e.to_string()))?,
+            None => NukeOutcome { clipboard_cleared: true, keys_regenerated: true, ..NukeOutcome::default() },
+        };
+        if !scope.is_full() {
+            audit::record("nuke", &format!("部分销毁 {}", Self::describe_nuke_scope(scope)));
         }
         
-        // 停止定时器
-        if let Some(timer) = &self.destruct_timer {
-            let timer = timer.lock().unwrap();
-            timer.stop_countdown()
-                .map_err(|e| CliError::TimerError(e.to_string()))?;
+        // 受保护内容已清除时停止定时器
+        if outcome.clipboard_cleared {
+            if let Some(timer) = &self.destruct_timer {
+                let timer = timer.lock().unwrap();
+                timer.stop_countdown()
+                    .map_err(|e| CliError::TimerError(e.to_string()))?;
+            }
         }
         
-        // 执行全局内存清理
-        SecureMemory::secure_zero_memory();
-        
-        println!("✅ 紧急销毁完成");
-        println!("   - 剪贴板已清除");
-        println!("   - 内存已安全擦除");
-        println!("   - 加密密钥已重新生成");
+        if self.output_format == OutputFormat::Json {
+            output::emit_json(&serde_json::json!(outcome));
+        }
+        notice!("✅ 紧急销毁完成");
+        if outcome.clipboard_cleared {
+            notice!("   - 剪贴板已清除");
+        }
+        if outcome.history_removed > 0 {
+            notice!("   - 已删除 {} 条历史记录", outcome.history_removed);
+        }
+        notice!("   - 内存已安全擦除");
+        if outcome.keys_regenerated {
+            notice!("   - 加密密钥已重新生成");
+        }
+        if outcome.restored_plain {
+            notice!("   - 最近一条非敏感内容已恢复");
+        }
         
         Ok(())
     }
     
-    /// 显示服务状态
+    /// 部分紧急销毁范围的说明
+    fn describe_nuke_scope(scope: &NukeScope) -> String {
+        let mut parts = Vec::new();
+        if scope.clipboard_only {
+            parts.push("仅剪贴板".to_string());
+        }
+        if scope.history_only {
+            parts.push("仅历史记录".to_string());
+        }
+        if scope.keys_only {
+            parts.push("仅密钥".to_string());
+        }
+        if !scope.slots.is_empty() {
+            let slots: Vec<String> = scope.slots.iter().map(|slot| format!("#{}", slot)).collect();
+            parts.push(format!("条目 {}", slots.join(",")));
+        }
+        if let Some(older_than) = scope.older_than {
+            parts.push(format!("早于 {}", Self::format_duration(older_than)));
+        }
+        parts.join("，")
+    }
+    
+    /// 登记定时紧急销毁
+    /// 
+    /// # 参数
+    /// * `at` - 绝对时间（如 18:00）
+    /// * `delay` - 相对时长（如 2h）
     /// 
     /// # 返回值
     /// * `Result<(), CliError>` - 操作结果
-    pub async fn show_status(&self) -> Result<(), CliError> {
-        let status = self.service_status.lock().unwrap().clone();
+    pub async fn schedule_nuke(&self, at: Option<String>, delay: Option<String>, code: Option<&str>) -> Result<(), CliError> {
+        let scheduler = self.local_nuke_scheduler()?;
+        let time = schedule::resolve(at.as_deref(), delay.as_deref())
+            .map_err(|e| CliError::TimerError(e.to_string()))?;
+        self.authorize_nuke(code)?;
         
-        println!("📊 ClipVanish™ 服务状态");
-        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        if let Some(replaced) = scheduler.schedule(time, "交互命令") {
+            notice!("ℹ️  已取代原计划 {}", replaced.at.format("%Y-%m-%d %H:%M:%S"));
+        }
+        let scheduled = scheduler.pending().ok_or(CliError::ServiceNotRunning)?;
         
-        if status.is_running {
-            println!("🟢 状态: 运行中");
-            
-            if let Some(start_time) = status.start_time {
-                let uptime = start_time.elapsed();
-                println!("⏱️  运行时间: {}", Self::format_duration(uptime));
+        if self.output_format == OutputFormat::Json {
+            output::emit_json(&serde_json::json!({ "scheduled_nuke": scheduled }));
+        }
+        notice!(
+            "⏳ 已登记定时紧急销毁: {}（{}后）",
+            scheduled.at.format("%Y-%m-%d %H:%M:%S"),
+            Self::format_duration(scheduled.remaining())
+        );
+        notice!("   使用 nuke --cancel 取消");
+        Ok(())
+    }
+    
+    /// 取本进程中的定时销毁调度器
+    /// 
+    /// 计划只保存在运行服务的进程内，单独执行的命令无法访问其他进程中的服务，
+    /// 因此定时销毁只能在交互模式中登记与取消，其他进程需通过控制接口的 schedule_nuke/cancel_nuke 请求
+    fn local_nuke_scheduler(&self) -> Result<&Arc<NukeScheduler>, CliError> {
+        self.nuke_scheduler.as_ref().ok_or_else(|| CliError::ServiceError(
+            "定时紧急销毁只能在交互模式（clipvanish -i）中登记或取消，其他进程请通过控制接口的 schedule_nuke/cancel_nuke 请求".to_string(),
+        ))
+    }
+    
+    /// 验证紧急销毁的第二因素（未启用双人确认时直接通过）
+    /// 
+    /// # 参数
+    /// * `code` - TOTP验证码（未提供时请求硬件密钥触摸）
+    fn authorize_nuke(&self, code: Option<&str>) -> Result<(), CliError> {
+        let guard = &self.config.nuke_guard;
+        if !guard.enabled {
+            return Ok(());
+        }
+        if code.is_none() && guard.active_hardware_key().is_some() {
+            notice!("🔑 请触摸硬件密钥以确认紧急销毁...");
+        }
+        match nuke_guard::authorize(guard, code, "命令行") {
+            Ok(factor) => {
+                info!("紧急销毁已通过第二因素确认: {}", factor.name());
+                Ok(())
             }
-            
-            println!("📈 处理事件: {} 次", status.total_events);
-            
-            if status.encrypted_content_length > 0 {
-                println!("🔒 加密内容: {} 字节", status.encrypted_content_length);
-            } else {
-                println!("📋 剪贴板: 空");
+            Err(e) => {
+                if code.is_none() && guard.active_hardware_key().is_none() {
+                    notice!("   使用 nuke --code <验证码> 提供TOTP验证码");
+                }
+                Err(CliError::SecondFactorError(e.to_string()))
             }
-            
-            match status.timer_state {
-                TimerState::Idle => println!("⏰ 定时器: 待机"),
-                TimerState::Running { .. } => {
-                    if let Some(remaining) = status.remaining_time {
-                        println!("⏰ 倒计时: {}", Self::format_duration(remaining));
-                        
-                        // 显示进度条
-                        if self.config.ui.show_progress {
-                            let total_duration = self.config.get_default_countdown_duration();
-                            let progress = 1.0 - (remaining.as_secs_f64() / total_duration.as_secs_f64());
-                            self.display_progress_bar(progress);
-                        }
-                    }
-                },
-                TimerState::Completed => println!("⏰ 定时器: 已完成"),
-                TimerState::Cancelled => println!("⏰ 定时器: 已取消"),
-                TimerState::Error(ref msg) => println!("⏰ 定时器: 错误 - {}", msg),
+        }
+    }
+    
+    /// 验证热键、菜单或信号触发的紧急销毁（未启用双人确认时直接通过）
+    ///
+    /// 这些触发方式无法输入验证码，启用双人确认后只能通过硬件密钥触摸确认
+    ///
+    /// # 参数
+    /// * `guard` - 双人确认配置
+    /// * `trigger` - 触发来源（热键、菜单、信号），用于提示与审计
+    #[cfg(any(unix, feature = "hotkeys", all(target_os = "macos", feature = "menubar")))]
+    fn authorize_triggered_nuke(guard: &NukeGuardConfig, trigger: &str) -> bool {
+        if !guard.enabled {
+            return true;
+        }
+        if guard.active_hardware_key().is_some() {
+            notice!("\n🔑 请触摸硬件密钥以确认{}触发的紧急销毁...", trigger);
+        }
+        match nuke_guard::authorize(guard, None, trigger) {
+            Ok(factor) => {
+                info!("{}触发的紧急销毁已通过第二因素确认: {}", trigger, factor.name());
+                true
+            }
+            Err(e) => {
+                notice!("\n⛔ {}触发的紧急销毁未执行: {}", trigger, e);
+                false
             }
-        } else {
-            println!("🔴 状态: 未运行");
         }
-        
-        println!();
-        println!("🔧 配置信息:");
-        println!("   默认倒计时: {}秒", self.config.timer.default_countdown);
-        println!("   内存锁定: {}", if self.config.security.enable_memory_locking { "启用" } else { "禁用" });
-        println!("   全局热键: {}", if self.config.hotkeys.enable_global_hotkeys { "启用" } else { "禁用" });
-        
-        Ok(())
     }
     
-    /// 停止服务
+    /// 取消定时紧急销毁
     /// 
     /// # 返回值
     /// * `Result<(), CliError>` - 操作结果
-    pub async fn stop_service(&self) -> Result<(), CliError> {
-        let is_running = self.service_status.lock().unwrap().is_running;
+    pub async fn cancel_scheduled_nuke(&self) -> Result<(), CliError> {
+        let scheduler = self.local_nuke_scheduler()?;
+        match scheduler.cancel("交互命令") {
+            Some(cancelled) => notice!("🚫 已取消定时紧急销毁（原定 {}）", cancelled.at.format("%Y-%m-%d %H:%M:%S")),
+            None => notice!("ℹ️  没有已登记的定时紧急销毁"),
+        }
+        Ok(())
+    }
+    
+    /// 结束当前会话，输出摘要并写入审计日志
+    /// 
+    /// 没有进行中的会话时不做任何处理
+    pub fn finish_session(&self) {
+        win_history::restore_after_session();
+        handoff::restore_after_session();
         
-        if !is_running {
-            println!("ℹ️  ClipVanish服务未运行");
-            return Ok(());
+        Self::record_session(&self.session);
+    }
+    
+    /// 输出会话摘要并写入审计日志（没有进行中的会话时不做任何处理）
+    fn record_session(session: &Mutex<Option<SessionStats>>) {
+        let Some(stats) = session.lock().unwrap().take() else {
+            return;
+        };
+        
+        let summary = stats.summary();
+        notice!();
+        notice!("{}", summary);
+        audit::record("session", &summary.to_audit_detail());
+    }
+    
+    /// 提示以安全模式启动的原因与限制，并记录审计与停用的功能
+    ///
+    /// # 参数
+    /// * `safe_mode` - 安全模式
+    fn announce_safe_mode(safe_mode: &SafeMode) {
+        capabilities::disable(capabilities::Feature::KeyboardHooks, "安全模式");
+        capabilities::disable(capabilities::Feature::GlobalHotkeys, "安全模式");
+        warn!("以安全模式启动: {}", safe_mode);
+        audit::record("safe_mode", &safe_mode.to_string());
+        notifications::notify(
+            Severity::Critical,
+            "ClipVanish：安全模式",
+            &format!("{}。已停用键盘监听与全局热键，剪贴板仍受保护", safe_mode),
+        );
+        notice!("🛟 安全模式: {}", safe_mode);
+        notice!("   已停用键盘监听、全局热键、悬浮窗与延迟渲染，剪贴板只以轮询方式保护");
+        notice!("   倒计时从复制时开始，不超过{}秒；粘贴时不会自动解密，请使用 get 命令", SafeMode::countdown_cap());
+        notice!("   排查完成后正常停止服务（stop 或 Ctrl+C），下次启动即恢复正常模式");
+    }
+    
+    /// 启动健康检查任务
+    ///
+    /// 定期写入健康状态文件并向systemd发送看门狗通知；监听循环卡住时请求监督器重启，
+    /// 连续不健康达到 `health.exit_after` 次后执行关闭流程并以非零状态退出，交由服务管理器重启
+    ///
+    /// # 参数
+    /// * `monitor` - 剪贴板监听器
+    /// * `timer` - 自毁定时器
+    /// * `restart_monitor` - 通知监督器重启监听循环
+    fn start_watchdog(&self, monitor: &Arc<ClipboardMonitor>, timer: &Arc<Mutex<DestructTimer>>, restart_monitor: Arc<Notify>) {
+        let config = self.config.health.clone();
+        let monitor = monitor.clone();
+        let timer = timer.clone();
+        let heartbeat = monitor.heartbeat();
+        let systemd = config.systemd_watchdog && health::systemd::available();
+        // systemd要求的通知间隔更短时按其要求检查
+        let interval = match health::systemd::watchdog_interval().filter(|_| systemd) {
+            Some(watchdog) => config.interval().min(watchdog),
+            None => config.interval(),
+        };
+        if systemd {
+            if let Err(e) = health::systemd::notify("READY=1") {
+                warn!("向systemd发送就绪通知失败: {}", e);
+            }
+        }
+        
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            let mut unhealthy = 0u32;
+            while !monitor.stop_requested() {
+                ticker.tick().await;
+                let timer_overdue = match timer.lock().map(|timer| timer.get_state()) {
+                    Ok(TimerState::Running { start_time, total_duration }) => start_time.elapsed().checked_sub(total_duration),
+                    _ => None,
+                };
+                let probe = health::Probe {
+                    since_tick: heartbeat.since_tick(),
+                    uptime: heartbeat.uptime(),
+                    supervisor: monitor.get_state().health,
+                    timer_overdue,
+                    read_failures: heartbeat.read_failures(),
+                };
+                let report = health::evaluate(&config, &probe, chrono::Utc::now());
+                health::publish(&report);
+                
+                if report.healthy() {
+                    if unhealthy > 0 {
+                        info!("健康检查恢复正常");
+                        audit::record("health_recovered", &format!("连续 {} 次不健康后恢复", unhealthy));
+                    }
+                    unhealthy = 0;
+                    if systemd {
+                        if let Err(e) = health::systemd::notify("WATCHDOG=1") {
+                            debug!("向systemd发送看门狗通知失败: {}", e);
+                        }
+                    }
+                    continue;
+                }
+                
+                unhealthy += 1;
+                let problems = report.problems();
+                warn!("健康检查未通过（连续第 {} 次）: {}", unhealthy, problems);
+                if unhealthy == 1 {
+                    audit::record("health_check_failed", &problems);
+                    if report.monitor_loop_unhealthy() {
+                        warn!("请求重启监听循环");
+                        restart_monitor.notify_one();
+                    }
+                }
+                if config.exit_after > 0 && unhealthy >= config.exit_after {
+                    error!("连续 {} 次健康检查未通过，清除数据后退出以便服务管理器重启", unhealthy);
+                    audit::record("health_exit", &format!("连续 {} 次不健康: {}", unhealthy, problems));
+                    safe_mode::record_crash(Subsystem::from_health(&report), &format!("看门狗退出: {}", problems));
+                    notifications::notify(Severity::Critical, "ClipVanish：服务不健康", &format!("已清除数据并退出：{}", problems));
+                    Self::run_shutdown(Trigger::Watchdog).await;
+                    std::process::exit(health::EXIT_WATCHDOG);
+                }
+            }
+        });
+        *self.watchdog_task.lock().unwrap() = Some(task);
+    }
+    
+    /// 经由关闭协调器按阶段关闭服务，未完成的步骤逐一提示
+    ///
+    /// 没有已登记的步骤（服务未运行或已经关闭）时不做任何处理
+    ///
+    /// # 参数
+    /// * `trigger` - 触发原因
+    pub async fn run_shutdown(trigger: Trigger) {
+        let report = tokio::task::spawn_blocking(move || shutdown::coordinator().run(trigger))
+            .await
+            .ok()
+            .flatten();
+        for problem in report.iter().flat_map(|report| report.problems()) {
+            notice!("⚠️  关闭步骤 {}/{} 未完成（{}ms）: {:?}", problem.phase, problem.name, problem.elapsed.as_millis(), problem.status);
         }
+    }
+    
+    /// 登记销毁条目、擦除密钥与释放钩子的关闭步骤（使用弱引用，不延长监听器生命周期）
+    fn register_shutdown_teardown(&self, clipboard_monitor: &Arc<ClipboardMonitor>) {
+        let coordinator = shutdown::coordinator();
+        coordinator.set_deadline(self.config.shutdown.deadline());
+        
+        let weak_monitor = Arc::downgrade(clipboard_monitor);
+        coordinator.register(Phase::DestroyItems, "clipboard", Box::new(move |trigger| {
+            let Some(monitor) = weak_monitor.upgrade() else {
+                return Ok(());
+            };
+            // 崩溃时锁可能仍被持有，只做不会阻塞的清理
+            if trigger == Trigger::Panic {
+                monitor.panic_teardown();
+                return Ok(());
+            }
+            monitor.destroy_for_shutdown().map_err(|e| e.to_string())
+        }));
         
-        println!("🛑 正在停止ClipVanish服务...");
+        let weak_monitor = Arc::downgrade(clipboard_monitor);
+        coordinator.register(Phase::WipeKeys, "keys", Box::new(move |_| match weak_monitor.upgrade() {
+            Some(monitor) => monitor.wipe_keys().map_err(|e| e.to_string()),
+            None => {
+                erase_scheduler::flush();
+                Ok(())
+            }
+        }));
         
-        // 设置停止标志
-        *self.should_stop.lock().unwrap() = true;
+        coordinator.register(Phase::ReleaseHooks, "system_settings", Box::new(|_| {
+            win_history::restore_after_session();
+            handoff::restore_after_session();
+            Ok(())
+        }));
         
-        // 停止剪贴板监听
-        if let Some(monitor) = &self.clipboard_monitor {
+        // 正常停止时清空崩溃计数；崩溃与看门狗退出已各自计入
+        coordinator.register(Phase::ReleaseHooks, "crash_counter", Box::new(|trigger| {
+            if !matches!(trigger, Trigger::Panic | Trigger::Watchdog) {
+                safe_mode::finish_run();
+            }
+            Ok(())
+        }));
+        
+        let bridge = clipboard_monitor.manager_bridge();
+        coordinator.register(Phase::ReleaseHooks, "clipboard_managers", Box::new(move |_| bridge.shutdown()));
+    }
+    
+    /// 登记停止接收、取消定时器、释放键盘钩子与写入会话摘要的关闭步骤（服务组件全部启动后调用）
+    fn register_shutdown_services(&self, clipboard_monitor: &Arc<ClipboardMonitor>, destruct_timer: &Arc<Mutex<DestructTimer>>) {
+        let coordinator = shutdown::coordinator();
+        
+        let monitor = clipboard_monitor.clone();
+        let status = self.service_status.clone();
+        coordinator.register(Phase::StopIntake, "monitor", Box::new(move |_| {
             monitor.stop_monitoring();
+            if let Ok(mut status) = status.lock() {
+                status.is_running = false;
+                status.start_time = None;
+            }
+            Ok(())
+        }));
+        
+        let servers: Vec<_> = [
+            &self.status_page_task,
+            &self.watchdog_task,
+            #[cfg(all(unix, feature = "api"))]
+            &self.ipc_task,
+            #[cfg(all(unix, feature = "api"))]
+            &self.agent_task,
+        ]
+        .iter()
+        .filter_map(|task| task.lock().unwrap().as_ref().map(|task| task.abort_handle()))
+        .collect();
+        coordinator.register(Phase::StopIntake, "servers", Box::new(move |_| {
+            servers.iter().for_each(|server| server.abort());
+            Ok(())
+        }));
+        if self.config.health.enabled {
+            coordinator.register(Phase::StopIntake, "health", Box::new(|_| {
+                health::clear();
+                if health::systemd::available() {
+                    let _ = health::systemd::notify("STOPPING=1");
+                }
+                Ok(())
+            }));
         }
-
-        // 停止键盘监听
-        if let Some(keyboard) = &self.keyboard_monitor {
-            keyboard.stop_monitoring();
+        
+        // 取消并等待尚未结束的倒计时任务，避免其在服务停止后继续操作剪贴板
+        let monitor = clipboard_monitor.clone();
+        let runtime = tokio::runtime::Handle::current();
+        coordinator.register(Phase::CancelTimers, "countdowns", Box::new(move |trigger| {
+            if trigger == Trigger::Panic {
+                monitor.abort_tasks();
+            } else {
+                runtime.block_on(monitor.shutdown_tasks());
+            }
+            Ok(())
+        }));
+        
+        let timer = destruct_timer.clone();
+        let countdown_status = self.config.ui.countdown_status;
+        coordinator.register(Phase::CancelTimers, "timer", Box::new(move |_| {
+            if countdown_status {
+                countdown_display::clear();
+            }
+            timer.lock().map_err(|e| e.to_string())?.shutdown().map_err(|e| e.to_string())
+        }));
+        
+        // 服务停止后无法执行定时销毁，取消并提示
+        if let Some(scheduler) = self.nuke_scheduler.clone() {
+            coordinator.register(Phase::CancelTimers, "scheduled_nuke", Box::new(move |_| {
+                if let Some(cancelled) = scheduler.cancel("服务停止") {
+                    notice!("🚫 定时紧急销毁（原定 {}）已随服务停止取消", cancelled.at.format("%Y-%m-%d %H:%M:%S"));
+                }
+                Ok(())
+            }));
         }
-
-        // 停止定时器
-        if let Some(timer) = &self.destruct_timer {
-            let timer = timer.lock().unwrap();
-            timer.shutdown()
-                .map_err(|e| CliError::TimerError(e.to_string()))?;
+        
+        #[cfg(feature = "keyboard-hooks")]
+        if let Some(keyboard) = self.keyboard_monitor.clone() {
+            coordinator.register(Phase::ReleaseHooks, "keyboard", Box::new(move |_| {
+                keyboard.stop_monitoring();
+                Ok(())
+            }));
+        }
+        
+        let session = self.session.clone();
+        coordinator.register(Phase::FlushAudit, "session", Box::new(move |_| {
+            Self::record_session(&session);
+            Ok(())
+        }));
+    }
+    
+    /// 启动本地控制接口
+    #[cfg(all(unix, feature = "api"))]
+    fn start_control_server(
+        &self,
+        clipboard_monitor: &Arc<ClipboardMonitor>,
+        destruct_timer: &Arc<Mutex<DestructTimer>>,
+    ) {
+        let paths = self.config.ipc.resolved_socket_path()
+            .map_err(|e| e.to_string())
+            .and_then(|socket| TokenStore::default_path()
+                .map(|tokens| (socket, tokens))
+                .map_err(|e| e.to_string()));
+        let (socket_path, token_path) = match paths {
+            Ok(paths) => paths,
+            Err(e) => {
+                warn!("无法确定控制接口路径: {}", e);
+                return;
+            }
+        };
+        
+        let Some(scheduler) = self.nuke_scheduler.clone() else {
+            return;
+        };
+        let server = Arc::new(ControlServer::new(
+            socket_path,
+            token_path,
+            clipboard_monitor.clone(),
+            destruct_timer.clone(),
+            scheduler,
+            self.config.nuke_guard.clone(),
+            self.config.api.security.clone(),
+            self.should_stop.clone(),
+        ));
+        
+        match server.bind() {
+            Ok(listener) => {
+                notice!("🔌 控制接口: {}", server.socket_path().display());
+                *self.ipc_task.lock().unwrap() = Some(tokio::spawn(server.serve(listener)));
+            }
+            Err(e) => warn!("启动控制接口失败: {}", e),
+        }
+    }
+    
+    /// 启动代理套接字
+    #[cfg(all(unix, feature = "api"))]
+    fn start_agent_server(&self, clipboard_monitor: &Arc<ClipboardMonitor>) {
+        let socket_path = match self.config.agent.resolved_socket_path() {
+            Ok(path) => path,
+            Err(e) => {
+                warn!("无法确定代理套接字路径: {}", e);
+                return;
+            }
+        };
+        if self.config.agent.clients.is_empty() {
+            warn!("代理套接字已启用但没有登记任何客户端，所有请求都将被拒绝");
+        }
+        
+        let server = Arc::new(AgentServer::new(socket_path, self.config.agent.clone(), clipboard_monitor.clone()));
+        match server.bind() {
+            Ok(listener) => {
+                notice!("🗝️  代理套接字: {}（{} 个已批准客户端）", server.socket_path().display(), self.config.agent.clients.len());
+                *self.agent_task.lock().unwrap() = Some(tokio::spawn(server.serve(listener)));
+            }
+            Err(e) => warn!("启动代理套接字失败: {}", e),
+        }
+    }
+    
+    /// 管理控制接口令牌
+    /// 
+    /// # 参数
+    /// * `action` - 令牌操作
+    /// 
+    /// # 返回值
+    /// * `Result<(), CliError>` - 操作结果
+    #[cfg(feature = "api")]
+    pub async fn manage_tokens(&self, action: TokenAction) -> Result<(), CliError> {
+        let path = TokenStore::default_path()
+            .map_err(|e| CliError::ConfigError(e.to_string()))?;
+        let mut store = TokenStore::load(&path)
+            .map_err(|e| CliError::ConfigError(e.to_string()))?;
+        
+        match action {
+            TokenAction::Create { name, capability } => {
+                let token = store.create(&name, capability)
+                    .map_err(|e| CliError::ConfigError(e.to_string()))?;
+                store.save(&path)
+                    .map_err(|e| CliError::ConfigError(e.to_string()))?;
+                audit::record("ipc_token", &format!("创建令牌 {} ({})", name, capability));
+                
+                notice!("🔑 已创建令牌 {} ({})，令牌仅显示一次，请妥善保存", name, capability);
+                println!("{}", token);
+            }
+            TokenAction::List => {
+                let mut empty = true;
+                for (name, capability) in store.entries() {
+                    notice!("🔑 {} ({})", name, capability);
+                    empty = false;
+                }
+                if empty {
+                    notice!("ℹ️  尚未创建任何令牌");
+                }
+            }
+            TokenAction::Revoke { name } => {
+                if !store.revoke(&name) {
+                    notice!("ℹ️  令牌不存在: {}", name);
+                    return Ok(());
+                }
+                store.save(&path)
+                    .map_err(|e| CliError::ConfigError(e.to_string()))?;
+                audit::record("ipc_token", &format!("吊销令牌 {}", name));
+                notice!("🗑️  已吊销令牌 {}", name);
+            }
+            TokenAction::Passphrase => {
+                notice!("🔐 请输入口令（从stdin读取一行）:");
+                let mut passphrase = Zeroizing::new(String::new());
+                std::io::stdin().read_line(&mut passphrase)
+                    .map_err(|e| CliError::ConfigError(e.to_string()))?;
+                let passphrase = passphrase.trim_end_matches(['\r', '\n']);
+                if passphrase.is_empty() {
+                    return Err(CliError::ConfigError("口令不能为空".to_string()));
+                }
+                audit::record("ipc_token", "生成破坏性命令口令哈希");
+                notice!("   将下面的值写入配置的 api.security.passphrase_hash");
+                println!("{}", ipc_security::hash_passphrase(passphrase));
+            }
+            TokenAction::Keygen { output } => {
+                if std::path::Path::new(&output).exists() {
+                    return Err(CliError::ConfigError(format!("私钥文件已存在: {}", output)));
+                }
+                let public_key = ipc_security::generate_signing_key(&output)
+                    .map_err(|e| CliError::ConfigError(e.to_string()))?;
+                audit::record("ipc_token", "生成请求签名密钥对");
+                notice!("🔑 已生成请求签名密钥对，私钥已写入 {}（仅供调用脚本读取）", output);
+                notice!("   将下面的公钥写入配置的 api.security.signing_public_key");
+                println!("{}", public_key);
+            }
+            TokenAction::Sign { command, key, argument } => {
+                let fields = ipc_security::sign_request(&key, &command, &argument, chrono::Utc::now().timestamp())
+                    .map_err(|e| CliError::ConfigError(format!("签名失败: {}", e)))?;
+                println!("{}", fields);
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// 按配置启用Windows延迟渲染
+    #[cfg(target_os = "windows")]
+    fn start_delayed_rendering(&self, clipboard_monitor: &Arc<ClipboardMonitor>) {
+        if !self.config.clipboard.delayed_rendering {
+            return;
+        }
+        
+        // 使用弱引用，渲染器由监听器持有
+        let weak_monitor = Arc::downgrade(clipboard_monitor);
+        let paste_policy = self.config.paste_context.clone();
+        let provider: RenderProvider = Arc::new(move || {
+            let monitor = weak_monitor.upgrade()?;
+            
+            // 按粘贴上下文拒绝渲染，请求方得到空数据
+            let context = PasteContext::current();
+            if let Some(reason) = paste_policy.blocked_reason(&context) {
+                warn!("粘贴上下文被策略阻止（{}），拒绝渲染", reason);
+                audit::record("paste_blocked", &context.to_string());
+                return None;
+            }
+            
+            match monitor.get_decrypted_content_for_paste() {
+                Ok(Some(content)) => {
+                    info!("✅ 应用请求了受保护内容，已渲染并重置密钥");
+                    let content = Zeroizing::new(content);
+                    let content = monitor.paste_transforms().apply_to_foreground(&content).unwrap_or(content);
+                    if let Err(e) = monitor.handle_paste(&content) {
+                        error!("处理粘贴操作失败: {}", e);
+                    }
+                    Some(content)
+                },
+                Ok(None) => None,
+                Err(e) => {
+                    error!("渲染时解密失败: {}", e);
+                    None
+                },
+            }
+        });
+        
+        match DelayedRenderer::start(provider) {
+            Ok(renderer) => {
+                clipboard_monitor.set_delayed_renderer(renderer);
+                notice!("🪄 延迟渲染已启用，受保护内容仅在粘贴请求时解密");
+            },
+            Err(e) => warn!("无法启用延迟渲染，受保护内容仍以密文写入剪贴板: {}", e),
+        }
+    }
+    
+    /// 按配置处理macOS通用剪贴板（Handoff）
+    fn apply_handoff_policy(&self) {
+        let Some(status) = handoff::status() else {
+            return;
+        };
+        
+        if !status.is_available() {
+            info!("Handoff未启用，受保护内容不会同步到其他设备");
+            return;
+        }
+        
+        match self.config.clipboard.handoff_policy {
+            HandoffPolicy::Ignore => {
+                warn!("Handoff已启用，受保护内容可能通过通用剪贴板同步到其他设备");
+            },
+            HandoffPolicy::Conceal => {
+                info!("Handoff已启用，受保护内容将以仅限本机方式写入");
+            },
+            HandoffPolicy::Disable => {
+                if !self.confirm("📱 是否在本次会话期间关闭Handoff（停止后自动恢复）？") {
+                    notice!("ℹ️  保留Handoff，受保护内容仍以仅限本机方式写入");
+                    return;
+                }
+                
+                match handoff::disable_for_session() {
+                    Ok(()) => {
+                        notice!("📱 本次会话已关闭Handoff");
+                        audit::record("handoff", "会话期间关闭Handoff");
+                    },
+                    Err(e) => warn!("关闭Handoff失败: {}", e),
+                }
+            },
+        }
+    }
+    
+    /// 解除当前条目的保护
+    /// 
+    /// # 返回值
+    /// * `Result<(), CliError>` - 操作结果
+    pub async fn unprotect_current(&self) -> Result<(), CliError> {
+        let monitor = self.clipboard_monitor.as_ref().ok_or(CliError::ServiceNotRunning)?;
+        
+        match monitor.unprotect_current().map_err(|e| CliError::ClipboardError(e.to_string()))? {
+            Some(item_id) => {
+                if let Some(timer) = &self.destruct_timer {
+                    timer.lock().unwrap().stop_countdown()
+                        .map_err(|e| CliError::TimerError(e.to_string()))?;
+                }
+                audit::record("unprotect", &format!("条目 {} 已解除保护", item_id));
+            }
+            None => notice!("ℹ️  当前没有受保护的条目"),
+        }
+        
+        Ok(())
+    }
+    
+    /// 延长受保护条目的存活时间
+    /// 
+    /// # 参数
+    /// * `item_id` - 要延长的条目ID（None表示当前条目）
+    /// * `seconds` - 延长的秒数
+    /// 
+    /// # 返回值
+    /// * `Result<(), CliError>` - 操作结果
+    pub async fn extend_item(&self, item_id: Option<ItemId>, requested: Duration) -> Result<(), CliError> {
+        let monitor = self.clipboard_monitor.as_ref().ok_or(CliError::ServiceNotRunning)?;
+        
+        let mut outcome = monitor.extend_item(item_id, requested, false);
+        if let ExtendOutcome::ConfirmationRequired { granted, .. } = outcome {
+            if !self.confirm(&format!("该条目已多次延长，确认再延长 {}?", duration::format(granted))) {
+                notice!("❌ 操作已取消");
+                return Err(CliError::OperationCancelled);
+            }
+            outcome = monitor.extend_item(item_id, requested, true);
+        }
+        
+        match outcome {
+            ExtendOutcome::Extended { granted, remaining, capped } => {
+                if let Some(timer) = &self.destruct_timer {
+                    timer.lock().unwrap().start_countdown(remaining)
+                        .map_err(|e| CliError::TimerError(e.to_string()))?;
+                }
+                if let Some(cap) = capped {
+                    notice!("⛔ 该类内容最长只能存活 {}，本次只延长 {}（请求 {}），剩余 {}", duration::format(cap), duration::format(granted), duration::format(requested), duration::format(remaining));
+                } else if granted < requested {
+                    notice!("⏳ 该条目已多次延长，本次只延长 {}（请求 {}），剩余 {}", duration::format(granted), duration::format(requested), duration::format(remaining));
+                } else {
+                    notice!("⏳ 已延长 {}，剩余 {}", duration::format(requested), duration::format(remaining));
+                }
+            }
+            ExtendOutcome::Destroyed => {
+                if let Some(timer) = &self.destruct_timer {
+                    timer.lock().unwrap().stop_countdown()
+                        .map_err(|e| CliError::TimerError(e.to_string()))?;
+                }
+            }
+            ExtendOutcome::ConfirmationRequired { .. } => return Err(CliError::OperationCancelled),
+            ExtendOutcome::NotFound => match item_id {
+                Some(item_id) => notice!("ℹ️  条目 {} 不存在或已被清除", item_id),
+                None => notice!("ℹ️  当前没有受保护的条目"),
+            },
+        }
+        
+        Ok(())
+    }
+    
+    /// 查看或重置已学习的偏好
+    /// 
+    /// # 参数
+    /// * `reset` - 是否清空已学习的偏好
+    /// 
+    /// # 返回值
+    /// * `Result<(), CliError>` - 操作结果
+    pub async fn manage_learned(&self, reset: bool) -> Result<(), CliError> {
+        if reset {
+            if !self.confirm("确认清空所有已学习的偏好?") {
+                notice!("❌ 操作已取消");
+                return Err(CliError::OperationCancelled);
+            }
+            
+            match &self.clipboard_monitor {
+                Some(monitor) => monitor.reset_learned_preferences(),
+                None => {
+                    if let Some(path) = PreferenceStore::default_path() {
+                        PreferenceStore::default().save(&path)
+                            .map_err(|e| CliError::ConfigError(e.to_string()))?;
+                    }
+                }
+            }
+            notice!("✅ 已清空已学习的偏好");
+            return Ok(());
+        }
+        
+        let preferences = match &self.clipboard_monitor {
+            Some(monitor) => monitor.learned_preferences(),
+            None => PreferenceStore::default_path()
+                .map(PreferenceStore::load)
+                .unwrap_or_default()
+                .preferences()
+                .to_vec(),
+        };
+        
+        if preferences.is_empty() {
+            notice!("ℹ️  尚未学习到任何偏好");
+            return Ok(());
+        }
+        
+        let threshold = self.config.learning.threshold;
+        notice!("🧠 已学习的偏好（生效阈值 {} 次）", threshold);
+        notice!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        for preference in &preferences {
+            let effect = match PreferenceStore::bias_of(preference, threshold) {
+                Bias::SkipProtection => "跳过保护".to_string(),
+                Bias::ExtendLifetime(extra) => format!("延长 {} 秒", extra.as_secs()),
+                Bias::None => "尚未生效".to_string(),
+            };
+            notice!(
+                "   {} | 解除保护 {} 次 | 延长 {} 次 | {}",
+                preference.shape, preference.unprotects, preference.extensions, effect
+            );
+        }
+        
+        Ok(())
+    }
+    
+    /// 查看规则试运行状态，或将规则转为强制执行；导入导出规则包
+    /// 
+    /// # 参数
+    /// * `promote` - 是否转为强制执行
+    /// * `rule` - 规则名称（省略时为最近一次只警告的规则，或唯一处于仅警告阶段的规则）
+    /// * `action` - 规则包操作
+    /// 
+    /// # 返回值
+    /// * `Result<(), CliError>` - 操作结果
+    pub async fn manage_rules(&self, promote: bool, rule: Option<String>, action: Option<RulesAction>) -> Result<(), CliError> {
+        if let Some(action) = action {
+            return self.manage_rule_pack(action);
+        }
+        
+        let trial_config = &self.config.rule_trial;
+        let store = TrialStore::default_path().and_then(TrialStore::load).unwrap_or_default();
+        let now = chrono::Utc::now();
+        
+        if promote {
+            let rule = rule
+                .or_else(|| self.clipboard_monitor.as_ref().and_then(|monitor| monitor.take_last_warned_rule()))
+                .or_else(|| {
+                    let mut warning = store.trials().iter().filter(|trial| trial.stage(trial_config, now) == RuleStage::Warn);
+                    match (warning.next(), warning.next()) {
+                        (Some(trial), None) => Some(trial.rule.clone()),
+                        _ => None,
+                    }
+                })
+                .ok_or_else(|| CliError::ConfigError("请指定要转为强制执行的规则（rules --promote <规则>）".to_string()))?;
+            
+            let found = rule_trial::promote(&rule).map_err(|e| CliError::ConfigError(e.to_string()))?;
+            if !found {
+                return Err(CliError::ConfigError(format!("规则 {} 不存在", rule)));
+            }
+            audit::record("rule_promoted", &format!("规则={} 来源=命令", rule));
+            notice!("🛡️  规则 {} 已转为强制执行", rule);
+            return Ok(());
+        }
+        
+        if store.trials().is_empty() {
+            notice!("ℹ️  尚未登记任何规则（启动监听服务后登记）");
+            return Ok(());
+        }
+        
+        notice!("🧪 规则试运行状态（新规则在 {} 天或 {} 次命中内仅警告）", trial_config.days, trial_config.matches);
+        notice!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        for trial in store.trials() {
+            match trial.stage(trial_config, now) {
+                RuleStage::Enforce => notice!("   {} | {}", trial.rule, RuleStage::Enforce),
+                RuleStage::Warn => {
+                    let ends_at = trial.started_at + chrono::Duration::days(trial_config.days as i64);
+                    notice!(
+                        "   {} | {} | 命中 {}/{} | 最迟 {} 转为强制执行",
+                        trial.rule,
+                        RuleStage::Warn,
+                        trial.matches,
+                        trial_config.matches,
+                        ends_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+                    );
+                }
+            }
+        }
+        
+        Ok(())
+    }
+
+    /// 导出、导入规则包或生成签名密钥
+    fn manage_rule_pack(&self, action: RulesAction) -> Result<(), CliError> {
+        let pack_error = |e: rule_pack::RulePackError| CliError::ConfigError(e.to_string());
+        
+        match action {
+            RulesAction::Export { file, name, sign_key } => {
+                let name = name.unwrap_or_else(|| {
+                    std::path::Path::new(&file).file_stem().map_or_else(|| file.clone(), |stem| stem.to_string_lossy().to_string())
+                });
+                let mut pack = RulePack::from_config(&self.config, &name).map_err(pack_error)?;
+                if let Some(sign_key) = &sign_key {
+                    pack.sign(&rule_pack::load_signing_key(sign_key).map_err(pack_error)?);
+                }
+                pack.save(&file).map_err(pack_error)?;
+                
+                notice!("📦 已导出规则包 {}（{} 条敏感模式{}）到 {}", name, pack.rules.len(),
+                    if pack.entropy_rule.is_some() { "，含熵值规则" } else { "" }, file);
+                match &pack.signature {
+                    Some(signature) => notice!("   签名公钥: {}", signature.public_key),
+                    None => notice!("⚠️  规则包未签名，导入时需要 --allow-unsigned"),
+                }
+            }
+            RulesAction::Import { source, allow_unsigned, trust, replace } => {
+                let trust = trust
+                    .map(|key| escrow::parse_key(&key).map_err(|e| CliError::ConfigError(e.to_string())))
+                    .transpose()?;
+                let pack = RulePack::load(&source).map_err(pack_error)?;
+                
+                // 修改配置文件本身，而不是合并了受管策略与覆盖项的运行配置
+                let path = Config::get_config_file_path().map_err(|e| CliError::ConfigError(e.to_string()))?;
+                let mut config = if path.exists() {
+                    Config::load_from_file(&path).map_err(|e| CliError::ConfigError(e.to_string()))?
+                } else {
+                    Config::default()
+                };
+                let signer = pack.verify(&config.rule_packs, trust.as_ref(), allow_unsigned).map_err(pack_error)?;
+                
+                notice!("📦 规则包 {}{}", pack.name, if pack.description.is_empty() { String::new() } else { format!("：{}", pack.description) });
+                match &signer {
+                    Some(signer) => notice!("   签名有效，签名公钥 {}", signer),
+                    None => notice!("⚠️  规则包未签名，来源无法验证"),
+                }
+                if !self.confirm("确认合并进配置?") {
+                    notice!("❌ 操作已取消");
+                    return Err(CliError::OperationCancelled);
+                }
+                
+                let summary = pack.merge_into(&mut config, replace);
+                if let Some(signer) = &signer {
+                    if !config.rule_packs.trusted_keys.contains(signer) {
+                        config.rule_packs.trusted_keys.push(signer.clone());
+                    }
+                }
+                config.validate().map_err(|e| CliError::ConfigError(e.to_string()))?;
+                config.save_to_file(&path).map_err(|e| CliError::ConfigError(e.to_string()))?;
+                
+                audit::record("rule_pack_imported", &format!(
+                    "规则包={} 签名={} 新增={} 跳过={}",
+                    pack.name,
+                    signer.as_deref().unwrap_or("未签名"),
+                    summary.added.len(),
+                    summary.skipped.len()
+                ));
+                notice!("✅ 已合并 {} 条敏感模式{}", summary.added.len(),
+                    if summary.skipped.is_empty() { String::new() } else { format!("（{} 条已存在，跳过）", summary.skipped.len()) });
+                if summary.entropy_enabled {
+                    notice!("   已启用规则包中的熵值规则");
+                } else if summary.allow_patterns_added > 0 {
+                    notice!("   熵值规则新增 {} 条白名单", summary.allow_patterns_added);
+                }
+                if !replace && config.sensitive_pattern.starts_with("(?:.*)|") {
+                    notice!("⚠️  原有的 sensitive_pattern 匹配所有内容，导入的规则不会缩小保护范围（可使用 --replace 替换）");
+                }
+                notice!("   新规则先进入试运行阶段；重新加载配置（SIGHUP）或重启服务后生效");
+            }
+            RulesAction::Keygen { output } => {
+                if std::path::Path::new(&output).exists() {
+                    return Err(CliError::ConfigError(format!("私钥文件已存在: {}", output)));
+                }
+                let public_key = rule_pack::generate_signing_key(&output).map_err(pack_error)?;
+                
+                notice!("🔑 已生成规则包签名密钥对，私钥已写入 {}（请妥善保管）", output);
+                notice!("   成员将下面的公钥加入 rule_packs.trusted_keys，或导入时使用 --trust 指定");
+                println!("{}", public_key);
+            }
+        }
+        
+        Ok(())
+    }
+
+    /// 按剪贴板保护使用的同一套规则分类内容（供git钩子、CI扫描等外部工具使用）
+    ///
+    /// # 参数
+    /// * `text` - 要分类的内容，未指定时从stdin读取
+    /// * `json` - 是否以JSON输出
+    ///
+    /// # 返回值
+    /// * `Result<bool, CliError>` - 内容复制到剪贴板时是否会受保护
+    pub async fn classify_content(&self, text: Option<String>, json: bool) -> Result<bool, CliError> {
+        let content = Self::text_or_stdin(text)?;
+        let result = classifier::classify(&self.config, &content);
+
+        if json || self.output_format == OutputFormat::Json {
+            output::emit_json(&serde_json::to_value(&result).unwrap_or_default());
+        } else if let (Some(rule), Some(detector)) = (&result.rule, &result.detector) {
+            let (icon, stage) = if result.protected { ("🔒", RuleStage::Enforce) } else { ("⚠️ ", RuleStage::Warn) };
+            let critical = if result.critical { "，粘贴前需确认" } else { "" };
+            notice!("{} 命中规则 {}/{}（{}）{}", icon, rule, detector, stage, critical);
+        } else {
+            notice!("✅ 未命中任何规则");
+        }
+
+        Ok(result.protected)
+    }
+
+    /// 撤销最近一次倒计时清除
+    /// 
+    /// # 返回值
+    /// * `Result<(), CliError>` - 操作结果
+    pub async fn undo_last_clear(&self) -> Result<(), CliError> {
+        let monitor = self.clipboard_monitor.as_ref().ok_or(CliError::ServiceNotRunning)?;
+        
+        let restored = monitor.undo_last_clear()
+            .map_err(|e| CliError::ClipboardError(e.to_string()))?;
+        
+        if restored {
+            notice!("↩️  已撤销最近一次清除，内容已恢复并重新受保护");
+        } else if self.config.timer.undo_grace_seconds == 0 {
+            notice!("ℹ️  撤销功能未启用（配置 timer.undo_grace_seconds）");
+        } else {
+            notice!("ℹ️  没有可撤销的清除（宽限期已过或已撤销）");
+        }
+        
+        Ok(())
+    }
+    
+    /// 将内容写入剪贴板（未指定时从stdin读取，去掉末尾的一个换行）
+    /// 
+    /// 服务运行中时写入监听器使用的剪贴板，下一次轮询即按普通复制处理（敏感内容会受保护）
+    /// 
+    /// # 参数
+    /// * `text` - 要写入的内容
+    /// 
+    /// # 返回值
+    /// * `Result<(), CliError>` - 操作结果
+    pub async fn put_content(&self, text: Option<String>) -> Result<(), CliError> {
+        let content = Self::text_or_stdin(text)?;
+        
+        let result = match &self.clipboard_monitor {
+            Some(monitor) => {
+                let text = content.to_string();
+                monitor.clipboard_actor()
+                    .run(Priority::Restore, move |ctx| ctx.set_contents(text).map_err(|e| e.to_string()))
+                    .map_err(|e| e.to_string())
+                    .and_then(|result| result)
+            }
+            None => ClipboardBackend::open(self.config.clipboard.access)
+                .and_then(|mut ctx| ctx.set_contents(content.to_string()))
+                .map_err(|e| e.to_string()),
+        };
+        result.map_err(CliError::ClipboardError)?;
+        
+        notice!("📋 已写入剪贴板（{} 字节）", content.len());
+        Ok(())
+    }
+    
+    /// 编辑器插件的参考实现：从stdin读取机密文件中选中的文本，先通过控制接口声明其SHA-256，
+    /// 再写入剪贴板，随后把服务推送的编辑器事件逐行输出到stdout，直到条目销毁、被取代或标记过期
+    /// 
+    /// # 参数
+    /// * `source` - 来源文件（只记录文件名）
+    /// * `token_file` - 保存control令牌的文件
+    /// 
+    /// # 返回值
+    /// * `Result<(), CliError>` - 操作结果
+    #[cfg(all(unix, feature = "api"))]
+    pub async fn editor_copy(&self, source: Option<String>, token_file: std::path::PathBuf) -> Result<(), CliError> {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+        
+        let content = Self::text_or_stdin(None)?;
+        let token = Zeroizing::new(
+            std::fs::read_to_string(&token_file)
+                .map_err(|e| CliError::ConfigError(format!("无法读取令牌文件 {}: {}", token_file.display(), e)))?
+                .trim()
+                .to_string(),
+        );
+        let socket_path = self.config.ipc.resolved_socket_path()
+            .map_err(|e| CliError::ConfigError(e.to_string()))?;
+        let stream = tokio::net::UnixStream::connect(&socket_path).await
+            .map_err(|_| CliError::ServiceNotRunning)?;
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let ipc_error = |e: std::io::Error| CliError::ServiceError(format!("控制接口通信失败: {}", e));
+        
+        let request = serde_json::json!({
+            "token": token.as_str(),
+            "command": "mark_secret",
+            "sha256": editor::sha256_hex(&content),
+            "source": source,
+        });
+        writer.write_all(format!("{}\n", request).as_bytes()).await.map_err(ipc_error)?;
+        let response = lines.next_line().await.map_err(ipc_error)?
+            .ok_or_else(|| CliError::ServiceError("控制接口未响应".to_string()))?;
+        let response: serde_json::Value = serde_json::from_str(&response)
+            .map_err(|e| CliError::ServiceError(format!("无效响应: {}", e)))?;
+        if response["ok"] != true {
+            return Err(CliError::ServiceError(response["error"].as_str().unwrap_or("未知错误").to_string()));
+        }
+        
+        // 标记已生效后才写入剪贴板，服务下一次轮询即按关键规则保护
+        ClipboardBackend::open(self.config.clipboard.access)
+            .and_then(|mut ctx| ctx.set_contents(content.to_string()))
+            .map_err(|e| CliError::ClipboardError(e.to_string()))?;
+        notice!("🔒 已声明来自机密文件并写入剪贴板（{} 字节）", content.len());
+        
+        while let Some(line) = lines.next_line().await.map_err(ipc_error)? {
+            println!("{}", line);
+            let event: serde_json::Value = serde_json::from_str(&line).unwrap_or_default();
+            if matches!(event["event"].as_str(), Some("destroyed" | "superseded" | "expired")) {
+                break;
+            }
+        }
+        Ok(())
+    }
+    
+    /// 取命令行给出的文本，未指定时从stdin读取（去掉末尾的一个换行）
+    fn text_or_stdin(text: Option<String>) -> Result<Zeroizing<String>, CliError> {
+        let Some(text) = text else {
+            let mut input = Zeroizing::new(String::new());
+            std::io::Read::read_to_string(&mut std::io::stdin(), &mut input)
+                .map_err(|e| CliError::ClipboardError(e.to_string()))?;
+            let trimmed = input.strip_suffix('\n').map(|rest| rest.strip_suffix('\r').unwrap_or(rest));
+            return Ok(match trimmed {
+                Some(rest) => Zeroizing::new(rest.to_string()),
+                None => input,
+            });
+        };
+        Ok(Zeroizing::new(text))
+    }
+    
+    /// 管理阅后即焚笔记
+    /// 
+    /// 读取时先从笔记库中删除笔记，再将内容放入受保护剪贴板：服务运行中时按受保护条目处理，
+    /// 否则直接写入剪贴板并在 `clear_delay_seconds` 后清除
+    /// 
+    /// # 参数
+    /// * `action` - 笔记操作
+    /// 
+    /// # 返回值
+    /// * `Result<(), CliError>` - 操作结果
+    pub async fn manage_notes(&self, action: NoteAction) -> Result<(), CliError> {
+        let path = NoteStore::default_path()
+            .ok_or_else(|| CliError::ConfigError("无法确定配置目录".to_string()))?;
+        let now = chrono::Utc::now();
+        let mut store = NoteStore::load(&path, now)
+            .map_err(|e| CliError::ConfigError(e.to_string()))?;
+        
+        match action {
+            NoteAction::Create { text, ttl } => {
+                let content = Self::text_or_stdin(text)?;
+                let (handle, expires_at) = store.create(&content, ttl, now)
+                    .map_err(|e| CliError::ConfigError(e.to_string()))?;
+                store.save(&path)
+                    .map_err(|e| CliError::ConfigError(e.to_string()))?;
+                audit::record("note_create", &format!("条目 {}，{} 后过期", handle.id(), Self::format_duration(ttl)));
+                
+                notice!("📝 已创建阅后即焚笔记，{} 后过期", Self::format_duration(ttl));
+                notice!("   读取凭据只显示这一次，丢失后笔记无法读取: clipvanish note read <凭据>");
+                if self.output_format == OutputFormat::Json {
+                    output::emit_json(&serde_json::json!({
+                        "id": handle.id(),
+                        "handle": handle.to_string(),
+                        "expires_at": expires_at.to_rfc3339(),
+                    }));
+                } else {
+                    println!("{}", handle);
+                }
+            }
+            NoteAction::Read { handle } => {
+                // 保护暂停时内容会以明文留在剪贴板，先拒绝读取而不是销毁笔记
+                if self.clipboard_monitor.as_ref().is_some_and(|monitor| monitor.is_paused()) {
+                    return Err(CliError::ServiceError("保护已暂停，请恢复后再读取笔记".to_string()));
+                }
+                
+                let content = store.take(&handle)
+                    .map_err(|e| CliError::ConfigError(e.to_string()))?;
+                store.save(&path)
+                    .map_err(|e| CliError::ConfigError(e.to_string()))?;
+                audit::record("note_read", &format!("条目 {}", handle.id()));
+                
+                match &self.clipboard_monitor {
+                    Some(monitor) => {
+                        monitor.protect_content(&content)
+                            .map_err(|e| CliError::ClipboardError(e.to_string()))?;
+                        notice!("🔥 笔记已销毁，内容已放入受保护剪贴板");
+                    }
+                    None => {
+                        let mut ctx = ClipboardBackend::open(self.config.clipboard.access)
+                            .map_err(|e| CliError::ClipboardError(e.to_string()))?;
+                        ctx.set_contents(content.to_string())
+                            .map_err(|e| CliError::ClipboardError(e.to_string()))?;
+                        
+                        let delay = Duration::from_secs(self.config.clear_delay_seconds);
+                        notice!("🔥 笔记已销毁，内容已放入剪贴板，{} 后清除", Self::format_duration(delay));
+                        sleep(delay).await;
+                        
+                        // 期间被其他内容替换时不清除
+                        let unchanged = ctx.get_contents().is_ok_and(|current| Zeroizing::new(current).as_str() == content.as_str());
+                        if unchanged {
+                            ctx.set_contents(String::new())
+                                .map_err(|e| CliError::ClipboardError(e.to_string()))?;
+                            notice!("🧹 剪贴板已清除");
+                        }
+                    }
+                }
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// 将当前剪贴板内容原样写到stdout
+    /// 
+    /// 服务运行中且剪贴板中是受保护内容时，按一次粘贴处理：解密输出后立即重置密钥，
+    /// 明文不会写回剪贴板
+    /// 
+    /// # 返回值
+    /// * `Result<(), CliError>` - 操作结果
+    pub async fn get_content(&self) -> Result<(), CliError> {
+        let content = match &self.clipboard_monitor {
+            Some(monitor) => {
+                // 延迟渲染承诺尚未兑现时不读取剪贴板，读取会触发渲染
+                let pending = monitor.delayed_promise_pending();
+                let current = if pending {
+                    None
+                } else {
+                    monitor.read_clipboard_content()
+                        .map_err(|e| CliError::ClipboardError(e.to_string()))?
+                };
+                match current {
+                    Some(current) if !monitor.is_our_encrypted_content(&current) => Some(current),
+                    None if !pending => None,
+                    _ => {
+                        audit::record("get", "受保护内容已输出到stdout");
+                        monitor.get_decrypted_content_for_paste()
+                            .map_err(|e| CliError::ClipboardError(e.to_string()))?
+                    },
+                }
+            },
+            None => {
+                let mut ctx: ClipboardBackend = ClipboardProvider::new()
+                    .map_err(|e| CliError::ClipboardError(e.to_string()))?;
+                ctx.get_contents().ok().filter(|content| !content.is_empty())
+            },
+        };
+        
+        match content.map(Zeroizing::new) {
+            Some(content) => output::emit_raw(&content)
+                .map_err(|e| CliError::ClipboardError(e.to_string())),
+            None => {
+                notice!("📋 剪贴板为空");
+                Ok(())
+            },
+        }
+    }
+    
+    /// 检查运行中服务的健康状态
+    ///
+    /// 服务运行在其他进程中，从健康状态文件读取最近一次检查结果；文件缺失或过期视为不健康
+    /// 
+    /// # 返回值
+    /// * `Result<bool, CliError>` - 服务是否健康
+    pub fn show_health(&self) -> Result<bool, CliError> {
+        let Some(mut report) = health::read_report() else {
+            if self.output_format == OutputFormat::Json {
+                output::emit_json(&serde_json::json!({ "running": false, "healthy": false }));
+            } else {
+                notice!("🔴 服务未运行或未启用健康检查");
+            }
+            return Ok(false);
+        };
+        
+        let age = (chrono::Utc::now() - report.checked_at).to_std().unwrap_or_default();
+        if age > self.config.health.report_ttl() {
+            report.checks.push(health::HealthCheck {
+                name: "report".to_string(),
+                healthy: false,
+                detail: format!("健康状态已 {} 未更新", Self::format_duration(age)),
+            });
+        }
+        let healthy = report.healthy();
+        
+        if self.output_format == OutputFormat::Json {
+            output::emit_json(&serde_json::json!({
+                "running": true,
+                "healthy": healthy,
+                "pid": report.pid,
+                "checked_at": report.checked_at,
+                "checks": report.checks,
+            }));
+            return Ok(healthy);
+        }
+        
+        notice!("{} 服务 (PID {}) {}", if healthy { "🟢" } else { "🔴" }, report.pid, if healthy { "健康" } else { "不健康" });
+        for check in &report.checks {
+            notice!("   {} {}: {}", if check.healthy { "✅" } else { "❌" }, check.name, check.detail);
+        }
+        Ok(healthy)
+    }
+    
+    /// 显示服务状态
+    /// 
+    /// # 参数
+    /// * `tmux` - 只输出供tmux状态栏使用的紧凑倒计时
+    /// 
+    /// # 返回值
+    /// * `Result<(), CliError>` - 操作结果
+    pub async fn show_status(&self, tmux: bool) -> Result<(), CliError> {
+        // 服务运行在其他进程中，从状态文件读取截止时间；没有倒计时时输出为空
+        if tmux {
+            return output::emit_raw(&countdown_display::tmux_status(chrono::Utc::now()))
+                .map_err(|e| CliError::ServiceError(e.to_string()));
+        }
+        
+        let status = self.service_status.lock().unwrap().clone();
+        
+        if self.output_format == OutputFormat::Json {
+            let monitor = self.clipboard_monitor.as_ref();
+            output::emit_json(&serde_json::json!({
+                "running": status.is_running,
+                "uptime_seconds": status.start_time.map(|t| t.elapsed().as_secs()),
+                "total_events": status.total_events,
+                "encrypted_content_length": status.encrypted_content_length,
+                "remaining_seconds": status.remaining_time.map(|d| d.as_secs()),
+                "paused": monitor.is_some_and(|m| m.is_paused()),
+                "current_item": monitor.and_then(|m| m.current_item_id()),
+                "undo_remaining_seconds": monitor.and_then(|m| m.undo_remaining()).map(|d| d.as_secs()),
+                "background_tasks": monitor.map(|m| m.active_task_count()),
+                "history": monitor.map(|m| m.history_stats()),
+                "monitor_health": monitor.map(|m| m.get_state().health),
+                "features": BuildInfo::current().features,
+                "headless": virtual_clipboard::is_headless(),
+                "no_subprocess": helpers::is_strict(),
+                "capabilities": self.capability_statuses(),
+                "escrow": self.config.escrow.recipient().ok().flatten().map(|r| r.fingerprint()),
+                "managed_policy": self.config.managed_policy.as_ref().map(|policy| &policy.source),
+                "scheduled_nuke": self.nuke_scheduler.as_ref().and_then(|scheduler| scheduler.pending()),
+                "hygiene": advisor::current(&self.config),
+            }));
+            return Ok(());
+        }
+        
+        notice!("📊 ClipVanish™ 服务状态");
+        notice!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        
+        // 托管模式下管理员可恢复受保护内容，放在最前面提示
+        if let Ok(Some(recipient)) = self.config.escrow.recipient() {
+            notice!("🔑 密钥托管: 已启用（恢复公钥 {}）", recipient.fingerprint());
+            notice!("   持有恢复私钥的管理员可以解密导出的受保护内容");
+            notice!();
+        }
+        
+        if status.is_running {
+            let health = self.clipboard_monitor.as_ref().map_or(MonitorHealth::Healthy, |m| m.get_state().health);
+            match &health {
+                MonitorHealth::Healthy => notice!("🟢 状态: 运行中"),
+                MonitorHealth::Recovering { .. } => notice!("🟡 状态: 运行中，监听循环{}", health),
+                MonitorHealth::Degraded { .. } => notice!("🟠 状态: 监听循环{}，剪贴板可能未受保护", health),
+            }
+            
+            if let Some(start_time) = status.start_time {
+                let uptime = start_time.elapsed();
+                notice!("⏱️  运行时间: {}", Self::format_duration(uptime));
+            }
+            
+            notice!("📈 处理事件: {} 次", status.total_events);
+            
+            if let Some(monitor) = &self.clipboard_monitor {
+                if monitor.is_paused() {
+                    notice!("⏸️  保护: 已暂停");
+                }
+                if let Some(item_id) = monitor.current_item_id() {
+                    notice!("🔖 当前条目: {}", item_id);
+                }
+                if let Some(remaining) = monitor.undo_remaining() {
+                    notice!("↩️  可撤销: 剩余 {}", Self::format_duration(remaining));
+                }
+                notice!("🧵 后台任务: {} 个", monitor.active_task_count());
+                let history = monitor.history_stats();
+                notice!(
+                    "🗂️  历史缓冲区: {}/{} 条，{} 字节{}",
+                    history.occupancy,
+                    history.capacity,
+                    history.reserved_bytes,
+                    if history.locked { "（已锁定内存）" } else { "" }
+                );
+            }
+            
+            if let Some(scheduled) = self.nuke_scheduler.as_ref().and_then(|scheduler| scheduler.pending()) {
+                notice!(
+                    "⏳ 定时销毁: {}（剩余 {}，nuke --cancel 取消）",
+                    scheduled.at.format("%Y-%m-%d %H:%M:%S"),
+                    Self::format_duration(scheduled.remaining())
+                );
+            }
+            
+            if status.encrypted_content_length > 0 {
+                notice!("🔒 加密内容: {} 字节", status.encrypted_content_length);
+            } else {
+                notice!("📋 剪贴板: 空");
+            }
+            
+            match status.timer_state {
+                TimerState::Idle => notice!("⏰ 定时器: 待机"),
+                TimerState::Running { .. } => {
+                    if let Some(remaining) = status.remaining_time {
+                        notice!("⏰ 倒计时: {}", Self::format_duration(remaining));
+                        
+                        // 显示进度条
+                        if self.config.ui.show_progress {
+                            let total_duration = self.config.get_default_countdown_duration();
+                            let progress = 1.0 - (remaining.as_secs_f64() / total_duration.as_secs_f64());
+                            self.display_progress_bar(progress);
+                        }
+                    }
+                },
+                TimerState::Completed => notice!("⏰ 定时器: 已完成"),
+                TimerState::Cancelled => notice!("⏰ 定时器: 已取消"),
+                TimerState::Error(ref msg) => notice!("⏰ 定时器: 错误 - {}", msg),
+            }
+        } else {
+            notice!("🔴 状态: 未运行");
+        }
+        
+        notice!();
+        notice!("🔧 配置信息:");
+        notice!("   默认倒计时: {}秒", self.config.timer.default_countdown);
+        if let Some(policy) = &self.config.managed_policy {
+            notice!("   受管策略: {}（{} 项强制，{} 项与用户配置冲突）", policy.source, policy.enforced.len(), policy.conflicts.len());
+        }
+        notice!("   内存锁定: {}", if self.config.security.enable_memory_locking { "启用" } else { "禁用" });
+        notice!("   全局热键: {}", if self.config.hotkeys.enable_global_hotkeys { "启用" } else { "禁用" });
+        
+        notice!();
+        match advisor::current(&self.config) {
+            Some(report) => {
+                notice!("🧼 剪贴板卫生评分: {}", report);
+                for recommendation in &report.recommendations {
+                    notice!("   💡 {}", recommendation);
+                }
+            }
+            None => notice!("🧼 剪贴板卫生评分: 数据不足（最近7天受保护条目少于 {} 个）", advisor::MIN_ITEMS),
+        }
+        
+        notice!();
+        notice!("🧭 功能状态:");
+        for capability in self.capability_statuses() {
+            notice!("   {}", capability);
+        }
+        
+        let build = BuildInfo::current();
+        notice!(
+            "🧩 已编译特性: {}",
+            if build.features.is_empty() { "无（最小构建）".to_string() } else { build.features.join(", ") }
+        );
+        
+        #[cfg(unix)]
+        {
+            notice!();
+            notice!("📡 信号控制 (PID {}):", std::process::id());
+            notice!("   SIGUSR1 → 紧急销毁");
+            notice!("   SIGUSR2 → 暂停/恢复保护");
+            notice!("   SIGHUP  → 重新加载配置");
+        }
+        
+        Ok(())
+    }
+    
+    /// 持续显示单行实时状态（状态 | 条目存活 | 剩余 | 今日事件），直到按下Ctrl+C
+    /// 
+    /// 终端中原地刷新；`plain` 或 `--output json` 时每次变化输出一行
+    /// 
+    /// # 参数
+    /// * `plain` - 输出纯ASCII文本
+    /// * `interval` - 刷新间隔
+    /// 
+    /// # 返回值
+    /// * `Result<(), CliError>` - 操作结果
+    pub async fn watch(&self, plain: bool, interval: Duration) -> Result<(), CliError> {
+        use std::io::IsTerminal;
+        
+        let log = audit::AuditLog::open_default()
+            .map_err(|e| CliError::ConfigError(e.to_string()))?;
+        let mut counter = EventCounter::new(log.path().to_path_buf());
+        let json = self.output_format == OutputFormat::Json;
+        let live = !plain && !json && std::io::stdout().is_terminal();
+        if !self.config.ui.countdown_status {
+            notice!("ℹ️  未启用 ui.countdown_status，无法显示剩余时间与条目存活时间");
+        }
+        
+        let mut ticker = tokio::time::interval(interval.max(Duration::from_millis(100)));
+        let mut last = None;
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = signal::ctrl_c() => break,
+            }
+            let events_today = counter.refresh(chrono::Local::now().date_naive()).unwrap_or_else(|e| {
+                debug!("读取审计日志失败: {}", e);
+                0
+            });
+            let line = WatchLine::new(
+                health::read_report().as_ref(),
+                self.config.health.report_ttl(),
+                countdown_display::read().as_ref(),
+                events_today,
+                chrono::Utc::now(),
+            );
+            let rendered = if json {
+                serde_json::to_string(&line).unwrap_or_default()
+            } else if plain {
+                line.plain()
+            } else {
+                line.text()
+            };
+            let result = if live {
+                output::emit_raw(&format!("\r\x1b[2K{}", rendered))
+            } else if last.as_ref() != Some(&line) {
+                output::emit_raw(&format!("{}\n", rendered))
+            } else {
+                Ok(())
+            };
+            // 下游管道关闭时结束
+            if result.is_err() {
+                return Ok(());
+            }
+            last = Some(line);
+        }
+        if live {
+            let _ = output::emit_raw("\n");
+        }
+        Ok(())
+    }
+    
+    /// 功能状态：服务运行中时为启动检测与运行时记录的结果，否则现场检测
+    fn capability_statuses(&self) -> Vec<CapabilityStatus> {
+        let recorded = capabilities::snapshot();
+        if self.clipboard_monitor.is_some() && !recorded.is_empty() {
+            recorded
+        } else {
+            capabilities::probe(&self.config)
+        }
+    }
+    
+    /// 停止服务
+    /// 
+    /// # 返回值
+    /// * `Result<(), CliError>` - 操作结果
+    pub async fn stop_service(&self) -> Result<(), CliError> {
+        let is_running = self.service_status.lock().unwrap().is_running;
+        
+        if !is_running {
+            notice!("ℹ️  ClipVanish服务未运行");
+            return Ok(());
+        }
+        
+        notice!("🛑 正在停止ClipVanish服务...");
+        Self::run_shutdown(Trigger::Stop).await;
+        *self.should_stop.lock().unwrap() = true;
+        
+        notice!("✅ ClipVanish服务已停止");
+        self.finish_session();
+        Ok(())
+    }
+    
+    /// 管理配置
+    /// 
+    /// # 参数
+    /// * `reset` - 是否重置为默认配置
+    /// 
+    /// # 返回值
+    /// * `Result<(), CliError>` - 操作结果
+    pub async fn manage_config(&mut self, reset: bool, action: Option<ConfigAction>) -> Result<(), CliError> {
+        if let Some(action) = action {
+            return self.manage_config_backups(action);
+        }
+        
+        if reset {
+            notice!("⚠️  重置配置");
+            notice!("   当前配置文件将被默认配置覆盖");
+            
+            if !self.confirm("确认重置?") {
+                notice!("❌ 操作已取消");
+                return Err(CliError::OperationCancelled);
+            }
+            
+            self.config.reset_to_default()
+                .map_err(|e| CliError::ConfigError(e.to_string()))?;
+            notice!("✅ 配置已重置为默认值");
+        } else if self.output_format == OutputFormat::Json {
+            let mut value = serde_json::to_value(&self.config)
+                .map_err(|e| CliError::ConfigError(e.to_string()))?;
+            if let (Some(object), Some(policy)) = (value.as_object_mut(), &self.config.managed_policy) {
+                object.insert("managed_policy".to_string(), serde_json::json!(policy));
+            }
+            output::emit_json(&value);
+        } else {
+            self.config.display();
+        }
+        
+        Ok(())
+    }
+    
+    /// 列出或恢复配置备份
+    fn manage_config_backups(&mut self, action: ConfigAction) -> Result<(), CliError> {
+        let backups = Config::list_backups()
+            .map_err(|e| CliError::ConfigError(e.to_string()))?;
+        
+        match action {
+            ConfigAction::Backups => {
+                if self.output_format == OutputFormat::Json {
+                    let paths: Vec<String> = backups.iter().map(|path| path.display().to_string()).collect();
+                    output::emit_json(&serde_json::json!(paths));
+                } else if backups.is_empty() {
+                    notice!("🗂️  暂无配置备份");
+                } else {
+                    notice!("🗂️  配置备份（最新的在前）");
+                    for (index, path) in backups.iter().enumerate() {
+                        notice!("   {}. {}", index + 1, path.display());
+                    }
+                }
+            }
+            ConfigAction::Restore { backup } => {
+                let Some(path) = backup.checked_sub(1).and_then(|i| backups.get(i)) else {
+                    return Err(CliError::ConfigError(format!("备份 {} 不存在（共 {} 份）", backup, backups.len())));
+                };
+                notice!("⚠️  将配置回滚到备份 {}: {}", backup, path.display());
+                notice!("   当前配置会先被备份，可再次回滚");
+                
+                if !self.confirm("确认回滚?") {
+                    notice!("❌ 操作已取消");
+                    return Err(CliError::OperationCancelled);
+                }
+                
+                self.config.restore_backup(backup)
+                    .map_err(|e| CliError::ConfigError(e.to_string()))?;
+                notice!("✅ 配置已回滚，重新启动服务后生效");
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// 显示历史记录
+    pub async fn show_history(&self) -> Result<(), CliError> {
+        if let Some(monitor) = &self.clipboard_monitor {
+            let history = monitor.get_history();
+            
+            // 机器输出只包含元数据，不包含内容
+            if self.output_format == OutputFormat::Json {
+                let items: Vec<serde_json::Value> = history
+                    .iter()
+                    .map(|item| serde_json::json!({
+                        "age_seconds": item.timestamp.elapsed().as_secs(),
+                        "length": item.length,
+                        "operation": match &item.operation {
+                            ClipboardOperation::Copy => "copy".to_string(),
+                            ClipboardOperation::Paste => "paste".to_string(),
+                            ClipboardOperation::Clear(reason) => format!("clear:{:?}", reason),
+                        },
+                    }))
+                    .collect();
+                output::emit_json(&serde_json::Value::Array(items));
+                return Ok(());
+            }
+            
+            if history.is_empty() {
+                notice!("📋 暂无剪贴板历史记录");
+                return Ok(());
+            }
+
+            notice!("📋 剪贴板历史记录");
+            notice!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+            let stats = monitor.history_stats();
+            notice!("缓冲区: {}/{} 条{}", stats.occupancy, stats.capacity, if stats.locked { "（已锁定内存）" } else { "" });
+            notice!();
+            
+            for (index, item) in history.iter().enumerate() {
+                let elapsed = item.timestamp.elapsed();
+                notice!("#{} - {} 前", 
+                    history.len() - index,
+                    Self::format_duration(elapsed)
+                );
+                
+                match &item.operation {
+                    ClipboardOperation::Copy => {
+                        notice!("   📥 复制: {} 字节{}", item.length, if item.sealed { "（内容已加密保存）" } else { "" });
+                        if let Some(item_id) = item.item_id {
+                            notice!("      条目: {}", item_id);
+                        }
+                    },
+                    ClipboardOperation::Paste => {
+                        notice!("   📤 粘贴操作");
+                    },
+                    ClipboardOperation::Clear(ref reason) => {
+                        let reason_str = match reason {
+                            ClearReason::TimerExpired => "倒计时到期",
+                            ClearReason::ManualClear => "手动清除",
+                            ClearReason::EmergencyNuke => "紧急销毁",
+                            ClearReason::Shutdown => "程序退出",
+                            ClearReason::Unprotected => "解除保护",
+                            ClearReason::Escalated => "延长过多强制销毁",
+                        };
+                        notice!("   🧹 清除: {}", reason_str);
+                    },
+                }
+                notice!();
+            }
+        } else {
+            notice!("⚠️ 服务未运行，无法获取历史记录");
+        }
+        
+        Ok(())
+    }
+    
+    /// 运行环境诊断
+    pub async fn run_doctor(&self) -> Result<(), CliError> {
+        notice!("🩺 ClipVanish™ 环境诊断");
+        notice!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+
+        for check in doctor::run_checks(&self.config) {
+            notice!("{}", check);
+        }
+
+        Ok(())
+    }
+
+    /// 生成问题报告归档
+    ///
+    /// 逐个章节征求用户同意后收集脱敏诊断信息
+    ///
+    /// # 参数
+    /// * `output` - 输出文件路径，未指定时在当前目录生成
+    ///
+    /// # 返回值
+    /// * `Result<(), CliError>` - 操作结果
+    pub async fn generate_report(&self, output: Option<String>) -> Result<(), CliError> {
+        notice!("📝 生成问题报告");
+        notice!("   以下每个章节都需要您的确认才会被包含");
+        notice!();
+
+        let mut sections = Vec::new();
+        for section in ReportSection::ALL {
+            if !self.confirm(&format!("包含「{}」?", section.title())) {
+                notice!("   ⏭️  已跳过");
+                continue;
+            }
+
+            match section.collect(&self.config) {
+                Ok(Some(content)) => sections.push((section, content)),
+                Ok(None) => notice!("   ℹ️  无可用数据"),
+                Err(e) => warn!("收集报告章节失败: {}", e),
+            }
+        }
+
+        if sections.is_empty() {
+            notice!("❌ 未选择任何章节，报告未生成");
+            return Err(CliError::OperationCancelled);
+        }
+
+        let path = output.unwrap_or_else(report::default_report_file_name);
+        report::write_report(&path, &sections)
+            .map_err(|e| CliError::ReportError(e.to_string()))?;
+
+        notice!("✅ 问题报告已生成: {}", path);
+        notice!("   共 {} 个章节，请检查内容后再附加到Issue中", sections.len());
+        Ok(())
+    }
+
+    /// 导出取证归档，校验已导出的归档，或输出签名公钥
+    /// 
+    /// # 参数
+    /// * `file` - 归档文件路径
+    /// * `verify` - 为true时校验归档而不是导出
+    /// * `public_key` - 校验时使用的签名公钥，None时使用本机公钥
+    /// * `show_public_key` - 为true时只输出本机签名公钥
+    /// 
+    /// # 返回值
+    /// * `Result<(), CliError>` - 操作结果
+    pub async fn export_forensics(
+        &self,
+        file: Option<String>,
+        verify: bool,
+        public_key: Option<String>,
+        show_public_key: bool,
+    ) -> Result<(), CliError> {
+        if verify {
+            let path = file.ok_or_else(|| CliError::ReportError("请指定要校验的归档".to_string()))?;
+            let public_key = match public_key {
+                Some(text) => escrow::parse_key(&text).map_err(|e| CliError::ReportError(e.to_string()))?,
+                None => Self::load_forensics_key()?.public_key(),
+            };
+            let manifest = forensics::verify_archive(&path, &public_key)
+                .map_err(|e| CliError::ReportError(e.to_string()))?;
+            notice!("✅ 取证归档校验通过: {}", path);
+            notice!("   导出时间: {}", manifest.created_at.to_rfc3339());
+            notice!("   密文条目: {} 个，审计记录: {} 条", manifest.items.len(), manifest.audit_entries);
+            notice!("   签名公钥: {}", encode_hex(&public_key));
+            return Ok(());
+        }
+        
+        let key = Self::load_forensics_key()?;
+        if show_public_key {
+            println!("{}", encode_hex(&key.public_key()));
+            return Ok(());
+        }
+        
+        let items = self.clipboard_monitor
+            .as_ref()
+            .map(|monitor| monitor.forensic_items())
+            .unwrap_or_default();
+        if self.clipboard_monitor.is_none() {
+            notice!("ℹ️  服务未运行，归档仅包含审计日志");
+        }
+        
+        let audit_log = audit::AuditLog::open_default()
+            .and_then(|log| log.read_text())
+            .map_err(|e| CliError::ReportError(e.to_string()))?;
+        let manifest = forensics::build_manifest(items, &audit_log);
+        
+        let path = file.unwrap_or_else(forensics::default_file_name);
+        forensics::write_archive(&path, &manifest, &audit_log, &key)
+            .map_err(|e| CliError::ReportError(e.to_string()))?;
+        audit::record("forensics", &format!("导出取证归档 {} 条目={}", path, manifest.items.len()));
+        
+        notice!("✅ 取证归档已生成: {}", path);
+        notice!("   密文条目: {} 个，审计记录: {} 条（不含密钥与明文）", manifest.items.len(), manifest.audit_entries);
+        notice!("   签名公钥: {}（export-forensics --verify --key <公钥> {} 校验完整性）", encode_hex(&key.public_key()), path);
+        Ok(())
+    }
+    
+    /// 加载本机取证签名密钥，不存在时生成
+    fn load_forensics_key() -> Result<ForensicsKey, CliError> {
+        let key_path = ForensicsKey::default_path()
+            .map_err(|e| CliError::ReportError(e.to_string()))?;
+        ForensicsKey::load_or_create(&key_path)
+            .map_err(|e| CliError::ReportError(e.to_string()))
+    }
+
+    /// 运行浸泡测试，验证长时间运行的稳定性
+    ///
+    /// # 参数
+    /// * `hours` - 运行小时数
+    pub async fn run_soak(&self, hours: f64) -> Result<(), CliError> {
+        let options = SoakOptions::hours(hours);
+        notice!("🧪 浸泡测试开始，预计运行 {}（使用测试剪贴板，不影响系统剪贴板）", Self::format_duration(options.duration));
+        notice!("   每个循环: 加密复制 → 解密粘贴并重置密钥 → {}秒倒计时清除", options.countdown.as_secs());
+        
+        let unknown = || "未知".to_string();
+        let report = soak::run(&options, |sample| {
+            notice!(
+                "   [{}] 循环 {} | 内存 {} | 描述符 {} | 任务 {}",
+                Self::format_duration(Duration::from_secs(sample.elapsed_seconds)),
+                sample.cycles,
+                sample.rss_kb.map_or_else(unknown, |kb| format!("{}KB", kb)),
+                sample.open_fds.map_or_else(unknown, |n| n.to_string()),
+                sample.tasks
+            );
+        }).await;
+        
+        if self.output_format == OutputFormat::Json {
+            output::emit_json(&serde_json::json!(report));
+        }
+        
+        notice!();
+        notice!("📈 完成循环: {} 次（失败 {} 次）", report.cycles, report.cycle_errors);
+        notice!("⏱️  计时偏差: 平均 {}ms，最大 {}ms", report.mean_drift_ms, report.max_drift_ms);
+        
+        if report.passed() {
+            notice!("✅ 浸泡测试通过");
+            Ok(())
+        } else {
+            for failure in &report.failures {
+                notice!("❌ {}", failure);
+            }
+            Err(CliError::ServiceError(format!("浸泡测试失败: {}", report.failures.join("; "))))
+        }
+    }
+    
+    /// 模拟攻击测试：高频读取系统剪贴板的嗅探器是否能在拦截前后读到明文
+    /// 
+    /// 需要服务正在运行（本进程的交互模式或其他进程中的监听服务）
+    /// 
+    /// # 参数
+    /// * `seconds` - 写入合成内容后继续嗅探的秒数
+    /// * `interval_ms` - 嗅探器轮询间隔（毫秒）
+    /// 
+    /// # 返回值
+    /// * `Result<(), CliError>` - 测试未通过时返回错误
+    pub async fn run_redteam(&self, seconds: u64, interval_ms: u64) -> Result<(), CliError> {
+        if virtual_clipboard::is_headless() && self.clipboard_monitor.is_none() {
+            return Err(CliError::ConfigError("无头模式下的虚拟剪贴板只在进程内可见，请在交互模式中运行 redteam".to_string()));
+        }
+        let secret = redteam::synthetic_secret(&self.config)
+            .ok_or_else(|| CliError::ConfigError("当前配置下没有任何规则会保护测试内容".to_string()))?;
+        let options = RedteamOptions {
+            window: Duration::from_secs(seconds.max(1)),
+            poll_interval: Duration::from_millis(interval_ms),
+            slo: self.config.clipboard.interception_slo(),
+        };
+        
+        notice!("🕵️  模拟攻击测试开始：嗅探器每 {}ms 读取一次剪贴板，持续 {}", interval_ms, Self::format_duration(options.window));
+        notice!("   写入一段随机生成的测试内容（{} 字节），请勿在测试期间复制其他内容", secret.len());
+        
+        let access = self.config.clipboard.access;
+        let actor = self.clipboard_monitor.as_ref().map(|monitor| monitor.clipboard_actor());
+        let report = tokio::task::spawn_blocking(move || {
+            redteam::run(&options, access, &secret, |content| {
+                let content = content.to_string();
+                match actor {
+                    Some(actor) => actor
+                        .run(Priority::Restore, move |ctx| ctx.set_contents(content).map_err(|e| e.to_string()))
+                        .map_err(|e| e.to_string())
+                        .and_then(|result| result),
+                    None => ClipboardBackend::open(access)
+                        .and_then(|mut ctx| ctx.set_contents(content))
+                        .map_err(|e| e.to_string()),
+                }
+            })
+        })
+        .await
+        .map_err(|e| CliError::ServiceError(e.to_string()))?
+        .map_err(CliError::ClipboardError)?;
+        
+        if self.output_format == OutputFormat::Json {
+            output::emit_json(&serde_json::json!(report));
+        }
+        
+        let ms = |value: Option<u64>| value.map_or_else(|| "-".to_string(), |ms| format!("{}ms", ms));
+        notice!();
+        notice!(
+            "📈 采样 {} 次（平均间隔 {}µs）：明文 {} | 密文 {} | 空 {} | 其他 {}",
+            report.samples,
+            report.mean_poll_interval_us,
+            report.plaintext_samples,
+            report.ciphertext_samples,
+            report.empty_samples,
+            report.other_samples
+        );
+        if report.plaintext_samples == 0 {
+            notice!("🛡️  嗅探器从未读到明文");
+        } else {
+            notice!("⏱️  明文暴露窗口: {}（首次 {}，拦截完成 {}）", ms(report.last_plaintext_ms), ms(report.first_plaintext_ms), ms(report.intercepted_ms));
+        }
+        notice!("   测试条目将按倒计时清除，也可以执行 clear 立即清除");
+        
+        if report.passed() {
+            notice!("✅ 模拟攻击测试通过");
+            Ok(())
+        } else {
+            for failure in &report.failures {
+                notice!("❌ {}", failure);
+            }
+            Err(CliError::ServiceError(format!("模拟攻击测试失败: {}", report.failures.join("; "))))
+        }
+    }
+    
+    /// 生成使用统计报告（完全在本地生成）
+    pub async fn manage_stats(&self, action: StatsAction) -> Result<(), CliError> {
+        match action {
+            StatsAction::Report { html, output, weeks } => {
+                let entries = audit::AuditLog::open_default()
+                    .and_then(|log| log.read_all())
+                    .map_err(|e| CliError::ReportError(e.to_string()))?;
+                let now = chrono::Utc::now();
+                let report = UsageReport::from_entries(&entries, now, weeks);
+                
+                if html {
+                    let path = output
+                        .map(std::path::PathBuf::from)
+                        .or_else(|| stats::default_html_path(now))
+                        .ok_or_else(|| CliError::ReportError("无法确定报告保存位置".to_string()))?;
+                    if let Some(parent) = path.parent() {
+                        std::fs::create_dir_all(parent)
+                            .map_err(|e| CliError::ReportError(e.to_string()))?;
+                    }
+                    std::fs::write(&path, report.to_html())
+                        .map_err(|e| CliError::ReportError(e.to_string()))?;
+                    notice!("📄 使用报告已保存到 {}", path.display());
+                } else if self.output_format == OutputFormat::Json {
+                    output::emit_json(&serde_json::json!(report));
+                } else {
+                    notice!("{}", report);
+                }
+            }
+            StatsAction::Share { epsilon, days, output } => {
+                stats::validate_epsilon(epsilon).map_err(CliError::ReportError)?;
+                let entries = audit::AuditLog::open_default()
+                    .and_then(|log| log.read_all())
+                    .map_err(|e| CliError::ReportError(e.to_string()))?;
+                let today = chrono::Local::now().date_naive();
+                let shared = stats::SharedStats::from_entries(&entries, today, days, epsilon, &mut rand::thread_rng());
+                let json = serde_json::to_string_pretty(&shared)
+                    .map_err(|e| CliError::ReportError(e.to_string()))?;
+                
+                notice!("🔏 加噪统计: {} 至 {}，拉普拉斯机制 ε={}（每个计数的噪声尺度 {:.1}）", shared.period_start, shared.period_end, epsilon, shared.sensitivity / epsilon);
+                notice!("   只包含固定类别的计数，不含剪贴板内容、条目ID、自定义规则名或比一天更细的时间");
+                match output {
+                    Some(path) => {
+                        std::fs::write(&path, json).map_err(|e| CliError::ReportError(e.to_string()))?;
+                        notice!("📄 已保存到 {}，确认内容后可自行决定是否分享", path);
+                    }
+                    None => println!("{}", json),
+                }
+                audit::record("stats_share", &format!("ε={} 区间={}至{}", epsilon, shared.period_start, shared.period_end));
+            }
+        }
+        Ok(())
+    }
+    
+    /// 配置紧急销毁双人确认（输出的值需写入配置的 nuke_guard 部分）
+    pub async fn manage_guard(&self, action: GuardAction) -> Result<(), CliError> {
+        match action {
+            GuardAction::SetupTotp => {
+                let secret = nuke_guard::generate_totp_secret();
+                audit::record("nuke_guard", "生成新的TOTP共享密钥");
+                notice!("🔐 已生成TOTP共享密钥，请由第二位管理员导入其验证器应用：");
+                notice!("   {}", nuke_guard::otpauth_uri(&self.config.nuke_guard, &secret));
+                notice!("   将下面的值写入配置的 nuke_guard.totp_secret，并设置 nuke_guard.enabled 为 true");
+                println!("{}", secret);
+            }
+            #[cfg(feature = "hardware-keys")]
+            GuardAction::EnrollKey { slot } => {
+                notice!("🔑 正在向硬件密钥槽位 {} 发送挑战值（如需请触摸密钥）...", slot);
+                let key = nuke_guard::enroll_hardware_key(slot)
+                    .map_err(|e| CliError::ConfigError(e.to_string()))?;
+                audit::record("nuke_guard", &format!("登记硬件密钥 槽位={}", slot));
+                notice!("✅ 已登记硬件密钥，将下面的值写入配置的 nuke_guard.hardware_key，并设置 nuke_guard.enabled 为 true");
+                println!("{}", serde_json::to_string_pretty(&key).unwrap_or_default());
+            }
+        }
+        Ok(())
+    }
+    
+    /// 管理诱饵令牌（返回 `check` 是否发现了令牌）
+    pub async fn manage_canary(&self, action: CanaryAction) -> Result<bool, CliError> {
+        let path = CanaryStore::default_path().map_err(|e| CliError::ConfigError(e.to_string()))?;
+        let mut store = CanaryStore::load(&path).map_err(|e| CliError::ConfigError(e.to_string()))?;
+        let now = chrono::Utc::now();
+        
+        match action {
+            CanaryAction::Generate => {
+                let token = store.rotate(self.config.canary.retain, now).token.clone();
+                store.save(&path).map_err(|e| CliError::ConfigError(e.to_string()))?;
+                println!("{}", token);
+                notice!("🐤 已生成新的诱饵令牌，之前的令牌已停用但仍可被 `canary check` 识别");
+            }
+            CanaryAction::Rotate => {
+                if store.active().is_none() {
+                    notice!("ℹ️  当前没有使用中的诱饵令牌");
+                    return Ok(false);
+                }
+                let label = store.rotate(self.config.canary.retain, now).label().to_string();
+                store.save(&path).map_err(|e| CliError::ConfigError(e.to_string()))?;
+                notice!("🔄 诱饵令牌已轮换，新令牌 {}…", label);
+            }
+            CanaryAction::List => {
+                if self.output_format == OutputFormat::Json {
+                    output::emit_json(&serde_json::json!(store.tokens()));
+                    return Ok(false);
+                }
+                if store.tokens().is_empty() {
+                    notice!("ℹ️  尚未发出任何诱饵令牌");
+                }
+                for token in store.tokens() {
+                    let state = match token.retired_at {
+                        _ if token.decoy => "诱饵内容".to_string(),
+                        Some(at) => format!("{} 停用", at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")),
+                        None => "使用中".to_string(),
+                    };
+                    notice!("   {}  生成于 {}  {}", token.token, token.created_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"), state);
+                }
+            }
+            CanaryAction::Check { files } => {
+                let mut sources = Vec::new();
+                if files.is_empty() {
+                    let mut text = String::new();
+                    std::io::Read::read_to_string(&mut std::io::stdin(), &mut text)
+                        .map_err(|e| CliError::ReportError(format!("读取stdin失败: {}", e)))?;
+                    sources.push(("stdin".to_string(), text));
+                }
+                for file in files {
+                    let bytes = std::fs::read(&file)
+                        .map_err(|e| CliError::ReportError(format!("无法读取 {}: {}", file, e)))?;
+                    sources.push((file, String::from_utf8_lossy(&bytes).into_owned()));
+                }
+                
+                let hits: Vec<_> = sources
+                    .iter()
+                    .flat_map(|(source, text)| store.find(text).into_iter().map(move |token| (source, token)))
+                    .collect();
+                if self.output_format == OutputFormat::Json {
+                    let hits: Vec<_> = hits
+                        .iter()
+                        .map(|(source, token)| serde_json::json!({ "source": source, "token": token }))
+                        .collect();
+                    output::emit_json(&serde_json::json!({ "found": hits }));
+                } else if hits.is_empty() {
+                    notice!("✅ 未发现诱饵令牌");
+                } else {
+                    for (source, token) in &hits {
+                        notice!(
+                            "🚨 {} 中出现诱饵令牌 {}（生成于 {}），剪贴板内容可能已被窃取并带出",
+                            source,
+                            token.token,
+                            token.created_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+                        );
+                    }
+                }
+                if !hits.is_empty() {
+                    audit::record("canary_tripped", &format!("检查发现 {} 处诱饵令牌", hits.len()));
+                }
+                return Ok(!hits.is_empty());
+            }
+        }
+        Ok(false)
+    }
+    
+    /// 导出审计日志
+    pub async fn manage_audit(&self, action: AuditAction) -> Result<(), CliError> {
+        match action {
+            AuditAction::Export { from, to, format, output } => {
+                let now = chrono::Utc::now();
+                let from = match from {
+                    Some(from) => audit::parse_bound(&from, now, false),
+                    None => Ok(chrono::DateTime::<chrono::Utc>::MIN_UTC),
+                };
+                let (from, to) = from
+                    .and_then(|from| Ok((from, audit::parse_bound(&to, now, true)?)))
+                    .map_err(|e| CliError::ReportError(e.to_string()))?;
+                let log = audit::AuditLog::open_default()
+                    .map_err(|e| CliError::ReportError(e.to_string()))?;
+                
+                let summary = match &output {
+                    Some(path) => {
+                        let file = std::fs::File::create(path)
+                            .map_err(|e| CliError::ReportError(format!("无法创建 {}: {}", path, e)))?;
+                        log.export(from, to, format, std::io::BufWriter::new(file))
+                    }
+                    None => log.export(from, to, format, std::io::stdout().lock()),
+                }
+                .map_err(|e| CliError::ReportError(e.to_string()))?;
+                
+                match &output {
+                    Some(path) => notice!("📤 已导出 {} 条审计记录到 {}", summary.exported, path),
+                    None => notice!("📤 已导出 {} 条审计记录", summary.exported),
+                }
+                notice!("   链起点: {}", summary.anchor);
+                notice!("   链终点: {}", summary.head);
+                if let Some(index) = summary.broken_at {
+                    notice!("⚠️  哈希链在第 {} 条记录处对不上，审计日志可能被修改", index);
+                }
+            }
+        }
+        Ok(())
+    }
+    
+    /// 列出持久化格式或输出其JSON Schema
+    pub async fn manage_formats(&self, action: FormatsAction) -> Result<(), CliError> {
+        match action {
+            FormatsAction::List => {
+                if self.output_format == OutputFormat::Json {
+                    let list = formats::FORMATS
+                        .iter()
+                        .map(|format| serde_json::json!({
+                            "name": format.name,
+                            "file": format.file,
+                            "revision": format.current_revision(),
+                            "description": format.description,
+                        }))
+                        .collect::<Vec<_>>();
+                    output::emit_json(&serde_json::json!(list));
+                    return Ok(());
+                }
+                for format in formats::FORMATS {
+                    notice!("   {:<10} {:<14} 修订 {}  {}", format.name, format.file, format.current_revision(), format.description);
+                }
+            }
+            FormatsAction::DumpSchema { format } => {
+                let schema = formats::dump_schema(format.as_deref()).map_err(CliError::ConfigError)?;
+                output::emit_json(&schema);
+            }
         }
-        
-        println!("✅ ClipVanish服务已停止");
         Ok(())
     }
     
-    /// 管理配置
-    /// 
-    /// # 参数
-    /// * `reset` - 是否重置为默认配置
-    /// 
-    /// # 返回值
-    /// * `Result<(), CliError>` - 操作结果
-    pub async fn manage_config(&mut self, reset: bool) -> Result<(), CliError> {
-        if reset {
-            self.config.reset_to_default()
-                .map_err(|e| CliError::ConfigError(e.to_string()))?;
-            println!("✅ 配置已重置为默认值");
-        } else {
-            self.config.display();
+    /// 创建或恢复用户数据备份
+    pub async fn manage_backup(&self, action: BackupAction) -> Result<(), CliError> {
+        let dir = Config::get_config_directory().map_err(|e| CliError::ConfigError(e.to_string()))?;
+        notice!("🔐 请输入备份口令（从stdin读取一行）:");
+        let mut passphrase = Zeroizing::new(String::new());
+        std::io::stdin().read_line(&mut passphrase)
+            .map_err(|e| CliError::ConfigError(e.to_string()))?;
+        let passphrase = passphrase.trim_end_matches(['\r', '\n']);
+
+        match action {
+            BackupAction::Create { file, include_notes } => {
+                if std::path::Path::new(&file).exists() {
+                    return Err(CliError::ConfigError(format!("备份文件已存在: {}", file)));
+                }
+                let manifest = backup::create(&dir, std::path::Path::new(&file), include_notes, passphrase)
+                    .map_err(|e| CliError::ConfigError(e.to_string()))?;
+                audit::record("backup_created", &format!("文件数={}", manifest.files.len()));
+                notice!("💾 已备份 {} 个文件到 {}", manifest.files.len(), file);
+                for entry in &manifest.files {
+                    notice!("   {:<26} {}  {} 字节", entry.name, entry.component, entry.length);
+                }
+                notice!("   请妥善保管口令，忘记口令将无法恢复");
+            }
+            BackupAction::Restore { file } => {
+                let manifest = backup::restore(&dir, std::path::Path::new(&file), passphrase)
+                    .map_err(|e| CliError::ConfigError(e.to_string()))?;
+                audit::record("backup_restored", &format!("文件数={} 备份时间={}", manifest.files.len(), manifest.created_at.to_rfc3339()));
+                notice!(
+                    "📥 已从 {}（{} 创建，版本 {}）恢复 {} 个文件",
+                    file,
+                    manifest.created_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+                    manifest.build,
+                    manifest.files.len()
+                );
+                for entry in &manifest.files {
+                    notice!("   {:<26} {}", entry.name, entry.component);
+                }
+                notice!("   如服务正在运行，请重新启动以加载恢复的配置");
+            }
         }
-        
         Ok(())
     }
     
-    /// 显示历史记录
-    pub async fn show_history(&self) -> Result<(), CliError> {
-        if let Some(monitor) = &self.clipboard_monitor {
-            let history = monitor.get_history();
-            
-            if history.is_empty() {
-                println!("📋 暂无剪贴板历史记录");
-                return Ok(());
+    /// 管理密钥托管（在管理员设备上使用）
+    pub async fn manage_escrow(&self, action: EscrowAction) -> Result<(), CliError> {
+        match action {
+            EscrowAction::Keygen { output } => {
+                if std::path::Path::new(&output).exists() {
+                    return Err(CliError::ConfigError(format!("私钥文件已存在: {}", output)));
+                }
+                let key = EscrowPrivateKey::generate();
+                key.save(&output)
+                    .map_err(|e| CliError::ConfigError(e.to_string()))?;
+                let recipient = key.recipient();
+                
+                notice!("🔑 已生成恢复密钥对，私钥已写入 {}（请离线妥善保管）", output);
+                notice!("   将下面的公钥写入受管设备配置的 escrow.recovery_public_key");
+                println!("{}", recipient.to_hex());
             }
-
-            println!("📋 剪贴板历史记录");
-            println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-            
-            for (index, item) in history.iter().enumerate() {
-                let elapsed = item.timestamp.elapsed();
-                println!("#{} - {} 前", 
-                    history.len() - index,
-                    Self::format_duration(elapsed)
-                );
+            EscrowAction::Decrypt { archive, private_key } => {
+                let key = EscrowPrivateKey::load(&private_key)
+                    .map_err(|e| CliError::ConfigError(e.to_string()))?;
+                let manifest = forensics::read_manifest(&archive)
+                    .map_err(|e| CliError::ReportError(e.to_string()))?;
                 
-                match &item.operation {
-                    ClipboardOperation::Copy => {
-                        if let Some(content) = &item.content {
-                            let preview = if content.len() > 50 {
-                                format!("{}...", &content[..47])
-                            } else {
-                                content.clone()
-                            };
-                            println!("   📥 复制: \"{}\"", preview);
-                            println!("      大小: {} 字节", item.length);
-                        }
-                    },
-                    ClipboardOperation::Paste => {
-                        println!("   📤 粘贴操作");
-                    },
-                    ClipboardOperation::Clear(ref reason) => {
-                        let reason_str = match reason {
-                            ClearReason::TimerExpired => "倒计时到期",
-                            ClearReason::ManualClear => "手动清除",
-                            ClearReason::EmergencyNuke => "紧急销毁",
-                            ClearReason::Shutdown => "程序退出",
-                        };
-                        println!("   🧹 清除: {}", reason_str);
-                    },
+                let mut recovered = Vec::new();
+                for item in &manifest.items {
+                    let label = item.item_id.map_or_else(|| item.slot.clone(), |id| id.to_string());
+                    let Some(wrapped) = &item.escrow else {
+                        notice!("⚠️  条目 {} 没有托管封装，无法恢复", label);
+                        continue;
+                    };
+                    let plaintext = key.unwrap_key(wrapped)
+                        .map_err(|e| e.to_string())
+                        .and_then(|session_key| CryptoEngine::from_key(session_key).map_err(|e| e.to_string()))
+                        .and_then(|engine| {
+                            let data = EncryptedData::from_base64(&item.ciphertext).map_err(|e| e.to_string())?;
+                            engine.decrypt(&data).map_err(|e| e.to_string())
+                        });
+                    match plaintext {
+                        Ok(plaintext) => recovered.push((label, Zeroizing::new(String::from_utf8_lossy(&plaintext).to_string()))),
+                        Err(e) => notice!("⚠️  条目 {} 恢复失败: {}", label, e),
+                    }
                 }
-                println!();
+                
+                audit::record("escrow_recover", &format!("从 {} 恢复 {} 个条目", archive, recovered.len()));
+                if self.output_format == OutputFormat::Json {
+                    output::emit_json(&serde_json::Value::Array(
+                        recovered
+                            .iter()
+                            .map(|(label, content)| serde_json::json!({ "item": label, "content": content.as_str() }))
+                            .collect(),
+                    ));
+                } else {
+                    for (label, content) in &recovered {
+                        println!("{}\t{}", label, content.as_str());
+                    }
+                }
+                notice!("✅ 已恢复 {}/{} 个条目", recovered.len(), manifest.items.len());
             }
-        } else {
-            println!("⚠️ 服务未运行，无法获取历史记录");
         }
         
         Ok(())
     }
-    
+
+    /// 提示用户确认（y/N）
+    ///
+    /// 非交互终端或等待超时时视为取消，--yes时直接确认
+    ///
+    /// # 参数
+    /// * `question` - 提示问题
+    ///
+    /// # 返回值
+    /// * `bool` - 用户是否确认
+    fn confirm(&self, question: &str) -> bool {
+        let timeout = Duration::from_secs(self.config.ui.confirm_timeout_seconds);
+        ConfirmPrompt::new(self.assume_yes, timeout)
+            .ask(question)
+            .is_confirmed()
+    }
+
     /// 设置事件回调
     fn setup_event_callbacks(
         &self,
         clipboard_monitor: &Arc<ClipboardMonitor>,
         destruct_timer: &Arc<Mutex<DestructTimer>>,
-        keyboard_monitor: &Arc<KeyboardMonitor>,
         timer_duration: u64,
     ) {
         let timer_clone = destruct_timer.clone();
         let status_clone = self.service_status.clone();
         let show_progress = self.config.ui.show_progress;
         let monitor_clone = clipboard_monitor.clone();
+        let anchor = self.config.timer.anchor;
+        let max_armed_seconds = self.config.timer.max_armed_seconds;
+        let undo_grace_seconds = self.config.timer.undo_grace_seconds;
+        let undo_key = self.config.hotkeys.undo_key.clone();
+        let promote_rule_key = self.config.hotkeys.promote_rule_key.clone();
+        let session_clone = self.session.clone();
         
         // 剪贴板事件回调
         let clipboard_callback = Arc::new(move |event: ClipboardEvent| {
             match event {
-                ClipboardEvent::ContentCopied { length, timestamp, .. } => {
-                    info!("🔒 检测到剪贴板内容 ({}字节) - 已加密存储", length);
+                ClipboardEvent::ContentCopied { item_id, length, matched, source_app, lifetime, .. } => {
+                    info!(
+                        "🔒 检测到剪贴板内容 {} ({}字节, 规则: {}/{}, 来源: {}, 存活: {}) - 已加密存储",
+                        item_id,
+                        length,
+                        matched.rule,
+                        matched.detector,
+                        source_app.as_deref().unwrap_or("未知"),
+                        Self::format_duration(lifetime)
+                    );
                     
-                    // 启动倒计时
-                    if let Ok(timer) = timer_clone.lock() {
-                        if let Err(e) = timer.start_countdown(Duration::from_secs(timer_duration)) {
-                            error!("启动倒计时失败: {}", e);
-                        }
+                    // 启动倒计时（粘贴锚点模式下等待首次粘贴）
+                    match anchor {
+                        CountdownAnchor::Copy => {
+                            if let Ok(timer) = timer_clone.lock() {
+                                if let Err(e) = timer.start_countdown(monitor_clone.cap_countdown(Duration::from_secs(timer_duration))) {
+                                    error!("启动倒计时失败: {}", e);
+                                }
+                            }
+                        },
+                        CountdownAnchor::Paste => {
+                            notice!("⏳ 已武装，等待首次粘贴后开始倒计时（最长 {}）", Self::format_duration(Duration::from_secs(max_armed_seconds)));
+                        },
+                    }
+                    
+                    if let Some(stats) = session_clone.lock().unwrap().as_mut() {
+                        stats.record_protected(item_id);
                     }
+                    audit::record(
+                        "protected",
+                        &format!("条目 {} 类别={}/{} 长度={}", item_id, matched.rule, matched.detector, length),
+                    );
                     
                     // 更新状态
                     let mut status = status_clone.lock().unwrap();
                     status.total_events += 1;
                     status.encrypted_content_length = length;
                 },
-                ClipboardEvent::ContentPasted { .. } => {
-                    debug!("用户粘贴操作");
+                ClipboardEvent::Intercepted { latency, slo_violated, .. } => {
+                    if let Some(stats) = session_clone.lock().unwrap().as_mut() {
+                        stats.record_interception(latency.total(), slo_violated);
+                    }
+                },
+                ClipboardEvent::RuleWarned { matched, matches, limit, .. } => {
+                    notice!("🧪 规则 {}/{} 处于仅警告阶段，本次命中未拦截（{}/{}）", matched.rule, matched.detector, matches, limit);
+                    notice!("   按 {} 或执行 rules --promote 转为强制执行", promote_rule_key);
+                    audit::record(
+                        "rule_warned",
+                        &format!("规则={}/{} 命中={}/{}", matched.rule, matched.detector, matches, limit),
+                    );
+                },
+                ClipboardEvent::ProtectionFailed { error, .. } => {
+                    notice!("⚠️  敏感内容未能受保护: {}", error);
+                    
+                    if let Some(stats) = session_clone.lock().unwrap().as_mut() {
+                        stats.record_unprotected();
+                    }
+                },
+                ClipboardEvent::ContentPasted { item_id, .. } => {
+                    debug!("用户粘贴操作，条目: {:?}", item_id);
+                    
+                    if anchor == CountdownAnchor::Paste {
+                        if let Ok(timer) = timer_clone.lock() {
+                            if let Err(e) = timer.start_countdown(monitor_clone.cap_countdown(Duration::from_secs(timer_duration))) {
+                                error!("启动倒计时失败: {}", e);
+                            }
+                        }
+                    }
                 },
-                ClipboardEvent::ContentCleared { reason, .. } => {
+                ClipboardEvent::ContentCleared { item_id, reason, .. } => {
+                    debug!("条目已清除: {:?}", item_id);
+                    
+                    if let Some(stats) = session_clone.lock().unwrap().as_mut() {
+                        stats.record_cleared(item_id, &reason);
+                    }
+                    if let Some(item_id) = item_id {
+                        audit::record("destroyed", &format!("条目 {} 原因={}", item_id, session::reason_label(&reason)));
+                    }
                     match reason {
                         ClearReason::TimerExpired => {
                             // 倒计时结束时，清除超时记录
                             monitor_clone.clear_expired_history();
-                            println!("🔥 倒计时结束 - 剪贴板已自动清除");
+                            notice!("🔥 倒计时结束 - 剪贴板已自动清除");
+                            if undo_grace_seconds > 0 {
+                                notice!("↩️  {}秒内可执行 undo 或按 {} 撤销", undo_grace_seconds, undo_key);
+                            }
                         },
-                        ClearReason::ManualClear => println!("🧹 剪贴板已手动清除"),
-                        ClearReason::EmergencyNuke => println!("💥 紧急销毁 - 所有数据已清除"),
+                        ClearReason::ManualClear => notice!("🧹 剪贴板已手动清除"),
+                        ClearReason::EmergencyNuke => notice!("💥 紧急销毁 - 所有数据已清除"),
                         ClearReason::Shutdown => debug!("程序退出时清除剪贴板"),
+                        ClearReason::Unprotected => notice!("🔓 已解除保护，内容保留在剪贴板中"),
+                        ClearReason::Escalated => notice!("💥 该条目延长次数过多 - 已强制销毁"),
                     }
                     
                     // 更新状态
@@ -555,10 +3252,46 @@ impl CliHandler {
         
         // 定时器事件回调
         let status_clone2 = self.service_status.clone();
+        let countdown_title = self.config.ui.countdown_title;
+        let countdown_status = self.config.ui.countdown_status;
+        let title_pushed = Arc::new(AtomicBool::new(false));
+        let overlay = self.overlay.clone();
+        let countdown_monitor = Arc::downgrade(clipboard_monitor);
+        // 倒计时结束或取消时撤销标题、状态文件与悬浮窗
+        let finish_display = {
+            let title_pushed = title_pushed.clone();
+            let overlay = overlay.clone();
+            move || {
+                if let Some(overlay) = &overlay {
+                    overlay.set_remaining(None);
+                }
+                if countdown_status {
+                    countdown_display::clear();
+                }
+                if title_pushed.swap(false, Ordering::SeqCst) {
+                    TerminalTitle::pop();
+                }
+            }
+        };
         let timer_callback = Arc::new(move |event: TimerEvent| {
             match event {
                 TimerEvent::Started { duration, .. } => {
-                    println!("⏰ 自毁倒计时已启动: {}", Self::format_duration(duration));
+                    notice!("⏰ 自毁倒计时已启动: {}", Self::format_duration(duration));
+                    
+                    // 延长条目时会重新启动倒计时，只在第一次保存原标题
+                    if let Some(overlay) = &overlay {
+                        overlay.set_remaining(Some(duration));
+                    }
+                    if countdown_status {
+                        let item = countdown_monitor.upgrade().and_then(|monitor| monitor.current_item_id());
+                        countdown_display::publish(chrono::Utc::now() + chrono::Duration::from_std(duration).unwrap_or_default(), item);
+                    }
+                    if countdown_title {
+                        if !title_pushed.swap(true, Ordering::SeqCst) {
+                            TerminalTitle::push();
+                        }
+                        TerminalTitle::set(duration);
+                    }
                 },
                 TimerEvent::Tick { remaining, .. } => {
                     // 更新状态中的剩余时间
@@ -567,17 +3300,20 @@ impl CliHandler {
                         status.remaining_time = Some(remaining);
                     }
                     
+                    if countdown_title {
+                        TerminalTitle::set(remaining);
+                    }
+                    
                     // 显示倒计时（仅在最后几秒）
                     if remaining.as_secs() <= 10 && remaining.as_secs() > 0 {
                         if show_progress {
-                            print!("\r⏰ 倒计时: {}秒 ", remaining.as_secs());
-                            use std::io::{self, Write};
-                            io::stdout().flush().unwrap();
+                            notice_inline!("\r⏰ 倒计时: {}秒 ", remaining.as_secs());
                         }
                     }
                 },
                 TimerEvent::Completed { .. } => {
-                    println!("\n🔥 倒计时完成 - 执行自动销毁");
+                    notice!("\n🔥 倒计时完成 - 执行自动销毁");
+                    finish_display();
                     
                     // 更新状态
                     let mut status = status_clone2.lock().unwrap();
@@ -585,6 +3321,7 @@ impl CliHandler {
                 },
                 TimerEvent::Cancelled { .. } => {
                     debug!("定时器被取消");
+                    finish_display();
                     
                     // 更新状态
                     let mut status = status_clone2.lock().unwrap();
@@ -597,23 +3334,85 @@ impl CliHandler {
         });
         
         destruct_timer.lock().unwrap().set_callback(timer_callback);
-
-        // 键盘事件回调
+    }
+    
+    /// 设置键盘事件回调（粘贴时解密）
+    #[cfg(feature = "keyboard-hooks")]
+    fn setup_keyboard_callback(
+        &self,
+        clipboard_monitor: &Arc<ClipboardMonitor>,
+        keyboard_monitor: &Arc<KeyboardMonitor>,
+    ) {
         let clipboard_clone = clipboard_monitor.clone();
+        #[cfg(windows)]
+        let history_policy = self.config.clipboard.history_policy;
+        let paste_policy = self.config.paste_context.clone();
+        let confirm_seconds = self.config.paste_confirm.confirm_seconds;
+        let approve_hint = if cfg!(feature = "hotkeys") && self.config.hotkeys.enable_global_hotkeys {
+            format!("或按 {} ", self.config.hotkeys.approve_paste_key)
+        } else {
+            String::new()
+        };
         let keyboard_callback = Arc::new(move |event: KeyboardEvent| {
             match event {
-                KeyboardEvent::PasteDetected { timestamp: _, key_combination } => {
-                    info!("🔍 检测到粘贴操作: {}", key_combination);
+                KeyboardEvent::PasteDetected { timestamp: _, gesture, context } => {
+                    info!("🔍 检测到粘贴操作: {} ({})", gesture, context);
+
+                    // 中键粘贴读取PRIMARY选区，剪贴板中的密文不参与
+                    #[cfg(target_os = "linux")]
+                    if gesture == PasteGesture::MiddleClick {
+                        Self::handle_primary_paste(&clipboard_clone);
+                        return;
+                    }
+
+                    // 延迟渲染模式下由应用请求数据时解密，读取剪贴板反而会抢先触发渲染
+                    if clipboard_clone.delayed_promise_pending() {
+                        debug!("剪贴板中是延迟渲染承诺，等待应用请求数据");
+                        return;
+                    }
 
                     // 检查剪贴板中是否有我们的加密内容
                     if let Ok(Some(current_content)) = clipboard_clone.read_clipboard_content() {
                         if clipboard_clone.is_our_encrypted_content(&current_content) {
+                            // 按粘贴上下文阻止解密，粘贴得到的仍是密文
+                            if let Some(reason) = paste_policy.blocked_reason(&context) {
+                                warn!("粘贴上下文被策略阻止（{}），保持加密", reason);
+                                notice!("🚫 在{}上的粘贴已被阻止，内容保持加密", reason);
+                                audit::record("paste_blocked", &context.to_string());
+                                return;
+                            }
+                            
+                            // 关键条目：第一次粘贴只提示目标程序，确认后才交付明文
+                            if clipboard_clone.current_is_critical() {
+                                let target = source_app::foreground_app();
+                                let target_label = target.clone().unwrap_or_else(|| "未知程序".to_string());
+                                let item = clipboard_clone.current_item_id().map(|id| id.to_string()).unwrap_or_default();
+                                if clipboard_clone.request_paste(target) == PasteDecision::Confirm {
+                                    let prompt = format!(
+                                        "条目 {} 命中高风险规则，即将粘贴到 {}：{}秒内再次按粘贴键{}确认",
+                                        item, target_label, confirm_seconds, approve_hint
+                                    );
+                                    notice!("⚠️  {}", prompt);
+                                    notifications::notify(Severity::Normal, "ClipVanish：确认粘贴", &prompt);
+                                    audit::record("paste_confirm_requested", &format!("条目 {} 目标={}", item, target_label));
+                                    return;
+                                }
+                                audit::record("paste_confirmed", &format!("条目 {} 目标={} 来源=再次粘贴", item, target_label));
+                            }
+                            
                             info!("检测到加密内容，开始解密处理");
 
                             // 在粘贴时进行解密并重置密钥
                             match clipboard_clone.get_decrypted_content_for_paste() {
                                 Ok(Some(decrypted_content)) => {
                                     info!("✅ 解密成功，内容长度: {} 字符", decrypted_content.len());
+                                    let decrypted_content = Zeroizing::new(decrypted_content);
+                                    
+                                    // 按目标程序的粘贴转换规则处理
+                                    let decrypted_content = clipboard_clone
+                                        .paste_transforms()
+                                        .apply_to_foreground(&decrypted_content)
+                                        .unwrap_or(decrypted_content);
 
                                     // 立即将解密内容放到剪贴板中，替换加密内容
                                     match clipboard_clone.set_clipboard_content(&decrypted_content) {
@@ -629,6 +3428,7 @@ impl CliHandler {
                                             error!("将解密内容放入剪贴板失败: {}", e);
                                         }
                                     }
+                                    erase_scheduler::retire(decrypted_content);
                                 },
                                 Ok(None) => {
                                     warn!("解密返回空内容");
@@ -644,6 +3444,20 @@ impl CliHandler {
                         debug!("无法读取剪贴板内容");
                     }
                 },
+                #[cfg(windows)]
+                KeyboardEvent::ClipboardHistoryShortcut { .. } => {
+                    if history_policy == ClipboardHistoryPolicy::Ignore
+                        || !clipboard_clone.has_protected_item()
+                    {
+                        return;
+                    }
+                    
+                    // 剪贴板历史可能已记录粘贴时解密的明文
+                    match win_history::clear_history() {
+                        Ok(()) => info!("🧹 存在受保护条目，已清空剪贴板历史"),
+                        Err(e) => warn!("清空剪贴板历史失败: {}", e),
+                    }
+                },
                 KeyboardEvent::OtherShortcut { keys, .. } => {
                     debug!("检测到其他快捷键: {:?}", keys);
                 },
@@ -653,38 +3467,93 @@ impl CliHandler {
         keyboard_monitor.set_event_callback(keyboard_callback);
     }
     
+    /// 处理鼠标中键粘贴：PRIMARY选区中是受保护条目的明文时，视为该条目已被粘贴
+    ///
+    /// 清空PRIMARY选区以免再次中键粘贴得到明文，并开始粘贴后的倒计时清除
+    ///
+    /// # 参数
+    /// * `monitor` - 剪贴板监听器
+    #[cfg(all(target_os = "linux", feature = "keyboard-hooks"))]
+    fn handle_primary_paste(monitor: &ClipboardMonitor) {
+        if !monitor.has_protected_item() {
+            return;
+        }
+        let Some(primary) = paste_gestures::read_primary() else {
+            return;
+        };
+        let content = match monitor.get_decrypted_content() {
+            Ok(Some(content)) => Zeroizing::new(content),
+            Ok(None) => return,
+            Err(e) => {
+                debug!("比较PRIMARY选区时解密失败: {}", e);
+                return;
+            }
+        };
+        if *primary != *content {
+            debug!("PRIMARY选区不是受保护条目，忽略中键粘贴");
+            return;
+        }
+        
+        info!("中键粘贴了PRIMARY选区中受保护条目的明文");
+        let item = monitor.current_item_id().map(|id| id.to_string()).unwrap_or_default();
+        audit::record("primary_paste", &format!("条目 {}", item));
+        if let Err(e) = paste_gestures::clear_primary() {
+            warn!("清空PRIMARY选区失败: {}", e);
+        }
+        if let Err(e) = monitor.handle_paste(&content) {
+            error!("处理粘贴操作失败: {}", e);
+        }
+        erase_scheduler::retire(primary);
+        erase_scheduler::retire(content);
+    }
+    
     /// 注册全局热键
+    ///
+    /// 按 `HotkeyConfig::effective_bindings` 逐个注册，单个按键无法解析或已被占用时只跳过该绑定
+    #[cfg(feature = "hotkeys")]
     fn register_global_hotkeys(
         &mut self,
         clipboard_monitor: &Arc<ClipboardMonitor>,
-        _destruct_timer: &Arc<Mutex<DestructTimer>>,
+        destruct_timer: &Arc<Mutex<DestructTimer>>,
     ) -> Result<(), CliError> {
         let manager = GlobalHotKeyManager::new()
             .map_err(|e| CliError::HotkeyError(e.to_string()))?;
         
-        // 注册紧急销毁热键 (Ctrl+Alt+V)
-        let emergency_hotkey = HotKey::new(
-            Some(Modifiers::CONTROL | Modifiers::ALT),
-            Code::KeyV,
-        );
+        let mut actions: HashMap<u32, HotkeyAction> = HashMap::new();
+        let mut registered = Vec::new();
+        for binding in self.config.hotkeys.effective_bindings() {
+            let hotkey: HotKey = match binding.key.parse() {
+                Ok(hotkey) => hotkey,
+                Err(e) => {
+                    warn!("无法解析热键 {}（{}）: {}", binding.key, binding.action, e);
+                    continue;
+                }
+            };
+            if let Err(e) = manager.register(hotkey) {
+                warn!("注册热键 {}（{}）失败: {}", binding.key, binding.action, e);
+                continue;
+            }
+            registered.push(format!("{}={}", binding.key, binding.action));
+            actions.insert(hotkey.id(), binding.action);
+        }
         
-        manager.register(emergency_hotkey)
-            .map_err(|e| CliError::HotkeyError(e.to_string()))?;
+        if actions.is_empty() {
+            return Err(CliError::HotkeyError("没有可用的热键绑定".to_string()));
+        }
         
         // 启动热键事件处理
         let monitor_clone = Arc::clone(clipboard_monitor);
+        let timer_clone = Arc::clone(destruct_timer);
+        let overlay = self.overlay.clone();
         tokio::spawn(async move {
             let receiver = GlobalHotKeyEvent::receiver();
             
             loop {
                 if let Ok(event) = receiver.try_recv() {
                     if event.state == HotKeyState::Pressed {
-                        info!("检测到紧急销毁热键");
-                        
-                        if let Err(e) = monitor_clone.emergency_nuke() {
-                            error!("热键触发的紧急销毁失败: {}", e);
-                        } else {
-                            println!("\n💥 热键触发紧急销毁 - 所有数据已清除");
+                        if let Some(action) = actions.get(&event.id) {
+                            info!("检测到热键: {}", action);
+                            Self::run_hotkey_action(action, "热键", &monitor_clone, &timer_clone, overlay.as_deref());
                         }
                     }
                 }
@@ -694,11 +3563,261 @@ impl CliHandler {
         });
         
         self.hotkey_manager = Some(manager);
-        info!("全局热键已注册: {}", self.config.hotkeys.emergency_nuke_key);
+        info!("全局热键已注册: {}", registered.join(", "));
         
         Ok(())
     }
     
+    /// 执行热键或菜单栏动作
+    ///
+    /// # 参数
+    /// * `action` - 热键动作
+    /// * `trigger` - 触发来源（热键、菜单），用于提示与审计
+    /// * `monitor` - 剪贴板监听器
+    /// * `timer` - 自毁定时器
+    /// * `overlay` - 倒计时悬浮窗
+    #[cfg(any(feature = "hotkeys", all(target_os = "macos", feature = "menubar")))]
+    fn run_hotkey_action(
+        action: &HotkeyAction,
+        trigger: &str,
+        monitor: &ClipboardMonitor,
+        timer: &Mutex<DestructTimer>,
+        overlay: Option<&Overlay>,
+    ) {
+        match action {
+            HotkeyAction::Nuke => {
+                if Self::authorize_triggered_nuke(&monitor.nuke_guard(), trigger) {
+                    match monitor.emergency_nuke() {
+                        Ok(_) => notice!("\n💥 {}触发紧急销毁 - 所有数据已清除", trigger),
+                        Err(e) => error!("{}触发的紧急销毁失败: {}", trigger, e),
+                    }
+                }
+            }
+            HotkeyAction::Pause => {
+                let paused = monitor.toggle_paused();
+                notice!("\n{}（{}）", if paused { "⏸️  保护已暂停" } else { "▶️  保护已恢复" }, trigger);
+            }
+            HotkeyAction::Extend { seconds } => match monitor.extend_item(None, Duration::from_secs(*seconds), false) {
+                ExtendOutcome::Extended { granted, remaining, capped } => {
+                    if let Err(e) = timer.lock().unwrap().start_countdown(remaining) {
+                        error!("重新启动倒计时失败: {}", e);
+                    }
+                    notice!("\n⏳ {}触发延长 {}，剩余 {}", trigger, duration::format(granted), duration::format(remaining));
+                    if let Some(cap) = capped {
+                        notice!("⛔ 该类内容最长只能存活 {}，延长已截断", DestructTimer::format_duration(cap));
+                    }
+                }
+                outcome => Self::notice_escalation(outcome, trigger, timer),
+            },
+            HotkeyAction::Pin => match monitor.pin_current(false) {
+                ExtendOutcome::Extended { remaining, capped: Some(cap), .. } => {
+                    if let Err(e) = timer.lock().unwrap().start_countdown(remaining) {
+                        error!("重新启动倒计时失败: {}", e);
+                    }
+                    notice!(
+                        "\n⛔ 该类内容最长只能存活 {}，无法固定，将在 {} 后清除",
+                        DestructTimer::format_duration(cap),
+                        DestructTimer::format_duration(remaining)
+                    );
+                }
+                ExtendOutcome::Extended { granted, .. } => {
+                    if let Err(e) = timer.lock().unwrap().stop_countdown() {
+                        warn!("停止倒计时失败: {}", e);
+                    }
+                    notice!(
+                        "\n📌 当前条目已固定，{} 内不再自动清除（紧急销毁或手动清除仍然有效）",
+                        DestructTimer::format_duration(granted)
+                    );
+                }
+                outcome => Self::notice_escalation(outcome, trigger, timer),
+            },
+            HotkeyAction::PasteSlot { slot } => match monitor.restore_history_slot(*slot) {
+                Ok(true) => notice!("\n📋 第 {} 条历史记录已放回剪贴板（受保护）", slot),
+                Ok(false) => notice!("\nℹ️  第 {} 条历史记录不存在或未保留内容", slot),
+                Err(e) => error!("放回第 {} 条历史记录失败: {}", slot, e),
+            },
+            HotkeyAction::ShowStatus => {
+                let mut parts = vec![match (monitor.current_item_id(), monitor.current_remaining()) {
+                    (Some(item_id), Some(remaining)) => format!("条目 {} 剩余 {}", item_id, DestructTimer::format_duration(remaining)),
+                    _ => "没有受保护的条目".to_string(),
+                }];
+                if monitor.is_paused() {
+                    parts.push("保护已暂停".to_string());
+                }
+                if monitor.is_observing() {
+                    parts.push("观察模式".to_string());
+                }
+                notice!("\n📊 {}", parts.join(" | "));
+            }
+            HotkeyAction::ToggleObserve => {
+                let observing = monitor.toggle_observing();
+                notice!("\n{}", if observing { "👁️  观察模式已开启 - 命中规则时只记录，不拦截" } else { "🛡️  观察模式已关闭 - 恢复拦截" });
+            }
+            HotkeyAction::Undo => match monitor.undo_last_clear() {
+                Ok(true) => notice!("\n↩️  {}触发撤销 - 内容已恢复并重新受保护", trigger),
+                Ok(false) => notice!("\nℹ️  没有可撤销的清除（宽限期已过或已撤销）"),
+                Err(e) => error!("{}触发的撤销失败: {}", trigger, e),
+            },
+            HotkeyAction::PromoteRule => match monitor.take_last_warned_rule() {
+                Some(rule) => match rule_trial::promote(&rule) {
+                    Ok(_) => {
+                        audit::record("rule_promoted", &format!("规则={} 来源={}", rule, trigger));
+                        notice!("\n🛡️  {}触发转正 - 规则 {} 已转为强制执行", trigger, rule);
+                    }
+                    Err(e) => error!("{}触发的规则转正失败: {}", trigger, e),
+                },
+                None => notice!("\nℹ️  没有处于仅警告阶段的最近命中"),
+            },
+            HotkeyAction::ApprovePaste => Self::approve_pending_paste(monitor, trigger),
+            HotkeyAction::ToggleOverlay => match overlay.map(Overlay::toggle) {
+                Some(Ok(true)) => notice!("\n🪟 倒计时悬浮窗已显示"),
+                Some(Ok(false)) => notice!("\n🪟 倒计时悬浮窗已隐藏"),
+                Some(Err(e)) => warn!("无法显示倒计时悬浮窗: {}", e),
+                None => notice!("\nℹ️  当前环境不支持倒计时悬浮窗"),
+            },
+        }
+    }
+    
+    /// 提示热键或菜单的延长、固定未获准的原因
+    ///
+    /// # 参数
+    /// * `outcome` - 延长结果
+    /// * `trigger` - 触发来源
+    /// * `timer` - 自毁定时器
+    #[cfg(any(feature = "hotkeys", all(target_os = "macos", feature = "menubar")))]
+    fn notice_escalation(outcome: ExtendOutcome, trigger: &str, timer: &Mutex<DestructTimer>) {
+        match outcome {
+            ExtendOutcome::ConfirmationRequired { granted, window } => notice!(
+                "\n⚠️  该条目已多次延长，请在 {} 内再次通过{}操作以确认（将延长 {}）",
+                DestructTimer::format_duration(window),
+                trigger,
+                DestructTimer::format_duration(granted)
+            ),
+            ExtendOutcome::Destroyed => {
+                if let Err(e) = timer.lock().unwrap().stop_countdown() {
+                    warn!("停止倒计时失败: {}", e);
+                }
+            }
+            ExtendOutcome::NotFound => notice!("\nℹ️  当前没有受保护的条目"),
+            ExtendOutcome::Extended { .. } => {}
+        }
+    }
+    
+    /// 确认待确认的高风险粘贴：解密后直接输入到目标程序，剪贴板中不出现明文
+    ///
+    /// # 参数
+    /// * `monitor` - 剪贴板监听器
+    /// * `trigger` - 触发来源
+    #[cfg(all(any(feature = "hotkeys", all(target_os = "macos", feature = "menubar")), feature = "keyboard-hooks"))]
+    fn approve_pending_paste(monitor: &ClipboardMonitor, trigger: &str) {
+        let Some(request) = monitor.approve_pending_paste() else {
+            notice!("\nℹ️  没有待确认的粘贴");
+            return;
+        };
+        let target_label = request.target.clone().unwrap_or_else(|| "未知程序".to_string());
+        
+        // 焦点已切换到其他程序时不交付
+        if request.target.is_some() && source_app::foreground_app() != request.target {
+            warn!("焦点已离开 {}，取消待确认的粘贴", target_label);
+            notice!("\n🚫 焦点已离开 {}，粘贴已取消，内容保持加密", target_label);
+            return;
+        }
+        
+        match monitor.get_decrypted_content_for_paste() {
+            Ok(Some(content)) => {
+                let content = Zeroizing::new(content);
+                let content = monitor.paste_transforms().apply(request.target.as_deref(), &content).unwrap_or(content);
+                let strategy = monitor.paste_strategies().for_app(request.target.as_deref());
+                let delivered = match strategy {
+                    PasteStrategy::ClipboardOnly => {
+                        monitor.set_clipboard_content(&content).map_err(|e| PasteError::Clipboard(e.to_string()))
+                    }
+                    PasteStrategy::TypeText => KeyboardMonitor::simulate_text_input(&content)
+                        .map_err(|e| PasteError::Delivery { strategy, message: e.to_string() }),
+                    _ => KeyboardMonitor::secure_paste_text(&content, &monitor.clipboard_actor(), strategy),
+                };
+                match delivered {
+                    Ok(()) => {
+                        audit::record(
+                            "paste_confirmed",
+                            &format!("条目 {} 目标={} 来源={} 方式={}", request.item_id, target_label, trigger, strategy),
+                        );
+                        if strategy == PasteStrategy::ClipboardOnly {
+                            notice!("\n📋 明文已放入剪贴板，请在 {} 中手动粘贴", target_label);
+                        } else {
+                            notice!("\n✅ 已确认粘贴到 {}", target_label);
+                        }
+                        if let Err(e) = monitor.handle_paste(&content) {
+                            error!("处理粘贴操作失败: {}", e);
+                        }
+                    }
+                    Err(e) => {
+                        error!("输入已确认的内容失败: {}", e);
+                        notice!("\n❌ 粘贴到 {} 失败: {}", target_label, e);
+                    }
+                }
+            }
+            Ok(None) => warn!("解密返回空内容"),
+            Err(e) => error!("确认粘贴时解密失败: {}", e),
+        }
+    }
+    
+    /// 确认待确认的高风险粘贴（需要键盘监听）
+    #[cfg(all(any(feature = "hotkeys", all(target_os = "macos", feature = "menubar")), not(feature = "keyboard-hooks")))]
+    fn approve_pending_paste(_monitor: &ClipboardMonitor, _trigger: &str) {
+        notice!("\nℹ️  高风险粘贴确认需要键盘监听（keyboard-hooks 特性）");
+    }
+    
+    /// 启动菜单栏桥接任务
+    ///
+    /// 执行菜单栏发来的命令（与热键动作走同一逻辑），并定期更新菜单栏读取的状态快照。
+    /// 任务随运行时一同结束并释放连接，菜单栏随之退出，因此会话收尾完成之前菜单栏不会先退出
+    ///
+    /// # 参数
+    /// * `service` - 菜单栏连接的服务一侧
+    /// * `clipboard_monitor` - 剪贴板监听器
+    /// * `destruct_timer` - 自毁定时器
+    #[cfg(all(target_os = "macos", feature = "menubar"))]
+    fn start_menubar_bridge(
+        &self,
+        service: MenuBarService,
+        clipboard_monitor: &Arc<ClipboardMonitor>,
+        destruct_timer: &Arc<Mutex<DestructTimer>>,
+    ) {
+        let monitor = Arc::clone(clipboard_monitor);
+        let timer = Arc::clone(destruct_timer);
+        let overlay = self.overlay.clone();
+        let should_stop = self.should_stop.clone();
+        let config = self.config.clone();
+        
+        tokio::spawn(async move {
+            loop {
+                while let Some(command) = service.try_recv() {
+                    match command {
+                        MenuCommand::Action(action) => {
+                            info!("菜单栏动作: {}", action);
+                            Self::run_hotkey_action(&action, "菜单", &monitor, &timer, overlay.as_deref());
+                        }
+                        MenuCommand::Quit => *should_stop.lock().unwrap() = true,
+                        // 由菜单栏线程直接处理
+                        MenuCommand::OpenAccessibilitySettings => {}
+                    }
+                }
+                service.update(|state| {
+                    state.running = true;
+                    state.remaining = monitor.current_remaining();
+                    state.paused = monitor.is_paused();
+                    state.observing = monitor.is_observing();
+                    // 评分按小时缓存，这里只读取缓存
+                    let hygiene = advisor::current(&config);
+                    state.hygiene = hygiene.as_ref().map(ToString::to_string);
+                    state.hygiene_tip = hygiene.and_then(|report| report.recommendations.into_iter().next());
+                });
+                sleep(Duration::from_millis(200)).await;
+            }
+        });
+    }
+    
     /// 启动状态更新任务
     async fn start_status_update_task(&self) {
         let status_clone = self.service_status.clone();
@@ -724,30 +3843,103 @@ impl CliHandler {
     }
     
     /// 启动信号处理任务
-    async fn start_signal_handler(&self) {
+    ///
+    /// Unix信号约定：
+    /// * SIGINT/SIGTERM - 停止服务（仅前台模式注册，后台模式保留默认行为）
+    /// * SIGUSR1 - 紧急销毁
+    /// * SIGUSR2 - 暂停/恢复保护
+    /// * SIGHUP - 重新加载配置
+    ///
+    /// # 参数
+    /// * `handle_termination` - 是否接管SIGINT/SIGTERM
+    async fn start_signal_handler(&self, handle_termination: bool) {
         let should_stop = self.should_stop.clone();
+        let monitor = self.clipboard_monitor.clone();
+        let timer = self.destruct_timer.clone();
         
         tokio::spawn(async move {
             #[cfg(unix)]
             {
-                let mut sigint = signal::unix::signal(signal::unix::SignalKind::interrupt())
-                    .expect("无法注册SIGINT处理器");
-                
-                let mut sigterm = signal::unix::signal(signal::unix::SignalKind::terminate())
-                    .expect("无法注册SIGTERM处理器");
+                use signal::unix::{signal, SignalKind};
+
+                let mut sigusr1 = signal(SignalKind::user_defined1())
+                    .expect("无法注册SIGUSR1处理器");
+                let mut sigusr2 = signal(SignalKind::user_defined2())
+                    .expect("无法注册SIGUSR2处理器");
+                let mut sighup = signal(SignalKind::hangup())
+                    .expect("无法注册SIGHUP处理器");
+                let mut termination = if handle_termination {
+                    Some((
+                        signal(SignalKind::interrupt()).expect("无法注册SIGINT处理器"),
+                        signal(SignalKind::terminate()).expect("无法注册SIGTERM处理器"),
+                    ))
+                } else {
+                    None
+                };
                 
-                tokio::select! {
-                    _ = sigint.recv() => {
-                        info!("收到SIGINT信号");
-                    },
-                    _ = sigterm.recv() => {
-                        info!("收到SIGTERM信号");
-                    },
+                loop {
+                    let terminate = async {
+                        match termination.as_mut() {
+                            Some((sigint, sigterm)) => tokio::select! {
+                                _ = sigint.recv() => "SIGINT",
+                                _ = sigterm.recv() => "SIGTERM",
+                            },
+                            None => std::future::pending().await,
+                        }
+                    };
+
+                    tokio::select! {
+                        name = terminate => {
+                            info!("收到{}信号，开始关闭", name);
+                            Self::run_shutdown(Trigger::Signal(name)).await;
+                            break;
+                        },
+                        _ = sigusr1.recv() => {
+                            info!("收到SIGUSR1信号，执行紧急销毁");
+                            if let Some(monitor) = &monitor {
+                                if !Self::authorize_triggered_nuke(&monitor.nuke_guard(), "SIGUSR1信号") {
+                                    continue;
+                                }
+                                match monitor.emergency_nuke() {
+                                    Ok(_) => notice!("\n💥 信号触发紧急销毁 - 所有数据已清除"),
+                                    Err(e) => error!("信号触发的紧急销毁失败: {}", e),
+                                }
+                            }
+                            if let Some(timer) = &timer {
+                                if let Err(e) = timer.lock().unwrap().stop_countdown() {
+                                    warn!("停止倒计时失败: {}", e);
+                                }
+                            }
+                        },
+                        _ = sigusr2.recv() => {
+                            if let Some(monitor) = &monitor {
+                                let paused = monitor.toggle_paused();
+                                notice!("\n{}", if paused { "⏸️  保护已暂停 (SIGUSR2)" } else { "▶️  保护已恢复 (SIGUSR2)" });
+                            }
+                        },
+                        _ = sighup.recv() => {
+                            info!("收到SIGHUP信号，重新加载配置");
+                            match Config::load() {
+                                Ok(config) => {
+                                    rule_trial::sync(&config);
+                                    if let Some(monitor) = &monitor {
+                                        monitor.reload_config(config);
+                                    }
+                                    notice!("\n🔄 配置已重新加载 (热键配置需重启服务后生效)");
+                                },
+                                Err(e) => error!("重新加载配置失败，继续使用当前配置: {}", e),
+                            }
+                        },
+                    }
                 }
             }
             
             #[cfg(windows)]
             {
+                if !handle_termination {
+                    return;
+                }
+
                 match signal::ctrl_c().await {
                     Ok(_) => {
                         info!("收到Ctrl+C信号");
@@ -756,49 +3948,28 @@ impl CliHandler {
                         error!("信号处理错误: {}", e);
                     }
                 }
+                Self::run_shutdown(Trigger::Signal("Ctrl+C")).await;
             }
             
             *should_stop.lock().unwrap() = true;
         });
     }
     
-    /// 清理服务资源
-    async fn cleanup_service(&mut self) -> Result<(), CliError> {
-        info!("清理服务资源");
-        
-        // 如果配置要求，在退出时清除剪贴板
-        if self.config.security.auto_clear_on_exit {
-            if let Some(monitor) = &self.clipboard_monitor {
-                monitor.clear_clipboard(ClearReason::Shutdown)
-                    .map_err(|e| CliError::ClipboardError(e.to_string()))?;
-            }
-        }
-        
-        // 更新服务状态
-        {
-            let mut status = self.service_status.lock().unwrap();
-            status.is_running = false;
-            status.start_time = None;
-        }
-        
-        // 清理组件引用
-        self.clipboard_monitor = None;
-        self.destruct_timer = None;
-        self.hotkey_manager = None;
-        
-        println!("🧹 资源清理完成");
-        Ok(())
-    }
-    
     /// 显示启动信息
     fn display_startup_info(&self, timer_duration: u64) {
-        println!("🚀 启动ClipVanish™监听服务");
-        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-        println!("🔒 加密算法: AES-256-GCM-SIV");
-        println!("⏰ 自毁倒计时: {}秒", timer_duration);
-        println!("🛡️ 内存保护: {}", if self.config.security.enable_memory_locking { "启用" } else { "禁用" });
-        println!("⌨️ 紧急热键: {}", self.config.hotkeys.emergency_nuke_key);
-        println!();
+        notice!("🚀 启动ClipVanish™监听服务");
+        notice!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        notice!("🔒 加密算法: AES-256-GCM-SIV");
+        notice!("⏰ 自毁倒计时: {}", duration::format(Duration::from_secs(timer_duration)));
+        notice!("🛡️ 内存保护: {}", if self.config.security.enable_memory_locking { "启用" } else { "禁用" });
+        notice!("⌨️ 紧急热键: {}", self.config.hotkeys.emergency_nuke_key);
+        if virtual_clipboard::is_headless() {
+            notice!("🧪 无头模式: 使用进程内虚拟剪贴板（热键与键盘监听已停用，用 get 代替粘贴）");
+        }
+        if helpers::is_strict() {
+            notice!("🚫 严格模式: 不启动任何辅助程序（原生清除与粘贴模拟等功能回退或停用）");
+        }
+        notice!();
     }
     
     /// 显示进度条
@@ -807,32 +3978,16 @@ impl CliHandler {
         let filled = (progress * width as f64) as usize;
         let empty = width - filled;
         
-        print!("\r📊 进度: [{}{}] {:.1}%",
+        notice_inline!("\r📊 进度: [{}{}] {:.1}%",
             "█".repeat(filled),
             "░".repeat(empty),
             progress * 100.0
         );
-        
-        use std::io::{self, Write};
-        io::stdout().flush().unwrap();
     }
     
     /// 格式化时间长度
     fn format_duration(duration: Duration) -> String {
-        let total_seconds = duration.as_secs();
-        
-        if total_seconds >= 3600 {
-            let hours = total_seconds / 3600;
-            let minutes = (total_seconds % 3600) / 60;
-            let seconds = total_seconds % 60;
-            format!("{}:{:02}:{:02}", hours, minutes, seconds)
-        } else if total_seconds >= 60 {
-            let minutes = total_seconds / 60;
-            let seconds = total_seconds % 60;
-            format!("{}:{:02}", minutes, seconds)
-        } else {
-            format!("{}秒", total_seconds)
-        }
+        duration::format(duration)
     }
 }
 
@@ -853,9 +4008,9 @@ mod tests {
     
     #[test]
     fn test_duration_formatting() {
-        assert_eq!(CliHandler::format_duration(Duration::from_secs(30)), "30秒");
-        assert_eq!(CliHandler::format_duration(Duration::from_secs(90)), "1:30");
-        assert_eq!(CliHandler::format_duration(Duration::from_secs(3661)), "1:01:01");
+        assert_eq!(CliHandler::format_duration(Duration::from_secs(30)), "30s");
+        assert_eq!(CliHandler::format_duration(Duration::from_secs(90)), "1m30s");
+        assert_eq!(CliHandler::format_duration(Duration::from_secs(3661)), "1h1m1s");
     }
     
     #[tokio::test]
@@ -864,7 +4019,22 @@ mod tests {
         let mut handler = CliHandler::new(config);
         
         // 测试显示配置（不重置）
-        let result = handler.manage_config(false).await;
+        let result = handler.manage_config(false, None).await;
         assert!(result.is_ok());
     }
+    
+    #[test]
+    fn test_assume_yes_confirms() {
+        let mut handler = CliHandler::new(Config::default());
+        handler.set_assume_yes(true);
+        assert!(handler.confirm("确认执行?"));
+    }
+    
+    #[tokio::test]
+    async fn test_schedule_nuke_requires_local_service() {
+        let handler = CliHandler::new(Config::default());
+        let error = handler.schedule_nuke(None, Some("2h".to_string()), None).await.unwrap_err();
+        assert!(error.to_string().contains("交互模式"));
+        assert!(handler.cancel_scheduled_nuke().await.is_err());
+    }
 }
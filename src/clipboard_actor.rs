@@ -0,0 +1,223 @@
+/*!
+ * ClipVanish™ 剪贴板访问任务模块
+ *
+ * 由单一后台线程独占剪贴板后端，监听器、倒计时、粘贴处理与热键任务的全部读写都经命令通道串行执行
+ * 特点：
+ * - 同一时刻只有一个操作访问剪贴板，不会出现恢复与清除交错写入
+ * - 排队中的命令按优先级执行：紧急销毁 > 清除 > 写入 > 轮询，同一优先级按提交顺序
+ * - 多步操作（如原生清除失败后回退到写入空内容）作为一条命令整体执行
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::collections::BinaryHeap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use log::error;
+use crate::virtual_clipboard::ClipboardBackend;
+
+/// 命令优先级（声明顺序由低到高）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// 轮询读取
+    Poll,
+    /// 写入（粘贴时恢复明文、放置密文、撤销恢复）
+    Restore,
+    /// 清除（倒计时结束、手动清除、隔离未知格式）
+    Clear,
+    /// 紧急销毁与崩溃清理
+    Nuke,
+}
+
+/// 剪贴板访问任务已停止
+#[derive(Debug)]
+pub struct ActorStopped;
+
+impl std::fmt::Display for ActorStopped {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "剪贴板访问任务已停止")
+    }
+}
+
+impl std::error::Error for ActorStopped {}
+
+/// 排队中的命令
+struct Job<T> {
+    priority: Priority,
+    /// 提交序号（同一优先级先提交先执行）
+    seq: u64,
+    run: Box<dyn FnOnce(&mut T) + Send>,
+}
+
+impl<T> PartialEq for Job<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority && self.seq == other.seq
+    }
+}
+
+impl<T> Eq for Job<T> {}
+
+impl<T> PartialOrd for Job<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Job<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // 最大堆：优先级高者在前，同一优先级序号小者在前
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+/// 剪贴板访问任务的句柄（可克隆，全部句柄释放后后台线程退出）
+pub struct ClipboardActor<T: Send + 'static = ClipboardBackend> {
+    sender: mpsc::Sender<Job<T>>,
+    next_seq: Arc<AtomicU64>,
+}
+
+impl<T: Send + 'static> Clone for ClipboardActor<T> {
+    fn clone(&self) -> Self {
+        ClipboardActor {
+            sender: self.sender.clone(),
+            next_seq: self.next_seq.clone(),
+        }
+    }
+}
+
+impl<T: Send + 'static> ClipboardActor<T> {
+    /// 启动后台线程并移交剪贴板后端
+    ///
+    /// # 参数
+    /// * `resource` - 剪贴板后端
+    pub fn spawn(resource: T) -> std::io::Result<Self> {
+        let (sender, receiver) = mpsc::channel();
+        thread::Builder::new()
+            .name("clipboard-actor".to_string())
+            .spawn(move || Self::work(resource, receiver))?;
+        Ok(ClipboardActor { sender, next_seq: Arc::new(AtomicU64::new(0)) })
+    }
+
+    /// 提交命令并等待执行结果
+    ///
+    /// 命令中不能再次调用同一任务，否则会互相等待
+    ///
+    /// # 参数
+    /// * `priority` - 优先级
+    /// * `job` - 在后台线程中执行的操作
+    pub fn run<R, F>(&self, priority: Priority, job: F) -> Result<R, ActorStopped>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut T) -> R + Send + 'static,
+    {
+        self.submit(priority, job)?.recv().map_err(|_| ActorStopped)
+    }
+
+    /// 提交命令并最多等待 `timeout`（崩溃清理时使用，避免在任务卡住时挂起）
+    ///
+    /// # 返回值
+    /// * `Option<R>` - 超时或任务已停止时为None（命令仍可能稍后执行）
+    pub fn run_timeout<R, F>(&self, priority: Priority, timeout: Duration, job: F) -> Option<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut T) -> R + Send + 'static,
+    {
+        self.submit(priority, job).ok()?.recv_timeout(timeout).ok()
+    }
+
+    fn submit<R, F>(&self, priority: Priority, job: F) -> Result<mpsc::Receiver<R>, ActorStopped>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut T) -> R + Send + 'static,
+    {
+        let (reply, result) = mpsc::sync_channel(1);
+        let job = Job {
+            priority,
+            seq: self.next_seq.fetch_add(1, Ordering::Relaxed),
+            run: Box::new(move |resource: &mut T| {
+                let _ = reply.send(job(resource));
+            }),
+        };
+        self.sender.send(job).map_err(|_| ActorStopped)?;
+        Ok(result)
+    }
+
+    /// 后台线程：收取已到达的全部命令，每次执行优先级最高的一条
+    fn work(mut resource: T, receiver: mpsc::Receiver<Job<T>>) {
+        let mut queue = BinaryHeap::new();
+        loop {
+            if queue.is_empty() {
+                match receiver.recv() {
+                    Ok(job) => queue.push(job),
+                    Err(_) => return,
+                }
+            }
+            queue.extend(receiver.try_iter());
+
+            if let Some(job) = queue.pop() {
+                // 单条命令panic不影响后续命令（等待结果的一方会收到任务已停止）
+                if panic::catch_unwind(AssertUnwindSafe(|| (job.run)(&mut resource))).is_err() {
+                    error!("剪贴板访问命令执行时发生panic（优先级 {:?}）", job.priority);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Barrier;
+
+    #[test]
+    fn test_queued_commands_run_by_priority() {
+        let actor = ClipboardActor::spawn(Vec::<&'static str>::new()).unwrap();
+
+        // 第一条命令阻塞后台线程，期间提交的命令全部排队
+        let barrier = Arc::new(Barrier::new(2));
+        let blocker = {
+            let actor = actor.clone();
+            let barrier = barrier.clone();
+            thread::spawn(move || actor.run(Priority::Poll, move |log| {
+                barrier.wait();
+                thread::sleep(Duration::from_millis(100));
+                log.push("blocker");
+            }))
+        };
+        barrier.wait();
+
+        let submitted: Vec<_> = [
+            (Priority::Poll, "poll"),
+            (Priority::Restore, "restore"),
+            (Priority::Clear, "clear-1"),
+            (Priority::Nuke, "nuke"),
+            (Priority::Clear, "clear-2"),
+        ]
+        .into_iter()
+        .map(|(priority, name)| actor.submit(priority, move |log: &mut Vec<&'static str>| log.push(name)).unwrap())
+        .collect();
+
+        blocker.join().unwrap().unwrap();
+        for result in submitted {
+            result.recv().unwrap();
+        }
+        assert_eq!(
+            actor.run(Priority::Poll, |log| log.clone()).unwrap(),
+            ["blocker", "nuke", "clear-1", "clear-2", "restore", "poll"]
+        );
+    }
+
+    #[test]
+    fn test_panicking_command_does_not_stop_actor() {
+        let actor = ClipboardActor::spawn(0u32).unwrap();
+        assert!(actor.run(Priority::Clear, |_| panic!("boom")).is_err());
+        assert_eq!(actor.run(Priority::Poll, |count| { *count += 1; *count }).unwrap(), 1);
+        assert_eq!(actor.run_timeout(Priority::Nuke, Duration::from_secs(1), |count| *count), Some(1));
+    }
+}
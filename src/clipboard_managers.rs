@@ -0,0 +1,498 @@
+/*!
+ * ClipVanish™ 剪贴板管理器协作模块
+ *
+ * CopyQ、Maccy等剪贴板管理器会把每一次复制写入自己的历史，受保护内容的明文（以及粘贴时解密的明文）因此留在其中。
+ * 受保护条目出现时，通过各管理器自己的接口要求它删除该条目或暂停记录；条目结束后再次清理并恢复记录
+ * 特点：
+ * - CopyQ：`copyq eval -` 按SHA-256删除历史中的明文（脚本经stdin传入），或以 `copyq disable`/`enable` 暂停记录
+ * - Maccy（macOS）：写入其 `ignoreEvents` 设置暂停记录，条目结束后恢复原值；Maccy没有删除单条记录的接口
+ * - 其他管理器通过钩子程序接入：每个事件以一行JSON写入钩子的stdin（协议见 docs/clipboard-manager-integration.md）
+ * - 所有操作在单独的线程中按顺序执行，不拖慢拦截；管理器未运行时跳过
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use crate::audit::Subscription;
+use crate::editor;
+use crate::helpers;
+use crate::item_id::ItemId;
+
+/// 受保护后等待管理器记录原始复制的时长（之后再删除，避免删除早于记录）
+const SWEEP_DELAY: Duration = Duration::from_millis(500);
+
+/// 关闭时等待恢复完成的最长时间
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 对单个管理器采取的操作
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ManagerMode {
+    /// 不协作
+    Off,
+    /// 条目受保护期间暂停记录（原始复制可能已被记录）
+    Ignore,
+    /// 从历史中删除受保护内容，条目结束时再次删除（含粘贴时解密后被记录的明文）
+    Delete,
+}
+
+impl fmt::Display for ManagerMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManagerMode::Off => write!(f, "off"),
+            ManagerMode::Ignore => write!(f, "ignore"),
+            ManagerMode::Delete => write!(f, "delete"),
+        }
+    }
+}
+
+/// 钩子程序（实现集成协议的其他剪贴板管理器）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManagerHook {
+    /// 名称（用于日志）
+    pub name: String,
+    /// 程序路径
+    pub program: String,
+    /// 参数
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+/// 剪贴板管理器协作配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClipboardManagersConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// CopyQ
+    pub copyq: ManagerMode,
+    /// Maccy（只支持 off 与 ignore）
+    pub maccy: ManagerMode,
+    /// 钩子程序
+    pub hooks: Vec<ManagerHook>,
+}
+
+impl Default for ClipboardManagersConfig {
+    fn default() -> Self {
+        ClipboardManagersConfig {
+            enabled: true,
+            copyq: ManagerMode::Delete,
+            maccy: ManagerMode::Ignore,
+            hooks: Vec::new(),
+        }
+    }
+}
+
+/// 写入钩子程序stdin的事件（每个事件一行JSON）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ManagerEvent {
+    /// 条目受保护：删除历史中SHA-256相同的条目，并在结束前不再记录
+    Protected {
+        /// 条目ID
+        item: ItemId,
+        /// 内容的SHA-256（十六进制小写）
+        sha256: String,
+    },
+    /// 条目结束：恢复记录；`purge` 为true时再次删除SHA-256相同的条目
+    Released {
+        /// 条目ID
+        item: ItemId,
+        /// 内容的SHA-256（十六进制小写）
+        sha256: String,
+        /// 是否删除（解除保护时为false，内容作为普通内容保留）
+        purge: bool,
+    },
+}
+
+/// 协作线程的任务
+enum Job {
+    /// 管理器事件（条目被取代时不恢复记录，紧接着的新条目仍需暂停）
+    Event {
+        /// 事件
+        event: ManagerEvent,
+        /// 提交时的配置
+        config: ClipboardManagersConfig,
+        /// 执行后是否恢复暂停的记录
+        resume: bool,
+    },
+    /// 恢复全部暂停的记录（完成后应答）
+    Resume(Sender<()>),
+}
+
+/// 当前受保护条目
+struct ActiveItem {
+    /// 条目ID
+    item: ItemId,
+    /// 内容的SHA-256
+    sha256: String,
+}
+
+/// 剪贴板管理器协作
+///
+/// 由监听器在条目受保护时通知，经审计事件总线得知条目结束
+pub struct ManagerBridge {
+    /// 配置（可重新加载）
+    config: Mutex<ClipboardManagersConfig>,
+    /// 当前受保护条目
+    active: Mutex<Option<ActiveItem>>,
+    /// 协作线程（启动后才有）
+    worker: Mutex<Option<Sender<Job>>>,
+}
+
+impl ManagerBridge {
+    /// 创建协作实例（启动前的通知被忽略）
+    ///
+    /// # 参数
+    /// * `config` - 配置
+    pub fn new(config: ClipboardManagersConfig) -> Self {
+        ManagerBridge { config: Mutex::new(config), active: Mutex::new(None), worker: Mutex::new(None) }
+    }
+
+    /// 更新配置
+    ///
+    /// # 参数
+    /// * `config` - 新配置
+    pub fn set_config(&self, config: ClipboardManagersConfig) {
+        *self.config.lock().unwrap() = config;
+    }
+
+    /// 启动协作线程
+    pub fn start(&self) {
+        let mut worker = self.worker.lock().unwrap();
+        if worker.is_none() {
+            let (sender, receiver) = mpsc::channel();
+            std::thread::spawn(move || work(receiver));
+            *worker = Some(sender);
+        }
+    }
+
+    /// 提交任务（未启动或未启用时忽略）
+    fn submit(&self, event: ManagerEvent, resume: bool) {
+        let config = self.config.lock().unwrap().clone();
+        if !config.enabled {
+            return;
+        }
+        if let Some(worker) = self.worker.lock().unwrap().as_ref() {
+            let _ = worker.send(Job::Event { event, config, resume });
+        }
+    }
+
+    /// 条目受保护（同一条目内容更新时同样调用）
+    ///
+    /// 此前的条目视为被取代：删除其内容但不恢复记录
+    ///
+    /// # 参数
+    /// * `item` - 条目ID
+    /// * `content` - 受保护的内容
+    pub fn protected(&self, item: ItemId, content: &str) {
+        if self.worker.lock().unwrap().is_none() {
+            return;
+        }
+        let sha256 = editor::sha256_hex(content);
+        let previous = self.active.lock().unwrap().replace(ActiveItem { item, sha256: sha256.clone() });
+        if let Some(previous) = previous {
+            self.submit(ManagerEvent::Released { item: previous.item, sha256: previous.sha256, purge: true }, false);
+        }
+        self.submit(ManagerEvent::Protected { item, sha256 }, false);
+    }
+
+    /// 条目结束（不是当前条目时忽略）
+    ///
+    /// # 参数
+    /// * `item` - 条目ID
+    /// * `purge` - 是否删除管理器中的内容
+    pub fn released(&self, item: ItemId, purge: bool) {
+        let released = {
+            let mut active = self.active.lock().unwrap();
+            match active.as_ref() {
+                Some(current) if current.item == item => active.take(),
+                _ => None,
+            }
+        };
+        if let Some(released) = released {
+            self.submit(ManagerEvent::Released { item, sha256: released.sha256, purge }, true);
+        }
+    }
+
+    /// 跟随审计事件：条目被销毁时通知管理器（解除保护时不删除）
+    ///
+    /// # 参数
+    /// * `events` - 审计事件订阅
+    pub async fn run(self: Arc<Self>, mut events: Subscription) {
+        loop {
+            let entry = match events.recv().await {
+                Ok(entry) => entry,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("剪贴板管理器协作落后，跳过 {} 条审计事件", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => return,
+            };
+            let Some(item) = self.active.lock().unwrap().as_ref().map(|active| active.item) else {
+                continue;
+            };
+            if let Some(reason) = editor::destroyed_reason(&entry, item) {
+                self.released(item, reason != "解除保护");
+            }
+        }
+    }
+
+    /// 关闭时结束当前条目并恢复全部暂停的记录
+    pub fn shutdown(&self) -> Result<(), String> {
+        if let Some(active) = self.active.lock().unwrap().take() {
+            self.submit(ManagerEvent::Released { item: active.item, sha256: active.sha256, purge: true }, true);
+        }
+        let Some(worker) = self.worker.lock().unwrap().clone() else {
+            return Ok(());
+        };
+        let (done, finished) = mpsc::channel();
+        worker.send(Job::Resume(done)).map_err(|_| "剪贴板管理器协作线程已退出".to_string())?;
+        finished.recv_timeout(SHUTDOWN_TIMEOUT).map_err(|_| "等待剪贴板管理器恢复记录超时".to_string())
+    }
+}
+
+/// 已暂停记录、条目结束后需要恢复的管理器
+#[derive(Debug, Default)]
+struct Paused {
+    /// 暂停前CopyQ正在记录
+    copyq: bool,
+    /// 暂停前Maccy的 `ignoreEvents`（None表示未暂停）
+    maccy: Option<Option<bool>>,
+}
+
+/// 协作线程：按顺序执行任务
+fn work(jobs: Receiver<Job>) {
+    let mut paused = Paused::default();
+    for job in jobs {
+        match job {
+            Job::Event { event, config, resume: resume_after } => {
+                if matches!(event, ManagerEvent::Protected { .. }) {
+                    std::thread::sleep(SWEEP_DELAY);
+                }
+                apply(&event, &config, &mut paused);
+                if resume_after {
+                    resume(&mut paused);
+                }
+                for hook in &config.hooks {
+                    if let Err(e) = notify_hook(hook, &event) {
+                        warn!("剪贴板管理器钩子 {} 执行失败: {}", hook.name, e);
+                    }
+                }
+            }
+            Job::Resume(done) => {
+                resume(&mut paused);
+                let _ = done.send(());
+            }
+        }
+    }
+}
+
+/// 对内置支持的管理器执行事件
+fn apply(event: &ManagerEvent, config: &ClipboardManagersConfig, paused: &mut Paused) {
+    match event {
+        ManagerEvent::Protected { sha256, .. } => {
+            if config.copyq != ManagerMode::Off && copyq::running() {
+                if config.copyq == ManagerMode::Delete {
+                    report("CopyQ删除受保护内容", copyq::delete(sha256));
+                }
+                if !paused.copyq && copyq::monitoring() == Some(true) {
+                    report("CopyQ暂停记录", copyq::set_monitoring(false));
+                    paused.copyq = true;
+                }
+            }
+            if config.maccy == ManagerMode::Ignore && paused.maccy.is_none() && maccy::installed() {
+                match maccy::ignore_events() {
+                    Ok(previous) => {
+                        report("Maccy暂停记录", maccy::set_ignore_events(Some(true)));
+                        paused.maccy = Some(previous);
+                    }
+                    Err(e) => warn!("读取Maccy设置失败: {}", e),
+                }
+            }
+        }
+        ManagerEvent::Released { sha256, purge, .. } => {
+            if *purge && config.copyq == ManagerMode::Delete && copyq::running() {
+                report("CopyQ删除受保护内容", copyq::delete(sha256));
+            }
+        }
+    }
+}
+
+/// 恢复暂停的记录
+fn resume(paused: &mut Paused) {
+    if std::mem::take(&mut paused.copyq) {
+        report("CopyQ恢复记录", copyq::set_monitoring(true));
+    }
+    if let Some(previous) = paused.maccy.take() {
+        report("Maccy恢复记录", maccy::set_ignore_events(previous));
+    }
+}
+
+/// 记录操作结果
+fn report(action: &str, result: Result<(), String>) {
+    match result {
+        Ok(()) => info!("{}", action),
+        Err(e) => warn!("{}失败: {}", action, e),
+    }
+}
+
+/// 向钩子程序写入一行事件
+fn notify_hook(hook: &ManagerHook, event: &ManagerEvent) -> Result<(), String> {
+    let mut line = serde_json::to_vec(event).map_err(|e| e.to_string())?;
+    line.push(b'\n');
+    let args: Vec<&str> = hook.args.iter().map(String::as_str).collect();
+    let status = helpers::run_with_stdin(&hook.program, &args, &line)?;
+    if status.success() {
+        debug!("剪贴板管理器钩子 {} 已处理事件", hook.name);
+        Ok(())
+    } else {
+        Err(format!("退出码: {:?}", status.code()))
+    }
+}
+
+/// CopyQ（命令行接口）
+mod copyq {
+    use crate::helpers;
+
+    /// 删除历史中SHA-256相同的文本条目的脚本（经stdin传入，不出现在进程参数中）
+    pub(super) fn delete_script(sha256: &str) -> String {
+        format!(
+            "var hash = '{}';\n\
+             for (var row = size() - 1; row >= 0; --row) {{\n    \
+                 if (str(sha256sum(read(mimeText, row))) === hash)\n        \
+                     remove(row);\n\
+             }}\n",
+            sha256
+        )
+    }
+
+    /// CopyQ服务是否在运行
+    pub(super) fn running() -> bool {
+        monitoring().is_some()
+    }
+
+    /// CopyQ是否正在记录剪贴板（未运行时返回None）
+    pub(super) fn monitoring() -> Option<bool> {
+        let output = helpers::output("copyq", &["monitoring"]).ok()?;
+        output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim() == "true")
+    }
+
+    /// 开始或暂停记录剪贴板
+    pub(super) fn set_monitoring(enabled: bool) -> Result<(), String> {
+        let output = helpers::output("copyq", &[if enabled { "enable" } else { "disable" }])?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
+    }
+
+    /// 删除历史中SHA-256相同的条目
+    pub(super) fn delete(sha256: &str) -> Result<(), String> {
+        let status = helpers::run_with_stdin("copyq", &["eval", "-"], delete_script(sha256).as_bytes())?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(format!("copyq退出码: {:?}", status.code()))
+        }
+    }
+}
+
+/// Maccy（通过其 `ignoreEvents` 设置暂停记录）
+mod maccy {
+    /// Maccy的设置域
+    #[cfg(target_os = "macos")]
+    const DOMAIN: &str = "org.p0deje.Maccy";
+
+    /// 是否安装了Maccy
+    #[cfg(target_os = "macos")]
+    pub(super) fn installed() -> bool {
+        std::path::Path::new("/Applications/Maccy.app").exists()
+    }
+
+    /// 是否安装了Maccy
+    #[cfg(not(target_os = "macos"))]
+    pub(super) fn installed() -> bool {
+        false
+    }
+
+    /// 读取 `ignoreEvents`（未设置时返回None）
+    #[cfg(target_os = "macos")]
+    pub(super) fn ignore_events() -> Result<Option<bool>, String> {
+        let output = crate::helpers::output("defaults", &["read", DOMAIN, "ignoreEvents"])?;
+        Ok(output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim() == "1"))
+    }
+
+    /// 读取 `ignoreEvents`
+    #[cfg(not(target_os = "macos"))]
+    pub(super) fn ignore_events() -> Result<Option<bool>, String> {
+        Ok(None)
+    }
+
+    /// 设置或删除 `ignoreEvents`
+    #[cfg(target_os = "macos")]
+    pub(super) fn set_ignore_events(value: Option<bool>) -> Result<(), String> {
+        let output = match value {
+            Some(value) => crate::helpers::output("defaults", &["write", DOMAIN, "ignoreEvents", "-bool", if value { "true" } else { "false" }])?,
+            None => crate::helpers::output("defaults", &["delete", DOMAIN, "ignoreEvents"])?,
+        };
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
+    }
+
+    /// 设置或删除 `ignoreEvents`
+    #[cfg(not(target_os = "macos"))]
+    pub(super) fn set_ignore_events(_value: Option<bool>) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_contract() {
+        let item = ItemId::generate();
+        let sha256 = editor::sha256_hex("hunter2");
+        let protected = serde_json::to_value(ManagerEvent::Protected { item, sha256: sha256.clone() }).unwrap();
+        assert_eq!(protected, serde_json::json!({ "event": "protected", "item": item.to_string(), "sha256": sha256 }));
+
+        let released = serde_json::to_value(ManagerEvent::Released { item, sha256, purge: false }).unwrap();
+        assert_eq!(released["event"], "released");
+        assert_eq!(released["purge"], false);
+
+        let script = copyq::delete_script(&editor::sha256_hex("hunter2"));
+        assert!(script.contains(&editor::sha256_hex("hunter2")));
+        assert!(!script.contains("hunter2'"));
+    }
+
+    #[test]
+    fn test_superseded_item_is_purged() {
+        let bridge = ManagerBridge::new(ClipboardManagersConfig { enabled: false, ..Default::default() });
+        let (first, second) = (ItemId::generate(), ItemId::generate());
+
+        // 未启动时不跟踪条目
+        bridge.protected(first, "secret one");
+        assert!(bridge.active.lock().unwrap().is_none());
+
+        bridge.start();
+        bridge.protected(first, "secret one");
+        bridge.protected(second, "secret two");
+        bridge.released(first, true);
+        assert_eq!(bridge.active.lock().unwrap().as_ref().map(|active| active.item), Some(second));
+        bridge.released(second, true);
+        assert!(bridge.active.lock().unwrap().is_none());
+        assert!(bridge.shutdown().is_ok());
+    }
+}
@@ -0,0 +1,102 @@
+/*!
+ * ClipVanish™ 时间源模块
+ *
+ * 定时器、剪贴板监听器与历史记录过期统一从注入的时钟读取当前时间
+ * 特点：
+ * - 正式构建使用单调时钟，不受系统时间调整影响
+ * - 测试可使用手动推进的模拟时钟，或跟随tokio暂停时间的时钟，无需真实等待
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 时间源
+pub trait Clock: Send + Sync {
+    /// 当前时间
+    fn now(&self) -> Instant;
+
+    /// 自 `since` 起经过的时间（`since` 晚于当前时间时为零）
+    fn elapsed(&self, since: Instant) -> Duration {
+        self.now().saturating_duration_since(since)
+    }
+}
+
+/// 共享的时间源
+pub type SharedClock = Arc<dyn Clock>;
+
+/// 单调时钟
+pub struct MonotonicClock;
+
+impl Clock for MonotonicClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// 正式构建使用的时间源
+pub fn monotonic() -> SharedClock {
+    Arc::new(MonotonicClock)
+}
+
+/// 跟随tokio时间的时钟（配合 `tokio::time::pause` 使用）
+#[cfg(test)]
+pub struct TokioClock;
+
+#[cfg(test)]
+impl Clock for TokioClock {
+    fn now(&self) -> Instant {
+        tokio::time::Instant::now().into_std()
+    }
+}
+
+/// 手动推进的模拟时钟
+#[cfg(test)]
+pub struct MockClock {
+    start: Instant,
+    offset: std::sync::Mutex<Duration>,
+}
+
+#[cfg(test)]
+impl MockClock {
+    pub fn new() -> Arc<Self> {
+        Arc::new(MockClock { start: Instant::now(), offset: std::sync::Mutex::new(Duration::ZERO) })
+    }
+
+    /// 推进时间
+    pub fn advance(&self, duration: Duration) {
+        *self.offset.lock().unwrap() += duration;
+    }
+}
+
+#[cfg(test)]
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.start + *self.offset.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_clock_advances_only_on_demand() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        assert_eq!(clock.elapsed(start), Duration::ZERO);
+
+        clock.advance(Duration::from_secs(90));
+        assert_eq!(clock.elapsed(start), Duration::from_secs(90));
+        assert_eq!(clock.elapsed(clock.now() + Duration::from_secs(1)), Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_tokio_clock_follows_paused_time() {
+        let clock = TokioClock;
+        let start = clock.now();
+        tokio::time::sleep(Duration::from_secs(3600)).await;
+        assert_eq!(clock.elapsed(start), Duration::from_secs(3600));
+    }
+}
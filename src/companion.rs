@@ -0,0 +1,184 @@
+/*!
+ * ClipVanish™ 配套GUI交接模块
+ *
+ * 配套图形界面通过控制接口一次性取得受保护内容：先申请一次性交接令牌，
+ * 再提交自己的临时公钥，服务返回加密给该公钥的内容
+ * 特点：
+ * - 交接令牌只能使用一次，30秒后过期，且只能由申请它的控制令牌兑现
+ * - 内容使用X25519临时密钥协商 + AES-256-GCM-SIV 加密，套接字上从不出现明文
+ * - 交接视同一次粘贴：密钥重置，粘贴倒计时照常启动
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use aes_gcm_siv::aead::OsRng;
+use rand::RngCore;
+use serde::Serialize;
+use crate::escrow::{self, EscrowError};
+use crate::encoding::encode_hex;
+
+/// 交接令牌有效期
+pub const HANDOFF_TOKEN_TTL: Duration = Duration::from_secs(30);
+
+/// 交接内容使用的密钥推导标签（与托管封装区分，密文不能跨用途解开）
+pub const HANDOFF_LABEL: &[u8] = b"clipvanish-handoff-v1";
+
+/// 交接错误类型
+#[derive(Debug)]
+pub enum HandoffError {
+    /// 交接令牌无效、已使用或不属于当前控制令牌
+    InvalidToken,
+    /// 交接令牌已过期
+    Expired,
+    /// 图形界面提供的公钥无效
+    InvalidKey(String),
+    /// 加密失败
+    SealFailed(String),
+}
+
+impl std::fmt::Display for HandoffError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HandoffError::InvalidToken => write!(f, "交接令牌无效或已使用"),
+            HandoffError::Expired => write!(f, "交接令牌已过期"),
+            HandoffError::InvalidKey(msg) => write!(f, "交接公钥无效: {}", msg),
+            HandoffError::SealFailed(msg) => write!(f, "交接内容加密失败: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for HandoffError {}
+
+/// 已签发的交接令牌
+struct IssuedToken {
+    /// 申请方的控制令牌名称
+    owner: String,
+    /// 签发时间
+    issued_at: Instant,
+}
+
+/// 一次性交接令牌登记表
+#[derive(Default)]
+pub struct HandoffTokens {
+    /// 尚未兑现的令牌
+    issued: Mutex<HashMap<String, IssuedToken>>,
+}
+
+impl HandoffTokens {
+    /// 签发交接令牌
+    ///
+    /// # 参数
+    /// * `owner` - 申请方的控制令牌名称
+    ///
+    /// # 返回值
+    /// * `String` - 一次性交接令牌
+    pub fn issue(&self, owner: &str) -> String {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        let token = encode_hex(&bytes);
+
+        let mut issued = self.issued.lock().unwrap();
+        // 顺带清理过期令牌，登记表不会无限增长
+        issued.retain(|_, entry| entry.issued_at.elapsed() < HANDOFF_TOKEN_TTL);
+        issued.insert(token.clone(), IssuedToken { owner: owner.to_string(), issued_at: Instant::now() });
+        token
+    }
+
+    /// 兑现交接令牌（无论成功与否，令牌都会被作废）
+    ///
+    /// # 参数
+    /// * `token` - 交接令牌
+    /// * `owner` - 兑现方的控制令牌名称
+    pub fn redeem(&self, token: &str, owner: &str) -> Result<(), HandoffError> {
+        let entry = self.issued.lock().unwrap().remove(token).ok_or(HandoffError::InvalidToken)?;
+        if entry.owner != owner {
+            return Err(HandoffError::InvalidToken);
+        }
+        if entry.issued_at.elapsed() >= HANDOFF_TOKEN_TTL {
+            return Err(HandoffError::Expired);
+        }
+        Ok(())
+    }
+}
+
+/// 图形界面的临时公钥
+pub struct HandoffRecipient {
+    /// X25519公钥
+    public_key: [u8; 32],
+}
+
+impl HandoffRecipient {
+    /// 从十六进制公钥创建（解密内容之前校验，避免白白消耗一次粘贴）
+    pub fn from_hex(text: &str) -> Result<Self, HandoffError> {
+        escrow::parse_key(text)
+            .map(|public_key| HandoffRecipient { public_key })
+            .map_err(|e| HandoffError::InvalidKey(e.to_string()))
+    }
+
+    /// 将内容加密给图形界面
+    pub fn seal(&self, content: &str) -> Result<SealedContent, HandoffError> {
+        let (ephemeral_public, sealed) = escrow::seal(&self.public_key, content.as_bytes(), HANDOFF_LABEL)
+            .map_err(|e| match e {
+                EscrowError::InvalidKey(msg) => HandoffError::InvalidKey(msg),
+                other => HandoffError::SealFailed(other.to_string()),
+            })?;
+
+        Ok(SealedContent {
+            ephemeral_public_key: encode_hex(&ephemeral_public),
+            ciphertext: sealed.to_base64(),
+        })
+    }
+}
+
+/// 加密给图形界面的内容
+#[derive(Debug, Serialize)]
+pub struct SealedContent {
+    /// 服务端临时公钥（十六进制）
+    pub ephemeral_public_key: String,
+    /// 密文（Base64，含nonce）
+    pub ciphertext: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::EncryptedData;
+
+    #[test]
+    fn test_tokens_are_single_use_and_bound() {
+        let tokens = HandoffTokens::default();
+
+        let token = tokens.issue("gui");
+        assert!(tokens.redeem(&token, "gui").is_ok());
+        assert!(matches!(tokens.redeem(&token, "gui"), Err(HandoffError::InvalidToken)));
+
+        // 其他控制令牌不能兑现，且尝试后令牌作废
+        let token = tokens.issue("gui");
+        assert!(matches!(tokens.redeem(&token, "script"), Err(HandoffError::InvalidToken)));
+        assert!(tokens.redeem(&token, "gui").is_err());
+
+        let token = tokens.issue("gui");
+        tokens.issued.lock().unwrap().get_mut(&token).unwrap().issued_at -= HANDOFF_TOKEN_TTL;
+        assert!(matches!(tokens.redeem(&token, "gui"), Err(HandoffError::Expired)));
+    }
+
+    #[test]
+    fn test_sealed_content_opens_with_gui_key() {
+        // RFC 7748 测试向量中的密钥对
+        let secret = escrow::parse_key("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a").unwrap();
+        let recipient =
+            HandoffRecipient::from_hex("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a").unwrap();
+
+        let sealed = recipient.seal("hunter2").unwrap();
+        let ephemeral = escrow::parse_key(&sealed.ephemeral_public_key).unwrap();
+        let data = EncryptedData::from_base64(&sealed.ciphertext).unwrap();
+        assert_eq!(escrow::open(&secret, &ephemeral, &data, HANDOFF_LABEL).unwrap(), b"hunter2");
+
+        // 托管标签无法解开交接密文
+        assert!(escrow::open(&secret, &ephemeral, &data, b"clipvanish-escrow-kek").is_err());
+        assert!(HandoffRecipient::from_hex("zz").is_err());
+    }
+}
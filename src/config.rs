@@ -7,15 +7,55 @@
  * - 默认配置自动生成
  * - 配置验证和错误处理
  * - 跨平台配置目录支持
+ * - 原子写入（临时文件+重命名），保留带时间戳的备份
  *
  * 作者: ClipVanish Team
  */
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 use log::{info, warn, debug, error};
+use regex::Regex;
+use crate::entropy::EntropyRuleConfig;
+use crate::escrow::EscrowConfig;
+use crate::rule_pack::RulePackConfig;
+use crate::learning::LearningConfig;
+use crate::memory::{self, ErasePattern};
+use crate::rule_trial::RuleTrialConfig;
+use crate::overlay::OverlayConfig;
+use crate::paste_confirm::PasteConfirmConfig;
+use crate::escalation::EscalationConfig;
+use crate::lifetime_cap::LifetimeCapConfig;
+use crate::login_pages::LoginPagesConfig;
+use crate::notifications::NotificationsConfig;
+use crate::webhook::WebhookConfig;
+use crate::mqtt::MqttConfig;
+use crate::clipboard_managers::{ClipboardManagersConfig, ManagerMode};
+use crate::canary::CanaryConfig;
+use crate::paste_strategy::{PasteStrategy, PasteStrategyConfig};
+use crate::paste_transform::PasteTransformConfig;
+use crate::decoy::{self, DecoyConfig};
+use crate::nuke_guard::{self, NukeGuardConfig};
+use crate::ipc_security::IpcSecurityConfig;
+use crate::status_page::StatusPageConfig;
+use crate::shutdown::ShutdownConfig;
+use crate::health::HealthConfig;
+use crate::safe_mode::SafeModeConfig;
+use crate::encoding::decode_hex;
+use crate::output::notice;
+use crate::paste_context::PasteContextConfig;
+use crate::paste_gestures::PasteGesturesConfig;
+use crate::policy::{ManagedPolicy, PolicyReport};
+use crate::overrides::{self, OverrideError};
+
+/// 保留的配置备份数量
+const MAX_CONFIG_BACKUPS: usize = 5;
+
+/// 配置无效时的退出码（sysexits.h 中的 EX_CONFIG）
+pub const EXIT_INVALID_CONFIG: i32 = 78;
 
 /// 配置错误类型
 #[derive(Debug)]
@@ -28,6 +68,8 @@ pub enum ConfigError {
     ParseError(serde_json::Error),
     /// 配置验证失败
     ValidationError(String),
+    /// 配置项取值无效（包含全部违规项）
+    Invalid(Vec<Violation>),
     /// 配置目录创建失败
     DirectoryCreationError(std::io::Error),
 }
@@ -39,6 +81,16 @@ impl std::fmt::Display for ConfigError {
             ConfigError::FileWriteError(e) => write!(f, "配置文件写入失败: {}", e),
             ConfigError::ParseError(e) => write!(f, "配置文件解析失败: {}", e),
             ConfigError::ValidationError(msg) => write!(f, "配置验证失败: {}", msg),
+            ConfigError::Invalid(violations) => {
+                write!(f, "配置验证失败（{} 项）: ", violations.len())?;
+                for (index, violation) in violations.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, "；")?;
+                    }
+                    write!(f, "{}", violation)?;
+                }
+                Ok(())
+            }
             ConfigError::DirectoryCreationError(e) => write!(f, "配置目录创建失败: {}", e),
         }
     }
@@ -46,6 +98,91 @@ impl std::fmt::Display for ConfigError {
 
 impl std::error::Error for ConfigError {}
 
+/// 单个配置违规项
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Violation {
+    /// 配置项路径（与 `--set` 使用的键一致，如 `timer.min_countdown`）
+    pub path: String,
+    /// 期望的取值范围或格式
+    pub expected: String,
+    /// 实际值
+    pub actual: String,
+}
+
+impl Violation {
+    fn new(path: impl Into<String>, expected: impl std::fmt::Display, actual: impl std::fmt::Display) -> Self {
+        Violation { path: path.into(), expected: expected.to_string(), actual: actual.to_string() }
+    }
+
+    /// 正则表达式无法编译（只保留错误信息的最后一行）
+    fn regex(path: &str, pattern: &str, error: &regex::Error) -> Self {
+        let error = error.to_string();
+        let reason = error.lines().last().unwrap_or_default().trim();
+        Violation::new(path, "有效的正则表达式", format!("{:?}（{}）", pattern, reason))
+    }
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: 期望 {}，实际为 {}", self.path, self.expected, self.actual)
+    }
+}
+
+/// 检查热键字符串（如 `Ctrl+Alt+V`）：至少一个修饰键加一个按键
+///
+/// # 返回值
+/// * `Result<(), String>` - 无效时返回原因
+pub fn check_hotkey(text: &str) -> Result<(), String> {
+    const MODIFIERS: [&str; 8] = ["ctrl", "control", "alt", "option", "shift", "cmd", "command", "super"];
+    const NAMED_KEYS: [&str; 16] = [
+        "space", "enter", "tab", "esc", "escape", "delete", "backspace", "insert",
+        "home", "end", "pageup", "pagedown", "up", "down", "left", "right",
+    ];
+
+    let parts: Vec<String> = text.split('+').map(|part| part.trim().to_ascii_lowercase()).collect();
+    if parts.iter().any(|part| part.is_empty()) {
+        return Err("存在空的组合键".to_string());
+    }
+
+    let (key, modifiers) = parts.split_last().ok_or("热键为空")?;
+    if modifiers.is_empty() {
+        return Err("缺少修饰键".to_string());
+    }
+    if let Some(unknown) = modifiers.iter().find(|m| !MODIFIERS.contains(&m.as_str())) {
+        return Err(format!("未知的修饰键 {}", unknown));
+    }
+
+    let is_function_key = key
+        .strip_prefix('f')
+        .and_then(|n| n.parse::<u8>().ok())
+        .is_some_and(|n| (1..=24).contains(&n));
+    let is_single_char = key.chars().count() == 1 && key.chars().all(|c| c.is_ascii_alphanumeric());
+    if !(is_single_char || is_function_key || NAMED_KEYS.contains(&key.as_str())) {
+        return Err(format!("未知的按键 {}", key));
+    }
+    Ok(())
+}
+
+/// 倒计时锚点：决定自毁倒计时从何时开始
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CountdownAnchor {
+    /// 复制时立即开始倒计时
+    #[default]
+    Copy,
+    /// 首次粘贴时才开始倒计时（超过最长武装时间仍会清除）
+    Paste,
+}
+
+impl std::fmt::Display for CountdownAnchor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CountdownAnchor::Copy => write!(f, "复制时开始"),
+            CountdownAnchor::Paste => write!(f, "首次粘贴时开始"),
+        }
+    }
+}
+
 /// 定时器配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimerConfig {
@@ -59,6 +196,19 @@ pub struct TimerConfig {
     pub enable_warnings: bool,
     /// 提醒阈值（秒）- 剩余时间少于此值时开始提醒
     pub warning_threshold: u64,
+    /// 倒计时锚点
+    #[serde(default)]
+    pub anchor: CountdownAnchor,
+    /// 粘贴锚点模式下等待首次粘贴的最长时间（秒），超时后直接清除
+    #[serde(default = "default_max_armed_seconds")]
+    pub max_armed_seconds: u64,
+    /// 倒计时清除后允许撤销的宽限期（秒），0表示不允许撤销
+    #[serde(default)]
+    pub undo_grace_seconds: u64,
+}
+
+fn default_max_armed_seconds() -> u64 {
+    300
 }
 
 impl Default for TimerConfig {
@@ -69,6 +219,9 @@ impl Default for TimerConfig {
             max_countdown: 3600, // 1小时
             enable_warnings: true,
             warning_threshold: 10,
+            anchor: CountdownAnchor::Copy,
+            max_armed_seconds: default_max_armed_seconds(),
+            undo_grace_seconds: 0,
         }
     }
 }
@@ -80,6 +233,9 @@ pub struct SecurityConfig {
     pub enable_memory_locking: bool,
     /// 内存擦除轮数
     pub memory_erase_rounds: u32,
+    /// 每轮擦除使用的覆盖模式（zeros/ones/random，轮数多于模式数时循环使用）
+    #[serde(default = "memory::default_erase_patterns")]
+    pub memory_erase_patterns: Vec<ErasePattern>,
     /// 是否在程序退出时自动清除剪贴板
     pub auto_clear_on_exit: bool,
     /// 是否启用加密密钥自动轮换
@@ -95,6 +251,9 @@ pub struct SecurityConfig {
     /// 支持标准正则表达式语法，(?i)表示不区分大小写
     /// 多个模式使用|分隔，例如: (?i)password|secret|token|api[_-]?key
     pub sensitive_pattern: String,
+    /// 紧急销毁时保留最近一条非敏感内容，并在销毁后恢复到剪贴板
+    #[serde(default)]
+    pub preserve_plain_on_nuke: bool,
 }
 
 impl Default for SecurityConfig {
@@ -102,12 +261,14 @@ impl Default for SecurityConfig {
         SecurityConfig {
             enable_memory_locking: true,
             memory_erase_rounds: 3,
+            memory_erase_patterns: memory::default_erase_patterns(),
             auto_clear_on_exit: true,
             enable_key_rotation: false,
             key_rotation_interval: 60, // 1小时
             destroy_on_paste: true,  // 启用粘贴即销毁，测试倒计时删除功能
             min_length_for_protection: 8, // 降低默认最小保护长度，以更好地保护密码等短文本
             sensitive_pattern: ".*".to_string(), // 匹配所有内容
+            preserve_plain_on_nuke: false,
         }
     }
 }
@@ -125,6 +286,25 @@ pub struct UiConfig {
     pub log_level: String,
     /// 是否启用系统托盘图标
     pub enable_tray_icon: bool,
+    /// 按模块覆盖日志级别，例如 keyboard=warn, clipboard=debug
+    #[serde(default)]
+    pub module_log_levels: BTreeMap<String, String>,
+    /// 确认提示的等待超时（秒），超时默认取消，0表示一直等待
+    #[serde(default = "default_confirm_timeout_seconds")]
+    pub confirm_timeout_seconds: u64,
+    /// 是否在终端标题中显示剩余倒计时
+    #[serde(default)]
+    pub countdown_title: bool,
+    /// 是否发布倒计时状态文件（供 `status --tmux` 读取）
+    #[serde(default)]
+    pub countdown_status: bool,
+    /// 是否将交互模式的命令历史保存到配置目录（只保存不含内容与凭据的命令）
+    #[serde(default)]
+    pub repl_history: bool,
+}
+
+fn default_confirm_timeout_seconds() -> u64 {
+    30
 }
 
 impl Default for UiConfig {
@@ -135,10 +315,142 @@ impl Default for UiConfig {
             enable_colors: true,
             log_level: "info".to_string(),
             enable_tray_icon: true,
+            module_log_levels: BTreeMap::new(),
+            confirm_timeout_seconds: default_confirm_timeout_seconds(),
+            countdown_title: false,
+            countdown_status: false,
+            repl_history: false,
+        }
+    }
+}
+
+impl UiConfig {
+    /// 叠加 `ui.` 开头的覆盖（用于日志系统初始化前，覆盖无效时忽略）
+    fn with_overrides(self) -> Self {
+        let active: Vec<_> = overrides::active()
+            .into_iter()
+            .filter(|item| item.key.starts_with("ui."))
+            .collect();
+        let Ok(mut value) = serde_json::to_value(serde_json::json!({ "ui": &self })) else {
+            return self;
+        };
+        overrides::apply(&mut value, &active)
+            .ok()
+            .and_then(|_| serde_json::from_value(value["ui"].take()).ok())
+            .unwrap_or(self)
+    }
+
+    /// 构建env_logger过滤表达式
+    ///
+    /// 优先级从低到高：基础级别 < 模块覆盖 < 命令行过滤表达式
+    ///
+    /// # 参数
+    /// * `base_level` - 基础日志级别
+    /// * `extra_filter` - 命令行传入的过滤表达式（env_logger语法）
+    ///
+    /// # 返回值
+    /// * `String` - 过滤表达式
+    pub fn build_log_filter(&self, base_level: &str, extra_filter: Option<&str>) -> String {
+        let mut directives = vec![format!("clipvanish={}", base_level)];
+
+        for (module, level) in &self.module_log_levels {
+            let target = if module.starts_with("clipvanish") {
+                module.clone()
+            } else {
+                format!("clipvanish::{}", module)
+            };
+            directives.push(format!("{}={}", target, level));
+        }
+
+        if let Some(filter) = extra_filter {
+            if !filter.trim().is_empty() {
+                directives.push(filter.trim().to_string());
+            }
+        }
+
+        directives.join(",")
+    }
+}
+
+/// 热键动作
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum HotkeyAction {
+    /// 紧急销毁
+    Nuke,
+    /// 暂停/恢复保护
+    Pause,
+    /// 延长当前条目的存活时间
+    Extend {
+        /// 延长秒数
+        #[serde(default = "default_extend_seconds")]
+        seconds: u64,
+    },
+    /// 固定当前条目（取消自动清除，直到手动清除、紧急销毁或被新内容取代）
+    Pin,
+    /// 将第N条历史记录（1为最新）作为受保护内容放回剪贴板
+    PasteSlot {
+        /// 历史条目编号
+        slot: usize,
+    },
+    /// 显示状态
+    ShowStatus,
+    /// 切换观察模式（命中规则时只记录，不拦截）
+    ToggleObserve,
+    /// 撤销最近一次清除
+    Undo,
+    /// 将最近一次警告的规则转为强制执行
+    PromoteRule,
+    /// 显示/隐藏倒计时悬浮窗
+    ToggleOverlay,
+    /// 确认待确认的高风险粘贴
+    ApprovePaste,
+}
+
+fn default_extend_seconds() -> u64 {
+    60
+}
+
+impl std::fmt::Display for HotkeyAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HotkeyAction::Nuke => write!(f, "紧急销毁"),
+            HotkeyAction::Pause => write!(f, "暂停/恢复保护"),
+            HotkeyAction::Extend { seconds } => write!(f, "延长 {} 秒", seconds),
+            HotkeyAction::Pin => write!(f, "固定当前条目"),
+            HotkeyAction::PasteSlot { slot } => write!(f, "放回第 {} 条历史记录", slot),
+            HotkeyAction::ShowStatus => write!(f, "显示状态"),
+            HotkeyAction::ToggleObserve => write!(f, "切换观察模式"),
+            HotkeyAction::Undo => write!(f, "撤销清除"),
+            HotkeyAction::PromoteRule => write!(f, "规则转为强制执行"),
+            HotkeyAction::ToggleOverlay => write!(f, "显示/隐藏倒计时悬浮窗"),
+            HotkeyAction::ApprovePaste => write!(f, "确认高风险粘贴"),
         }
     }
 }
 
+/// 热键绑定（按键与动作）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HotkeyBinding {
+    /// 按键组合，如 `Ctrl+Alt+1`
+    pub key: String,
+    /// 动作及其参数
+    #[serde(flatten)]
+    pub action: HotkeyAction,
+}
+
+impl HotkeyBinding {
+    #[cfg_attr(not(feature = "hotkeys"), allow(dead_code))]
+    fn new(key: &str, action: HotkeyAction) -> Self {
+        HotkeyBinding { key: key.to_string(), action }
+    }
+}
+
+/// 归一化按键组合（忽略大小写与空格），用于判断两个绑定是否冲突
+fn normalize_hotkey(key: &str) -> String {
+    key.split('+').map(|part| part.trim().to_ascii_lowercase()).collect::<Vec<_>>().join("+")
+}
+
 /// 热键配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HotkeyConfig {
@@ -150,6 +462,60 @@ pub struct HotkeyConfig {
     pub show_status_key: String,
     /// 暂停/恢复监听热键
     pub toggle_monitoring_key: String,
+    /// 撤销最近一次清除热键
+    #[serde(default = "default_undo_key")]
+    pub undo_key: String,
+    /// 将最近一次警告的规则转为强制执行热键
+    #[serde(default = "default_promote_rule_key")]
+    pub promote_rule_key: String,
+    /// 显示/隐藏倒计时悬浮窗热键
+    #[serde(default = "default_toggle_overlay_key")]
+    pub toggle_overlay_key: String,
+    /// 确认高风险粘贴热键
+    #[serde(default = "default_approve_paste_key")]
+    pub approve_paste_key: String,
+    /// 自定义热键绑定（与上述热键使用同一按键时覆盖之）
+    #[serde(default)]
+    pub bindings: Vec<HotkeyBinding>,
+}
+
+impl HotkeyConfig {
+    /// 生效的全部热键绑定：内置热键在前，自定义绑定覆盖同一按键的内置热键
+    #[cfg_attr(not(feature = "hotkeys"), allow(dead_code))]
+    pub fn effective_bindings(&self) -> Vec<HotkeyBinding> {
+        let mut bindings: Vec<HotkeyBinding> = [
+            HotkeyBinding::new(&self.emergency_nuke_key, HotkeyAction::Nuke),
+            HotkeyBinding::new(&self.show_status_key, HotkeyAction::ShowStatus),
+            HotkeyBinding::new(&self.toggle_monitoring_key, HotkeyAction::Pause),
+            HotkeyBinding::new(&self.undo_key, HotkeyAction::Undo),
+            HotkeyBinding::new(&self.promote_rule_key, HotkeyAction::PromoteRule),
+            HotkeyBinding::new(&self.toggle_overlay_key, HotkeyAction::ToggleOverlay),
+            HotkeyBinding::new(&self.approve_paste_key, HotkeyAction::ApprovePaste),
+        ]
+        .into_iter()
+        .filter(|builtin| {
+            !self.bindings.iter().any(|custom| normalize_hotkey(&custom.key) == normalize_hotkey(&builtin.key))
+        })
+        .collect();
+        bindings.extend(self.bindings.iter().cloned());
+        bindings
+    }
+}
+
+fn default_undo_key() -> String {
+    "Ctrl+Alt+Z".to_string()
+}
+
+fn default_promote_rule_key() -> String {
+    "Ctrl+Alt+E".to_string()
+}
+
+fn default_toggle_overlay_key() -> String {
+    "Ctrl+Alt+C".to_string()
+}
+
+fn default_approve_paste_key() -> String {
+    "Ctrl+Alt+Y".to_string()
 }
 
 impl Default for HotkeyConfig {
@@ -159,6 +525,80 @@ impl Default for HotkeyConfig {
             emergency_nuke_key: "Ctrl+Alt+V".to_string(),
             show_status_key: "Ctrl+Alt+S".to_string(),
             toggle_monitoring_key: "Ctrl+Alt+M".to_string(),
+            undo_key: default_undo_key(),
+            promote_rule_key: default_promote_rule_key(),
+            toggle_overlay_key: default_toggle_overlay_key(),
+            approve_paste_key: default_approve_paste_key(),
+            bindings: Vec::new(),
+        }
+    }
+}
+
+/// Windows剪贴板历史（Win+V）策略
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardHistoryPolicy {
+    /// 不做任何处理
+    Ignore,
+    /// 存在受保护条目时按下Win+V立即清空剪贴板历史
+    #[default]
+    Purge,
+    /// 会话期间禁用剪贴板历史（结束时恢复原设置），并保留清空行为
+    Suppress,
+}
+
+impl std::fmt::Display for ClipboardHistoryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClipboardHistoryPolicy::Ignore => write!(f, "不处理"),
+            ClipboardHistoryPolicy::Purge => write!(f, "按下Win+V时清空"),
+            ClipboardHistoryPolicy::Suppress => write!(f, "会话期间禁用"),
+        }
+    }
+}
+
+/// macOS通用剪贴板（Handoff）策略
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HandoffPolicy {
+    /// 不做任何处理
+    Ignore,
+    /// 受保护内容以"仅限本机"方式写入，并附加Concealed/Transient标记
+    #[default]
+    Conceal,
+    /// 在标记的基础上，经用户同意后会话期间关闭Handoff（结束时恢复原设置）
+    Disable,
+}
+
+impl std::fmt::Display for HandoffPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HandoffPolicy::Ignore => write!(f, "不处理"),
+            HandoffPolicy::Conceal => write!(f, "仅限本机并标记"),
+            HandoffPolicy::Disable => write!(f, "会话期间关闭Handoff"),
+        }
+    }
+}
+
+/// 剪贴板访问方式
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardAccess {
+    /// Flatpak/Snap沙箱内使用xdg-desktop-portal，其他情况直接访问
+    #[default]
+    Auto,
+    /// 始终使用xdg-desktop-portal（需要 `portal` 特性）
+    Portal,
+    /// 始终直接访问（X11/系统接口）
+    Direct,
+}
+
+impl std::fmt::Display for ClipboardAccess {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClipboardAccess::Auto => write!(f, "自动（沙箱内使用门户）"),
+            ClipboardAccess::Portal => write!(f, "xdg-desktop-portal"),
+            ClipboardAccess::Direct => write!(f, "直接访问"),
         }
     }
 }
@@ -174,6 +614,52 @@ pub struct ClipboardConfig {
     pub max_content_length: usize,
     /// 是否启用内容长度限制
     pub enable_length_limit: bool,
+    /// 检测到敏感复制时隔离并清除剪贴板中的未知格式
+    #[serde(default)]
+    pub quarantine_unknown_formats: bool,
+    /// 隔离后允许在受保护内容清除时恢复的格式（默认不恢复任何格式）
+    #[serde(default)]
+    pub quarantine_allowlist: Vec<String>,
+    /// Windows剪贴板历史（Win+V）策略
+    #[serde(default)]
+    pub history_policy: ClipboardHistoryPolicy,
+    /// macOS通用剪贴板（Handoff）策略
+    #[serde(default)]
+    pub handoff_policy: HandoffPolicy,
+    /// Windows延迟渲染：受保护内容只以承诺形式放到剪贴板，应用请求数据时才解密
+    #[serde(default)]
+    pub delayed_rendering: bool,
+    /// 与当前受保护条目的相似度达到此值时视为增量编辑，原地更新密文（0表示禁用）
+    #[serde(default = "default_incremental_edit_similarity")]
+    pub incremental_edit_similarity: f64,
+    /// 增量编辑允许的最大变化字符数
+    #[serde(default = "default_incremental_edit_max_delta")]
+    pub incremental_edit_max_delta: usize,
+    /// 拦截延迟SLO（毫秒）：从剪贴板变化到密文替换的最长允许时间，超出时写入审计日志（0表示不检查）
+    #[serde(default = "default_interception_slo_ms")]
+    pub interception_slo_ms: u64,
+    /// 剪贴板访问方式
+    #[serde(default)]
+    pub access: ClipboardAccess,
+}
+
+fn default_interception_slo_ms() -> u64 {
+    1000
+}
+
+impl ClipboardConfig {
+    /// 拦截延迟SLO（未启用时为None）
+    pub fn interception_slo(&self) -> Option<Duration> {
+        (self.interception_slo_ms > 0).then(|| Duration::from_millis(self.interception_slo_ms))
+    }
+}
+
+fn default_incremental_edit_similarity() -> f64 {
+    0.8
+}
+
+fn default_incremental_edit_max_delta() -> usize {
+    32
 }
 
 impl Default for ClipboardConfig {
@@ -183,10 +669,223 @@ impl Default for ClipboardConfig {
             supported_types: vec!["text".to_string()],
             max_content_length: 1024 * 1024, // 1MB
             enable_length_limit: true,
+            quarantine_unknown_formats: false,
+            quarantine_allowlist: Vec::new(),
+            history_policy: ClipboardHistoryPolicy::Purge,
+            handoff_policy: HandoffPolicy::Conceal,
+            delayed_rendering: false,
+            incremental_edit_similarity: default_incremental_edit_similarity(),
+            incremental_edit_max_delta: default_incremental_edit_max_delta(),
+            interception_slo_ms: default_interception_slo_ms(),
+            access: ClipboardAccess::Auto,
+        }
+    }
+}
+
+/// 剪贴板历史记录保留策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryConfig {
+    /// 历史记录缓冲区的容量，即最多保留的记录数（0表示不记录历史）
+    #[serde(default = "default_history_max_items")]
+    pub max_items: usize,
+    /// 记录最长保留时间（秒，0表示不按时间清理）
+    #[serde(default = "default_history_max_age_seconds")]
+    pub max_age_seconds: u64,
+    /// 从不在历史记录中保存明文，只保留长度、类型等元数据
+    #[serde(default)]
+    pub never_store_plaintext: bool,
+    /// 将历史记录元数据保存到配置目录（从不包含明文），重启后保留
+    #[serde(default)]
+    pub persist: bool,
+}
+
+fn default_history_max_items() -> usize {
+    100
+}
+
+fn default_history_max_age_seconds() -> u64 {
+    30
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        HistoryConfig {
+            max_items: default_history_max_items(),
+            max_age_seconds: default_history_max_age_seconds(),
+            never_store_plaintext: false,
+            persist: false,
+        }
+    }
+}
+
+impl HistoryConfig {
+    /// 记录最长保留时间（None表示不按时间清理）
+    pub fn max_age(&self) -> Option<Duration> {
+        (self.max_age_seconds > 0).then(|| Duration::from_secs(self.max_age_seconds))
+    }
+}
+
+/// 本地控制接口（IPC）配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IpcConfig {
+    /// 是否启用控制套接字
+    #[serde(default)]
+    pub enabled: bool,
+    /// 控制套接字路径（默认位于运行时目录下）
+    #[serde(default)]
+    pub socket_path: Option<PathBuf>,
+}
+
+/// 控制接口（API）的访问控制配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApiConfig {
+    /// 破坏性命令的口令或签名验证
+    #[serde(default)]
+    pub security: IpcSecurityConfig,
+}
+
+impl IpcConfig {
+    /// 实际使用的控制套接字路径
+    ///
+    /// 优先使用配置值，其次为 $XDG_RUNTIME_DIR/clipvanish/control.sock，最后为配置目录下的run/control.sock
+    pub fn resolved_socket_path(&self) -> Result<PathBuf, ConfigError> {
+        if let Some(path) = &self.socket_path {
+            return Ok(path.clone());
+        }
+
+        if let Ok(runtime_dir) = std::env::var("XDG_RUNTIME_DIR") {
+            return Ok(PathBuf::from(runtime_dir).join("clipvanish").join("control.sock"));
+        }
+
+        Ok(Config::get_config_directory()?.join("run").join("control.sock"))
+    }
+}
+
+/// 第三方集成配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IntegrationsConfig {
+    /// MQTT状态发布
+    #[serde(default)]
+    pub mqtt: MqttConfig,
+    /// 剪贴板管理器协作（CopyQ、Maccy与钩子程序）
+    #[serde(default)]
+    pub clipboard_managers: ClipboardManagersConfig,
+}
+
+/// 代理客户端的审批策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentApproval {
+    /// 每次请求都在服务终端询问
+    #[default]
+    Ask,
+    /// 无需询问直接放行
+    Allow,
+    /// 拒绝（保留条目但暂时停用）
+    Deny,
+}
+
+/// 预先批准的代理客户端
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentClient {
+    /// 客户端名称（记录在审计日志中）
+    pub name: String,
+    /// 可执行文件的绝对路径
+    pub path: PathBuf,
+    /// 可执行文件的SHA-256（十六进制），升级客户端后需要更新
+    pub sha256: String,
+    /// 审批策略
+    #[serde(default)]
+    pub approval: AgentApproval,
+}
+
+/// 可疑读取者检测配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReaderWatchConfig {
+    /// 是否启用（仅Windows）
+    pub enabled: bool,
+    /// 每次变化后采样读取者的时长（毫秒）
+    pub window_ms: u64,
+    /// 预热期（剪贴板变化次数）：期间出现的读取者视为已知，且至少读取这么多次才会被标记为可疑
+    pub min_changes: u64,
+    /// 读取比例阈值：进程读取的变化次数占其出现以来变化次数的比例
+    pub read_ratio: f64,
+    /// 不计入检测的进程名（不区分大小写）
+    pub allowed_readers: Vec<String>,
+    /// 存在受保护条目时出现未知读取者则紧急销毁
+    pub auto_nuke_unknown: bool,
+}
+
+impl Default for ReaderWatchConfig {
+    fn default() -> Self {
+        ReaderWatchConfig {
+            enabled: false,
+            window_ms: 300,
+            min_changes: 5,
+            read_ratio: 0.8,
+            allowed_readers: vec!["rdpclip.exe".to_string(), "TextInputHost.exe".to_string()],
+            auto_nuke_unknown: false,
+        }
+    }
+}
+
+impl ReaderWatchConfig {
+    /// 进程是否在白名单中
+    pub fn allows(&self, app: &str) -> bool {
+        self.allowed_readers.iter().any(|allowed| allowed.eq_ignore_ascii_case(app))
+    }
+}
+
+/// 代理套接字配置（已批准的程序通过它取得受保护内容）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentConfig {
+    /// 是否启用代理套接字
+    #[serde(default)]
+    pub enabled: bool,
+    /// 代理套接字路径（默认位于运行时目录下）
+    #[serde(default)]
+    pub socket_path: Option<PathBuf>,
+    /// 审批提示的等待时间（秒），超时视为拒绝
+    #[serde(default = "default_agent_approval_timeout_seconds")]
+    pub approval_timeout_seconds: u64,
+    /// 预先批准的客户端
+    #[serde(default)]
+    pub clients: Vec<AgentClient>,
+}
+
+fn default_agent_approval_timeout_seconds() -> u64 {
+    30
+}
+
+impl Default for AgentConfig {
+    fn default() -> Self {
+        AgentConfig {
+            enabled: false,
+            socket_path: None,
+            approval_timeout_seconds: default_agent_approval_timeout_seconds(),
+            clients: Vec::new(),
         }
     }
 }
 
+impl AgentConfig {
+    /// 实际使用的代理套接字路径
+    ///
+    /// 优先使用配置值，其次为 $XDG_RUNTIME_DIR/clipvanish/agent.sock，最后为配置目录下的run/agent.sock
+    pub fn resolved_socket_path(&self) -> Result<PathBuf, ConfigError> {
+        if let Some(path) = &self.socket_path {
+            return Ok(path.clone());
+        }
+
+        if let Ok(runtime_dir) = std::env::var("XDG_RUNTIME_DIR") {
+            return Ok(PathBuf::from(runtime_dir).join("clipvanish").join("agent.sock"));
+        }
+
+        Ok(Config::get_config_directory()?.join("run").join("agent.sock"))
+    }
+}
+
 /// 主配置结构体
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -202,6 +901,96 @@ pub struct Config {
     pub hotkeys: HotkeyConfig,
     /// 剪贴板配置
     pub clipboard: ClipboardConfig,
+    /// 历史记录保留策略
+    #[serde(default)]
+    pub history: HistoryConfig,
+    /// 本地控制接口配置
+    #[serde(default)]
+    pub ipc: IpcConfig,
+    /// 控制接口的访问控制配置
+    #[serde(default)]
+    pub api: ApiConfig,
+    /// 代理套接字配置
+    #[serde(default)]
+    pub agent: AgentConfig,
+    /// 熵值规则（未匹配敏感模式的高熵片段同样受保护）
+    #[serde(default)]
+    pub entropy_rule: EntropyRuleConfig,
+    /// 本地偏好学习配置
+    #[serde(default)]
+    pub learning: LearningConfig,
+    /// 新规则试运行（仅警告阶段）配置
+    #[serde(default)]
+    pub rule_trial: RuleTrialConfig,
+    /// 倒计时悬浮窗配置
+    #[serde(default)]
+    pub overlay: OverlayConfig,
+    /// 按粘贴上下文（显示器、虚拟桌面）阻止解密
+    #[serde(default)]
+    pub paste_context: PasteContextConfig,
+    /// 高风险粘贴确认
+    #[serde(default)]
+    pub paste_confirm: PasteConfirmConfig,
+    /// 识别的粘贴手势（终端快捷键与鼠标中键）
+    #[serde(default)]
+    pub paste_gestures: PasteGesturesConfig,
+    /// 按目标程序的粘贴转换
+    #[serde(default)]
+    pub paste_transforms: PasteTransformConfig,
+    /// 按目标程序的确认粘贴方式
+    #[serde(default)]
+    pub paste_strategies: PasteStrategyConfig,
+    /// 反复延长、固定时的严格程度自动升级
+    #[serde(default)]
+    pub escalation: EscalationConfig,
+    /// 按内容类别的绝对存活上限
+    #[serde(default)]
+    pub lifetime_caps: LifetimeCapConfig,
+    /// 浏览器登录页面检测（按窗口标题提高敏感度）
+    #[serde(default)]
+    pub login_pages: LoginPagesConfig,
+    /// 桌面通知与专注/勿扰模式
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+    /// 事件Webhook推送
+    #[serde(default)]
+    pub webhooks: WebhookConfig,
+    /// 第三方集成（MQTT）
+    #[serde(default)]
+    pub integrations: IntegrationsConfig,
+    /// 剪贴板诱饵令牌
+    #[serde(default)]
+    pub canary: CanaryConfig,
+    /// 诱饵内容替换
+    #[serde(default)]
+    pub decoy: DecoyConfig,
+    /// 可疑剪贴板读取者检测（仅Windows）
+    #[serde(default)]
+    pub reader_watch: ReaderWatchConfig,
+    /// 紧急销毁双人确认（第二因素）
+    #[serde(default)]
+    pub nuke_guard: NukeGuardConfig,
+    /// 只读状态页（挂墙显示屏）
+    #[serde(default)]
+    pub status_page: StatusPageConfig,
+    /// 关闭协调（各阶段的总期限）
+    #[serde(default)]
+    pub shutdown: ShutdownConfig,
+    /// 健康检查与systemd看门狗
+    #[serde(default)]
+    pub health: HealthConfig,
+    /// 反复崩溃后的安全模式
+    #[serde(default)]
+    pub safe_mode: SafeModeConfig,
+    /// 密钥托管（企业恢复公钥）
+    #[serde(default)]
+    pub escrow: EscrowConfig,
+    /// 规则包（受信任的签名公钥）
+    #[serde(default)]
+    pub rule_packs: RulePackConfig,
+    /// 已应用的受管策略（运行时信息，不写入配置文件）
+    #[serde(skip)]
+    pub managed_policy: Option<PolicyReport>,
     /// 清除延迟时间（秒）
     pub clear_delay_seconds: u64,
     pub min_length_for_protection: usize,
@@ -217,6 +1006,36 @@ impl Default for Config {
             ui: UiConfig::default(),
             hotkeys: HotkeyConfig::default(),
             clipboard: ClipboardConfig::default(),
+            history: HistoryConfig::default(),
+            ipc: IpcConfig::default(),
+            api: ApiConfig::default(),
+            agent: AgentConfig::default(),
+            entropy_rule: EntropyRuleConfig::default(),
+            learning: LearningConfig::default(),
+            rule_trial: RuleTrialConfig::default(),
+            overlay: OverlayConfig::default(),
+            paste_context: PasteContextConfig::default(),
+            paste_confirm: PasteConfirmConfig::default(),
+            paste_gestures: PasteGesturesConfig::default(),
+            paste_transforms: PasteTransformConfig::default(),
+            paste_strategies: PasteStrategyConfig::default(),
+            escalation: EscalationConfig::default(),
+            lifetime_caps: LifetimeCapConfig::default(),
+            login_pages: LoginPagesConfig::default(),
+            notifications: NotificationsConfig::default(),
+            webhooks: WebhookConfig::default(),
+            integrations: IntegrationsConfig::default(),
+            canary: CanaryConfig::default(),
+            decoy: DecoyConfig::default(),
+            reader_watch: ReaderWatchConfig::default(),
+            nuke_guard: NukeGuardConfig::default(),
+            status_page: StatusPageConfig::default(),
+            shutdown: ShutdownConfig::default(),
+            health: HealthConfig::default(),
+            safe_mode: SafeModeConfig::default(),
+            escrow: EscrowConfig::default(),
+            rule_packs: RulePackConfig::default(),
+            managed_policy: None,
             clear_delay_seconds: 30, // 默认30秒
             min_length_for_protection: 8,
             sensitive_pattern: ".*".to_string(), // 匹配所有内容
@@ -225,22 +1044,65 @@ impl Default for Config {
 }
 
 impl Config {
-    /// 加载配置文件
+    /// 加载配置文件，并合并系统级受管策略
     ///
     /// # 返回值
     /// * `Result<Config, ConfigError>` - 成功返回配置实例
     pub fn load() -> Result<Self, ConfigError> {
         let config_path = Self::get_config_file_path()?;
 
-        if config_path.exists() {
+        let config = if config_path.exists() {
             debug!("从文件加载配置: {:?}", config_path);
-            Self::load_from_file(&config_path)
+            Self::load_from_file(&config_path)?
         } else {
             info!("配置文件不存在，创建默认配置");
             let config = Config::default();
             config.save()?;
-            Ok(config)
+            config
+        };
+
+        config.with_overrides()?.with_managed_policy()
+    }
+
+    /// 叠加环境变量与命令行 `--set` 覆盖（配置文件 < 环境变量 < 命令行）
+    ///
+    /// 覆盖只作用于内存中的配置，不会写回配置文件
+    pub fn with_overrides(self) -> Result<Self, ConfigError> {
+        let active = overrides::active();
+        if active.is_empty() {
+            return Ok(self);
+        }
+
+        let mut value = serde_json::to_value(&self).map_err(ConfigError::ParseError)?;
+        overrides::apply(&mut value, &active)
+            .map_err(|e| ConfigError::ValidationError(e.to_string()))?;
+        let config: Config = serde_json::from_value(value)
+            .map_err(|e| ConfigError::ValidationError(OverrideError::InvalidConfig(e.to_string()).to_string()))?;
+        config.validate()?;
+
+        for item in &active {
+            info!("配置覆盖: {} ({})", item.key, item.source);
         }
+        Ok(config)
+    }
+
+    /// 合并系统级受管策略（策略优先，冲突写入日志）
+    ///
+    /// 策略存在但无法应用时返回错误，避免在策略失效的情况下运行
+    pub fn with_managed_policy(self) -> Result<Self, ConfigError> {
+        let policy = ManagedPolicy::load_system()
+            .map_err(|e| ConfigError::ValidationError(e.to_string()))?;
+        let Some(policy) = policy else {
+            return Ok(self);
+        };
+
+        let (config, report) = policy.apply(&self)
+            .map_err(|e| ConfigError::ValidationError(format!("{} ({})", e, policy.source())))?;
+        info!("已应用受管策略 {}，强制 {} 项设置", report.source, report.enforced.len());
+        for conflict in &report.conflicts {
+            warn!("受管策略覆盖了用户配置 {}", conflict);
+        }
+        Ok(config)
     }
 
     /// 从指定文件加载配置
@@ -267,40 +1129,36 @@ impl Config {
                 warn!("配置加载出现问题: {}", e);
                 info!("尝试使用现有值并添加缺失的字段...");
 
-                // 创建默认配置
+                // 文件已损坏（不是有效的JSON）时从最近的备份恢复，而不是在损坏的内容上修补
+                let Ok(mut json) = serde_json::from_str::<serde_json::Value>(&content) else {
+                    return Self::recover_from_backup(path.as_ref());
+                };
+
+                // 添加缺失的安全配置字段
                 let default_config = Config::default();
+                if let Some(security) = json.get_mut("security").and_then(|v| v.as_object_mut()) {
+                    if !security.contains_key("destroy_on_paste") {
+                        security.insert(
+                            "destroy_on_paste".to_string(),
+                            serde_json::Value::Bool(default_config.security.destroy_on_paste)
+                        );
+                    }
+                }
 
-                // 尝试解析现有配置
-                if let Ok(mut json) = serde_json::from_str::<serde_json::Value>(&content) {
-                    if let Some(obj) = json.as_object_mut() {
-                        // 添加缺失的安全配置字段
-                        if let Some(security) = obj.get_mut("security") {
-                            if let Some(security_obj) = security.as_object_mut() {
-                                if !security_obj.contains_key("destroy_on_paste") {
-                                    security_obj.insert(
-                                        "destroy_on_paste".to_string(),
-                                        serde_json::Value::Bool(default_config.security.destroy_on_paste)
-                                    );
-                                }
-                            }
-                        }
-
-                        // 保存更新后的配置并重新加载
-                        let path_ref = path.as_ref();
+                // 只有补全后能够完整解析时才写回文件，否则按损坏处理
+                match serde_json::from_value::<Config>(json.clone()) {
+                    Ok(config) => {
                         let updated_content = serde_json::to_string_pretty(&json)
                             .map_err(ConfigError::ParseError)?;
-                        fs::write(path_ref, &updated_content)
+                        write_atomic(path.as_ref(), &updated_content)
                             .map_err(ConfigError::FileWriteError)?;
-
-                        // 使用更新后的内容直接解析，而不是重新读取文件
-                        return serde_json::from_str(&updated_content)
-                            .map_err(ConfigError::ParseError);
+                        config
+                    }
+                    Err(_) => {
+                        warn!("无法修复配置文件");
+                        return Self::recover_from_backup(path.as_ref());
                     }
                 }
-
-                // 如果更新失败，返回默认配置
-                warn!("无法修复配置文件，使用默认配置");
-                default_config
             }
         };
 
@@ -334,70 +1192,543 @@ impl Config {
         let content = serde_json::to_string_pretty(self)
             .map_err(ConfigError::ParseError)?;
 
-        fs::write(path, content)
+        Self::backup_existing(path.as_ref())?;
+        write_atomic(path.as_ref(), &content)
             .map_err(ConfigError::FileWriteError)?;
 
         debug!("配置保存成功");
         Ok(())
     }
 
+    /// 覆盖前备份现有的配置文件（仅备份可解析的内容），并只保留最近的若干份
+    fn backup_existing(path: &Path) -> Result<(), ConfigError> {
+        let Ok(content) = fs::read_to_string(path) else {
+            return Ok(());
+        };
+        if serde_json::from_str::<serde_json::Value>(&content).is_err() {
+            return Ok(());
+        }
+
+        let dir = backup_directory(path);
+        fs::create_dir_all(&dir).map_err(ConfigError::DirectoryCreationError)?;
+        let name = format!(
+            "{}-{}.json",
+            backup_prefix(path),
+            chrono::Utc::now().format("%Y%m%dT%H%M%S%.6f")
+        );
+        write_atomic(&dir.join(name), &content).map_err(ConfigError::FileWriteError)?;
+
+        for old in Self::list_backups_for(path).into_iter().skip(MAX_CONFIG_BACKUPS) {
+            if let Err(e) = fs::remove_file(&old) {
+                warn!("删除旧配置备份失败 {:?}: {}", old, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// 列出指定配置文件的备份（最新的在前）
+    ///
+    /// # 参数
+    /// * `path` - 配置文件路径
+    pub fn list_backups_for(path: &Path) -> Vec<PathBuf> {
+        let prefix = format!("{}-", backup_prefix(path));
+        let mut backups: Vec<PathBuf> = fs::read_dir(backup_directory(path))
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|backup| {
+                        backup.file_name().and_then(|name| name.to_str()).is_some_and(|name| {
+                            name.starts_with(&prefix) && name.ends_with(".json")
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        // 文件名中的时间戳可按字典序排序
+        backups.sort();
+        backups.reverse();
+        backups
+    }
+
+    /// 列出默认配置文件的备份（最新的在前）
+    pub fn list_backups() -> Result<Vec<PathBuf>, ConfigError> {
+        Ok(Self::list_backups_for(&Self::get_config_file_path()?))
+    }
+
+    /// 将指定配置文件回滚到第 `index` 份备份（1为最新）
+    ///
+    /// 回滚前的配置本身也会被备份，因此回滚可以撤销
+    ///
+    /// # 参数
+    /// * `path` - 配置文件路径
+    /// * `index` - 备份序号（1为最新）
+    ///
+    /// # 返回值
+    /// * `Result<Config, ConfigError>` - 恢复后的配置
+    pub fn restore_backup_for(path: &Path, index: usize) -> Result<Self, ConfigError> {
+        let backups = Self::list_backups_for(path);
+        let backup = index
+            .checked_sub(1)
+            .and_then(|i| backups.get(i))
+            .ok_or_else(|| ConfigError::ValidationError(format!(
+                "备份 {} 不存在（共 {} 份）", index, backups.len()
+            )))?;
+
+        let content = fs::read_to_string(backup).map_err(ConfigError::FileReadError)?;
+        let config: Config = serde_json::from_str(&content).map_err(ConfigError::ParseError)?;
+        config.validate()?;
+        config.save_to_file(path)?;
+
+        info!("配置已从备份 {:?} 恢复", backup);
+        Ok(config)
+    }
+
+    /// 将默认配置文件回滚到第 `index` 份备份（1为最新），并重新合并受管策略
+    pub fn restore_backup(&mut self, index: usize) -> Result<(), ConfigError> {
+        let config = Self::restore_backup_for(&Self::get_config_file_path()?, index)?;
+        *self = config.with_overrides()?.with_managed_policy()?;
+        Ok(())
+    }
+
+    /// 配置文件损坏时从最近的有效备份恢复，没有可用备份时使用默认配置
+    ///
+    /// 损坏的文件会重命名为 `*.corrupt` 保留，便于排查
+    fn recover_from_backup(path: &Path) -> Result<Self, ConfigError> {
+        let corrupt = path.with_extension("json.corrupt");
+        if let Err(e) = fs::rename(path, &corrupt) {
+            warn!("无法保留损坏的配置文件: {}", e);
+        } else {
+            warn!("配置文件已损坏，已保留为 {:?}", corrupt);
+        }
+
+        for backup in Self::list_backups_for(path) {
+            let restored = fs::read_to_string(&backup)
+                .ok()
+                .and_then(|content| serde_json::from_str::<Config>(&content).ok())
+                .filter(|config| config.validate().is_ok());
+            if let Some(config) = restored {
+                write_atomic(path, &serde_json::to_string_pretty(&config).map_err(ConfigError::ParseError)?)
+                    .map_err(ConfigError::FileWriteError)?;
+                warn!("已从备份 {:?} 恢复配置", backup);
+                return Ok(config);
+            }
+        }
+
+        warn!("没有可用的配置备份，使用默认配置");
+        let config = Config::default();
+        config.save_to_file(path)?;
+        Ok(config)
+    }
+
     /// 验证配置的有效性
     ///
+    /// 一次收集全部违规项，而不是在第一处错误处停止
+    ///
     /// # 返回值
-    /// * `Result<(), ConfigError>` - 验证结果
+    /// * `Result<(), ConfigError>` - 验证结果，失败时为 `ConfigError::Invalid`
     pub fn validate(&self) -> Result<(), ConfigError> {
+        let violations = self.violations();
+        if !violations.is_empty() {
+            return Err(ConfigError::Invalid(violations));
+        }
+
+        if self.security.memory_erase_rounds > 10 {
+            warn!("内存擦除轮数过多可能影响性能: {}", self.security.memory_erase_rounds);
+        }
+        if self.clipboard.poll_interval_ms < 50 {
+            warn!("轮询间隔过短可能影响性能: {}ms", self.clipboard.poll_interval_ms);
+        }
+
+        debug!("配置验证通过");
+        Ok(())
+    }
+
+    /// 收集全部配置违规项
+    ///
+    /// # 返回值
+    /// * `Vec<Violation>` - 违规项，配置有效时为空
+    pub fn violations(&self) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        let timer = &self.timer;
+
         // 验证定时器配置
-        if self.timer.min_countdown > self.timer.max_countdown {
-            return Err(ConfigError::ValidationError(
-                "最小倒计时不能大于最大倒计时".to_string()
+        if timer.min_countdown > timer.max_countdown {
+            violations.push(Violation::new(
+                "timer.min_countdown",
+                format!("≤ timer.max_countdown ({})", timer.max_countdown),
+                timer.min_countdown,
             ));
         }
 
-        if self.timer.default_countdown < self.timer.min_countdown ||
-            self.timer.default_countdown > self.timer.max_countdown {
-            return Err(ConfigError::ValidationError(
-                "默认倒计时必须在最小值和最大值之间".to_string()
+        if timer.default_countdown < timer.min_countdown || timer.default_countdown > timer.max_countdown {
+            violations.push(Violation::new(
+                "timer.default_countdown",
+                format!("{}..={}", timer.min_countdown, timer.max_countdown),
+                timer.default_countdown,
             ));
         }
 
-        if self.timer.warning_threshold > self.timer.default_countdown {
-            return Err(ConfigError::ValidationError(
-                "警告阈值不能大于默认倒计时".to_string()
+        if timer.warning_threshold > timer.default_countdown {
+            violations.push(Violation::new(
+                "timer.warning_threshold",
+                format!("≤ timer.default_countdown ({})", timer.default_countdown),
+                timer.warning_threshold,
             ));
         }
 
+        if timer.anchor == CountdownAnchor::Paste && timer.max_armed_seconds == 0 {
+            violations.push(Violation::new("timer.max_armed_seconds", "> 0（timer.anchor 为 paste 时）", 0));
+        }
+
         // 验证安全配置
         if self.security.memory_erase_rounds == 0 {
-            return Err(ConfigError::ValidationError(
-                "内存擦除轮数必须大于0".to_string()
-            ));
+            violations.push(Violation::new("security.memory_erase_rounds", "≥ 1", 0));
         }
-
-        if self.security.memory_erase_rounds > 10 {
-            warn!("内存擦除轮数过多可能影响性能: {}", self.security.memory_erase_rounds);
+        if self.security.memory_erase_patterns.is_empty() {
+            violations.push(Violation::new("security.memory_erase_patterns", "至少一种覆盖模式", "[]"));
         }
 
         // 验证剪贴板配置
         if self.clipboard.poll_interval_ms == 0 {
-            return Err(ConfigError::ValidationError(
-                "轮询间隔必须大于0".to_string()
+            violations.push(Violation::new("clipboard.poll_interval_ms", "≥ 1", 0));
+        }
+
+        if !(0.0..=1.0).contains(&self.clipboard.incremental_edit_similarity) {
+            violations.push(Violation::new(
+                "clipboard.incremental_edit_similarity",
+                "0.0..=1.0",
+                self.clipboard.incremental_edit_similarity,
             ));
         }
 
-        if self.clipboard.poll_interval_ms < 50 {
-            warn!("轮询间隔过短可能影响性能: {}ms", self.clipboard.poll_interval_ms);
+        if !self.sensitive_pattern.is_empty() {
+            if let Err(e) = Regex::new(&self.sensitive_pattern) {
+                violations.push(Violation::regex("sensitive_pattern", &self.sensitive_pattern, &e));
+            }
+        }
+
+        // 验证历史记录配置
+        if self.history.max_items > 10_000 {
+            violations.push(Violation::new("history.max_items", "0..=10000", self.history.max_items));
+        }
+
+        // 验证熵值规则
+        let entropy = &self.entropy_rule;
+        if entropy.enabled && entropy.min_length > entropy.max_length {
+            violations.push(Violation::new(
+                "entropy_rule.min_length",
+                format!("≤ entropy_rule.max_length ({})", entropy.max_length),
+                entropy.min_length,
+            ));
+        }
+
+        for (index, pattern) in entropy.allow_patterns.iter().enumerate() {
+            if let Err(e) = Regex::new(&format!("^(?:{})$", pattern)) {
+                violations.push(Violation::regex(&format!("entropy_rule.allow_patterns[{}]", index), pattern, &e));
+            }
+        }
+
+        let confirm = &self.paste_confirm;
+        if let Err(e) = Regex::new(&confirm.critical_pattern) {
+            violations.push(Violation::regex("paste_confirm.critical_pattern", &confirm.critical_pattern, &e));
+        }
+        if confirm.confirm_seconds == 0 {
+            violations.push(Violation::new("paste_confirm.confirm_seconds", "> 0", 0));
+        }
+
+        let escalation = &self.escalation;
+        if !(escalation.shrink_factor > 0.0 && escalation.shrink_factor <= 1.0) {
+            violations.push(Violation::new("escalation.shrink_factor", "0 < x ≤ 1", escalation.shrink_factor));
+        }
+        if escalation.confirm_seconds == 0 {
+            violations.push(Violation::new("escalation.confirm_seconds", "> 0", 0));
+        }
+
+        for (index, cap) in self.lifetime_caps.caps.iter().enumerate() {
+            if cap.name.trim().is_empty() {
+                violations.push(Violation::new(format!("lifetime_caps.caps[{}].name", index), "非空", "\"\""));
+            }
+            if cap.rules.is_empty() && cap.pattern.is_empty() {
+                violations.push(Violation::new(format!("lifetime_caps.caps[{}]", index), "rules 或 pattern 至少一项", "均为空"));
+            }
+            if let Err(e) = Regex::new(&cap.pattern) {
+                violations.push(Violation::regex(&format!("lifetime_caps.caps[{}].pattern", index), &cap.pattern, &e));
+            }
+            if cap.max_seconds == 0 {
+                violations.push(Violation::new(format!("lifetime_caps.caps[{}].max_seconds", index), "> 0", 0));
+            }
+        }
+
+        if self.login_pages.clear_delay_seconds == 0 {
+            violations.push(Violation::new("login_pages.clear_delay_seconds", "> 0", 0));
+        }
+        if self.login_pages.keywords.iter().any(|keyword| keyword.trim().is_empty()) {
+            violations.push(Violation::new("login_pages.keywords", "不含空字符串", "\"\""));
+        }
+        for (index, site) in self.login_pages.sites.iter().enumerate() {
+            if site.domain.trim().is_empty() {
+                violations.push(Violation::new(format!("login_pages.sites[{}].domain", index), "非空", "\"\""));
+            }
+            if site.titles.iter().any(|title| title.trim().is_empty()) {
+                violations.push(Violation::new(format!("login_pages.sites[{}].titles", index), "不含空字符串", "\"\""));
+            }
+            if site.clear_delay_seconds == Some(0) {
+                violations.push(Violation::new(format!("login_pages.sites[{}].clear_delay_seconds", index), "> 0", 0));
+            }
+        }
+
+        for (index, endpoint) in self.webhooks.endpoints.iter().enumerate() {
+            if !endpoint.url.starts_with("https://") && !endpoint.url.starts_with("http://") {
+                violations.push(Violation::new(format!("webhooks.endpoints[{}].url", index), "http(s)://地址", format!("{:?}", endpoint.url)));
+            }
+            if endpoint.secret.is_empty() {
+                violations.push(Violation::new(format!("webhooks.endpoints[{}].secret", index), "非空", "\"\""));
+            }
+        }
+        if self.webhooks.max_attempts == 0 {
+            violations.push(Violation::new("webhooks.max_attempts", "> 0", 0));
+        }
+        if self.webhooks.timeout_seconds == 0 {
+            violations.push(Violation::new("webhooks.timeout_seconds", "> 0", 0));
+        }
+
+        if self.canary.rotate_seconds == 0 {
+            violations.push(Violation::new("canary.rotate_seconds", "> 0", 0));
+        }
+        if self.canary.retain == 0 {
+            violations.push(Violation::new("canary.retain", "≥ 1", 0));
+        }
+
+        for (i, rule) in self.paste_transforms.rules.iter().enumerate() {
+            if rule.apps.is_empty() {
+                violations.push(Violation::new(format!("paste_transforms.rules[{}].apps", i), "至少一个程序名称", "空列表"));
+            }
+        }
+
+        for (i, rule) in self.paste_strategies.rules.iter().enumerate() {
+            if rule.apps.is_empty() {
+                violations.push(Violation::new(format!("paste_strategies.rules[{}].apps", i), "至少一个程序名称", "空列表"));
+            }
+        }
+
+        if self.decoy.enabled && self.decoy.templates.is_empty() {
+            violations.push(Violation::new("decoy.templates", "至少一个模板", "空列表"));
+        }
+        for (i, template) in self.decoy.templates.iter().enumerate() {
+            if let Err(e) = decoy::validate_template(template) {
+                violations.push(Violation::new(format!("decoy.templates[{}]", i), "有效的诱饵模板", e));
+            }
+        }
+
+        let ratio = self.reader_watch.read_ratio;
+        if !(ratio > 0.0 && ratio <= 1.0) {
+            violations.push(Violation::new("reader_watch.read_ratio", "(0, 1]", ratio));
+        }
+        if !(1..=5000).contains(&self.reader_watch.window_ms) {
+            violations.push(Violation::new("reader_watch.window_ms", "1..=5000", self.reader_watch.window_ms));
+        }
+
+        let security = &self.api.security;
+        if security.freshness_seconds == 0 {
+            violations.push(Violation::new("api.security.freshness_seconds", "> 0", 0));
+        }
+        if let Some(hash) = &security.passphrase_hash {
+            if !hash.starts_with("pbkdf2-sha256$") || hash.split('$').count() != 4 {
+                violations.push(Violation::new("api.security.passphrase_hash", "token passphrase 生成的哈希", "格式无效"));
+            }
+        }
+        if let Some(key) = &security.signing_public_key {
+            if decode_hex(key.trim()).is_none_or(|key| key.len() != 32) {
+                violations.push(Violation::new("api.security.signing_public_key", "64位十六进制", format!("{:?}", key)));
+            }
+        }
+
+        let guard = &self.nuke_guard;
+        if guard.enabled && guard.totp_secret.is_none() && guard.active_hardware_key().is_none() {
+            violations.push(Violation::new("nuke_guard", "totp_secret 或 hardware_key", "均未配置"));
+        }
+        if let Some(secret) = &guard.totp_secret {
+            if nuke_guard::decode_base32(secret).is_none_or(|secret| secret.len() < 10) {
+                violations.push(Violation::new("nuke_guard.totp_secret", "至少80位的Base32密钥", "无效或过短"));
+            }
+        }
+        if !(6..=8).contains(&guard.totp_digits) {
+            violations.push(Violation::new("nuke_guard.totp_digits", "6..=8", guard.totp_digits));
+        }
+        if guard.totp_step == 0 {
+            violations.push(Violation::new("nuke_guard.totp_step", "> 0", 0));
+        }
+        if let Some(key) = &guard.hardware_key {
+            if !matches!(key.slot, 1 | 2) {
+                violations.push(Violation::new("nuke_guard.hardware_key.slot", "1 或 2", key.slot));
+            }
+            if decode_hex(&key.challenge).is_none_or(|challenge| challenge.is_empty() || challenge.len() > 64) {
+                violations.push(Violation::new("nuke_guard.hardware_key.challenge", "1到64字节的十六进制", format!("{:?}", key.challenge)));
+            }
+            if decode_hex(&key.response_sha256).is_none_or(|hash| hash.len() != 32) {
+                violations.push(Violation::new("nuke_guard.hardware_key.response_sha256", "64位十六进制", format!("{:?}", key.response_sha256)));
+            }
+        }
+
+        if self.status_page.enabled && self.status_page.port == 0 {
+            violations.push(Violation::new("status_page.port", "1..=65535", 0));
+        }
+        if self.status_page.refresh_seconds == 0 {
+            violations.push(Violation::new("status_page.refresh_seconds", "> 0", 0));
+        }
+        if !(100..=60_000).contains(&self.shutdown.deadline_ms) {
+            violations.push(Violation::new("shutdown.deadline_ms", "100..=60000", self.shutdown.deadline_ms));
+        }
+        if self.health.interval_seconds == 0 {
+            violations.push(Violation::new("health.interval_seconds", "> 0", 0));
+        }
+        if self.health.stall_seconds <= self.health.interval_seconds {
+            violations.push(Violation::new(
+                "health.stall_seconds",
+                format!("> health.interval_seconds（{}）", self.health.interval_seconds),
+                self.health.stall_seconds,
+            ));
+        }
+        if self.safe_mode.enabled {
+            if self.safe_mode.crash_threshold == 0 {
+                violations.push(Violation::new("safe_mode.crash_threshold", "> 0", 0));
+            }
+            if self.safe_mode.window_minutes == 0 {
+                violations.push(Violation::new("safe_mode.window_minutes", "> 0", 0));
+            }
+        }
+
+        let mqtt = &self.integrations.mqtt;
+        if mqtt.enabled {
+            if mqtt.broker.rsplit_once(':').is_none_or(|(host, port)| host.is_empty() || port.parse::<u16>().is_err()) {
+                violations.push(Violation::new("integrations.mqtt.broker", "主机:端口", format!("{:?}", mqtt.broker)));
+            }
+            if mqtt.client_id.is_empty() || mqtt.client_id.len() > 23 {
+                violations.push(Violation::new("integrations.mqtt.client_id", "1..=23个字符", format!("{:?}", mqtt.client_id)));
+            }
+            if mqtt.topic_prefix.trim_end_matches('/').is_empty() || mqtt.topic_prefix.contains(['+', '#']) {
+                violations.push(Violation::new("integrations.mqtt.topic_prefix", "非空且不含通配符", format!("{:?}", mqtt.topic_prefix)));
+            }
+            if mqtt.username.is_empty() && !mqtt.password.is_empty() {
+                violations.push(Violation::new("integrations.mqtt.username", "设置密码时必须设置用户名", "\"\""));
+            }
+        }
+
+        let managers = &self.integrations.clipboard_managers;
+        if managers.maccy == ManagerMode::Delete {
+            violations.push(Violation::new("integrations.clipboard_managers.maccy", "off 或 ignore（Maccy不支持删除单条记录）", "delete"));
+        }
+        for (index, hook) in managers.hooks.iter().enumerate() {
+            if hook.name.is_empty() || hook.program.is_empty() {
+                violations.push(Violation::new(format!("integrations.clipboard_managers.hooks[{}]", index), "name 与 program 非空", format!("{:?}", hook.name)));
+            }
+        }
+
+        // 验证热键
+        let hotkeys = [
+            ("hotkeys.emergency_nuke_key", &self.hotkeys.emergency_nuke_key),
+            ("hotkeys.show_status_key", &self.hotkeys.show_status_key),
+            ("hotkeys.toggle_monitoring_key", &self.hotkeys.toggle_monitoring_key),
+            ("hotkeys.undo_key", &self.hotkeys.undo_key),
+            ("hotkeys.promote_rule_key", &self.hotkeys.promote_rule_key),
+            ("hotkeys.toggle_overlay_key", &self.hotkeys.toggle_overlay_key),
+            ("hotkeys.approve_paste_key", &self.hotkeys.approve_paste_key),
+        ];
+        let bindings = self.hotkeys.bindings.iter().enumerate().map(|(index, binding)| {
+            (format!("hotkeys.bindings[{}].key", index), &binding.key)
+        });
+        let hotkeys = hotkeys.into_iter().map(|(path, hotkey)| (path.to_string(), hotkey)).chain(bindings);
+        for (path, hotkey) in hotkeys {
+            if let Err(reason) = check_hotkey(hotkey) {
+                violations.push(Violation::new(
+                    path,
+                    "修饰键+按键，如 Ctrl+Alt+V",
+                    format!("{:?}（{}）", hotkey, reason),
+                ));
+            }
+        }
+
+        for (index, binding) in self.hotkeys.bindings.iter().enumerate() {
+            let duplicate = self.hotkeys.bindings[..index]
+                .iter()
+                .any(|earlier| normalize_hotkey(&earlier.key) == normalize_hotkey(&binding.key));
+            if duplicate {
+                violations.push(Violation::new(
+                    format!("hotkeys.bindings[{}].key", index),
+                    "未被其他自定义绑定使用的按键",
+                    format!("{:?}", binding.key),
+                ));
+            }
+            match binding.action {
+                HotkeyAction::Extend { seconds: 0 } => violations.push(Violation::new(
+                    format!("hotkeys.bindings[{}].seconds", index),
+                    "≥ 1",
+                    0,
+                )),
+                HotkeyAction::PasteSlot { slot: 0 } => violations.push(Violation::new(
+                    format!("hotkeys.bindings[{}].slot", index),
+                    "≥ 1（1为最新）",
+                    0,
+                )),
+                _ => {}
+            }
+        }
+
+        // 验证托管公钥
+        if let Err(e) = self.escrow.recipient() {
+            violations.push(Violation::new(
+                "escrow.recovery_public_key",
+                "64位十六进制X25519公钥",
+                format!("{:?}（{}）", self.escrow.recovery_public_key.as_deref().unwrap_or_default(), e),
+            ));
+        }
+
+        // 验证规则包签名公钥
+        if let Some(key) = self.rule_packs.invalid_key() {
+            violations.push(Violation::new("rule_packs.trusted_keys", "64位十六进制Ed25519公钥", format!("{:?}", key)));
+        }
+
+        // 验证代理套接字客户端
+        for (index, client) in self.agent.clients.iter().enumerate() {
+            if !client.path.is_absolute() {
+                violations.push(Violation::new(
+                    format!("agent.clients[{}].path", index),
+                    "绝对路径",
+                    format!("{:?}", client.path),
+                ));
+            }
+            let sha256 = client.sha256.trim();
+            if sha256.len() != 64 || !sha256.bytes().all(|b| b.is_ascii_hexdigit()) {
+                violations.push(Violation::new(
+                    format!("agent.clients[{}].sha256", index),
+                    "64位十六进制SHA-256",
+                    format!("{:?}", client.sha256),
+                ));
+            }
+        }
+
+        if !(self.overlay.opacity > 0.0 && self.overlay.opacity <= 1.0) {
+            violations.push(Violation::new("overlay.opacity", "(0, 1]", self.overlay.opacity));
         }
 
         // 验证日志级别
-        let valid_log_levels = ["error", "warn", "info", "debug", "trace"];
+        let valid_log_levels = ["error", "warn", "info", "debug", "trace", "off"];
+        let expected_level = valid_log_levels.join("|");
         if !valid_log_levels.contains(&self.ui.log_level.as_str()) {
-            return Err(ConfigError::ValidationError(
-                format!("无效的日志级别: {}", self.ui.log_level)
-            ));
+            violations.push(Violation::new("ui.log_level", &expected_level, format!("{:?}", self.ui.log_level)));
         }
 
-        debug!("配置验证通过");
-        Ok(())
+        for (module, level) in &self.ui.module_log_levels {
+            if !valid_log_levels.contains(&level.as_str()) {
+                violations.push(Violation::new(
+                    format!("ui.module_log_levels.{}", module),
+                    &expected_level,
+                    format!("{:?}", level),
+                ));
+            }
+        }
+
+        violations
     }
 
     /// 重置为默认配置
@@ -405,8 +1736,8 @@ impl Config {
     /// # 返回值
     /// * `Result<(), ConfigError>` - 操作结果
     pub fn reset_to_default(&mut self) -> Result<(), ConfigError> {
-        *self = Config::default();
-        self.save()?;
+        Config::default().save()?;
+        *self = Config::default().with_overrides()?.with_managed_policy()?;
         info!("配置已重置为默认值");
         Ok(())
     }
@@ -415,16 +1746,60 @@ impl Config {
     ///
     /// # 返回值
     /// * `Result<PathBuf, ConfigError>` - 配置文件路径
-    fn get_config_file_path() -> Result<PathBuf, ConfigError> {
+    pub fn get_config_file_path() -> Result<PathBuf, ConfigError> {
         let config_dir = Self::get_config_directory()?;
         Ok(config_dir.join("config.json"))
     }
 
+    /// 在日志系统初始化前读取界面配置
+    ///
+    /// 不输出任何日志，读取失败时返回默认值
+    ///
+    /// # 返回值
+    /// * `UiConfig` - 界面配置
+    pub fn peek_ui_config() -> UiConfig {
+        Self::get_config_file_path()
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+            .and_then(|json| json.get("ui").cloned())
+            .and_then(|ui| serde_json::from_value::<UiConfig>(ui).ok())
+            .unwrap_or_default()
+            .with_overrides()
+    }
+
+    /// 获取日志文件路径
+    ///
+    /// # 返回值
+    /// * `Result<PathBuf, ConfigError>` - 日志文件路径
+    pub fn get_log_file_path() -> Result<PathBuf, ConfigError> {
+        let config_dir = Self::get_config_directory()?;
+        Ok(config_dir.join("clipvanish.log"))
+    }
+
+    /// 获取最近一次崩溃记录的文件路径
+    ///
+    /// # 返回值
+    /// * `Result<PathBuf, ConfigError>` - 崩溃记录文件路径
+    pub fn get_panic_file_path() -> Result<PathBuf, ConfigError> {
+        let config_dir = Self::get_config_directory()?;
+        Ok(config_dir.join("last_panic.txt"))
+    }
+
+    /// 获取崩溃计数文件路径（安全模式判断依据）
+    ///
+    /// # 返回值
+    /// * `Result<PathBuf, ConfigError>` - 崩溃计数文件路径
+    pub fn get_crash_counter_path() -> Result<PathBuf, ConfigError> {
+        let config_dir = Self::get_config_directory()?;
+        Ok(config_dir.join("crash_counter.json"))
+    }
+
     /// 获取配置目录路径
     ///
     /// # 返回值
     /// * `Result<PathBuf, ConfigError>` - 配置目录路径
-    fn get_config_directory() -> Result<PathBuf, ConfigError> {
+    pub fn get_config_directory() -> Result<PathBuf, ConfigError> {
         // 跨平台配置目录
         let config_dir = if cfg!(windows) {
             // Windows: %APPDATA%\ClipVanish
@@ -490,49 +1865,295 @@ impl Config {
 
     /// 显示当前配置
     pub fn display(&self) {
-        println!("📋 ClipVanish™ 配置信息");
-        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-        println!("🔧 版本: {}", self.version);
-        println!();
-
-        println!("⏰ 定时器配置:");
-        println!("   默认倒计时: {}秒", self.timer.default_countdown);
-        println!("   倒计时范围: {}-{}秒", self.timer.min_countdown, self.timer.max_countdown);
-        println!("   警告阈值: {}秒", self.timer.warning_threshold);
-        println!("   启用警告: {}", if self.timer.enable_warnings { "是" } else { "否" });
-        println!();
-
-        println!("🛡️ 安全配置:");
-        println!("   内存锁定: {}", if self.security.enable_memory_locking { "启用" } else { "禁用" });
-        println!("   擦除轮数: {}轮", self.security.memory_erase_rounds);
-        println!("   退出时清除: {}", if self.security.auto_clear_on_exit { "是" } else { "否" });
-        println!("   密钥轮换: {}", if self.security.enable_key_rotation { "启用" } else { "禁用" });
-        println!("   粘贴即销毁: {}", if self.security.destroy_on_paste { "启用" } else { "禁用" });
-        println!("   最小保护长度: {} 字节", self.security.min_length_for_protection);
-        println!("   敏感内容模式: {}", self.security.sensitive_pattern);
-        println!();
-
-        println!("🎨 界面配置:");
-        println!("   详细输出: {}", if self.ui.verbose_output { "是" } else { "否" });
-        println!("   显示进度: {}", if self.ui.show_progress { "是" } else { "否" });
-        println!("   彩色输出: {}", if self.ui.enable_colors { "是" } else { "否" });
-        println!("   日志级别: {}", self.ui.log_level);
-        println!();
-
-        println!("⌨️ 热键配置:");
-        println!("   全局热键: {}", if self.hotkeys.enable_global_hotkeys { "启用" } else { "禁用" });
-        println!("   紧急销毁: {}", self.hotkeys.emergency_nuke_key);
-        println!("   显示状态: {}", self.hotkeys.show_status_key);
-        println!("   切换监听: {}", self.hotkeys.toggle_monitoring_key);
-        println!();
-
-        println!("📋 剪贴板配置:");
-        println!("   轮询间隔: {}ms", self.clipboard.poll_interval_ms);
-        println!("   支持类型: {}", self.clipboard.supported_types.join(", "));
-        println!("   最大长度: {} 字节", self.clipboard.max_content_length);
+        notice!("📋 ClipVanish™ 配置信息");
+        notice!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        notice!("🔧 版本: {}", self.version);
+        notice!();
+
+        if let Some(policy) = &self.managed_policy {
+            notice!("🏢 受管策略: {}（强制 {} 项设置，不可在用户配置中修改）", policy.source, policy.enforced.len());
+            for conflict in &policy.conflicts {
+                notice!("   ⚠️  {}", conflict);
+            }
+            notice!();
+        }
+
+        notice!("⏰ 定时器配置:");
+        notice!("   默认倒计时: {}秒", self.timer.default_countdown);
+        notice!("   倒计时范围: {}-{}秒", self.timer.min_countdown, self.timer.max_countdown);
+        notice!("   警告阈值: {}秒", self.timer.warning_threshold);
+        notice!("   启用警告: {}", if self.timer.enable_warnings { "是" } else { "否" });
+        notice!("   倒计时锚点: {}", self.timer.anchor);
+        if self.timer.anchor == CountdownAnchor::Paste {
+            notice!("   最长武装时间: {}秒", self.timer.max_armed_seconds);
+        }
+        if self.timer.undo_grace_seconds > 0 {
+            notice!("   撤销宽限期: {}秒", self.timer.undo_grace_seconds);
+        }
+        notice!();
+
+        notice!("🛡️ 安全配置:");
+        notice!("   内存锁定: {}", if self.security.enable_memory_locking { "启用" } else { "禁用" });
+        let patterns: Vec<String> = self.security.memory_erase_patterns.iter().map(|pattern| pattern.to_string()).collect();
+        notice!("   擦除轮数: {}轮（{}）", self.security.memory_erase_rounds, patterns.join(" → "));
+        notice!("   退出时清除: {}", if self.security.auto_clear_on_exit { "是" } else { "否" });
+        notice!("   密钥轮换: {}", if self.security.enable_key_rotation { "启用" } else { "禁用" });
+        notice!("   粘贴即销毁: {}", if self.security.destroy_on_paste { "启用" } else { "禁用" });
+        notice!("   最小保护长度: {} 字节", self.security.min_length_for_protection);
+        notice!("   敏感内容模式: {}", self.security.sensitive_pattern);
+        notice!("   熵值规则: {}", if self.entropy_rule.enabled { "启用" } else { "禁用" });
+        notice!("   偏好学习: {}", if self.learning.enabled { format!("启用（阈值{}次）", self.learning.threshold) } else { "禁用".to_string() });
+        notice!("   新规则试运行: {}", if self.rule_trial.enabled { format!("{}天或{}次命中内仅警告", self.rule_trial.days, self.rule_trial.matches) } else { "禁用".to_string() });
+        if !self.paste_context.blocked_monitors.is_empty() {
+            notice!("   禁止粘贴的显示器: {}", self.paste_context.blocked_monitors.join(", "));
+        }
+        if !self.paste_context.blocked_desktops.is_empty() {
+            notice!("   禁止粘贴的虚拟桌面: {}", self.paste_context.blocked_desktops.join(", "));
+        }
+        let gestures: Vec<&str> = [
+            (self.paste_gestures.ctrl_shift_v, "Ctrl+Shift+V"),
+            (self.paste_gestures.shift_insert, "Shift+Insert"),
+            (self.paste_gestures.middle_click, "鼠标中键"),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, gesture)| enabled.then_some(gesture))
+        .collect();
+        notice!("   粘贴手势: Ctrl+V{}", gestures.iter().map(|gesture| format!("、{}", gesture)).collect::<String>());
+        let mut critical = self.paste_confirm.critical_rules.clone();
+        if !self.paste_confirm.critical_pattern.is_empty() {
+            critical.push(format!("/{}/", self.paste_confirm.critical_pattern));
+        }
+        if !critical.is_empty() {
+            notice!("   粘贴前需确认: {}（{}秒内再次粘贴或按热键确认）", critical.join(", "), self.paste_confirm.confirm_seconds);
+        }
+        if self.escalation.enabled {
+            let escalation = &self.escalation;
+            let stage = |count: u32| if count == 0 { "不启用".to_string() } else { format!("{}次后", count) };
+            notice!(
+                "   延长收紧: 确认 {} / 递减 {}（×{}，不少于{}秒） / 销毁 {}",
+                stage(escalation.confirm_after),
+                stage(escalation.shrink_after),
+                escalation.shrink_factor,
+                escalation.min_extension_seconds,
+                stage(escalation.destroy_after)
+            );
+        } else {
+            notice!("   延长收紧: 关闭");
+        }
+        if self.lifetime_caps.enabled && !self.lifetime_caps.caps.is_empty() {
+            let caps: Vec<String> = self.lifetime_caps.caps.iter().map(|cap| format!("{} {}秒", cap.name, cap.max_seconds)).collect();
+            notice!("   存活上限: {}（延长与固定不能越过）", caps.join(", "));
+        }
+        if self.login_pages.enabled {
+            notice!(
+                "   登录页面检测: 启用（{}秒，{}追加站点 {} 个）",
+                self.login_pages.clear_delay_seconds,
+                if self.login_pages.builtin_sites { "内置站点 + " } else { "" },
+                self.login_pages.sites.len()
+            );
+        }
+        if self.notifications.respect_focus_mode {
+            let fallback: Vec<&str> = [(self.notifications.terminal_bell, "终端响铃"), (self.notifications.flash_window, "窗口闪烁")]
+                .iter()
+                .filter(|(enabled, _)| *enabled)
+                .map(|(_, channel)| *channel)
+                .collect();
+            notice!(
+                "   勿扰模式: 遵循（关键告警{}）",
+                if fallback.is_empty() { "仍发送紧急通知".to_string() } else { format!("另用{}", fallback.join("与")) }
+            );
+        }
+        if !self.webhooks.endpoints.is_empty() {
+            notice!("   Webhook推送: {} 个端点", self.webhooks.endpoints.len());
+        }
+        if self.canary.enabled {
+            notice!("   诱饵令牌: 剪贴板空闲 {}秒后放置，每 {}秒轮换", self.canary.idle_seconds, self.canary.rotate_seconds);
+        }
+        if !self.paste_transforms.rules.is_empty() {
+            notice!("   粘贴转换: {} 条按程序生效的规则", self.paste_transforms.rules.len());
+        }
+        if !self.paste_strategies.rules.is_empty() || self.paste_strategies.default != PasteStrategy::Keystroke {
+            notice!(
+                "   粘贴方式: 默认 {}，{} 条按程序生效的规则",
+                self.paste_strategies.default,
+                self.paste_strategies.rules.len()
+            );
+        }
+        if self.decoy.enabled {
+            notice!("   诱饵内容: 剪贴板中以 {} 个模板生成的诱饵文本代替密文", self.decoy.templates.len());
+        }
+        if self.reader_watch.enabled {
+            notice!(
+                "   可疑读取者检测: 启用{}",
+                if self.reader_watch.auto_nuke_unknown { "（未知读取者出现时自动销毁）" } else { "" }
+            );
+        }
+        if self.nuke_guard.enabled {
+            let factors: Vec<&str> = [
+                self.nuke_guard.totp_secret.as_ref().map(|_| "TOTP验证码"),
+                self.nuke_guard.active_hardware_key().map(|_| "硬件密钥"),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+            notice!("   紧急销毁双人确认: {}", factors.join("或"));
+        }
+        if self.status_page.enabled {
+            notice!("   状态页: http://127.0.0.1:{}/（每 {}秒刷新）", self.status_page.port, self.status_page.refresh_seconds);
+        }
+        notice!("   关闭期限: {}毫秒", self.shutdown.deadline_ms);
+        if self.health.enabled {
+            notice!(
+                "   健康检查: 每 {}秒（{}秒无进展视为卡住，{}）",
+                self.health.interval_seconds,
+                self.health.stall_seconds,
+                if self.health.exit_after == 0 {
+                    "不健康时不退出".to_string()
+                } else {
+                    format!("连续 {} 次不健康后退出", self.health.exit_after)
+                }
+            );
+        }
+        if self.safe_mode.enabled {
+            notice!("   安全模式: {} 分钟内崩溃 {} 次后启用", self.safe_mode.window_minutes, self.safe_mode.crash_threshold);
+        }
+        if self.integrations.mqtt.enabled {
+            notice!("   MQTT发布: {}（主题前缀 {}）", self.integrations.mqtt.broker, self.integrations.mqtt.topic_prefix);
+        }
+        let managers = &self.integrations.clipboard_managers;
+        if managers.enabled {
+            notice!("   剪贴板管理器协作: CopyQ {}，Maccy {}，钩子 {} 个", managers.copyq, managers.maccy, managers.hooks.len());
+        }
+        if let Ok(Some(recipient)) = self.escrow.recipient() {
+            notice!("   密钥托管: 启用（恢复公钥 {}）", recipient.fingerprint());
+        }
+        if !self.rule_packs.trusted_keys.is_empty() {
+            notice!("   受信任的规则包签名公钥: {} 个", self.rule_packs.trusted_keys.len());
+        }
+        notice!("   销毁时保留非敏感内容: {}", if self.security.preserve_plain_on_nuke { "是" } else { "否" });
+        notice!();
+
+        notice!("🎨 界面配置:");
+        notice!("   详细输出: {}", if self.ui.verbose_output { "是" } else { "否" });
+        notice!("   显示进度: {}", if self.ui.show_progress { "是" } else { "否" });
+        notice!("   彩色输出: {}", if self.ui.enable_colors { "是" } else { "否" });
+        notice!("   日志级别: {}", self.ui.log_level);
+        for (module, level) in &self.ui.module_log_levels {
+            notice!("     {}: {}", module, level);
+        }
+        notice!("   确认超时: {}秒", self.ui.confirm_timeout_seconds);
+        notice!("   标题倒计时: {}", if self.ui.countdown_title { "是" } else { "否" });
+        notice!("   倒计时状态文件: {}", if self.ui.countdown_status { "是" } else { "否" });
+        notice!("   保存命令历史: {}", if self.ui.repl_history { "是" } else { "否" });
+        notice!("   倒计时悬浮窗: {}", if self.overlay.enabled { format!("启动时显示（{:?}）", self.overlay.position) } else { "隐藏".to_string() });
+        notice!();
+
+        notice!("⌨️ 热键配置:");
+        notice!("   全局热键: {}", if self.hotkeys.enable_global_hotkeys { "启用" } else { "禁用" });
+        notice!("   紧急销毁: {}", self.hotkeys.emergency_nuke_key);
+        notice!("   显示状态: {}", self.hotkeys.show_status_key);
+        notice!("   切换监听: {}", self.hotkeys.toggle_monitoring_key);
+        notice!("   撤销清除: {}", self.hotkeys.undo_key);
+        notice!("   倒计时悬浮窗: {}", self.hotkeys.toggle_overlay_key);
+        notice!("   确认高风险粘贴: {}", self.hotkeys.approve_paste_key);
+        notice!();
+
+        notice!("📋 剪贴板配置:");
+        notice!("   轮询间隔: {}ms", self.clipboard.poll_interval_ms);
+        notice!("   支持类型: {}", self.clipboard.supported_types.join(", "));
+        notice!("   访问方式: {}", self.clipboard.access);
+        notice!("   最大长度: {} 字节", self.clipboard.max_content_length);
+        notice!("   隔离未知格式: {}", if self.clipboard.quarantine_unknown_formats { "启用" } else { "禁用" });
+        if !self.clipboard.quarantine_allowlist.is_empty() {
+            notice!("   隔离恢复白名单: {}", self.clipboard.quarantine_allowlist.join(", "));
+        }
+        notice!("   剪贴板历史(Win+V): {}", self.clipboard.history_policy);
+        notice!("   通用剪贴板(Handoff): {}", self.clipboard.handoff_policy);
+        notice!("   延迟渲染(Windows): {}", if self.clipboard.delayed_rendering { "启用" } else { "禁用" });
+        if self.clipboard.incremental_edit_similarity > 0.0 {
+            notice!(
+                "   增量编辑合并: 相似度≥{:.0}%，变化≤{}字符",
+                self.clipboard.incremental_edit_similarity * 100.0,
+                self.clipboard.incremental_edit_max_delta
+            );
+        } else {
+            notice!("   增量编辑合并: 禁用");
+        }
+        match self.clipboard.interception_slo() {
+            Some(slo) => notice!("   拦截延迟SLO: {}ms", slo.as_millis()),
+            None => notice!("   拦截延迟SLO: 不检查"),
+        }
+        notice!();
+
+        notice!("🕘 历史记录:");
+        notice!("   缓冲区容量: {} 条", self.history.max_items);
+        match self.history.max_age() {
+            Some(max_age) => notice!("   保留时间: {}秒", max_age.as_secs()),
+            None => notice!("   保留时间: 不限"),
+        }
+        notice!("   保存明文: {}", if self.history.never_store_plaintext { "从不" } else { "是" });
+        notice!("   持久化元数据: {}", if self.history.persist { "启用" } else { "禁用" });
+        notice!();
+
+        notice!("🔌 控制接口:");
+        notice!("   控制套接字: {}", if self.ipc.enabled { "启用" } else { "禁用" });
+        if let Ok(path) = self.ipc.resolved_socket_path() {
+            notice!("   套接字路径: {}", path.display());
+        }
+        if self.api.security.enabled() {
+            let commands: Vec<&str> = self.api.security.protected_commands.iter().map(|command| command.name()).collect();
+            notice!("   二次验证: {}（口令或签名）", commands.join("、"));
+        }
+        notice!("   代理套接字: {}", if self.agent.enabled { "启用" } else { "禁用" });
+        if let Ok(path) = self.agent.resolved_socket_path() {
+            notice!("   代理套接字路径: {}", path.display());
+        }
+        for client in &self.agent.clients {
+            notice!("   代理客户端: {} ({}, {:?})", client.name, client.path.display(), client.approval);
+        }
     }
 }
 
+/// 原子写入文件：先写入同目录下的临时文件并同步到磁盘，再重命名覆盖目标
+///
+/// 写入中途崩溃时目标文件保持原样
+///
+/// # 参数
+/// * `path` - 目标文件路径
+/// * `content` - 文件内容
+pub fn write_atomic(path: &Path, content: &str) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let temp_path = path.with_file_name(format!(".{}.tmp", file_name));
+
+    let result = (|| {
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temp_path, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// 配置备份目录（配置文件所在目录下的backups）
+fn backup_directory(path: &Path) -> PathBuf {
+    path.parent().unwrap_or_else(|| Path::new(".")).join("backups")
+}
+
+/// 备份文件名前缀（配置文件名去掉扩展名）
+fn backup_prefix(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "config".to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -569,6 +2190,107 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_validation_reports_every_violation() {
+        let mut config = Config::default();
+        config.timer.min_countdown = 100;
+        config.timer.max_countdown = 50;
+        config.history.max_items = 20_000;
+        config.sensitive_pattern = "(unclosed".to_string();
+        config.entropy_rule.allow_patterns = vec!["[0-9a-f]{40}".to_string(), "[z-a]".to_string()];
+        config.hotkeys.undo_key = "Ctrl+Hyper+Z".to_string();
+
+        let paths: Vec<String> = config.violations().into_iter().map(|v| v.path).collect();
+        assert_eq!(paths, [
+            "timer.min_countdown",
+            "timer.default_countdown",
+            "sensitive_pattern",
+            "history.max_items",
+            "entropy_rule.allow_patterns[1]",
+            "hotkeys.undo_key",
+        ]);
+
+        match config.validate() {
+            Err(ConfigError::Invalid(violations)) => {
+                assert_eq!(violations.len(), 6);
+                assert_eq!(violations[3].expected, "0..=10000");
+                assert_eq!(violations[3].actual, "20000");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_hotkey_bindings() {
+        let json = r#"{"enable_global_hotkeys": true, "emergency_nuke_key": "Ctrl+Alt+V",
+            "show_status_key": "Ctrl+Alt+S", "toggle_monitoring_key": "Ctrl+Alt+M",
+            "bindings": [
+                {"key": "ctrl + alt + s", "action": "pin"},
+                {"key": "Ctrl+Alt+1", "action": "paste_slot", "slot": 1},
+                {"key": "Ctrl+Alt+X", "action": "extend"}
+            ]}"#;
+        let hotkeys: HotkeyConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(hotkeys.bindings[2].action, HotkeyAction::Extend { seconds: 60 });
+
+        // 自定义绑定覆盖同一按键的内置热键
+        let bindings = hotkeys.effective_bindings();
+        assert_eq!(bindings.len(), 9);
+        assert!(!bindings.iter().any(|binding| binding.action == HotkeyAction::ShowStatus));
+        assert!(bindings.contains(&HotkeyBinding::new("Ctrl+Alt+C", HotkeyAction::ToggleOverlay)));
+        assert!(bindings.contains(&HotkeyBinding::new("Ctrl+Alt+Y", HotkeyAction::ApprovePaste)));
+        assert!(bindings.contains(&HotkeyBinding::new("Ctrl+Alt+1", HotkeyAction::PasteSlot { slot: 1 })));
+
+        let mut config = Config { hotkeys, ..Default::default() };
+        config.hotkeys.bindings.push(HotkeyBinding::new("Ctrl+Alt+1", HotkeyAction::PasteSlot { slot: 0 }));
+        let paths: Vec<String> = config.violations().into_iter().map(|v| v.path).collect();
+        assert_eq!(paths, ["hotkeys.bindings[3].key", "hotkeys.bindings[3].slot"]);
+    }
+
+    #[test]
+    fn test_hotkey_strings() {
+        for hotkey in ["Ctrl+Alt+V", "ctrl + shift + f12", "Cmd+Space", "Super+1"] {
+            assert!(check_hotkey(hotkey).is_ok(), "{}", hotkey);
+        }
+        for hotkey in ["V", "Ctrl+", "Ctrl+Alt+VV", "Hyper+V", "Ctrl+F25", ""] {
+            assert!(check_hotkey(hotkey).is_err(), "{}", hotkey);
+        }
+    }
+
+    #[test]
+    fn test_log_filter_building() {
+        let mut ui = UiConfig::default();
+        assert_eq!(ui.build_log_filter("info", None), "clipvanish=info");
+
+        ui.module_log_levels.insert("keyboard".to_string(), "warn".to_string());
+        ui.module_log_levels.insert("clipvanish::clipboard".to_string(), "debug".to_string());
+        assert_eq!(
+            ui.build_log_filter("debug", Some("clipvanish::timer=trace")),
+            "clipvanish=debug,clipvanish::clipboard=debug,clipvanish::keyboard=warn,clipvanish::timer=trace"
+        );
+
+        // 无效的模块日志级别无法通过验证
+        let mut config = Config::default();
+        config.ui.module_log_levels.insert("keyboard".to_string(), "loud".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_countdown_anchor() {
+        let config = Config::default();
+        assert_eq!(config.timer.anchor, CountdownAnchor::Copy);
+
+        // 旧配置文件缺少锚点字段时使用默认值
+        let mut json = serde_json::to_value(&config).unwrap();
+        json["timer"].as_object_mut().unwrap().remove("anchor");
+        json["timer"].as_object_mut().unwrap().remove("max_armed_seconds");
+        let parsed: Config = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.timer.anchor, CountdownAnchor::Copy);
+        assert_eq!(parsed.timer.max_armed_seconds, 300);
+
+        let anchor: CountdownAnchor = serde_json::from_str("\"paste\"").unwrap();
+        assert_eq!(anchor, CountdownAnchor::Paste);
+    }
+
     #[test]
     fn test_config_save_load() {
         let temp_dir = tempdir().unwrap();
@@ -588,6 +2310,46 @@ mod tests {
         assert_eq!(original_config.security.memory_erase_rounds, loaded_config.security.memory_erase_rounds);
     }
 
+    #[test]
+    fn test_config_backups_and_restore() {
+        let temp_dir = tempdir().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+
+        let mut config = Config::default();
+        for countdown in 10..18 {
+            config.timer.default_countdown = countdown;
+            config.save_to_file(&config_path).unwrap();
+        }
+
+        // 每次覆盖前备份旧文件，只保留最近的若干份
+        let backups = Config::list_backups_for(&config_path);
+        assert_eq!(backups.len(), MAX_CONFIG_BACKUPS);
+        assert!(!temp_dir.path().join(".config.json.tmp").exists());
+
+        let restored = Config::restore_backup_for(&config_path, 1).unwrap();
+        assert_eq!(restored.timer.default_countdown, 16);
+        assert_eq!(Config::load_from_file(&config_path).unwrap().timer.default_countdown, 16);
+        assert!(Config::restore_backup_for(&config_path, 99).is_err());
+    }
+
+    #[test]
+    fn test_corrupt_config_recovers_from_backup() {
+        let temp_dir = tempdir().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+
+        let mut config = Config::default();
+        config.timer.default_countdown = 42;
+        config.save_to_file(&config_path).unwrap();
+        config.save_to_file(&config_path).unwrap();
+
+        // 模拟写入中途崩溃留下的截断文件
+        fs::write(&config_path, "{\"version\": \"0.1.0\", \"timer\": {").unwrap();
+
+        let recovered = Config::load_from_file(&config_path).unwrap();
+        assert_eq!(recovered.timer.default_countdown, 42);
+        assert!(temp_dir.path().join("config.json.corrupt").exists());
+    }
+
     #[test]
     fn test_duration_helpers() {
         let config = Config::default();
@@ -0,0 +1,205 @@
+/*!
+ * ClipVanish™ 沙箱环境检测模块
+ *
+ * 识别 Flatpak、Snap、AppArmor、SELinux 与 macOS App Sandbox 等限制环境，
+ * 在剪贴板或键盘访问可能被阻止时给出针对性的处理建议
+ * 特点：
+ * - 只读取 /.flatpak-info、/proc/self/attr 与环境变量，不修改系统状态
+ * - 未受限（unconfined）的安全模块配置不报告
+ * - 检测 xdg-desktop-portal 是否可用，便于判断能否改用门户访问剪贴板
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::fs;
+use std::path::Path;
+
+/// 检测到的限制环境
+#[derive(Debug, Clone, PartialEq)]
+pub enum Confinement {
+    /// Flatpak沙箱（应用ID）
+    Flatpak { app_id: String },
+    /// Snap沙箱（名称, 限制级别）
+    Snap { name: String, confinement: String },
+    /// AppArmor配置（配置名, 是否强制模式）
+    AppArmor { profile: String, enforce: bool },
+    /// SELinux上下文
+    SeLinux { context: String },
+    /// macOS App Sandbox（容器ID）
+    AppSandbox { container: String },
+}
+
+impl Confinement {
+    /// 环境名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            Confinement::Flatpak { .. } => "Flatpak沙箱",
+            Confinement::Snap { .. } => "Snap沙箱",
+            Confinement::AppArmor { .. } => "AppArmor",
+            Confinement::SeLinux { .. } => "SELinux",
+            Confinement::AppSandbox { .. } => "App Sandbox",
+        }
+    }
+
+    /// 环境说明
+    pub fn describe(&self) -> String {
+        match self {
+            Confinement::Flatpak { app_id } => format!("应用 {}", app_id),
+            Confinement::Snap { name, confinement } => format!("{}（{}）", name, confinement),
+            Confinement::AppArmor { profile, enforce } => {
+                format!("配置 {}（{}）", profile, if *enforce { "强制模式" } else { "投诉模式" })
+            }
+            Confinement::SeLinux { context } => format!("上下文 {}", context),
+            Confinement::AppSandbox { container } => format!("容器 {}", container),
+        }
+    }
+
+    /// 剪贴板或键盘访问被阻止时的处理建议
+    pub fn guidance(&self) -> String {
+        match self {
+            Confinement::Flatpak { app_id } => format!(
+                "需要X11或Wayland访问：flatpak override --user --socket=x11 --socket=wayland {}",
+                app_id
+            ),
+            Confinement::Snap { name, .. } => format!(
+                "连接图形接口：snap connect {0}:x11 && snap connect {0}:wayland；键盘监听需要classic或devmode安装",
+                name
+            ),
+            Confinement::AppArmor { profile, .. } => format!(
+                "在配置 {} 中允许访问X11套接字（/tmp/.X11-unix/*）与 /dev/input，或临时使用 aa-complain 排查",
+                profile
+            ),
+            Confinement::SeLinux { .. } => {
+                "使用 ausearch -m avc -ts recent 查看被拒绝的访问，并为X11与输入设备访问添加策略".to_string()
+            }
+            Confinement::AppSandbox { .. } => {
+                "在应用的entitlements中允许剪贴板与辅助功能访问，并在系统设置中授予辅助功能权限".to_string()
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Confinement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.label(), self.describe())
+    }
+}
+
+/// 检测当前进程所处的限制环境
+pub fn detect() -> Vec<Confinement> {
+    let mut found = Vec::new();
+
+    if let Ok(info) = fs::read_to_string("/.flatpak-info") {
+        found.push(parse_flatpak_info(&info));
+    }
+
+    if let Ok(name) = std::env::var("SNAP_NAME") {
+        let confinement = std::env::var("SNAP_CONFINEMENT").unwrap_or_else(|_| "strict".to_string());
+        // classic安装不受沙箱限制
+        if confinement != "classic" {
+            found.push(Confinement::Snap { name, confinement });
+        }
+    }
+
+    // 较新的内核将AppArmor标签放在独立的目录中
+    let apparmor = fs::read_to_string("/proc/self/attr/apparmor/current")
+        .or_else(|_| fs::read_to_string("/proc/self/attr/current"));
+    if let Ok(label) = apparmor {
+        found.extend(parse_security_label(&label, Path::new("/sys/fs/selinux").exists()));
+    }
+
+    if let Ok(container) = std::env::var("APP_SANDBOX_CONTAINER_ID") {
+        found.push(Confinement::AppSandbox { container });
+    }
+
+    found
+}
+
+/// 是否运行在应用沙箱（Flatpak或严格限制的Snap）中，此时应经门户访问剪贴板
+pub fn is_app_sandbox() -> bool {
+    detect()
+        .iter()
+        .any(|confinement| matches!(confinement, Confinement::Flatpak { .. } | Confinement::Snap { .. }))
+}
+
+/// 为访问失败的错误信息附加沙箱处理建议（未检测到限制环境时原样返回）
+///
+/// # 参数
+/// * `message` - 原始错误信息
+pub fn annotate(message: &str) -> String {
+    match detect().first() {
+        Some(confinement) => format!("{}（检测到{}，{}）", message, confinement, confinement.guidance()),
+        None => message.to_string(),
+    }
+}
+
+/// xdg-desktop-portal 是否可用（存在会话总线且安装了门户服务）
+pub fn portal_available() -> bool {
+    std::env::var_os("DBUS_SESSION_BUS_ADDRESS").is_some()
+        && ["/usr/share/dbus-1/services", "/usr/local/share/dbus-1/services"]
+            .iter()
+            .any(|dir| Path::new(dir).join("org.freedesktop.portal.Desktop.service").exists())
+}
+
+/// 从 /.flatpak-info 中读取应用ID
+fn parse_flatpak_info(info: &str) -> Confinement {
+    let app_id = info
+        .lines()
+        .find_map(|line| line.strip_prefix("name="))
+        .map(|name| name.trim().to_string())
+        .unwrap_or_else(|| "未知".to_string());
+    Confinement::Flatpak { app_id }
+}
+
+/// 解析 /proc/self/attr 中的安全标签
+///
+/// # 参数
+/// * `label` - 标签内容，如 `snap.foo (enforce)` 或 `unconfined_u:unconfined_r:unconfined_t:s0`
+/// * `selinux` - 系统是否启用了SELinux
+fn parse_security_label(label: &str, selinux: bool) -> Option<Confinement> {
+    let label = label.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+    if label.is_empty() || label == "unconfined" {
+        return None;
+    }
+
+    if selinux {
+        // 未受限域（unconfined_t）不会阻止剪贴板访问
+        return (!label.contains("unconfined_t")).then(|| Confinement::SeLinux { context: label.to_string() });
+    }
+
+    let (profile, mode) = label
+        .rsplit_once(" (")
+        .map(|(profile, mode)| (profile, mode.trim_end_matches(')')))
+        .unwrap_or((label, "enforce"));
+    (mode != "unconfined").then(|| Confinement::AppArmor {
+        profile: profile.to_string(),
+        enforce: mode == "enforce",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_security_label() {
+        assert_eq!(parse_security_label("unconfined\n", false), None);
+        assert_eq!(parse_security_label("/usr/bin/foo (unconfined)\n", false), None);
+        assert_eq!(
+            parse_security_label("snap.clipvanish.clipvanish (enforce)\n", false),
+            Some(Confinement::AppArmor { profile: "snap.clipvanish.clipvanish".to_string(), enforce: true })
+        );
+        assert_eq!(parse_security_label("unconfined_u:unconfined_r:unconfined_t:s0-s0:c0.c1023\0", true), None);
+        assert!(matches!(
+            parse_security_label("system_u:system_r:container_t:s0", true),
+            Some(Confinement::SeLinux { .. })
+        ));
+    }
+
+    #[test]
+    fn test_flatpak_guidance_names_app() {
+        let confinement = parse_flatpak_info("[Application]\nname=com.clipvanish.ClipVanish\nruntime=runtime/org.gnome.Platform\n");
+        assert_eq!(confinement.to_string(), "Flatpak沙箱: 应用 com.clipvanish.ClipVanish");
+        assert!(confinement.guidance().contains("--socket=x11"));
+    }
+}
@@ -0,0 +1,194 @@
+/*!
+ * ClipVanish™ 倒计时显示模块
+ *
+ * 将剩余倒计时发布到终端标题与状态文件，工作时无需切换窗口也能看到清除时间
+ * 特点：
+ * - 终端标题使用OSC 2序列，倒计时开始时保存原标题、结束后恢复（XTWINOPS 22/23）
+ * - 状态文件只包含截止时间与条目ID，供 `clipvanish status --tmux`、`clipvanish watch` 在其他进程中读取，从不包含内容
+ * - 紧凑格式如 `🔒 12s`、`🔒 2m05s`，适合tmux状态栏
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::fs;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use log::debug;
+use crate::config::Config;
+use crate::item_id::ItemId;
+
+/// 紧凑格式的剩余时间，例如 `🔒 12s`
+///
+/// # 参数
+/// * `remaining` - 剩余时间
+pub fn compact(remaining: Duration) -> String {
+    let secs = remaining.as_secs();
+    match secs {
+        0..=59 => format!("🔒 {}s", secs),
+        60..=3599 => format!("🔒 {}m{:02}s", secs / 60, secs % 60),
+        _ => format!("🔒 {}h{:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+/// 终端标题（与提示信息一样写入stderr，仅在stderr为终端时写入）
+pub struct TerminalTitle;
+
+impl TerminalTitle {
+    /// 保存当前标题（倒计时开始时调用）
+    pub fn push() {
+        Self::write("\x1b[22;0t");
+    }
+
+    /// 将剩余时间写入标题
+    pub fn set(remaining: Duration) {
+        Self::write(&format!("\x1b]2;{}\x07", compact(remaining)));
+    }
+
+    /// 恢复倒计时开始前的标题
+    pub fn pop() {
+        Self::write("\x1b[23;0t");
+    }
+
+    fn write(sequence: &str) {
+        let stderr = std::io::stderr();
+        if !stderr.is_terminal() {
+            return;
+        }
+        let mut stderr = stderr.lock();
+        let _ = stderr.write_all(sequence.as_bytes()).and_then(|_| stderr.flush());
+    }
+}
+
+/// 运行时目录（与控制套接字相同）
+pub fn runtime_dir() -> Option<PathBuf> {
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(runtime_dir) => Some(PathBuf::from(runtime_dir).join("clipvanish")),
+        None => Config::get_config_directory().ok().map(|dir| dir.join("run")),
+    }
+}
+
+/// 状态文件路径
+pub fn status_path() -> Option<PathBuf> {
+    runtime_dir().map(|dir| dir.join("countdown"))
+}
+
+/// 状态文件中发布的倒计时
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Countdown {
+    /// 截止时间
+    pub deadline: DateTime<Utc>,
+    /// 倒计时所属的条目
+    pub item: Option<ItemId>,
+}
+
+/// 发布倒计时截止时间
+///
+/// # 参数
+/// * `deadline` - 截止时间
+/// * `item` - 倒计时所属的条目
+pub fn publish(deadline: DateTime<Utc>, item: Option<ItemId>) {
+    if let Some(path) = status_path() {
+        if let Err(e) = write_countdown(&path, &Countdown { deadline, item }) {
+            debug!("写入倒计时状态文件失败: {}", e);
+        }
+    }
+}
+
+/// 撤销已发布的倒计时（倒计时结束、取消或服务停止时调用）
+pub fn clear() {
+    if let Some(path) = status_path() {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// tmux状态栏使用的字符串，没有进行中的倒计时时为空
+///
+/// # 参数
+/// * `now` - 当前时间
+pub fn tmux_status(now: DateTime<Utc>) -> String {
+    read()
+        .and_then(|countdown| (countdown.deadline - now).to_std().ok())
+        .filter(|remaining| !remaining.is_zero())
+        .map(compact)
+        .unwrap_or_default()
+}
+
+/// 读取其他进程发布的倒计时（没有进行中的倒计时时返回None）
+pub fn read() -> Option<Countdown> {
+    status_path().and_then(|path| read_countdown(&path))
+}
+
+/// 写入截止时间与条目ID，各占一行（目录0700、文件0600）
+fn write_countdown(path: &Path, countdown: &Countdown) -> std::io::Result<()> {
+    let mut content = countdown.deadline.to_rfc3339();
+    if let Some(item) = countdown.item {
+        content.push('\n');
+        content.push_str(&item.to_string());
+    }
+    prepare_runtime_dir(path)?.open(path)?.write_all(content.as_bytes())
+}
+
+/// 创建状态文件所在目录（0700），返回以0600权限覆盖写入该文件的打开选项
+///
+/// # 参数
+/// * `path` - 状态文件路径
+pub fn prepare_runtime_dir(path: &Path) -> std::io::Result<fs::OpenOptions> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(parent, fs::Permissions::from_mode(0o700))?;
+        }
+    }
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    Ok(options)
+}
+
+/// 读取截止时间与条目ID（文件不存在或损坏时返回None；旧版本写入的文件只有截止时间）
+fn read_countdown(path: &Path) -> Option<Countdown> {
+    let content = fs::read_to_string(path).ok()?;
+    let mut lines = content.lines();
+    let deadline = DateTime::parse_from_rfc3339(lines.next()?.trim()).ok()?.with_timezone(&Utc);
+    let item = lines.next().and_then(|line| line.trim().parse().ok());
+    Some(Countdown { deadline, item })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_compact_format() {
+        assert_eq!(compact(Duration::from_secs(12)), "🔒 12s");
+        assert_eq!(compact(Duration::from_secs(125)), "🔒 2m05s");
+        assert_eq!(compact(Duration::from_secs(3720)), "🔒 1h02m");
+    }
+
+    #[test]
+    fn test_deadline_round_trip() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("run").join("countdown");
+        let deadline = Utc::now() + chrono::Duration::seconds(30);
+
+        write_countdown(&path, &Countdown { deadline, item: None }).unwrap();
+        assert_eq!(read_countdown(&path).map(|c| c.deadline.timestamp()), Some(deadline.timestamp()));
+
+        let item = ItemId::generate();
+        write_countdown(&path, &Countdown { deadline, item: Some(item) }).unwrap();
+        assert_eq!(read_countdown(&path).and_then(|c| c.item), Some(item));
+
+        fs::write(&path, "garbage").unwrap();
+        assert!(read_countdown(&path).is_none());
+    }
+}
@@ -0,0 +1,153 @@
+/*!
+ * ClipVanish™ 崩溃处理模块
+ *
+ * 安装panic钩子，在程序异常终止前尽力完成安全清理
+ * 特点：
+ * - 受监督的剪贴板监听循环中的panic只记录原因，交由监督器重启
+ * - 其他panic视为致命错误：清理后立即终止进程（与panic=abort一致），Drop不会执行，此钩子是最后的清理机会
+ * - 终止仍在运行的辅助程序，经由关闭协调器按阶段清除剪贴板、零化密钥，最后立即擦除等待批量处理的明文缓冲区
+ * - 保存崩溃记录、计入安全模式的崩溃计数并写入审计日志
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::any::Any;
+use std::cell::Cell;
+use std::fs;
+use std::panic::{self, PanicHookInfo};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use log::error;
+use crate::audit;
+use crate::config::Config;
+use crate::helpers;
+use crate::erase_scheduler;
+use crate::redact::Scrubber;
+use crate::safe_mode::{self, Subsystem};
+use crate::shutdown::{self, Trigger};
+
+/// 防止清理过程中再次panic导致递归
+static IN_TEARDOWN: AtomicBool = AtomicBool::new(false);
+
+/// 受监督任务中最近一次panic的原因（由监督器取走）
+static SUPERVISED_PANIC: Mutex<Option<String>> = Mutex::new(None);
+
+thread_local! {
+    /// 当前线程是否正在轮询受监督的任务
+    static SUPERVISED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// 受监督范围：存在期间当前线程上的panic交由监督器处理
+pub struct SupervisedScope {
+    previous: bool,
+}
+
+impl SupervisedScope {
+    pub fn enter() -> Self {
+        SupervisedScope { previous: SUPERVISED.with(|flag| flag.replace(true)) }
+    }
+}
+
+impl Drop for SupervisedScope {
+    fn drop(&mut self) {
+        SUPERVISED.with(|flag| flag.set(self.previous));
+    }
+}
+
+/// 取走受监督任务中最近一次panic的原因
+pub fn take_supervised_panic() -> Option<String> {
+    SUPERVISED_PANIC.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take()
+}
+
+/// 安装panic钩子
+///
+/// 保留默认钩子的输出行为，在其之前执行安全清理
+pub fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        if SUPERVISED.with(Cell::get) {
+            // 消息可能包含触发panic的内容，经过脱敏后再交给监督器
+            let message = Scrubber::new().scrub(&panic_message(info)).into_owned();
+            *SUPERVISED_PANIC.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(message);
+            return;
+        }
+
+        if !IN_TEARDOWN.swap(true, Ordering::SeqCst) {
+            secure_teardown();
+            record_panic(info);
+        }
+
+        default_hook(info);
+        std::process::abort();
+    }));
+}
+
+/// 执行已登记的关闭步骤并立即擦除等待批量处理的缓冲区
+fn secure_teardown() {
+    helpers::terminate_all();
+    shutdown::coordinator().run(Trigger::Panic);
+    erase_scheduler::flush();
+}
+
+/// 提取panic载荷中的消息
+pub fn payload_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "未知panic".to_string()
+    }
+}
+
+/// 提取panic消息
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    let message = payload_message(info.payload());
+
+    match info.location() {
+        Some(location) => format!("{} ({}:{})", message, location.file(), location.line()),
+        None => message,
+    }
+}
+
+/// 保存崩溃记录并写入审计日志
+fn record_panic(info: &PanicHookInfo<'_>) {
+    // 消息可能包含触发panic的内容，崩溃记录、安全模式计数与审计日志都只使用脱敏后的消息
+    let message = Scrubber::new().scrub(&panic_message(info)).into_owned();
+    let backtrace = std::backtrace::Backtrace::force_capture();
+
+    if let Ok(path) = Config::get_panic_file_path() {
+        let content = format!(
+            "时间: {}\n版本: {}\n消息: {}\n\n{}",
+            chrono::Local::now().to_rfc3339(),
+            env!("CARGO_PKG_VERSION"),
+            message,
+            backtrace
+        );
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Err(e) = fs::write(&path, content) {
+            error!("保存崩溃记录失败: {}", e);
+        }
+    }
+
+    safe_mode::record_crash(Subsystem::from_panic(&message, &backtrace.to_string()), &message);
+    audit::record("panic", &format!("程序崩溃，已执行安全清理: {}", message));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_message() {
+        let text: Box<dyn Any + Send> = Box::new("崩溃");
+        let owned: Box<dyn Any + Send> = Box::new(String::from("崩溃原因"));
+        let other: Box<dyn Any + Send> = Box::new(42);
+        assert_eq!(payload_message(text.as_ref()), "崩溃");
+        assert_eq!(payload_message(owned.as_ref()), "崩溃原因");
+        assert_eq!(payload_message(other.as_ref()), "未知panic");
+    }
+}
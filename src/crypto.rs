@@ -6,15 +6,24 @@
  * - 使用AES-GCM-SIV避免时序攻击
  * - 内存零残留设计
  * - 密钥自动生成和管理
+ * - Linux上会话密钥保存在内核密钥环中，每次加解密时临时取出（不可用时回退为进程内存）
  * 
  * 作者: ClipVanish Team
  */
 
 use aes_gcm_siv::{Aes256GcmSiv, KeyInit, Nonce};
-use aes_gcm_siv::aead::{Aead, OsRng};
+use aes_gcm_siv::aead::{Aead, AeadInPlace, OsRng};
+use hmac::{Hmac, Mac};
 use rand::{RngCore, CryptoRng};
-use zeroize::{Zeroize, ZeroizeOnDrop};
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 use std::fmt;
+use crate::escrow::{EscrowRecipient, WrappedKey};
+use crate::keyring::KeyringKey;
+#[cfg(target_os = "linux")]
+use crate::capabilities::{self, CapabilityState, Feature};
+use crate::memory;
 
 /// AES-GCM-SIV nonce 长度（96位）
 const NONCE_LENGTH: usize = 12;
@@ -54,32 +63,58 @@ impl std::error::Error for CryptoError {}
 /// 简单的Base64编码表
 const BASE64_CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
 
-/// 简单的Base64编码实现
+/// 小内容快速路径的上限（字节）
+///
+/// 不超过该长度的明文在栈上的临时缓冲区中原地加密，明文副本不进入堆内存，密文只按准确长度分配一次
+const SMALL_CONTENT_LIMIT: usize = 4096;
+
+/// Base64编码后的长度（含填充）
+fn base64_encoded_len(len: usize) -> usize {
+    len.div_ceil(3) * 4
+}
+
+/// 逐个生成Base64字符，不分配内存
 ///
 /// # 参数
-/// * `input` - 待编码的字节数组
+/// * `input` - 待编码的字节
+/// * `emit` - 接收每个字符，返回false时提前停止
 ///
 /// # 返回值
-/// * `String` - Base64编码的字符串
-fn base64_encode(input: &[u8]) -> String {
-    let mut result = String::new();
-    let mut i = 0;
-
-    while i < input.len() {
-        let b1 = input[i];
-        let b2 = if i + 1 < input.len() { input[i + 1] } else { 0 };
-        let b3 = if i + 2 < input.len() { input[i + 2] } else { 0 };
-
-        let n = ((b1 as u32) << 16) | ((b2 as u32) << 8) | (b3 as u32);
-
-        result.push(BASE64_CHARS[((n >> 18) & 63) as usize] as char);
-        result.push(BASE64_CHARS[((n >> 12) & 63) as usize] as char);
-        result.push(if i + 1 < input.len() { BASE64_CHARS[((n >> 6) & 63) as usize] as char } else { '=' });
-        result.push(if i + 2 < input.len() { BASE64_CHARS[(n & 63) as usize] as char } else { '=' });
-
-        i += 3;
+/// * `bool` - 是否编码完全部输入（未被提前停止）
+fn base64_encode_with(mut input: impl Iterator<Item = u8>, mut emit: impl FnMut(u8) -> bool) -> bool {
+    while let Some(b1) = input.next() {
+        let b2 = input.next();
+        let b3 = b2.and_then(|_| input.next());
+
+        let n = ((b1 as u32) << 16) | ((b2.unwrap_or(0) as u32) << 8) | (b3.unwrap_or(0) as u32);
+
+        let quad = [
+            BASE64_CHARS[((n >> 18) & 63) as usize],
+            BASE64_CHARS[((n >> 12) & 63) as usize],
+            if b2.is_some() { BASE64_CHARS[((n >> 6) & 63) as usize] } else { b'=' },
+            if b3.is_some() { BASE64_CHARS[(n & 63) as usize] } else { b'=' },
+        ];
+        if !quad.into_iter().all(&mut emit) {
+            return false;
+        }
     }
+    true
+}
 
+/// 简单的Base64编码实现
+///
+/// # 参数
+/// * `input` - 待编码的字节
+/// * `len` - 输入长度（用于一次性分配结果）
+///
+/// # 返回值
+/// * `String` - Base64编码的字符串
+fn base64_encode(input: impl Iterator<Item = u8>, len: usize) -> String {
+    let mut result = String::with_capacity(base64_encoded_len(len));
+    base64_encode_with(input, |c| {
+        result.push(c as char);
+        true
+    });
     result
 }
 
@@ -138,8 +173,8 @@ fn char_to_base64_value(c: char) -> Result<u32, String> {
 
 /// 安全密钥结构体
 /// 
-/// 自动实现内存零化，确保密钥在销毁时被安全擦除
-#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+/// 销毁时按配置的擦除策略多轮覆盖密钥数据，确保密钥被安全擦除
+#[derive(Clone, Zeroize)]
 pub struct SecureKey {
     /// AES-256密钥数据
     key_data: [u8; KEY_LENGTH],
@@ -166,6 +201,48 @@ impl SecureKey {
     pub fn as_bytes(&self) -> &[u8; KEY_LENGTH] {
         &self.key_data
     }
+    
+    /// 从已保存的密钥数据恢复密钥
+    /// 
+    /// # 参数
+    /// * `key_data` - 密钥数据
+    pub fn from_bytes(key_data: [u8; KEY_LENGTH]) -> Self {
+        SecureKey { key_data }
+    }
+}
+
+impl Drop for SecureKey {
+    fn drop(&mut self) {
+        memory::erase(&mut self.key_data);
+    }
+}
+
+/// HKDF-SHA256（RFC 5869）派生32字节密钥
+///
+/// # 参数
+/// * `salt` - 盐（可为空）
+/// * `ikm` - 输入密钥材料
+/// * `info` - 用途标签与上下文，不同用途使用不同标签以实现域分离
+///
+/// # 返回值
+/// * `SecureKey` - 派生出的密钥
+pub fn hkdf_sha256(salt: &[u8], ikm: &[u8], info: &[u8]) -> SecureKey {
+    let mut extract = <Hmac<Sha256> as Mac>::new_from_slice(salt).expect("HMAC接受任意长度的密钥");
+    extract.update(ikm);
+    let prk: Zeroizing<[u8; KEY_LENGTH]> = Zeroizing::new(extract.finalize().into_bytes().into());
+
+    // 输出长度等于一个摘要块，只需 T(1) = HMAC(PRK, info || 0x01)
+    let mut expand = <Hmac<Sha256> as Mac>::new_from_slice(prk.as_ref()).expect("HMAC接受任意长度的密钥");
+    expand.update(info);
+    expand.update(&[1]);
+    SecureKey::from_bytes(expand.finalize().into_bytes().into())
+}
+
+/// 常量时间比较，避免通过响应时间猜测令牌、口令哈希或验证码
+///
+/// 长度不同时直接返回false（长度不是秘密）
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
 }
 
 /// 加密后的数据结构
@@ -209,13 +286,28 @@ impl EncryptedData {
     /// # 返回值
     /// * `String` - Base64编码的加密数据
     pub fn to_base64(&self) -> String {
-        // 将nonce和密文合并
-        let mut combined = Vec::with_capacity(NONCE_LENGTH + self.ciphertext.len());
-        combined.extend_from_slice(&self.nonce);
-        combined.extend_from_slice(&self.ciphertext);
+        // nonce与密文依次编码，不再拼接中间缓冲区
+        base64_encode(self.bytes(), self.total_length())
+    }
+
+    /// 文本是否恰好是该加密数据的Base64编码（忽略首尾空白）
+    ///
+    /// 逐字符比较，不分配内存；剪贴板每次变化时都会调用
+    ///
+    /// # 参数
+    /// * `text` - 待比较的文本
+    pub fn matches_base64(&self, text: &str) -> bool {
+        let text = text.trim();
+        if text.len() != base64_encoded_len(self.total_length()) {
+            return false;
+        }
+        let mut expected = text.bytes();
+        base64_encode_with(self.bytes(), |c| expected.next() == Some(c))
+    }
 
-        // 使用简单的Base64编码
-        base64_encode(&combined)
+    /// nonce与密文依次组成的字节序列
+    fn bytes(&self) -> impl Iterator<Item = u8> + '_ {
+        self.nonce.iter().chain(self.ciphertext.iter()).copied()
     }
 
     /// 从Base64字符串解码为加密数据
@@ -229,6 +321,22 @@ impl EncryptedData {
         let combined = base64_decode(base64_str)
             .map_err(|_| CryptoError::InvalidCiphertext)?;
 
+        Self::from_bytes(&combined)
+    }
+
+    /// 编码为nonce与密文依次组成的字节序列（用于保存到锁定内存）
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.bytes().collect()
+    }
+
+    /// 从nonce与密文依次组成的字节序列还原
+    ///
+    /// # 参数
+    /// * `combined` - nonce与密文
+    ///
+    /// # 返回值
+    /// * `Result<EncryptedData, CryptoError>` - 还原的加密数据
+    pub fn from_bytes(combined: &[u8]) -> Result<Self, CryptoError> {
         if combined.len() < NONCE_LENGTH {
             return Err(CryptoError::InvalidCiphertext);
         }
@@ -241,14 +349,77 @@ impl EncryptedData {
     }
 }
 
+/// 会话密钥的保存位置
+enum KeyStorage {
+    /// 进程内存（加密器预先创建）
+    Memory {
+        /// 密钥
+        key: SecureKey,
+        /// AES-GCM-SIV加密器实例（含展开的轮密钥，装箱避免撑大枚举）
+        cipher: Box<Aes256GcmSiv>,
+    },
+    /// 内核密钥环（每次操作时取出，用完即擦除）
+    Keyring(KeyringKey),
+}
+
+impl KeyStorage {
+    /// 保存在进程内存中
+    fn memory(key: SecureKey) -> Result<Self, CryptoError> {
+        let cipher = Aes256GcmSiv::new_from_slice(key.as_bytes())
+            .map_err(|_| CryptoError::KeyGenerationFailed)?;
+        Ok(KeyStorage::Memory { key, cipher: Box::new(cipher) })
+    }
+    
+    /// 优先保存在内核密钥环中，不可用时回退为进程内存
+    fn session(key: SecureKey) -> Result<Self, CryptoError> {
+        match KeyringKey::store(&key) {
+            Ok(stored) => {
+                #[cfg(target_os = "linux")]
+                capabilities::record(Feature::KernelKeyring, CapabilityState::Active, None);
+                Ok(KeyStorage::Keyring(stored))
+            }
+            Err(_e) => {
+                #[cfg(target_os = "linux")]
+                capabilities::degrade(Feature::KernelKeyring, &format!("{}，会话密钥保存在进程内存中", _e));
+                Self::memory(key)
+            }
+        }
+    }
+    
+    /// 使用密钥执行操作
+    fn with_key<T>(&self, operation: impl FnOnce(&SecureKey) -> Result<T, CryptoError>) -> Result<T, CryptoError> {
+        match self {
+            KeyStorage::Memory { key, .. } => operation(key),
+            KeyStorage::Keyring(stored) => {
+                let key = stored.load().map_err(|e| CryptoError::MemoryError(format!("读取内核密钥环失败: {}", e)))?;
+                operation(&key)
+            }
+        }
+    }
+    
+    /// 使用加密器执行操作
+    fn with_cipher<T>(&self, operation: impl FnOnce(&Aes256GcmSiv) -> Result<T, CryptoError>) -> Result<T, CryptoError> {
+        match self {
+            KeyStorage::Memory { cipher, .. } => operation(cipher),
+            KeyStorage::Keyring(_) => self.with_key(|key| {
+                let cipher = Aes256GcmSiv::new_from_slice(key.as_bytes())
+                    .map_err(|_| CryptoError::KeyGenerationFailed)?;
+                operation(&cipher)
+            }),
+        }
+    }
+}
+
 /// ClipVanish加密引擎
 /// 
 /// 核心加密/解密功能实现，负责剪贴板内容的安全处理
 pub struct CryptoEngine {
-    /// AES-GCM-SIV加密器实例
-    cipher: Aes256GcmSiv,
     /// 当前使用的密钥
-    current_key: SecureKey,
+    key: KeyStorage,
+    /// 密钥托管接收方（启用时每个会话密钥都额外封装给它）
+    escrow: Option<EscrowRecipient>,
+    /// 当前密钥的托管封装
+    escrowed_key: Option<WrappedKey>,
 }
 
 impl CryptoEngine {
@@ -257,16 +428,62 @@ impl CryptoEngine {
     /// # 返回值
     /// * `Result<CryptoEngine, CryptoError>` - 成功返回引擎实例
     pub fn new() -> Result<Self, CryptoError> {
-        let key = SecureKey::generate()?;
-        let cipher = Aes256GcmSiv::new_from_slice(key.as_bytes())
-            .map_err(|_| CryptoError::KeyGenerationFailed)?;
-        
         Ok(CryptoEngine {
-            cipher,
-            current_key: key,
+            key: KeyStorage::session(SecureKey::generate()?)?,
+            escrow: None,
+            escrowed_key: None,
         })
     }
     
+    /// 创建加密引擎，并将每个会话密钥额外封装给托管接收方
+    /// 
+    /// # 参数
+    /// * `escrow` - 托管接收方，为None时等同于 `new()`
+    pub fn with_escrow(escrow: Option<EscrowRecipient>) -> Result<Self, CryptoError> {
+        let mut engine = Self::new()?;
+        engine.set_escrow(escrow)?;
+        Ok(engine)
+    }
+    
+    /// 使用已有密钥创建加密引擎（用于托管恢复与密钥封装，密钥保存在进程内存中）
+    /// 
+    /// # 参数
+    /// * `key` - 密钥
+    pub fn from_key(key: SecureKey) -> Result<Self, CryptoError> {
+        Ok(CryptoEngine {
+            key: KeyStorage::memory(key)?,
+            escrow: None,
+            escrowed_key: None,
+        })
+    }
+    
+    /// 设置托管接收方并封装当前密钥（配置重新加载时使用）
+    pub fn set_escrow(&mut self, escrow: Option<EscrowRecipient>) -> Result<(), CryptoError> {
+        self.escrow = escrow;
+        self.escrow_current_key()
+    }
+    
+    /// 托管接收方
+    pub fn escrow_recipient(&self) -> Option<&EscrowRecipient> {
+        self.escrow.as_ref()
+    }
+    
+    /// 当前密钥的托管封装（未启用托管时为None）
+    pub fn escrowed_key(&self) -> Option<&WrappedKey> {
+        self.escrowed_key.as_ref()
+    }
+    
+    /// 将当前密钥封装给托管接收方
+    fn escrow_current_key(&mut self) -> Result<(), CryptoError> {
+        self.escrowed_key = match &self.escrow {
+            Some(recipient) => Some(
+                self.key.with_key(|key| recipient.wrap(key).map_err(|e| CryptoError::MemoryError(e.to_string())))?,
+            ),
+            None => None,
+        };
+        Ok(())
+    }
+    
     /// 加密明文数据
     /// 
     /// # 参数
@@ -281,9 +498,22 @@ impl CryptoEngine {
         let nonce = Nonce::from_slice(&nonce_bytes);
         
         // 执行加密操作
-        let ciphertext = self.cipher
-            .encrypt(nonce, plaintext)
-            .map_err(|_| CryptoError::EncryptionFailed)?;
+        let ciphertext = self.key.with_cipher(|cipher| {
+            if plaintext.len() > SMALL_CONTENT_LIMIT {
+                return cipher.encrypt(nonce, plaintext).map_err(|_| CryptoError::EncryptionFailed);
+            }
+            // 小内容快速路径：在栈上原地加密，离开作用域时擦除
+            let mut scratch = Zeroizing::new([0u8; SMALL_CONTENT_LIMIT]);
+            let buffer = &mut scratch[..plaintext.len()];
+            buffer.copy_from_slice(plaintext);
+            let tag = cipher
+                .encrypt_in_place_detached(nonce, b"", buffer)
+                .map_err(|_| CryptoError::EncryptionFailed)?;
+            let mut ciphertext = Vec::with_capacity(buffer.len() + tag.len());
+            ciphertext.extend_from_slice(buffer);
+            ciphertext.extend_from_slice(&tag);
+            Ok(ciphertext)
+        })?;
         
         Ok(EncryptedData::new(nonce_bytes, ciphertext))
     }
@@ -299,9 +529,9 @@ impl CryptoEngine {
         let nonce = Nonce::from_slice(&encrypted_data.nonce);
 
         // 执行解密操作
-        let plaintext = self.cipher
-            .decrypt(nonce, encrypted_data.ciphertext.as_ref())
-            .map_err(|_| CryptoError::DecryptionFailed)?;
+        let plaintext = self.key.with_cipher(|cipher| {
+            cipher.decrypt(nonce, encrypted_data.ciphertext.as_ref()).map_err(|_| CryptoError::DecryptionFailed)
+        })?;
 
         Ok(plaintext)
     }
@@ -319,9 +549,9 @@ impl CryptoEngine {
         let nonce = Nonce::from_slice(&encrypted_data.nonce);
 
         // 执行解密操作
-        let plaintext = self.cipher
-            .decrypt(nonce, encrypted_data.ciphertext.as_ref())
-            .map_err(|_| CryptoError::DecryptionFailed)?;
+        let plaintext = self.key.with_cipher(|cipher| {
+            cipher.decrypt(nonce, encrypted_data.ciphertext.as_ref()).map_err(|_| CryptoError::DecryptionFailed)
+        })?;
 
         // 立即重置密钥以增强安全性
         self.regenerate_key()?;
@@ -335,14 +565,9 @@ impl CryptoEngine {
     /// # 返回值
     /// * `Result<(), CryptoError>` - 操作结果
     pub fn regenerate_key(&mut self) -> Result<(), CryptoError> {
-        // 生成新密钥
-        let new_key = SecureKey::generate()?;
-        let new_cipher = Aes256GcmSiv::new_from_slice(new_key.as_bytes())
-            .map_err(|_| CryptoError::KeyGenerationFailed)?;
-        
-        // 替换旧密钥和加密器
-        self.current_key = new_key;
-        self.cipher = new_cipher;
+        // 生成新密钥并替换旧密钥（旧密钥销毁时擦除或从密钥环中作废）
+        self.key = KeyStorage::session(SecureKey::generate()?)?;
+        self.escrow_current_key()?;
         
         log::info!("加密密钥已重新生成");
         Ok(())
@@ -356,11 +581,16 @@ impl CryptoEngine {
         use std::collections::hash_map::DefaultHasher;
         use std::hash::{Hash, Hasher};
         
-        let mut hasher = DefaultHasher::new();
-        self.current_key.as_bytes().hash(&mut hasher);
-        let hash = hasher.finish();
+        let hash = self.key.with_key(|key| {
+            let mut hasher = DefaultHasher::new();
+            key.as_bytes().hash(&mut hasher);
+            Ok(hasher.finish())
+        });
         
-        format!("{:016x}", hash)[..16].to_string()
+        match hash {
+            Ok(hash) => format!("{:016x}", hash)[..16].to_string(),
+            Err(_) => "unavailable".to_string(),
+        }
     }
 }
 
@@ -452,6 +682,21 @@ mod tests {
         assert!(decrypt_result.is_err());
     }
 
+    #[test]
+    fn test_hkdf_sha256_rfc5869_vector() {
+        // RFC 5869 测试用例1（取输出的前32字节）
+        let salt: Vec<u8> = (0x00..=0x0c).collect();
+        let info: Vec<u8> = (0xf0..=0xf9).collect();
+        let key = hkdf_sha256(&salt, &[0x0b; 22], &info);
+        assert_eq!(
+            crate::encoding::encode_hex(key.as_bytes()),
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf"
+        );
+
+        // 不同用途标签得到不同密钥
+        assert_ne!(hkdf_sha256(&salt, &[0x0b; 22], b"other").as_bytes(), key.as_bytes());
+    }
+
     #[test]
     fn test_base64_encoding_decoding() {
         let engine = CryptoEngine::new().unwrap();
@@ -486,4 +731,31 @@ mod tests {
         let result = EncryptedData::from_base64("dGVzdA=="); // "test" in base64, too short
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_small_content_fast_path() {
+        let engine = CryptoEngine::new().unwrap();
+        for len in [0, 1, 2, 3, SMALL_CONTENT_LIMIT - 1, SMALL_CONTENT_LIMIT, SMALL_CONTENT_LIMIT + 1] {
+            let plaintext: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let encrypted = engine.encrypt(&plaintext).unwrap();
+
+            // 快速路径的输出与标准实现一致
+            let expected = engine.key.with_cipher(|cipher| {
+                cipher.encrypt(Nonce::from_slice(encrypted.nonce()), plaintext.as_slice()).map_err(|_| CryptoError::EncryptionFailed)
+            }).unwrap();
+            assert_eq!(encrypted.ciphertext(), expected.as_slice());
+            assert_eq!(engine.decrypt(&encrypted).unwrap(), plaintext);
+
+            let base64_str = encrypted.to_base64();
+            assert_eq!(base64_str.len(), base64_encoded_len(encrypted.total_length()));
+            assert!(encrypted.matches_base64(&format!(" {}\n", base64_str)));
+            assert!(!encrypted.matches_base64(&base64_str[..base64_str.len() - 4]));
+            let mut tampered = base64_str.clone().into_bytes();
+            tampered[0] = if tampered[0] == b'A' { b'B' } else { b'A' };
+            assert!(!encrypted.matches_base64(std::str::from_utf8(&tampered).unwrap()));
+        }
+        assert_eq!(base64_encode(b"foobar".iter().copied(), 6), "Zm9vYmFy");
+        assert_eq!(base64_encode(b"fooba".iter().copied(), 5), "Zm9vYmE=");
+        assert_eq!(base64_encode(b"foob".iter().copied(), 4), "Zm9vYg==");
+    }
 }
@@ -0,0 +1,176 @@
+/*!
+ * ClipVanish™ 后台运行模块
+ *
+ * `start --daemon` 在Unix上以标准的两次fork方式脱离终端，之后才启动tokio运行时
+ * 特点：
+ * - 必须在创建任何线程（包括tokio运行时）之前调用：fork只复制调用线程，已有的反应器与工作线程在子进程中不可用
+ * - 新会话（setsid）+ 第二次fork，守护进程不会再获得控制终端
+ * - umask 077、工作目录切换到根目录，stdin指向 /dev/null，stdout/stderr追加写入日志文件
+ * - 关闭继承的其他文件描述符，原进程在守护进程就绪后输出其PID再退出
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::path::Path;
+
+/// 后台运行错误
+#[derive(Debug)]
+pub struct DaemonError {
+    /// 失败的步骤
+    step: &'static str,
+    /// 底层错误
+    source: std::io::Error,
+}
+
+impl std::fmt::Display for DaemonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}失败: {}", self.step, self.source)
+    }
+}
+
+impl std::error::Error for DaemonError {}
+
+impl DaemonError {
+    fn new(step: &'static str, source: std::io::Error) -> Self {
+        DaemonError { step, source }
+    }
+
+    #[cfg(unix)]
+    fn last_os_error(step: &'static str) -> Self {
+        Self::new(step, std::io::Error::last_os_error())
+    }
+}
+
+/// 脱离终端转为守护进程
+///
+/// 原进程与中间进程在此函数内退出，只有守护进程返回；调用时进程中只能有当前一个线程
+///
+/// # 参数
+/// * `log_path` - 接收stdout/stderr的日志文件
+///
+/// # 返回值
+/// * `Result<(), DaemonError>` - 守护进程中返回Ok，失败时仍在原进程中返回错误
+#[cfg(unix)]
+pub fn daemonize(log_path: &Path) -> Result<(), DaemonError> {
+    use std::fs::{self, File, OpenOptions};
+    use std::io::{Read, Write};
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::os::unix::io::{AsRawFd, FromRawFd};
+
+    // 先在原进程中打开日志文件，路径或权限有问题时直接报错，不必等到脱离终端之后
+    if let Some(parent) = log_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| DaemonError::new("创建日志目录", e))?;
+    }
+    let log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .mode(0o600)
+        .open(log_path)
+        .map_err(|e| DaemonError::new("打开日志文件", e))?;
+    let null = File::open("/dev/null").map_err(|e| DaemonError::new("打开 /dev/null", e))?;
+
+    // 守护进程就绪后经管道回报PID
+    let mut fds = [0; 2];
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+        return Err(DaemonError::last_os_error("创建管道"));
+    }
+    let (read_fd, write_fd) = (fds[0], fds[1]);
+
+    match unsafe { libc::fork() } {
+        -1 => {
+            unsafe {
+                libc::close(read_fd);
+                libc::close(write_fd);
+            }
+            return Err(DaemonError::last_os_error("fork"));
+        }
+        0 => {}
+        child => {
+            // 原进程：等待中间进程退出，读取守护进程PID后退出
+            unsafe { libc::close(write_fd) };
+            let mut status = 0;
+            unsafe { libc::waitpid(child, &mut status, 0) };
+
+            let mut reply = String::new();
+            let _ = unsafe { File::from_raw_fd(read_fd) }.read_to_string(&mut reply);
+            match reply.trim().parse::<u32>() {
+                Ok(pid) => {
+                    eprintln!("🔄 ClipVanish已在后台启动（PID {}），日志: {}", pid, log_path.display());
+                    unsafe { libc::_exit(0) };
+                }
+                Err(_) => {
+                    eprintln!("❌ 后台进程启动失败: {}", reply.trim());
+                    unsafe { libc::_exit(1) };
+                }
+            }
+        }
+    }
+
+    // 中间进程：成为新会话的首进程，再fork一次以放弃会话首进程身份
+    unsafe { libc::close(read_fd) };
+    let mut report = unsafe { File::from_raw_fd(write_fd) };
+    let fail = |report: &mut File, error: DaemonError| -> ! {
+        let _ = write!(report, "{}", error);
+        unsafe { libc::_exit(1) };
+    };
+
+    if unsafe { libc::setsid() } == -1 {
+        fail(&mut report, DaemonError::last_os_error("setsid"));
+    }
+    match unsafe { libc::fork() } {
+        -1 => fail(&mut report, DaemonError::last_os_error("第二次fork")),
+        0 => {}
+        _ => unsafe { libc::_exit(0) },
+    }
+
+    // 守护进程
+    unsafe { libc::umask(0o077) };
+    if let Err(e) = std::env::set_current_dir("/") {
+        fail(&mut report, DaemonError::new("切换工作目录", e));
+    }
+    for (source, target) in [(null.as_raw_fd(), 0), (log.as_raw_fd(), 1), (log.as_raw_fd(), 2)] {
+        if unsafe { libc::dup2(source, target) } == -1 {
+            fail(&mut report, DaemonError::last_os_error("重定向标准输入输出"));
+        }
+    }
+    drop((null, log));
+
+    let _ = write!(report, "{}", std::process::id());
+    drop(report);
+
+    // 关闭从启动环境继承的其他文件描述符（此时进程中没有其他线程，也没有仍需使用的描述符）
+    let max_fd = match unsafe { libc::sysconf(libc::_SC_OPEN_MAX) } {
+        limit if limit > 0 => limit.min(65536) as libc::c_int,
+        _ => 1024,
+    };
+    for fd in 3..max_fd {
+        unsafe { libc::close(fd) };
+    }
+
+    Ok(())
+}
+
+/// 非Unix平台不支持脱离终端
+#[cfg(not(unix))]
+pub fn daemonize(_log_path: &Path) -> Result<(), DaemonError> {
+    Err(DaemonError::new(
+        "脱离终端",
+        std::io::Error::new(std::io::ErrorKind::Unsupported, "当前平台不支持，请使用 --foreground 或系统服务管理器"),
+    ))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_file_errors_reported_before_fork() {
+        // 日志文件无法创建时在原进程中返回错误，不会fork
+        let temp_dir = tempfile::tempdir().unwrap();
+        let blocker = temp_dir.path().join("file");
+        std::fs::write(&blocker, "").unwrap();
+
+        let error = daemonize(&blocker.join("clipvanish.log")).unwrap_err();
+        assert!(error.to_string().starts_with("创建日志目录失败"));
+    }
+}
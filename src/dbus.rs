@@ -0,0 +1,974 @@
+/*!
+ * ClipVanish™ D-Bus客户端模块
+ *
+ * 供门户剪贴板使用的最小会话总线客户端，不依赖libdbus
+ * 特点：
+ * - 只实现小端序消息的编码与解码，以及EXTERNAL认证
+ * - 解码时执行规范的长度与嵌套上限（消息128 MiB、数组64 MiB、数组/结构体各32层、总计64层），
+ *   总线上的任何对端都可以发来消息，畸形或恶意嵌套的消息只会得到错误而不会耗尽栈
+ * - 支持接收随消息传递的文件描述符（SCM_RIGHTS），用于剪贴板数据传输
+ * - 读写分离：读取端由单个线程持有，发送端可在线程间共享
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::net::UnixStream;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 总线守护进程的名称与路径
+const BUS_NAME: &str = "org.freedesktop.DBus";
+const BUS_PATH: &str = "/org/freedesktop/DBus";
+
+/// 规范规定的消息与数组长度上限
+const MAX_MESSAGE_LENGTH: usize = 1 << 27;
+const MAX_ARRAY_LENGTH: usize = 1 << 26;
+
+/// 规范规定的容器嵌套上限（总计包含变体）
+const MAX_ARRAY_DEPTH: usize = 32;
+const MAX_STRUCT_DEPTH: usize = 32;
+const MAX_TOTAL_DEPTH: usize = 64;
+
+/// D-Bus错误类型
+#[derive(Debug)]
+pub enum DbusError {
+    /// 无法连接会话总线
+    Connect(String),
+    /// 认证失败
+    Auth(String),
+    /// 读写失败
+    Io(std::io::Error),
+    /// 消息格式错误
+    Protocol(String),
+    /// 对方返回的错误
+    Remote { name: String, message: String },
+}
+
+impl std::fmt::Display for DbusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DbusError::Connect(msg) => write!(f, "无法连接会话总线: {}", msg),
+            DbusError::Auth(msg) => write!(f, "会话总线认证失败: {}", msg),
+            DbusError::Io(e) => write!(f, "会话总线读写失败: {}", e),
+            DbusError::Protocol(msg) => write!(f, "D-Bus消息格式错误: {}", msg),
+            DbusError::Remote { name, message } => write!(f, "{}: {}", name, message),
+        }
+    }
+}
+
+impl std::error::Error for DbusError {}
+
+impl From<std::io::Error> for DbusError {
+    fn from(e: std::io::Error) -> Self {
+        DbusError::Io(e)
+    }
+}
+
+/// D-Bus值
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Byte(u8),
+    Bool(bool),
+    I16(i16),
+    U16(u16),
+    I32(i32),
+    U32(u32),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Str(String),
+    Path(String),
+    Signature(String),
+    /// 文件描述符在消息附带列表中的序号
+    Fd(u32),
+    Variant(Box<Value>),
+    /// 数组（元素签名, 元素）
+    Array(String, Vec<Value>),
+    Struct(Vec<Value>),
+    DictEntry(Box<Value>, Box<Value>),
+}
+
+impl Value {
+    /// 字符串值
+    pub fn str(text: &str) -> Value {
+        Value::Str(text.to_string())
+    }
+
+    /// 字符串数组（as）
+    pub fn string_array(items: &[&str]) -> Value {
+        Value::Array("s".to_string(), items.iter().map(|item| Value::str(item)).collect())
+    }
+
+    /// 选项字典（a{sv}）
+    pub fn dict(entries: Vec<(&str, Value)>) -> Value {
+        Value::Array(
+            "{sv}".to_string(),
+            entries
+                .into_iter()
+                .map(|(key, value)| Value::DictEntry(Box::new(Value::str(key)), Box::new(Value::Variant(Box::new(value)))))
+                .collect(),
+        )
+    }
+
+    /// 类型签名
+    pub fn signature(&self) -> String {
+        match self {
+            Value::Byte(_) => "y".to_string(),
+            Value::Bool(_) => "b".to_string(),
+            Value::I16(_) => "n".to_string(),
+            Value::U16(_) => "q".to_string(),
+            Value::I32(_) => "i".to_string(),
+            Value::U32(_) => "u".to_string(),
+            Value::I64(_) => "x".to_string(),
+            Value::U64(_) => "t".to_string(),
+            Value::F64(_) => "d".to_string(),
+            Value::Str(_) => "s".to_string(),
+            Value::Path(_) => "o".to_string(),
+            Value::Signature(_) => "g".to_string(),
+            Value::Fd(_) => "h".to_string(),
+            Value::Variant(_) => "v".to_string(),
+            Value::Array(element, _) => format!("a{}", element),
+            Value::Struct(items) => format!("({})", items.iter().map(Value::signature).collect::<String>()),
+            Value::DictEntry(key, value) => format!("{{{}{}}}", key.signature(), value.signature()),
+        }
+    }
+
+    /// 去掉外层variant
+    fn inner(&self) -> &Value {
+        match self {
+            Value::Variant(inner) => inner.inner(),
+            other => other,
+        }
+    }
+
+    /// 字符串、对象路径或签名的内容
+    pub fn as_str(&self) -> Option<&str> {
+        match self.inner() {
+            Value::Str(text) | Value::Path(text) | Value::Signature(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// 无符号整数
+    pub fn as_u32(&self) -> Option<u32> {
+        match self.inner() {
+            Value::U32(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// 布尔值
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.inner() {
+            Value::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// 数组元素
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self.inner() {
+            Value::Array(_, items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// 在a{sv}字典中按键查找
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.as_array()?.iter().find_map(|entry| match entry {
+            Value::DictEntry(k, v) if k.as_str() == Some(key) => Some(v.inner()),
+            _ => None,
+        })
+    }
+}
+
+/// 消息类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MessageKind {
+    MethodCall,
+    MethodReturn,
+    Error,
+    Signal,
+}
+
+/// 收到的消息
+#[derive(Debug)]
+pub struct Message {
+    /// 消息类型
+    pub kind: MessageKind,
+    /// 回复的调用序号
+    pub reply_serial: Option<u32>,
+    /// 对象路径
+    pub path: Option<String>,
+    /// 接口
+    pub interface: Option<String>,
+    /// 成员（方法或信号名）
+    pub member: Option<String>,
+    /// 错误名称
+    pub error_name: Option<String>,
+    /// 消息体
+    pub body: Vec<Value>,
+    /// 随消息传递的文件描述符
+    pub fds: Vec<OwnedFd>,
+}
+
+impl Message {
+    /// 错误消息转换为错误，其他消息原样返回
+    pub fn into_result(self) -> Result<Message, DbusError> {
+        if self.kind == MessageKind::Error {
+            return Err(DbusError::Remote {
+                name: self.error_name.unwrap_or_default(),
+                message: self.body.first().and_then(Value::as_str).unwrap_or_default().to_string(),
+            });
+        }
+        Ok(self)
+    }
+}
+
+/// 发送端（可在线程间共享）
+pub struct Sender {
+    stream: Mutex<UnixStream>,
+    next_serial: AtomicU32,
+}
+
+impl Sender {
+    /// 分配调用序号（先登记等待者再发送，避免回复先于登记到达）
+    pub fn next_serial(&self) -> u32 {
+        self.next_serial.fetch_add(1, Ordering::SeqCst)
+    }
+
+    /// 以指定序号发送方法调用
+    ///
+    /// # 参数
+    /// * `serial` - 由 `next_serial` 分配的序号
+    /// * `destination` / `path` / `interface` / `member` - 调用目标
+    /// * `body` - 参数
+    pub fn call(
+        &self,
+        serial: u32,
+        destination: &str,
+        path: &str,
+        interface: &str,
+        member: &str,
+        body: &[Value],
+    ) -> Result<(), DbusError> {
+        let bytes = encode_call(serial, destination, path, interface, member, body);
+        self.stream.lock().unwrap().write_all(&bytes)?;
+        Ok(())
+    }
+}
+
+/// 会话总线连接（读取端）
+pub struct Connection {
+    stream: UnixStream,
+    sender: Arc<Sender>,
+    buffer: Vec<u8>,
+    fds: VecDeque<OwnedFd>,
+    /// 等待回复期间收到的其他消息
+    queued: VecDeque<Message>,
+    unique_name: String,
+}
+
+impl Connection {
+    /// 连接当前用户的会话总线
+    pub fn session() -> Result<Self, DbusError> {
+        let address = std::env::var("DBUS_SESSION_BUS_ADDRESS")
+            .map_err(|_| DbusError::Connect("未设置DBUS_SESSION_BUS_ADDRESS".to_string()))?;
+        let mut stream = connect_address(&address)?;
+        authenticate(&mut stream)?;
+
+        let writer = stream.try_clone()?;
+        let mut connection = Connection {
+            stream,
+            sender: Arc::new(Sender { stream: Mutex::new(writer), next_serial: AtomicU32::new(1) }),
+            buffer: Vec::new(),
+            fds: VecDeque::new(),
+            queued: VecDeque::new(),
+            unique_name: String::new(),
+        };
+
+        let reply = connection.call(BUS_NAME, BUS_PATH, BUS_NAME, "Hello", &[])?;
+        connection.unique_name = reply
+            .body
+            .first()
+            .and_then(Value::as_str)
+            .ok_or_else(|| DbusError::Protocol("Hello未返回连接名".to_string()))?
+            .to_string();
+        Ok(connection)
+    }
+
+    /// 总线分配的唯一连接名（如 `:1.42`）
+    pub fn unique_name(&self) -> &str {
+        &self.unique_name
+    }
+
+    /// 共享的发送端
+    pub fn sender(&self) -> Arc<Sender> {
+        Arc::clone(&self.sender)
+    }
+
+    /// 设置读取超时（None表示一直等待）
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> Result<(), DbusError> {
+        self.stream.set_read_timeout(timeout)?;
+        Ok(())
+    }
+
+    /// 订阅信号
+    ///
+    /// # 参数
+    /// * `rule` - 匹配规则，如 `type='signal',interface='org.freedesktop.portal.Request'`
+    pub fn add_match(&mut self, rule: &str) -> Result<(), DbusError> {
+        self.call(BUS_NAME, BUS_PATH, BUS_NAME, "AddMatch", &[Value::str(rule)])?;
+        Ok(())
+    }
+
+    /// 发送方法调用并等待回复（期间收到的其他消息留给 `read_message`）
+    pub fn call(
+        &mut self,
+        destination: &str,
+        path: &str,
+        interface: &str,
+        member: &str,
+        body: &[Value],
+    ) -> Result<Message, DbusError> {
+        let serial = self.sender.next_serial();
+        self.sender.call(serial, destination, path, interface, member, body)?;
+
+        loop {
+            let message = self.receive()?;
+            if message.reply_serial == Some(serial) {
+                return message.into_result();
+            }
+            self.queued.push_back(message);
+        }
+    }
+
+    /// 读取下一条消息
+    pub fn read_message(&mut self) -> Result<Message, DbusError> {
+        match self.queued.pop_front() {
+            Some(message) => Ok(message),
+            None => self.receive(),
+        }
+    }
+
+    /// 从套接字读取下一条完整消息
+    fn receive(&mut self) -> Result<Message, DbusError> {
+        loop {
+            if let Some(message) = decode_message(&mut self.buffer, &mut self.fds)? {
+                return Ok(message);
+            }
+            self.fill()?;
+        }
+    }
+
+    /// 读取更多数据，并收集随附的文件描述符
+    fn fill(&mut self) -> Result<(), DbusError> {
+        let mut data = [0u8; 4096];
+        // u64数组保证控制消息缓冲区按cmsghdr对齐
+        let mut control = [0u64; 32];
+        let mut iov = libc::iovec { iov_base: data.as_mut_ptr() as *mut libc::c_void, iov_len: data.len() };
+
+        let received = unsafe {
+            let mut header: libc::msghdr = std::mem::zeroed();
+            header.msg_iov = &mut iov;
+            header.msg_iovlen = 1;
+            header.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+            header.msg_controllen = std::mem::size_of_val(&control) as _;
+
+            let received = libc::recvmsg(self.stream.as_raw_fd(), &mut header, libc::MSG_CMSG_CLOEXEC);
+            if received < 0 {
+                return Err(std::io::Error::last_os_error().into());
+            }
+
+            let mut cmsg = libc::CMSG_FIRSTHDR(&header);
+            while !cmsg.is_null() {
+                if (*cmsg).cmsg_level == libc::SOL_SOCKET && (*cmsg).cmsg_type == libc::SCM_RIGHTS {
+                    let data = libc::CMSG_DATA(cmsg) as *const libc::c_int;
+                    let count = ((*cmsg).cmsg_len as usize - libc::CMSG_LEN(0) as usize) / std::mem::size_of::<libc::c_int>();
+                    for index in 0..count {
+                        let fd = std::ptr::read_unaligned(data.add(index));
+                        self.fds.push_back(OwnedFd::from_raw_fd(fd));
+                    }
+                }
+                cmsg = libc::CMSG_NXTHDR(&header, cmsg);
+            }
+            received as usize
+        };
+
+        if received == 0 {
+            return Err(DbusError::Io(std::io::ErrorKind::UnexpectedEof.into()));
+        }
+        self.buffer.extend_from_slice(&data[..received]);
+        Ok(())
+    }
+}
+
+/// 按地址连接（支持 `unix:path=` 与 `unix:abstract=`，多个地址依次尝试）
+fn connect_address(address: &str) -> Result<UnixStream, DbusError> {
+    use std::os::linux::net::SocketAddrExt;
+    use std::os::unix::net::SocketAddr;
+
+    let mut last_error = format!("不支持的地址: {}", address);
+    for entry in address.split(';') {
+        let Some(params) = entry.strip_prefix("unix:") else { continue };
+        for param in params.split(',') {
+            let result = match param.split_once('=') {
+                Some(("path", path)) => UnixStream::connect(unescape(path)),
+                Some(("abstract", name)) => {
+                    SocketAddr::from_abstract_name(unescape(name).as_bytes()).and_then(|addr| UnixStream::connect_addr(&addr))
+                }
+                _ => continue,
+            };
+            match result {
+                Ok(stream) => return Ok(stream),
+                Err(e) => last_error = e.to_string(),
+            }
+        }
+    }
+    Err(DbusError::Connect(last_error))
+}
+
+/// 解码地址中的 `%xx` 转义
+fn unescape(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let decoded = (bytes[index] == b'%')
+            .then(|| text.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match decoded {
+            Some(byte) => {
+                result.push(byte);
+                index += 3;
+            }
+            None => {
+                result.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&result).to_string()
+}
+
+/// EXTERNAL认证，并协商文件描述符传递
+fn authenticate(stream: &mut UnixStream) -> Result<(), DbusError> {
+    let uid = unsafe { libc::getuid() }.to_string();
+    let hex: String = uid.bytes().map(|byte| format!("{:02x}", byte)).collect();
+
+    stream.write_all(format!("\0AUTH EXTERNAL {}\r\n", hex).as_bytes())?;
+    let reply = read_line(stream)?;
+    if !reply.starts_with("OK ") {
+        return Err(DbusError::Auth(reply));
+    }
+
+    stream.write_all(b"NEGOTIATE_UNIX_FD\r\n")?;
+    let reply = read_line(stream)?;
+    if reply != "AGREE_UNIX_FD" {
+        return Err(DbusError::Auth(format!("总线不支持传递文件描述符: {}", reply)));
+    }
+
+    stream.write_all(b"BEGIN\r\n")?;
+    Ok(())
+}
+
+/// 读取一行认证回复（逐字节读取，避免读入后续的二进制消息）
+fn read_line(stream: &mut UnixStream) -> Result<String, DbusError> {
+    let mut line = Vec::new();
+    let mut byte = [0u8; 1];
+    while !line.ends_with(b"\r\n") {
+        if stream.read(&mut byte)? == 0 {
+            return Err(DbusError::Auth("连接在认证期间关闭".to_string()));
+        }
+        line.push(byte[0]);
+        if line.len() > 512 {
+            return Err(DbusError::Auth("认证回复过长".to_string()));
+        }
+    }
+    line.truncate(line.len() - 2);
+    Ok(String::from_utf8_lossy(&line).to_string())
+}
+
+/// 签名首字符对应的对齐字节数
+fn alignment(signature: &str) -> usize {
+    match signature.as_bytes().first() {
+        Some(b'n' | b'q') => 2,
+        Some(b'b' | b'i' | b'u' | b'h' | b's' | b'o' | b'a') => 4,
+        Some(b'x' | b't' | b'd' | b'(' | b'{') => 8,
+        _ => 1,
+    }
+}
+
+/// 拆出签名中的第一个完整类型
+fn split_signature(signature: &str) -> Result<(&str, &str), DbusError> {
+    let bytes = signature.as_bytes();
+    let end = match bytes.first() {
+        None => return Err(DbusError::Protocol("签名为空".to_string())),
+        Some(b'a') => 1 + split_signature(&signature[1..])?.0.len(),
+        Some(&open @ (b'(' | b'{')) => {
+            let close = if open == b'(' { b')' } else { b'}' };
+            let mut depth = 0;
+            let mut end = None;
+            for (index, &byte) in bytes.iter().enumerate() {
+                if byte == open {
+                    depth += 1;
+                } else if byte == close {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = Some(index + 1);
+                        break;
+                    }
+                }
+            }
+            let end = end.ok_or_else(|| DbusError::Protocol(format!("签名括号不匹配: {}", signature)))?;
+            // 空结构体不占任何字节，放在数组中会使解码无法前进
+            if end == 2 {
+                return Err(DbusError::Protocol(format!("签名含空结构体: {}", signature)));
+            }
+            end
+        }
+        Some(_) => 1,
+    };
+    Ok(signature.split_at(end))
+}
+
+/// 消息编码器
+struct Encoder {
+    buffer: Vec<u8>,
+}
+
+impl Encoder {
+    fn pad(&mut self, align: usize) {
+        while !self.buffer.len().is_multiple_of(align) {
+            self.buffer.push(0);
+        }
+    }
+
+    fn put_u32(&mut self, value: u32) {
+        self.pad(4);
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn write(&mut self, value: &Value) {
+        match value {
+            Value::Byte(byte) => self.buffer.push(*byte),
+            Value::Bool(flag) => self.put_u32(*flag as u32),
+            Value::I16(number) => {
+                self.pad(2);
+                self.buffer.extend_from_slice(&number.to_le_bytes());
+            }
+            Value::U16(number) => {
+                self.pad(2);
+                self.buffer.extend_from_slice(&number.to_le_bytes());
+            }
+            Value::I32(number) => {
+                self.pad(4);
+                self.buffer.extend_from_slice(&number.to_le_bytes());
+            }
+            Value::U32(number) | Value::Fd(number) => self.put_u32(*number),
+            Value::I64(number) => {
+                self.pad(8);
+                self.buffer.extend_from_slice(&number.to_le_bytes());
+            }
+            Value::U64(number) => {
+                self.pad(8);
+                self.buffer.extend_from_slice(&number.to_le_bytes());
+            }
+            Value::F64(number) => {
+                self.pad(8);
+                self.buffer.extend_from_slice(&number.to_le_bytes());
+            }
+            Value::Str(text) | Value::Path(text) => {
+                self.put_u32(text.len() as u32);
+                self.buffer.extend_from_slice(text.as_bytes());
+                self.buffer.push(0);
+            }
+            Value::Signature(text) => {
+                self.buffer.push(text.len() as u8);
+                self.buffer.extend_from_slice(text.as_bytes());
+                self.buffer.push(0);
+            }
+            Value::Variant(inner) => {
+                self.write(&Value::Signature(inner.signature()));
+                self.write(inner);
+            }
+            Value::Array(element, items) => {
+                self.put_u32(0);
+                let length_at = self.buffer.len() - 4;
+                self.pad(alignment(element));
+                let start = self.buffer.len();
+                for item in items {
+                    self.write(item);
+                }
+                let length = (self.buffer.len() - start) as u32;
+                self.buffer[length_at..length_at + 4].copy_from_slice(&length.to_le_bytes());
+            }
+            Value::Struct(items) => {
+                self.pad(8);
+                for item in items {
+                    self.write(item);
+                }
+            }
+            Value::DictEntry(key, value) => {
+                self.pad(8);
+                self.write(key);
+                self.write(value);
+            }
+        }
+    }
+}
+
+/// 消息解码器
+struct Decoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+    /// 当前所在的数组、结构体（含字典项）与变体层数
+    arrays: usize,
+    structs: usize,
+    variants: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn new(data: &'a [u8], pos: usize) -> Self {
+        Decoder { data, pos, arrays: 0, structs: 0, variants: 0 }
+    }
+
+    /// 进入一层容器，超过规范的嵌套上限时报错
+    fn enter(&mut self, container: u8) -> Result<(), DbusError> {
+        match container {
+            b'a' => self.arrays += 1,
+            b'v' => self.variants += 1,
+            _ => self.structs += 1,
+        }
+        if self.arrays > MAX_ARRAY_DEPTH
+            || self.structs > MAX_STRUCT_DEPTH
+            || self.arrays + self.structs + self.variants > MAX_TOTAL_DEPTH
+        {
+            return Err(DbusError::Protocol("容器嵌套超过规范上限".to_string()));
+        }
+        Ok(())
+    }
+
+    /// 离开一层容器
+    fn leave(&mut self, container: u8) {
+        match container {
+            b'a' => self.arrays -= 1,
+            b'v' => self.variants -= 1,
+            _ => self.structs -= 1,
+        }
+    }
+
+    fn align(&mut self, align: usize) -> Result<(), DbusError> {
+        self.pos = self.pos.div_ceil(align) * align;
+        if self.pos > self.data.len() {
+            return Err(DbusError::Protocol("消息被截断".to_string()));
+        }
+        Ok(())
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8], DbusError> {
+        let end = self.pos.checked_add(count).filter(|&end| end <= self.data.len());
+        let end = end.ok_or_else(|| DbusError::Protocol("消息被截断".to_string()))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn fixed<const N: usize>(&mut self) -> Result<[u8; N], DbusError> {
+        self.align(N)?;
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn text(&mut self, length: usize) -> Result<String, DbusError> {
+        let bytes = self.take(length)?;
+        self.take(1)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| DbusError::Protocol("字符串不是UTF-8".to_string()))
+    }
+
+    /// 按单个完整类型的签名读取一个值
+    fn read(&mut self, signature: &str) -> Result<Value, DbusError> {
+        let container = signature.as_bytes()[0];
+        if matches!(container, b'a' | b'v' | b'(' | b'{') {
+            self.enter(container)?;
+        }
+        let value = match container {
+            b'y' => Value::Byte(self.take(1)?[0]),
+            b'b' => Value::Bool(u32::from_le_bytes(self.fixed()?) != 0),
+            b'n' => Value::I16(i16::from_le_bytes(self.fixed()?)),
+            b'q' => Value::U16(u16::from_le_bytes(self.fixed()?)),
+            b'i' => Value::I32(i32::from_le_bytes(self.fixed()?)),
+            b'u' => Value::U32(u32::from_le_bytes(self.fixed()?)),
+            b'h' => Value::Fd(u32::from_le_bytes(self.fixed()?)),
+            b'x' => Value::I64(i64::from_le_bytes(self.fixed()?)),
+            b't' => Value::U64(u64::from_le_bytes(self.fixed()?)),
+            b'd' => Value::F64(f64::from_le_bytes(self.fixed()?)),
+            b's' | b'o' => {
+                let length = u32::from_le_bytes(self.fixed()?) as usize;
+                let text = self.text(length)?;
+                if signature.starts_with('s') { Value::Str(text) } else { Value::Path(text) }
+            }
+            b'g' => {
+                let length = self.take(1)?[0] as usize;
+                Value::Signature(self.text(length)?)
+            }
+            b'v' => {
+                let length = self.take(1)?[0] as usize;
+                let inner = self.text(length)?;
+                Value::Variant(Box::new(self.read(split_signature(&inner)?.0)?))
+            }
+            b'a' => {
+                let element = &signature[1..];
+                let length = u32::from_le_bytes(self.fixed()?) as usize;
+                if length > MAX_ARRAY_LENGTH {
+                    return Err(DbusError::Protocol(format!("数组长度超过规范上限: {}", length)));
+                }
+                self.align(alignment(element))?;
+                let end = self.pos + length;
+                if end > self.data.len() {
+                    return Err(DbusError::Protocol("消息被截断".to_string()));
+                }
+                let mut items = Vec::new();
+                while self.pos < end {
+                    items.push(self.read(element)?);
+                }
+                Value::Array(element.to_string(), items)
+            }
+            b'(' | b'{' => {
+                self.align(8)?;
+                let mut rest = &signature[1..signature.len() - 1];
+                let mut items = Vec::new();
+                while !rest.is_empty() {
+                    let (single, remaining) = split_signature(rest)?;
+                    items.push(self.read(single)?);
+                    rest = remaining;
+                }
+                if signature.starts_with('(') {
+                    Value::Struct(items)
+                } else {
+                    let mut items = items.into_iter();
+                    match (items.next(), items.next()) {
+                        (Some(key), Some(value)) => Value::DictEntry(Box::new(key), Box::new(value)),
+                        _ => return Err(DbusError::Protocol("字典项缺少键或值".to_string())),
+                    }
+                }
+            }
+            other => return Err(DbusError::Protocol(format!("不支持的类型: {}", other as char))),
+        };
+        if matches!(container, b'a' | b'v' | b'(' | b'{') {
+            self.leave(container);
+        }
+        Ok(value)
+    }
+
+    /// 按签名读取全部值
+    fn read_all(&mut self, mut signature: &str) -> Result<Vec<Value>, DbusError> {
+        let mut values = Vec::new();
+        while !signature.is_empty() {
+            let (single, rest) = split_signature(signature)?;
+            values.push(self.read(single)?);
+            signature = rest;
+        }
+        Ok(values)
+    }
+}
+
+/// 编码方法调用
+fn encode_call(serial: u32, destination: &str, path: &str, interface: &str, member: &str, body: &[Value]) -> Vec<u8> {
+    let mut body_encoder = Encoder { buffer: Vec::new() };
+    for value in body {
+        body_encoder.write(value);
+    }
+    let signature: String = body.iter().map(Value::signature).collect();
+
+    let field = |code: u8, value: Value| Value::Struct(vec![Value::Byte(code), Value::Variant(Box::new(value))]);
+    let mut fields = vec![
+        field(1, Value::Path(path.to_string())),
+        field(2, Value::str(interface)),
+        field(3, Value::str(member)),
+        field(6, Value::str(destination)),
+    ];
+    if !signature.is_empty() {
+        fields.push(field(8, Value::Signature(signature)));
+    }
+
+    let mut encoder = Encoder { buffer: vec![b'l', 1, 0, 1] };
+    encoder.put_u32(body_encoder.buffer.len() as u32);
+    encoder.put_u32(serial);
+    encoder.write(&Value::Array("(yv)".to_string(), fields));
+    encoder.pad(8);
+    encoder.buffer.extend_from_slice(&body_encoder.buffer);
+    encoder.buffer
+}
+
+/// 从缓冲区解码一条完整消息（数据不足时返回None）
+fn decode_message(buffer: &mut Vec<u8>, fds: &mut VecDeque<OwnedFd>) -> Result<Option<Message>, DbusError> {
+    if buffer.len() < 16 {
+        return Ok(None);
+    }
+    if buffer[0] != b'l' {
+        return Err(DbusError::Protocol("只支持小端序消息".to_string()));
+    }
+
+    let word = |offset: usize| u32::from_le_bytes(buffer[offset..offset + 4].try_into().unwrap()) as usize;
+    let body_length = word(4);
+    let header_end = (16 + word(12)).div_ceil(8) * 8;
+    let total = header_end + body_length;
+    // 先检查声明的长度，避免为超长消息无限缓冲
+    if total > MAX_MESSAGE_LENGTH {
+        return Err(DbusError::Protocol(format!("消息长度超过规范上限: {}", total)));
+    }
+    if buffer.len() < total {
+        return Ok(None);
+    }
+
+    let kind = match buffer[1] {
+        1 => MessageKind::MethodCall,
+        2 => MessageKind::MethodReturn,
+        3 => MessageKind::Error,
+        4 => MessageKind::Signal,
+        other => return Err(DbusError::Protocol(format!("未知消息类型: {}", other))),
+    };
+
+    let mut header = Decoder::new(&buffer[..header_end], 12);
+    let fields = header.read("a(yv)")?;
+    let mut message = Message {
+        kind,
+        reply_serial: None,
+        path: None,
+        interface: None,
+        member: None,
+        error_name: None,
+        body: Vec::new(),
+        fds: Vec::new(),
+    };
+    let mut signature = String::new();
+    let mut fd_count = 0;
+    for field in fields.as_array().unwrap_or_default() {
+        let Value::Struct(items) = field else { continue };
+        let (Some(Value::Byte(code)), Some(value)) = (items.first(), items.get(1)) else { continue };
+        let text = value.as_str().map(str::to_string);
+        match code {
+            1 => message.path = text,
+            2 => message.interface = text,
+            3 => message.member = text,
+            4 => message.error_name = text,
+            5 => message.reply_serial = value.as_u32(),
+            8 => signature = text.unwrap_or_default(),
+            9 => fd_count = value.as_u32().unwrap_or(0) as usize,
+            _ => {}
+        }
+    }
+
+    message.body = Decoder::new(&buffer[header_end..total], 0).read_all(&signature)?;
+    if fds.len() < fd_count {
+        return Err(DbusError::Protocol("消息附带的文件描述符缺失".to_string()));
+    }
+    message.fds = fds.drain(..fd_count).collect();
+    buffer.drain(..total);
+    Ok(Some(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_splitting() {
+        assert_eq!(split_signature("a{sv}u").unwrap(), ("a{sv}", "u"));
+        assert_eq!(split_signature("(ua(yv))s").unwrap(), ("(ua(yv))", "s"));
+        assert!(split_signature("(ua").is_err());
+        assert!(split_signature("a()").is_err());
+        assert_eq!(unescape("/run/user/1000/bus%2c1"), "/run/user/1000/bus,1");
+    }
+
+    #[test]
+    fn test_call_round_trips_through_decoder() {
+        let options = Value::dict(vec![
+            ("handle_token", Value::str("clipvanish1")),
+            ("types", Value::U32(1)),
+            ("mime_types", Value::string_array(&["text/plain"])),
+        ]);
+        let body = vec![Value::Path("/org/freedesktop/portal/desktop/session/1".to_string()), options];
+        let mut bytes = encode_call(7, "org.freedesktop.portal.Desktop", "/org/freedesktop/portal/desktop",
+            "org.freedesktop.portal.RemoteDesktop", "SelectDevices", &body);
+        bytes.extend_from_slice(b"partial");
+
+        let message = decode_message(&mut bytes, &mut VecDeque::new()).unwrap().unwrap();
+        assert_eq!(bytes, b"partial");
+        assert_eq!(message.kind, MessageKind::MethodCall);
+        assert_eq!(message.member.as_deref(), Some("SelectDevices"));
+        assert_eq!(message.body, body);
+        assert_eq!(message.body[1].get("types").and_then(Value::as_u32), Some(1));
+        assert_eq!(decode_message(&mut bytes, &mut VecDeque::new()).unwrap().map(|m| m.kind), None);
+    }
+
+    /// 编码单个值
+    fn encode(value: &Value) -> Vec<u8> {
+        let mut encoder = Encoder { buffer: Vec::new() };
+        encoder.write(value);
+        encoder.buffer
+    }
+
+    fn protocol_error(result: Result<Vec<Value>, DbusError>) -> bool {
+        matches!(result, Err(DbusError::Protocol(_)))
+    }
+
+    #[test]
+    fn test_nested_variants_are_limited() {
+        let nested = |depth: usize| (0..depth).fold(Value::U32(7), |inner, _| Value::Variant(Box::new(inner)));
+
+        let allowed = encode(&nested(MAX_TOTAL_DEPTH));
+        assert_eq!(Decoder::new(&allowed, 0).read_all("v").unwrap(), vec![nested(MAX_TOTAL_DEPTH)]);
+
+        let too_deep = encode(&nested(MAX_TOTAL_DEPTH + 1));
+        assert!(protocol_error(Decoder::new(&too_deep, 0).read_all("v")));
+
+        // 对端发来的深层嵌套返回错误而不是耗尽栈
+        let hostile = [1, b'v', 0].repeat(100_000);
+        assert!(protocol_error(Decoder::new(&hostile, 0).read_all("v")));
+    }
+
+    #[test]
+    fn test_nested_arrays_are_limited() {
+        let nested = |depth: usize| {
+            (1..depth).fold(Value::Array("y".to_string(), vec![Value::Byte(1)]), |inner, level| {
+                Value::Array(format!("{}y", "a".repeat(level)), vec![inner])
+            })
+        };
+
+        let signature = format!("{}y", "a".repeat(MAX_ARRAY_DEPTH));
+        assert!(Decoder::new(&encode(&nested(MAX_ARRAY_DEPTH)), 0).read_all(&signature).is_ok());
+
+        let signature = format!("{}y", "a".repeat(MAX_ARRAY_DEPTH + 1));
+        assert!(protocol_error(Decoder::new(&encode(&nested(MAX_ARRAY_DEPTH + 1)), 0).read_all(&signature)));
+    }
+
+    #[test]
+    fn test_malformed_lengths_are_rejected() {
+        // 数组长度超出数据或超过规范上限
+        let mut bytes = 8u32.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&[1, 2, 3, 4]);
+        assert!(protocol_error(Decoder::new(&bytes, 0).read_all("ay")));
+        let bytes = ((MAX_ARRAY_LENGTH + 1) as u32).to_le_bytes();
+        assert!(protocol_error(Decoder::new(&bytes, 0).read_all("ay")));
+
+        // 字符串长度超出数据
+        let bytes = u32::MAX.to_le_bytes();
+        assert!(protocol_error(Decoder::new(&bytes, 0).read_all("s")));
+
+        // 变体签名中的空结构体与不支持的类型
+        assert!(protocol_error(Decoder::new(&[2, b'(', b')', 0], 0).read_all("v")));
+        assert!(protocol_error(Decoder::new(&[1, b'z', 0], 0).read_all("v")));
+
+        // 声明超长的消息立即报错，不再等待更多数据
+        let mut message = vec![b'l', 2, 0, 1];
+        message.extend_from_slice(&(MAX_MESSAGE_LENGTH as u32).to_le_bytes());
+        message.extend_from_slice(&[0; 8]);
+        assert!(matches!(decode_message(&mut message, &mut VecDeque::new()), Err(DbusError::Protocol(_))));
+    }
+}
@@ -0,0 +1,176 @@
+/*!
+ * ClipVanish™ 诱饵内容替换模块
+ *
+ * 受保护内容在剪贴板中显示为逼真的诱饵文本（如形似API密钥的字符串），而不是密文
+ * 特点：
+ * - 真实内容仍加密保存在内存中，只在验证过的粘贴时解密注入
+ * - 诱饵按模板随机生成，模板可配置；窃取到的剪贴板数据毫无价值
+ * - 生成的诱饵记入诱饵令牌记录，之后出现在日志或剪贴板中时可由 `canary check` 追查
+ *
+ * 模板占位符（n为长度）：
+ * - `{U:n}` 大写字母与数字   - `{L:n}` 小写字母与数字   - `{X:n}` 大小写字母与数字
+ * - `{D:n}` 数字             - `{H:n}` 小写十六进制
+ *
+ * 作者: ClipVanish Team
+ */
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// 单个占位符的最大长度
+const MAX_PLACEHOLDER_LEN: usize = 256;
+
+/// 诱饵内容配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DecoyConfig {
+    /// 是否以诱饵文本代替剪贴板中的密文
+    pub enabled: bool,
+    /// 诱饵模板（每个条目随机选择一个）
+    pub templates: Vec<String>,
+    /// 是否将生成的诱饵记入诱饵令牌记录以便追查
+    pub track: bool,
+}
+
+impl Default for DecoyConfig {
+    fn default() -> Self {
+        DecoyConfig {
+            enabled: false,
+            templates: vec![
+                "AKIA{U:16}".to_string(),
+                "ghp_{X:36}".to_string(),
+                "sk-{X:48}".to_string(),
+                "xoxb-{D:12}-{D:13}-{X:24}".to_string(),
+                "{H:8}-{H:4}-{H:4}-{H:4}-{H:12}".to_string(),
+            ],
+            track: true,
+        }
+    }
+}
+
+/// 占位符字符集
+fn alphabet(class: char) -> Option<&'static [u8]> {
+    match class {
+        'U' => Some(b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789"),
+        'L' => Some(b"abcdefghijklmnopqrstuvwxyz0123456789"),
+        'X' => Some(b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789"),
+        'D' => Some(b"0123456789"),
+        'H' => Some(b"0123456789abcdef"),
+        _ => None,
+    }
+}
+
+/// 模板片段
+enum Segment<'a> {
+    /// 原样输出的文本
+    Literal(&'a str),
+    /// 随机字符（字符集，长度）
+    Random(&'static [u8], usize),
+}
+
+/// 解析模板
+///
+/// # 参数
+/// * `template` - 诱饵模板
+fn parse(template: &str) -> Result<Vec<Segment<'_>>, String> {
+    let mut segments = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        if start > 0 {
+            segments.push(Segment::Literal(&rest[..start]));
+        }
+        let end = rest[start..].find('}').ok_or_else(|| format!("模板 {:?} 中的占位符没有闭合", template))? + start;
+        let placeholder = &rest[start + 1..end];
+        let invalid = || format!("模板 {:?} 中的占位符 {{{}}} 无效（格式为 {{U:n}}、{{L:n}}、{{X:n}}、{{D:n}} 或 {{H:n}}）", template, placeholder);
+
+        let (class, length) = placeholder.split_once(':').ok_or_else(invalid)?;
+        let mut chars = class.chars();
+        let alphabet = match (chars.next(), chars.next()) {
+            (Some(class), None) => alphabet(class).ok_or_else(invalid)?,
+            _ => return Err(invalid()),
+        };
+        let length: usize = length.parse().map_err(|_| invalid())?;
+        if length == 0 || length > MAX_PLACEHOLDER_LEN {
+            return Err(invalid());
+        }
+        segments.push(Segment::Random(alphabet, length));
+        rest = &rest[end + 1..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Literal(rest));
+    }
+    Ok(segments)
+}
+
+/// 检查模板是否有效（至少含一个占位符，否则每次生成的诱饵都相同）
+///
+/// # 参数
+/// * `template` - 诱饵模板
+pub fn validate_template(template: &str) -> Result<(), String> {
+    let segments = parse(template)?;
+    if !segments.iter().any(|segment| matches!(segment, Segment::Random(..))) {
+        return Err(format!("模板 {:?} 不含随机占位符", template));
+    }
+    Ok(())
+}
+
+/// 按模板生成诱饵文本
+///
+/// # 参数
+/// * `template` - 诱饵模板
+pub fn render(template: &str) -> Result<String, String> {
+    let mut rng = rand::rngs::OsRng;
+    let mut decoy = String::new();
+    for segment in parse(template)? {
+        match segment {
+            Segment::Literal(text) => decoy.push_str(text),
+            Segment::Random(alphabet, length) => {
+                decoy.extend((0..length).map(|_| alphabet[rng.gen_range(0..alphabet.len())] as char));
+            }
+        }
+    }
+    Ok(decoy)
+}
+
+/// 随机选择一个模板生成诱饵文本（没有可用模板时返回None）
+///
+/// # 参数
+/// * `config` - 诱饵内容配置
+pub fn generate(config: &DecoyConfig) -> Option<String> {
+    let template = config.templates.choose(&mut rand::rngs::OsRng)?;
+    render(template).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_templates() {
+        let key = render("AKIA{U:16}").unwrap();
+        assert_eq!(key.len(), 20);
+        assert!(key.starts_with("AKIA") && key[4..].chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()));
+
+        let token = render("xoxb-{D:3}-{H:4}").unwrap();
+        let parts: Vec<&str> = token.split('-').collect();
+        assert_eq!(parts[0], "xoxb");
+        assert!(parts[1].len() == 3 && parts[1].chars().all(|c| c.is_ascii_digit()));
+        assert!(parts[2].len() == 4 && parts[2].chars().all(|c| c.is_ascii_hexdigit()));
+
+        for template in DecoyConfig::default().templates {
+            assert!(validate_template(&template).is_ok(), "{}", template);
+        }
+    }
+
+    #[test]
+    fn test_invalid_templates() {
+        assert!(validate_template("plain text").is_err());
+        assert!(validate_template("sk-{X:48").is_err());
+        assert!(validate_template("sk-{Q:4}").is_err());
+        assert!(validate_template("sk-{X:0}").is_err());
+        assert!(validate_template("sk-{XX:4}").is_err());
+        assert!(generate(&DecoyConfig { templates: Vec::new(), ..DecoyConfig::default() }).is_none());
+    }
+}
@@ -0,0 +1,355 @@
+/*!
+ * ClipVanish™ 延迟渲染模块（Windows）
+ *
+ * 受保护内容不再以密文写入剪贴板，而是只放置一个"承诺"：剪贴板中登记了文本格式但没有数据，
+ * 直到某个应用真正请求该格式（粘贴）时，系统才向剪贴板所有者发送WM_RENDERFORMAT，此时才解密并交付
+ * 特点：
+ * - 由后台线程持有一个隐藏的消息窗口（HWND_MESSAGE）作为剪贴板所有者
+ * - 放置承诺时附加 ExcludeClipboardContentFromMonitorProcessing 等标记，剪贴板历史、云剪贴板与遵守约定的剪贴板管理器不会请求渲染
+ * - 窗口销毁时仍持有承诺的（WM_RENDERALLFORMATS）直接清空剪贴板，而不是按惯例渲染全部格式
+ * - 渲染后的明文由系统保存，仍按粘贴倒计时清除
+ * - 仅在Windows上编译
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use log::warn;
+use zeroize::Zeroizing;
+
+/// 渲染回调：应用请求数据时返回要交付的明文，返回None表示拒绝渲染
+pub type RenderProvider = Arc<dyn Fn() -> Option<Zeroizing<String>> + Send + Sync>;
+
+/// 窗口线程与调用方共享的渲染状态
+struct RenderState {
+    /// 渲染回调
+    provider: RenderProvider,
+    /// 当前承诺是否仍未兑现（已放置且未渲染、未被替换）
+    pending: AtomicBool,
+}
+
+impl RenderState {
+    /// 兑现承诺，返回以0结尾的UTF-16文本（CF_UNICODETEXT格式）
+    ///
+    /// 无论回调是否交付内容，承诺都视为已兑现，之后的请求不会再次解密
+    fn render_utf16(&self) -> Option<Zeroizing<Vec<u16>>> {
+        if !self.pending.swap(false, Ordering::SeqCst) {
+            return None;
+        }
+
+        let Some(text) = (self.provider)() else {
+            warn!("渲染回调拒绝交付内容，粘贴将得到空数据");
+            return None;
+        };
+        Some(Zeroizing::new(text.encode_utf16().chain(std::iter::once(0)).collect()))
+    }
+}
+
+/// 延迟渲染器
+pub struct DelayedRenderer {
+    /// 渲染状态
+    state: Arc<RenderState>,
+    /// 隐藏窗口句柄（以整数保存以便跨线程共享）
+    window: usize,
+    /// 窗口消息循环线程
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl DelayedRenderer {
+    /// 创建隐藏的剪贴板所有者窗口
+    ///
+    /// # 参数
+    /// * `provider` - 渲染回调，在窗口线程中调用，不能再访问剪贴板
+    ///
+    /// # 返回值
+    /// * `Result<DelayedRenderer, String>` - 延迟渲染器
+    pub fn start(provider: RenderProvider) -> Result<Self, String> {
+        let state = Arc::new(RenderState { provider, pending: AtomicBool::new(false) });
+        let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+
+        let thread_state = state.clone();
+        let thread = std::thread::Builder::new()
+            .name("clipvanish-render".to_string())
+            .spawn(move || windows::run_window(thread_state, ready_tx))
+            .map_err(|e| e.to_string())?;
+
+        let window = ready_rx
+            .recv()
+            .map_err(|_| "剪贴板窗口线程意外退出".to_string())??;
+        log::info!("延迟渲染已启用，受保护内容仅在粘贴请求时解密");
+        Ok(DelayedRenderer { state, window, thread: Some(thread) })
+    }
+
+    /// 清空剪贴板并放置受保护内容的承诺（不写入任何数据）
+    ///
+    /// # 返回值
+    /// * `Result<(), String>` - 操作结果
+    pub fn promise(&self) -> Result<(), String> {
+        // 在窗口线程中打开剪贴板，使隐藏窗口成为所有者
+        if windows::send_promise(self.window) {
+            self.state.pending.store(true, Ordering::SeqCst);
+            Ok(())
+        } else {
+            Err("无法放置延迟渲染承诺".to_string())
+        }
+    }
+
+    /// 剪贴板中是否仍是尚未兑现的承诺
+    ///
+    /// 此时读取剪贴板会触发渲染（相当于一次粘贴），监听器应跳过读取
+    pub fn is_pending(&self) -> bool {
+        self.state.pending.load(Ordering::SeqCst) && self.owns_clipboard()
+    }
+
+    /// 隐藏窗口是否仍是剪贴板所有者
+    fn owns_clipboard(&self) -> bool {
+        windows::owns_clipboard(self.window)
+    }
+}
+
+impl Drop for DelayedRenderer {
+    fn drop(&mut self) {
+        // 关闭窗口；仍持有承诺时窗口会收到WM_RENDERALLFORMATS并清空剪贴板
+        windows::close(self.window);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+mod windows {
+    use super::RenderState;
+    use std::cell::RefCell;
+    use std::ptr;
+    use std::sync::atomic::Ordering;
+    use std::sync::mpsc::Sender;
+    use std::sync::Arc;
+    use log::{debug, warn};
+    use winapi::shared::minwindef::{LPARAM, LRESULT, UINT, WPARAM};
+    use winapi::shared::windef::HWND;
+    use winapi::um::errhandlingapi::GetLastError;
+    use winapi::um::libloaderapi::GetModuleHandleW;
+    use winapi::um::winbase::{GlobalAlloc, GlobalFree, GlobalLock, GlobalUnlock, GMEM_MOVEABLE};
+    use winapi::um::winuser::{
+        CloseClipboard, CreateWindowExW, DefWindowProcW, DispatchMessageW, EmptyClipboard, GetClipboardOwner,
+        GetMessageW, OpenClipboard, PostMessageW, PostQuitMessage, RegisterClassW, RegisterClipboardFormatW,
+        SendMessageW, SetClipboardData, TranslateMessage, CF_UNICODETEXT, HWND_MESSAGE, MSG, WM_APP, WM_CLOSE,
+        WM_DESTROY, WM_DESTROYCLIPBOARD, WM_RENDERALLFORMATS, WM_RENDERFORMAT, WNDCLASSW,
+    };
+
+    /// 放置承诺（在窗口线程中执行）
+    const WM_PROMISE: UINT = WM_APP + 1;
+
+    /// 隐藏窗口的类名
+    const CLASS_NAME: &str = "ClipVanishDelayedRender";
+
+    /// 随承诺一起放置的标记格式：剪贴板历史、云剪贴板与剪贴板监视程序不处理该内容
+    const EXCLUSION_FORMATS: &[(&str, u32)] = &[
+        ("ExcludeClipboardContentFromMonitorProcessing", 0),
+        ("CanIncludeInClipboardHistory", 0),
+        ("CanUploadToCloudClipboard", 0),
+    ];
+
+    thread_local! {
+        /// 窗口线程持有的渲染状态（窗口过程只在该线程中运行）
+        static STATE: RefCell<Option<Arc<RenderState>>> = const { RefCell::new(None) };
+    }
+
+    /// 以0结尾的UTF-16字符串
+    fn wide(text: &str) -> Vec<u16> {
+        text.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+
+    /// 创建隐藏窗口并运行消息循环，直到窗口被销毁
+    pub(super) fn run_window(state: Arc<RenderState>, ready: Sender<Result<usize, String>>) {
+        let class_name = wide(CLASS_NAME);
+
+        unsafe {
+            let instance = GetModuleHandleW(ptr::null());
+            let mut class: WNDCLASSW = std::mem::zeroed();
+            class.lpfnWndProc = Some(window_proc);
+            class.hInstance = instance;
+            class.lpszClassName = class_name.as_ptr();
+            // 重复启动时类已注册，失败可以忽略，由创建窗口的结果判断
+            RegisterClassW(&class);
+
+            let window = CreateWindowExW(
+                0,
+                class_name.as_ptr(),
+                class_name.as_ptr(),
+                0,
+                0,
+                0,
+                0,
+                0,
+                HWND_MESSAGE,
+                ptr::null_mut(),
+                instance,
+                ptr::null_mut(),
+            );
+            if window.is_null() {
+                let _ = ready.send(Err(format!("创建隐藏剪贴板窗口失败，错误码: {}", GetLastError())));
+                return;
+            }
+
+            STATE.with(|cell| *cell.borrow_mut() = Some(state));
+            let _ = ready.send(Ok(window as usize));
+
+            let mut message: MSG = std::mem::zeroed();
+            while GetMessageW(&mut message, ptr::null_mut(), 0, 0) > 0 {
+                TranslateMessage(&message);
+                DispatchMessageW(&message);
+            }
+
+            STATE.with(|cell| *cell.borrow_mut() = None);
+            debug!("延迟渲染窗口已关闭");
+        }
+    }
+
+    /// 请求窗口线程放置承诺
+    pub(super) fn send_promise(window: usize) -> bool {
+        unsafe { SendMessageW(window as HWND, WM_PROMISE, 0, 0) != 0 }
+    }
+
+    /// 隐藏窗口是否仍是剪贴板所有者
+    pub(super) fn owns_clipboard(window: usize) -> bool {
+        unsafe { GetClipboardOwner() as usize == window }
+    }
+
+    /// 请求关闭窗口
+    pub(super) fn close(window: usize) {
+        unsafe {
+            PostMessageW(window as HWND, WM_CLOSE, 0, 0);
+        }
+    }
+
+    /// 窗口过程
+    unsafe extern "system" fn window_proc(window: HWND, message: UINT, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        match message {
+            WM_PROMISE => place_promise(window) as LRESULT,
+            WM_RENDERFORMAT => {
+                // 渲染期间剪贴板已由请求方打开，这里只能调用SetClipboardData
+                if wparam as UINT == CF_UNICODETEXT {
+                    render_text();
+                }
+                0
+            },
+            WM_RENDERALLFORMATS => {
+                // 退出时仍持有承诺：清空而不是渲染，明文不会在进程退出后留在剪贴板中
+                if OpenClipboard(window) != 0 {
+                    if GetClipboardOwner() == window {
+                        EmptyClipboard();
+                        debug!("退出时清空了尚未兑现的延迟渲染承诺");
+                    }
+                    CloseClipboard();
+                }
+                0
+            },
+            WM_DESTROYCLIPBOARD => {
+                // 剪贴板被清空或被其他程序接管，承诺作废
+                STATE.with(|cell| {
+                    if let Some(state) = cell.borrow().as_ref() {
+                        state.pending.store(false, Ordering::SeqCst);
+                    }
+                });
+                0
+            },
+            WM_DESTROY => {
+                PostQuitMessage(0);
+                0
+            },
+            _ => DefWindowProcW(window, message, wparam, lparam),
+        }
+    }
+
+    /// 清空剪贴板，只登记文本格式（数据为空即延迟渲染）并附加标记格式
+    unsafe fn place_promise(window: HWND) -> bool {
+        if OpenClipboard(window) == 0 {
+            warn!("无法打开剪贴板放置延迟渲染承诺，错误码: {}", GetLastError());
+            return false;
+        }
+
+        let placed = EmptyClipboard() != 0;
+        if placed {
+            SetClipboardData(CF_UNICODETEXT, ptr::null_mut());
+            for (name, value) in EXCLUSION_FORMATS {
+                set_dword_format(name, *value);
+            }
+        }
+
+        CloseClipboard();
+        placed
+    }
+
+    /// 以DWORD数据设置已注册的自定义格式
+    unsafe fn set_dword_format(name: &str, value: u32) {
+        let format = RegisterClipboardFormatW(wide(name).as_ptr());
+        if format == 0 {
+            return;
+        }
+
+        let handle = GlobalAlloc(GMEM_MOVEABLE, std::mem::size_of::<u32>());
+        if handle.is_null() {
+            return;
+        }
+        let data = GlobalLock(handle) as *mut u32;
+        if data.is_null() {
+            GlobalFree(handle);
+            return;
+        }
+        *data = value;
+        GlobalUnlock(handle);
+
+        // 设置成功后内存归系统所有
+        if SetClipboardData(format, handle).is_null() {
+            GlobalFree(handle);
+        }
+    }
+
+    /// 响应WM_RENDERFORMAT：解密并交付CF_UNICODETEXT数据
+    unsafe fn render_text() {
+        let Some(text) = STATE.with(|cell| cell.borrow().as_ref().and_then(|state| state.render_utf16())) else {
+            return;
+        };
+
+        let size = text.len() * std::mem::size_of::<u16>();
+        let handle = GlobalAlloc(GMEM_MOVEABLE, size);
+        if handle.is_null() {
+            warn!("分配渲染内存失败，错误码: {}", GetLastError());
+            return;
+        }
+        let data = GlobalLock(handle) as *mut u16;
+        if data.is_null() {
+            GlobalFree(handle);
+            return;
+        }
+        ptr::copy_nonoverlapping(text.as_ptr(), data, text.len());
+        GlobalUnlock(handle);
+
+        if SetClipboardData(CF_UNICODETEXT, handle).is_null() {
+            warn!("交付渲染数据失败，错误码: {}", GetLastError());
+            GlobalFree(handle);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_promise_renders_once() {
+        let state = RenderState {
+            provider: Arc::new(|| Some(Zeroizing::new("密钥abc".to_string()))),
+            pending: AtomicBool::new(true),
+        };
+
+        let rendered = state.render_utf16().unwrap();
+        assert_eq!(rendered.last(), Some(&0));
+        assert_eq!(String::from_utf16(&rendered[..rendered.len() - 1]).unwrap(), "密钥abc");
+
+        // 承诺只兑现一次
+        assert!(state.render_utf16().is_none());
+    }
+}
@@ -0,0 +1,205 @@
+/*!
+ * ClipVanish™ 环境诊断模块
+ *
+ * 检查运行环境中各项依赖是否可用，帮助定位问题
+ * 特点：
+ * - 配置文件有效性检查
+ * - 剪贴板访问检查
+ * - 各项功能的启用/降级/停用状态（与 status 相同）
+ * - 平台辅助工具检查
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::path::PathBuf;
+use clipboard::{ClipboardProvider, ClipboardContext};
+use crate::virtual_clipboard;
+use crate::config::Config;
+use crate::capabilities::{self, CapabilityState};
+use crate::helpers::{self, HelperInput};
+use crate::confinement;
+
+/// 检查结果状态
+#[derive(Debug, Clone, PartialEq)]
+pub enum CheckStatus {
+    /// 正常
+    Pass,
+    /// 可用但功能受限
+    Warn,
+    /// 不可用
+    Fail,
+}
+
+impl CheckStatus {
+    /// 状态对应的显示符号
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            CheckStatus::Pass => "✅",
+            CheckStatus::Warn => "⚠️",
+            CheckStatus::Fail => "❌",
+        }
+    }
+}
+
+/// 单项诊断结果
+#[derive(Debug, Clone)]
+pub struct DoctorCheck {
+    /// 检查项名称
+    pub name: String,
+    /// 检查结果
+    pub status: CheckStatus,
+    /// 详细说明
+    pub detail: String,
+}
+
+impl DoctorCheck {
+    fn new(name: &str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        DoctorCheck {
+            name: name.to_string(),
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for DoctorCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}: {}", self.status.symbol(), self.name, self.detail)
+    }
+}
+
+/// 执行全部诊断检查
+///
+/// # 参数
+/// * `config` - 当前配置
+///
+/// # 返回值
+/// * `Vec<DoctorCheck>` - 各项检查结果
+pub fn run_checks(config: &Config) -> Vec<DoctorCheck> {
+    let mut checks = Vec::new();
+
+    // 配置有效性
+    checks.push(match config.validate() {
+        Ok(()) => DoctorCheck::new("配置文件", CheckStatus::Pass, "配置有效"),
+        Err(e) => DoctorCheck::new("配置文件", CheckStatus::Fail, e.to_string()),
+    });
+
+    // 剪贴板访问（无头模式下使用虚拟剪贴板，不依赖图形环境；门户只检查是否可用，不发起授权）
+    let portal = !virtual_clipboard::is_headless() && virtual_clipboard::uses_portal(config.clipboard.access);
+    checks.push(if portal && !cfg!(all(target_os = "linux", feature = "portal")) {
+        DoctorCheck::new("剪贴板访问", CheckStatus::Fail, "配置要求使用xdg-desktop-portal，但当前构建未包含portal特性")
+    } else if portal && confinement::portal_available() {
+        DoctorCheck::new("剪贴板访问", CheckStatus::Pass, "通过xdg-desktop-portal访问（首次使用时需在系统对话框中授权）")
+    } else if portal {
+        DoctorCheck::new("剪贴板访问", CheckStatus::Fail, "配置要求使用xdg-desktop-portal，但未检测到门户服务")
+    } else {
+        match ClipboardContext::new() {
+            Ok(_) => DoctorCheck::new("剪贴板访问", CheckStatus::Pass, "系统剪贴板可用"),
+            Err(e) if virtual_clipboard::is_headless() => {
+                DoctorCheck::new("剪贴板访问", CheckStatus::Pass, format!("无头模式，使用虚拟剪贴板（系统剪贴板不可用: {}）", e))
+            }
+            Err(e) => DoctorCheck::new(
+                "剪贴板访问",
+                CheckStatus::Fail,
+                confinement::annotate(&format!("{}（无图形环境时可使用 --headless）", e)),
+            ),
+        }
+    });
+
+    // 沙箱环境（Flatpak/Snap/AppArmor/SELinux/App Sandbox）
+    let confined = confinement::detect();
+    for item in &confined {
+        checks.push(DoctorCheck::new(
+            "沙箱环境",
+            CheckStatus::Warn,
+            format!("{}；若剪贴板或键盘访问受阻：{}", item, item.guidance()),
+        ));
+    }
+    if !confined.is_empty() && cfg!(target_os = "linux") && !portal {
+        let detail = if !cfg!(feature = "portal") {
+            "当前构建未包含portal特性，沙箱内仍需X11/Wayland访问"
+        } else if confinement::portal_available() {
+            "xdg-desktop-portal可用，可设置 clipboard.access 为 portal"
+        } else {
+            "未检测到xdg-desktop-portal，需要直接的X11/Wayland访问"
+        };
+        checks.push(DoctorCheck::new("剪贴板门户", CheckStatus::Warn, detail));
+    }
+
+    // 功能降级检测（热键、键盘监听、内存锁定、原生清除、控制接口）
+    for capability in capabilities::probe(config) {
+        let status = match capability.state {
+            CapabilityState::Active => CheckStatus::Pass,
+            CapabilityState::Degraded | CapabilityState::Disabled => CheckStatus::Warn,
+        };
+        let detail = match &capability.reason {
+            Some(reason) => format!("{}（{}）", capability.state.label(), reason),
+            None => capability.state.label().to_string(),
+        };
+        checks.push(DoctorCheck::new(capability.feature.label(), status, detail));
+    }
+
+    // 平台辅助工具（严格模式下一律不启动）
+    for helper in helpers::platform_helpers() {
+        let channel = match helper.input {
+            HelperInput::Stdin => "，内容经stdin传递",
+            HelperInput::None => "",
+        };
+        checks.push(if helpers::is_strict() {
+            DoctorCheck::new(helper.program, CheckStatus::Warn, format!("严格模式已禁止，{}功能将回退", helper.purpose))
+        } else {
+            match find_in_path(helper.program) {
+                Some(path) => DoctorCheck::new(
+                    helper.program,
+                    CheckStatus::Pass,
+                    format!("{}{} ({})", helper.purpose, channel, path.display()),
+                ),
+                None => DoctorCheck::new(helper.program, CheckStatus::Warn, format!("未找到，{}功能将回退", helper.purpose)),
+            }
+        });
+    }
+
+    checks
+}
+
+/// 在PATH中查找可执行文件
+///
+/// # 参数
+/// * `program` - 程序名称
+///
+/// # 返回值
+/// * `Option<PathBuf>` - 找到时返回完整路径
+pub fn find_in_path(program: &str) -> Option<PathBuf> {
+    let path_var = std::env::var_os("PATH")?;
+    let candidates: Vec<String> = if cfg!(windows) {
+        vec![format!("{}.exe", program), program.to_string()]
+    } else {
+        vec![program.to_string()]
+    };
+
+    std::env::split_paths(&path_var)
+        .flat_map(|dir| candidates.iter().map(move |name| dir.join(name)))
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_in_path_missing_program() {
+        assert!(find_in_path("clipvanish-definitely-missing-tool").is_none());
+    }
+
+    #[test]
+    fn test_check_display() {
+        let check = DoctorCheck::new("测试项", CheckStatus::Warn, "说明");
+        assert_eq!(check.to_string(), "⚠️ 测试项: 说明");
+    }
+
+    #[test]
+    fn test_run_checks_includes_config() {
+        let checks = run_checks(&Config::default());
+        assert!(checks.iter().any(|c| c.name == "配置文件" && c.status == CheckStatus::Pass));
+    }
+}
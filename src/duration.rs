@@ -0,0 +1,175 @@
+/*!
+ * ClipVanish™ 时长解析模块
+ *
+ * 命令行参数与交互命令共用的时长解析与显示（start --timer、extend、nuke --in/--older-than、note --ttl、审计时间范围）
+ * 特点：
+ * - 纯数字按秒解析，与之前只接受秒数的参数兼容
+ * - 单位可以组合（1h30m、2m30s），支持英文单位（s/sec、m/min、h/hr、d/day）与中文单位（秒、分钟、小时、天）
+ * - 错误信息指出无法识别的部分，并给出可用的写法
+ * - 显示时使用同样的写法（1h30m、2m5s），输出可以直接作为参数再次使用
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::fmt;
+use std::time::Duration;
+
+/// 单位名称与对应的秒数
+const UNITS: &[(&[&str], u64)] = &[
+    (&["d", "day", "days", "天", "日"], 86_400),
+    (&["h", "hr", "hrs", "hour", "hours", "小时", "时"], 3_600),
+    (&["m", "min", "mins", "minute", "minutes", "分钟", "分"], 60),
+    (&["s", "sec", "secs", "second", "seconds", "秒", "秒钟"], 1),
+];
+
+/// 时长解析错误
+#[derive(Debug, Clone, PartialEq)]
+pub enum DurationError {
+    /// 空文本
+    Empty,
+    /// 无法识别的单位
+    UnknownUnit { text: String, unit: String },
+    /// 组合写法中的数字缺少单位（如 1h30）
+    MissingUnit { text: String, number: String },
+    /// 单位前缺少数字（如 h30m）
+    MissingNumber { text: String },
+    /// 时长为0
+    Zero(String),
+    /// 超出可表示的范围
+    TooLarge(String),
+}
+
+impl fmt::Display for DurationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const EXAMPLES: &str = "示例: 90、90s、2m、1h30m、1小时30分钟";
+        match self {
+            DurationError::Empty => write!(f, "时长不能为空（{}）", EXAMPLES),
+            DurationError::UnknownUnit { text, unit } => {
+                write!(f, "无法解析的时长 {}: 未知的单位 \"{}\"（可用 s、m、h、d 或 秒、分钟、小时、天；{}）", text, unit, EXAMPLES)
+            }
+            DurationError::MissingUnit { text, number } => {
+                write!(f, "无法解析的时长 {}: {} 缺少单位（只有单独的数字才按秒计；{}）", text, number, EXAMPLES)
+            }
+            DurationError::MissingNumber { text } => write!(f, "无法解析的时长 {}: 单位前缺少数字（{}）", text, EXAMPLES),
+            DurationError::Zero(text) => write!(f, "时长必须大于0: {}", text),
+            DurationError::TooLarge(text) => write!(f, "时长过长: {}", text),
+        }
+    }
+}
+
+impl std::error::Error for DurationError {}
+
+/// 解析时长
+///
+/// 单独的数字按秒计；带单位时每个数字都必须有单位，数字与单位之间可以有空格
+///
+/// # 参数
+/// * `text` - 时长文本（如 90、90s、2m、1h30m、1小时30分钟）
+pub fn parse(text: &str) -> Result<Duration, DurationError> {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return Err(DurationError::Empty);
+    }
+    let too_large = || DurationError::TooLarge(trimmed.to_string());
+
+    if trimmed.chars().all(|c| c.is_ascii_digit()) {
+        let seconds: u64 = trimmed.parse().map_err(|_| too_large())?;
+        return non_zero(seconds, trimmed);
+    }
+
+    let mut total = 0u64;
+    let mut chars = trimmed.chars().filter(|c| !c.is_whitespace()).peekable();
+    while chars.peek().is_some() {
+        let number: String = std::iter::from_fn(|| chars.next_if(|c| c.is_ascii_digit())).collect();
+        let unit: String = std::iter::from_fn(|| chars.next_if(|c| !c.is_ascii_digit())).collect();
+        if number.is_empty() {
+            return Err(DurationError::MissingNumber { text: trimmed.to_string() });
+        }
+        if unit.is_empty() {
+            return Err(DurationError::MissingUnit { text: trimmed.to_string(), number });
+        }
+
+        let lower = unit.to_lowercase();
+        let scale = UNITS
+            .iter()
+            .find(|(names, _)| names.contains(&lower.as_str()))
+            .map(|&(_, scale)| scale)
+            .ok_or_else(|| DurationError::UnknownUnit { text: trimmed.to_string(), unit })?;
+        let value: u64 = number.parse().map_err(|_| too_large())?;
+        total = value
+            .checked_mul(scale)
+            .and_then(|seconds| total.checked_add(seconds))
+            .ok_or_else(too_large)?;
+    }
+    non_zero(total, trimmed)
+}
+
+/// 大于0的秒数转为时长
+fn non_zero(seconds: u64, text: &str) -> Result<Duration, DurationError> {
+    if seconds == 0 {
+        return Err(DurationError::Zero(text.to_string()));
+    }
+    Ok(Duration::from_secs(seconds))
+}
+
+/// 以解析时接受的写法显示时长（如 45s、2m5s、1h30m、1d2h），不足1秒的部分舍去
+///
+/// # 参数
+/// * `duration` - 时长
+pub fn format(duration: Duration) -> String {
+    let mut remaining = duration.as_secs();
+    if remaining == 0 {
+        return "0s".to_string();
+    }
+
+    let mut text = String::new();
+    for &(names, scale) in UNITS {
+        let value = remaining / scale;
+        if value > 0 {
+            text.push_str(&format!("{}{}", value, names[0]));
+            remaining %= scale;
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse(" 1H 30Min "), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse("1小时30分钟"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse("2天"), Ok(Duration::from_secs(172_800)));
+        assert_eq!(parse("45秒"), Ok(Duration::from_secs(45)));
+
+        assert_eq!(parse(""), Err(DurationError::Empty));
+        assert_eq!(parse("0"), Err(DurationError::Zero("0".to_string())));
+        assert_eq!(parse("0m"), Err(DurationError::Zero("0m".to_string())));
+        assert_eq!(
+            parse("1h30"),
+            Err(DurationError::MissingUnit { text: "1h30".to_string(), number: "30".to_string() })
+        );
+        assert_eq!(parse("2x"), Err(DurationError::UnknownUnit { text: "2x".to_string(), unit: "x".to_string() }));
+        assert_eq!(parse("h30m"), Err(DurationError::MissingNumber { text: "h30m".to_string() }));
+        assert!(matches!(parse("99999999999999999999d"), Err(DurationError::TooLarge(_))));
+        assert!(parse("1.5h").unwrap_err().to_string().contains("未知的单位 \".\""));
+    }
+
+    #[test]
+    fn test_format_round_trips() {
+        assert_eq!(format(Duration::ZERO), "0s");
+        assert_eq!(format(Duration::from_millis(45_900)), "45s");
+        assert_eq!(format(Duration::from_secs(125)), "2m5s");
+        assert_eq!(format(Duration::from_secs(5400)), "1h30m");
+        assert_eq!(format(Duration::from_secs(93_600)), "1d2h");
+        for seconds in [1, 59, 60, 61, 3599, 3600, 3661, 86_400, 90_061] {
+            assert_eq!(parse(&format(Duration::from_secs(seconds))), Ok(Duration::from_secs(seconds)));
+        }
+    }
+}
@@ -0,0 +1,244 @@
+/*!
+ * ClipVanish™ 编辑器集成模块
+ *
+ * 编辑器插件（VS Code、JetBrains等）通过控制接口声明"这次复制来自机密文件"，ClipVanish自动按关键规则保护，
+ * 并在条目被销毁时回报，供编辑器显示行内确认
+ * 特点：
+ * - 插件只发送所复制文本的SHA-256与来源文件名，明文从不经过控制套接字
+ * - 标记在短时间内有效，且只匹配一次；匹配的条目视为关键条目（粘贴前需确认）
+ * - 同一连接上依次推送 `protected`、`destroyed`（或 `superseded`、`expired`）事件
+ *
+ * 协议与参考实现见 docs/editor-protocol.md 与 `clipvanish editor-copy`
+ *
+ * 作者: ClipVanish Team
+ */
+
+#![cfg_attr(not(all(unix, feature = "api")), allow(dead_code))]
+
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use crate::audit::AuditEntry;
+use crate::encoding::encode_hex;
+use crate::item_id::ItemId;
+
+/// 编辑器标记的内容命中的规则名称
+pub const EDITOR_RULE: &str = "editor_secret";
+
+/// 标记的有效期（插件应在标记后立即复制）
+pub const MARK_TTL: Duration = Duration::from_secs(10);
+
+/// 计算内容的SHA-256（十六进制小写）
+///
+/// # 参数
+/// * `content` - 内容
+pub fn sha256_hex(content: &str) -> String {
+    let mut hasher = Sha256::default();
+    hasher.update(content.as_bytes());
+    encode_hex(&hasher.finalize())
+}
+
+/// 是否为64位十六进制SHA-256
+///
+/// # 参数
+/// * `text` - 待检查的文本
+pub fn is_sha256_hex(text: &str) -> bool {
+    text.len() == 64 && text.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// 来源的显示名称（只保留文件名，不记录完整路径）
+///
+/// # 参数
+/// * `source` - 插件提供的来源
+pub fn source_label(source: Option<&str>) -> String {
+    source
+        .and_then(|source| Path::new(source).file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "未知".to_string())
+}
+
+/// 标记的状态
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarkState {
+    /// 等待复制
+    Pending,
+    /// 已匹配到受保护条目
+    Captured(ItemId),
+    /// 已过期或不存在
+    Expired,
+}
+
+/// 单个标记
+#[derive(Debug)]
+struct Mark {
+    /// 内容的SHA-256（十六进制小写）
+    sha256: String,
+    /// 来源文件名
+    source: String,
+    /// 过期时间
+    expires_at: Instant,
+    /// 匹配到的条目
+    item_id: Option<ItemId>,
+}
+
+/// 编辑器标记
+#[derive(Debug, Default)]
+pub struct EditorMarks {
+    /// 尚未取走状态的标记
+    marks: Mutex<Vec<Mark>>,
+    /// 刚匹配、尚未分配条目ID的标记的SHA-256
+    capturing: Mutex<Option<String>>,
+}
+
+impl EditorMarks {
+    /// 登记标记（同一内容的旧标记被替换）
+    ///
+    /// # 参数
+    /// * `sha256` - 内容的SHA-256（十六进制）
+    /// * `source` - 来源文件名
+    /// * `now` - 当前时间
+    pub fn mark(&self, sha256: &str, source: &str, now: Instant) {
+        let sha256 = sha256.to_ascii_lowercase();
+        let mut marks = self.marks.lock().unwrap();
+        marks.retain(|mark| mark.sha256 != sha256 && (mark.item_id.is_some() || mark.expires_at > now));
+        marks.push(Mark { sha256, source: source.to_string(), expires_at: now + MARK_TTL, item_id: None });
+    }
+
+    /// 新复制的内容是否匹配未过期的标记，匹配时返回来源文件名
+    ///
+    /// # 参数
+    /// * `content` - 新复制的内容
+    /// * `now` - 当前时间
+    pub fn take(&self, content: &str, now: Instant) -> Option<String> {
+        let marks = self.marks.lock().unwrap();
+        if !marks.iter().any(|mark| mark.item_id.is_none() && mark.expires_at > now) {
+            return None;
+        }
+        let sha256 = sha256_hex(content);
+        let mark = marks.iter().find(|mark| mark.item_id.is_none() && mark.expires_at > now && mark.sha256 == sha256)?;
+        *self.capturing.lock().unwrap() = Some(sha256);
+        Some(mark.source.clone())
+    }
+
+    /// 记录刚匹配的标记对应的条目
+    ///
+    /// # 参数
+    /// * `item_id` - 受保护条目
+    pub fn captured(&self, item_id: ItemId) {
+        let Some(sha256) = self.capturing.lock().unwrap().take() else {
+            return;
+        };
+        if let Some(mark) = self.marks.lock().unwrap().iter_mut().find(|mark| mark.sha256 == sha256) {
+            mark.item_id = Some(item_id);
+        }
+    }
+
+    /// 查询标记的状态（已匹配或已过期的标记随之移除）
+    ///
+    /// # 参数
+    /// * `sha256` - 内容的SHA-256（十六进制）
+    /// * `now` - 当前时间
+    pub fn state(&self, sha256: &str, now: Instant) -> MarkState {
+        let sha256 = sha256.to_ascii_lowercase();
+        let mut marks = self.marks.lock().unwrap();
+        let Some(index) = marks.iter().position(|mark| mark.sha256 == sha256) else {
+            return MarkState::Expired;
+        };
+        match marks[index].item_id {
+            Some(item_id) => {
+                marks.remove(index);
+                MarkState::Captured(item_id)
+            }
+            None if marks[index].expires_at <= now => {
+                marks.remove(index);
+                MarkState::Expired
+            }
+            None => MarkState::Pending,
+        }
+    }
+}
+
+/// 推送给编辑器的事件
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum EditorEvent {
+    /// 复制的内容已受保护
+    Protected {
+        /// 条目ID
+        item: ItemId,
+    },
+    /// 条目已销毁
+    Destroyed {
+        /// 条目ID
+        item: ItemId,
+        /// 销毁原因
+        reason: String,
+    },
+    /// 条目被新复制的内容取代（由新条目自己的倒计时负责）
+    Superseded {
+        /// 条目ID
+        item: ItemId,
+    },
+    /// 标记过期前没有匹配的复制
+    Expired,
+}
+
+/// 审计事件是否表示条目已销毁（紧急销毁同样经由清除事件记录），是时返回原因
+///
+/// # 参数
+/// * `entry` - 审计记录
+/// * `item_id` - 条目ID
+pub fn destroyed_reason(entry: &AuditEntry, item_id: ItemId) -> Option<String> {
+    if entry.event != "destroyed" {
+        return None;
+    }
+    entry.detail.strip_prefix(&format!("条目 {} 原因=", item_id)).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mark_lifecycle() {
+        let marks = EditorMarks::default();
+        let now = Instant::now();
+        let sha256 = sha256_hex("AKIAEXAMPLE");
+        assert!(is_sha256_hex(&sha256));
+
+        marks.mark(&sha256.to_uppercase(), ".env", now);
+        assert_eq!(marks.take("other content", now), None);
+        assert_eq!(marks.state(&sha256, now), MarkState::Pending);
+
+        assert_eq!(marks.take("AKIAEXAMPLE", now).as_deref(), Some(".env"));
+        let item_id = ItemId::generate();
+        marks.captured(item_id);
+        assert_eq!(marks.take("AKIAEXAMPLE", now), None);
+        assert_eq!(marks.state(&sha256, now), MarkState::Captured(item_id));
+        assert_eq!(marks.state(&sha256, now), MarkState::Expired);
+
+        marks.mark(&sha256, ".env", now);
+        assert_eq!(marks.take("AKIAEXAMPLE", now + MARK_TTL), None);
+        assert_eq!(marks.state(&sha256, now + MARK_TTL), MarkState::Expired);
+        assert_eq!(source_label(Some("/home/me/project/.env")), ".env");
+        assert_eq!(source_label(None), "未知");
+    }
+
+    #[test]
+    fn test_destroyed_reason_and_events() {
+        let item_id = ItemId::generate();
+        let entry = |event: &str, detail: String| AuditEntry { timestamp: chrono::Utc::now(), event: event.to_string(), detail, hash: None };
+
+        assert_eq!(destroyed_reason(&entry("destroyed", format!("条目 {} 原因=倒计时到期", item_id)), item_id).as_deref(), Some("倒计时到期"));
+        assert_eq!(destroyed_reason(&entry("destroyed", format!("条目 {} 原因=倒计时到期", ItemId::generate())), item_id), None);
+        assert_eq!(destroyed_reason(&entry("nuke", format!("条目 {} 原因=紧急销毁", item_id)), item_id), None);
+
+        let event = serde_json::to_value(EditorEvent::Destroyed { item: item_id, reason: "手动清除".to_string() }).unwrap();
+        assert_eq!(event["event"], "destroyed");
+        assert_eq!(event["item"], item_id.to_string());
+        assert_eq!(serde_json::to_value(EditorEvent::Expired).unwrap(), serde_json::json!({ "event": "expired" }));
+    }
+}
@@ -0,0 +1,258 @@
+/*!
+ * ClipVanish™ 权限隔离检测模块
+ *
+ * Windows的UIPI（用户界面特权隔离）会静默丢弃普通权限进程发往管理员权限窗口的模拟输入，
+ * 低级键盘钩子也收不到这些窗口中的按键，粘贴时自动解密与确认后的安全粘贴因此无声失效
+ * 特点：
+ * - 后台线程每秒检查前台窗口，进入或离开管理员权限程序时更新功能状态表，`status` 与 `doctor` 直接展示
+ * - 每个程序每次会话只通知一次，并给出 `start --elevated` 或 clipboard_only 粘贴方式的建议
+ * - `start --elevated` 通过UAC提示以管理员身份重新启动自身
+ * - 仅在Windows上编译，其他平台没有这类隔离
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+use log::warn;
+use crate::audit;
+use crate::capabilities::{self, CapabilityState, Feature};
+use crate::clipboard::ClipboardMonitor;
+use crate::notifications::{self, Severity};
+use crate::output::notice;
+
+/// 前台程序权限变化
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transition {
+    /// 前台切换到管理员权限程序（`first` 表示本次会话中首次遇到该程序）
+    Entered { app: String, first: bool },
+    /// 前台离开管理员权限程序
+    Left,
+    /// 没有变化
+    Unchanged,
+}
+
+/// 前台权限状态跟踪
+#[derive(Debug, Default)]
+pub struct ElevationTracker {
+    /// 当前前台的管理员权限程序
+    current: Option<String>,
+    /// 本次会话中已通知过的程序
+    notified: BTreeSet<String>,
+}
+
+impl ElevationTracker {
+    /// 记录一次检查结果
+    ///
+    /// # 参数
+    /// * `mismatch` - 以更高权限运行的前台程序（没有时为None）
+    pub fn observe(&mut self, mismatch: Option<String>) -> Transition {
+        if mismatch == self.current {
+            return Transition::Unchanged;
+        }
+        self.current = mismatch.clone();
+        match mismatch {
+            Some(app) => {
+                let first = self.notified.insert(app.to_lowercase());
+                Transition::Entered { app, first }
+            }
+            None => Transition::Left,
+        }
+    }
+}
+
+/// 遇到管理员权限程序时的建议
+///
+/// # 参数
+/// * `app` - 程序名称
+pub fn guidance(app: &str) -> String {
+    format!(
+        "使用 clipvanish start --elevated 以管理员身份运行，或在 paste_strategies 中将 {} 设为 clipboard_only 并手动粘贴",
+        app
+    )
+}
+
+/// 启动前台权限检测线程（本进程未提升权限时）
+///
+/// # 参数
+/// * `monitor` - 剪贴板监听器
+pub fn spawn(monitor: Arc<ClipboardMonitor>) {
+    if is_elevated() {
+        return;
+    }
+
+    std::thread::spawn(move || {
+        let mut tracker = ElevationTracker::default();
+        while !monitor.stop_requested() {
+            std::thread::sleep(Duration::from_secs(1));
+            match tracker.observe(foreground_mismatch()) {
+                Transition::Entered { app, first } => {
+                    let reason = format!("前台程序 {} 以管理员权限运行，键盘监听与安全粘贴对其无效", app);
+                    capabilities::degrade(Feature::ElevatedTargets, &reason);
+                    if first {
+                        warn!("{}", reason);
+                        notice!("\n🛡️  {}；{}", reason, guidance(&app));
+                        notifications::notify(Severity::Normal, "ClipVanish无法与管理员程序交互", &guidance(&app));
+                        audit::record("elevation_mismatch", &format!("程序={}", app));
+                    }
+                }
+                Transition::Left => capabilities::record(Feature::ElevatedTargets, CapabilityState::Active, None),
+                Transition::Unchanged => {}
+            }
+        }
+    });
+}
+
+/// 本进程是否以管理员权限运行
+pub fn is_elevated() -> bool {
+    use winapi::um::processthreadsapi::GetCurrentProcess;
+
+    unsafe { token_elevated(GetCurrentProcess()) == Some(true) }
+}
+
+/// 前台程序是否以比本进程更高的权限运行（返回其名称）
+///
+/// 只在本进程未提升权限时检查；无法读取前台进程令牌时视为权限更高
+pub fn foreground_mismatch() -> Option<String> {
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::processthreadsapi::{GetCurrentProcess, OpenProcess};
+    use winapi::um::winnt::PROCESS_QUERY_LIMITED_INFORMATION;
+    use winapi::um::winuser::GetForegroundWindow;
+    use crate::source_app;
+
+    unsafe {
+        if token_elevated(GetCurrentProcess()) != Some(false) {
+            return None;
+        }
+
+        let pid = source_app::window_process(GetForegroundWindow())?;
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        let elevated = if process.is_null() {
+            true
+        } else {
+            let elevated = token_elevated(process);
+            CloseHandle(process);
+            elevated.unwrap_or(true)
+        };
+
+        elevated.then(|| source_app::process_name(pid).unwrap_or_else(|| format!("进程 {}", pid)))
+    }
+}
+
+/// 进程令牌是否已提升权限（无法读取时为None）
+unsafe fn token_elevated(process: winapi::um::winnt::HANDLE) -> Option<bool> {
+    use std::{mem, ptr};
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::processthreadsapi::OpenProcessToken;
+    use winapi::um::securitybaseapi::GetTokenInformation;
+    use winapi::um::winnt::{TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY};
+
+    let mut token = ptr::null_mut();
+    if OpenProcessToken(process, TOKEN_QUERY, &mut token) == 0 {
+        return None;
+    }
+
+    let mut elevation: TOKEN_ELEVATION = mem::zeroed();
+    let mut len = 0u32;
+    let ok = GetTokenInformation(
+        token,
+        TokenElevation,
+        &mut elevation as *mut TOKEN_ELEVATION as *mut _,
+        mem::size_of::<TOKEN_ELEVATION>() as u32,
+        &mut len,
+    );
+    CloseHandle(token);
+    (ok != 0).then_some(elevation.TokenIsElevated != 0)
+}
+
+/// 通过UAC提示以管理员身份重新启动自身（去掉 `--elevated`，其余参数不变）
+///
+/// # 返回值
+/// * `Result<bool, String>` - 已启动新进程时为true（当前进程应退出），已是管理员权限时为false
+pub fn relaunch_elevated() -> Result<bool, String> {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr;
+    use winapi::um::shellapi::ShellExecuteW;
+    use winapi::um::winuser::SW_SHOWNORMAL;
+
+    if is_elevated() {
+        return Ok(false);
+    }
+
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let args = std::env::args()
+        .skip(1)
+        .filter(|arg| arg != "--elevated")
+        .map(|arg| quote_arg(&arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let wide = |text: &OsStr| text.encode_wide().chain(Some(0)).collect::<Vec<u16>>();
+    let (verb, file, params) = (wide(OsStr::new("runas")), wide(exe.as_os_str()), wide(OsStr::new(&args)));
+
+    let result = unsafe {
+        ShellExecuteW(ptr::null_mut(), verb.as_ptr(), file.as_ptr(), params.as_ptr(), ptr::null(), SW_SHOWNORMAL)
+    };
+    // 返回值不大于32表示失败（包括用户取消UAC提示）
+    if result as isize <= 32 {
+        return Err(format!("ShellExecuteW失败（代码 {}），可能已取消UAC提示", result as isize));
+    }
+    Ok(true)
+}
+
+/// 按Windows命令行规则为参数加引号
+///
+/// # 参数
+/// * `arg` - 原始参数
+fn quote_arg(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+        return arg.to_string();
+    }
+
+    // 引号前的反斜杠需要加倍，末尾的反斜杠也要加倍，避免转义结尾的引号
+    let mut quoted = String::from('"');
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        if c == '\\' {
+            backslashes += 1;
+            continue;
+        }
+        let escaped = if c == '"' { backslashes * 2 + 1 } else { backslashes };
+        quoted.push_str(&"\\".repeat(escaped));
+        quoted.push(c);
+        backslashes = 0;
+    }
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker_notifies_once_per_app() {
+        let mut tracker = ElevationTracker::default();
+        assert_eq!(tracker.observe(None), Transition::Unchanged);
+        assert_eq!(
+            tracker.observe(Some("Taskmgr.exe".to_string())),
+            Transition::Entered { app: "Taskmgr.exe".to_string(), first: true }
+        );
+        assert_eq!(tracker.observe(Some("Taskmgr.exe".to_string())), Transition::Unchanged);
+        assert_eq!(tracker.observe(None), Transition::Left);
+        assert_eq!(
+            tracker.observe(Some("taskmgr.exe".to_string())),
+            Transition::Entered { app: "taskmgr.exe".to_string(), first: false }
+        );
+    }
+
+    #[test]
+    fn test_quote_arg() {
+        assert_eq!(quote_arg("--timer"), "--timer");
+        assert_eq!(quote_arg(""), "\"\"");
+        assert_eq!(quote_arg(r"C:\My Files\"), r#""C:\My Files\\""#);
+        assert_eq!(quote_arg(r#"say "hi""#), r#""say \"hi\"""#);
+    }
+}
@@ -0,0 +1,43 @@
+/*!
+ * ClipVanish™ 编码工具模块
+ *
+ * 密钥、指纹、签名与摘要在文件和命令行中的文本表示
+ * 特点：
+ * - 十六进制统一使用小写输出，解码时大小写均可
+ * - 解码失败返回None，由调用方给出具体的错误信息
+ *
+ * 作者: ClipVanish Team
+ */
+
+/// 十六进制编码
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 十六进制解码
+pub fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_roundtrip() {
+        assert_eq!(encode_hex(&[0x00, 0xab, 0xff]), "00abff");
+        assert_eq!(decode_hex("00ABff"), Some(vec![0x00, 0xab, 0xff]));
+        assert_eq!(decode_hex(""), Some(Vec::new()));
+
+        // 奇数长度、非十六进制字符与多字节字符均被拒绝
+        assert_eq!(decode_hex("abc"), None);
+        assert_eq!(decode_hex("zz"), None);
+        assert_eq!(decode_hex("é0"), None);
+    }
+}
@@ -0,0 +1,273 @@
+/*!
+ * ClipVanish™ 熵值检测模块
+ *
+ * 正则表达式无法覆盖随机生成的令牌与密钥，本模块按信息熵识别这类内容
+ * 特点：
+ * - 按滑动窗口计算香农熵，取各窗口最大值
+ * - 识别十六进制与Base64字符集，分别使用不同阈值
+ * - 长度上下限、字母数字混合要求与白名单正则用于控制误报
+ *
+ * 作者: ClipVanish Team
+ */
+
+use log::warn;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// 熵值规则配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntropyRuleConfig {
+    /// 是否启用熵值规则
+    #[serde(default)]
+    pub enabled: bool,
+    /// 参与检测的最短片段长度
+    #[serde(default = "default_min_length")]
+    pub min_length: usize,
+    /// 参与检测的最长片段长度（更长的片段通常是正文或数据）
+    #[serde(default = "default_max_length")]
+    pub max_length: usize,
+    /// 滑动窗口大小
+    #[serde(default = "default_window_size")]
+    pub window_size: usize,
+    /// 十六进制片段的熵阈值（比特/字符，上限4.0）
+    #[serde(default = "default_hex_threshold")]
+    pub hex_threshold: f64,
+    /// Base64片段的熵阈值（比特/字符，上限6.0）
+    #[serde(default = "default_base64_threshold")]
+    pub base64_threshold: f64,
+    /// 其他字符集片段的熵阈值
+    #[serde(default = "default_other_threshold")]
+    pub other_threshold: f64,
+    /// 要求片段同时包含字母与数字
+    #[serde(default = "default_require_mixed")]
+    pub require_letters_and_digits: bool,
+    /// 白名单正则：完整匹配的片段不视为敏感（如UUID、git提交哈希）
+    #[serde(default)]
+    pub allow_patterns: Vec<String>,
+}
+
+fn default_min_length() -> usize {
+    20
+}
+
+fn default_max_length() -> usize {
+    512
+}
+
+fn default_window_size() -> usize {
+    20
+}
+
+fn default_hex_threshold() -> f64 {
+    3.5
+}
+
+fn default_base64_threshold() -> f64 {
+    4.0
+}
+
+fn default_other_threshold() -> f64 {
+    4.2
+}
+
+fn default_require_mixed() -> bool {
+    true
+}
+
+impl Default for EntropyRuleConfig {
+    fn default() -> Self {
+        EntropyRuleConfig {
+            enabled: false,
+            min_length: default_min_length(),
+            max_length: default_max_length(),
+            window_size: default_window_size(),
+            hex_threshold: default_hex_threshold(),
+            base64_threshold: default_base64_threshold(),
+            other_threshold: default_other_threshold(),
+            require_letters_and_digits: default_require_mixed(),
+            allow_patterns: Vec::new(),
+        }
+    }
+}
+
+/// 片段字符集
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Charset {
+    /// 十六进制
+    Hex,
+    /// Base64（含URL安全变体）
+    Base64,
+    /// 其他
+    Other,
+}
+
+impl Charset {
+    /// 检测片段字符集
+    pub fn detect(token: &str) -> Charset {
+        if token.chars().all(|c| c.is_ascii_hexdigit()) {
+            Charset::Hex
+        } else if token.trim_end_matches('=').chars().all(|c| c.is_ascii_alphanumeric() || "+/-_".contains(c)) {
+            Charset::Base64
+        } else {
+            Charset::Other
+        }
+    }
+
+    /// 检测器名称
+    pub fn detector_name(&self) -> &'static str {
+        match self {
+            Charset::Hex => "entropy_hex",
+            Charset::Base64 => "entropy_base64",
+            Charset::Other => "entropy",
+        }
+    }
+}
+
+/// 计算香农熵（比特/字符）
+pub fn shannon_entropy(text: &str) -> f64 {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.is_empty() {
+        return 0.0;
+    }
+
+    let mut counts = std::collections::HashMap::new();
+    for c in &chars {
+        *counts.entry(*c).or_insert(0usize) += 1;
+    }
+
+    let len = chars.len() as f64;
+    counts
+        .values()
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// 熵值检测器
+pub struct EntropyDetector {
+    /// 规则配置
+    config: EntropyRuleConfig,
+    /// 编译后的白名单
+    allow: Vec<Regex>,
+}
+
+impl EntropyDetector {
+    /// 根据配置创建检测器（规则未启用时返回None）
+    pub fn from_config(config: &EntropyRuleConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+
+        let allow = config
+            .allow_patterns
+            .iter()
+            .filter_map(|pattern| match Regex::new(&format!("^(?:{})$", pattern)) {
+                Ok(regex) => Some(regex),
+                Err(e) => {
+                    warn!("熵值规则白名单正则无效: {}: {}", pattern, e);
+                    None
+                }
+            })
+            .collect();
+
+        Some(EntropyDetector { config: config.clone(), allow })
+    }
+
+    /// 检测内容中是否存在高熵片段
+    ///
+    /// # 返回值
+    /// * `Option<Charset>` - 命中片段的字符集
+    pub fn detect(&self, content: &str) -> Option<Charset> {
+        content
+            .split(|c: char| c.is_whitespace() || "\"'`,;()[]{}<>".contains(c))
+            .find_map(|token| self.check_token(token))
+    }
+
+    /// 检查单个片段
+    fn check_token(&self, token: &str) -> Option<Charset> {
+        let len = token.chars().count();
+        if len < self.config.min_length || len > self.config.max_length {
+            return None;
+        }
+
+        if self.config.require_letters_and_digits
+            && !(token.chars().any(|c| c.is_ascii_alphabetic()) && token.chars().any(|c| c.is_ascii_digit()))
+        {
+            return None;
+        }
+
+        if self.allow.iter().any(|regex| regex.is_match(token)) {
+            return None;
+        }
+
+        let charset = Charset::detect(token);
+        let threshold = match charset {
+            Charset::Hex => self.config.hex_threshold,
+            Charset::Base64 => self.config.base64_threshold,
+            Charset::Other => self.config.other_threshold,
+        };
+
+        (max_window_entropy(token, self.config.window_size) >= threshold).then_some(charset)
+    }
+}
+
+/// 各滑动窗口中的最大熵
+fn max_window_entropy(token: &str, window_size: usize) -> f64 {
+    let chars: Vec<char> = token.chars().collect();
+    if window_size == 0 || chars.len() <= window_size {
+        return shannon_entropy(token);
+    }
+
+    chars
+        .windows(window_size)
+        .map(|window| shannon_entropy(&window.iter().collect::<String>()))
+        .fold(0.0, f64::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detector() -> EntropyDetector {
+        EntropyDetector::from_config(&EntropyRuleConfig { enabled: true, ..Default::default() }).unwrap()
+    }
+
+    #[test]
+    fn test_shannon_entropy() {
+        assert_eq!(shannon_entropy(""), 0.0);
+        assert_eq!(shannon_entropy("aaaa"), 0.0);
+        assert!((shannon_entropy("abcd") - 2.0).abs() < 1e-9);
+        assert_eq!(Charset::detect("deadbeef0123"), Charset::Hex);
+        assert_eq!(Charset::detect("aGVsbG8gd29ybGQ="), Charset::Base64);
+        assert_eq!(Charset::detect("a.b!c"), Charset::Other);
+    }
+
+    #[test]
+    fn test_detects_random_tokens() {
+        let detector = detector();
+        assert_eq!(detector.detect("9f86d081884c7d659a2feaa0c55ad015a3bf4f1b"), Some(Charset::Hex));
+        assert_eq!(detector.detect("export TOKEN=\"xK9mQ2vLp7RtZ4wN8bYc3JfH\""), Some(Charset::Base64));
+        // 普通文本、短字符串与低熵重复内容不触发
+        assert_eq!(detector.detect("今天下午三点开会，记得带上笔记本电脑"), None);
+        assert_eq!(detector.detect("abc123"), None);
+        assert_eq!(detector.detect("aaaaaaaaaaaaaaaaaaaaaaaa1111"), None);
+    }
+
+    #[test]
+    fn test_false_positive_controls() {
+        // git提交哈希是高熵的十六进制串，可通过白名单排除
+        let commit = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b";
+        let config = EntropyRuleConfig {
+            enabled: true,
+            allow_patterns: vec!["[0-9a-f]{40}".to_string()],
+            ..Default::default()
+        };
+        assert!(detector().detect(commit).is_some());
+        assert_eq!(EntropyDetector::from_config(&config).unwrap().detect(commit), None);
+
+        // 未启用时不创建检测器
+        assert!(EntropyDetector::from_config(&EntropyRuleConfig::default()).is_none());
+    }
+}
@@ -0,0 +1,258 @@
+/*!
+ * ClipVanish™ 批量擦除调度模块
+ *
+ * 取代每次事件后对栈上临时缓冲区的一次性清理：调用方把真正持有过明文的缓冲区交给调度器，
+ * 由低优先级的后台线程按擦除策略（`security.memory_erase_rounds`/`memory_erase_patterns`）批量覆盖后释放
+ * 特点：
+ * - 只擦除登记的缓冲区，连同已分配但未使用的容量一起覆盖
+ * - 短时间内登记的缓冲区合并为一批处理，不在剪贴板处理路径上执行多轮覆盖
+ * - 紧急销毁、关闭服务与崩溃时立即同步擦除全部待处理的缓冲区
+ * - 后台线程以较低的优先级运行（Linux调低nice值，Windows调低线程优先级）
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::sync::{Condvar, Mutex, MutexGuard, OnceLock};
+use std::time::Duration;
+use log::{debug, warn};
+use zeroize::{Zeroize, Zeroizing};
+use crate::memory;
+
+/// 登记后等待合并的时间
+const COALESCE_DELAY: Duration = Duration::from_millis(200);
+
+/// 待擦除的缓冲区
+pub struct SecretBuffer(Vec<u8>);
+
+impl SecretBuffer {
+    /// 按擦除策略覆盖全部容量后释放，返回擦除的字节数
+    fn erase(mut self) -> usize {
+        let capacity = self.0.capacity();
+        self.0.resize(capacity, 0);
+        memory::erase(&mut self.0);
+        // 再次清零并释放（zeroize同样覆盖全部容量）
+        self.0.zeroize();
+        capacity
+    }
+}
+
+impl From<Vec<u8>> for SecretBuffer {
+    fn from(bytes: Vec<u8>) -> Self {
+        SecretBuffer(bytes)
+    }
+}
+
+impl From<String> for SecretBuffer {
+    fn from(text: String) -> Self {
+        SecretBuffer(text.into_bytes())
+    }
+}
+
+impl From<Zeroizing<String>> for SecretBuffer {
+    fn from(mut text: Zeroizing<String>) -> Self {
+        SecretBuffer(std::mem::take(&mut *text).into_bytes())
+    }
+}
+
+impl From<Zeroizing<Vec<u8>>> for SecretBuffer {
+    fn from(mut bytes: Zeroizing<Vec<u8>>) -> Self {
+        SecretBuffer(std::mem::take(&mut *bytes))
+    }
+}
+
+impl Drop for SecretBuffer {
+    fn drop(&mut self) {
+        // 未经调度器擦除（如进程退出时仍在队列中）也不留下明文
+        self.0.zeroize();
+    }
+}
+
+/// 一批擦除的统计
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EraseStats {
+    /// 缓冲区数量
+    pub buffers: usize,
+    /// 字节数
+    pub bytes: usize,
+}
+
+/// 调度器状态
+#[derive(Default)]
+struct Queue {
+    /// 待擦除的缓冲区
+    pending: Vec<SecretBuffer>,
+    /// 后台线程正在擦除的批次数
+    in_flight: usize,
+    /// 后台线程是否已启动
+    worker_started: bool,
+}
+
+/// 批量擦除调度器
+#[derive(Default)]
+pub struct EraseScheduler {
+    queue: Mutex<Queue>,
+    /// 有新的缓冲区登记
+    retired: Condvar,
+    /// 后台线程完成一批
+    finished: Condvar,
+}
+
+impl EraseScheduler {
+    /// 锁定队列（崩溃清理时锁可能已中毒，仍继续使用）
+    fn lock(&self) -> MutexGuard<'_, Queue> {
+        self.queue.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// 登记待擦除的缓冲区，由后台线程稍后批量擦除
+    ///
+    /// # 参数
+    /// * `buffer` - 曾持有明文的缓冲区
+    pub fn retire(&'static self, buffer: impl Into<SecretBuffer>) {
+        let buffer = buffer.into();
+        let mut queue = self.lock();
+        queue.pending.push(buffer);
+        if !queue.worker_started {
+            queue.worker_started = true;
+            if let Err(e) = std::thread::Builder::new().name("erase-scheduler".to_string()).spawn(move || self.run()) {
+                // 无法启动后台线程时在当前线程擦除
+                warn!("无法启动擦除调度线程: {}", e);
+                queue.worker_started = false;
+                let batch = std::mem::take(&mut queue.pending);
+                drop(queue);
+                Self::erase_batch(batch);
+                return;
+            }
+        }
+        self.retired.notify_one();
+    }
+
+    /// 立即擦除全部待处理的缓冲区，并等待后台线程正在处理的批次完成
+    ///
+    /// 用于紧急销毁、关闭服务与崩溃清理
+    pub fn flush(&self) -> EraseStats {
+        let batch = std::mem::take(&mut self.lock().pending);
+        let stats = Self::erase_batch(batch);
+
+        let mut queue = self.lock();
+        while queue.in_flight > 0 {
+            let (guard, timeout) = self
+                .finished
+                .wait_timeout(queue, Duration::from_secs(1))
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            queue = guard;
+            if timeout.timed_out() {
+                warn!("等待后台擦除超时");
+                break;
+            }
+        }
+        stats
+    }
+
+    /// 待擦除的缓冲区数量
+    #[cfg(test)]
+    pub fn pending(&self) -> usize {
+        self.lock().pending.len()
+    }
+
+    /// 后台线程：等待登记，合并短时间内的登记后批量擦除
+    fn run(&self) {
+        lower_thread_priority();
+        loop {
+            let mut queue = self.lock();
+            while queue.pending.is_empty() {
+                queue = self.retired.wait(queue).unwrap_or_else(|poisoned| poisoned.into_inner());
+            }
+            drop(queue);
+            std::thread::sleep(COALESCE_DELAY);
+
+            let batch = {
+                let mut queue = self.lock();
+                queue.in_flight += 1;
+                std::mem::take(&mut queue.pending)
+            };
+            Self::erase_batch(batch);
+            self.lock().in_flight -= 1;
+            self.finished.notify_all();
+        }
+    }
+
+    /// 擦除一批缓冲区
+    fn erase_batch(batch: Vec<SecretBuffer>) -> EraseStats {
+        let stats = EraseStats {
+            buffers: batch.len(),
+            bytes: batch.into_iter().map(SecretBuffer::erase).sum(),
+        };
+        if stats.buffers > 0 {
+            debug!("已擦除 {} 个缓冲区，共 {} 字节", stats.buffers, stats.bytes);
+        }
+        stats
+    }
+}
+
+/// 进程范围的擦除调度器
+pub fn scheduler() -> &'static EraseScheduler {
+    static SCHEDULER: OnceLock<EraseScheduler> = OnceLock::new();
+    SCHEDULER.get_or_init(EraseScheduler::default)
+}
+
+/// 登记待擦除的缓冲区（见 [`EraseScheduler::retire`]）
+///
+/// # 参数
+/// * `buffer` - 曾持有明文的缓冲区
+pub fn retire(buffer: impl Into<SecretBuffer>) {
+    scheduler().retire(buffer);
+}
+
+/// 立即擦除全部待处理的缓冲区（见 [`EraseScheduler::flush`]）
+pub fn flush() -> EraseStats {
+    scheduler().flush()
+}
+
+/// Linux: 调低当前线程的nice值（Linux上按线程生效）
+#[cfg(target_os = "linux")]
+fn lower_thread_priority() {
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, 10) } != 0 {
+        debug!("调低擦除线程优先级失败: {}", std::io::Error::last_os_error());
+    }
+}
+
+/// Windows: 调低当前线程的优先级
+#[cfg(windows)]
+fn lower_thread_priority() {
+    use winapi::um::processthreadsapi::{GetCurrentThread, SetThreadPriority};
+    use winapi::um::winbase::THREAD_PRIORITY_BELOW_NORMAL;
+
+    if unsafe { SetThreadPriority(GetCurrentThread(), THREAD_PRIORITY_BELOW_NORMAL as i32) } == 0 {
+        debug!("调低擦除线程优先级失败: {}", std::io::Error::last_os_error());
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn lower_thread_priority() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_erase_covers_capacity() {
+        let mut bytes = Vec::with_capacity(64);
+        bytes.extend_from_slice(b"hunter2");
+        assert_eq!(SecretBuffer::from(bytes).erase(), 64);
+
+        let text = Zeroizing::new("correct horse".to_string());
+        assert_eq!(EraseScheduler::erase_batch(vec![SecretBuffer::from(text)]).buffers, 1);
+    }
+
+    #[test]
+    fn test_retire_and_flush() {
+        let scheduler: &'static EraseScheduler = Box::leak(Box::default());
+        scheduler.retire("secret-one".to_string());
+        scheduler.retire(b"secret-two".to_vec());
+        // 合并窗口内仍在队列中，flush立即擦除
+        let stats = scheduler.flush();
+        assert_eq!(scheduler.pending(), 0);
+        assert_eq!(stats.buffers, 2);
+        assert_eq!(scheduler.flush(), EraseStats::default());
+    }
+}
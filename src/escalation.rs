@@ -0,0 +1,214 @@
+/*!
+ * ClipVanish™ 严格程度自动升级模块
+ *
+ * 同一条目被反复延长或长时间固定时逐级收紧，避免工具被习惯性绕过
+ * 特点：
+ * - 延长次数达到阈值后，继续延长需要确认（命令行中提示确认；热键、菜单与控制接口需在有效期内重复一次）
+ * - 之后每次允许的延长时长按比例递减，不低于下限
+ * - 达到上限后再请求延长会立即销毁条目
+ * - 固定计为一次延长，且最长只固定 `max_pin_seconds`
+ * - 升级曲线（各阶段阈值、递减比例与下限）均可配置；按条目计数，新条目从头开始
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use crate::item_id::ItemId;
+
+/// 严格程度升级配置（各阶段阈值为该条目已获准的延长次数，0表示不启用该阶段）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EscalationConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 已延长这么多次后，继续延长需要确认
+    pub confirm_after: u32,
+    /// 已延长这么多次后，每次允许的延长时长开始递减
+    pub shrink_after: u32,
+    /// 递减比例（每多延长一次，允许时长乘以该比例）
+    pub shrink_factor: f64,
+    /// 递减后的最短延长时长（秒）
+    pub min_extension_seconds: u64,
+    /// 已延长这么多次后，再请求延长会立即销毁条目
+    pub destroy_after: u32,
+    /// 固定的最长时长（秒，0表示不限）
+    pub max_pin_seconds: u64,
+    /// 确认有效期（秒）：热键、菜单与控制接口在此时间内重复请求视为确认
+    pub confirm_seconds: u64,
+}
+
+impl Default for EscalationConfig {
+    fn default() -> Self {
+        EscalationConfig {
+            enabled: true,
+            confirm_after: 3,
+            shrink_after: 5,
+            shrink_factor: 0.5,
+            min_extension_seconds: 10,
+            destroy_after: 8,
+            max_pin_seconds: 3600,
+            confirm_seconds: 10,
+        }
+    }
+}
+
+impl EscalationConfig {
+    /// 按升级曲线裁决一次延长请求
+    ///
+    /// # 参数
+    /// * `granted` - 该条目已获准的延长次数
+    /// * `requested` - 请求的延长时长
+    pub fn judge(&self, granted: u32, requested: Duration) -> Verdict {
+        if !self.enabled {
+            return Verdict::Grant(requested);
+        }
+        let reached = |threshold: u32| threshold > 0 && granted >= threshold;
+
+        if reached(self.destroy_after) {
+            return Verdict::Destroy;
+        }
+
+        let allowed = if reached(self.shrink_after) {
+            let steps = (granted - self.shrink_after + 1).min(i32::MAX as u32) as i32;
+            let shrunk = requested.mul_f64(self.shrink_factor.clamp(0.0, 1.0).powi(steps));
+            shrunk.max(Duration::from_secs(self.min_extension_seconds)).min(requested)
+        } else {
+            requested
+        };
+
+        if reached(self.confirm_after) {
+            Verdict::Confirm(allowed)
+        } else {
+            Verdict::Grant(allowed)
+        }
+    }
+
+    /// 固定的最长时长（None表示不限）
+    pub fn max_pin(&self) -> Option<Duration> {
+        (self.enabled && self.max_pin_seconds > 0).then(|| Duration::from_secs(self.max_pin_seconds))
+    }
+}
+
+/// 延长请求的裁决
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verdict {
+    /// 允许延长（时长可能已被缩短）
+    Grant(Duration),
+    /// 需要确认后才允许延长
+    Confirm(Duration),
+    /// 延长次数过多，销毁条目
+    Destroy,
+}
+
+/// 当前条目的延长记录
+#[derive(Debug)]
+struct Record {
+    /// 条目ID
+    item_id: ItemId,
+    /// 已获准的延长次数
+    granted: u32,
+    /// 待确认请求的作废时间
+    confirm_until: Option<Instant>,
+}
+
+/// 条目延长次数跟踪
+#[derive(Debug, Default)]
+pub struct ExtensionTracker {
+    /// 当前条目的记录（条目被替换后从头计数）
+    record: Mutex<Option<Record>>,
+}
+
+impl ExtensionTracker {
+    /// 处理一次延长请求，获准时计入延长次数
+    ///
+    /// 需要确认时登记待确认请求，有效期内对同一条目的再次请求视为确认
+    ///
+    /// # 参数
+    /// * `config` - 升级配置
+    /// * `item_id` - 条目ID
+    /// * `requested` - 请求的延长时长
+    /// * `confirmed` - 调用方是否已经确认（如命令行提示）
+    /// * `now` - 当前时间
+    pub fn request(
+        &self,
+        config: &EscalationConfig,
+        item_id: ItemId,
+        requested: Duration,
+        confirmed: bool,
+        now: Instant,
+    ) -> Verdict {
+        let mut guard = self.record.lock().unwrap();
+        if guard.as_ref().is_none_or(|record| record.item_id != item_id) {
+            *guard = Some(Record { item_id, granted: 0, confirm_until: None });
+        }
+        let record = guard.as_mut().expect("记录已初始化");
+
+        match config.judge(record.granted, requested) {
+            Verdict::Confirm(allowed) if confirmed || record.confirm_until.is_some_and(|until| until > now) => {
+                record.granted += 1;
+                record.confirm_until = None;
+                Verdict::Grant(allowed)
+            }
+            verdict @ Verdict::Confirm(_) => {
+                record.confirm_until = Some(now + Duration::from_secs(config.confirm_seconds));
+                verdict
+            }
+            verdict @ Verdict::Grant(_) => {
+                record.granted += 1;
+                verdict
+            }
+            Verdict::Destroy => {
+                *guard = None;
+                Verdict::Destroy
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_curve_confirms_shrinks_then_destroys() {
+        let config = EscalationConfig::default();
+        let minute = Duration::from_secs(60);
+
+        assert_eq!(config.judge(0, minute), Verdict::Grant(minute));
+        assert_eq!(config.judge(3, minute), Verdict::Confirm(minute));
+        assert_eq!(config.judge(5, minute), Verdict::Confirm(Duration::from_secs(30)));
+        assert_eq!(config.judge(6, minute), Verdict::Confirm(Duration::from_secs(15)));
+        // 不低于下限，也不超过请求的时长
+        assert_eq!(config.judge(7, minute), Verdict::Confirm(Duration::from_secs(10)));
+        assert_eq!(config.judge(7, Duration::from_secs(5)), Verdict::Confirm(Duration::from_secs(5)));
+        assert_eq!(config.judge(8, minute), Verdict::Destroy);
+
+        let disabled = EscalationConfig { enabled: false, ..EscalationConfig::default() };
+        assert_eq!(disabled.judge(100, minute), Verdict::Grant(minute));
+        assert_eq!(disabled.max_pin(), None);
+    }
+
+    #[test]
+    fn test_tracker_counts_per_item_and_repeat_confirms() {
+        let config = EscalationConfig { confirm_after: 1, destroy_after: 3, ..EscalationConfig::default() };
+        let tracker = ExtensionTracker::default();
+        let minute = Duration::from_secs(60);
+        let now = Instant::now();
+        let (item, other) = (ItemId::generate(), ItemId::generate());
+
+        assert_eq!(tracker.request(&config, item, minute, false, now), Verdict::Grant(minute));
+        assert_eq!(tracker.request(&config, item, minute, false, now), Verdict::Confirm(minute));
+        // 有效期内重复请求视为确认；超时后需要重新确认
+        assert_eq!(tracker.request(&config, item, minute, false, now), Verdict::Grant(minute));
+        assert_eq!(tracker.request(&config, item, minute, false, now), Verdict::Confirm(minute));
+        let later = now + Duration::from_secs(config.confirm_seconds + 1);
+        assert_eq!(tracker.request(&config, item, minute, false, later), Verdict::Confirm(minute));
+        assert_eq!(tracker.request(&config, item, minute, true, later), Verdict::Grant(minute));
+        assert_eq!(tracker.request(&config, item, minute, true, later), Verdict::Destroy);
+
+        // 新条目从头计数
+        assert_eq!(tracker.request(&config, other, minute, false, later), Verdict::Grant(minute));
+    }
+}
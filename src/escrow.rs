@@ -0,0 +1,270 @@
+/*!
+ * ClipVanish™ 密钥托管模块
+ *
+ * 企业托管部署下，每个会话密钥都额外封装给管理员的恢复公钥，
+ * 持有恢复私钥的管理员可以解密取证归档中的密文用于事件调查
+ * 特点：
+ * - 封装方式：X25519临时密钥协商 + HKDF-SHA256 + AES-256-GCM-SIV（类似sealed box）
+ * - 客户端只保存恢复公钥，无法自行解开封装
+ * - 封装结果随取证归档一起导出，从不单独落盘
+ * - 未配置恢复公钥时完全不生效
+ *
+ * 注意：启用托管意味着管理员可以恢复受保护内容，`status` 会醒目提示
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+use aes_gcm_siv::aead::OsRng;
+use x25519_dalek::{EphemeralSecret, PublicKey, StaticSecret};
+use crate::crypto::{self, CryptoEngine, EncryptedData, SecureKey};
+use crate::encoding::{decode_hex, encode_hex};
+use crate::platform;
+
+/// 密钥托管错误类型
+#[derive(Debug)]
+pub enum EscrowError {
+    /// 密钥格式错误
+    InvalidKey(String),
+    /// 封装或解封失败
+    CryptoError(String),
+    /// 封装不属于该私钥
+    RecipientMismatch,
+    /// 文件访问失败
+    IoError(std::io::Error),
+}
+
+impl std::fmt::Display for EscrowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EscrowError::InvalidKey(msg) => write!(f, "托管密钥格式错误: {}", msg),
+            EscrowError::CryptoError(msg) => write!(f, "托管封装失败: {}", msg),
+            EscrowError::RecipientMismatch => write!(f, "该封装不属于此恢复私钥"),
+            EscrowError::IoError(e) => write!(f, "托管密钥文件访问失败: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for EscrowError {}
+
+/// 密钥托管配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EscrowConfig {
+    /// 管理员恢复公钥（64位十六进制），为空时不启用托管
+    #[serde(default)]
+    pub recovery_public_key: Option<String>,
+}
+
+impl EscrowConfig {
+    /// 解析托管接收方
+    ///
+    /// # 返回值
+    /// * `Result<Option<EscrowRecipient>, EscrowError>` - 未启用时返回None
+    pub fn recipient(&self) -> Result<Option<EscrowRecipient>, EscrowError> {
+        match self.recovery_public_key.as_deref().map(str::trim) {
+            Some(key) if !key.is_empty() => EscrowRecipient::from_hex(key).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// 托管接收方（管理员恢复公钥）
+#[derive(Debug, Clone, PartialEq)]
+pub struct EscrowRecipient {
+    /// X25519公钥
+    public_key: [u8; 32],
+}
+
+impl EscrowRecipient {
+    /// 从十六进制公钥创建
+    pub fn from_hex(text: &str) -> Result<Self, EscrowError> {
+        Ok(EscrowRecipient { public_key: parse_key(text)? })
+    }
+
+    /// 公钥的十六进制表示（写入配置）
+    pub fn to_hex(&self) -> String {
+        encode_hex(&self.public_key)
+    }
+
+    /// 公钥指纹（公钥前8字节的十六进制）
+    pub fn fingerprint(&self) -> String {
+        encode_hex(&self.public_key[..8])
+    }
+
+    /// 将会话密钥封装给恢复公钥
+    ///
+    /// 每次封装使用新的临时密钥对，只有恢复私钥能推导出相同的封装密钥
+    pub fn wrap(&self, key: &SecureKey) -> Result<WrappedKey, EscrowError> {
+        let (ephemeral_public, wrapped) = seal(&self.public_key, key.as_bytes(), ESCROW_LABEL)?;
+
+        Ok(WrappedKey {
+            recipient: self.fingerprint(),
+            ephemeral_public_key: encode_hex(&ephemeral_public),
+            wrapped_key: wrapped.to_base64(),
+        })
+    }
+}
+
+/// 封装后的会话密钥
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WrappedKey {
+    /// 恢复公钥指纹
+    pub recipient: String,
+    /// 临时公钥（十六进制）
+    pub ephemeral_public_key: String,
+    /// 封装后的会话密钥（Base64，含nonce）
+    pub wrapped_key: String,
+}
+
+/// 管理员恢复私钥（只应保存在管理员的离线设备上）
+pub struct EscrowPrivateKey {
+    /// X25519私钥
+    secret: StaticSecret,
+}
+
+impl EscrowPrivateKey {
+    /// 生成新的恢复密钥对
+    pub fn generate() -> Self {
+        EscrowPrivateKey { secret: StaticSecret::random_from_rng(OsRng) }
+    }
+
+    /// 从十六进制私钥文件加载
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, EscrowError> {
+        let content = Zeroizing::new(fs::read_to_string(path).map_err(EscrowError::IoError)?);
+        let secret = Zeroizing::new(parse_key(&content)?);
+        Ok(EscrowPrivateKey { secret: StaticSecret::from(*secret) })
+    }
+
+    /// 保存到新文件（仅当前用户可读写，不覆盖已有文件）
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), EscrowError> {
+        let content = Zeroizing::new(encode_hex(self.secret.as_bytes()));
+        platform::create_private(path, content.as_bytes()).map_err(EscrowError::IoError)
+    }
+
+    /// 对应的恢复公钥
+    pub fn recipient(&self) -> EscrowRecipient {
+        EscrowRecipient { public_key: PublicKey::from(&self.secret).to_bytes() }
+    }
+
+    /// 解开封装，恢复会话密钥
+    pub fn unwrap_key(&self, wrapped: &WrappedKey) -> Result<SecureKey, EscrowError> {
+        let recipient = self.recipient();
+        if wrapped.recipient != recipient.fingerprint() {
+            return Err(EscrowError::RecipientMismatch);
+        }
+
+        let ephemeral_public = parse_key(&wrapped.ephemeral_public_key)?;
+        let data = EncryptedData::from_base64(&wrapped.wrapped_key)
+            .map_err(|e| EscrowError::CryptoError(e.to_string()))?;
+        let key_bytes = Zeroizing::new(open(self.secret.as_bytes(), &ephemeral_public, &data, ESCROW_LABEL)?);
+        let key_data: [u8; 32] = key_bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| EscrowError::CryptoError("会话密钥长度错误".to_string()))?;
+
+        Ok(SecureKey::from_bytes(key_data))
+    }
+}
+
+/// 会话密钥托管使用的密钥推导标签
+const ESCROW_LABEL: &[u8] = b"clipvanish-escrow-kek";
+
+/// 使用临时X25519密钥对将数据加密给接收方公钥（类似sealed box）
+///
+/// # 参数
+/// * `recipient_public` - 接收方X25519公钥
+/// * `plaintext` - 待加密数据
+/// * `label` - 密钥推导标签，不同用途使用不同标签，密文不能跨用途解开
+///
+/// # 返回值
+/// * `Result<([u8; 32], EncryptedData), EscrowError>` - 临时公钥与密文
+pub fn seal(
+    recipient_public: &[u8; 32],
+    plaintext: &[u8],
+    label: &[u8],
+) -> Result<([u8; 32], EncryptedData), EscrowError> {
+    let ephemeral_secret = EphemeralSecret::random_from_rng(OsRng);
+    let ephemeral_public = PublicKey::from(&ephemeral_secret).to_bytes();
+    let shared = ephemeral_secret.diffie_hellman(&PublicKey::from(*recipient_public));
+
+    let kek = derive_wrapping_key(shared.as_bytes(), &ephemeral_public, recipient_public, label)?;
+    let sealed = CryptoEngine::from_key(kek)
+        .and_then(|engine| engine.encrypt(plaintext))
+        .map_err(|e| EscrowError::CryptoError(e.to_string()))?;
+    Ok((ephemeral_public, sealed))
+}
+
+/// 使用接收方私钥解开 [`seal`] 的结果
+///
+/// # 参数
+/// * `secret` - 接收方X25519私钥
+/// * `ephemeral_public` - 封装时的临时公钥
+/// * `data` - 密文
+/// * `label` - 封装时使用的密钥推导标签
+pub fn open(
+    secret: &[u8; 32],
+    ephemeral_public: &[u8; 32],
+    data: &EncryptedData,
+    label: &[u8],
+) -> Result<Vec<u8>, EscrowError> {
+    let secret = StaticSecret::from(*secret);
+    let recipient_public = PublicKey::from(&secret).to_bytes();
+    let shared = secret.diffie_hellman(&PublicKey::from(*ephemeral_public));
+    let kek = derive_wrapping_key(shared.as_bytes(), ephemeral_public, &recipient_public, label)?;
+
+    CryptoEngine::from_key(kek)
+        .and_then(|engine| engine.decrypt(data))
+        .map_err(|_| EscrowError::RecipientMismatch)
+}
+
+/// 解析32字节十六进制密钥
+pub fn parse_key(text: &str) -> Result<[u8; 32], EscrowError> {
+    decode_hex(text.trim())
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| EscrowError::InvalidKey("需要64位十六进制字符".to_string()))
+}
+
+/// 由共享秘密以HKDF-SHA256推导封装密钥（用途标签做域分离，并绑定临时公钥与恢复公钥）
+fn derive_wrapping_key(
+    shared: &[u8; 32],
+    ephemeral_public: &[u8; 32],
+    recipient_public: &[u8; 32],
+    label: &[u8],
+) -> Result<SecureKey, EscrowError> {
+    // 小阶点会得到全零共享秘密
+    if shared.iter().all(|&b| b == 0) {
+        return Err(EscrowError::InvalidKey("无效的公钥".to_string()));
+    }
+
+    let mut info = Vec::with_capacity(label.len() + 64);
+    info.extend_from_slice(label);
+    info.extend_from_slice(ephemeral_public);
+    info.extend_from_slice(recipient_public);
+    Ok(crypto::hkdf_sha256(&[], shared, &info))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_and_unwrap() {
+        let admin = EscrowPrivateKey::generate();
+        let config = EscrowConfig { recovery_public_key: Some(admin.recipient().to_hex()) };
+        let recipient = config.recipient().unwrap().unwrap();
+
+        let session_key = SecureKey::generate().unwrap();
+        let wrapped = recipient.wrap(&session_key).unwrap();
+        assert_eq!(admin.unwrap_key(&wrapped).unwrap().as_bytes(), session_key.as_bytes());
+
+        // 其他私钥无法解开
+        let other = EscrowPrivateKey::generate();
+        assert!(matches!(other.unwrap_key(&wrapped), Err(EscrowError::RecipientMismatch)));
+
+        assert!(EscrowConfig::default().recipient().unwrap().is_none());
+        assert!(EscrowConfig { recovery_public_key: Some("zz".to_string()) }.recipient().is_err());
+    }
+}
@@ -0,0 +1,324 @@
+/*!
+ * ClipVanish™ 事件取证导出模块
+ *
+ * 为安全团队导出事后调查所需的材料：当前密文条目、审计日志与密钥指纹
+ * 特点：
+ * - 从不包含密钥或明文，只包含密文、数据类别与时间
+ * - 启用密钥托管时附带封装给恢复公钥的会话密钥，仅管理员可解开
+ * - 输出标准tar归档（manifest.json、audit.log、signature.json）
+ * - 归档以本机取证私钥做Ed25519签名，安全团队只需公钥（--public-key 导出）即可用 --verify 校验
+ *
+ * 注意：公钥应在事件发生前登记给安全团队，校验时指定登记的公钥；归档自带的公钥只说明由哪把密钥签名，
+ * 不能证明来源（Windows上私钥文件经DPAPI绑定到当前用户）
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use aes_gcm_siv::aead::OsRng;
+use ed25519_dalek::{Signature as Ed25519Signature, Signer, SigningKey, VerifyingKey, SIGNATURE_LENGTH};
+use zeroize::Zeroizing;
+use crate::build_info::BuildInfo;
+use crate::config::Config;
+use crate::encoding::{decode_hex, encode_hex};
+use crate::escrow::WrappedKey;
+use crate::item_id::ItemId;
+use crate::platform;
+use crate::report::{self, TarWriter};
+
+/// 签名算法名称
+const SIGNATURE_ALGORITHM: &str = "Ed25519";
+
+/// 归档内文件名
+const MANIFEST_FILE: &str = "manifest.json";
+const AUDIT_FILE: &str = "audit.log";
+const SIGNATURE_FILE: &str = "signature.json";
+
+/// 取证导出错误类型
+#[derive(Debug)]
+pub enum ForensicsError {
+    /// 文件访问失败
+    IoError(std::io::Error),
+    /// 清单或签名格式错误
+    FormatError(String),
+    /// 签名不匹配（归档被修改或不是由指定公钥签名）
+    SignatureMismatch,
+    /// 无法确定文件位置
+    LocationError(String),
+}
+
+impl std::fmt::Display for ForensicsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ForensicsError::IoError(e) => write!(f, "取证文件访问失败: {}", e),
+            ForensicsError::FormatError(msg) => write!(f, "取证归档格式错误: {}", msg),
+            ForensicsError::SignatureMismatch => write!(f, "签名不匹配，归档可能已被修改或不是由指定公钥签名"),
+            ForensicsError::LocationError(msg) => write!(f, "无法确定取证密钥位置: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ForensicsError {}
+
+/// 单个密文条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForensicItem {
+    /// 条目ID（撤销暂存区的内容没有ID）
+    pub item_id: Option<ItemId>,
+    /// 条目来源：current（当前受保护内容）或 undo（撤销暂存区）
+    pub slot: String,
+    /// 密文（Base64，含nonce）
+    pub ciphertext: String,
+    /// 加密所用密钥的指纹
+    pub key_fingerprint: String,
+    /// 封装给恢复公钥的会话密钥（仅启用密钥托管时存在）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub escrow: Option<WrappedKey>,
+}
+
+/// 归档清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    /// 导出时间（UTC）
+    pub created_at: DateTime<Utc>,
+    /// 导出程序版本
+    pub build: String,
+    /// 操作系统
+    pub os: String,
+    /// 密文条目
+    pub items: Vec<ForensicItem>,
+    /// 审计日志行数
+    pub audit_entries: usize,
+}
+
+/// 归档签名
+#[derive(Debug, Serialize, Deserialize)]
+struct Signature {
+    /// 算法
+    algorithm: String,
+    /// 签名公钥（十六进制）
+    public_key: String,
+    /// 签名（十六进制）
+    signature: String,
+}
+
+/// 本机取证签名密钥
+pub struct ForensicsKey {
+    /// 签名私钥
+    key: SigningKey,
+}
+
+impl ForensicsKey {
+    /// 默认密钥文件路径
+    pub fn default_path() -> Result<PathBuf, ForensicsError> {
+        Config::get_config_directory()
+            .map(|dir| dir.join("forensics.key"))
+            .map_err(|e| ForensicsError::LocationError(e.to_string()))
+    }
+
+    /// 加载取证签名密钥，不存在时生成并保存（仅当前用户可读写）
+    pub fn load_or_create<P: AsRef<Path>>(path: P) -> Result<Self, ForensicsError> {
+        let path = path.as_ref();
+        // 无法读取或解开包装的密钥文件不覆盖，避免已登记的公钥失效
+        match platform::read_state_to_string(path) {
+            Ok(content) => {
+                let content = Zeroizing::new(content);
+                let seed: Zeroizing<[u8; 32]> = decode_hex(content.trim())
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .map(Zeroizing::new)
+                    .ok_or_else(|| ForensicsError::FormatError("取证密钥文件格式错误".to_string()))?;
+                return Ok(ForensicsKey { key: SigningKey::from_bytes(&seed) });
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(ForensicsError::IoError(e)),
+        }
+
+        let key = SigningKey::generate(&mut OsRng);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(ForensicsError::IoError)?;
+        }
+        let content = Zeroizing::new(encode_hex(&key.to_bytes()));
+        platform::write_state(path, content.as_bytes()).map_err(ForensicsError::IoError)?;
+
+        Ok(ForensicsKey { key })
+    }
+
+    /// 签名公钥
+    pub fn public_key(&self) -> [u8; 32] {
+        self.key.verifying_key().to_bytes()
+    }
+}
+
+/// 创建清单
+///
+/// # 参数
+/// * `items` - 密文条目
+/// * `audit_log` - 审计日志原文
+pub fn build_manifest(items: Vec<ForensicItem>, audit_log: &str) -> Manifest {
+    Manifest {
+        created_at: Utc::now(),
+        build: BuildInfo::current().to_string(),
+        os: std::env::consts::OS.to_string(),
+        items,
+        audit_entries: audit_log.lines().filter(|line| !line.trim().is_empty()).count(),
+    }
+}
+
+/// 写出取证归档
+///
+/// # 参数
+/// * `path` - 输出文件路径
+/// * `manifest` - 归档清单
+/// * `audit_log` - 审计日志原文
+/// * `key` - 取证签名密钥
+pub fn write_archive<P: AsRef<Path>>(
+    path: P,
+    manifest: &Manifest,
+    audit_log: &str,
+    key: &ForensicsKey,
+) -> Result<(), ForensicsError> {
+    let manifest_json = serde_json::to_string_pretty(manifest)
+        .map_err(|e| ForensicsError::FormatError(e.to_string()))?;
+
+    let files = [(MANIFEST_FILE, manifest_json.as_bytes()), (AUDIT_FILE, audit_log.as_bytes())];
+    let signature = Signature {
+        algorithm: SIGNATURE_ALGORITHM.to_string(),
+        public_key: encode_hex(&key.public_key()),
+        signature: encode_hex(&key.key.sign(&signed_payload(&files)).to_bytes()),
+    };
+    let signature_json = serde_json::to_string_pretty(&signature)
+        .map_err(|e| ForensicsError::FormatError(e.to_string()))?;
+
+    let mut tar = TarWriter::new();
+    for (name, data) in files {
+        tar.append_file(name, data);
+    }
+    tar.append_file(SIGNATURE_FILE, signature_json.as_bytes());
+
+    platform::write_private(path, &tar.finish()).map_err(ForensicsError::IoError)
+}
+
+/// 校验取证归档
+///
+/// # 参数
+/// * `path` - 归档文件路径
+/// * `public_key` - 登记的签名公钥，归档必须由它签名
+///
+/// # 返回值
+/// * `Result<Manifest, ForensicsError>` - 校验通过时返回清单
+pub fn verify_archive<P: AsRef<Path>>(path: P, public_key: &[u8; 32]) -> Result<Manifest, ForensicsError> {
+    let entries = read_entries(path)?;
+    let manifest_data = find_entry(&entries, MANIFEST_FILE)?;
+    let audit_data = find_entry(&entries, AUDIT_FILE)?;
+    let signature: Signature = serde_json::from_slice(find_entry(&entries, SIGNATURE_FILE)?)
+        .map_err(|e| ForensicsError::FormatError(e.to_string()))?;
+
+    if signature.algorithm != SIGNATURE_ALGORITHM || decode_hex(&signature.public_key).as_deref() != Some(&public_key[..]) {
+        return Err(ForensicsError::SignatureMismatch);
+    }
+
+    let signature_bytes: [u8; SIGNATURE_LENGTH] = decode_hex(&signature.signature)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(ForensicsError::SignatureMismatch)?;
+    let payload = signed_payload(&[(MANIFEST_FILE, manifest_data), (AUDIT_FILE, audit_data)]);
+    VerifyingKey::from_bytes(public_key)
+        .and_then(|key| key.verify_strict(&payload, &Ed25519Signature::from_bytes(&signature_bytes)))
+        .map_err(|_| ForensicsError::SignatureMismatch)?;
+
+    serde_json::from_slice(manifest_data).map_err(|e| ForensicsError::FormatError(e.to_string()))
+}
+
+/// 读取归档清单（不校验签名，用于在管理员设备上做托管恢复）
+pub fn read_manifest<P: AsRef<Path>>(path: P) -> Result<Manifest, ForensicsError> {
+    let entries = read_entries(path)?;
+    serde_json::from_slice(find_entry(&entries, MANIFEST_FILE)?)
+        .map_err(|e| ForensicsError::FormatError(e.to_string()))
+}
+
+/// 读取tar归档中的全部文件
+fn read_entries<P: AsRef<Path>>(path: P) -> Result<Vec<(String, Vec<u8>)>, ForensicsError> {
+    let data = fs::read(path).map_err(ForensicsError::IoError)?;
+    report::read_tar(&data).ok_or_else(|| ForensicsError::FormatError("无法解析tar归档".to_string()))
+}
+
+/// 按名称查找归档中的文件
+fn find_entry<'a>(entries: &'a [(String, Vec<u8>)], name: &str) -> Result<&'a [u8], ForensicsError> {
+    entries
+        .iter()
+        .find(|(entry, _)| entry == name)
+        .map(|(_, content)| content.as_slice())
+        .ok_or_else(|| ForensicsError::FormatError(format!("缺少 {}", name)))
+}
+
+/// 生成默认的归档文件名
+pub fn default_file_name() -> String {
+    format!("clipvanish-forensics-{}.tar", chrono::Local::now().format("%Y%m%d-%H%M%S"))
+}
+
+/// 被签名的数据：依次为每个文件的名称、长度与内容
+fn signed_payload(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut payload = Vec::new();
+    for (name, data) in files {
+        payload.extend_from_slice(name.as_bytes());
+        payload.push(0);
+        payload.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        payload.extend_from_slice(data);
+    }
+    payload
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_archive_roundtrip() {
+        let temp_dir = tempdir().unwrap();
+        let key = ForensicsKey::load_or_create(temp_dir.path().join("forensics.key")).unwrap();
+        let audit_log = "{\"event\":\"nuke\"}\n";
+        let item_id = ItemId::generate();
+        let item = ForensicItem {
+            item_id: Some(item_id),
+            slot: "current".to_string(),
+            ciphertext: "AAAA".to_string(),
+            key_fingerprint: "0011223344556677".to_string(),
+            escrow: None,
+        };
+
+        let path = temp_dir.path().join("out.tar");
+        write_archive(&path, &build_manifest(vec![item], audit_log), audit_log, &key).unwrap();
+
+        let manifest = verify_archive(&path, &key.public_key()).unwrap();
+        assert_eq!(manifest.items[0].item_id, Some(item_id));
+        assert_eq!(manifest.audit_entries, 1);
+
+        // 重新加载的密钥相同
+        let reloaded = ForensicsKey::load_or_create(temp_dir.path().join("forensics.key")).unwrap();
+        assert_eq!(reloaded.public_key(), key.public_key());
+    }
+
+    #[test]
+    fn test_tampered_archive_rejected() {
+        let temp_dir = tempdir().unwrap();
+        let key = ForensicsKey::load_or_create(temp_dir.path().join("forensics.key")).unwrap();
+        let path = temp_dir.path().join("out.tar");
+        write_archive(&path, &build_manifest(Vec::new(), "line\n"), "line\n", &key).unwrap();
+
+        // 修改审计日志内容
+        let mut data = fs::read(&path).unwrap();
+        let pos = data.windows(4).position(|w| w == b"line").unwrap();
+        data[pos] = b'L';
+        fs::write(&path, data).unwrap();
+        assert!(matches!(verify_archive(&path, &key.public_key()), Err(ForensicsError::SignatureMismatch)));
+
+        // 用其他密钥重新签名的归档不能通过登记公钥的校验
+        let other = ForensicsKey::load_or_create(temp_dir.path().join("other.key")).unwrap();
+        write_archive(&path, &build_manifest(Vec::new(), ""), "", &other).unwrap();
+        assert!(verify_archive(&path, &other.public_key()).is_ok());
+        assert!(matches!(verify_archive(&path, &key.public_key()), Err(ForensicsError::SignatureMismatch)));
+    }
+}
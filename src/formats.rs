@@ -0,0 +1,357 @@
+/*!
+ * ClipVanish™ 持久化格式模块
+ *
+ * 记录ClipVanish写入磁盘的全部数据格式（审计日志、历史记录元数据、加密笔记、诱饵令牌、备份文件），
+ * 供 `clipvanish formats dump-schema` 输出机器可读的JSON Schema
+ * 特点：
+ * - 每种格式带修订号与修订说明，格式变化时在此登记新修订
+ * - 每个修订在 tests/fixtures/formats/ 下保留一份样本，兼容性测试用当前代码逐一加载，
+ *   保证升级后旧版本写入的数据（包括加密笔记）仍可读取
+ * - 只描述结构，不包含任何用户数据
+ *
+ * 作者: ClipVanish Team
+ */
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// 格式修订
+#[derive(Debug, Clone, Serialize)]
+pub struct Revision {
+    /// 修订号（从1开始）
+    pub revision: u32,
+    /// 该修订的变化
+    pub changes: &'static str,
+}
+
+/// 持久化格式
+#[derive(Debug, Clone)]
+pub struct Format {
+    /// 格式名称（`dump-schema` 的参数）
+    pub name: &'static str,
+    /// 文件名（配置目录下；备份文件由用户指定）
+    pub file: &'static str,
+    /// 说明
+    pub description: &'static str,
+    /// 全部修订（最后一个为当前写入的修订）
+    pub revisions: &'static [Revision],
+    /// 当前修订的JSON Schema
+    schema: fn() -> Value,
+}
+
+impl Format {
+    /// 当前写入的修订号
+    pub fn current_revision(&self) -> u32 {
+        self.revisions.last().map_or(1, |revision| revision.revision)
+    }
+
+    /// 完整的JSON Schema文档（含格式元数据与修订历史）
+    pub fn schema(&self) -> Value {
+        let mut schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": format!("https://clipvanish.dev/schemas/{}-{}.json", self.name, self.current_revision()),
+            "title": self.file,
+            "description": self.description,
+            "x-clipvanish-format": self.name,
+            "x-clipvanish-revision": self.current_revision(),
+            "x-clipvanish-revisions": self.revisions,
+        });
+        if let (Some(document), Value::Object(body)) = (schema.as_object_mut(), (self.schema)()) {
+            document.extend(body);
+        }
+        schema
+    }
+}
+
+/// 全部持久化格式
+pub const FORMATS: &[Format] = &[
+    Format {
+        name: "audit",
+        file: "audit.log",
+        description: "审计日志：JSON Lines，每行一条记录，只追加；记录中不含剪贴板明文",
+        revisions: &[
+            Revision { revision: 1, changes: "初始格式：timestamp、event、detail" },
+            Revision {
+                revision: 2,
+                changes: "新增hash：SHA-256(上一条链值 + \"\\n\" + 按键名排序的紧凑JSON {detail,event,timestamp})，第一条以64个0为上一条链值；旧记录没有hash，只能出现在链之前",
+            },
+        ],
+        schema: audit_schema,
+    },
+    Format {
+        name: "history",
+        file: "history.json",
+        description: "历史记录元数据：JSON数组，只记录长度、类型与操作，不含明文",
+        revisions: &[
+            Revision { revision: 1, changes: "初始格式：recorded_at、length、content_type、operation" },
+            Revision { revision: 2, changes: "新增可选的item_id（ULID），关联同一条目的复制、粘贴与清除" },
+        ],
+        schema: history_schema,
+    },
+    Format {
+        name: "notes",
+        file: "notes.json",
+        description: "一次性加密笔记：每条笔记使用独立的随机密钥，密钥只在读取凭据中，不写入磁盘",
+        revisions: &[Revision {
+            revision: 1,
+            changes: "初始格式：ciphertext为Base64(12字节nonce + AES-256-GCM-SIV密文)，无附加数据",
+        }],
+        schema: notes_schema,
+    },
+    Format {
+        name: "canaries",
+        file: "canaries.json",
+        description: "诱饵令牌记录：发出过的令牌，按生成时间排序，最后一个可能仍在使用",
+        revisions: &[
+            Revision { revision: 1, changes: "初始格式：token、created_at" },
+            Revision { revision: 2, changes: "新增可选的retired_at、placed_at与decoy（缺省为false）" },
+        ],
+        schema: canaries_schema,
+    },
+    Format {
+        name: "backup",
+        file: "<backup create 指定的文件>",
+        description: "用户数据备份：只有密钥派生参数是明文，其余内容为口令加密的tar归档（manifest.json + 配置目录中的文件）",
+        revisions: &[Revision {
+            revision: 1,
+            changes: "初始格式：PBKDF2-HMAC-SHA256派生密钥，ciphertext为Base64(12字节nonce + AES-256-GCM-SIV密文)，无附加数据",
+        }],
+        schema: backup_schema,
+    },
+];
+
+/// 按名称查找格式
+///
+/// # 参数
+/// * `name` - 格式名称
+pub fn find(name: &str) -> Option<&'static Format> {
+    FORMATS.iter().find(|format| format.name == name)
+}
+
+/// 输出JSON Schema：指定名称时为单个文档，否则为以格式名称为键的对象
+///
+/// # 参数
+/// * `name` - 格式名称（None表示全部）
+pub fn dump_schema(name: Option<&str>) -> Result<Value, String> {
+    match name {
+        Some(name) => find(name).map(Format::schema).ok_or_else(|| {
+            let names = FORMATS.iter().map(|format| format.name).collect::<Vec<_>>().join(", ");
+            format!("未知的格式: {}（可选: {}）", name, names)
+        }),
+        None => Ok(Value::Object(
+            FORMATS.iter().map(|format| (format.name.to_string(), format.schema())).collect(),
+        )),
+    }
+}
+
+/// RFC 3339 UTC时间
+fn timestamp() -> Value {
+    json!({ "type": "string", "format": "date-time" })
+}
+
+/// 可为null的RFC 3339时间
+fn optional_timestamp() -> Value {
+    json!({ "type": ["string", "null"], "format": "date-time" })
+}
+
+/// 条目ID（ULID，Crockford Base32）
+fn item_id() -> Value {
+    json!({ "type": "string", "pattern": "^[0-7][0-9A-HJKMNP-TV-Z]{25}$" })
+}
+
+/// audit.log中每一行的结构
+fn audit_schema() -> Value {
+    json!({
+        "x-clipvanish-encoding": "jsonl",
+        "type": "object",
+        "required": ["timestamp", "event", "detail"],
+        "properties": {
+            "timestamp": timestamp(),
+            "event": { "type": "string" },
+            "detail": { "type": "string" },
+            "hash": { "type": "string", "pattern": "^[0-9a-f]{64}$" },
+        },
+    })
+}
+
+/// history.json的结构
+fn history_schema() -> Value {
+    let reasons = ["TimerExpired", "ManualClear", "EmergencyNuke", "Shutdown", "Unprotected", "Escalated"];
+    json!({
+        "type": "array",
+        "items": {
+            "type": "object",
+            "required": ["recorded_at", "length", "content_type", "operation"],
+            "properties": {
+                "recorded_at": timestamp(),
+                "length": { "type": "integer", "minimum": 0 },
+                "content_type": { "enum": ["Text", "Image", "Files", "Unknown"] },
+                "operation": {
+                    "oneOf": [
+                        { "enum": ["Copy", "Paste"] },
+                        {
+                            "type": "object",
+                            "required": ["Clear"],
+                            "additionalProperties": false,
+                            "properties": { "Clear": { "enum": reasons } },
+                        },
+                    ],
+                },
+                "item_id": { "oneOf": [item_id(), { "type": "null" }] },
+            },
+        },
+    })
+}
+
+/// notes.json的结构
+fn notes_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["notes"],
+        "properties": {
+            "notes": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["id", "created_at", "expires_at", "ciphertext"],
+                    "properties": {
+                        "id": item_id(),
+                        "created_at": timestamp(),
+                        "expires_at": timestamp(),
+                        "ciphertext": { "type": "string", "contentEncoding": "base64" },
+                    },
+                },
+            },
+        },
+    })
+}
+
+/// canaries.json的结构
+fn canaries_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["tokens"],
+        "properties": {
+            "tokens": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["token", "created_at"],
+                    "properties": {
+                        "token": { "type": "string" },
+                        "created_at": timestamp(),
+                        "retired_at": optional_timestamp(),
+                        "placed_at": optional_timestamp(),
+                        "decoy": { "type": "boolean", "default": false },
+                    },
+                },
+            },
+        },
+    })
+}
+
+/// 备份文件的结构
+fn backup_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["format", "revision", "kdf", "iterations", "salt", "ciphertext"],
+        "properties": {
+            "format": { "const": "clipvanish-backup" },
+            "revision": { "type": "integer", "minimum": 1 },
+            "kdf": { "const": "pbkdf2-sha256" },
+            "iterations": { "type": "integer", "minimum": 1 },
+            "salt": { "type": "string", "pattern": "^([0-9a-f]{2})+$" },
+            "ciphertext": { "type": "string", "contentEncoding": "base64" },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+    use chrono::{TimeZone, Utc};
+    use tempfile::tempdir;
+    use crate::audit::{AuditLog, ExportFormat};
+    use crate::backup;
+    use crate::canary::CanaryStore;
+    use crate::clipboard::ClipboardOperation;
+    use crate::history;
+    use crate::notes::{NoteHandle, NoteStore};
+
+    /// 每种格式每个修订的样本（格式名称, 修订号, 内容）
+    const FIXTURES: &[(&str, u32, &str)] = &[
+        ("audit", 1, include_str!("../tests/fixtures/formats/audit-1.jsonl")),
+        ("audit", 2, include_str!("../tests/fixtures/formats/audit-2.jsonl")),
+        ("history", 1, include_str!("../tests/fixtures/formats/history-1.json")),
+        ("history", 2, include_str!("../tests/fixtures/formats/history-2.json")),
+        ("notes", 1, include_str!("../tests/fixtures/formats/notes-1.json")),
+        ("canaries", 1, include_str!("../tests/fixtures/formats/canaries-1.json")),
+        ("canaries", 2, include_str!("../tests/fixtures/formats/canaries-2.json")),
+        ("backup", 1, include_str!("../tests/fixtures/formats/backup-1.json")),
+    ];
+
+    /// notes-1.json中笔记的读取凭据
+    const NOTE_HANDLE: &str = "01HQXW5P7R8ZJ6V4K2M9N3B5CD.000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+    #[test]
+    fn test_every_revision_has_fixture() {
+        for format in FORMATS {
+            for revision in format.revisions {
+                assert!(
+                    FIXTURES.iter().any(|(name, number, _)| *name == format.name && *number == revision.revision),
+                    "{} 修订 {} 缺少样本",
+                    format.name,
+                    revision.revision
+                );
+            }
+            assert_eq!(format.schema()["x-clipvanish-revision"], format.current_revision());
+        }
+        assert_eq!(dump_schema(None).unwrap().as_object().unwrap().len(), FORMATS.len());
+        assert!(dump_schema(Some("vault")).is_err());
+    }
+
+    #[test]
+    fn test_fixtures_load_with_current_code() {
+        let temp_dir = tempdir().unwrap();
+        for (name, revision, content) in FIXTURES {
+            let path = temp_dir.path().join(format!("{}-{}", name, revision));
+            std::fs::write(&path, content).unwrap();
+            let context = format!("{} 修订 {}", name, revision);
+
+            match *name {
+                "audit" => {
+                    let summary = AuditLog::new(&path)
+                        .export(Utc.timestamp_opt(0, 0).unwrap(), Utc::now(), ExportFormat::Jsonl, std::io::sink())
+                        .expect(&context);
+                    assert_eq!(summary.exported, content.lines().count(), "{}", context);
+                    assert_eq!(summary.broken_at, None, "{}", context);
+                }
+                "history" => {
+                    // 远在未来的单调时钟，确保旧样本中的记录不会因早于本次启动而被丢弃
+                    let items = history::load(&path, Instant::now() + Duration::from_secs(100 * 365 * 86400));
+                    assert_eq!(items.len(), serde_json::from_str::<Vec<Value>>(content).unwrap().len(), "{}", context);
+                    assert!(matches!(items[0].operation, ClipboardOperation::Copy), "{}", context);
+                    assert_eq!(items[0].item_id.is_some(), *revision >= 2, "{}", context);
+                }
+                "notes" => {
+                    let now = Utc.with_ymd_and_hms(2024, 3, 1, 10, 0, 0).unwrap();
+                    let mut store = NoteStore::load(&path, now).expect(&context);
+                    let handle: NoteHandle = NOTE_HANDLE.parse().unwrap();
+                    assert_eq!(store.take(&handle).expect(&context).as_str(), "door code 4711");
+                }
+                "canaries" => {
+                    let store = CanaryStore::load(&path).expect(&context);
+                    assert!(!store.tokens().is_empty(), "{}", context);
+                    assert!(store.tokens().iter().all(|token| token.token.starts_with("AKIA")), "{}", context);
+                }
+                "backup" => {
+                    let (manifest, files) = backup::open(content.as_bytes(), "correct horse battery").expect(&context);
+                    assert_eq!(manifest.files.len(), files.len(), "{}", context);
+                    assert!(files.iter().any(|(name, _)| name == "audit.log"), "{}", context);
+                }
+                other => panic!("未登记的格式样本: {}", other),
+            }
+        }
+    }
+}
@@ -0,0 +1,197 @@
+/*!
+ * ClipVanish™ macOS通用剪贴板（Handoff）模块
+ *
+ * 通用剪贴板可能在内容销毁前将其同步到同一Apple ID下的其他设备
+ * 特点：
+ * - 检测Handoff是否启用
+ * - 以"仅限本机"方式写入剪贴板，并附加Concealed/Transient标记类型
+ * - 会话期间关闭Handoff（需用户同意），会话结束后恢复原设置
+ * - 非macOS平台上所有操作均为空操作
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::sync::Mutex;
+use log::warn;
+#[cfg(target_os = "macos")]
+use log::info;
+#[cfg(target_os = "macos")]
+use crate::helpers;
+
+/// 会话开始前的Handoff设置（None表示未修改过）
+static PREVIOUS_STATE: Mutex<Option<HandoffStatus>> = Mutex::new(None);
+
+/// Handoff设置所在的偏好设置域（按主机区分）
+#[cfg(target_os = "macos")]
+const HANDOFF_DOMAIN: &str = "com.apple.coreservices.useractivityd";
+
+/// 标记内容为敏感或临时的剪贴板类型（nspasteboard.org约定）
+#[cfg(target_os = "macos")]
+const MARKER_TYPES: &[&str] = &["org.nspasteboard.ConcealedType", "org.nspasteboard.TransientType"];
+
+/// Handoff状态
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HandoffStatus {
+    /// 是否允许向其他设备广播（通用剪贴板发送端）
+    pub advertising: bool,
+    /// 是否允许接收其他设备的活动
+    pub receiving: bool,
+}
+
+impl HandoffStatus {
+    /// 本机内容是否可能被同步到其他设备
+    pub fn is_available(&self) -> bool {
+        self.advertising
+    }
+}
+
+/// 检测当前Handoff状态
+///
+/// # 返回值
+/// * `Option<HandoffStatus>` - Handoff状态，非macOS平台返回None
+#[cfg(target_os = "macos")]
+pub fn status() -> Option<HandoffStatus> {
+    Some(HandoffStatus {
+        advertising: read_flag("ActivityAdvertisingAllowed"),
+        receiving: read_flag("ActivityReceivingAllowed"),
+    })
+}
+
+/// 检测当前Handoff状态
+#[cfg(not(target_os = "macos"))]
+pub fn status() -> Option<HandoffStatus> {
+    None
+}
+
+/// 读取Handoff开关（未设置时系统默认启用）
+#[cfg(target_os = "macos")]
+fn read_flag(key: &str) -> bool {
+    helpers::output("defaults", &["-currentHost", "read", HANDOFF_DOMAIN, key])
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim() != "0")
+        .unwrap_or(true)
+}
+
+/// 写入Handoff开关
+#[cfg(target_os = "macos")]
+fn write_flag(key: &str, enabled: bool) -> Result<(), String> {
+    let status = helpers::status(helpers::command("defaults")?.args([
+        "-currentHost", "write", HANDOFF_DOMAIN, key,
+        "-bool", if enabled { "true" } else { "false" },
+    ]))
+    .map_err(|e| e.to_string())?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("defaults退出码: {:?}", status.code()))
+    }
+}
+
+/// 以"仅限本机"方式写入剪贴板，并附加Concealed/Transient标记
+///
+/// 内容通过标准输入传递，不出现在进程参数中
+///
+/// # 参数
+/// * `content` - 要写入的内容
+///
+/// # 返回值
+/// * `Result<(), String>` - 操作结果
+#[cfg(target_os = "macos")]
+pub fn write_local_only(content: &str) -> Result<(), String> {
+    let markers: Vec<String> = MARKER_TYPES
+        .iter()
+        .map(|marker| format!("pb.setStringForType($(''), '{}');", marker))
+        .collect();
+    let script = format!(
+        "ObjC.import('AppKit'); \
+         var data = $.NSFileHandle.fileHandleWithStandardInput.readDataToEndOfFile; \
+         var text = $.NSString.alloc.initWithDataEncoding(data, $.NSUTF8StringEncoding); \
+         var pb = $.NSPasteboard.generalPasteboard; \
+         pb.prepareForNewContentsWithOptions($.NSPasteboardContentsCurrentHostOnly); \
+         pb.setStringForType(text, $.NSPasteboardTypeString); {}",
+        markers.join(" ")
+    );
+
+    let status = helpers::run_with_stdin("osascript", &["-l", "JavaScript", "-e", &script], content.as_bytes())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("osascript退出码: {:?}", status.code()))
+    }
+}
+
+/// 以"仅限本机"方式写入剪贴板
+#[cfg(not(target_os = "macos"))]
+pub fn write_local_only(_content: &str) -> Result<(), String> {
+    Err("当前平台不支持通用剪贴板控制".to_string())
+}
+
+/// 会话期间关闭Handoff，并记录原设置以便恢复
+///
+/// # 返回值
+/// * `Result<(), String>` - 操作结果
+#[cfg(target_os = "macos")]
+pub fn disable_for_session() -> Result<(), String> {
+    let mut previous = PREVIOUS_STATE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if previous.is_some() {
+        return Ok(());
+    }
+
+    let current = status().ok_or_else(|| "无法读取Handoff状态".to_string())?;
+    write_flag("ActivityAdvertisingAllowed", false)?;
+    write_flag("ActivityReceivingAllowed", false)?;
+    *previous = Some(current);
+    info!("本次会话已关闭Handoff");
+    Ok(())
+}
+
+/// 会话期间关闭Handoff
+#[cfg(not(target_os = "macos"))]
+pub fn disable_for_session() -> Result<(), String> {
+    Ok(())
+}
+
+/// 恢复会话开始前的Handoff设置（未修改过时不做任何处理）
+pub fn restore_after_session() {
+    let mut previous = PREVIOUS_STATE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let Some(state) = previous.take() else {
+        return;
+    };
+
+    #[cfg(target_os = "macos")]
+    {
+        let result = write_flag("ActivityAdvertisingAllowed", state.advertising)
+            .and_then(|_| write_flag("ActivityReceivingAllowed", state.receiving));
+        match result {
+            Ok(()) => info!("已恢复Handoff设置"),
+            Err(e) => warn!("恢复Handoff设置失败: {}", e),
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = state;
+        warn!("当前平台不应存在Handoff修改记录");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_availability_follows_advertising() {
+        let status = HandoffStatus { advertising: false, receiving: true };
+        assert!(!status.is_available());
+        let status = HandoffStatus { advertising: true, receiving: false };
+        assert!(status.is_available());
+    }
+
+    #[test]
+    fn test_restore_without_disable_is_noop() {
+        restore_after_session();
+        assert!(PREVIOUS_STATE.lock().unwrap().is_none());
+    }
+}
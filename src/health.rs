@@ -0,0 +1,378 @@
+/*!
+ * ClipVanish™ 健康检查模块
+ *
+ * 服务运行期间定期检查监听循环、倒计时与剪贴板连接，把结果写入运行时目录中的健康状态文件，
+ * 供 `clipvanish health`、进程管理器与systemd看门狗判断服务是否仍在保护剪贴板
+ * 特点：
+ * - 监听循环超过 `stall_seconds` 未轮询、监督器已降级、倒计时超时未结束或剪贴板连续读取失败都视为不健康
+ * - 第一次不健康时请求监督器重启监听循环；连续 `exit_after` 次检查仍不健康时按关闭流程清除数据后以非零状态退出，交由服务管理器重启
+ * - 由systemd以 `Type=notify` 启动时发送 `READY=1`，健康时按 `WatchdogSec` 发送 `WATCHDOG=1`，不健康时停止发送
+ * - 健康状态文件只包含检查结果，不含剪贴板内容；服务停止时删除
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Utc};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use crate::countdown_display;
+use crate::supervisor::MonitorHealth;
+
+/// `clipvanish health` 在服务不健康、未运行或状态过期时的退出码
+pub const EXIT_UNHEALTHY: i32 = 1;
+
+/// 看门狗判定服务不健康后的退出码（EX_SOFTWARE）
+pub const EXIT_WATCHDOG: i32 = 70;
+
+/// 健康检查配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 检查间隔（秒）
+    pub interval_seconds: u64,
+    /// 监听循环超过该时间未轮询、倒计时超时该时间仍未结束即视为卡住（秒）
+    pub stall_seconds: u64,
+    /// 剪贴板连续读取失败多少次视为不可用
+    pub clipboard_failures: u32,
+    /// 连续多少次检查不健康后退出（0表示从不退出）
+    pub exit_after: u32,
+    /// 是否向systemd发送就绪与看门狗通知（未由systemd启动时不发送）
+    pub systemd_watchdog: bool,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        HealthConfig {
+            enabled: true,
+            interval_seconds: 5,
+            stall_seconds: 30,
+            clipboard_failures: 10,
+            exit_after: 6,
+            systemd_watchdog: true,
+        }
+    }
+}
+
+impl HealthConfig {
+    /// 检查间隔
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_seconds)
+    }
+
+    /// 健康状态文件超过该时间未更新即视为过期（服务卡住或已退出）
+    pub fn report_ttl(&self) -> Duration {
+        Duration::from_secs(self.interval_seconds.saturating_mul(3).max(self.stall_seconds))
+    }
+}
+
+/// 监听循环心跳
+#[derive(Debug)]
+pub struct Heartbeat {
+    /// 计时起点
+    anchor: Instant,
+    /// 最近一次轮询距起点的毫秒数加一（0表示尚未轮询）
+    last_tick: AtomicU64,
+    /// 剪贴板连续读取失败次数
+    read_failures: AtomicU32,
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Heartbeat {
+            anchor: Instant::now(),
+            last_tick: AtomicU64::new(0),
+            read_failures: AtomicU32::new(0),
+        }
+    }
+}
+
+impl Heartbeat {
+    /// 记录一次轮询
+    pub fn tick(&self) {
+        let elapsed = self.anchor.elapsed().as_millis() as u64;
+        self.last_tick.store(elapsed + 1, Ordering::Relaxed);
+    }
+
+    /// 记录一次剪贴板读取的结果
+    ///
+    /// # 参数
+    /// * `ok` - 是否读取成功
+    pub fn record_read(&self, ok: bool) {
+        if ok {
+            self.read_failures.store(0, Ordering::Relaxed);
+        } else {
+            self.read_failures.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// 距最近一次轮询的时间（尚未轮询时返回None）
+    pub fn since_tick(&self) -> Option<Duration> {
+        match self.last_tick.load(Ordering::Relaxed) {
+            0 => None,
+            tick => Some(self.anchor.elapsed().saturating_sub(Duration::from_millis(tick - 1))),
+        }
+    }
+
+    /// 剪贴板连续读取失败次数
+    pub fn read_failures(&self) -> u32 {
+        self.read_failures.load(Ordering::Relaxed)
+    }
+
+    /// 服务启动（心跳创建）以来的时间
+    pub fn uptime(&self) -> Duration {
+        self.anchor.elapsed()
+    }
+}
+
+/// 一次检查所需的观测值
+#[derive(Debug, Clone)]
+pub struct Probe {
+    /// 距最近一次轮询的时间
+    pub since_tick: Option<Duration>,
+    /// 服务运行时间
+    pub uptime: Duration,
+    /// 监督器报告的监听循环状态
+    pub supervisor: MonitorHealth,
+    /// 倒计时超出总时长仍未结束的时间
+    pub timer_overdue: Option<Duration>,
+    /// 剪贴板连续读取失败次数
+    pub read_failures: u32,
+}
+
+/// 单项检查结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthCheck {
+    /// 检查项（`monitor_loop`、`timers`、`clipboard`）
+    pub name: String,
+    /// 是否健康
+    pub healthy: bool,
+    /// 说明
+    pub detail: String,
+}
+
+impl HealthCheck {
+    fn new(name: &str, healthy: bool, detail: String) -> Self {
+        HealthCheck { name: name.to_string(), healthy, detail }
+    }
+}
+
+/// 健康检查报告（写入健康状态文件）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// 检查时间
+    pub checked_at: DateTime<Utc>,
+    /// 服务进程ID
+    pub pid: u32,
+    /// 各项检查结果
+    pub checks: Vec<HealthCheck>,
+}
+
+impl HealthReport {
+    /// 是否全部健康
+    pub fn healthy(&self) -> bool {
+        self.checks.iter().all(|check| check.healthy)
+    }
+
+    /// 不健康项的说明（用于日志与审计）
+    pub fn problems(&self) -> String {
+        self.checks
+            .iter()
+            .filter(|check| !check.healthy)
+            .map(|check| format!("{}: {}", check.name, check.detail))
+            .collect::<Vec<_>>()
+            .join("；")
+    }
+
+    /// 监听循环是否卡住或失败（需要重启监听循环）
+    pub fn monitor_loop_unhealthy(&self) -> bool {
+        self.checks.iter().any(|check| check.name == "monitor_loop" && !check.healthy)
+    }
+}
+
+/// 按观测值检查健康状态
+///
+/// # 参数
+/// * `config` - 健康检查配置
+/// * `probe` - 观测值
+/// * `now` - 当前时间
+pub fn evaluate(config: &HealthConfig, probe: &Probe, now: DateTime<Utc>) -> HealthReport {
+    let stall = Duration::from_secs(config.stall_seconds);
+
+    let monitor_loop = match (&probe.supervisor, probe.since_tick) {
+        (MonitorHealth::Degraded { .. }, _) => HealthCheck::new("monitor_loop", false, format!("监听循环{}", probe.supervisor)),
+        (_, Some(since)) if since > stall => HealthCheck::new("monitor_loop", false, format!("监听循环 {}秒未轮询", since.as_secs())),
+        (_, None) if probe.uptime > stall => HealthCheck::new("monitor_loop", false, "监听循环尚未开始轮询".to_string()),
+        (_, since) => HealthCheck::new(
+            "monitor_loop",
+            true,
+            since.map_or("等待首次轮询".to_string(), |since| format!("{}ms前轮询", since.as_millis())),
+        ),
+    };
+
+    let timers = match probe.timer_overdue {
+        Some(overdue) if overdue > stall => {
+            HealthCheck::new("timers", false, format!("倒计时已超时 {}秒仍未结束", overdue.as_secs()))
+        }
+        _ => HealthCheck::new("timers", true, "正常".to_string()),
+    };
+
+    let clipboard = if config.clipboard_failures > 0 && probe.read_failures >= config.clipboard_failures {
+        HealthCheck::new("clipboard", false, format!("连续 {} 次读取失败", probe.read_failures))
+    } else {
+        HealthCheck::new("clipboard", true, "可访问".to_string())
+    };
+
+    HealthReport { checked_at: now, pid: std::process::id(), checks: vec![monitor_loop, timers, clipboard] }
+}
+
+/// 健康状态文件路径（与倒计时状态文件位于同一运行时目录）
+pub fn report_path() -> Option<PathBuf> {
+    countdown_display::runtime_dir().map(|dir| dir.join("health"))
+}
+
+/// 写入健康状态文件（目录0700、文件0600）
+///
+/// # 参数
+/// * `report` - 检查报告
+pub fn publish(report: &HealthReport) {
+    let Some(path) = report_path() else {
+        return;
+    };
+    let result = countdown_display::prepare_runtime_dir(&path).and_then(|options| {
+        let json = serde_json::to_vec(report).map_err(std::io::Error::other)?;
+        options.open(&path)?.write_all(&json)
+    });
+    if let Err(e) = result {
+        debug!("写入健康状态文件失败: {}", e);
+    }
+}
+
+/// 删除健康状态文件（服务停止时调用）
+pub fn clear() {
+    if let Some(path) = report_path() {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// 读取健康状态文件（不存在或损坏时返回None）
+pub fn read_report() -> Option<HealthReport> {
+    let content = fs::read_to_string(report_path()?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// systemd通知（`sd_notify` 协议）
+pub mod systemd {
+    use std::time::Duration;
+
+    /// 是否由systemd以通知方式启动
+    pub fn available() -> bool {
+        std::env::var_os("NOTIFY_SOCKET").is_some()
+    }
+
+    /// systemd要求的看门狗通知间隔（`WatchdogSec` 的一半；未启用看门狗时返回None）
+    pub fn watchdog_interval() -> Option<Duration> {
+        let usec: u64 = std::env::var("WATCHDOG_USEC").ok()?.parse().ok()?;
+        // 设置了WATCHDOG_PID时只有该进程需要发送
+        if let Some(pid) = std::env::var("WATCHDOG_PID").ok().and_then(|pid| pid.parse::<u32>().ok()) {
+            if pid != std::process::id() {
+                return None;
+            }
+        }
+        (usec > 0).then(|| Duration::from_micros(usec / 2))
+    }
+
+    /// 发送通知（如 `READY=1`、`WATCHDOG=1`）
+    ///
+    /// # 参数
+    /// * `state` - 通知内容
+    #[cfg(unix)]
+    pub fn notify(state: &str) -> std::io::Result<()> {
+        use std::os::unix::net::UnixDatagram;
+
+        let Some(socket_path) = std::env::var_os("NOTIFY_SOCKET") else {
+            return Ok(());
+        };
+        let socket = UnixDatagram::unbound()?;
+        let bytes = socket_path.as_encoded_bytes();
+        // '@' 开头表示Linux抽象命名空间套接字
+        if let Some(name) = bytes.strip_prefix(b"@") {
+            #[cfg(target_os = "linux")]
+            {
+                use std::os::linux::net::SocketAddrExt;
+                let address = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+                socket.send_to_addr(state.as_bytes(), &address)?;
+            }
+            #[cfg(not(target_os = "linux"))]
+            let _ = name;
+        } else {
+            socket.send_to(state.as_bytes(), &socket_path)?;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    pub fn notify(_state: &str) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe() -> Probe {
+        Probe {
+            since_tick: Some(Duration::from_millis(200)),
+            uptime: Duration::from_secs(600),
+            supervisor: MonitorHealth::Healthy,
+            timer_overdue: None,
+            read_failures: 0,
+        }
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let config = HealthConfig::default();
+        let now = Utc::now();
+        assert!(evaluate(&config, &probe(), now).healthy());
+
+        let stalled = evaluate(&config, &Probe { since_tick: Some(Duration::from_secs(45)), ..probe() }, now);
+        assert!(!stalled.healthy());
+        assert!(stalled.monitor_loop_unhealthy());
+        assert_eq!(stalled.problems(), "monitor_loop: 监听循环 45秒未轮询");
+
+        let never_polled = Probe { since_tick: None, ..probe() };
+        assert!(evaluate(&config, &never_polled, now).monitor_loop_unhealthy());
+        assert!(evaluate(&config, &Probe { uptime: Duration::from_secs(3), ..never_polled }, now).healthy());
+
+        let degraded = Probe { supervisor: MonitorHealth::Degraded { failures: 5, last_error: "x".to_string() }, ..probe() };
+        assert!(evaluate(&config, &degraded, now).monitor_loop_unhealthy());
+
+        let timer = evaluate(&config, &Probe { timer_overdue: Some(Duration::from_secs(40)), ..probe() }, now);
+        assert!(!timer.healthy() && !timer.monitor_loop_unhealthy());
+        assert!(evaluate(&config, &Probe { timer_overdue: Some(Duration::from_secs(2)), ..probe() }, now).healthy());
+
+        assert!(!evaluate(&config, &Probe { read_failures: 10, ..probe() }, now).healthy());
+    }
+
+    #[test]
+    fn test_heartbeat() {
+        let heartbeat = Heartbeat::default();
+        assert_eq!(heartbeat.since_tick(), None);
+        heartbeat.tick();
+        assert!(heartbeat.since_tick().unwrap() < Duration::from_secs(1));
+
+        heartbeat.record_read(false);
+        heartbeat.record_read(false);
+        assert_eq!(heartbeat.read_failures(), 2);
+        heartbeat.record_read(true);
+        assert_eq!(heartbeat.read_failures(), 0);
+    }
+}
@@ -0,0 +1,310 @@
+/*!
+ * ClipVanish™ 辅助程序模块
+ *
+ * 统一启动 osascript/xdotool/xclip/powershell 等辅助程序，确保敏感内容不会落入临时文件、命令历史或进程参数
+ * 特点：
+ * - 直接执行程序，从不经过shell，不产生命令历史
+ * - 剪贴板内容只通过stdin传递，从不出现在命令行参数中（参数对同一主机上的其他用户可见）
+ * - 不继承终端的stdin；PowerShell以 -NoProfile -NonInteractive 运行，不加载配置也不写入历史
+ * - `--no-subprocess` 严格模式下拒绝启动任何辅助程序，相关功能回退或停用
+ * - Windows上所有辅助程序加入"关闭即终止"的作业对象：进程异常退出时由系统一并终止，紧急销毁时主动终止
+ *
+ * 受管策略读取（plutil/reg）与浸泡测试采样（ps）不经过本模块：它们不接触剪贴板内容，
+ * 且受管策略不能被命令行参数绕过
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::io::{self, Write};
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 是否处于严格模式（禁止辅助程序）
+static STRICT: AtomicBool = AtomicBool::new(false);
+
+/// 辅助程序接收剪贴板数据的方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HelperInput {
+    /// 不接收剪贴板数据
+    None,
+    /// 通过stdin接收
+    Stdin,
+}
+
+/// 辅助程序及其用途
+#[derive(Debug, Clone, Copy)]
+pub struct Helper {
+    /// 程序名称
+    pub program: &'static str,
+    /// 用途
+    pub purpose: &'static str,
+    /// 接收剪贴板数据的方式
+    pub input: HelperInput,
+}
+
+/// Linux使用的辅助程序
+const LINUX_HELPERS: &[Helper] = &[
+    Helper { program: "xclip", purpose: "剪贴板清除、隔离格式恢复与中键粘贴（PRIMARY选区）检查", input: HelperInput::Stdin },
+    Helper { program: "xsel", purpose: "剪贴板清除", input: HelperInput::None },
+    Helper { program: "xdotool", purpose: "安全粘贴、前台窗口检测与告警窗口闪烁", input: HelperInput::None },
+    Helper { program: "xrandr", purpose: "显示器检测", input: HelperInput::None },
+    Helper { program: "notify-send", purpose: "粘贴确认与关键告警通知", input: HelperInput::None },
+    Helper { program: "gsettings", purpose: "勿扰模式检测", input: HelperInput::None },
+    Helper { program: "curl", purpose: "规则包下载与Webhook推送", input: HelperInput::Stdin },
+    Helper { program: "ykchalresp", purpose: "紧急销毁的硬件密钥确认", input: HelperInput::None },
+    Helper { program: "copyq", purpose: "CopyQ历史清理与暂停记录", input: HelperInput::Stdin },
+];
+
+/// macOS使用的辅助程序
+const MACOS_HELPERS: &[Helper] = &[
+    Helper { program: "osascript", purpose: "剪贴板清除、安全粘贴、仅限本机写入、粘贴确认与关键告警通知", input: HelperInput::Stdin },
+    Helper { program: "defaults", purpose: "Handoff开关与Maccy暂停记录", input: HelperInput::None },
+    Helper { program: "curl", purpose: "规则包下载与Webhook推送", input: HelperInput::Stdin },
+    Helper { program: "ykchalresp", purpose: "紧急销毁的硬件密钥确认", input: HelperInput::None },
+    Helper { program: "copyq", purpose: "CopyQ历史清理与暂停记录", input: HelperInput::Stdin },
+];
+
+/// Windows使用的辅助程序
+const WINDOWS_HELPERS: &[Helper] = &[
+    Helper { program: "powershell", purpose: "安全粘贴与剪贴板历史清除", input: HelperInput::None },
+    Helper { program: "reg", purpose: "剪贴板历史开关", input: HelperInput::None },
+    Helper { program: "curl", purpose: "规则包下载与Webhook推送", input: HelperInput::Stdin },
+    Helper { program: "ykchalresp", purpose: "紧急销毁的硬件密钥确认", input: HelperInput::None },
+    Helper { program: "copyq", purpose: "CopyQ历史清理与暂停记录", input: HelperInput::Stdin },
+];
+
+/// 设置严格模式（需在启动任何功能之前调用）
+pub fn set_strict(strict: bool) {
+    STRICT.store(strict, Ordering::SeqCst);
+}
+
+/// 是否处于严格模式
+pub fn is_strict() -> bool {
+    STRICT.load(Ordering::SeqCst)
+}
+
+/// 当前平台使用的辅助程序
+pub fn platform_helpers() -> &'static [Helper] {
+    if cfg!(target_os = "windows") {
+        WINDOWS_HELPERS
+    } else if cfg!(target_os = "macos") {
+        MACOS_HELPERS
+    } else {
+        LINUX_HELPERS
+    }
+}
+
+/// 创建辅助程序命令（严格模式下返回错误）
+///
+/// # 参数
+/// * `program` - 程序名称
+pub fn command(program: &str) -> Result<Command, String> {
+    build(program, is_strict())
+}
+
+/// 启动辅助程序（Windows上加入辅助程序作业对象）
+///
+/// 由 `command` 创建的命令都应经此启动，而不是直接调用 `spawn`/`status`/`output`
+///
+/// # 参数
+/// * `command` - 辅助程序命令
+pub fn spawn(command: &mut Command) -> io::Result<Child> {
+    let child = command.spawn()?;
+    #[cfg(windows)]
+    job::adopt(&child);
+    Ok(child)
+}
+
+/// 启动辅助程序并等待其结束
+///
+/// # 参数
+/// * `command` - 辅助程序命令
+#[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+pub fn status(command: &mut Command) -> io::Result<ExitStatus> {
+    spawn(command)?.wait()
+}
+
+/// 运行辅助程序并收集输出
+///
+/// # 参数
+/// * `program` - 程序名称
+/// * `args` - 参数（不得包含剪贴板内容）
+pub fn output(program: &str, args: &[&str]) -> Result<Output, String> {
+    let mut command = command(program)?;
+    command.args(args).stdout(Stdio::piped()).stderr(Stdio::piped());
+    spawn(&mut command)
+        .and_then(|child| child.wait_with_output())
+        .map_err(|e| e.to_string())
+}
+
+/// 运行辅助程序，并通过stdin传入数据
+///
+/// # 参数
+/// * `program` - 程序名称
+/// * `args` - 参数（不得包含剪贴板内容）
+/// * `data` - 写入stdin的数据
+pub fn run_with_stdin(program: &str, args: &[&str], data: &[u8]) -> Result<ExitStatus, String> {
+    let mut child = spawn(command(program)?.args(args).stdin(Stdio::piped()).stdout(Stdio::null()))
+        .map_err(|e| e.to_string())?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // 写完立即关闭stdin，辅助程序读到EOF后才会结束
+        stdin.write_all(data).map_err(|e| e.to_string())?;
+    }
+
+    child.wait().map_err(|e| e.to_string())
+}
+
+/// 终止仍在运行的辅助程序（紧急销毁与崩溃清理时调用）
+///
+/// 正在执行的安全粘贴等辅助程序可能仍持有敏感参数或窗口焦点；目前只有Windows跟踪辅助程序，其他平台返回0
+///
+/// # 返回值
+/// * `usize` - 被终止的进程数
+pub fn terminate_all() -> usize {
+    #[cfg(windows)]
+    return job::terminate_all();
+
+    #[cfg(not(windows))]
+    0
+}
+
+/// Windows辅助程序作业对象
+///
+/// 作业对象在第一次启动辅助程序时创建，句柄一直保留到进程退出；
+/// 设置了 `JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE`，进程以任何方式退出（包括崩溃与被强制结束）时系统关闭句柄并终止其中全部进程。
+/// 辅助程序在启动后立即加入作业对象，期间它自己启动的子进程不会被纳入（SendKeys等脚本不会启动子进程）
+#[cfg(windows)]
+mod job {
+    use std::os::windows::io::AsRawHandle;
+    use std::process::Child;
+    use std::sync::OnceLock;
+    use std::{mem, ptr};
+    use log::{debug, warn};
+    use winapi::shared::minwindef::{DWORD, LPVOID};
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::jobapi2::{
+        AssignProcessToJobObject, CreateJobObjectW, QueryInformationJobObject, SetInformationJobObject, TerminateJobObject,
+    };
+    use winapi::um::winnt::{
+        JobObjectBasicAccountingInformation, JobObjectExtendedLimitInformation, HANDLE,
+        JOBOBJECT_BASIC_ACCOUNTING_INFORMATION, JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+    };
+
+    /// 作业对象句柄（只在创建后读取，可在线程间共享）
+    struct Job(HANDLE);
+
+    unsafe impl Send for Job {}
+    unsafe impl Sync for Job {}
+
+    /// 辅助程序作业对象（创建失败时为None，辅助程序照常运行但不受跟踪）
+    static JOB: OnceLock<Option<Job>> = OnceLock::new();
+
+    fn job() -> Option<HANDLE> {
+        JOB.get_or_init(|| unsafe {
+            let handle = CreateJobObjectW(ptr::null_mut(), ptr::null());
+            if handle.is_null() {
+                warn!("创建辅助程序作业对象失败，错误码: {}", winapi::um::errhandlingapi::GetLastError());
+                return None;
+            }
+
+            let mut limits: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = mem::zeroed();
+            limits.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            let configured = SetInformationJobObject(
+                handle,
+                JobObjectExtendedLimitInformation,
+                &mut limits as *mut _ as LPVOID,
+                mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as DWORD,
+            );
+            if configured == 0 {
+                warn!("设置辅助程序作业对象失败，错误码: {}", winapi::um::errhandlingapi::GetLastError());
+                CloseHandle(handle);
+                return None;
+            }
+            Some(Job(handle))
+        })
+        .as_ref()
+        .map(|job| job.0)
+    }
+
+    /// 将刚启动的辅助程序加入作业对象
+    pub(super) fn adopt(child: &Child) {
+        let Some(job) = job() else {
+            return;
+        };
+        if unsafe { AssignProcessToJobObject(job, child.as_raw_handle() as HANDLE) } == 0 {
+            let code = unsafe { winapi::um::errhandlingapi::GetLastError() };
+            warn!("辅助程序（PID {}）加入作业对象失败，错误码: {}", child.id(), code);
+        }
+    }
+
+    /// 终止作业对象中的全部进程
+    pub(super) fn terminate_all() -> usize {
+        let Some(job) = JOB.get().and_then(|job| job.as_ref()).map(|job| job.0) else {
+            return 0;
+        };
+
+        let mut accounting: JOBOBJECT_BASIC_ACCOUNTING_INFORMATION = unsafe { mem::zeroed() };
+        let queried = unsafe {
+            QueryInformationJobObject(
+                job,
+                JobObjectBasicAccountingInformation,
+                &mut accounting as *mut _ as LPVOID,
+                mem::size_of::<JOBOBJECT_BASIC_ACCOUNTING_INFORMATION>() as DWORD,
+                ptr::null_mut(),
+            )
+        };
+        let active = if queried != 0 { accounting.ActiveProcesses as usize } else { 0 };
+        if queried != 0 && active == 0 {
+            return 0;
+        }
+
+        // 作业对象终止后仍可继续使用，之后启动的辅助程序照常加入
+        if unsafe { TerminateJobObject(job, 1) } == 0 {
+            warn!("终止辅助程序失败，错误码: {}", unsafe { winapi::um::errhandlingapi::GetLastError() });
+            return 0;
+        }
+        debug!("已终止 {} 个辅助程序", active);
+        active
+    }
+}
+
+/// 按模式创建命令
+fn build(program: &str, strict: bool) -> Result<Command, String> {
+    if strict {
+        return Err(format!("严格模式（--no-subprocess）禁止启动辅助程序 {}", program));
+    }
+
+    let mut command = Command::new(program);
+    command.stdin(Stdio::null());
+    if program == "powershell" {
+        command.args(["-NoProfile", "-NonInteractive"]);
+    }
+    Ok(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strict_mode_refuses_helpers() {
+        // 直接使用内部构造函数，不切换全局模式，避免影响并行运行的其他测试
+        let error = build("xclip", true).unwrap_err();
+        assert!(error.contains("--no-subprocess"));
+
+        let command = build("powershell", false).unwrap();
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(args, vec!["-NoProfile", "-NonInteractive"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_output_collects_stdout() {
+        let output = output("printf", &["clipvanish"]).unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"clipvanish");
+        // 非Windows平台不跟踪辅助程序
+        assert_eq!(terminate_all(), 0);
+    }
+}
@@ -0,0 +1,601 @@
+/*!
+ * ClipVanish™ 历史记录保留模块
+ *
+ * 将剪贴板历史记录保存在固定大小的环形缓冲区中，按 `history` 配置裁剪，并可选地将元数据持久化到配置目录
+ * 特点：
+ * - 缓冲区容量即 `max_items`，启动时在锁定内存中一次性分配，写满后覆盖最早的记录
+ * - 每条记录为32字节的紧凑编码，时间按与前一条记录的差值存储；内容只以独立密钥加密后保存，密文同样位于锁定内存
+ * - 加密内容登记到紧急销毁，即使历史记录未及清空也会被擦除
+ * - 按条数与存活时间裁剪，监听器中所有写入历史的路径共用同一套规则
+ * - 持久化文件只包含时间、长度、类型、操作与条目ID，从不包含明文
+ * - 文件不存在或损坏时视为空历史
+ * - Windows上经DPAPI绑定到当前用户后落盘
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+use crate::clipboard::{ClearReason, ClipboardHistoryItem, ClipboardOperation, ContentType};
+use crate::config::{Config, HistoryConfig};
+use crate::crypto::{CryptoEngine, CryptoError, EncryptedData};
+use crate::item_id::ItemId;
+use crate::memory::{SecureBuffer, SecureMemory, SecureMemoryBlock};
+use crate::platform;
+use crate::redact::Secret;
+
+/// 持久化的历史记录（仅元数据）
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedItem {
+    /// 记录时间（UTC）
+    recorded_at: DateTime<Utc>,
+    /// 内容长度（字节）
+    length: usize,
+    /// 内容类型
+    content_type: ContentType,
+    /// 操作类型
+    operation: ClipboardOperation,
+    /// 条目ID（旧版本文件中没有）
+    #[serde(default)]
+    item_id: Option<ItemId>,
+}
+
+/// 默认的持久化文件路径（配置目录下的history.json）
+pub fn default_path() -> Option<PathBuf> {
+    Config::get_config_directory()
+        .ok()
+        .map(|dir| dir.join("history.json"))
+}
+
+/// 单条记录在缓冲区中占用的字节数
+pub const RECORD_SIZE: usize = 32;
+
+// 记录布局：
+// [0..4)   与前一条记录的时间差（毫秒，小端；最早一条相对于锚点）
+// [4..8)   内容长度（字节，小端）
+// [8]      操作类型
+// [9]      内容类型
+// [10]     标志位
+// [16..32) 条目ID（大端）
+const FLAG_ITEM_ID: u8 = 0x01;
+const FLAG_SEALED: u8 = 0x02;
+
+fn encode_operation(operation: &ClipboardOperation) -> u8 {
+    match operation {
+        ClipboardOperation::Copy => 0,
+        ClipboardOperation::Paste => 1,
+        ClipboardOperation::Clear(ClearReason::TimerExpired) => 2,
+        ClipboardOperation::Clear(ClearReason::ManualClear) => 3,
+        ClipboardOperation::Clear(ClearReason::EmergencyNuke) => 4,
+        ClipboardOperation::Clear(ClearReason::Shutdown) => 5,
+        ClipboardOperation::Clear(ClearReason::Unprotected) => 6,
+        ClipboardOperation::Clear(ClearReason::Escalated) => 7,
+    }
+}
+
+fn decode_operation(code: u8) -> ClipboardOperation {
+    match code {
+        0 => ClipboardOperation::Copy,
+        1 => ClipboardOperation::Paste,
+        2 => ClipboardOperation::Clear(ClearReason::TimerExpired),
+        3 => ClipboardOperation::Clear(ClearReason::ManualClear),
+        4 => ClipboardOperation::Clear(ClearReason::EmergencyNuke),
+        5 => ClipboardOperation::Clear(ClearReason::Shutdown),
+        6 => ClipboardOperation::Clear(ClearReason::Unprotected),
+        _ => ClipboardOperation::Clear(ClearReason::Escalated),
+    }
+}
+
+fn encode_content_type(content_type: &ContentType) -> u8 {
+    match content_type {
+        ContentType::Text => 0,
+        ContentType::Image => 1,
+        ContentType::Files => 2,
+        ContentType::Unknown => 3,
+    }
+}
+
+fn decode_content_type(code: u8) -> ContentType {
+    match code {
+        0 => ContentType::Text,
+        1 => ContentType::Image,
+        2 => ContentType::Files,
+        _ => ContentType::Unknown,
+    }
+}
+
+/// 历史记录缓冲区的统计信息
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct HistoryStats {
+    /// 容量（条）
+    pub capacity: usize,
+    /// 当前占用（条）
+    pub occupancy: usize,
+    /// 预分配的记录区大小（字节）
+    pub reserved_bytes: usize,
+    /// 记录区是否已锁定在物理内存中
+    pub locked: bool,
+}
+
+/// 固定大小的历史记录环形缓冲区
+///
+/// 记录区在创建时按容量一次性分配并尝试锁定，之后不再增长；
+/// 复制的内容以缓冲区自己的密钥加密后逐条保存在安全内存中（按同一设置锁定），与剪贴板加密密钥的重置互不影响
+pub struct HistoryRing {
+    /// 紧凑编码的记录（容量为0或分配失败时为None）
+    records: Option<SecureMemoryBlock>,
+    /// 各槽位加密保存的内容（nonce与密文）
+    sealed: Vec<Option<SecureBuffer>>,
+    /// 是否将记录区与加密内容锁定在物理内存中
+    lock: bool,
+    /// 内容加密引擎
+    engine: CryptoEngine,
+    /// 最早一条记录所在的槽位
+    head: usize,
+    /// 当前记录数
+    len: usize,
+    /// 时间锚点（单调时钟与墙上时间），最早一条记录的时间差相对于它
+    anchor: (Instant, DateTime<Utc>),
+    /// 最新一条记录解码后的时间（下一条记录的时间差相对于它）
+    newest: Instant,
+}
+
+impl HistoryRing {
+    /// 创建缓冲区
+    ///
+    /// # 参数
+    /// * `capacity` - 容量（条）
+    /// * `lock` - 是否将记录区锁定在物理内存中
+    pub fn new(capacity: usize, lock: bool) -> Result<Self, CryptoError> {
+        let records = match capacity.checked_mul(RECORD_SIZE).map(SecureMemoryBlock::allocate) {
+            Some(Ok(mut block)) => {
+                // 锁定失败已由内存模块记录为降级，缓冲区照常使用
+                if lock {
+                    let _ = block.lock();
+                }
+                Some(block)
+            }
+            _ if capacity == 0 => None,
+            _ => {
+                warn!("历史记录缓冲区分配失败（{} 条），不再记录历史", capacity);
+                None
+            }
+        };
+        let now = Instant::now();
+        Ok(HistoryRing {
+            sealed: if records.is_some() { vec![None; capacity] } else { Vec::new() },
+            records,
+            lock,
+            engine: CryptoEngine::new()?,
+            head: 0,
+            len: 0,
+            anchor: (now, Utc::now()),
+            newest: now,
+        })
+    }
+
+    /// 容量（条）
+    pub fn capacity(&self) -> usize {
+        self.sealed.len()
+    }
+
+    /// 当前记录数
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 统计信息
+    pub fn stats(&self) -> HistoryStats {
+        HistoryStats {
+            capacity: self.capacity(),
+            occupancy: self.len,
+            reserved_bytes: self.records.as_ref().map_or(0, SecureMemoryBlock::size),
+            locked: self.records.as_ref().is_some_and(SecureMemoryBlock::is_locked),
+        }
+    }
+
+    /// 第 `index` 条记录（0为最早）所在的槽位
+    fn slot(&self, index: usize) -> usize {
+        (self.head + index) % self.capacity()
+    }
+
+    /// 槽位的记录字节
+    fn record(&self, slot: usize) -> &[u8] {
+        let records = self.records.as_ref().expect("容量为0的缓冲区没有记录");
+        &records.as_slice()[slot * RECORD_SIZE..(slot + 1) * RECORD_SIZE]
+    }
+
+    /// 槽位的可变记录字节
+    fn record_mut(&mut self, slot: usize) -> &mut [u8] {
+        let records = self.records.as_mut().expect("容量为0的缓冲区没有记录");
+        &mut records.as_mut_slice()[slot * RECORD_SIZE..(slot + 1) * RECORD_SIZE]
+    }
+
+    fn delta(record: &[u8]) -> Duration {
+        Duration::from_millis(u32::from_le_bytes(record[0..4].try_into().unwrap()) as u64)
+    }
+
+    /// 追加一条记录，缓冲区已满时覆盖最早的一条
+    ///
+    /// # 参数
+    /// * `item` - 记录的元数据（时间应不早于上一条记录）
+    /// * `content` - 要加密保存的内容（不保存时为None）
+    pub fn push(&mut self, item: &ClipboardHistoryItem, content: Option<&str>) -> Result<(), CryptoError> {
+        let sealed = content.map(|content| self.seal(content)).transpose()?;
+        self.push_sealed(item, sealed);
+        Ok(())
+    }
+
+    /// 加密内容并复制到安全内存中，同时登记到紧急销毁
+    fn seal(&self, content: &str) -> Result<SecureBuffer, CryptoError> {
+        let data = self.engine.encrypt(content.as_bytes())?;
+        let buffer = SecureBuffer::from_bytes("历史记录", &data.to_bytes(), self.lock)
+            .map_err(|e| CryptoError::MemoryError(e.to_string()))?;
+        SecureMemory::register_wipe_target(buffer.clone());
+        Ok(buffer)
+    }
+
+    fn push_sealed(&mut self, item: &ClipboardHistoryItem, sealed: Option<SecureBuffer>) {
+        if self.capacity() == 0 {
+            return;
+        }
+        if self.len == self.capacity() {
+            self.pop_front();
+        }
+        if self.is_empty() {
+            self.anchor = (item.timestamp, item.recorded_at);
+            self.newest = item.timestamp;
+        }
+
+        // 时间差截断到毫秒，并以解码后的时间为基准，误差不会逐条累积
+        let delta_ms = item.timestamp.saturating_duration_since(self.newest).as_millis().min(u32::MAX as u128) as u32;
+        self.newest += Duration::from_millis(delta_ms as u64);
+
+        let mut flags = 0;
+        if item.item_id.is_some() {
+            flags |= FLAG_ITEM_ID;
+        }
+        if sealed.is_some() {
+            flags |= FLAG_SEALED;
+        }
+        let slot = self.slot(self.len);
+        let record = self.record_mut(slot);
+        record.fill(0);
+        record[0..4].copy_from_slice(&delta_ms.to_le_bytes());
+        record[4..8].copy_from_slice(&(item.length.min(u32::MAX as usize) as u32).to_le_bytes());
+        record[8] = encode_operation(&item.operation);
+        record[9] = encode_content_type(&item.content_type);
+        record[10] = flags;
+        record[16..32].copy_from_slice(&item.item_id.map_or([0; 16], ItemId::to_bytes));
+        self.sealed[slot] = sealed;
+        self.len += 1;
+    }
+
+    /// 移除最早的一条记录
+    fn pop_front(&mut self) {
+        if self.is_empty() {
+            return;
+        }
+        let slot = self.head;
+        let delta = Self::delta(self.record(slot));
+        self.anchor.0 += delta;
+        self.anchor.1 += chrono::Duration::from_std(delta).unwrap_or_default();
+        self.record_mut(slot).fill(0);
+        self.sealed[slot] = None;
+        self.head = (self.head + 1) % self.capacity();
+        self.len -= 1;
+    }
+
+    /// 按时间先后逐条计算记录时间，不解码其余字段
+    pub fn timestamps(&self) -> impl Iterator<Item = Instant> + '_ {
+        (0..self.len).scan(Duration::ZERO, |offset, index| {
+            *offset += Self::delta(self.record(self.slot(index)));
+            Some(self.anchor.0 + *offset)
+        })
+    }
+
+    /// 按时间先后解码全部记录（不包含内容）
+    pub fn items(&self) -> Vec<ClipboardHistoryItem> {
+        let mut items = Vec::with_capacity(self.len);
+        let mut offset = Duration::ZERO;
+        for index in 0..self.len {
+            let record = self.record(self.slot(index));
+            offset += Self::delta(record);
+            items.push(ClipboardHistoryItem {
+                timestamp: self.anchor.0 + offset,
+                recorded_at: self.anchor.1 + chrono::Duration::from_std(offset).unwrap_or_default(),
+                length: u32::from_le_bytes(record[4..8].try_into().unwrap()) as usize,
+                operation: decode_operation(record[8]),
+                content_type: decode_content_type(record[9]),
+                item_id: (record[10] & FLAG_ITEM_ID != 0).then(|| ItemId::from_bytes(record[16..32].try_into().unwrap())),
+                sealed: record[10] & FLAG_SEALED != 0,
+            });
+        }
+        items
+    }
+
+    /// 解密第 `slot` 条记录（1为最新）保存的内容
+    ///
+    /// # 返回值
+    /// * `Option<Secret<String>>` - 记录不存在、未保存内容或解密失败时返回None
+    pub fn reveal(&self, slot: usize) -> Option<Secret<String>> {
+        let index = self.len.checked_sub(slot.checked_sub(1)? + 1)?;
+        let sealed = self.sealed[self.slot(index)].as_ref()?.with(EncryptedData::from_bytes)?.ok()?;
+        let plaintext = Zeroizing::new(self.engine.decrypt(&sealed).ok()?);
+        std::str::from_utf8(&plaintext).ok().map(|content| Secret::new(content.to_string()))
+    }
+
+    /// 更新条目的长度，并重新加密已保存的内容
+    ///
+    /// # 参数
+    /// * `item_id` - 条目ID
+    /// * `content` - 新内容
+    pub fn update_item(&mut self, item_id: ItemId, content: &str) -> Result<(), CryptoError> {
+        let id = item_id.to_bytes();
+        for index in 0..self.len {
+            let slot = self.slot(index);
+            let record = self.record(slot);
+            if record[10] & FLAG_ITEM_ID == 0 || record[16..32] != id {
+                continue;
+            }
+            if self.sealed[slot].is_some() {
+                self.sealed[slot] = Some(self.seal(content)?);
+            }
+            let length = content.len().min(u32::MAX as usize) as u32;
+            self.record_mut(slot)[4..8].copy_from_slice(&length.to_le_bytes());
+        }
+        Ok(())
+    }
+
+    /// 只保留满足条件的记录（保持时间先后），返回删除的记录数
+    ///
+    /// # 参数
+    /// * `keep` - 按时间先后对每条记录调用
+    pub fn retain<F: FnMut(&ClipboardHistoryItem) -> bool>(&mut self, mut keep: F) -> usize {
+        let items = self.items();
+        let mut kept = Vec::with_capacity(items.len());
+        for (index, item) in items.into_iter().enumerate() {
+            if keep(&item) {
+                let slot = self.slot(index);
+                kept.push((item, self.sealed[slot].take()));
+            }
+        }
+        let removed = self.len - kept.len();
+        if removed > 0 {
+            self.erase_records();
+            for (item, sealed) in &mut kept {
+                self.push_sealed(item, sealed.take());
+            }
+        } else {
+            // 没有删除时原样放回
+            for (index, (_, sealed)) in kept.into_iter().enumerate() {
+                let slot = self.slot(index);
+                self.sealed[slot] = sealed;
+            }
+        }
+        removed
+    }
+
+    /// 改变容量（保留最新的记录），容量不变时不做任何事
+    ///
+    /// # 参数
+    /// * `capacity` - 新容量（条）
+    /// * `lock` - 是否将记录区锁定在物理内存中
+    pub fn resize(&mut self, capacity: usize, lock: bool) -> Result<(), CryptoError> {
+        if capacity == self.capacity() {
+            return Ok(());
+        }
+        let mut resized = HistoryRing::new(capacity, lock)?;
+        std::mem::swap(&mut resized.engine, &mut self.engine);
+        let items = self.items();
+        let skip = items.len().saturating_sub(capacity);
+        for (index, item) in items.into_iter().enumerate().skip(skip) {
+            let slot = self.slot(index);
+            resized.push_sealed(&item, self.sealed[slot].take());
+        }
+        *self = resized;
+        Ok(())
+    }
+
+    /// 擦除记录区与加密内容
+    fn erase_records(&mut self) {
+        if let Some(records) = self.records.as_mut() {
+            records.secure_erase();
+        }
+        self.sealed.iter_mut().for_each(|sealed| *sealed = None);
+        self.head = 0;
+        self.len = 0;
+    }
+
+    /// 清空全部记录，并更换内容加密密钥
+    pub fn clear(&mut self) {
+        self.erase_records();
+        if let Err(e) = self.engine.regenerate_key() {
+            warn!("重新生成历史记录密钥失败: {}", e);
+        }
+    }
+}
+
+/// 按保留策略裁剪历史记录
+///
+/// # 参数
+/// * `history` - 历史记录
+/// * `config` - 保留策略
+/// * `now` - 当前时间（来自监听器的时间源）
+///
+/// # 返回值
+/// * `usize` - 被删除的记录数
+pub fn prune(history: &mut HistoryRing, config: &HistoryConfig, now: Instant) -> usize {
+    let original_len = history.len();
+
+    // 记录按时间先后排列，过期的记录都在最前面
+    if let Some(max_age) = config.max_age() {
+        let expired = history
+            .timestamps()
+            .take_while(|timestamp| now.saturating_duration_since(*timestamp) >= max_age)
+            .count();
+        (0..expired).for_each(|_| history.pop_front());
+    }
+
+    while history.len() > config.max_items {
+        history.pop_front();
+    }
+
+    original_len - history.len()
+}
+
+/// 从文件加载历史记录元数据（文件不存在或损坏时返回空列表）
+///
+/// # 参数
+/// * `path` - 持久化文件路径
+/// * `now` - 当前时间（来自监听器的时间源）
+pub fn load<P: AsRef<Path>>(path: P, now: Instant) -> Vec<ClipboardHistoryItem> {
+    let items: Vec<PersistedItem> = platform::read_state_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+
+    items
+        .into_iter()
+        .filter_map(|item| {
+            // 将墙上时间换算回单调时钟；早于本次启动可表示范围的记录直接丢弃
+            let age = (Utc::now() - item.recorded_at).to_std().unwrap_or(Duration::ZERO);
+            Some(ClipboardHistoryItem {
+                timestamp: now.checked_sub(age)?,
+                recorded_at: item.recorded_at,
+                length: item.length,
+                content_type: item.content_type,
+                operation: item.operation,
+                item_id: item.item_id,
+                sealed: false,
+            })
+        })
+        .collect()
+}
+
+/// 保存历史记录元数据（不包含明文）
+///
+/// # 参数
+/// * `path` - 持久化文件路径
+/// * `history` - 历史记录
+pub fn save<P: AsRef<Path>>(path: P, history: &[ClipboardHistoryItem]) -> std::io::Result<()> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let items: Vec<PersistedItem> = history
+        .iter()
+        .map(|item| PersistedItem {
+            recorded_at: item.recorded_at,
+            length: item.length,
+            content_type: item.content_type.clone(),
+            operation: item.operation.clone(),
+            item_id: item.item_id,
+        })
+        .collect();
+    let content = serde_json::to_string_pretty(&items).map_err(std::io::Error::from)?;
+    platform::write_state(path, content.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, MockClock};
+    use tempfile::tempdir;
+
+    fn item(now: Instant, age: Duration, content: &str) -> ClipboardHistoryItem {
+        ClipboardHistoryItem {
+            timestamp: now - age,
+            recorded_at: Utc::now() - chrono::Duration::from_std(age).unwrap(),
+            length: content.len(),
+            content_type: ContentType::Text,
+            operation: ClipboardOperation::Copy,
+            item_id: Some(ItemId::generate()),
+            sealed: false,
+        }
+    }
+
+    fn ring(capacity: usize, now: Instant, entries: &[(u64, &str)]) -> HistoryRing {
+        let mut history = HistoryRing::new(capacity, false).unwrap();
+        for &(age, content) in entries {
+            history.push(&item(now, Duration::from_secs(age), content), Some(content)).unwrap();
+        }
+        history
+    }
+
+    fn revealed(history: &HistoryRing) -> Vec<String> {
+        (1..=history.len()).rev().filter_map(|slot| history.reveal(slot)).map(|content| content.expose().clone()).collect()
+    }
+
+    #[test]
+    fn test_prune_by_age_and_count() {
+        let config = HistoryConfig { max_items: 2, max_age_seconds: 60, ..HistoryConfig::default() };
+        let clock = MockClock::new();
+        let mut history = ring(4, clock.now(), &[(120, "old"), (3, "a"), (2, "b"), (1, "c")]);
+
+        assert_eq!(prune(&mut history, &config, clock.now()), 2);
+        assert_eq!(revealed(&history), vec!["b", "c"]);
+
+        // 时钟推进后按同一时钟过期
+        clock.advance(Duration::from_secs(58));
+        assert_eq!(prune(&mut history, &config, clock.now()), 1);
+        assert_eq!(history.len(), 1);
+    }
+
+    #[test]
+    fn test_ring_overwrites_and_delta_encodes() {
+        let now = Instant::now();
+        let mut history = ring(3, now, &[(40, "one"), (30, "two"), (20, "three"), (10, "four")]);
+
+        let stats = history.stats();
+        assert_eq!((stats.capacity, stats.occupancy, stats.reserved_bytes), (3, 3, 3 * RECORD_SIZE));
+        assert_eq!(revealed(&history), vec!["two", "three", "four"]);
+        let ages: Vec<u64> = history.items().iter().map(|item| now.duration_since(item.timestamp).as_secs()).collect();
+        assert_eq!(ages, vec![30, 20, 10]);
+        assert!(history.items().iter().all(|item| item.sealed && item.length > 0));
+
+        // 记录区中只有元数据，没有明文
+        let records = history.records.as_ref().unwrap().as_slice();
+        assert!(!records.windows(5).any(|window| window == b"three"));
+
+        let newest = history.items()[2].item_id.unwrap();
+        history.update_item(newest, "FOUR!").unwrap();
+        assert_eq!(history.reveal(1).unwrap().expose(), "FOUR!");
+        assert_eq!(history.retain(|item| item.item_id != Some(newest)), 1);
+        assert_eq!(revealed(&history), vec!["two", "three"]);
+
+        history.resize(1, false).unwrap();
+        assert_eq!(revealed(&history), vec!["three"]);
+        history.clear();
+        assert!(history.is_empty());
+        assert!(history.reveal(1).is_none());
+    }
+
+    #[test]
+    fn test_persisted_history_has_no_plaintext() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("history.json");
+
+        let now = Instant::now();
+        let history = ring(4, now, &[(5, "secret-token")]);
+        save(&path, &history.items()).unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains("secret-token"));
+
+        let loaded = load(&path, now);
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].length, "secret-token".len());
+        assert!(!loaded[0].sealed);
+        assert!(now.duration_since(loaded[0].timestamp) >= Duration::from_secs(4));
+    }
+}
@@ -0,0 +1,640 @@
+/*!
+ * ClipVanish™ 本地控制接口模块
+ *
+ * 通过Unix域套接字向本机其他程序提供状态查询与控制命令
+ * 特点：
+ * - 每行一条JSON请求/响应
+ * - 每个请求都必须携带令牌，按命令检查能力（见ipc_auth模块）
+ * - 拒绝其他用户的连接
+ * - 控制命令与被拒绝的请求写入审计日志
+ * - 配置了 `api.security` 时，破坏性命令还需要口令或带时效的签名请求（见ipc_security模块）
+ * - 从不返回明文剪贴板内容；配套图形界面只能取得加密给其临时公钥的内容（见companion模块）
+ * - 编辑器插件的 mark_secret 连接随后推送该次复制的保护与销毁事件（见editor模块）
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::broadcast::error::RecvError;
+use zeroize::Zeroizing;
+use crate::audit;
+use crate::clipboard::{ClipboardMonitor, ExtendOutcome};
+use crate::companion::{HandoffRecipient, HandoffTokens, HANDOFF_TOKEN_TTL};
+use crate::editor::{self, EditorEvent, MarkState};
+use crate::item_id::ItemId;
+use crate::ipc_auth::{self, AuthError, Capability, ClientIdentity, TokenStore};
+use crate::nuke_guard::{self, NukeGuardConfig};
+use crate::ipc_security::{IpcSecurityConfig, Proof, RequestGuard};
+use crate::schedule::{self, NukeScheduler};
+use crate::shutdown::{self, Trigger};
+use crate::timer::DestructTimer;
+
+/// 控制命令
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IpcCommand {
+    /// 查询运行状态
+    Status,
+    /// 紧急销毁
+    Nuke,
+    /// 暂停保护
+    Pause,
+    /// 恢复保护
+    Resume,
+    /// 停止服务（销毁受保护条目并擦除密钥后退出）
+    Stop,
+    /// 撤销最近一次倒计时清除
+    Undo,
+    /// 登记定时紧急销毁（请求需带 at 或 in）
+    ScheduleNuke,
+    /// 取消定时紧急销毁
+    CancelNuke,
+    /// 延长受保护条目的存活时间（请求可带 item 与 seconds）
+    Extend,
+    /// 申请一次性交接令牌
+    HandoffToken,
+    /// 兑现交接令牌，取得加密给图形界面公钥的内容（请求需带 handoff_token 与 public_key）
+    Handoff,
+    /// 声明即将复制的内容来自机密文件（请求需带 sha256，可带 source），随后推送编辑器事件
+    MarkSecret,
+}
+
+impl IpcCommand {
+    /// 命令名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            IpcCommand::Status => "status",
+            IpcCommand::Nuke => "nuke",
+            IpcCommand::Pause => "pause",
+            IpcCommand::Resume => "resume",
+            IpcCommand::Stop => "stop",
+            IpcCommand::Undo => "undo",
+            IpcCommand::ScheduleNuke => "schedule_nuke",
+            IpcCommand::CancelNuke => "cancel_nuke",
+            IpcCommand::Extend => "extend",
+            IpcCommand::HandoffToken => "handoff_token",
+            IpcCommand::Handoff => "handoff",
+            IpcCommand::MarkSecret => "mark_secret",
+        }
+    }
+
+    /// 命令所需能力
+    pub fn required_capability(&self) -> Capability {
+        match self {
+            IpcCommand::Status => Capability::Status,
+            IpcCommand::Nuke
+            | IpcCommand::Pause
+            | IpcCommand::Resume
+            | IpcCommand::Stop
+            | IpcCommand::Undo
+            | IpcCommand::ScheduleNuke
+            | IpcCommand::CancelNuke
+            | IpcCommand::Extend
+            | IpcCommand::MarkSecret => Capability::Control,
+            IpcCommand::HandoffToken | IpcCommand::Handoff => Capability::Content,
+        }
+    }
+}
+
+/// 控制请求
+#[derive(Debug, Deserialize)]
+pub struct IpcRequest {
+    /// 能力令牌
+    #[serde(default)]
+    pub token: Option<String>,
+    /// 命令
+    pub command: IpcCommand,
+    /// 定时销毁的绝对时间（schedule_nuke）
+    #[serde(default)]
+    pub at: Option<String>,
+    /// 定时销毁的相对时长（schedule_nuke）
+    #[serde(default, rename = "in")]
+    pub delay: Option<String>,
+    /// 条目ID（extend，缺省为当前条目）
+    #[serde(default)]
+    pub item: Option<ItemId>,
+    /// 延长的秒数（extend，缺省为60）
+    #[serde(default)]
+    pub seconds: Option<u64>,
+    /// 一次性交接令牌（handoff）
+    #[serde(default)]
+    pub handoff_token: Option<String>,
+    /// 图形界面的临时X25519公钥，十六进制（handoff）
+    #[serde(default)]
+    pub public_key: Option<String>,
+    /// 双人确认的TOTP验证码（nuke、schedule_nuke，启用 nuke_guard 时需要）
+    #[serde(default)]
+    pub code: Option<String>,
+    /// 即将复制的内容的SHA-256，十六进制（mark_secret）
+    #[serde(default)]
+    pub sha256: Option<String>,
+    /// 来源文件（mark_secret，只记录文件名）
+    #[serde(default)]
+    pub source: Option<String>,
+    /// 二次验证口令（配置了 api.security 时，受保护的命令需要口令或签名）
+    #[serde(default)]
+    pub passphrase: Option<String>,
+    /// 签名时间，Unix秒（签名请求）
+    #[serde(default)]
+    pub timestamp: Option<i64>,
+    /// 随机数，时效窗口内不能重复（签名请求）
+    #[serde(default)]
+    pub nonce: Option<String>,
+    /// Ed25519签名，十六进制（签名请求）
+    #[serde(default)]
+    pub signature: Option<String>,
+}
+
+impl IpcRequest {
+    /// 参与签名的命令参数（定时销毁的时间）
+    pub fn argument(&self) -> &str {
+        self.at.as_deref().or(self.delay.as_deref()).unwrap_or("")
+    }
+
+    /// 请求携带的二次验证信息
+    pub fn proof(&self) -> Proof<'_> {
+        Proof {
+            passphrase: self.passphrase.as_deref(),
+            timestamp: self.timestamp,
+            nonce: self.nonce.as_deref(),
+            signature: self.signature.as_deref(),
+        }
+    }
+}
+
+/// 控制响应
+#[derive(Debug, Serialize)]
+pub struct IpcResponse {
+    /// 是否成功
+    pub ok: bool,
+    /// 结果数据
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+    /// 错误信息
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl IpcResponse {
+    pub(crate) fn success(data: serde_json::Value) -> Self {
+        IpcResponse { ok: true, data: Some(data), error: None }
+    }
+
+    pub(crate) fn failure(error: impl std::fmt::Display) -> Self {
+        IpcResponse { ok: false, data: None, error: Some(error.to_string()) }
+    }
+}
+
+/// 控制接口服务
+pub struct ControlServer {
+    /// 控制套接字路径
+    socket_path: PathBuf,
+    /// 令牌文件路径（每次请求重新加载，吊销立即生效）
+    token_path: PathBuf,
+    /// 剪贴板监听器
+    monitor: Arc<ClipboardMonitor>,
+    /// 自毁定时器
+    timer: Arc<Mutex<DestructTimer>>,
+    /// 定时紧急销毁调度器
+    scheduler: Arc<NukeScheduler>,
+    /// 尚未兑现的交接令牌
+    handoff_tokens: HandoffTokens,
+    /// 紧急销毁双人确认配置
+    nuke_guard: NukeGuardConfig,
+    /// 破坏性命令的口令或签名验证
+    security: RequestGuard,
+    /// 服务停止标志（stop命令设置）
+    should_stop: Arc<Mutex<bool>>,
+}
+
+impl ControlServer {
+    /// 创建控制接口服务
+    ///
+    /// # 参数
+    /// * `socket_path` - 控制套接字路径
+    /// * `token_path` - 令牌文件路径
+    /// * `monitor` - 剪贴板监听器
+    /// * `timer` - 自毁定时器
+    /// * `scheduler` - 定时紧急销毁调度器
+    /// * `nuke_guard` - 紧急销毁双人确认配置
+    /// * `security` - 破坏性命令的口令或签名验证配置
+    /// * `should_stop` - 服务停止标志
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        socket_path: PathBuf,
+        token_path: PathBuf,
+        monitor: Arc<ClipboardMonitor>,
+        timer: Arc<Mutex<DestructTimer>>,
+        scheduler: Arc<NukeScheduler>,
+        nuke_guard: NukeGuardConfig,
+        security: IpcSecurityConfig,
+        should_stop: Arc<Mutex<bool>>,
+    ) -> Self {
+        ControlServer {
+            socket_path,
+            token_path,
+            monitor,
+            timer,
+            scheduler,
+            handoff_tokens: HandoffTokens::default(),
+            nuke_guard,
+            security: RequestGuard::new(security),
+            should_stop,
+        }
+    }
+
+    /// 控制套接字路径
+    pub fn socket_path(&self) -> &Path {
+        &self.socket_path
+    }
+
+    /// 绑定控制套接字（仅当前用户可访问）
+    ///
+    /// # 返回值
+    /// * `std::io::Result<UnixListener>` - 监听器
+    pub fn bind(&self) -> std::io::Result<UnixListener> {
+        ipc_auth::prepare_socket_path(&self.socket_path)?;
+        let listener = UnixListener::bind(&self.socket_path)?;
+        ipc_auth::restrict_permissions(&self.socket_path, 0o600)?;
+        info!("控制套接字已启动: {}", self.socket_path.display());
+        Ok(listener)
+    }
+
+    /// 接受并处理连接，直到任务被取消
+    ///
+    /// # 参数
+    /// * `listener` - 已绑定的监听器
+    pub async fn serve(self: Arc<Self>, listener: UnixListener) {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let server = self.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = server.handle_connection(stream).await {
+                            debug!("控制连接结束: {}", e);
+                        }
+                    });
+                },
+                Err(e) => warn!("接受控制连接失败: {}", e),
+            }
+        }
+    }
+
+    /// 处理单个连接
+    async fn handle_connection(&self, stream: UnixStream) -> std::io::Result<()> {
+        let (uid, pid) = match stream.peer_cred() {
+            Ok(cred) => (Some(cred.uid()), cred.pid()),
+            Err(e) => {
+                warn!("无法获取控制连接对端凭据: {}", e);
+                (None, None)
+            },
+        };
+
+        let (reader, mut writer) = stream.into_split();
+
+        // 无法确认对端身份或对端属于其他用户时直接拒绝
+        let peer_check = match uid {
+            Some(uid) => ipc_auth::check_peer_uid(uid),
+            None => Err(AuthError::PeerMismatch(u32::MAX)),
+        };
+        if let Err(e) = peer_check {
+            audit::record("ipc_denied", &format!("{} pid={:?}", e, pid));
+            let response = IpcResponse::failure(&e);
+            writer.write_all(format!("{}\n", serde_json::to_string(&response)?).as_bytes()).await?;
+            return Ok(());
+        }
+
+        let mut lines = BufReader::new(reader).lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let (response, marked) = match serde_json::from_str::<IpcRequest>(&line) {
+                Ok(request) => {
+                    let marked = (request.command == IpcCommand::MarkSecret).then(|| request.sha256.clone()).flatten();
+                    (self.handle_request(request, uid, pid), marked)
+                },
+                Err(e) => (IpcResponse::failure(format!("无效请求: {}", e)), None),
+            };
+            writer.write_all(format!("{}\n", serde_json::to_string(&response)?).as_bytes()).await?;
+
+            if let (true, Some(sha256)) = (response.ok, marked) {
+                if !self.follow_editor_mark(&sha256, &mut lines, &mut writer).await? {
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 推送编辑器标记的后续事件：匹配后推送 protected，随后在条目销毁或被取代时结束
+    ///
+    /// 推送期间收到的请求一律拒绝
+    ///
+    /// # 返回值
+    /// * `std::io::Result<bool>` - 对端是否仍保持连接
+    async fn follow_editor_mark(
+        &self,
+        sha256: &str,
+        lines: &mut Lines<BufReader<OwnedReadHalf>>,
+        writer: &mut OwnedWriteHalf,
+    ) -> std::io::Result<bool> {
+        // 先订阅审计事件，避免错过匹配后立即发生的销毁
+        let mut events = audit::subscribe(audit::EventFilter::events(&["destroyed"]));
+        let mut ticker = tokio::time::interval(EDITOR_POLL_INTERVAL);
+        let mut item = None;
+        let event = loop {
+            tokio::select! {
+                line = lines.next_line() => match line? {
+                    Some(_) => {
+                        let response = IpcResponse::failure("正在推送编辑器事件，请在事件结束后再发送请求");
+                        writer.write_all(format!("{}\n", serde_json::to_string(&response)?).as_bytes()).await?;
+                    },
+                    None => return Ok(false),
+                },
+                received = events.recv(), if item.is_some() => match (received, item) {
+                    (Ok(entry), Some(id)) => {
+                        if let Some(reason) = editor::destroyed_reason(&entry, id) {
+                            break EditorEvent::Destroyed { item: id, reason };
+                        }
+                    },
+                    (Err(RecvError::Lagged(skipped)), _) => debug!("编辑器事件推送落后，跳过 {} 条审计记录", skipped),
+                    _ => return Ok(true),
+                },
+                _ = ticker.tick() => match item {
+                    None => match self.monitor.editor_mark_state(sha256) {
+                        MarkState::Pending => {},
+                        MarkState::Captured(id) => {
+                            item = Some(id);
+                            write_event(writer, &EditorEvent::Protected { item: id }).await?;
+                        },
+                        MarkState::Expired => break EditorEvent::Expired,
+                    },
+                    // 当前条目已是其他内容时，本条目不会再有销毁事件
+                    Some(id) => {
+                        if self.monitor.current_item_id().is_some_and(|current| current != id) {
+                            break EditorEvent::Superseded { item: id };
+                        }
+                    },
+                },
+            }
+        };
+        write_event(writer, &event).await?;
+        Ok(true)
+    }
+
+    /// 认证并执行单个请求
+    fn handle_request(&self, request: IpcRequest, uid: Option<u32>, pid: Option<i32>) -> IpcResponse {
+        let command = request.command;
+        let required = command.required_capability();
+
+        let store = match TokenStore::load(&self.token_path) {
+            Ok(store) => store,
+            Err(e) => return IpcResponse::failure(e),
+        };
+
+        let client = match store.authorize(request.token.as_deref(), command.name(), required) {
+            Ok(entry) => ClientIdentity {
+                token_name: entry.name.clone(),
+                capability: entry.capability,
+                uid,
+                pid,
+            },
+            Err(e) => {
+                audit::record(
+                    "ipc_denied",
+                    &format!("命令={} 原因={} uid={:?} pid={:?}", command.name(), e, uid, pid),
+                );
+                return IpcResponse::failure(e);
+            },
+        };
+
+        // 配置了二次验证时，受保护的破坏性命令还需要口令或签名
+        let verified = match self.security.check(command.name(), request.argument(), &request.proof(), chrono::Utc::now().timestamp()) {
+            Ok(method) => method,
+            Err(e) => {
+                audit::record("ipc_denied", &format!("命令={} 原因={} {}", command.name(), e, client));
+                return IpcResponse::failure(e);
+            },
+        };
+
+        // 控制与内容交接命令记录调用方
+        if required != Capability::Status {
+            let method = verified.map(|method| format!(" 验证={}", method.name())).unwrap_or_default();
+            audit::record("ipc", &format!("命令={} {}{}", command.name(), client, method));
+        }
+        info!("控制命令 {} 来自 {}", command.name(), client);
+
+        match self.execute(&request, &client) {
+            Ok(data) => IpcResponse::success(data),
+            Err(e) => IpcResponse::failure(e),
+        }
+    }
+
+    /// 验证紧急销毁的第二因素（未启用双人确认时直接通过）
+    fn authorize_nuke(&self, request: &IpcRequest, client: &ClientIdentity) -> Result<(), String> {
+        if !self.nuke_guard.enabled {
+            return Ok(());
+        }
+        nuke_guard::authorize(&self.nuke_guard, request.code.as_deref(), &format!("控制接口令牌 {}", client.token_name))
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    /// 执行已通过认证的命令
+    fn execute(&self, request: &IpcRequest, client: &ClientIdentity) -> Result<serde_json::Value, String> {
+        match request.command {
+            IpcCommand::Status => Ok(serde_json::json!({
+                "running": self.monitor.get_state().is_running,
+                "monitor_health": self.monitor.get_state().health,
+                "paused": self.monitor.is_paused(),
+                "protected_item": self.monitor.has_protected_item(),
+                "current_item": self.monitor.current_item_id(),
+                "undo_available": self.monitor.undo_remaining().is_some(),
+                "scheduled_nuke": self.scheduler.pending(),
+            })),
+            IpcCommand::Nuke => {
+                self.authorize_nuke(request, client)?;
+                let restored_plain = self.monitor.emergency_nuke().map_err(|e| e.to_string())?;
+                self.timer.lock().unwrap().stop_countdown().map_err(|e| e.to_string())?;
+                Ok(serde_json::json!({ "restored_plain": restored_plain }))
+            },
+            IpcCommand::Pause => {
+                self.monitor.set_paused(true);
+                Ok(serde_json::json!({ "paused": true }))
+            },
+            IpcCommand::Resume => {
+                self.monitor.set_paused(false);
+                Ok(serde_json::json!({ "paused": false }))
+            },
+            IpcCommand::Stop => {
+                // 与 stop 命令相同：先执行关闭步骤，再通知服务循环退出；响应在关闭完成前返回
+                let should_stop = self.should_stop.clone();
+                tokio::task::spawn_blocking(move || {
+                    shutdown::coordinator().run(Trigger::Stop);
+                    *should_stop.lock().unwrap() = true;
+                });
+                Ok(serde_json::json!({ "stopping": true }))
+            },
+            IpcCommand::Undo => {
+                let restored = self.monitor.undo_last_clear().map_err(|e| e.to_string())?;
+                Ok(serde_json::json!({ "restored": restored }))
+            },
+            IpcCommand::ScheduleNuke => {
+                let at = schedule::resolve(request.at.as_deref(), request.delay.as_deref())
+                    .map_err(|e| e.to_string())?;
+                self.authorize_nuke(request, client)?;
+                let replaced = self.scheduler.schedule(at, &format!("控制接口令牌 {}", client.token_name));
+                Ok(serde_json::json!({ "scheduled_nuke": self.scheduler.pending(), "replaced": replaced }))
+            },
+            IpcCommand::CancelNuke => {
+                let cancelled = self.scheduler.cancel(&format!("控制接口令牌 {}", client.token_name));
+                Ok(serde_json::json!({ "cancelled": cancelled }))
+            },
+            IpcCommand::Extend => {
+                let seconds = request.seconds.unwrap_or(60);
+                let (granted, remaining, capped) = match self.monitor.extend_item(request.item, Duration::from_secs(seconds), false) {
+                    ExtendOutcome::Extended { granted, remaining, capped } => (granted, remaining, capped),
+                    ExtendOutcome::ConfirmationRequired { window, .. } => {
+                        return Err(format!("该条目已多次延长，需在 {} 秒内重复请求以确认", window.as_secs()));
+                    }
+                    ExtendOutcome::Destroyed => return Err("延长次数过多，条目已被强制销毁".to_string()),
+                    ExtendOutcome::NotFound => return Err("条目不存在或已被清除".to_string()),
+                };
+                self.timer.lock().unwrap().start_countdown(remaining).map_err(|e| e.to_string())?;
+                Ok(serde_json::json!({
+                    "item": self.monitor.current_item_id(),
+                    "granted_seconds": granted.as_secs(),
+                    "remaining_seconds": remaining.as_secs(),
+                    "capped_at_seconds": capped.map(|cap| cap.as_secs()),
+                }))
+            },
+            IpcCommand::HandoffToken => {
+                if !self.monitor.has_protected_item() {
+                    return Err("没有可交接的受保护内容".to_string());
+                }
+                let token = self.handoff_tokens.issue(&client.token_name);
+                Ok(serde_json::json!({
+                    "handoff_token": token,
+                    "expires_in_seconds": HANDOFF_TOKEN_TTL.as_secs(),
+                }))
+            },
+            IpcCommand::MarkSecret => {
+                let sha256 = request.sha256.as_deref().ok_or("缺少 sha256")?;
+                if !editor::is_sha256_hex(sha256) {
+                    return Err("sha256 必须是64位十六进制".to_string());
+                }
+                let source = editor::source_label(request.source.as_deref());
+                self.monitor.mark_editor_secret(sha256, &source);
+                audit::record("editor_mark", &format!("来源={} {}", source, client));
+                Ok(serde_json::json!({
+                    "armed": true,
+                    "expires_in_seconds": editor::MARK_TTL.as_secs(),
+                }))
+            },
+            IpcCommand::Handoff => {
+                let token = request.handoff_token.as_deref().ok_or("缺少 handoff_token")?;
+                let public_key = request.public_key.as_deref().ok_or("缺少 public_key")?;
+                self.handoff_tokens.redeem(token, &client.token_name).map_err(|e| e.to_string())?;
+                let recipient = HandoffRecipient::from_hex(public_key).map_err(|e| e.to_string())?;
+
+                // 与应用粘贴相同：解密一次并重置密钥，随后启动粘贴倒计时
+                let content = self.monitor.get_decrypted_content_for_paste()
+                    .map_err(|e| e.to_string())?
+                    .map(Zeroizing::new)
+                    .ok_or("没有可交接的受保护内容")?;
+                let item_id = self.monitor.current_item_id();
+                let sealed = recipient.seal(&content).map_err(|e| e.to_string())?;
+                self.monitor.handle_paste(&content).map_err(|e| e.to_string())?;
+
+                let label = item_id.map_or_else(|| "未知".to_string(), |id| id.to_string());
+                audit::record("gui_handoff", &format!("条目 {} 已加密交接给 {}", label, client));
+                Ok(serde_json::json!({
+                    "item": item_id,
+                    "ephemeral_public_key": sealed.ephemeral_public_key,
+                    "ciphertext": sealed.ciphertext,
+                }))
+            },
+        }
+    }
+}
+
+/// 编辑器事件推送时查询标记与当前条目的间隔
+const EDITOR_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 写出一行编辑器事件
+async fn write_event(writer: &mut OwnedWriteHalf, event: &EditorEvent) -> std::io::Result<()> {
+    writer.write_all(format!("{}\n", serde_json::to_string(event)?).as_bytes()).await
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.socket_path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_capabilities() {
+        assert_eq!(IpcCommand::Status.required_capability(), Capability::Status);
+        for command in [
+            IpcCommand::Nuke,
+            IpcCommand::Pause,
+            IpcCommand::Resume,
+            IpcCommand::Stop,
+            IpcCommand::Undo,
+            IpcCommand::ScheduleNuke,
+            IpcCommand::CancelNuke,
+            IpcCommand::Extend,
+            IpcCommand::MarkSecret,
+        ] {
+            assert_eq!(command.required_capability(), Capability::Control);
+        }
+        assert_eq!(IpcCommand::Handoff.required_capability(), Capability::Content);
+    }
+
+    #[test]
+    fn test_request_parsing() {
+        let request: IpcRequest = serde_json::from_str(r#"{"token":"abc","command":"nuke"}"#).unwrap();
+        assert_eq!(request.command, IpcCommand::Nuke);
+        assert_eq!(request.token.as_deref(), Some("abc"));
+
+        let request: IpcRequest = serde_json::from_str(r#"{"command":"status"}"#).unwrap();
+        assert!(request.token.is_none());
+
+        let request: IpcRequest = serde_json::from_str(r#"{"command":"schedule_nuke","in":"2h"}"#).unwrap();
+        assert_eq!(request.command, IpcCommand::ScheduleNuke);
+        assert_eq!(request.delay.as_deref(), Some("2h"));
+        assert!(serde_json::from_str::<IpcRequest>(r#"{"command":"paste"}"#).is_err());
+
+        let request: IpcRequest =
+            serde_json::from_str(r#"{"command":"extend","item":"01JAE5S8W1K7V3X9QZ2M4N6P8R","seconds":30}"#).unwrap();
+        assert_eq!(request.item.map(|id| id.to_string()).as_deref(), Some("01JAE5S8W1K7V3X9QZ2M4N6P8R"));
+        assert_eq!(request.seconds, Some(30));
+        assert!(serde_json::from_str::<IpcRequest>(r#"{"command":"extend","item":"42"}"#).is_err());
+
+        let request: IpcRequest =
+            serde_json::from_str(r#"{"command":"handoff","handoff_token":"t","public_key":"ab"}"#).unwrap();
+        assert_eq!(request.command, IpcCommand::Handoff);
+        assert_eq!(request.handoff_token.as_deref(), Some("t"));
+        assert_eq!(request.public_key.as_deref(), Some("ab"));
+
+        let request: IpcRequest =
+            serde_json::from_str(r#"{"command":"mark_secret","sha256":"00ff","source":"/srv/app/.env"}"#).unwrap();
+        assert_eq!(request.command, IpcCommand::MarkSecret);
+        assert_eq!(request.sha256.as_deref(), Some("00ff"));
+        assert_eq!(request.source.as_deref(), Some("/srv/app/.env"));
+    }
+}
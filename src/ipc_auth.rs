@@ -0,0 +1,416 @@
+/*!
+ * ClipVanish™ IPC认证模块
+ *
+ * 本地控制接口的认证与权限模型，防止任意本地进程读取状态或触发销毁
+ * 特点：
+ * - 控制套接字所在目录为0700、套接字文件为0600，仅当前用户可连接
+ * - 校验对端进程的用户ID，拒绝其他用户的连接
+ * - 按命令区分能力令牌：只读状态（status）、控制（control）与内容交接（content）
+ * - 令牌文件只保存令牌的SHA-256，令牌本身只在生成时显示一次
+ * - 破坏性命令记录调用方（令牌名称、UID、PID）到审计日志
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use aes_gcm_siv::aead::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+use crate::config::Config;
+use crate::crypto::constant_time_eq;
+use crate::encoding::encode_hex;
+use crate::platform;
+
+/// IPC认证错误类型
+#[derive(Debug)]
+pub enum AuthError {
+    /// 请求未携带令牌
+    MissingToken,
+    /// 令牌无效或已吊销
+    InvalidToken,
+    /// 令牌能力不足
+    Forbidden {
+        /// 请求的命令
+        command: String,
+        /// 所需能力
+        required: Capability,
+    },
+    /// 对端进程属于其他用户
+    PeerMismatch(u32),
+    /// 令牌文件访问失败
+    IoError(std::io::Error),
+    /// 令牌文件格式错误
+    FormatError(serde_json::Error),
+    /// 令牌名称已存在
+    DuplicateName(String),
+    /// 无法确定令牌文件位置
+    LocationError(String),
+}
+
+impl std::fmt::Display for AuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthError::MissingToken => write!(f, "请求未携带令牌"),
+            AuthError::InvalidToken => write!(f, "令牌无效或已吊销"),
+            AuthError::Forbidden { command, required } => {
+                write!(f, "命令 {} 需要 {} 能力", command, required)
+            },
+            AuthError::PeerMismatch(uid) => write!(f, "拒绝其他用户(UID {})的连接", uid),
+            AuthError::IoError(e) => write!(f, "令牌文件访问失败: {}", e),
+            AuthError::FormatError(e) => write!(f, "令牌文件格式错误: {}", e),
+            AuthError::DuplicateName(name) => write!(f, "令牌名称已存在: {}", name),
+            AuthError::LocationError(msg) => write!(f, "无法确定令牌文件位置: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+/// 令牌能力
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// 只读状态
+    Status,
+    /// 控制（包含只读状态）
+    Control,
+    /// 向配套图形界面交接剪贴板内容（包含控制）
+    Content,
+}
+
+impl Capability {
+    /// 当前能力是否满足所需能力
+    pub fn allows(&self, required: Capability) -> bool {
+        matches!(
+            (self, required),
+            (Capability::Content, _)
+                | (Capability::Control, Capability::Control | Capability::Status)
+                | (Capability::Status, Capability::Status)
+        )
+    }
+}
+
+impl std::fmt::Display for Capability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Capability::Status => write!(f, "status"),
+            Capability::Control => write!(f, "control"),
+            Capability::Content => write!(f, "content"),
+        }
+    }
+}
+
+impl std::str::FromStr for Capability {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "status" => Ok(Capability::Status),
+            "control" => Ok(Capability::Control),
+            "content" => Ok(Capability::Content),
+            _ => Err(format!("未知能力: {}（可选 status/control/content）", s)),
+        }
+    }
+}
+
+/// 单个令牌
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenEntry {
+    /// 令牌名称（用于审计）
+    pub name: String,
+    /// 令牌能力
+    pub capability: Capability,
+    /// 令牌值的SHA-256（十六进制）
+    #[serde(default)]
+    token_sha256: String,
+    /// 旧版本保存的明文令牌（加载时转换为哈希，不再写入）
+    #[serde(default, skip_serializing)]
+    token: Option<String>,
+}
+
+/// 发起请求的客户端身份
+#[derive(Debug, Clone)]
+pub struct ClientIdentity {
+    /// 令牌名称
+    pub token_name: String,
+    /// 令牌能力
+    pub capability: Capability,
+    /// 对端用户ID
+    pub uid: Option<u32>,
+    /// 对端进程ID
+    pub pid: Option<i32>,
+}
+
+impl std::fmt::Display for ClientIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "令牌={}({})", self.token_name, self.capability)?;
+        if let Some(uid) = self.uid {
+            write!(f, " uid={}", uid)?;
+        }
+        if let Some(pid) = self.pid {
+            write!(f, " pid={}", pid)?;
+        }
+        Ok(())
+    }
+}
+
+/// 令牌存储
+///
+/// 令牌文件位于配置目录下，权限为0600
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TokenStore {
+    /// 全部令牌
+    tokens: Vec<TokenEntry>,
+}
+
+impl TokenStore {
+    /// 默认令牌文件路径
+    pub fn default_path() -> Result<PathBuf, AuthError> {
+        Config::get_config_directory()
+            .map(|dir| dir.join("ipc_tokens.json"))
+            .map_err(|e| AuthError::LocationError(e.to_string()))
+    }
+
+    /// 从文件加载令牌（文件不存在时返回空存储）
+    ///
+    /// 旧版本保存的明文令牌转换为哈希后立即重写文件
+    ///
+    /// # 参数
+    /// * `path` - 令牌文件路径
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, AuthError> {
+        let path = path.as_ref();
+        let mut store: TokenStore = match platform::read_state_to_string(path) {
+            Ok(content) => serde_json::from_str(&Zeroizing::new(content)).map_err(AuthError::FormatError)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(TokenStore::default()),
+            Err(e) => return Err(AuthError::IoError(e)),
+        };
+
+        let mut migrated = false;
+        for entry in &mut store.tokens {
+            if let Some(token) = entry.token.take().map(Zeroizing::new) {
+                entry.token_sha256 = hash_token(&token);
+                migrated = true;
+            }
+        }
+        if migrated {
+            store.save(path)?;
+        }
+        Ok(store)
+    }
+
+    /// 保存令牌到文件（仅当前用户可读写）
+    ///
+    /// # 参数
+    /// * `path` - 令牌文件路径
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), AuthError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(AuthError::IoError)?;
+        }
+
+        let content = serde_json::to_string_pretty(self).map_err(AuthError::FormatError)?;
+        platform::write_state(path, content.as_bytes()).map_err(AuthError::IoError)
+    }
+
+    /// 生成新令牌
+    ///
+    /// # 参数
+    /// * `name` - 令牌名称
+    /// * `capability` - 令牌能力
+    ///
+    /// # 返回值
+    /// * `Result<String, AuthError>` - 新令牌的值（仅在生成时显示一次）
+    pub fn create(&mut self, name: &str, capability: Capability) -> Result<String, AuthError> {
+        if self.tokens.iter().any(|entry| entry.name == name) {
+            return Err(AuthError::DuplicateName(name.to_string()));
+        }
+
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        let token = encode_hex(&bytes);
+
+        self.tokens.push(TokenEntry {
+            name: name.to_string(),
+            capability,
+            token_sha256: hash_token(&token),
+            token: None,
+        });
+        Ok(token)
+    }
+
+    /// 吊销令牌
+    ///
+    /// # 返回值
+    /// * `bool` - 是否存在该令牌
+    pub fn revoke(&mut self, name: &str) -> bool {
+        let before = self.tokens.len();
+        self.tokens.retain(|entry| entry.name != name);
+        self.tokens.len() != before
+    }
+
+    /// 全部令牌（不含令牌值）
+    pub fn entries(&self) -> impl Iterator<Item = (&str, Capability)> {
+        self.tokens.iter().map(|entry| (entry.name.as_str(), entry.capability))
+    }
+
+    /// 校验令牌并检查所需能力
+    ///
+    /// # 参数
+    /// * `token` - 请求携带的令牌
+    /// * `command` - 请求的命令名称
+    /// * `required` - 命令所需能力
+    ///
+    /// # 返回值
+    /// * `Result<&TokenEntry, AuthError>` - 匹配的令牌
+    pub fn authorize(
+        &self,
+        token: Option<&str>,
+        command: &str,
+        required: Capability,
+    ) -> Result<&TokenEntry, AuthError> {
+        let token = token.filter(|t| !t.is_empty()).ok_or(AuthError::MissingToken)?;
+        let token_sha256 = hash_token(token);
+
+        let entry = self
+            .tokens
+            .iter()
+            .find(|entry| constant_time_eq(entry.token_sha256.as_bytes(), token_sha256.as_bytes()))
+            .ok_or(AuthError::InvalidToken)?;
+
+        if !entry.capability.allows(required) {
+            return Err(AuthError::Forbidden {
+                command: command.to_string(),
+                required,
+            });
+        }
+
+        Ok(entry)
+    }
+}
+
+/// 令牌的SHA-256（十六进制）
+fn hash_token(token: &str) -> String {
+    encode_hex(&Sha256::digest(token.as_bytes()))
+}
+
+/// 校验对端进程是否属于当前用户
+///
+/// # 参数
+/// * `peer_uid` - 对端用户ID
+#[cfg(unix)]
+pub fn check_peer_uid(peer_uid: u32) -> Result<(), AuthError> {
+    let own_uid = unsafe { libc::getuid() };
+    if peer_uid == own_uid {
+        Ok(())
+    } else {
+        Err(AuthError::PeerMismatch(peer_uid))
+    }
+}
+
+/// 准备控制套接字路径：父目录为0700，并移除残留的套接字文件
+///
+/// # 参数
+/// * `socket_path` - 控制套接字路径
+pub fn prepare_socket_path(socket_path: &Path) -> std::io::Result<()> {
+    if let Some(parent) = socket_path.parent() {
+        fs::create_dir_all(parent)?;
+        restrict_permissions(parent, 0o700)?;
+    }
+
+    match fs::remove_file(socket_path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// 设置文件权限（非Unix平台为空操作）
+pub fn restrict_permissions(path: &Path, mode: u32) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = (path, mode);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_capability_scopes() {
+        let mut store = TokenStore::default();
+        let status = store.create("monitor", Capability::Status).unwrap();
+        let control = store.create("editor", Capability::Control).unwrap();
+
+        assert!(store.authorize(Some(&status), "status", Capability::Status).is_ok());
+        assert!(matches!(
+            store.authorize(Some(&status), "nuke", Capability::Control),
+            Err(AuthError::Forbidden { .. })
+        ));
+        assert_eq!(store.authorize(Some(&control), "nuke", Capability::Control).unwrap().name, "editor");
+        assert!(matches!(store.authorize(None, "status", Capability::Status), Err(AuthError::MissingToken)));
+        assert!(matches!(store.authorize(Some("bogus"), "status", Capability::Status), Err(AuthError::InvalidToken)));
+
+        assert!(store.revoke("editor"));
+        assert!(store.authorize(Some(&control), "nuke", Capability::Control).is_err());
+        assert!(store.create("monitor", Capability::Control).is_err());
+
+        // 控制令牌不能读取内容，内容令牌包含控制能力
+        let content = store.create("gui", Capability::Content).unwrap();
+        assert!(store.authorize(Some(&status), "handoff", Capability::Content).is_err());
+        assert!(store.authorize(Some(&content), "nuke", Capability::Control).is_ok());
+        assert_eq!("content".parse::<Capability>().unwrap(), Capability::Content);
+    }
+
+    #[test]
+    fn test_token_file_permissions() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("ipc_tokens.json");
+
+        let mut store = TokenStore::default();
+        let token = store.create("cli", Capability::Control).unwrap();
+        store.save(&path).unwrap();
+
+        let loaded = TokenStore::load(&path).unwrap();
+        assert!(loaded.authorize(Some(&token), "nuke", Capability::Control).is_ok());
+
+        // 文件中只有令牌的哈希
+        let content = platform::read_state_to_string(&path).unwrap();
+        assert!(!content.contains(&token));
+        assert!(content.contains(&hash_token(&token)));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+
+            // 覆盖权限过宽的已有文件时先收紧权限再写入
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+            store.save(&path).unwrap();
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_plaintext_tokens_are_migrated() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("ipc_tokens.json");
+        fs::write(&path, r#"{"tokens":[{"name":"cli","capability":"control","token":"0123abcd"}]}"#).unwrap();
+
+        let store = TokenStore::load(&path).unwrap();
+        assert_eq!(store.authorize(Some("0123abcd"), "nuke", Capability::Control).unwrap().name, "cli");
+        assert!(!fs::read_to_string(&path).unwrap().contains("0123abcd"));
+        assert!(TokenStore::load(&path).unwrap().authorize(Some("0123abcd"), "nuke", Capability::Control).is_ok());
+    }
+}
@@ -0,0 +1,457 @@
+/*!
+ * ClipVanish™ 控制接口二次验证模块
+ *
+ * 通过控制接口从脚本发起的破坏性命令（紧急销毁、定时销毁、暂停保护、停止服务）可要求口令或签名请求，
+ * 防止拿到control令牌的恶意本地脚本关闭保护
+ * 特点：
+ * - 口令只以PBKDF2-HMAC-SHA256哈希（随机盐）保存在配置中，由 `token passphrase` 生成；
+ *   连续输错后暂停口令验证，限制本地脚本暴力尝试
+ * - 签名请求使用Ed25519：配置中只保存公钥，签名覆盖命令、参数、时间戳与随机数，
+ *   超出时效窗口或随机数重复的请求一律拒绝
+ * - 受保护的命令可配置（只接受可受保护的命令名称，拼写错误在加载配置时报错）；未配置口令与签名公钥时不做额外验证
+ *
+ * 作者: ClipVanish Team
+ */
+
+// 验证只在控制接口中使用，未启用api特性时只保留配置
+#![cfg_attr(not(all(unix, feature = "api")), allow(dead_code))]
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use aes_gcm_siv::aead::OsRng;
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey, SIGNATURE_LENGTH};
+use pbkdf2::pbkdf2_hmac;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+use crate::crypto::constant_time_eq;
+use crate::encoding::{decode_hex, encode_hex};
+use crate::lockout::FailureLockout;
+use crate::platform;
+
+/// 口令哈希的标识
+const PASSPHRASE_SCHEME: &str = "pbkdf2-sha256";
+
+/// 新口令哈希的迭代次数
+const PASSPHRASE_ITERATIONS: u32 = 100_000;
+
+/// 随机数最大长度
+const MAX_NONCE_LENGTH: usize = 64;
+
+/// 可要求二次验证的破坏性命令
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtectedCommand {
+    /// 紧急销毁
+    Nuke,
+    /// 登记定时紧急销毁
+    ScheduleNuke,
+    /// 取消定时紧急销毁
+    CancelNuke,
+    /// 暂停保护
+    Pause,
+    /// 停止服务
+    Stop,
+}
+
+impl ProtectedCommand {
+    /// 命令名称（与控制接口的命令名称一致）
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProtectedCommand::Nuke => "nuke",
+            ProtectedCommand::ScheduleNuke => "schedule_nuke",
+            ProtectedCommand::CancelNuke => "cancel_nuke",
+            ProtectedCommand::Pause => "pause",
+            ProtectedCommand::Stop => "stop",
+        }
+    }
+}
+
+/// 控制接口二次验证配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IpcSecurityConfig {
+    /// 需要口令或签名的命令
+    pub protected_commands: Vec<ProtectedCommand>,
+    /// 口令哈希，由 `token passphrase` 生成
+    pub passphrase_hash: Option<String>,
+    /// 签名请求的Ed25519公钥（十六进制），由 `token keygen` 生成
+    pub signing_public_key: Option<String>,
+    /// 签名请求的时效窗口（秒，允许前后偏差）
+    pub freshness_seconds: u64,
+}
+
+impl Default for IpcSecurityConfig {
+    fn default() -> Self {
+        IpcSecurityConfig {
+            protected_commands: vec![
+                ProtectedCommand::Nuke,
+                ProtectedCommand::ScheduleNuke,
+                ProtectedCommand::CancelNuke,
+                ProtectedCommand::Pause,
+                ProtectedCommand::Stop,
+            ],
+            passphrase_hash: None,
+            signing_public_key: None,
+            freshness_seconds: 30,
+        }
+    }
+}
+
+impl IpcSecurityConfig {
+    /// 是否配置了口令或签名公钥
+    pub fn enabled(&self) -> bool {
+        self.passphrase_hash.is_some() || self.signing_public_key.is_some()
+    }
+
+    /// 命令是否需要二次验证
+    ///
+    /// # 参数
+    /// * `command` - 命令名称
+    pub fn protects(&self, command: &str) -> bool {
+        self.enabled() && self.protected_commands.iter().any(|protected| protected.name() == command)
+    }
+}
+
+/// 二次验证错误
+#[derive(Debug, PartialEq)]
+pub enum SecurityError {
+    /// 请求既没有口令也没有签名
+    Required,
+    /// 口令错误
+    InvalidPassphrase,
+    /// 签名无效
+    InvalidSignature,
+    /// 时间戳超出时效窗口
+    Stale,
+    /// 随机数已使用过
+    Replayed,
+    /// 口令错误次数过多，暂停验证（剩余秒数）
+    RateLimited(u64),
+    /// 请求或配置格式错误
+    Malformed(String),
+}
+
+impl std::fmt::Display for SecurityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SecurityError::Required => write!(f, "该命令需要口令（passphrase）或签名请求（timestamp、nonce、signature）"),
+            SecurityError::InvalidPassphrase => write!(f, "口令错误"),
+            SecurityError::InvalidSignature => write!(f, "请求签名无效"),
+            SecurityError::Stale => write!(f, "请求时间戳超出时效窗口，请重新签名"),
+            SecurityError::Replayed => write!(f, "请求随机数已使用过，拒绝重放"),
+            SecurityError::RateLimited(seconds) => write!(f, "口令错误次数过多，请在{}秒后重试", seconds),
+            SecurityError::Malformed(reason) => write!(f, "验证信息格式错误: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for SecurityError {}
+
+/// 通过的验证方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Method {
+    /// 口令
+    Passphrase,
+    /// Ed25519签名
+    Signature,
+}
+
+impl Method {
+    /// 审计记录中的名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Method::Passphrase => "passphrase",
+            Method::Signature => "signature",
+        }
+    }
+}
+
+/// 请求携带的验证信息
+#[derive(Debug, Default)]
+pub struct Proof<'a> {
+    /// 口令
+    pub passphrase: Option<&'a str>,
+    /// 签名时间（Unix秒）
+    pub timestamp: Option<i64>,
+    /// 随机数
+    pub nonce: Option<&'a str>,
+    /// Ed25519签名（十六进制）
+    pub signature: Option<&'a str>,
+}
+
+/// 签名覆盖的内容
+///
+/// # 参数
+/// * `command` - 命令名称
+/// * `argument` - 命令参数（schedule_nuke的 at 或 in，没有时为空）
+/// * `timestamp` - 签名时间（Unix秒）
+/// * `nonce` - 随机数
+pub fn signed_message(command: &str, argument: &str, timestamp: i64, nonce: &str) -> String {
+    format!("clipvanish-ipc\n{}\n{}\n{}\n{}", command, argument, timestamp, nonce)
+}
+
+/// 控制接口的二次验证（记录已使用的随机数）
+pub struct RequestGuard {
+    /// 验证配置
+    config: IpcSecurityConfig,
+    /// 时效窗口内已使用的随机数及其时间戳
+    seen_nonces: Mutex<HashMap<String, i64>>,
+    /// 连续口令错误计数
+    passphrase_failures: Mutex<FailureLockout>,
+}
+
+impl RequestGuard {
+    /// 创建验证器
+    ///
+    /// # 参数
+    /// * `config` - 验证配置
+    pub fn new(config: IpcSecurityConfig) -> Self {
+        RequestGuard { config, seen_nonces: Mutex::new(HashMap::new()), passphrase_failures: Mutex::new(FailureLockout::default()) }
+    }
+
+    /// 验证请求（命令不受保护时返回None）
+    ///
+    /// 同时提供签名与口令时只校验签名
+    ///
+    /// # 参数
+    /// * `command` - 命令名称
+    /// * `argument` - 命令参数
+    /// * `proof` - 请求携带的验证信息
+    /// * `now` - 当前时间（Unix秒）
+    pub fn check(&self, command: &str, argument: &str, proof: &Proof, now: i64) -> Result<Option<Method>, SecurityError> {
+        if !self.config.protects(command) {
+            return Ok(None);
+        }
+
+        match (&self.config.signing_public_key, proof.signature, &self.config.passphrase_hash, proof.passphrase) {
+            (Some(public_key), Some(signature), _, _) => {
+                self.check_signature(public_key, signature, command, argument, proof, now).map(|_| Some(Method::Signature))
+            }
+            (_, _, Some(hash), Some(passphrase)) => {
+                self.check_passphrase(hash, passphrase, now).map(|_| Some(Method::Passphrase))
+            }
+            _ => Err(SecurityError::Required),
+        }
+    }
+
+    /// 校验口令（连续错误过多时在暂停期内直接拒绝，不再计算哈希）
+    fn check_passphrase(&self, hash: &str, passphrase: &str, now: i64) -> Result<(), SecurityError> {
+        let mut failures = self.passphrase_failures.lock().unwrap();
+        if let Some(remaining) = failures.locked_for(now) {
+            return Err(SecurityError::RateLimited(remaining));
+        }
+
+        if verify_passphrase(hash, passphrase)? {
+            failures.reset();
+            Ok(())
+        } else {
+            failures.record_failure(now);
+            Err(SecurityError::InvalidPassphrase)
+        }
+    }
+
+    /// 校验签名、时效与随机数
+    fn check_signature(
+        &self,
+        public_key: &str,
+        signature: &str,
+        command: &str,
+        argument: &str,
+        proof: &Proof,
+        now: i64,
+    ) -> Result<(), SecurityError> {
+        let timestamp = proof.timestamp.ok_or_else(|| SecurityError::Malformed("缺少 timestamp".to_string()))?;
+        let nonce = proof
+            .nonce
+            .filter(|nonce| !nonce.is_empty() && nonce.len() <= MAX_NONCE_LENGTH)
+            .ok_or_else(|| SecurityError::Malformed(format!("nonce 必须为1到{}个字符", MAX_NONCE_LENGTH)))?;
+        // 时间戳来自请求，差值用abs_diff计算，极端值不会溢出
+        let window = self.config.freshness_seconds;
+        if now.abs_diff(timestamp) > window {
+            return Err(SecurityError::Stale);
+        }
+
+        let public_key: [u8; 32] = decode_hex(public_key.trim())
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| SecurityError::Malformed("signing_public_key 不是32字节十六进制".to_string()))?;
+        let signature: [u8; SIGNATURE_LENGTH] = decode_hex(signature.trim())
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| SecurityError::Malformed("signature 不是64字节十六进制".to_string()))?;
+        let message = signed_message(command, argument, timestamp, nonce);
+        let verified = VerifyingKey::from_bytes(&public_key)
+            .and_then(|key| key.verify_strict(message.as_bytes(), &Signature::from_bytes(&signature)))
+            .is_ok();
+        if !verified {
+            return Err(SecurityError::InvalidSignature);
+        }
+
+        // 只在签名有效后登记随机数，伪造的请求不能占用随机数
+        let mut seen = self.seen_nonces.lock().unwrap();
+        seen.retain(|_, &mut seen_at| now.abs_diff(seen_at) <= window);
+        if seen.insert(nonce.to_string(), timestamp).is_some() {
+            return Err(SecurityError::Replayed);
+        }
+        Ok(())
+    }
+}
+
+/// 生成口令哈希（写入 `api.security.passphrase_hash`）
+///
+/// # 参数
+/// * `passphrase` - 口令
+pub fn hash_passphrase(passphrase: &str) -> String {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    format_hash(passphrase, &salt, PASSPHRASE_ITERATIONS)
+}
+
+/// 按指定盐与迭代次数生成口令哈希
+fn format_hash(passphrase: &str, salt: &[u8], iterations: u32) -> String {
+    let hash = derive(passphrase, salt, iterations);
+    format!("{}${}${}${}", PASSPHRASE_SCHEME, iterations, encode_hex(salt), encode_hex(hash.as_ref()))
+}
+
+/// 校验口令
+///
+/// # 参数
+/// * `stored` - 配置中的口令哈希
+/// * `passphrase` - 请求中的口令
+fn verify_passphrase(stored: &str, passphrase: &str) -> Result<bool, SecurityError> {
+    let malformed = || SecurityError::Malformed("passphrase_hash 格式应为 pbkdf2-sha256$迭代次数$盐$哈希".to_string());
+    let parts: Vec<&str> = stored.trim().split('$').collect();
+    let [PASSPHRASE_SCHEME, iterations, salt, expected] = parts[..] else {
+        return Err(malformed());
+    };
+    let iterations = iterations.parse::<u32>().ok().filter(|&n| n > 0).ok_or_else(malformed)?;
+    let salt = decode_hex(salt).ok_or_else(malformed)?;
+    let expected = decode_hex(expected).ok_or_else(malformed)?;
+    Ok(constant_time_eq(derive(passphrase, &salt, iterations).as_ref(), &expected))
+}
+
+/// PBKDF2-HMAC-SHA256派生32字节口令哈希
+fn derive(passphrase: &str, salt: &[u8], iterations: u32) -> Zeroizing<[u8; 32]> {
+    let mut hash = Zeroizing::new([0u8; 32]);
+    pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, iterations, hash.as_mut());
+    hash
+}
+
+/// 生成请求签名私钥并写入新文件（仅当前用户可读写，不覆盖已有文件）
+///
+/// # 返回值
+/// * `std::io::Result<String>` - 十六进制公钥
+pub fn generate_signing_key<P: AsRef<Path>>(path: P) -> std::io::Result<String> {
+    let key = SigningKey::generate(&mut OsRng);
+    let content = Zeroizing::new(encode_hex(&key.to_bytes()));
+    platform::create_private(path, content.as_bytes())?;
+    Ok(encode_hex(key.verifying_key().as_bytes()))
+}
+
+/// 用私钥文件为请求签名，返回需合并到请求中的字段
+///
+/// # 参数
+/// * `key_path` - 私钥文件
+/// * `command` - 命令名称
+/// * `argument` - 命令参数
+/// * `now` - 当前时间（Unix秒）
+pub fn sign_request<P: AsRef<Path>>(key_path: P, command: &str, argument: &str, now: i64) -> Result<serde_json::Value, String> {
+    let content = Zeroizing::new(fs::read_to_string(key_path).map_err(|e| e.to_string())?);
+    let seed: [u8; 32] = decode_hex(content.trim())
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or("私钥文件不是32字节十六进制")?;
+    let key = SigningKey::from_bytes(&seed);
+
+    let mut nonce = [0u8; 16];
+    OsRng.fill_bytes(&mut nonce);
+    let nonce = encode_hex(&nonce);
+    let signature = key.sign(signed_message(command, argument, now, &nonce).as_bytes());
+    Ok(serde_json::json!({
+        "command": command,
+        "timestamp": now,
+        "nonce": nonce,
+        "signature": encode_hex(&signature.to_bytes()),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> IpcSecurityConfig {
+        IpcSecurityConfig { passphrase_hash: Some(format_hash("correct horse", b"salt", 10)), ..Default::default() }
+    }
+
+    #[test]
+    fn test_passphrase() {
+        let guard = RequestGuard::new(config());
+        let proof = |passphrase| Proof { passphrase, ..Default::default() };
+        assert_eq!(guard.check("status", "", &proof(None), 0), Ok(None));
+        assert_eq!(guard.check("nuke", "", &proof(None), 0), Err(SecurityError::Required));
+        assert_eq!(guard.check("nuke", "", &proof(Some("wrong")), 0), Err(SecurityError::InvalidPassphrase));
+        assert_eq!(guard.check("pause", "", &proof(Some("correct horse")), 0), Ok(Some(Method::Passphrase)));
+        assert_eq!(guard.check("cancel_nuke", "", &proof(None), 0), Err(SecurityError::Required));
+        assert!(hash_passphrase("x").starts_with("pbkdf2-sha256$100000$"));
+    }
+
+    #[test]
+    fn test_passphrase_rate_limit() {
+        let guard = RequestGuard::new(config());
+        let proof = |passphrase| Proof { passphrase: Some(passphrase), ..Default::default() };
+        for _ in 0..crate::lockout::MAX_FAILURES {
+            assert_eq!(guard.check("nuke", "", &proof("wrong"), 100), Err(SecurityError::InvalidPassphrase));
+        }
+        // 暂停期内正确的口令同样被拒绝
+        assert_eq!(guard.check("nuke", "", &proof("correct horse"), 130), Err(SecurityError::RateLimited(30)));
+        assert_eq!(guard.check("nuke", "", &proof("correct horse"), 160), Ok(Some(Method::Passphrase)));
+        assert_eq!(guard.check("nuke", "", &proof("wrong"), 161), Err(SecurityError::InvalidPassphrase));
+    }
+
+    #[test]
+    fn test_unsigned_stop_rejected() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let key_path = temp_dir.path().join("ipc.key");
+        let public_key = generate_signing_key(&key_path).unwrap();
+        let guard = RequestGuard::new(IpcSecurityConfig { signing_public_key: Some(public_key), ..Default::default() });
+
+        assert_eq!(guard.check("stop", "", &Proof::default(), 1_000), Err(SecurityError::Required));
+
+        // 为其他命令签名的请求不能用于停止服务
+        let signed = sign_request(&key_path, "pause", "", 1_000).unwrap();
+        let (nonce, signature) = (signed["nonce"].as_str().unwrap(), signed["signature"].as_str().unwrap());
+        let proof = Proof { timestamp: Some(1_000), nonce: Some(nonce), signature: Some(signature), passphrase: None };
+        assert_eq!(guard.check("stop", "", &proof, 1_000), Err(SecurityError::InvalidSignature));
+
+        let signed = sign_request(&key_path, "stop", "", 1_000).unwrap();
+        let (nonce, signature) = (signed["nonce"].as_str().unwrap(), signed["signature"].as_str().unwrap());
+        let proof = Proof { timestamp: Some(1_000), nonce: Some(nonce), signature: Some(signature), passphrase: None };
+        assert_eq!(guard.check("stop", "", &proof, 1_000), Ok(Some(Method::Signature)));
+    }
+
+    #[test]
+    fn test_protected_commands_are_validated() {
+        let config: IpcSecurityConfig = serde_json::from_str(r#"{"protected_commands": ["nuke", "cancel_nuke"]}"#).unwrap();
+        assert_eq!(config.protected_commands, [ProtectedCommand::Nuke, ProtectedCommand::CancelNuke]);
+        assert!(serde_json::from_str::<IpcSecurityConfig>(r#"{"protected_commands": ["nuk"]}"#).is_err());
+    }
+
+    #[test]
+    fn test_signed_request() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let key_path = temp_dir.path().join("ipc.key");
+        let public_key = generate_signing_key(&key_path).unwrap();
+        let guard = RequestGuard::new(IpcSecurityConfig { signing_public_key: Some(public_key), ..config() });
+
+        let signed = sign_request(&key_path, "schedule_nuke", "18:00", 1_000).unwrap();
+        let (nonce, signature) = (signed["nonce"].as_str().unwrap(), signed["signature"].as_str().unwrap());
+        let proof = |timestamp| Proof { timestamp: Some(timestamp), nonce: Some(nonce), signature: Some(signature), passphrase: None };
+
+        assert_eq!(guard.check("schedule_nuke", "19:00", &proof(1_000), 1_010), Err(SecurityError::InvalidSignature));
+        assert_eq!(guard.check("schedule_nuke", "18:00", &proof(1_000), 1_100), Err(SecurityError::Stale));
+        assert_eq!(guard.check("schedule_nuke", "18:00", &proof(1_000), 1_010), Ok(Some(Method::Signature)));
+        assert_eq!(guard.check("schedule_nuke", "18:00", &proof(1_000), 1_020), Err(SecurityError::Replayed));
+        assert_eq!(guard.check("schedule_nuke", "18:00", &proof(i64::MIN), i64::MAX), Err(SecurityError::Stale));
+    }
+}
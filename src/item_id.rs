@@ -0,0 +1,145 @@
+/*!
+ * ClipVanish™ 条目ID模块
+ *
+ * 为每个受保护条目分配稳定的ULID，历史记录、事件、审计日志、控制接口与命令行都用它指代同一条目
+ * 特点：
+ * - 48位毫秒时间戳 + 80位随机数，26位Crockford Base32文本，按字典序即按时间排序
+ * - 同一毫秒内生成的ID单调递增，跨会话也不会重复
+ * - 解析时不区分大小写
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::sync::Mutex;
+use aes_gcm_siv::aead::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Crockford Base32字母表（不含 I、L、O、U）
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// ULID文本长度
+const ENCODED_LEN: usize = 26;
+
+/// 最近生成的ID（保证同一毫秒内单调递增）
+static LAST: Mutex<u128> = Mutex::new(0);
+
+/// 受保护条目ID（ULID）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ItemId(u128);
+
+impl ItemId {
+    /// 生成新的条目ID
+    pub fn generate() -> Self {
+        let millis = chrono::Utc::now().timestamp_millis().max(0) as u128;
+        let mut random = [0u8; 16];
+        OsRng.fill_bytes(&mut random[6..]);
+        let candidate = (millis << 80) | u128::from_be_bytes(random);
+
+        let mut last = LAST.lock().unwrap();
+        // 同一毫秒（或时钟回拨）时在上一个ID基础上递增
+        let id = if candidate > *last { candidate } else { *last + 1 };
+        *last = id;
+        ItemId(id)
+    }
+
+    /// 生成时间（ULID中的毫秒时间戳）
+    pub fn created_at(self) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::from_timestamp_millis((self.0 >> 80) as i64).unwrap_or_default()
+    }
+
+    /// 16字节大端表示（用于紧凑存储）
+    pub fn to_bytes(self) -> [u8; 16] {
+        self.0.to_be_bytes()
+    }
+
+    /// 从16字节大端表示还原
+    ///
+    /// # 参数
+    /// * `bytes` - `to_bytes` 的结果
+    pub fn from_bytes(bytes: [u8; 16]) -> Self {
+        ItemId(u128::from_be_bytes(bytes))
+    }
+}
+
+impl std::fmt::Display for ItemId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut text = [0u8; ENCODED_LEN];
+        for (index, byte) in text.iter_mut().enumerate() {
+            let shift = 5 * (ENCODED_LEN - 1 - index);
+            *byte = ALPHABET[((self.0 >> shift) & 0x1f) as usize];
+        }
+        f.write_str(std::str::from_utf8(&text).unwrap_or_default())
+    }
+}
+
+impl std::str::FromStr for ItemId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("无效的条目ID: {}（需要26位ULID）", s);
+        if s.len() != ENCODED_LEN {
+            return Err(invalid());
+        }
+
+        // 首位只能表示高3位，超出时会溢出128位
+        let mut value: u128 = 0;
+        for (index, c) in s.bytes().enumerate() {
+            let digit = ALPHABET
+                .iter()
+                .position(|&a| a == c.to_ascii_uppercase())
+                .ok_or_else(invalid)? as u128;
+            if index == 0 && digit > 7 {
+                return Err(invalid());
+            }
+            value = (value << 5) | digit;
+        }
+        Ok(ItemId(value))
+    }
+}
+
+impl Serialize for ItemId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ItemId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_parsing() {
+        let id = ItemId::generate();
+        let text = id.to_string();
+        assert_eq!(text.len(), 26);
+        assert_eq!(text.parse::<ItemId>().unwrap(), id);
+        assert_eq!(text.to_lowercase().parse::<ItemId>().unwrap(), id);
+
+        assert_eq!(ItemId(0).to_string(), "00000000000000000000000000");
+        assert_eq!(ItemId(u128::MAX).to_string(), "7ZZZZZZZZZZZZZZZZZZZZZZZZZ");
+        assert!("8ZZZZZZZZZZZZZZZZZZZZZZZZZ".parse::<ItemId>().is_err());
+        assert!("01ARZ3NDEKTSV4RRFFQ69G5FAU".parse::<ItemId>().is_err());
+        assert!("42".parse::<ItemId>().is_err());
+
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(serde_json::from_str::<ItemId>(&json).unwrap(), id);
+    }
+
+    #[test]
+    fn test_ids_are_monotonic() {
+        let ids: Vec<ItemId> = (0..100).map(|_| ItemId::generate()).collect();
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(ids.windows(2).all(|pair| pair[0].to_string() < pair[1].to_string()));
+
+        let before = chrono::Utc::now() - chrono::Duration::milliseconds(1);
+        assert!(ItemId::generate().created_at() >= before);
+    }
+}
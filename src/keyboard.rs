@@ -3,9 +3,10 @@
  *
  * 实现全局键盘事件监听，特别是粘贴快捷键的检测
  * 支持：
- * - macOS: Cmd+V (使用 CGEventTap)
- * - Windows: Ctrl+V (使用 SetWindowsHookEx)
- * - Linux: Ctrl+V (使用 X11)
+ * - macOS: Cmd+V、Cmd+Shift+V (使用 CGEventTap)
+ * - Windows: Ctrl+V、Ctrl+Shift+V、Shift+Insert (使用 SetWindowsHookEx)
+ * - Linux: Ctrl+V、Ctrl+Shift+V、Shift+Insert与鼠标中键 (使用 X11)
+ * - 快捷键识别与模拟输入按系统当前的键盘布局换算按键（AZERTY、Dvorak等）
  *
  * 作者: ClipVanish Team
  */
@@ -15,10 +16,19 @@ use std::time::Instant;
 use log::{info, warn, debug, error};
 use tokio::sync::mpsc;
 use rdev::{simulate, EventType, Key};
-use clipboard::{ClipboardProvider, ClipboardContext};
+use clipboard::ClipboardProvider;
+use crate::paste_context::PasteContext;
+use crate::paste_gestures::{self, Input, Modifiers, PasteGesture};
+#[cfg(target_os = "windows")]
+use crate::elevation;
+use crate::paste_strategy::{self, PasteError, PasteStrategy};
+use crate::clipboard_actor::{ClipboardActor, Priority};
+#[cfg(target_os = "macos")]
+use crate::platform::macos::pasteboard::PasteboardSnapshot;
 
 // 平台特定的模块
 mod platform;
+mod keymap;
 
 /// 键盘事件类型
 #[derive(Debug, Clone)]
@@ -26,8 +36,15 @@ pub enum KeyboardEvent {
     /// 粘贴操作检测到
     PasteDetected {
         timestamp: Instant,
-        /// 使用的快捷键组合
-        key_combination: String,
+        /// 使用的粘贴手势
+        gesture: PasteGesture,
+        /// 焦点窗口所在的虚拟桌面与显示器
+        context: PasteContext,
+    },
+    /// 剪贴板历史快捷键（Win+V）
+    #[cfg(windows)]
+    ClipboardHistoryShortcut {
+        timestamp: Instant,
     },
     /// 其他快捷键
     OtherShortcut {
@@ -133,35 +150,31 @@ impl KeyboardMonitor {
         *self.should_stop.lock().unwrap() = true;
     }
 
-    /// 手动触发粘贴检测
-    /// 这个方法可以被外部调用来模拟粘贴事件
-    pub fn trigger_paste_detection(&self, key_combination: &str) {
-        debug!("手动触发粘贴检测: {}", key_combination);
-
-        if let Some(callback) = &*self.event_callback.lock().unwrap() {
-            let event = KeyboardEvent::PasteDetected {
-                timestamp: Instant::now(),
-                key_combination: key_combination.to_string(),
-            };
-            callback(event);
-        }
-    }
-
     /// 安全粘贴文本到当前焦点窗口
     ///
-    /// 使用临时剪贴板替换的方式来支持所有字符（包括中文、emoji等）
+    /// 使用临时剪贴板替换的方式来支持所有字符（包括中文、emoji等）；
+    /// 备份、替换、发送粘贴命令与恢复作为一条命令执行，期间不会插入其他剪贴板读写；
+    /// macOS上按全部条目与类型恢复，其他平台只恢复纯文本。发送失败时同样先恢复剪贴板再返回错误
     ///
     /// # 参数
     /// * `text` - 要粘贴的文本
-    /// * `clipboard_ctx` - 剪贴板上下文的引用
+    /// * `clipboard` - 剪贴板访问任务
+    /// * `strategy` - 发送粘贴命令的方式（keystroke 或 wm_paste）
     ///
     /// # 返回值
-    /// * `Result<(), Box<dyn std::error::Error>>` - 操作结果
+    /// * `Result<(), PasteError>` - 操作结果，错误信息中附带建议的粘贴方式
     pub fn secure_paste_text(
         text: &str,
-        clipboard_ctx: &Arc<Mutex<ClipboardContext>>
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        info!("开始安全粘贴文本，长度: {} 字符", text.chars().count());
+        clipboard: &ClipboardActor,
+        strategy: PasteStrategy,
+    ) -> Result<(), PasteError> {
+        info!("开始安全粘贴文本，长度: {} 字符，方式: {}", text.chars().count(), strategy);
+
+        // 目标程序权限更高时模拟输入会被系统静默丢弃，提前报告
+        #[cfg(target_os = "windows")]
+        if let Some(app) = elevation::foreground_mismatch() {
+            return Err(PasteError::ElevationMismatch { app });
+        }
 
         // 设置粘贴进行状态，防止递归调用
         Self::set_paste_in_progress(true);
@@ -169,67 +182,45 @@ impl KeyboardMonitor {
         // 等待一小段时间确保粘贴快捷键释放
         std::thread::sleep(std::time::Duration::from_millis(20));
 
-        // 1. 备份当前剪贴板内容
-        let original_content = {
-            let mut ctx = clipboard_ctx.lock().unwrap();
-            ctx.get_contents().unwrap_or_default()
-        };
+        let text = text.to_string();
+        let result = clipboard.run(Priority::Restore, move |ctx| -> Result<(), PasteError> {
+            // 1. 备份当前剪贴板内容（macOS上快照全部条目与类型，恢复时保留富文本、文件URL等）
+            #[cfg(target_os = "macos")]
+            let snapshot = PasteboardSnapshot::capture()
+                .map_err(|e| warn!("快照剪贴板失败，只能恢复纯文本: {}", e))
+                .ok();
+            let original_content = ctx.get_contents().unwrap_or_default();
 
-        // 2. 临时设置要粘贴的内容到剪贴板
-        {
-            let mut ctx = clipboard_ctx.lock().unwrap();
-            ctx.set_contents(text.to_string())?;
-        }
+            // 2. 临时设置要粘贴的内容到剪贴板
+            ctx.set_contents(text).map_err(|e| PasteError::Clipboard(e.to_string()))?;
 
-        // 3. 等待一小段时间确保剪贴板内容已更新
-        std::thread::sleep(std::time::Duration::from_millis(5));
+            // 3. 等待一小段时间确保剪贴板内容已更新
+            std::thread::sleep(std::time::Duration::from_millis(5));
 
-        // 4. 直接发送粘贴命令而不是模拟按键（避免递归调用）
-        // 使用系统API发送粘贴命令
-        #[cfg(target_os = "windows")]
-        {
-            // Windows: 发送 WM_PASTE 消息到当前焦点窗口
-            use std::process::Command;
-            let _ = Command::new("powershell")
-                .args(&["-Command", "Add-Type -AssemblyName System.Windows.Forms; [System.Windows.Forms.SendKeys]::SendWait('^v')"])
-                .output();
-        }
-
-        #[cfg(target_os = "macos")]
-        {
-            // macOS: 使用 osascript 发送粘贴命令
-            use std::process::Command;
-            let _ = Command::new("osascript")
-                .args(&["-e", "tell application \"System Events\" to keystroke \"v\" using command down"])
-                .output();
-        }
+            // 4. 直接发送粘贴命令而不是模拟按键（避免递归调用）
+            let sent = paste_strategy::send_paste(strategy);
 
-        #[cfg(target_os = "linux")]
-        {
-            // Linux: 使用 xdotool 发送粘贴命令
-            use std::process::Command;
-            let _ = Command::new("xdotool")
-                .args(&["key", "ctrl+v"])
-                .output();
-        }
-
-        // 5. 等待粘贴操作完成（减少延迟）
-        std::thread::sleep(std::time::Duration::from_millis(30));
+            // 5. 等待粘贴操作完成（减少延迟）
+            if sent.is_ok() {
+                std::thread::sleep(std::time::Duration::from_millis(30));
+            }
 
-        // 6. 立即恢复原始剪贴板内容
-        {
-            let mut ctx = clipboard_ctx.lock().unwrap();
-            if !original_content.is_empty() {
-                ctx.set_contents(original_content)?;
-            } else {
-                // 如果原来是空的，清空剪贴板
-                ctx.set_contents("".to_string())?;
+            // 6. 立即恢复原始剪贴板内容（原来为空时清空剪贴板）
+            #[cfg(target_os = "macos")]
+            if let Some(snapshot) = snapshot {
+                match snapshot.restore() {
+                    Ok(_) => return sent,
+                    Err(e) => warn!("恢复剪贴板快照失败，改为恢复纯文本: {}", e),
+                }
             }
-        }
+            ctx.set_contents(original_content).map_err(|e| PasteError::Clipboard(e.to_string()))?;
+            sent
+        });
 
         // 清除粘贴进行状态
         Self::set_paste_in_progress(false);
 
+        result.map_err(|e| PasteError::Clipboard(e.to_string()))??;
         info!("安全粘贴完成，剪贴板已恢复");
         Ok(())
     }
@@ -264,7 +255,7 @@ impl KeyboardMonitor {
         }
     }
 
-    /// 直接输入文本到当前焦点窗口（仅支持ASCII字符）
+    /// 直接输入文本到当前焦点窗口（仅支持当前键盘布局可直接输入的字符）
     ///
     /// # 参数
     /// * `text` - 要输入的文本
@@ -274,105 +265,60 @@ impl KeyboardMonitor {
     pub fn simulate_text_input(text: &str) -> Result<(), Box<dyn std::error::Error>> {
         info!("开始模拟文本输入，长度: {} 字符", text.chars().count());
 
-        // 检查是否包含非ASCII字符
-        if !text.is_ascii() {
-            return Err("文本包含非ASCII字符，无法直接输入".into());
-        }
+        // 按当前布局换算按键，先检查全部字符，避免只输入一部分
+        let layout = keymap::current();
+        let keystrokes = text
+            .chars()
+            .map(|ch| layout.keystroke(ch).ok_or_else(|| format!("字符 '{}' 无法在当前键盘布局下直接输入", ch)))
+            .collect::<Result<Vec<_>, _>>()?;
 
         // 等待一小段时间确保粘贴快捷键释放
         std::thread::sleep(std::time::Duration::from_millis(50));
 
-        // 逐字符输入文本
-        for ch in text.chars() {
-            // 将字符转换为键盘事件
-            if let Some(key) = char_to_key(ch) {
-                // 模拟按键按下
-                simulate(&EventType::KeyPress(key))?;
-                // 模拟按键释放
-                simulate(&EventType::KeyRelease(key))?;
-
-                // 在字符之间添加小延迟，避免输入过快
-                std::thread::sleep(std::time::Duration::from_millis(1));
-            } else {
-                // 对于无法直接映射的字符，返回错误
-                return Err(format!("字符 '{}' 无法直接映射到按键", ch).into());
+        for (key, shift) in keystrokes {
+            if shift {
+                simulate(&EventType::KeyPress(Key::ShiftLeft))?;
             }
+            simulate(&EventType::KeyPress(key))?;
+            simulate(&EventType::KeyRelease(key))?;
+            if shift {
+                simulate(&EventType::KeyRelease(Key::ShiftLeft))?;
+            }
+
+            // 在字符之间添加小延迟，避免输入过快
+            std::thread::sleep(std::time::Duration::from_millis(1));
         }
 
         info!("文本输入完成");
         Ok(())
     }
+}
 
-
+impl Default for KeyboardMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-/// 将字符转换为对应的按键
+/// 按配置识别粘贴手势，识别到时通知回调（由各平台的钩子回调调用）
 ///
 /// # 参数
-/// * `ch` - 要转换的字符
-///
-/// # 返回值
-/// * `Option<Key>` - 对应的按键，如果无法映射则返回None
-fn char_to_key(ch: char) -> Option<Key> {
-    match ch {
-        // 字母
-        'a' | 'A' => Some(Key::KeyA),
-        'b' | 'B' => Some(Key::KeyB),
-        'c' | 'C' => Some(Key::KeyC),
-        'd' | 'D' => Some(Key::KeyD),
-        'e' | 'E' => Some(Key::KeyE),
-        'f' | 'F' => Some(Key::KeyF),
-        'g' | 'G' => Some(Key::KeyG),
-        'h' | 'H' => Some(Key::KeyH),
-        'i' | 'I' => Some(Key::KeyI),
-        'j' | 'J' => Some(Key::KeyJ),
-        'k' | 'K' => Some(Key::KeyK),
-        'l' | 'L' => Some(Key::KeyL),
-        'm' | 'M' => Some(Key::KeyM),
-        'n' | 'N' => Some(Key::KeyN),
-        'o' | 'O' => Some(Key::KeyO),
-        'p' | 'P' => Some(Key::KeyP),
-        'q' | 'Q' => Some(Key::KeyQ),
-        'r' | 'R' => Some(Key::KeyR),
-        's' | 'S' => Some(Key::KeyS),
-        't' | 'T' => Some(Key::KeyT),
-        'u' | 'U' => Some(Key::KeyU),
-        'v' | 'V' => Some(Key::KeyV),
-        'w' | 'W' => Some(Key::KeyW),
-        'x' | 'X' => Some(Key::KeyX),
-        'y' | 'Y' => Some(Key::KeyY),
-        'z' | 'Z' => Some(Key::KeyZ),
-
-        // 数字
-        '0' => Some(Key::Num0),
-        '1' => Some(Key::Num1),
-        '2' => Some(Key::Num2),
-        '3' => Some(Key::Num3),
-        '4' => Some(Key::Num4),
-        '5' => Some(Key::Num5),
-        '6' => Some(Key::Num6),
-        '7' => Some(Key::Num7),
-        '8' => Some(Key::Num8),
-        '9' => Some(Key::Num9),
-
-        // 常用符号
-        ' ' => Some(Key::Space),
-        '\n' => Some(Key::Return),
-        '\t' => Some(Key::Tab),
-        '.' => Some(Key::Dot),
-        ',' => Some(Key::Comma),
-        ';' => Some(Key::SemiColon),
-        '\'' => Some(Key::Quote),
-        '[' => Some(Key::LeftBracket),
-        ']' => Some(Key::RightBracket),
-        '\\' => Some(Key::BackSlash),
-        '/' => Some(Key::Slash),
-        '=' => Some(Key::Equal),
-        '-' => Some(Key::Minus),
-        '`' => Some(Key::BackQuote),
-
-        // 其他字符暂不支持直接映射
-        _ => None,
+/// * `modifiers` - 修饰键状态
+/// * `input` - 按下的按键或鼠标按钮
+/// * `callback` - 事件回调
+fn dispatch_paste(modifiers: Modifiers, input: Input, callback: &Mutex<Option<KeyboardEventCallback>>) {
+    let Some(gesture) = paste_gestures::detect(modifiers, input) else {
+        return;
+    };
+    info!("🔍 检测到 {} 粘贴", gesture);
+    let paste_event = KeyboardEvent::PasteDetected {
+        timestamp: Instant::now(),
+        gesture,
+        context: PasteContext::current(),
+    };
+
+    if let Some(callback) = &*callback.lock().unwrap() {
+        callback(paste_event);
     }
 }
 
@@ -0,0 +1,225 @@
+/*!
+ * 键盘布局映射
+ *
+ * rdev上报的按键按美式QWERTY命名，与用户实际使用的布局无关：AZERTY、Dvorak等布局下，
+ * 产生字母V的按键不一定是 `Key::KeyV`，按 `Key::KeyA` 模拟输入得到的也不一定是a
+ * 特点：
+ * - 通过系统键盘布局（X11 XLookupString、Windows ToUnicodeEx、macOS UCKeyTranslate，经由rdev）查询每个按键产生的字符
+ * - 快捷键识别：当前布局中产生该字母的按键；俄语、希腊语等非拉丁布局下与应用程序一致，退回美式布局的位置
+ * - 模拟输入：按字符找到按键以及是否需要Shift，当前布局无法直接输入的字符返回None
+ * - 查询不经过输入法（X11以 `@im=none` 打开输入上下文），中日韩输入法开启时得到的仍是底层布局
+ * - 布局缓存数秒后重新查询，切换布局后很快生效
+ */
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use log::debug;
+use rdev::{EventType, Key, Keyboard, KeyboardState};
+
+/// 布局缓存的有效时间
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 参与布局映射的按键（与布局表中字符的顺序一致）
+const KEYS: [Key; 48] = [
+    Key::BackQuote, Key::Num1, Key::Num2, Key::Num3, Key::Num4, Key::Num5, Key::Num6,
+    Key::Num7, Key::Num8, Key::Num9, Key::Num0, Key::Minus, Key::Equal,
+    Key::KeyQ, Key::KeyW, Key::KeyE, Key::KeyR, Key::KeyT, Key::KeyY, Key::KeyU,
+    Key::KeyI, Key::KeyO, Key::KeyP, Key::LeftBracket, Key::RightBracket,
+    Key::KeyA, Key::KeyS, Key::KeyD, Key::KeyF, Key::KeyG, Key::KeyH, Key::KeyJ,
+    Key::KeyK, Key::KeyL, Key::SemiColon, Key::Quote, Key::BackSlash, Key::IntlBackslash,
+    Key::KeyZ, Key::KeyX, Key::KeyC, Key::KeyV, Key::KeyB, Key::KeyN, Key::KeyM,
+    Key::Comma, Key::Dot, Key::Slash,
+];
+
+/// 单个按键产生的字符
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct KeyChars {
+    key: Key,
+    /// 不按Shift时的字符（死键或不产生字符时为None）
+    base: Option<char>,
+    /// 按住Shift时的字符
+    shifted: Option<char>,
+}
+
+/// 键盘布局
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    keys: Vec<KeyChars>,
+}
+
+impl Layout {
+    /// 按 `KEYS` 的顺序由两行字符构造布局（`\0` 表示该按键不产生字符）
+    ///
+    /// # 参数
+    /// * `base` - 不按Shift时的字符
+    /// * `shifted` - 按住Shift时的字符
+    fn from_rows(base: &str, shifted: &str) -> Self {
+        let present = |ch: char| (ch != '\0').then_some(ch);
+        Layout {
+            keys: KEYS
+                .iter()
+                .zip(base.chars().zip(shifted.chars()))
+                .map(|(&key, (base, shifted))| KeyChars { key, base: present(base), shifted: present(shifted) })
+                .collect(),
+        }
+    }
+
+    /// 美式QWERTY布局（无法查询系统布局时使用）
+    pub fn us() -> Self {
+        Layout::from_rows(
+            "`1234567890-=qwertyuiop[]asdfghjkl;'\\\0zxcvbnm,./",
+            "~!@#$%^&*()_+QWERTYUIOP{}ASDFGHJKL:\"|\0ZXCVBNM<>?",
+        )
+    }
+
+    /// 查询系统当前的键盘布局（没有图形会话等无法查询时返回None）
+    pub fn query() -> Option<Self> {
+        let mut keyboard = Keyboard::new()?;
+        let keys: Vec<KeyChars> = KEYS
+            .iter()
+            .map(|&key| {
+                keyboard.reset();
+                let base = single_char(keyboard.add(&EventType::KeyPress(key)));
+                keyboard.reset();
+                keyboard.add(&EventType::KeyPress(Key::ShiftLeft));
+                let shifted = single_char(keyboard.add(&EventType::KeyPress(key)));
+                KeyChars { key, base, shifted }
+            })
+            .collect();
+        keyboard.reset();
+        keys.iter().any(|chars| chars.base.is_some()).then_some(Layout { keys })
+    }
+
+    /// 产生该字母（不区分大小写）的按键
+    fn key_for_letter(&self, letter: char) -> Option<Key> {
+        self.keys
+            .iter()
+            .find(|chars| chars.base.is_some_and(|base| base.to_lowercase().eq(letter.to_lowercase())))
+            .map(|chars| chars.key)
+    }
+
+    /// 按键与修饰键组合时是否是该字母的快捷键（如Ctrl+V中的V）
+    ///
+    /// 当前布局中没有该字母（非拉丁布局）时，与应用程序一样按美式布局的位置判断
+    ///
+    /// # 参数
+    /// * `key` - rdev上报的按键
+    /// * `letter` - 快捷键字母
+    pub fn is_shortcut(&self, key: Key, letter: char) -> bool {
+        self.key_for_letter(letter)
+            .or_else(|| Layout::us().key_for_letter(letter))
+            .is_some_and(|shortcut| shortcut == key)
+    }
+
+    /// 输入字符所需的按键以及是否需要按住Shift
+    ///
+    /// # 参数
+    /// * `ch` - 要输入的字符
+    ///
+    /// # 返回值
+    /// * `Option<(Key, bool)>` - 按键与是否需要Shift，当前布局无法直接输入时返回None
+    pub fn keystroke(&self, ch: char) -> Option<(Key, bool)> {
+        match ch {
+            ' ' => return Some((Key::Space, false)),
+            '\n' => return Some((Key::Return, false)),
+            '\t' => return Some((Key::Tab, false)),
+            _ => {}
+        }
+        self.keys.iter().find_map(|chars| {
+            if chars.base == Some(ch) {
+                Some((chars.key, false))
+            } else if chars.shifted == Some(ch) {
+                Some((chars.key, true))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// 只产生单个可见字符时返回该字符
+fn single_char(name: Option<String>) -> Option<char> {
+    let name = name?;
+    let mut chars = name.chars();
+    let ch = chars.next()?;
+    (chars.next().is_none() && !ch.is_control()).then_some(ch)
+}
+
+/// 缓存的布局与查询时间
+static CURRENT: Mutex<Option<(Instant, Arc<Layout>)>> = Mutex::new(None);
+
+/// 当前的键盘布局（缓存超过 `REFRESH_INTERVAL` 后重新查询，查询失败时使用美式布局）
+pub fn current() -> Arc<Layout> {
+    let mut current = CURRENT.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some((queried_at, layout)) = current.as_ref() {
+        if queried_at.elapsed() < REFRESH_INTERVAL {
+            return layout.clone();
+        }
+    }
+    let layout = Arc::new(Layout::query().unwrap_or_else(|| {
+        debug!("无法查询键盘布局，按美式布局处理");
+        Layout::us()
+    }));
+    *current = Some((Instant::now(), layout.clone()));
+    layout
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 法语AZERTY（X11 `fr`，`^` 为死键）
+    fn azerty() -> Layout {
+        Layout::from_rows(
+            "²&é\"'(-è_çà)=azertyuiop\0$qsdfghjklmù*<wxcvbn,;:!",
+            "~1234567890°+AZERTYUIOP\0£QSDFGHJKLM%µ>WXCVBN?./§",
+        )
+    }
+
+    /// 美式Dvorak（X11 `us(dvorak)`）
+    fn dvorak() -> Layout {
+        Layout::from_rows(
+            "`1234567890[]',.pyfgcrl/=aoeuidhtns-\\\0;qjkxbmwvz",
+            "~!@#$%^&*(){}\"<>PYFGCRL?+AOEUIDHTNS_|\0:QJKXBMWVZ",
+        )
+    }
+
+    /// 俄语ЙЦУКЕН（X11 `ru`）
+    fn russian() -> Layout {
+        Layout::from_rows(
+            "ё1234567890-=йцукенгшщзхъфывапролджэ\\\0ячсмитьбю.",
+            "Ё!\"№;%:?*()_+ЙЦУКЕНГШЩЗХЪФЫВАПРОЛДЖЭ/\0ЯЧСМИТЬБЮ,",
+        )
+    }
+
+    #[test]
+    fn test_shortcut_keys() {
+        assert!(Layout::us().is_shortcut(Key::KeyV, 'v'));
+        assert!(azerty().is_shortcut(Key::KeyV, 'v'));
+        // AZERTY上Ctrl+A位于QWERTY的Q键
+        assert!(azerty().is_shortcut(Key::KeyQ, 'a'));
+        assert!(!azerty().is_shortcut(Key::KeyA, 'a'));
+        // Dvorak的V位于QWERTY的句点键
+        assert!(dvorak().is_shortcut(Key::Dot, 'v'));
+        assert!(!dvorak().is_shortcut(Key::KeyV, 'v'));
+        // 非拉丁布局退回美式布局的位置
+        assert!(russian().is_shortcut(Key::KeyV, 'v'));
+    }
+
+    #[test]
+    fn test_keystrokes() {
+        let azerty = azerty();
+        assert_eq!(azerty.keystroke('a'), Some((Key::KeyQ, false)));
+        assert_eq!(azerty.keystroke('A'), Some((Key::KeyQ, true)));
+        assert_eq!(azerty.keystroke('1'), Some((Key::Num1, true)));
+        assert_eq!(azerty.keystroke('m'), Some((Key::SemiColon, false)));
+        assert_eq!(azerty.keystroke('é'), Some((Key::Num2, false)));
+        assert_eq!(azerty.keystroke(' '), Some((Key::Space, false)));
+
+        assert_eq!(dvorak().keystroke('v'), Some((Key::Dot, false)));
+        assert_eq!(Layout::us().keystroke('?'), Some((Key::Slash, true)));
+        // 俄语布局无法直接输入拉丁字母
+        assert_eq!(russian().keystroke('a'), None);
+        assert_eq!(russian().keystroke('ф'), Some((Key::KeyA, false)));
+    }
+}
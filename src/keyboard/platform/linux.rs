@@ -1,15 +1,15 @@
 /*!
  * Linux 键盘事件监听实现
  *
- * 使用 rdev 库监听全局键盘事件
- * 检测 Ctrl+V 粘贴快捷键
+ * 使用 rdev 库监听全局键盘与鼠标事件
+ * 检测 Ctrl+V、Ctrl+Shift+V、Shift+Insert 粘贴快捷键与鼠标中键粘贴
  */
 
 use std::sync::{Arc, Mutex, OnceLock};
-use std::time::Instant;
 use log::{info, warn, debug, error};
-use rdev::{listen, Event, EventType, Key};
-use crate::keyboard::{KeyboardEvent, KeyboardEventCallback};
+use rdev::{listen, Button, Event, EventType, Key};
+use crate::keyboard::{dispatch_paste, keymap, KeyboardEventCallback};
+use crate::paste_gestures::{Input, Modifiers};
 
 /// 修饰键状态
 #[derive(Debug, Clone, Default)]
@@ -19,6 +19,12 @@ struct ModifierState {
     shift_pressed: bool,
 }
 
+impl ModifierState {
+    fn modifiers(&self) -> Modifiers {
+        Modifiers { primary: self.ctrl_pressed, shift: self.shift_pressed, alt: self.alt_pressed }
+    }
+}
+
 // 全局状态，用于在回调函数中访问
 static GLOBAL_MODIFIER_STATE: OnceLock<Arc<Mutex<ModifierState>>> = OnceLock::new();
 static GLOBAL_SHOULD_STOP: OnceLock<Arc<Mutex<bool>>> = OnceLock::new();
@@ -62,22 +68,18 @@ fn global_keyboard_callback(event: Event) {
                         state.shift_pressed = true;
                         debug!("Shift 键按下");
                     },
-                    Key::KeyV => {
-                        if state.ctrl_pressed && !state.alt_pressed {
-                            info!("🔍 检测到 Ctrl+V 粘贴快捷键");
-                            let paste_event = KeyboardEvent::PasteDetected {
-                                timestamp: Instant::now(),
-                                key_combination: "Ctrl+V".to_string(),
-                            };
-
-                            if let Some(callback) = &*callback_arc.lock().unwrap() {
-                                callback(paste_event);
-                            }
-                        }
+                    Key::Insert => dispatch_paste(state.modifiers(), Input::Insert, callback_arc),
+                    // 按当前布局判断哪个按键是V
+                    key if state.ctrl_pressed && keymap::current().is_shortcut(key, 'v') => {
+                        dispatch_paste(state.modifiers(), Input::KeyV, callback_arc)
                     },
                     _ => {}
                 }
             },
+            EventType::ButtonPress(Button::Middle) => {
+                let modifiers = state_arc.lock().unwrap().modifiers();
+                dispatch_paste(modifiers, Input::MiddleButton, callback_arc);
+            },
             EventType::KeyRelease(key) => {
                 let mut state = state_arc.lock().unwrap();
 
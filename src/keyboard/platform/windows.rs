@@ -2,14 +2,15 @@
  * Windows 键盘事件监听实现
  *
  * 使用 rdev 库监听全局键盘事件
- * 检测 Ctrl+V 粘贴快捷键
+ * 检测 Ctrl+V、Ctrl+Shift+V、Shift+Insert 粘贴快捷键与 Win+V 剪贴板历史快捷键
  */
 
 use std::sync::{Arc, Mutex, OnceLock};
 use std::time::Instant;
 use log::{info, warn, debug, error};
 use rdev::{listen, Event, EventType, Key};
-use crate::keyboard::{KeyboardEvent, KeyboardEventCallback};
+use crate::keyboard::{dispatch_paste, keymap, KeyboardEvent, KeyboardEventCallback};
+use crate::paste_gestures::{Input, Modifiers};
 
 /// 修饰键状态
 #[derive(Debug, Clone, Default)]
@@ -17,6 +18,13 @@ struct ModifierState {
     ctrl_pressed: bool,
     alt_pressed: bool,
     shift_pressed: bool,
+    meta_pressed: bool,
+}
+
+impl ModifierState {
+    fn modifiers(&self) -> Modifiers {
+        Modifiers { primary: self.ctrl_pressed, shift: self.shift_pressed, alt: self.alt_pressed }
+    }
 }
 
 // 全局状态，用于在回调函数中访问
@@ -62,19 +70,25 @@ fn global_keyboard_callback(event: Event) {
                         state.shift_pressed = true;
                         debug!("Shift 键按下");
                     },
-                    Key::KeyV => {
-                        if state.ctrl_pressed && !state.alt_pressed {
-                            info!("🔍 检测到 Ctrl+V 粘贴快捷键");
-                            let paste_event = KeyboardEvent::PasteDetected {
-                                timestamp: Instant::now(),
-                                key_combination: "Ctrl+V".to_string(),
-                            };
-
-                            if let Some(callback) = &*callback_arc.lock().unwrap() {
-                                callback(paste_event);
-                            }
+                    Key::MetaLeft | Key::MetaRight => {
+                        state.meta_pressed = true;
+                        debug!("Win 键按下");
+                    },
+                    key if state.meta_pressed && keymap::current().is_shortcut(key, 'v') => {
+                        info!("🔍 检测到 Win+V 剪贴板历史快捷键");
+                        let history_event = KeyboardEvent::ClipboardHistoryShortcut {
+                            timestamp: Instant::now(),
+                        };
+
+                        if let Some(callback) = &*callback_arc.lock().unwrap() {
+                            callback(history_event);
                         }
                     },
+                    Key::Insert => dispatch_paste(state.modifiers(), Input::Insert, callback_arc),
+                    // 按当前布局判断哪个按键是V
+                    key if state.ctrl_pressed && keymap::current().is_shortcut(key, 'v') => {
+                        dispatch_paste(state.modifiers(), Input::KeyV, callback_arc)
+                    },
                     _ => {}
                 }
             },
@@ -94,6 +108,10 @@ fn global_keyboard_callback(event: Event) {
                         state.shift_pressed = false;
                         debug!("Shift 键释放");
                     },
+                    Key::MetaLeft | Key::MetaRight => {
+                        state.meta_pressed = false;
+                        debug!("Win 键释放");
+                    },
                     _ => {}
                 }
             },
@@ -0,0 +1,168 @@
+/*!
+ * ClipVanish™ 内核密钥环模块
+ *
+ * Linux上将会话密钥保存在内核进程密钥环（keyctl）中，而不是进程堆内存
+ * 特点：
+ * - 进程密钥环只由本进程持有，密钥权限只授予持有者（possessor），同一用户的其他进程（包括同一登录会话中的）无法查看或读取
+ * - 进程退出（包括异常终止）时内核随进程密钥环一起丢弃密钥，不会残留在用户的会话密钥环中
+ * - 每次加解密时临时取出，用完立即擦除，直接转储进程堆内存得不到会话密钥
+ * - 密钥销毁时立即作废（KEYCTL_INVALIDATE，旧内核回退为撤销）
+ * - 内核不支持或被seccomp等拦截时返回错误，由调用方回退为进程内存保存
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::io;
+use zeroize::Zeroizing;
+use crate::crypto::SecureKey;
+
+/// 内核密钥环中的一个会话密钥
+pub struct KeyringKey {
+    /// 密钥序列号
+    serial: i32,
+}
+
+impl KeyringKey {
+    /// 将密钥存入进程密钥环
+    ///
+    /// # 参数
+    /// * `key` - 会话密钥
+    ///
+    /// # 返回值
+    /// * `io::Result<KeyringKey>` - 内核密钥环不可用时返回错误
+    pub fn store(key: &SecureKey) -> io::Result<Self> {
+        sys::add(key.as_bytes()).map(|serial| KeyringKey { serial })
+    }
+
+    /// 取出密钥（调用方用完即丢弃，`SecureKey` 销毁时擦除）
+    pub fn load(&self) -> io::Result<SecureKey> {
+        let mut key_data = Zeroizing::new([0u8; 32]);
+        let length = sys::read(self.serial, key_data.as_mut())?;
+        if length != key_data.len() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("密钥长度异常: {} 字节", length)));
+        }
+        Ok(SecureKey::from_bytes(*key_data))
+    }
+}
+
+impl Drop for KeyringKey {
+    fn drop(&mut self) {
+        if let Err(e) = sys::invalidate(self.serial) {
+            log::warn!("作废内核密钥环中的会话密钥失败: {}", e);
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use std::ffi::CString;
+    use std::io;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    /// 进程密钥环（首次使用时由内核创建，仅本进程持有）
+    const KEY_SPEC_PROCESS_KEYRING: libc::c_long = -2;
+
+    const KEYCTL_SETPERM: libc::c_long = 5;
+    const KEYCTL_REVOKE: libc::c_long = 3;
+    const KEYCTL_READ: libc::c_long = 11;
+    const KEYCTL_INVALIDATE: libc::c_long = 21;
+
+    /// 持有者可查看、读取、更新、搜索与设置属性；不允许链接到其他密钥环，用户、组与其他人无任何权限
+    const POSSESSOR_ONLY: libc::c_long = 0x2f00_0000;
+
+    /// 密钥描述序号（同一进程内唯一）
+    static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+    pub fn add(payload: &[u8]) -> io::Result<i32> {
+        let description = CString::new(format!(
+            "clipvanish:session:{}:{}",
+            std::process::id(),
+            SEQUENCE.fetch_add(1, Ordering::Relaxed)
+        ))
+        .expect("描述中不含NUL");
+
+        let serial = unsafe {
+            libc::syscall(
+                libc::SYS_add_key,
+                c"user".as_ptr(),
+                description.as_ptr(),
+                payload.as_ptr(),
+                payload.len(),
+                KEY_SPEC_PROCESS_KEYRING,
+            )
+        };
+        if serial < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let serial = serial as i32;
+
+        // 新建的user密钥对用户只有查看权限，收紧为仅持有者可访问
+        if unsafe { libc::syscall(libc::SYS_keyctl, KEYCTL_SETPERM, serial as libc::c_long, POSSESSOR_ONLY) } < 0 {
+            let e = io::Error::last_os_error();
+            let _ = invalidate(serial);
+            return Err(e);
+        }
+        Ok(serial)
+    }
+
+    pub fn read(serial: i32, buffer: &mut [u8]) -> io::Result<usize> {
+        let length = unsafe {
+            libc::syscall(libc::SYS_keyctl, KEYCTL_READ, serial as libc::c_long, buffer.as_mut_ptr(), buffer.len())
+        };
+        if length < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(length as usize)
+    }
+
+    pub fn invalidate(serial: i32) -> io::Result<()> {
+        if unsafe { libc::syscall(libc::SYS_keyctl, KEYCTL_INVALIDATE, serial as libc::c_long) } == 0 {
+            return Ok(());
+        }
+        // 3.5以前的内核没有KEYCTL_INVALIDATE
+        if unsafe { libc::syscall(libc::SYS_keyctl, KEYCTL_REVOKE, serial as libc::c_long) } == 0 {
+            return Ok(());
+        }
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod sys {
+    use std::io;
+
+    fn unsupported() -> io::Error {
+        io::Error::new(io::ErrorKind::Unsupported, "仅Linux支持内核密钥环")
+    }
+
+    pub fn add(_payload: &[u8]) -> io::Result<i32> {
+        Err(unsupported())
+    }
+
+    pub fn read(_serial: i32, _buffer: &mut [u8]) -> io::Result<usize> {
+        Err(unsupported())
+    }
+
+    pub fn invalidate(_serial: i32) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyring_round_trip_when_available() {
+        let key = SecureKey::generate().unwrap();
+        // 容器默认的seccomp配置会拦截keyctl，此时由调用方回退
+        let Ok(stored) = KeyringKey::store(&key) else {
+            return;
+        };
+        assert_eq!(stored.load().unwrap().as_bytes(), key.as_bytes());
+
+        let serial = stored.serial;
+        drop(stored);
+        assert!(sys::read(serial, &mut [0u8; 32]).is_err());
+    }
+}
@@ -0,0 +1,250 @@
+/*!
+ * ClipVanish™ 本地偏好学习模块
+ *
+ * 记录用户对特定"内容形态"的覆盖操作（解除保护、延长倒计时），并据此调整后续分类
+ * 特点：
+ * - 只记录匿名特征：长度区间、字符集类别、来源应用，从不记录内容本身
+ * - 数据仅保存在本机配置目录
+ * - 同一形态的覆盖次数达到阈值后才生效
+ * - 支持查看与重置已学习的偏好
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::config::Config;
+use crate::platform;
+use crate::entropy::Charset;
+
+/// 偏好学习配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LearningConfig {
+    /// 是否根据已学习的偏好调整分类
+    #[serde(default = "default_learning_enabled")]
+    pub enabled: bool,
+    /// 同一形态的覆盖次数达到此值后生效
+    #[serde(default = "default_learning_threshold")]
+    pub threshold: u32,
+}
+
+fn default_learning_enabled() -> bool {
+    true
+}
+
+fn default_learning_threshold() -> u32 {
+    3
+}
+
+impl Default for LearningConfig {
+    fn default() -> Self {
+        LearningConfig {
+            enabled: default_learning_enabled(),
+            threshold: default_learning_threshold(),
+        }
+    }
+}
+
+/// 内容形态（匿名特征向量）
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ContentShape {
+    /// 长度区间（按2的幂分桶，值为区间下限）
+    pub length_bucket: usize,
+    /// 字符集类别
+    pub charset: String,
+    /// 来源应用
+    pub source_app: Option<String>,
+}
+
+impl ContentShape {
+    /// 提取内容形态
+    ///
+    /// # 参数
+    /// * `content` - 内容（只用于计算特征，不会被保存）
+    /// * `source_app` - 来源应用
+    pub fn of(content: &str, source_app: Option<&str>) -> Self {
+        let len = content.chars().count();
+        let length_bucket = if len == 0 { 0 } else { 1 << (usize::BITS - 1 - len.leading_zeros()) };
+
+        let charset = if content.chars().any(char::is_whitespace) {
+            "text"
+        } else {
+            match Charset::detect(content) {
+                Charset::Hex => "hex",
+                Charset::Base64 => "base64",
+                Charset::Other => "symbols",
+            }
+        };
+
+        ContentShape {
+            length_bucket,
+            charset: charset.to_string(),
+            source_app: source_app.map(str::to_string),
+        }
+    }
+}
+
+impl std::fmt::Display for ContentShape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "长度{}-{} {} 来自{}",
+            self.length_bucket,
+            (self.length_bucket * 2).max(1) - 1,
+            self.charset,
+            self.source_app.as_deref().unwrap_or("未知应用")
+        )
+    }
+}
+
+/// 单个形态的学习记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LearnedPreference {
+    /// 内容形态
+    pub shape: ContentShape,
+    /// 解除保护次数
+    pub unprotects: u32,
+    /// 延长倒计时次数
+    pub extensions: u32,
+    /// 累计延长时长（秒）
+    pub extended_seconds: u64,
+}
+
+/// 学习结果对分类的影响
+#[derive(Debug, Clone, PartialEq)]
+pub enum Bias {
+    /// 不调整
+    None,
+    /// 跳过保护
+    SkipProtection,
+    /// 延长存活时间
+    ExtendLifetime(Duration),
+}
+
+/// 已学习的偏好
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PreferenceStore {
+    /// 全部记录
+    preferences: Vec<LearnedPreference>,
+}
+
+impl PreferenceStore {
+    /// 默认存储路径
+    pub fn default_path() -> Option<PathBuf> {
+        Config::get_config_directory()
+            .ok()
+            .map(|dir| dir.join("learned_preferences.json"))
+    }
+
+    /// 从文件加载（文件不存在或损坏时返回空存储）
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        platform::read_state_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// 保存到文件
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(std::io::Error::from)?;
+        platform::write_state(path, content.as_bytes())
+    }
+
+    /// 记录一次解除保护
+    pub fn record_unprotect(&mut self, shape: ContentShape) {
+        self.entry(shape).unprotects += 1;
+    }
+
+    /// 记录一次延长倒计时
+    pub fn record_extension(&mut self, shape: ContentShape, extra: Duration) {
+        let entry = self.entry(shape);
+        entry.extensions += 1;
+        entry.extended_seconds += extra.as_secs();
+    }
+
+    /// 根据已学习的偏好计算分类调整
+    ///
+    /// # 参数
+    /// * `shape` - 内容形态
+    /// * `threshold` - 生效所需的覆盖次数
+    pub fn bias(&self, shape: &ContentShape, threshold: u32) -> Bias {
+        self.preferences
+            .iter()
+            .find(|entry| &entry.shape == shape)
+            .map(|entry| Self::bias_of(entry, threshold))
+            .unwrap_or(Bias::None)
+    }
+
+    /// 单条记录对分类的影响
+    pub fn bias_of(entry: &LearnedPreference, threshold: u32) -> Bias {
+        if entry.unprotects >= threshold && entry.unprotects > entry.extensions {
+            Bias::SkipProtection
+        } else if entry.extensions >= threshold {
+            Bias::ExtendLifetime(Duration::from_secs(entry.extended_seconds / entry.extensions as u64))
+        } else {
+            Bias::None
+        }
+    }
+
+    /// 全部记录
+    pub fn preferences(&self) -> &[LearnedPreference] {
+        &self.preferences
+    }
+
+    /// 获取或创建形态记录
+    fn entry(&mut self, shape: ContentShape) -> &mut LearnedPreference {
+        let index = match self.preferences.iter().position(|entry| entry.shape == shape) {
+            Some(index) => index,
+            None => {
+                self.preferences.push(LearnedPreference {
+                    shape,
+                    unprotects: 0,
+                    extensions: 0,
+                    extended_seconds: 0,
+                });
+                self.preferences.len() - 1
+            }
+        };
+        &mut self.preferences[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shape_is_anonymous() {
+        let shape = ContentShape::of("deadbeefcafe0123", Some("Terminal"));
+        assert_eq!(shape.length_bucket, 16);
+        assert_eq!(shape.charset, "hex");
+        assert_eq!(ContentShape::of("hello world", None).charset, "text");
+        // 同一区间、同一字符集的不同内容形态相同
+        assert_eq!(shape, ContentShape::of("0123456789abcdef0", Some("Terminal")));
+    }
+
+    #[test]
+    fn test_bias_after_threshold() {
+        let shape = ContentShape::of("ghp_xK9mQ2vLp7RtZ4wN8bYc3JfH", Some("Browser"));
+        let mut store = PreferenceStore::default();
+
+        store.record_unprotect(shape.clone());
+        store.record_unprotect(shape.clone());
+        assert_eq!(store.bias(&shape, 3), Bias::None);
+        store.record_unprotect(shape.clone());
+        assert_eq!(store.bias(&shape, 3), Bias::SkipProtection);
+
+        let other = ContentShape::of("ghp_xK9mQ2vLp7RtZ4wN8bYc3JfH", Some("Terminal"));
+        for _ in 0..3 {
+            store.record_extension(other.clone(), Duration::from_secs(60));
+        }
+        assert_eq!(store.bias(&other, 3), Bias::ExtendLifetime(Duration::from_secs(60)));
+        assert_eq!(store.preferences().len(), 2);
+    }
+}
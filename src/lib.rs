@@ -0,0 +1,111 @@
+/*!
+ * ClipVanish™ 功能模块
+ *
+ * 剪贴板监听、加密、定时擦除等全部功能模块，由主程序（main.rs）调用
+ * 特点：
+ * - 命令行入口之外的代码都在这里，基准测试等独立目标可以直接链接
+ * - 可选功能按Cargo特性与目标平台裁剪
+ *
+ * 作者: ClipVanish Team
+ */
+
+pub mod crypto;
+pub mod keyring;
+pub mod clipboard;
+pub mod clipboard_actor;
+pub mod timer;
+pub mod clock;
+pub mod memory;
+pub mod erase_scheduler;
+pub mod cli;
+pub mod config;
+#[cfg(feature = "keyboard-hooks")]
+pub mod keyboard;
+pub mod doctor;
+pub mod daemon;
+pub mod report;
+pub mod audit;
+pub mod backup;
+pub mod formats;
+pub mod crash;
+pub mod shutdown;
+pub mod prompt;
+pub mod build_info;
+pub mod quarantine;
+pub mod source_app;
+#[cfg(target_os = "windows")]
+pub mod elevation;
+pub mod session;
+pub mod win_history;
+pub mod handoff;
+pub mod entropy;
+pub mod classifier;
+pub mod learning;
+pub mod rule_trial;
+pub mod rule_pack;
+#[cfg(all(target_os = "macos", feature = "menubar"))]
+pub mod menubar;
+pub mod output;
+pub mod paste_context;
+pub mod forensics;
+pub mod encoding;
+pub mod escrow;
+pub mod policy;
+pub mod soak;
+pub mod redteam;
+pub mod tasks;
+#[cfg(target_os = "windows")]
+pub mod delayed_render;
+pub mod schedule;
+pub mod duration;
+pub mod repl;
+pub mod history;
+pub mod stats;
+pub mod advisor;
+pub mod watch;
+pub mod overrides;
+pub mod virtual_clipboard;
+pub mod capabilities;
+pub mod countdown_display;
+pub mod overlay;
+pub mod paste_confirm;
+pub mod paste_gestures;
+pub mod notifications;
+pub mod paste_strategy;
+pub mod paste_transform;
+pub mod escalation;
+pub mod lifetime_cap;
+pub mod login_pages;
+pub mod webhook;
+pub mod mqtt;
+pub mod status_page;
+pub mod canary;
+pub mod editor;
+pub mod clipboard_managers;
+pub mod decoy;
+#[cfg(target_os = "windows")]
+pub mod reader_watch;
+pub mod nuke_guard;
+pub mod item_id;
+pub mod notes;
+pub mod helpers;
+pub mod redact;
+pub mod supervisor;
+pub mod confinement;
+pub mod platform;
+pub mod health;
+pub mod safe_mode;
+#[cfg(all(target_os = "linux", feature = "portal"))]
+pub mod dbus;
+#[cfg(all(target_os = "linux", feature = "portal"))]
+pub mod portal_clipboard;
+#[cfg(feature = "api")]
+pub mod ipc_auth;
+pub mod ipc_security;
+pub mod lockout;
+#[cfg(all(unix, feature = "api"))]
+pub mod ipc;
+#[cfg(all(unix, feature = "api"))]
+pub mod companion;
+#[cfg(all(unix, feature = "api"))]
+pub mod agent;
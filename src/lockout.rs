@@ -0,0 +1,77 @@
+/*!
+ * ClipVanish™ 失败锁定模块
+ *
+ * 口令、验证码等可被反复尝试的校验共用的失败计数，限制暴力尝试
+ * 特点：
+ * - 连续失败达到上限后暂停校验，暂停期从最近一次失败起算，期间正确的输入同样被拒绝
+ * - 暂停期内不再计算哈希或验证码，尝试不消耗CPU
+ * - 可序列化，需要跨进程生效的调用方把它与其他状态一起落盘
+ *
+ * 作者: ClipVanish Team
+ */
+
+use serde::{Deserialize, Serialize};
+
+/// 连续失败达到此次数后暂停校验
+pub const MAX_FAILURES: u32 = 5;
+
+/// 暂停校验的时长（秒，每次失败后重新计时）
+pub const LOCKOUT_SECONDS: i64 = 60;
+
+/// 连续失败计数
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FailureLockout {
+    /// 连续失败次数
+    failures: u32,
+    /// 最近一次失败的时间（Unix秒）
+    last_failure: i64,
+}
+
+impl FailureLockout {
+    /// 暂停期的剩余秒数，未暂停时为None
+    ///
+    /// # 参数
+    /// * `now` - 当前时间（Unix秒）
+    pub fn locked_for(&self, now: i64) -> Option<u64> {
+        if self.failures < MAX_FAILURES {
+            return None;
+        }
+        let remaining = self.last_failure.saturating_add(LOCKOUT_SECONDS).saturating_sub(now);
+        (remaining > 0).then_some(remaining as u64)
+    }
+
+    /// 记录一次失败
+    pub fn record_failure(&mut self, now: i64) {
+        self.failures = self.failures.saturating_add(1);
+        self.last_failure = now;
+    }
+
+    /// 校验通过后清零
+    pub fn reset(&mut self) {
+        *self = FailureLockout::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lockout_after_max_failures() {
+        let mut lockout = FailureLockout::default();
+        for attempt in 0..MAX_FAILURES {
+            assert_eq!(lockout.locked_for(100), None);
+            lockout.record_failure(100 + attempt as i64);
+        }
+        let last = 100 + MAX_FAILURES as i64 - 1;
+        assert_eq!(lockout.locked_for(last + 10), Some((LOCKOUT_SECONDS - 10) as u64));
+        assert_eq!(lockout.locked_for(last + LOCKOUT_SECONDS), None);
+
+        // 暂停期后再次失败重新计时
+        lockout.record_failure(last + LOCKOUT_SECONDS);
+        assert!(lockout.locked_for(last + LOCKOUT_SECONDS + 1).is_some());
+
+        lockout.reset();
+        assert_eq!(lockout.locked_for(last + LOCKOUT_SECONDS + 1), None);
+    }
+}
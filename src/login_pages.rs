@@ -0,0 +1,217 @@
+/*!
+ * ClipVanish™ 登录页面检测模块
+ *
+ * 不依赖浏览器扩展，按前台浏览器窗口的标题（浏览器标题会暴露当前页面）推测复制是否发生在登录或网银页面，
+ * 在这些页面上提高敏感度并按站点缩短存活时间
+ * 特点：
+ * - 只在前台应用是浏览器时读取窗口标题；标题只在内存中比较，从不写入日志或审计记录
+ * - 内置常见登录与网银站点，可在配置中追加站点；标题含"登录""Sign in"等关键词的页面同样视为登录页面
+ * - 登录页面上未命中任何规则、但形似口令或验证码的内容同样受保护（规则 `login_page`）
+ * - 按站点的清除时间作为该条目的存活上限，延长与固定都不能越过
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use crate::lifetime_cap::LifetimeLimit;
+
+/// 登录页面上形似口令的内容命中的规则名称
+pub const LOGIN_PAGE_RULE: &str = "login_page";
+
+/// 单个站点
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginSite {
+    /// 域名（标题中出现该域名即匹配）
+    pub domain: String,
+    /// 该站点页面标题中的特征文字（不区分大小写）
+    #[serde(default)]
+    pub titles: Vec<String>,
+    /// 该站点的清除时间（秒，未设置时使用 `clear_delay_seconds`）
+    #[serde(default)]
+    pub clear_delay_seconds: Option<u64>,
+}
+
+impl LoginSite {
+    fn new(domain: &str, titles: &[&str], clear_delay_seconds: Option<u64>) -> Self {
+        LoginSite {
+            domain: domain.to_string(),
+            titles: titles.iter().map(|title| title.to_string()).collect(),
+            clear_delay_seconds,
+        }
+    }
+
+    /// 标题（已转为小写）是否属于该站点
+    fn matches(&self, title: &str) -> bool {
+        title.contains(&self.domain.to_lowercase())
+            || self.titles.iter().any(|pattern| title.contains(&pattern.to_lowercase()))
+    }
+}
+
+/// 内置站点
+fn builtin_sites() -> Vec<LoginSite> {
+    vec![
+        LoginSite::new("accounts.google.com", &["Google Accounts", "Google 帐号", "Sign in - Google"], None),
+        LoginSite::new("login.microsoftonline.com", &["Sign in to your account", "登录到你的帐户"], None),
+        LoginSite::new("login.live.com", &["Microsoft account", "Microsoft 帐户"], None),
+        LoginSite::new("appleid.apple.com", &["Apple ID", "Apple Account"], None),
+        LoginSite::new("github.com/login", &["Sign in to GitHub"], None),
+        LoginSite::new("paypal.com", &["PayPal"], Some(15)),
+        LoginSite::new("alipay.com", &["支付宝"], Some(15)),
+        LoginSite::new("chase.com", &["Chase Online"], Some(15)),
+        LoginSite::new("bankofamerica.com", &["Bank of America"], Some(15)),
+        LoginSite::new("wellsfargo.com", &["Wells Fargo"], Some(15)),
+        LoginSite::new("icbc.com.cn", &["工商银行"], Some(15)),
+        LoginSite::new("cmbchina.com", &["招商银行"], Some(15)),
+    ]
+}
+
+/// 登录页面检测配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoginPagesConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 浏览器进程/应用名称（不区分大小写的子串）
+    pub browsers: Vec<String>,
+    /// 视为登录页面的标题关键词（不区分大小写）
+    pub keywords: Vec<String>,
+    /// 登录页面上条目的清除时间（秒）
+    pub clear_delay_seconds: u64,
+    /// 是否使用内置站点
+    pub builtin_sites: bool,
+    /// 追加的站点（先于内置站点匹配）
+    pub sites: Vec<LoginSite>,
+}
+
+impl Default for LoginPagesConfig {
+    fn default() -> Self {
+        LoginPagesConfig {
+            enabled: true,
+            browsers: ["firefox", "chrome", "chromium", "msedge", "microsoft edge", "brave", "safari", "opera", "vivaldi"]
+                .iter()
+                .map(|browser| browser.to_string())
+                .collect(),
+            keywords: ["sign in", "log in", "login", "online banking", "登录", "登入", "网上银行"]
+                .iter()
+                .map(|keyword| keyword.to_string())
+                .collect(),
+            clear_delay_seconds: 20,
+            builtin_sites: true,
+            sites: Vec::new(),
+        }
+    }
+}
+
+impl LoginPagesConfig {
+    /// 前台应用是否是浏览器
+    ///
+    /// # 参数
+    /// * `app` - 前台应用名称
+    pub fn is_browser(&self, app: &str) -> bool {
+        let app = app.to_lowercase();
+        self.browsers.iter().any(|browser| app.contains(&browser.to_lowercase()))
+    }
+
+    /// 判断复制是否发生在登录页面
+    ///
+    /// # 参数
+    /// * `app` - 前台应用名称
+    /// * `title` - 读取前台窗口标题（只在前台应用是浏览器时调用）
+    pub fn page_for(&self, app: Option<&str>, title: impl FnOnce() -> Option<String>) -> Option<LoginPage> {
+        if !self.enabled || !app.is_some_and(|app| self.is_browser(app)) {
+            return None;
+        }
+        let title = title()?.to_lowercase();
+
+        let builtin = if self.builtin_sites { builtin_sites() } else { Vec::new() };
+        if let Some(site) = self.sites.iter().chain(builtin.iter()).find(|site| site.matches(&title)) {
+            return Some(LoginPage {
+                site: site.domain.clone(),
+                clear_delay: Duration::from_secs(site.clear_delay_seconds.unwrap_or(self.clear_delay_seconds)),
+            });
+        }
+        self.keywords
+            .iter()
+            .any(|keyword| title.contains(&keyword.to_lowercase()))
+            .then(|| LoginPage { site: "登录页面".to_string(), clear_delay: Duration::from_secs(self.clear_delay_seconds) })
+    }
+}
+
+/// 复制发生的登录页面
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoginPage {
+    /// 站点域名（按关键词判断时为"登录页面"），不含窗口标题
+    pub site: String,
+    /// 清除时间
+    pub clear_delay: Duration,
+}
+
+impl LoginPage {
+    /// 作为条目存活上限
+    ///
+    /// # 参数
+    /// * `copied_at` - 复制时间
+    pub fn limit(&self, copied_at: Instant) -> LifetimeLimit {
+        LifetimeLimit {
+            class: format!("{}:{}", LOGIN_PAGE_RULE, self.site),
+            max: self.clear_delay,
+            until: copied_at + self.clear_delay,
+        }
+    }
+}
+
+/// 内容是否形似口令或验证码（单个词、长度适中）
+///
+/// # 参数
+/// * `content` - 复制的内容
+pub fn looks_like_credential(content: &str) -> bool {
+    let content = content.trim();
+    (6..=128).contains(&content.chars().count())
+        && !content.chars().any(char::is_whitespace)
+        && !content.starts_with("http://")
+        && !content.starts_with("https://")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_for() {
+        let config = LoginPagesConfig {
+            sites: vec![LoginSite::new("intranet.example.com", &["Example SSO"], Some(5))],
+            ..Default::default()
+        };
+        let title = |title: &'static str| move || Some(title.to_string());
+
+        let page = config.page_for(Some("firefox"), title("Example SSO — Mozilla Firefox")).unwrap();
+        assert_eq!(page, LoginPage { site: "intranet.example.com".to_string(), clear_delay: Duration::from_secs(5) });
+        assert_eq!(config.page_for(Some("Google Chrome"), title("PayPal: Log in - Google Chrome")).unwrap().site, "paypal.com");
+
+        let generic = config.page_for(Some("msedge.exe"), title("Sign In | Acme Portal - Microsoft Edge")).unwrap();
+        assert_eq!(generic.clear_delay, Duration::from_secs(20));
+
+        assert_eq!(config.page_for(Some("firefox"), title("Rust Documentation - Mozilla Firefox")), None);
+        // 不是浏览器时不读取标题
+        assert_eq!(config.page_for(Some("Terminal"), || panic!("不应读取标题")), None);
+        assert_eq!(config.page_for(None, || panic!("不应读取标题")), None);
+
+        let disabled = LoginPagesConfig { enabled: false, ..config };
+        assert_eq!(disabled.page_for(Some("firefox"), title("PayPal")), None);
+    }
+
+    #[test]
+    fn test_credential_shape_and_limit() {
+        assert!(looks_like_credential("Tr0ub4dor&3"));
+        assert!(looks_like_credential(" 123456\n"));
+        assert!(!looks_like_credential("12345"));
+        assert!(!looks_like_credential("correct horse battery staple"));
+        assert!(!looks_like_credential("https://example.com/login"));
+
+        let now = Instant::now();
+        let limit = LoginPage { site: "paypal.com".to_string(), clear_delay: Duration::from_secs(15) }.limit(now);
+        assert_eq!(limit.class, "login_page:paypal.com");
+        assert_eq!(limit.until, now + Duration::from_secs(15));
+    }
+}
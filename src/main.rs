@@ -10,25 +10,46 @@
 use clap::{Parser, Subcommand};
 use log::{info, error, warn};
 use std::process;
+use std::time::Duration;
 use tokio;
-use std::io::{self, Write};
 
-mod crypto;
-mod clipboard;
-mod timer;
-mod memory;
-mod cli;
-mod config;
-mod keyboard;
-
-use crate::cli::CliHandler;
-use crate::config::Config;
+use clipvanish::{
+    build_info, canary, classifier, crash, daemon, duration, health, helpers, item_id, memory,
+    notes, notifications, output, overrides, paste_gestures, redact, stats, virtual_clipboard,
+};
+#[cfg(target_os = "windows")]
+use clipvanish::elevation;
+#[cfg(all(target_os = "macos", feature = "menubar"))]
+use clipvanish::menubar;
+#[cfg(feature = "api")]
+use clipvanish::ipc_auth;
+use clipvanish::cli::CliHandler;
+use clipvanish::cli::EscrowAction;
+use clipvanish::cli::AuditAction;
+use clipvanish::cli::FormatsAction;
+use clipvanish::cli::BackupAction;
+use clipvanish::cli::CanaryAction;
+use clipvanish::cli::GuardAction;
+use clipvanish::cli::StatsAction;
+use clipvanish::cli::NoteAction;
+use clipvanish::cli::ConfigAction;
+use clipvanish::cli::RulesAction;
+#[cfg(feature = "api")]
+use clipvanish::cli::TokenAction;
+use clipvanish::clipboard::NukeScope;
+use clipvanish::audit::ExportFormat;
+use clipvanish::config::{Config, ConfigError, EXIT_INVALID_CONFIG};
+use clipvanish::overrides::ConfigOverride;
+use clipvanish::shutdown::Trigger;
+use clipvanish::output::{notice, OutputFormat};
+use clipvanish::repl::LineEditor;
 
 /// ClipVanish™ 命令行参数定义
 #[derive(Parser)]
 #[command(
     name = "clipvanish",
     version = "0.1.0",
+    disable_version_flag = true,
     about = "ClipVanish™ - 物理级自毁剪贴板工具",
     long_about = "全球首款「物理级自毁」剪贴板工具，实现隐私数据的秒级自动销毁。\n支持AES-256加密、倒计时自毁、一键紧急销毁等功能。"
 )]
@@ -40,13 +61,45 @@ struct Args {
     #[arg(short, long)]
     verbose: bool,
     
-    /// 静默模式（最小输出）
-    #[arg(short, long)]
+    /// 静默模式（不输出横幅与状态提示，仅保留机器输出和错误）
+    #[arg(short, long, global = true)]
     silent: bool,
     
+    /// 机器输出格式：text 或 json（提示信息始终写入stderr）
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+    
     /// 交互模式
     #[arg(short, long)]
     interactive: bool,
+    
+    /// 日志过滤表达式（env_logger语法，例如 clipvanish::keyboard=warn）
+    #[arg(long, value_name = "FILTER")]
+    log_filter: Option<String>,
+    
+    /// 覆盖配置项（可重复，如 --set timer.default_countdown=10），优先于环境变量与配置文件
+    #[arg(long = "set", value_name = "KEY=VALUE", global = true)]
+    set: Vec<String>,
+    
+    /// 无头模式：使用进程内虚拟剪贴板，用于容器与CI等没有图形环境的场景
+    #[arg(long, global = true)]
+    headless: bool,
+    
+    /// 严格模式：禁止启动 osascript/xdotool/xclip/powershell 等辅助程序，相关功能回退或停用
+    #[arg(long, global = true)]
+    no_subprocess: bool,
+    
+    /// 跳过所有确认提示（用于脚本和非交互环境）
+    #[arg(short = 'y', long, global = true)]
+    yes: bool,
+    
+    /// 显示版本信息
+    #[arg(short = 'V', long)]
+    version: bool,
+    
+    /// 以JSON格式输出版本及构建元数据（与 --version 一起使用）
+    #[arg(long, requires = "version")]
+    json: bool,
 }
 
 /// 支持的命令列表
@@ -54,13 +107,21 @@ struct Args {
 enum Commands {
     /// 启动剪贴板监听和自毁服务
     Start {
-        /// 自毁倒计时（秒），默认30秒
-        #[arg(short, long, default_value = "30")]
-        timer: u64,
+        /// 自毁倒计时（如 30、90s、2m、1h30m，单独的数字按秒计），默认30秒
+        #[arg(short, long, default_value = "30s", value_parser = duration::parse)]
+        timer: Duration,
         
-        /// 后台运行模式
+        /// 后台运行模式（Unix上脱离终端，输出写入日志文件）
         #[arg(short, long)]
         daemon: bool,
+        
+        /// 与 --daemon 一起使用时不脱离终端，服务在当前进程中后台运行（交互模式或由服务管理器托管时使用）
+        #[arg(long)]
+        foreground: bool,
+        
+        /// 通过UAC提示以管理员身份重新启动（仅Windows，用于粘贴到以管理员权限运行的程序）
+        #[arg(long)]
+        elevated: bool,
     },
     
     /// 立即销毁所有剪贴板数据（紧急模式）
@@ -68,10 +129,142 @@ enum Commands {
         /// 强制模式，跳过确认
         #[arg(short, long)]
         force: bool,
+        
+        /// 在指定时间执行（如 18:00、2024-05-01 18:00），仅限交互模式
+        #[arg(long, value_name = "TIME", conflicts_with_all = ["delay", "cancel"])]
+        at: Option<String>,
+        
+        /// 在指定时长后执行（如 90s、45m、1h30m），仅限交互模式
+        #[arg(long = "in", value_name = "DURATION", conflicts_with = "cancel")]
+        delay: Option<String>,
+        
+        /// 取消已登记的定时销毁（仅限交互模式）
+        #[arg(long)]
+        cancel: bool,
+        
+        /// 只清除剪贴板中的受保护内容与撤销暂存区
+        #[arg(long, conflicts_with_all = ["history_only", "keys_only"])]
+        clipboard_only: bool,
+        
+        /// 只清除历史记录
+        #[arg(long, conflicts_with = "keys_only")]
+        history_only: bool,
+        
+        /// 只重新生成加密密钥
+        #[arg(long, conflicts_with_all = ["slots", "older_than"])]
+        keys_only: bool,
+        
+        /// 只销毁指定编号的条目（与 history 中的编号一致，如 2,3）
+        #[arg(long, value_delimiter = ',', value_name = "N")]
+        slots: Vec<usize>,
+        
+        /// 只销毁早于该时长的条目（如 10m、2h、1h30m）
+        #[arg(long, value_name = "DURATION", value_parser = parse_older_than)]
+        older_than: Option<Duration>,
+        
+        /// 双人确认的TOTP验证码（启用 nuke_guard 时需要；未提供时请求硬件密钥触摸）
+        #[arg(long, value_name = "CODE", conflicts_with = "cancel")]
+        code: Option<String>,
+    },
+    
+    /// 撤销最近一次倒计时清除（需在宽限期内）
+    Undo,
+    
+    /// 解除当前条目的保护，内容以明文保留在剪贴板中
+    Unprotect,
+    
+    /// 延长受保护条目的存活时间
+    Extend {
+        /// 延长的时长（如 60、90s、2m、1h30m，单独的数字按秒计）
+        #[arg(default_value = "60s", value_parser = duration::parse)]
+        duration: Duration,
+        
+        /// 要延长的条目ID（默认为当前条目）
+        #[arg(long)]
+        item: Option<item_id::ItemId>,
+    },
+    
+    /// 查看或重置已学习的偏好
+    Learned {
+        /// 清空已学习的偏好
+        #[arg(long)]
+        reset: bool,
+    },
+    
+    /// 查看新规则试运行状态，或将规则转为强制执行；导入导出规则包
+    #[command(args_conflicts_with_subcommands = true)]
+    Rules {
+        /// 将规则转为强制执行（省略规则名时为最近一次只警告的规则）
+        #[arg(long)]
+        promote: bool,
+        
+        /// 规则名称（sensitive_pattern、entropy_rule）
+        rule: Option<String>,
+        
+        #[command(subcommand)]
+        action: Option<RulesAction>,
+    },
+    
+    /// 按剪贴板保护使用的同一套规则分类内容（命中且强制执行时退出码为3）
+    Classify {
+        /// 要分类的内容
+        #[arg(required_unless_present = "stdin", conflicts_with = "stdin")]
+        text: Option<String>,
+        
+        /// 从stdin读取内容
+        #[arg(long)]
+        stdin: bool,
+        
+        /// 以JSON输出分类结果（同 --output json）
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// 将当前剪贴板内容原样输出到stdout（受保护内容按一次粘贴处理）
+    Get,
+    
+    /// 将内容写入剪贴板（未指定时从stdin读取）
+    Put {
+        /// 要写入的内容
+        text: Option<String>,
+    },
+    
+    /// 编辑器插件参考实现：从stdin读取机密文件中的选中文本，声明后写入剪贴板并输出保护与销毁事件
+    #[cfg(all(unix, feature = "api"))]
+    EditorCopy {
+        /// 来源文件（只记录文件名）
+        #[arg(long)]
+        source: Option<String>,
+        /// 保存control令牌的文件
+        #[arg(long)]
+        token_file: std::path::PathBuf,
+    },
+    
+    /// 阅后即焚笔记：加密保存，只能读取一次
+    Note {
+        #[command(subcommand)]
+        action: NoteAction,
     },
     
     /// 显示当前运行状态
-    Status,
+    Status {
+        /// 只输出供tmux状态栏使用的紧凑倒计时（如 🔒 12s）
+        #[arg(long)]
+        tmux: bool,
+    },
+    
+    /// 持续刷新的单行状态（状态 | 条目存活 | 剩余 | 今日事件），适合放在小终端窗格中
+    Watch {
+        /// 输出纯ASCII文本，每次变化一行（便于管道与状态栏）
+        #[arg(long)]
+        plain: bool,
+        /// 刷新间隔（毫秒）
+        #[arg(long, default_value_t = 1000)]
+        interval_ms: u64,
+    },
+    
+    /// 检查运行中服务的健康状态（不健康时以非零状态退出）
+    Health,
     
     /// 停止运行中的ClipVanish服务
     Stop,
@@ -81,60 +274,288 @@ enum Commands {
         /// 重置为默认配置
         #[arg(long)]
         reset: bool,
+        
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
     },
     
     /// 查看剪贴板历史记录
     History,
     
+    /// 诊断运行环境
+    Doctor,
+    
+    /// 生成脱敏的问题报告归档（用于提交Issue）
+    Report {
+        /// 输出文件路径
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    
+    /// 导出取证归档（仅密文、审计日志与密钥指纹，带认证标签）
+    ExportForensics {
+        /// 归档文件路径
+        file: Option<String>,
+        
+        /// 校验已导出的归档
+        #[arg(long)]
+        verify: bool,
+        
+        /// 校验时使用的签名公钥（十六进制，默认为本机公钥）
+        #[arg(long, requires = "verify")]
+        key: Option<String>,
+        
+        /// 输出本机取证签名公钥（登记给安全团队用于校验）
+        #[arg(long, conflicts_with = "verify")]
+        public_key: bool,
+    },
+    
+    /// 浸泡测试：在测试剪贴板上长时间循环复制/粘贴/清除，检测泄漏与计时偏差
+    Soak {
+        /// 运行小时数（可为小数）
+        #[arg(long, default_value_t = 8.0)]
+        hours: f64,
+    },
+    
+    /// 模拟攻击测试：嗅探器高频读取剪贴板，报告能否读到明文及暴露窗口（需服务正在运行）
+    Redteam {
+        /// 写入测试内容后继续嗅探的秒数
+        #[arg(long, default_value_t = 5)]
+        seconds: u64,
+        /// 嗅探器轮询间隔（毫秒）
+        #[arg(long, default_value_t = 2)]
+        interval_ms: u64,
+    },
+    
+    /// 使用统计：根据审计日志生成本地使用报告
+    Stats {
+        #[command(subcommand)]
+        action: StatsAction,
+    },
+    
+    /// 剪贴板诱饵令牌：生成、轮换、列出，在文本中查找（发现令牌时退出码为3）
+    Canary {
+        #[command(subcommand)]
+        action: CanaryAction,
+    },
+    
+    /// 紧急销毁双人确认：生成TOTP密钥、登记硬件密钥
+    Guard {
+        #[command(subcommand)]
+        action: GuardAction,
+    },
+    
+    /// 审计日志：按时间范围导出（JSON Lines或CSV，含哈希链值）
+    Audit {
+        #[command(subcommand)]
+        action: AuditAction,
+    },
+    
+    /// 数据格式：列出写入磁盘的格式，输出机器可读的JSON Schema
+    Formats {
+        #[command(subcommand)]
+        action: FormatsAction,
+    },
+    
+    /// 备份与恢复：配置、规则、统计数据与可选的加密笔记，以口令加密（迁移到新设备时使用）
+    Backup {
+        #[command(subcommand)]
+        action: BackupAction,
+    },
+    
+    /// 密钥托管：生成恢复密钥对、解密取证归档（管理员使用）
+    Escrow {
+        #[command(subcommand)]
+        action: EscrowAction,
+    },
+    
+    /// 管理本地控制接口令牌
+    #[cfg(feature = "api")]
+    Token {
+        #[command(subcommand)]
+        action: TokenAction,
+    },
+    
+    /// 以macOS菜单栏模式运行监听服务（状态项显示倒计时，菜单提供热键动作与权限状态）
+    #[cfg(feature = "menubar")]
+    Menubar {
+        /// 自毁倒计时（如 30、90s、2m、1h30m，单独的数字按秒计），默认30秒
+        #[arg(short, long, default_value = "30s", value_parser = duration::parse)]
+        timer: Duration,
+    },
+    
     /// 退出程序
     Exit,
 }
 
-#[tokio::main]
-async fn main() {
+fn main() {
     // 解析命令行参数
-    let args = Args::parse();
+    let mut args = Args::parse();
+    
+    // 版本信息直接输出，不初始化其他组件
+    if args.version {
+        let info = build_info::BuildInfo::current();
+        if args.json || args.output == OutputFormat::Json {
+            println!("{}", info.to_json());
+        } else {
+            println!("{}", info);
+        }
+        return;
+    }
+    
+    // 提示信息写入stderr，--silent 时不输出
+    output::set_silent(args.silent);
+    
+    // 无头模式需在创建任何剪贴板上下文之前设置
+    virtual_clipboard::set_headless(args.headless);
+    
+    // 严格模式同样需在启动任何功能之前设置
+    helpers::set_strict(args.no_subprocess);
+    
+    // 命令行配置覆盖需在日志系统与配置加载之前登记
+    let cli_overrides: Result<Vec<_>, _> = args.set.iter().map(|text| ConfigOverride::parse_cli(text)).collect();
+    match cli_overrides {
+        Ok(cli_overrides) => overrides::set_cli_overrides(cli_overrides),
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            process::exit(2);
+        }
+    }
     
     // 初始化日志系统
-    init_logger(args.verbose, args.silent);
+    init_logger(args.verbose, args.silent, args.log_filter.as_deref());
+    
+    // 安装崩溃处理钩子，确保异常退出前执行安全清理
+    crash::install_panic_hook();
     
     // 显示启动信息
-    if !args.silent {
-        println!("🔒 ClipVanish™ v0.1.0 - 物理级自毁剪贴板工具");
-        println!("   作者: ClipVanish Team | MIT License\n");
-    }
+    notice!("🔒 ClipVanish™ v0.1.0 - 物理级自毁剪贴板工具");
+    notice!("   作者: ClipVanish Team | MIT License\n");
     
     // 加载配置
     let config = match Config::load() {
         Ok(cfg) => cfg,
+        Err(ConfigError::Invalid(violations)) => {
+            eprintln!("❌ 配置验证失败，共 {} 项:", violations.len());
+            for violation in &violations {
+                eprintln!("   • {}", violation);
+            }
+            process::exit(EXIT_INVALID_CONFIG);
+        }
         Err(e) => {
             error!("配置加载失败: {}", e);
             process::exit(1);
         }
     };
     
+    // 密钥、安全内存块与内存清理按配置的轮数与覆盖模式擦除
+    memory::set_erase_policy(memory::ErasePolicy::from_config(&config.security));
+    notifications::configure(config.notifications.clone());
+    paste_gestures::configure(config.paste_gestures.clone());
+    
+    // 以管理员身份重新启动时由新进程继续，当前进程直接退出
+    if let (false, Some(Commands::Start { elevated: true, .. })) = (args.interactive, &args.command) {
+        #[cfg(not(target_os = "windows"))]
+        {
+            eprintln!("❌ 无法以管理员身份启动: --elevated 仅支持Windows（其他平台没有界面特权隔离）");
+            process::exit(1);
+        }
+        #[cfg(target_os = "windows")]
+        match elevation::relaunch_elevated() {
+            Ok(true) => {
+                eprintln!("🛡️  已在新的管理员窗口中启动ClipVanish");
+                process::exit(0);
+            }
+            Ok(false) => info!("已以管理员身份运行"),
+            Err(e) => {
+                eprintln!("❌ 无法以管理员身份启动: {}", e);
+                process::exit(1);
+            }
+        }
+    }
+    
+    // 后台模式需在启动tokio运行时之前脱离终端：fork只复制当前线程，已创建的反应器在子进程中不可用
+    if let (false, Some(Commands::Start { daemon: daemon @ true, foreground: false, .. })) = (args.interactive, &mut args.command) {
+        let log_path = match Config::get_log_file_path() {
+            Ok(path) => path,
+            Err(e) => {
+                error!("无法确定日志文件路径: {}", e);
+                process::exit(1);
+            }
+        };
+        if let Err(e) = daemon::daemonize(&log_path) {
+            eprintln!("❌ {}", e);
+            process::exit(1);
+        }
+        // 已经脱离终端，服务在守护进程中按前台方式持续运行
+        *daemon = false;
+    }
+    
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            error!("启动异步运行时失败: {}", e);
+            process::exit(1);
+        }
+    };
+    
+    // 菜单栏模式：AppKit必须占用主线程，监听服务改在独立线程的运行时中运行
+    #[cfg(all(target_os = "macos", feature = "menubar"))]
+    if let (false, Some(Commands::Menubar { timer })) = (args.interactive, &args.command) {
+        let timer = timer.as_secs();
+        let (link, service) = menubar::channel();
+        let service_thread = std::thread::spawn(move || {
+            let mut cli_handler = CliHandler::new(config);
+            cli_handler.set_assume_yes(args.yes);
+            cli_handler.set_output_format(args.output);
+            cli_handler.attach_menubar(service);
+            if let Err(e) = runtime.block_on(cli_handler.start_monitoring(timer, false)) {
+                error!("命令执行失败: {}", e);
+            }
+            // 先完成会话收尾再关闭运行时，菜单栏在连接释放后退出
+            drop(cli_handler);
+            drop(runtime);
+        });
+        if let Err(e) = menubar::run(link) {
+            error!("菜单栏启动失败: {}", e);
+            process::exit(1);
+        }
+        let _ = service_thread.join();
+        return;
+    }
+    
+    runtime.block_on(run(args, config));
+}
+
+/// 在tokio运行时中创建CLI处理器并执行命令
+async fn run(args: Args, config: Config) {
     // 创建CLI处理器
+    let persist_history = config.ui.repl_history;
     let mut cli_handler = CliHandler::new(config);
+    cli_handler.set_assume_yes(args.yes);
+    cli_handler.set_output_format(args.output);
 
     if args.interactive {
         // 交互模式
-        println!("欢迎使用 ClipVanish™ 交互式命令行！");
-        println!("输入 'help' 查看可用命令，输入 'exit' 退出程序。\n");
+        eprintln!("欢迎使用 ClipVanish™ 交互式命令行！");
+        eprintln!("输入 'help' 查看可用命令，输入 'exit' 退出程序。\n");
 
+        let mut editor = LineEditor::new(persist_history);
         loop {
-            print!("clipvanish> ");
-            io::stdout().flush().unwrap();
-
-            let mut input = String::new();
-            if io::stdin().read_line(&mut input).is_err() {
-                error!("读取输入失败");
-                continue;
-            }
+            // 输入结束（Ctrl+D 或管道关闭）等同 exit
+            let line = match editor.read_line("clipvanish> ") {
+                Ok(line) => line.unwrap_or_else(|| "exit".to_string()),
+                Err(e) => {
+                    error!("读取输入失败: {}", e);
+                    continue;
+                }
+            };
 
-            let input = input.trim();
+            let input = line.trim();
             if input.is_empty() {
                 continue;
             }
+            editor.add_history(&line);
 
             // 处理帮助命令
             if input == "help" {
@@ -148,10 +569,18 @@ async fn main() {
                     command: Some(cmd),
                     verbose: args.verbose,
                     silent: args.silent,
+                    output: args.output,
                     interactive: true,
+                    log_filter: None,
+                    set: Vec::new(),
+                    headless: args.headless,
+                    no_subprocess: args.no_subprocess,
+                    yes: args.yes,
+                    version: false,
+                    json: false,
                 },
                 Err(e) => {
-                    println!("❌ 命令解析错误: {}", e);
+                    eprintln!("❌ 命令解析错误: {}", e);
                     continue;
                 }
             };
@@ -160,7 +589,9 @@ async fn main() {
             if let Some(cmd) = args.command {
                 match &cmd {
                     Commands::Exit => {
-                        println!("👋 感谢使用 ClipVanish™，再见！");
+                        CliHandler::run_shutdown(Trigger::Exit).await;
+                        cli_handler.finish_session();
+                        notice!("👋 感谢使用 ClipVanish™，再见！");
                         break;
                     }
                     _ => {
@@ -179,7 +610,7 @@ async fn main() {
                 process::exit(1);
             }
         } else {
-            println!("请使用 --help 查看使用说明");
+            eprintln!("请使用 --help 查看使用说明");
         }
     }
 }
@@ -187,20 +618,68 @@ async fn main() {
 /// 执行命令
 async fn execute_command(cli_handler: &mut CliHandler, command: Commands) -> Result<(), Box<dyn std::error::Error>> {
     match command {
-        Commands::Start { timer, daemon } => {
-            cli_handler.start_monitoring(timer, daemon).await?;
+        Commands::Start { timer, daemon, foreground: _, elevated: _ } => {
+            cli_handler.start_monitoring(timer.as_secs(), daemon).await?;
+        },
+        Commands::Nuke { force, at, delay, cancel, clipboard_only, history_only, keys_only, slots, older_than, code } => {
+            let scope = NukeScope { clipboard_only, history_only, keys_only, slots, older_than };
+            if cancel {
+                cli_handler.cancel_scheduled_nuke().await?;
+            } else if at.is_some() || delay.is_some() {
+                cli_handler.schedule_nuke(at, delay, code.as_deref()).await?;
+            } else {
+                cli_handler.emergency_nuke(force, &scope, code.as_deref()).await?;
+            }
+        },
+        Commands::Undo => {
+            cli_handler.undo_last_clear().await?;
+        },
+        Commands::Unprotect => {
+            cli_handler.unprotect_current().await?;
+        },
+        Commands::Extend { duration, item } => {
+            cli_handler.extend_item(item, duration).await?;
+        },
+        Commands::Learned { reset } => {
+            cli_handler.manage_learned(reset).await?;
+        },
+        Commands::Rules { promote, rule, action } => {
+            cli_handler.manage_rules(promote, rule, action).await?;
         },
-        Commands::Nuke { force } => {
-            cli_handler.emergency_nuke(force).await?;
+        Commands::Classify { text, stdin: _, json } => {
+            if cli_handler.classify_content(text, json).await? {
+                process::exit(classifier::EXIT_SENSITIVE);
+            }
+        },
+        Commands::Get => {
+            cli_handler.get_content().await?;
+        },
+        Commands::Put { text } => {
+            cli_handler.put_content(text).await?;
+        },
+        #[cfg(all(unix, feature = "api"))]
+        Commands::EditorCopy { source, token_file } => {
+            cli_handler.editor_copy(source, token_file).await?;
         },
-        Commands::Status => {
-            cli_handler.show_status().await?;
+        Commands::Note { action } => {
+            cli_handler.manage_notes(action).await?;
+        },
+        Commands::Status { tmux } => {
+            cli_handler.show_status(tmux).await?;
+        },
+        Commands::Watch { plain, interval_ms } => {
+            cli_handler.watch(plain, Duration::from_millis(interval_ms)).await?;
+        },
+        Commands::Health => {
+            if !cli_handler.show_health()? {
+                process::exit(health::EXIT_UNHEALTHY);
+            }
         },
         Commands::Stop => {
             cli_handler.stop_service().await?;
         },
-        Commands::Config { reset } => {
-            cli_handler.manage_config(reset).await?;
+        Commands::Config { reset, action } => {
+            cli_handler.manage_config(reset, action).await?;
         },
         Commands::Exit => {
             // 交互模式下的退出命令，在主循环中处理
@@ -208,39 +687,134 @@ async fn execute_command(cli_handler: &mut CliHandler, command: Commands) -> Res
         Commands::History => {
             cli_handler.show_history().await?;
         },
+        Commands::Doctor => {
+            cli_handler.run_doctor().await?;
+        },
+        Commands::Report { output } => {
+            cli_handler.generate_report(output).await?;
+        },
+        Commands::ExportForensics { file, verify, key, public_key } => {
+            cli_handler.export_forensics(file, verify, key, public_key).await?;
+        },
+        Commands::Soak { hours } => {
+            cli_handler.run_soak(hours).await?;
+        },
+        Commands::Redteam { seconds, interval_ms } => {
+            cli_handler.run_redteam(seconds, interval_ms).await?;
+        },
+        Commands::Stats { action } => {
+            cli_handler.manage_stats(action).await?;
+        },
+        Commands::Canary { action } => {
+            if cli_handler.manage_canary(action).await? {
+                process::exit(canary::EXIT_FOUND);
+            }
+        },
+        Commands::Guard { action } => {
+            cli_handler.manage_guard(action).await?;
+        },
+        Commands::Audit { action } => {
+            cli_handler.manage_audit(action).await?;
+        },
+        Commands::Formats { action } => {
+            cli_handler.manage_formats(action).await?;
+        },
+        Commands::Backup { action } => {
+            cli_handler.manage_backup(action).await?;
+        },
+        Commands::Escrow { action } => {
+            cli_handler.manage_escrow(action).await?;
+        },
+        #[cfg(feature = "api")]
+        Commands::Token { action } => {
+            cli_handler.manage_tokens(action).await?;
+        },
+        // macOS上非交互运行时在main中直接进入菜单栏，不会到达这里
+        #[cfg(feature = "menubar")]
+        Commands::Menubar { .. } => {
+            return Err("菜单栏模式仅支持macOS，且需直接运行 clipvanish menubar（不能在交互模式中启动）".into());
+        },
     }
     Ok(())
 }
 
+/// 解析 `nuke --older-than` 的时长（如 10m、2h）
+fn parse_older_than(text: &str) -> Result<Duration, String> {
+    duration::parse(text).map_err(|e| e.to_string())
+}
+
 /// 初始化日志系统
-fn init_logger(verbose: bool, silent: bool) {
+///
+/// 基础级别由 --silent/--verbose 或配置中的 ui.log_level 决定，
+/// 再叠加配置中的模块级别覆盖与 --log-filter 表达式
+fn init_logger(verbose: bool, silent: bool, log_filter: Option<&str>) {
+    let ui_config = Config::peek_ui_config();
     let log_level = if silent {
         "error"
     } else if verbose {
         "debug"
     } else {
-        "info"
+        ui_config.log_level.as_str()
     };
     
-    std::env::set_var("RUST_LOG", format!("clipvanish={}", log_level));
-    env_logger::init();
+    let filter = ui_config.build_log_filter(log_level, log_filter);
+    let logger = env_logger::Builder::new()
+        .parse_filters(&filter)
+        .build();
+    // 所有日志输出前先经过脱敏
+    redact::install(logger).expect("日志系统初始化失败");
     
     if verbose {
-        info!("日志系统已初始化，级别: {}", log_level);
+        info!("日志系统已初始化，过滤表达式: {}", filter);
     }
 }
 
 /// 打印帮助信息
 fn print_help() {
-    println!("可用命令：");
-    println!("  start [--timer <seconds>] [--daemon]  启动剪贴板监听服务");
-    println!("  nuke [--force]                       紧急销毁所有数据");
-    println!("  status                               显示当前状态");
-    println!("  history                              查看剪贴板历史记录");
-    println!("  stop                                 停止服务");
-    println!("  config [--reset]                     查看/重置配置");
-    println!("  help                                 显示此帮助信息");
-    println!("  exit                                 退出程序\n");
+    eprintln!("可用命令：");
+    eprintln!("  start [--timer <时长>] [--daemon]    启动剪贴板监听服务");
+    eprintln!("  nuke [--force] [--code <验证码>]     紧急销毁所有数据（启用双人确认时需第二因素）");
+    eprintln!("  nuke --at <时间> | --in <时长>        定时紧急销毁（nuke --cancel 取消）");
+    eprintln!("  nuke --clipboard-only | --history-only | --keys-only | --slots 2,3 | --older-than 10m");
+    eprintln!("                                       只销毁指定部分");
+    eprintln!("  undo                                 撤销最近一次倒计时清除");
+    eprintln!("  unprotect                            解除当前条目的保护");
+    eprintln!("  extend [时长] [--item <id>]          延长条目的存活时间（默认当前条目、60s；时长如 90s、2m）");
+    eprintln!("  learned [--reset]                    查看/重置已学习的偏好");
+    eprintln!("  rules [--promote [规则]]             查看新规则试运行状态/转为强制执行");
+    eprintln!("  rules export|import|keygen ...       导出/导入规则包、生成签名密钥");
+    eprintln!("  get                                  输出当前剪贴板内容到stdout");
+    eprintln!("  put <内容>                           将内容写入剪贴板");
+    eprintln!("  note create [--ttl 1h] <内容>        创建阅后即焚笔记（输出读取凭据）");
+    eprintln!("  note read <凭据>                     读取笔记到受保护剪贴板，笔记随即销毁");
+    eprintln!("  status                               显示当前状态");
+    eprintln!("  health                               检查服务健康状态");
+    eprintln!("  history                              查看剪贴板历史记录");
+    eprintln!("  stop                                 停止服务");
+    eprintln!("  config [--reset]                     查看/重置配置");
+    eprintln!("  config backups | restore [--backup n] 列出/回滚配置备份");
+    eprintln!("  doctor                               诊断运行环境");
+    eprintln!("  report [--output <file>]             生成问题报告");
+    eprintln!("  export-forensics [--verify [--key <公钥>]] [file] 导出/校验取证归档");
+    eprintln!("  export-forensics --public-key        输出取证签名公钥");
+    eprintln!("  soak [--hours <n>]                   浸泡测试（默认8小时）");
+    eprintln!("  redteam [--seconds <n>]              模拟攻击测试（嗅探器能否读到明文）");
+    eprintln!("  stats report [--html] [--output <f>] 生成最近7天的使用报告");
+    eprintln!("  stats share [--epsilon <e>] [--days <n>] 导出可共享的加噪统计");
+    eprintln!("  canary generate|rotate|list|check [files] 管理诱饵令牌");
+    eprintln!("  audit export [--from] [--to] [--format jsonl|csv] [-o <f>] 导出审计日志");
+    eprintln!("  formats list | dump-schema [name]    列出数据格式 / 输出JSON Schema");
+    eprintln!("  backup create|restore <file>         以口令加密备份或恢复用户数据");
+    eprintln!("  escrow keygen|decrypt                密钥托管（管理员）");
+    #[cfg(feature = "hardware-keys")]
+    eprintln!("  guard setup-totp | enroll-key [--slot 1|2] 配置紧急销毁双人确认");
+    #[cfg(not(feature = "hardware-keys"))]
+    eprintln!("  guard setup-totp                     配置紧急销毁双人确认");
+    #[cfg(feature = "api")]
+    eprintln!("  token list|create|revoke             管理控制接口令牌");
+    eprintln!("  help                                 显示此帮助信息");
+    eprintln!("  exit                                 退出程序");
+    eprintln!("Tab 补全命令与参数，↑/↓ 浏览历史，Ctrl+R 搜索历史，空行上 Ctrl+D 退出\n");
 }
 
 /// 解析交互式命令
@@ -252,7 +826,7 @@ fn parse_interactive_command(input: &str) -> Result<Commands, String> {
 
     match parts[0] {
         "start" => {
-            let mut timer = 30u64;
+            let mut timer = Duration::from_secs(30);
             let mut daemon = false;
 
             let mut i = 1;
@@ -262,8 +836,7 @@ fn parse_interactive_command(input: &str) -> Result<Commands, String> {
                         if i + 1 >= parts.len() {
                             return Err("--timer 需要一个参数".to_string());
                         }
-                        timer = parts[i + 1].parse()
-                            .map_err(|_| "timer 参数必须是一个数字".to_string())?;
+                        timer = duration::parse(parts[i + 1]).map_err(|e| e.to_string())?;
                         i += 2;
                     }
                     "--daemon" | "-d" => {
@@ -276,18 +849,322 @@ fn parse_interactive_command(input: &str) -> Result<Commands, String> {
                 }
             }
 
-            Ok(Commands::Start { timer, daemon })
+            Ok(Commands::Start { timer, daemon, foreground: false, elevated: false })
         }
         "nuke" => {
-            let force = parts.get(1).map_or(false, |&arg| arg == "--force" || arg == "-f");
-            Ok(Commands::Nuke { force })
+            let force = parts.contains(&"--force") || parts.contains(&"-f");
+            let cancel = parts.contains(&"--cancel");
+            // 取值到下一个选项为止，允许 "--at 2024-05-01 18:00" 这样带空格的时间
+            let value = |name: &str| {
+                parts.iter().position(|&arg| arg == name).map(|i| {
+                    parts[i + 1..].iter().take_while(|arg| !arg.starts_with('-')).copied().collect::<Vec<_>>().join(" ")
+                })
+            };
+            let at = value("--at").filter(|time| !time.is_empty());
+            let delay = value("--in").filter(|duration| !duration.is_empty());
+            if parts.contains(&"--at") && at.is_none() || parts.contains(&"--in") && delay.is_none() {
+                return Err("用法: nuke [--force] | nuke --at <时间> | nuke --in <时长> | nuke --cancel".to_string());
+            }
+            let clipboard_only = parts.contains(&"--clipboard-only");
+            let history_only = parts.contains(&"--history-only");
+            let keys_only = parts.contains(&"--keys-only");
+            let slots = match value("--slots") {
+                Some(list) => list
+                    .split(',')
+                    .map(|slot| slot.trim().parse().map_err(|_| format!("无效的条目编号: {}", slot)))
+                    .collect::<Result<Vec<usize>, _>>()?,
+                None => Vec::new(),
+            };
+            let older_than = value("--older-than").map(|text| parse_older_than(&text)).transpose()?;
+            let code = value("--code").filter(|code| !code.is_empty());
+            if [clipboard_only, history_only, keys_only].iter().filter(|&&only| only).count() > 1
+                || keys_only && (!slots.is_empty() || older_than.is_some())
+            {
+                return Err("--clipboard-only/--history-only/--keys-only 只能选择一个，且 --keys-only 不能与 --slots/--older-than 同时使用".to_string());
+            }
+            Ok(Commands::Nuke { force, at, delay, cancel, clipboard_only, history_only, keys_only, slots, older_than, code })
         }
-        "status" => Ok(Commands::Status),
+        "undo" => Ok(Commands::Undo),
+        "unprotect" => Ok(Commands::Unprotect),
+        "extend" => {
+            let mut duration = Duration::from_secs(60);
+            let mut item = None;
+            let mut args = parts[1..].iter();
+            while let Some(&arg) = args.next() {
+                match arg {
+                    "--item" => {
+                        let id = args.next().ok_or("用法: extend [时长] [--item <条目ID>]".to_string())?;
+                        item = Some(id.parse()?);
+                    }
+                    value => duration = duration::parse(value).map_err(|e| e.to_string())?,
+                }
+            }
+            Ok(Commands::Extend { duration, item })
+        }
+        "learned" => {
+            let reset = parts.get(1).is_some_and(|&arg| arg == "--reset");
+            Ok(Commands::Learned { reset })
+        }
+        "rules" => {
+            let flag = |names: &[&str]| {
+                parts.iter().position(|arg| names.contains(arg)).and_then(|i| parts.get(i + 1)).map(|v| v.to_string())
+            };
+            let action = match (parts.get(1), parts.get(2)) {
+                (Some(&"export"), Some(file)) if !file.starts_with('-') => Some(RulesAction::Export {
+                    file: file.to_string(),
+                    name: flag(&["--name"]),
+                    sign_key: flag(&["--sign-key"]),
+                }),
+                (Some(&"import"), Some(source)) if !source.starts_with('-') => Some(RulesAction::Import {
+                    source: source.to_string(),
+                    allow_unsigned: parts.contains(&"--allow-unsigned"),
+                    trust: flag(&["--trust"]),
+                    replace: parts.contains(&"--replace"),
+                }),
+                (Some(&"keygen"), _) => Some(RulesAction::Keygen {
+                    output: flag(&["--output", "-o"]).unwrap_or_else(|| "clipvanish-rules.key".to_string()),
+                }),
+                (Some(&"export") | Some(&"import"), _) => {
+                    return Err("用法: rules export <file> [--name <名称>] [--sign-key <file>] | rules import <file|url> [--allow-unsigned] [--trust <公钥>] [--replace]".to_string());
+                }
+                _ => None,
+            };
+            let promote = parts.contains(&"--promote");
+            let rule = match action {
+                Some(_) => None,
+                None => parts.iter().skip(1).find(|arg| !arg.starts_with("--")).map(|arg| arg.to_string()),
+            };
+            Ok(Commands::Rules { promote, rule, action })
+        }
+        "get" => Ok(Commands::Get),
+        "put" => {
+            // 交互模式下内容取命令后的全部文本
+            let text = input.trim_start().strip_prefix("put").unwrap_or_default().trim_start();
+            if text.is_empty() {
+                return Err("用法: put <内容>".to_string());
+            }
+            Ok(Commands::Put { text: Some(text.to_string()) })
+        }
+        "note" => {
+            let action = match parts.get(1) {
+                Some(&"create") => {
+                    let mut ttl = Duration::from_secs(3600);
+                    let mut words = Vec::new();
+                    let mut i = 2;
+                    while i < parts.len() {
+                        if parts[i] == "--ttl" {
+                            let value = parts.get(i + 1).ok_or_else(|| "--ttl 需要一个参数".to_string())?;
+                            ttl = duration::parse(value).map_err(|e| e.to_string())?;
+                            i += 2;
+                        } else {
+                            words.push(parts[i]);
+                            i += 1;
+                        }
+                    }
+                    if words.is_empty() {
+                        return Err("用法: note create [--ttl <时长>] <内容>".to_string());
+                    }
+                    NoteAction::Create { text: Some(words.join(" ")), ttl }
+                }
+                Some(&"read") => match parts.get(2) {
+                    Some(handle) => NoteAction::Read { handle: handle.parse().map_err(|e: notes::NoteError| e.to_string())? },
+                    None => return Err("用法: note read <凭据>".to_string()),
+                },
+                _ => return Err("用法: note create [--ttl <时长>] <内容> | note read <凭据>".to_string()),
+            };
+            Ok(Commands::Note { action })
+        }
+        "status" => Ok(Commands::Status { tmux: parts.get(1) == Some(&"--tmux") }),
+        "health" => Ok(Commands::Health),
         "stop" => Ok(Commands::Stop),
         "history" => Ok(Commands::History),
+        "doctor" => Ok(Commands::Doctor),
+        "report" => {
+            let output = match parts.get(1) {
+                Some(&"--output") | Some(&"-o") => {
+                    let path = parts.get(2).ok_or_else(|| "--output 需要一个参数".to_string())?;
+                    Some(path.to_string())
+                }
+                Some(arg) => return Err(format!("未知参数: {}", arg)),
+                None => None,
+            };
+            Ok(Commands::Report { output })
+        }
+        "export-forensics" => {
+            let verify = parts.contains(&"--verify");
+            let public_key = parts.contains(&"--public-key");
+            let key_index = parts.iter().position(|&arg| arg == "--key");
+            let key = match key_index {
+                Some(i) => Some(parts.get(i + 1).ok_or_else(|| "--key 需要一个参数".to_string())?.to_string()),
+                None => None,
+            };
+            if key.is_some() && !verify || public_key && verify {
+                return Err("用法: export-forensics [--verify [--key <公钥>]] [file] | export-forensics --public-key".to_string());
+            }
+            let file = parts
+                .iter()
+                .enumerate()
+                .skip(1)
+                .find(|&(i, arg)| !arg.starts_with("--") && key_index.map(|k| k + 1) != Some(i))
+                .map(|(_, arg)| arg.to_string());
+            Ok(Commands::ExportForensics { file, verify, key, public_key })
+        }
         "config" => {
-            let reset = parts.get(1).map_or(false, |&arg| arg == "--reset");
-            Ok(Commands::Config { reset })
+            let action = match (parts.get(1), parts.get(2), parts.get(3)) {
+                (Some(&"backups"), None, _) => Some(ConfigAction::Backups),
+                (Some(&"restore"), None, _) => Some(ConfigAction::Restore { backup: 1 }),
+                (Some(&"restore"), Some(&"--backup"), Some(value)) => Some(ConfigAction::Restore {
+                    backup: value.parse().map_err(|_| "--backup 参数必须是一个数字".to_string())?,
+                }),
+                (Some(&"backups") | Some(&"restore"), _, _) => {
+                    return Err("用法: config backups | config restore [--backup <n>]".to_string());
+                }
+                _ => None,
+            };
+            let reset = parts.get(1).is_some_and(|&arg| arg == "--reset");
+            Ok(Commands::Config { reset, action })
+        }
+        "soak" => {
+            let hours = match (parts.get(1), parts.get(2)) {
+                (Some(&"--hours"), Some(value)) => value.parse().map_err(|_| "--hours 参数必须是一个数字".to_string())?,
+                (None, _) => 8.0,
+                _ => return Err("用法: soak [--hours <n>]".to_string()),
+            };
+            Ok(Commands::Soak { hours })
+        }
+        "redteam" => {
+            let seconds = match (parts.get(1), parts.get(2)) {
+                (Some(&"--seconds"), Some(value)) => value.parse().map_err(|_| "--seconds 参数必须是一个整数".to_string())?,
+                (None, _) => 5,
+                _ => return Err("用法: redteam [--seconds <n>]".to_string()),
+            };
+            Ok(Commands::Redteam { seconds, interval_ms: 2 })
+        }
+        "stats" => {
+            let flag = |names: &[&str]| {
+                parts.iter().position(|arg| names.contains(arg)).and_then(|i| parts.get(i + 1)).map(|v| v.to_string())
+            };
+            if parts.get(1) == Some(&"share") {
+                let epsilon = match flag(&["--epsilon"]) {
+                    Some(value) => value.parse().map_err(|_| "epsilon 参数必须是一个数字".to_string())?,
+                    None => stats::DEFAULT_EPSILON,
+                };
+                let days = match flag(&["--days"]) {
+                    Some(value) => value.parse().map_err(|_| "days 参数必须是一个数字".to_string())?,
+                    None => 28,
+                };
+                return Ok(Commands::Stats { action: StatsAction::Share { epsilon, days, output: flag(&["--output", "-o"]) } });
+            }
+            if parts.get(1) != Some(&"report") {
+                return Err("用法: stats report [--html] [--output <file>] [--weeks <n>] | stats share [--epsilon <e>] [--days <n>] [--output <file>]".to_string());
+            }
+            let weeks = match flag(&["--weeks"]) {
+                Some(value) => value.parse().map_err(|_| "weeks 参数必须是一个数字".to_string())?,
+                None => 8,
+            };
+            Ok(Commands::Stats {
+                action: StatsAction::Report {
+                    html: parts.contains(&"--html"),
+                    output: flag(&["--output", "-o"]),
+                    weeks,
+                },
+            })
+        }
+        "canary" => {
+            let action = match parts.get(1) {
+                Some(&"generate") => CanaryAction::Generate,
+                Some(&"rotate") => CanaryAction::Rotate,
+                Some(&"list") => CanaryAction::List,
+                Some(&"check") => CanaryAction::Check { files: parts[2..].iter().map(|file| file.to_string()).collect() },
+                _ => return Err("用法: canary generate | rotate | list | check [file...]".to_string()),
+            };
+            Ok(Commands::Canary { action })
+        }
+        "guard" => {
+            let action = match (parts.get(1), parts.get(2), parts.get(3)) {
+                (Some(&"setup-totp"), None, _) => GuardAction::SetupTotp,
+                #[cfg(feature = "hardware-keys")]
+                (Some(&"enroll-key"), None, _) => GuardAction::EnrollKey { slot: 2 },
+                #[cfg(feature = "hardware-keys")]
+                (Some(&"enroll-key"), Some(&"--slot"), Some(slot)) => match slot.parse() {
+                    Ok(slot @ (1 | 2)) => GuardAction::EnrollKey { slot },
+                    _ => return Err(format!("无效的槽位: {}", slot)),
+                },
+                _ => return Err("用法: guard setup-totp | guard enroll-key [--slot 1|2]".to_string()),
+            };
+            Ok(Commands::Guard { action })
+        }
+        "audit" => {
+            let flag = |names: &[&str]| {
+                parts.iter().position(|arg| names.contains(arg)).and_then(|i| parts.get(i + 1)).map(|v| v.to_string())
+            };
+            if parts.get(1) != Some(&"export") {
+                return Err("用法: audit export [--from <时间>] [--to <时间>] [--format jsonl|csv] [--output <file>]".to_string());
+            }
+            let format = match flag(&["--format"]).as_deref() {
+                None | Some("jsonl") => ExportFormat::Jsonl,
+                Some("csv") => ExportFormat::Csv,
+                Some(other) => return Err(format!("不支持的导出格式: {}（可选 jsonl、csv）", other)),
+            };
+            Ok(Commands::Audit {
+                action: AuditAction::Export {
+                    from: flag(&["--from"]),
+                    to: flag(&["--to"]).unwrap_or_else(|| "now".to_string()),
+                    format,
+                    output: flag(&["--output", "-o"]),
+                },
+            })
+        }
+        "formats" => {
+            let action = match (parts.get(1), parts.get(2)) {
+                (Some(&"list"), None) => FormatsAction::List,
+                (Some(&"dump-schema"), format) => FormatsAction::DumpSchema { format: format.map(|format| format.to_string()) },
+                _ => return Err("用法: formats list | dump-schema [audit|history|notes|canaries|backup]".to_string()),
+            };
+            Ok(Commands::Formats { action })
+        }
+        "backup" => {
+            let action = match (parts.get(1), parts.get(2)) {
+                (Some(&"create"), Some(file)) => BackupAction::Create { file: file.to_string(), include_notes: parts.contains(&"--include-notes") },
+                (Some(&"restore"), Some(file)) => BackupAction::Restore { file: file.to_string() },
+                _ => return Err("用法: backup create <file> [--include-notes] | backup restore <file>".to_string()),
+            };
+            Ok(Commands::Backup { action })
+        }
+        "escrow" => {
+            let flag = |names: &[&str]| {
+                parts.iter().position(|arg| names.contains(arg)).and_then(|i| parts.get(i + 1)).map(|v| v.to_string())
+            };
+            let action = match parts.get(1) {
+                Some(&"keygen") => EscrowAction::Keygen {
+                    output: flag(&["--output", "-o"]).unwrap_or_else(|| "clipvanish-recovery.key".to_string()),
+                },
+                Some(&"decrypt") => match (parts.get(2), flag(&["--private-key", "-k"])) {
+                    (Some(archive), Some(private_key)) if !archive.starts_with('-') => {
+                        EscrowAction::Decrypt { archive: archive.to_string(), private_key }
+                    }
+                    _ => return Err("用法: escrow decrypt <archive> --private-key <file>".to_string()),
+                },
+                _ => return Err("用法: escrow keygen [--output <file>] | escrow decrypt <archive> --private-key <file>".to_string()),
+            };
+            Ok(Commands::Escrow { action })
+        }
+        #[cfg(feature = "api")]
+        "token" => {
+            let action = match (parts.get(1), parts.get(2)) {
+                (Some(&"list"), None) => TokenAction::List,
+                (Some(&"revoke"), Some(name)) => TokenAction::Revoke { name: name.to_string() },
+                (Some(&"create"), Some(name)) => {
+                    let capability = match (parts.get(3), parts.get(4)) {
+                        (Some(&"--capability") | Some(&"-c"), Some(value)) => value.parse()?,
+                        (None, _) => ipc_auth::Capability::Status,
+                        _ => return Err("用法: token create <name> [--capability status|control|content]".to_string()),
+                    };
+                    TokenAction::Create { name: name.to_string(), capability }
+                }
+                _ => return Err("用法: token list | token create <name> [--capability status|control|content] | token revoke <name>".to_string()),
+            };
+            Ok(Commands::Token { action })
         }
         "exit" => Ok(Commands::Exit),
         _ => Err(format!("未知命令: {}", parts[0])),
@@ -0,0 +1,442 @@
+/*!
+ * ClipVanish™ macOS菜单栏模块
+ *
+ * `clipvanish menubar` 在当前进程中启动监听服务，并在菜单栏显示原生状态项（NSStatusItem）
+ * 特点：
+ * - 状态项标题实时显示当前受保护条目的倒计时，暂停时显示暂停标记，从不显示内容
+ * - 菜单项与热键动作一致（延长、固定、撤销、暂停、观察模式、悬浮窗、规则转正、紧急销毁），经同一套逻辑执行
+ * - 菜单中显示剪贴板卫生评分与首要建议
+ * - 菜单中显示辅助功能权限状态（键盘监听与安全粘贴需要），未授予时可直接打开系统设置
+ * - AppKit必须运行在主线程：监听服务运行在独立线程的tokio运行时中，两者只通过命令通道与状态快照交互
+ * - 服务停止（菜单"退出"、Ctrl+C）后菜单栏随之退出
+ * - 仅在macOS上且启用 `menubar` 特性时编译
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use crate::config::HotkeyAction;
+use crate::countdown_display;
+use crate::timer::DestructTimer;
+
+/// 状态刷新间隔
+pub const REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 菜单中"延长"的秒数
+const EXTEND_SECONDS: u64 = 60;
+
+/// 菜单命令
+#[derive(Debug, Clone, PartialEq)]
+pub enum MenuCommand {
+    /// 执行热键动作
+    Action(HotkeyAction),
+    /// 打开系统设置中的辅助功能权限页（在菜单栏线程处理）
+    OpenAccessibilitySettings,
+    /// 停止服务并退出
+    Quit,
+}
+
+/// 服务状态快照（服务端定期更新，菜单栏线程读取）
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MenuBarState {
+    /// 服务是否已启动
+    pub running: bool,
+    /// 服务是否已结束（菜单栏随之退出）
+    pub stopped: bool,
+    /// 当前受保护条目的剩余时间
+    pub remaining: Option<Duration>,
+    /// 保护是否已暂停
+    pub paused: bool,
+    /// 是否处于观察模式
+    pub observing: bool,
+    /// 剪贴板卫生评分（条目不足时为None）
+    pub hygiene: Option<String>,
+    /// 首要的卫生建议
+    pub hygiene_tip: Option<String>,
+}
+
+/// 菜单栏一侧的连接
+pub struct MenuBarLink {
+    commands: Sender<MenuCommand>,
+    state: Arc<Mutex<MenuBarState>>,
+}
+
+impl MenuBarLink {
+    /// 向服务发送命令（服务已结束时忽略）
+    pub fn send(&self, command: MenuCommand) {
+        let _ = self.commands.send(command);
+    }
+
+    /// 当前状态快照
+    pub fn snapshot(&self) -> MenuBarState {
+        self.state.lock().map(|state| state.clone()).unwrap_or_default()
+    }
+}
+
+/// 服务一侧的连接（释放时通知菜单栏服务已结束）
+pub struct MenuBarService {
+    commands: Receiver<MenuCommand>,
+    state: Arc<Mutex<MenuBarState>>,
+}
+
+impl MenuBarService {
+    /// 取出一条待处理的命令
+    pub fn try_recv(&self) -> Option<MenuCommand> {
+        self.commands.try_recv().ok()
+    }
+
+    /// 更新状态快照
+    pub fn update(&self, update: impl FnOnce(&mut MenuBarState)) {
+        if let Ok(mut state) = self.state.lock() {
+            update(&mut state);
+        }
+    }
+}
+
+impl Drop for MenuBarService {
+    fn drop(&mut self) {
+        self.update(|state| state.stopped = true);
+    }
+}
+
+/// 创建菜单栏与服务之间的连接
+pub fn channel() -> (MenuBarLink, MenuBarService) {
+    let (sender, receiver) = mpsc::channel();
+    let state = Arc::new(Mutex::new(MenuBarState::default()));
+    (
+        MenuBarLink { commands: sender, state: state.clone() },
+        MenuBarService { commands: receiver, state },
+    )
+}
+
+/// 菜单项
+#[derive(Debug, Clone, PartialEq)]
+pub enum MenuEntry {
+    /// 不可点击的说明文字
+    Label(String),
+    /// 可点击的菜单项
+    Item {
+        /// 标题
+        label: String,
+        /// 点击时执行的命令
+        command: MenuCommand,
+        /// 是否显示勾选标记
+        checked: bool,
+    },
+    /// 分隔线
+    Separator,
+}
+
+impl MenuEntry {
+    fn action(action: HotkeyAction, checked: bool) -> Self {
+        MenuEntry::Item { label: action.to_string(), command: MenuCommand::Action(action), checked }
+    }
+}
+
+/// 状态项标题
+///
+/// # 参数
+/// * `state` - 服务状态快照
+pub fn title(state: &MenuBarState) -> String {
+    match (state.running, state.paused, state.remaining) {
+        (false, _, _) => "⏳".to_string(),
+        (true, true, _) => "⏸️".to_string(),
+        (true, false, Some(remaining)) => countdown_display::compact(remaining),
+        (true, false, None) => "🛡️".to_string(),
+    }
+}
+
+/// 菜单内容
+///
+/// # 参数
+/// * `state` - 服务状态快照
+/// * `accessibility_trusted` - 是否已授予辅助功能权限
+pub fn entries(state: &MenuBarState, accessibility_trusted: bool) -> Vec<MenuEntry> {
+    let status = match (state.running, state.remaining) {
+        (false, _) => "服务启动中…".to_string(),
+        (true, Some(remaining)) => format!("当前条目剩余 {}", DestructTimer::format_duration(remaining)),
+        (true, None) => "没有受保护的条目".to_string(),
+    };
+    let mut entries = vec![MenuEntry::Label(status)];
+    if state.observing {
+        entries.push(MenuEntry::Label("观察模式：命中规则时只记录，不拦截".to_string()));
+    }
+    if let Some(hygiene) = &state.hygiene {
+        entries.push(MenuEntry::Label(format!("🧼 卫生评分 {}", hygiene)));
+    }
+    if let Some(tip) = &state.hygiene_tip {
+        entries.push(MenuEntry::Label(format!("💡 {}", tip)));
+    }
+
+    entries.extend([
+        MenuEntry::Separator,
+        MenuEntry::action(HotkeyAction::Extend { seconds: EXTEND_SECONDS }, false),
+        MenuEntry::action(HotkeyAction::Pin, false),
+        MenuEntry::action(HotkeyAction::Undo, false),
+        MenuEntry::Separator,
+        MenuEntry::Item { label: "暂停保护".to_string(), command: MenuCommand::Action(HotkeyAction::Pause), checked: state.paused },
+        MenuEntry::Item {
+            label: "观察模式".to_string(),
+            command: MenuCommand::Action(HotkeyAction::ToggleObserve),
+            checked: state.observing,
+        },
+        MenuEntry::action(HotkeyAction::ToggleOverlay, false),
+        MenuEntry::action(HotkeyAction::PromoteRule, false),
+        MenuEntry::Separator,
+        MenuEntry::Item { label: "💥 紧急销毁".to_string(), command: MenuCommand::Action(HotkeyAction::Nuke), checked: false },
+        MenuEntry::Separator,
+    ]);
+
+    entries.push(if accessibility_trusted {
+        MenuEntry::Label("✅ 辅助功能权限：已授予".to_string())
+    } else {
+        MenuEntry::Item {
+            label: "⚠️ 辅助功能权限：未授予（键盘监听与安全粘贴不可用），打开系统设置…".to_string(),
+            command: MenuCommand::OpenAccessibilitySettings,
+            checked: false,
+        }
+    });
+    entries.push(MenuEntry::Item { label: "退出 ClipVanish".to_string(), command: MenuCommand::Quit, checked: false });
+    entries
+}
+
+/// 在主线程运行菜单栏，直到服务结束
+///
+/// # 参数
+/// * `link` - 与服务的连接
+pub fn run(link: MenuBarLink) -> Result<(), String> {
+    macos::run(link)
+}
+
+mod macos {
+    use super::{entries, title, MenuBarLink, MenuCommand, MenuEntry, REFRESH_INTERVAL};
+    use std::sync::{Mutex, Once, OnceLock};
+    use cocoa::base::{id, nil, BOOL, NO, YES};
+    use cocoa::foundation::{NSInteger, NSString};
+    use objc::declare::ClassDecl;
+    use objc::runtime::{Class, Object, Sel};
+    use objc::{class, msg_send, sel, sel_impl};
+
+    /// 系统设置中的辅助功能权限页
+    const ACCESSIBILITY_SETTINGS_URL: &str = "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility";
+
+    /// NSVariableStatusItemLength
+    const VARIABLE_LENGTH: f64 = -1.0;
+
+    /// NSApplicationActivationPolicyAccessory（不显示Dock图标）
+    const ACTIVATION_POLICY_ACCESSORY: NSInteger = 1;
+
+    /// 目标对象类名
+    const TARGET_CLASS: &str = "ClipVanishMenuBarTarget";
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn AXIsProcessTrusted() -> u8;
+    }
+
+    /// 与服务的连接（菜单回调中使用）
+    static LINK: OnceLock<MenuBarLink> = OnceLock::new();
+
+    /// 当前菜单中各项对应的命令（按tag索引）
+    static COMMANDS: Mutex<Vec<MenuCommand>> = Mutex::new(Vec::new());
+
+    pub(super) fn run(link: MenuBarLink) -> Result<(), String> {
+        if LINK.set(link).is_err() {
+            return Err("菜单栏已在运行".to_string());
+        }
+
+        unsafe {
+            let app: id = msg_send![class!(NSApplication), sharedApplication];
+            let _: BOOL = msg_send![app, setActivationPolicy: ACTIVATION_POLICY_ACCESSORY];
+
+            let status_bar: id = msg_send![class!(NSStatusBar), systemStatusBar];
+            let status_item: id = msg_send![status_bar, statusItemWithLength: VARIABLE_LENGTH];
+            if status_item == nil {
+                return Err("无法创建菜单栏状态项".to_string());
+            }
+            let _: id = msg_send![status_item, retain];
+
+            let target: id = msg_send![target_class(), new];
+            (*target).set_ivar("_statusItem", status_item);
+
+            // 菜单在每次打开前按最新状态重建
+            let menu: id = msg_send![class!(NSMenu), new];
+            let _: () = msg_send![menu, setAutoenablesItems: NO];
+            let _: () = msg_send![menu, setDelegate: target];
+            let _: () = msg_send![status_item, setMenu: menu];
+
+            // 加入common模式，菜单展开时标题仍然刷新
+            let timer: id = msg_send![
+                class!(NSTimer),
+                timerWithTimeInterval: REFRESH_INTERVAL.as_secs_f64()
+                target: target
+                selector: sel!(refresh:)
+                userInfo: nil
+                repeats: YES
+            ];
+            let run_loop: id = msg_send![class!(NSRunLoop), currentRunLoop];
+            let _: () = msg_send![run_loop, addTimer: timer forMode: ns_string("kCFRunLoopCommonModes")];
+
+            refresh(&*target, sel!(refresh:), nil);
+            let _: () = msg_send![app, run];
+        }
+        Ok(())
+    }
+
+    /// 注册菜单回调使用的Objective-C类
+    fn target_class() -> &'static Class {
+        static REGISTER: Once = Once::new();
+        REGISTER.call_once(|| {
+            let mut decl = ClassDecl::new(TARGET_CLASS, class!(NSObject)).expect("菜单栏目标类已存在");
+            decl.add_ivar::<id>("_statusItem");
+            unsafe {
+                decl.add_method(sel!(refresh:), refresh as extern "C" fn(&Object, Sel, id));
+                decl.add_method(sel!(menuNeedsUpdate:), menu_needs_update as extern "C" fn(&Object, Sel, id));
+                decl.add_method(sel!(select:), select as extern "C" fn(&Object, Sel, id));
+            }
+            decl.register();
+        });
+        Class::get(TARGET_CLASS).expect("菜单栏目标类未注册")
+    }
+
+    /// 定时刷新状态项标题；服务结束后退出
+    extern "C" fn refresh(this: &Object, _cmd: Sel, _timer: id) {
+        let Some(link) = LINK.get() else {
+            return;
+        };
+        let state = link.snapshot();
+        unsafe {
+            if state.stopped {
+                terminate();
+                return;
+            }
+            let status_item: id = *this.get_ivar("_statusItem");
+            let button: id = msg_send![status_item, button];
+            let _: () = msg_send![button, setTitle: ns_string(&title(&state))];
+        }
+    }
+
+    /// 菜单打开前按最新状态重建
+    extern "C" fn menu_needs_update(this: &Object, _cmd: Sel, menu: id) {
+        let Some(link) = LINK.get() else {
+            return;
+        };
+        let mut commands = COMMANDS.lock().unwrap_or_else(|e| e.into_inner());
+        commands.clear();
+
+        unsafe {
+            let _: () = msg_send![menu, removeAllItems];
+            for entry in entries(&link.snapshot(), accessibility_trusted()) {
+                let item: id = match entry {
+                    MenuEntry::Separator => msg_send![class!(NSMenuItem), separatorItem],
+                    MenuEntry::Label(label) => {
+                        let item = menu_item(&label);
+                        let _: () = msg_send![item, setEnabled: NO];
+                        item
+                    }
+                    MenuEntry::Item { label, command, checked } => {
+                        let item = menu_item(&label);
+                        let _: () = msg_send![item, setTarget: this];
+                        let _: () = msg_send![item, setAction: sel!(select:)];
+                        let _: () = msg_send![item, setTag: commands.len() as NSInteger];
+                        let _: () = msg_send![item, setState: checked as NSInteger];
+                        commands.push(command);
+                        item
+                    }
+                };
+                let _: () = msg_send![menu, addItem: item];
+            }
+        }
+    }
+
+    /// 菜单项点击
+    extern "C" fn select(_this: &Object, _cmd: Sel, sender: id) {
+        let Some(link) = LINK.get() else {
+            return;
+        };
+        let tag: NSInteger = unsafe { msg_send![sender, tag] };
+        let command = COMMANDS.lock().unwrap_or_else(|e| e.into_inner()).get(tag as usize).cloned();
+
+        match command {
+            Some(MenuCommand::OpenAccessibilitySettings) => unsafe {
+                let url: id = msg_send![class!(NSURL), URLWithString: ns_string(ACCESSIBILITY_SETTINGS_URL)];
+                let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+                let _: BOOL = msg_send![workspace, openURL: url];
+            },
+            Some(MenuCommand::Quit) if link.snapshot().stopped => unsafe { terminate() },
+            Some(command) => link.send(command),
+            None => {}
+        }
+    }
+
+    /// 是否已授予辅助功能权限
+    fn accessibility_trusted() -> bool {
+        unsafe { AXIsProcessTrusted() != 0 }
+    }
+
+    unsafe fn terminate() {
+        let app: id = msg_send![class!(NSApplication), sharedApplication];
+        let _: () = msg_send![app, terminate: nil];
+    }
+
+    unsafe fn menu_item(label: &str) -> id {
+        let item: id = msg_send![class!(NSMenuItem), alloc];
+        let item: id = msg_send![item, initWithTitle: ns_string(label) action: sel!(select:) keyEquivalent: ns_string("")];
+        msg_send![item, autorelease]
+    }
+
+    unsafe fn ns_string(text: &str) -> id {
+        let string = NSString::alloc(nil).init_str(text);
+        msg_send![string, autorelease]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_title_follows_state() {
+        let mut state = MenuBarState::default();
+        assert_eq!(title(&state), "⏳");
+
+        state.running = true;
+        assert_eq!(title(&state), "🛡️");
+        state.remaining = Some(Duration::from_secs(125));
+        assert_eq!(title(&state), "🔒 2m05s");
+        state.paused = true;
+        assert_eq!(title(&state), "⏸️");
+    }
+
+    #[test]
+    fn test_entries_mirror_hotkey_actions_and_permission() {
+        let (link, service) = channel();
+        service.update(|state| {
+            state.running = true;
+            state.paused = true;
+        });
+        let state = link.snapshot();
+
+        let menu = entries(&state, false);
+        assert!(menu.contains(&MenuEntry::Item {
+            label: "暂停保护".to_string(),
+            command: MenuCommand::Action(HotkeyAction::Pause),
+            checked: true,
+        }));
+        assert!(menu.iter().any(|entry| matches!(entry, MenuEntry::Item { command: MenuCommand::OpenAccessibilitySettings, .. })));
+        assert!(!menu.iter().any(|entry| matches!(entry, MenuEntry::Label(label) if label.starts_with("🧼"))));
+        let rated = MenuBarState { hygiene: Some("82/100（良好）".to_string()), ..state.clone() };
+        assert!(entries(&rated, true).contains(&MenuEntry::Label("🧼 卫生评分 82/100（良好）".to_string())));
+        assert!(!entries(&state, true)
+            .iter()
+            .any(|entry| matches!(entry, MenuEntry::Item { command: MenuCommand::OpenAccessibilitySettings, .. })));
+
+        // 服务端释放后菜单栏可以得知服务已结束
+        link.send(MenuCommand::Quit);
+        assert_eq!(service.try_recv(), Some(MenuCommand::Quit));
+        drop(service);
+        assert!(link.snapshot().stopped);
+    }
+}
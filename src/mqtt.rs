@@ -0,0 +1,324 @@
+/*!
+ * ClipVanish™ MQTT发布模块
+ *
+ * 向本地MQTT代理发布脱敏的状态与销毁事件，供Home Assistant等联动（例如剪贴板中有受保护内容时点亮指示灯）
+ * 特点：
+ * - `<前缀>/status`：保留消息，受保护条目出现、被清除时更新；服务异常断开时由遗嘱消息置为 `offline`
+ * - `<前缀>/destroyed`：订阅审计事件总线，条目被销毁时发布（详情经日志脱敏器处理，从不包含剪贴板内容）
+ * - 内置最小的MQTT 3.1.1客户端（QoS 0，可选用户名与密码），不依赖外部库；只支持明文TCP，应连接本机或可信局域网中的代理
+ * - 连接断开后按退避重连，不影响剪贴板保护本身
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::broadcast::error::RecvError;
+use crate::audit::Subscription;
+use crate::clipboard::ClipboardMonitor;
+use crate::item_id::ItemId;
+use crate::redact::Scrubber;
+
+/// 状态检查间隔
+const STATUS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 重连等待时长上限
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+/// MQTT发布配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MqttConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 代理地址（主机:端口）
+    pub broker: String,
+    /// 用户名（为空时不认证）
+    pub username: String,
+    /// 密码
+    pub password: String,
+    /// 客户端ID
+    pub client_id: String,
+    /// 主题前缀
+    pub topic_prefix: String,
+    /// 心跳间隔（秒）
+    pub keepalive_seconds: u16,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        MqttConfig {
+            enabled: false,
+            broker: "127.0.0.1:1883".to_string(),
+            username: String::new(),
+            password: String::new(),
+            client_id: "clipvanish".to_string(),
+            topic_prefix: "clipvanish".to_string(),
+            keepalive_seconds: 60,
+        }
+    }
+}
+
+impl MqttConfig {
+    /// 完整主题名
+    ///
+    /// # 参数
+    /// * `name` - 主题名（如 `status`）
+    pub fn topic(&self, name: &str) -> String {
+        format!("{}/{}", self.topic_prefix.trim_end_matches('/'), name)
+    }
+}
+
+/// 剪贴板保护状态
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Status {
+    /// 剪贴板中有受保护条目
+    Protected(ItemId),
+    /// 没有受保护条目
+    Clear,
+}
+
+impl Status {
+    fn current(monitor: &ClipboardMonitor) -> Self {
+        monitor.current_item_id().map_or(Status::Clear, Status::Protected)
+    }
+
+    fn payload(&self) -> String {
+        match self {
+            Status::Protected(item_id) => serde_json::json!({ "state": "protected", "item": item_id }),
+            Status::Clear => serde_json::json!({ "state": "clear" }),
+        }
+        .to_string()
+    }
+}
+
+/// 遗嘱消息（服务异常断开时由代理发布）
+fn offline_payload() -> String {
+    serde_json::json!({ "state": "offline" }).to_string()
+}
+
+/// 编码剩余长度（变长整数）
+fn encode_length(mut length: usize, packet: &mut Vec<u8>) {
+    loop {
+        let mut byte = (length % 128) as u8;
+        length /= 128;
+        if length > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+        if length == 0 {
+            break;
+        }
+    }
+}
+
+/// 编码带长度前缀的字符串或数据
+fn encode_field(data: &[u8], body: &mut Vec<u8>) {
+    body.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    body.extend_from_slice(data);
+}
+
+/// 组装报文：固定报头 + 剩余长度 + 报文体
+fn packet(header: u8, body: &[u8]) -> Vec<u8> {
+    let mut packet = vec![header];
+    encode_length(body.len(), &mut packet);
+    packet.extend_from_slice(body);
+    packet
+}
+
+/// CONNECT报文（清除会话，遗嘱为保留的离线状态）
+fn connect_packet(config: &MqttConfig) -> Vec<u8> {
+    let mut flags = 0x02 | 0x04 | 0x20; // 清除会话、遗嘱、遗嘱保留
+    if !config.username.is_empty() {
+        flags |= 0x80;
+        if !config.password.is_empty() {
+            flags |= 0x40;
+        }
+    }
+
+    let mut body = Vec::new();
+    encode_field(b"MQTT", &mut body);
+    body.push(4); // 协议级别 3.1.1
+    body.push(flags);
+    body.extend_from_slice(&config.keepalive_seconds.to_be_bytes());
+    encode_field(config.client_id.as_bytes(), &mut body);
+    encode_field(config.topic("status").as_bytes(), &mut body);
+    encode_field(offline_payload().as_bytes(), &mut body);
+    if !config.username.is_empty() {
+        encode_field(config.username.as_bytes(), &mut body);
+        if !config.password.is_empty() {
+            encode_field(config.password.as_bytes(), &mut body);
+        }
+    }
+    packet(0x10, &body)
+}
+
+/// PUBLISH报文（QoS 0）
+fn publish_packet(topic: &str, payload: &[u8], retain: bool) -> Vec<u8> {
+    let mut body = Vec::new();
+    encode_field(topic.as_bytes(), &mut body);
+    body.extend_from_slice(payload);
+    packet(if retain { 0x31 } else { 0x30 }, &body)
+}
+
+/// PINGREQ报文
+const PINGREQ: [u8; 2] = [0xc0, 0x00];
+
+/// DISCONNECT报文
+const DISCONNECT: [u8; 2] = [0xe0, 0x00];
+
+/// MQTT状态发布器
+pub struct MqttPublisher {
+    /// 配置
+    config: MqttConfig,
+    /// 剪贴板监听器
+    monitor: Arc<ClipboardMonitor>,
+    /// 详情脱敏器
+    scrubber: Scrubber,
+}
+
+impl MqttPublisher {
+    /// 创建发布器
+    ///
+    /// # 参数
+    /// * `config` - MQTT配置
+    /// * `monitor` - 剪贴板监听器
+    pub fn new(config: MqttConfig, monitor: Arc<ClipboardMonitor>) -> Self {
+        MqttPublisher { config, monitor, scrubber: Scrubber::new() }
+    }
+
+    /// 持续发布，断开后按退避重连，直到监听停止
+    ///
+    /// # 参数
+    /// * `events` - 审计事件订阅
+    pub async fn run(self, mut events: Subscription) {
+        let mut delay = Duration::from_secs(1);
+        while !self.monitor.stop_requested() {
+            match self.session(&mut events).await {
+                Ok(()) => return,
+                Err(e) => {
+                    warn!("MQTT连接 {} 中断: {}，{}秒后重连", self.config.broker, e, delay.as_secs());
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+                }
+            }
+        }
+    }
+
+    /// 一次连接：建立会话后发布状态变化与销毁事件
+    async fn session(&self, events: &mut Subscription) -> io::Result<()> {
+        let mut stream = TcpStream::connect(&self.config.broker).await?;
+        stream.write_all(&connect_packet(&self.config)).await?;
+
+        let mut connack = [0u8; 4];
+        tokio::time::timeout(Duration::from_secs(10), stream.read_exact(&mut connack))
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "等待CONNACK超时"))??;
+        if connack[0] != 0x20 || connack[3] != 0 {
+            return Err(io::Error::new(io::ErrorKind::PermissionDenied, format!("代理拒绝连接（返回码 {}）", connack[3])));
+        }
+        info!("已连接MQTT代理 {}", self.config.broker);
+
+        let (mut reader, mut writer) = stream.into_split();
+        let status_topic = self.config.topic("status");
+        let destroyed_topic = self.config.topic("destroyed");
+
+        let mut status = Status::current(&self.monitor);
+        writer.write_all(&publish_packet(&status_topic, status.payload().as_bytes(), true)).await?;
+
+        let mut ticker = tokio::time::interval(STATUS_INTERVAL);
+        let keepalive = Duration::from_secs(u64::from(self.config.keepalive_seconds.max(2)) / 2);
+        let mut last_sent = tokio::time::Instant::now();
+        let mut incoming = [0u8; 256];
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {
+                    if self.monitor.stop_requested() {
+                        // 正常退出时发布离线状态，再断开（正常断开不会触发遗嘱）
+                        writer.write_all(&publish_packet(&status_topic, offline_payload().as_bytes(), true)).await?;
+                        writer.write_all(&DISCONNECT).await?;
+                        return Ok(());
+                    }
+                    let current = Status::current(&self.monitor);
+                    if current != status {
+                        status = current;
+                        writer.write_all(&publish_packet(&status_topic, status.payload().as_bytes(), true)).await?;
+                        last_sent = tokio::time::Instant::now();
+                    } else if last_sent.elapsed() >= keepalive {
+                        writer.write_all(&PINGREQ).await?;
+                        last_sent = tokio::time::Instant::now();
+                    }
+                }
+                event = events.recv() => match event {
+                    Ok(entry) if entry.event == "destroyed" => {
+                        let payload = serde_json::json!({
+                            "detail": self.scrubber.scrub(&entry.detail),
+                            "timestamp": entry.timestamp,
+                        });
+                        writer.write_all(&publish_packet(&destroyed_topic, payload.to_string().as_bytes(), false)).await?;
+                        last_sent = tokio::time::Instant::now();
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(skipped)) => warn!("MQTT发布落后，已跳过 {} 条审计记录", skipped),
+                    Err(RecvError::Closed) => return Ok(()),
+                },
+                read = reader.read(&mut incoming) => match read? {
+                    0 => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "代理关闭了连接")),
+                    // 只会收到PINGRESP，无需处理
+                    length => debug!("收到MQTT代理的 {} 字节", length),
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remaining_length_encoding() {
+        for (length, expected) in [
+            (0, vec![0x00]),
+            (127, vec![0x7f]),
+            (128, vec![0x80, 0x01]),
+            (16_383, vec![0xff, 0x7f]),
+            (2_097_152, vec![0x80, 0x80, 0x80, 0x01]),
+        ] {
+            let mut encoded = Vec::new();
+            encode_length(length, &mut encoded);
+            assert_eq!(encoded, expected, "长度 {}", length);
+        }
+    }
+
+    #[test]
+    fn test_connect_and_publish_packets() {
+        let config = MqttConfig {
+            username: "ha".to_string(),
+            password: "pw".to_string(),
+            topic_prefix: "home/clip/".to_string(),
+            ..MqttConfig::default()
+        };
+        assert_eq!(config.topic("status"), "home/clip/status");
+
+        let connect = connect_packet(&config);
+        assert_eq!(connect[0], 0x10);
+        assert_eq!(&connect[2..10], b"\x00\x04MQTT\x04\xe6");
+        assert_eq!(&connect[10..12], &60u16.to_be_bytes());
+        assert!(connect.ends_with(b"\x00\x02ha\x00\x02pw"));
+        assert_eq!(connect[1] as usize, connect.len() - 2);
+
+        let anonymous = connect_packet(&MqttConfig::default());
+        assert_eq!(anonymous[9], 0x26);
+
+        let publish = publish_packet("a/b", b"{}", true);
+        assert_eq!(publish, b"\x31\x07\x00\x03a/b{}");
+        assert_eq!(publish_packet("a/b", b"{}", false)[0], 0x30);
+    }
+}
@@ -0,0 +1,266 @@
+/*!
+ * ClipVanish™ 阅后即焚笔记模块
+ *
+ * 在本地保存加密的短笔记，只能读取一次：读取时放入受保护剪贴板并立即从磁盘删除
+ * 特点：
+ * - 每条笔记使用独立的随机密钥加密，密钥只出现在读取凭据中，从不写入磁盘
+ * - 读取凭据为 `<条目ID>.<密钥>`，凭据丢失后笔记无法恢复，只能等待过期
+ * - 过期笔记在每次访问笔记库时清除
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use aes_gcm_siv::aead::OsRng;
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+use crate::config::Config;
+use crate::crypto::{CryptoEngine, CryptoError, EncryptedData, SecureKey};
+use crate::encoding::{decode_hex, encode_hex};
+use crate::item_id::ItemId;
+use crate::platform;
+
+/// 笔记内容上限（字节）
+pub const MAX_NOTE_LENGTH: usize = 4096;
+
+/// 笔记错误类型
+#[derive(Debug)]
+pub enum NoteError {
+    /// 笔记内容为空
+    Empty,
+    /// 笔记内容超出上限
+    TooLong(usize),
+    /// 读取凭据格式错误
+    InvalidHandle(String),
+    /// 笔记不存在（已读取或已过期清除）
+    NotFound(ItemId),
+    /// 密钥与笔记不匹配
+    WrongKey(ItemId),
+    /// 加密失败
+    CryptoError(CryptoError),
+    /// 笔记库文件访问失败
+    IoError(std::io::Error),
+    /// 笔记库文件格式错误
+    FormatError(serde_json::Error),
+}
+
+impl std::fmt::Display for NoteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NoteError::Empty => write!(f, "笔记内容不能为空"),
+            NoteError::TooLong(length) => write!(f, "笔记过长: {} 字节（上限 {} 字节）", length, MAX_NOTE_LENGTH),
+            NoteError::InvalidHandle(text) => write!(f, "无效的笔记凭据: {}（格式为 <条目ID>.<密钥>）", text),
+            NoteError::NotFound(id) => write!(f, "笔记 {} 不存在（已被读取或已过期）", id),
+            NoteError::WrongKey(id) => write!(f, "笔记 {} 的密钥不正确", id),
+            NoteError::CryptoError(e) => write!(f, "笔记加密失败: {}", e),
+            NoteError::IoError(e) => write!(f, "笔记库文件访问失败: {}", e),
+            NoteError::FormatError(e) => write!(f, "笔记库文件格式错误: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for NoteError {}
+
+/// 笔记读取凭据（条目ID + 笔记密钥）
+#[derive(Clone)]
+pub struct NoteHandle {
+    /// 笔记ID
+    id: ItemId,
+    /// 笔记密钥
+    key: Zeroizing<[u8; 32]>,
+}
+
+impl NoteHandle {
+    /// 笔记ID
+    pub fn id(&self) -> ItemId {
+        self.id
+    }
+}
+
+impl std::fmt::Display for NoteHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.id, encode_hex(self.key.as_ref()))
+    }
+}
+
+impl std::fmt::Debug for NoteHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // 调试输出中不包含密钥
+        write!(f, "NoteHandle({})", self.id)
+    }
+}
+
+impl std::str::FromStr for NoteHandle {
+    type Err = NoteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || NoteError::InvalidHandle(s.chars().take(26).collect::<String>() + "…");
+        let (id, key) = s.trim().split_once('.').ok_or_else(invalid)?;
+        let id = id.parse().map_err(|_| invalid())?;
+        let key = Zeroizing::new(decode_hex(key).ok_or_else(invalid)?);
+        let key: [u8; 32] = key.as_slice().try_into().map_err(|_| invalid())?;
+        Ok(NoteHandle { id, key: Zeroizing::new(key) })
+    }
+}
+
+/// 磁盘上的笔记（只有密文）
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredNote {
+    /// 笔记ID
+    id: ItemId,
+    /// 创建时间
+    created_at: DateTime<Utc>,
+    /// 过期时间
+    expires_at: DateTime<Utc>,
+    /// 密文（Base64，含nonce）
+    ciphertext: String,
+}
+
+/// 笔记库
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct NoteStore {
+    /// 尚未读取的笔记
+    notes: Vec<StoredNote>,
+}
+
+impl NoteStore {
+    /// 默认的笔记库路径（配置目录下的notes.json）
+    pub fn default_path() -> Option<PathBuf> {
+        Config::get_config_directory()
+            .ok()
+            .map(|dir| dir.join("notes.json"))
+    }
+
+    /// 从文件加载笔记库（文件不存在时返回空笔记库），并清除过期笔记
+    ///
+    /// # 参数
+    /// * `path` - 笔记库路径
+    /// * `now` - 当前时间
+    pub fn load<P: AsRef<Path>>(path: P, now: DateTime<Utc>) -> Result<Self, NoteError> {
+        let mut store: NoteStore = match platform::read_state_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).map_err(NoteError::FormatError)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => NoteStore::default(),
+            Err(e) => return Err(NoteError::IoError(e)),
+        };
+        store.notes.retain(|note| note.expires_at > now);
+        Ok(store)
+    }
+
+    /// 保存笔记库（仅当前用户可读写）
+    ///
+    /// # 参数
+    /// * `path` - 笔记库路径
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), NoteError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(NoteError::IoError)?;
+        }
+
+        let content = serde_json::to_string_pretty(self).map_err(NoteError::FormatError)?;
+        platform::write_state(path, content.as_bytes()).map_err(NoteError::IoError)
+    }
+
+    /// 加密并加入一条笔记
+    ///
+    /// # 参数
+    /// * `content` - 笔记内容
+    /// * `ttl` - 存活时间
+    /// * `now` - 当前时间
+    ///
+    /// # 返回值
+    /// * `Result<(NoteHandle, DateTime<Utc>), NoteError>` - 读取凭据与过期时间
+    pub fn create(&mut self, content: &str, ttl: Duration, now: DateTime<Utc>) -> Result<(NoteHandle, DateTime<Utc>), NoteError> {
+        if content.is_empty() {
+            return Err(NoteError::Empty);
+        }
+        if content.len() > MAX_NOTE_LENGTH {
+            return Err(NoteError::TooLong(content.len()));
+        }
+
+        let mut key = Zeroizing::new([0u8; 32]);
+        OsRng.fill_bytes(key.as_mut());
+        let engine = CryptoEngine::from_key(SecureKey::from_bytes(*key)).map_err(NoteError::CryptoError)?;
+        let ciphertext = engine.encrypt(content.as_bytes()).map_err(NoteError::CryptoError)?.to_base64();
+
+        let expires_at = now + chrono::Duration::from_std(ttl).unwrap_or(chrono::Duration::MAX);
+        let id = ItemId::generate();
+        self.notes.push(StoredNote { id, created_at: now, expires_at, ciphertext });
+        Ok((NoteHandle { id, key }, expires_at))
+    }
+
+    /// 解密并移出一条笔记
+    ///
+    /// 密钥不正确时笔记保留，只有成功解密后才会移出；调用方需保存笔记库后再使用内容
+    ///
+    /// # 参数
+    /// * `handle` - 读取凭据
+    pub fn take(&mut self, handle: &NoteHandle) -> Result<Zeroizing<String>, NoteError> {
+        let index = self.notes
+            .iter()
+            .position(|note| note.id == handle.id)
+            .ok_or(NoteError::NotFound(handle.id))?;
+
+        let engine = CryptoEngine::from_key(SecureKey::from_bytes(*handle.key)).map_err(NoteError::CryptoError)?;
+        let plaintext = EncryptedData::from_base64(&self.notes[index].ciphertext)
+            .and_then(|data| engine.decrypt(&data))
+            .map(Zeroizing::new)
+            .map_err(|_| NoteError::WrongKey(handle.id))?;
+        let content = Zeroizing::new(String::from_utf8_lossy(&plaintext).to_string());
+
+        self.notes.remove(index);
+        Ok(content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_note_is_readable_once() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("notes.json");
+        let now = Utc::now();
+
+        let mut store = NoteStore::load(&path, now).unwrap();
+        let (handle, _) = store.create("door code 4711", Duration::from_secs(3600), now).unwrap();
+        store.save(&path).unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains("4711"));
+
+        // 凭据可以往返解析，错误的密钥不会销毁笔记
+        let handle: NoteHandle = handle.to_string().parse().unwrap();
+        let mut wrong = handle.clone();
+        wrong.key[0] ^= 1;
+        let mut store = NoteStore::load(&path, now).unwrap();
+        assert!(matches!(store.take(&wrong), Err(NoteError::WrongKey(_))));
+
+        assert_eq!(store.take(&handle).unwrap().as_str(), "door code 4711");
+        assert!(matches!(store.take(&handle), Err(NoteError::NotFound(_))));
+        assert!("not-a-handle".parse::<NoteHandle>().is_err());
+    }
+
+    #[test]
+    fn test_expired_notes_are_purged() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("notes.json");
+        let now = Utc::now();
+
+        let mut store = NoteStore::default();
+        let (handle, expires_at) = store.create("short lived", Duration::from_secs(60), now).unwrap();
+        store.save(&path).unwrap();
+
+        assert_eq!(NoteStore::load(&path, now).unwrap().notes.len(), 1);
+        let mut store = NoteStore::load(&path, expires_at).unwrap();
+        assert!(store.notes.is_empty());
+        assert!(matches!(store.take(&handle), Err(NoteError::NotFound(_))));
+
+        assert!(matches!(store.create("", Duration::from_secs(60), now), Err(NoteError::Empty)));
+        let long = "x".repeat(MAX_NOTE_LENGTH + 1);
+        assert!(matches!(store.create(&long, Duration::from_secs(60), now), Err(NoteError::TooLong(_))));
+    }
+}
@@ -0,0 +1,279 @@
+/*!
+ * ClipVanish™ 通知模块
+ *
+ * 统一发送桌面通知，并遵循系统的专注/勿扰模式
+ * 特点：
+ * - 勿扰模式下不发送一般通知（如粘贴确认提示），只记录调试日志
+ * - 关键告警（紧急销毁失败、明文暴露超出SLO）始终送达：勿扰模式下仍发送紧急通知，并改用终端响铃与窗口闪烁
+ * - 勿扰检测：Linux读取GNOME的 `show-banners` 设置，macOS读取专注模式断言文件，Windows查询用户通知状态
+ * - 通知只包含条目ID等脱敏信息，从不包含剪贴板内容；发送在后台线程进行，不阻塞剪贴板处理
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::sync::RwLock;
+use log::debug;
+use serde::{Deserialize, Serialize};
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+use crate::helpers;
+
+/// 通知级别
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    /// 一般通知，勿扰模式下不发送
+    #[cfg_attr(not(feature = "keyboard-hooks"), allow(dead_code))]
+    Normal,
+    /// 关键告警，始终送达
+    Critical,
+}
+
+/// 通知配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    /// 是否遵循系统的专注/勿扰模式
+    pub respect_focus_mode: bool,
+    /// 勿扰模式下关键告警是否响终端铃
+    pub terminal_bell: bool,
+    /// 勿扰模式下关键告警是否闪烁终端窗口（Linux需要 `WINDOWID` 与xdotool，Windows闪烁控制台窗口）
+    pub flash_window: bool,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        NotificationsConfig {
+            respect_focus_mode: true,
+            terminal_bell: true,
+            flash_window: true,
+        }
+    }
+}
+
+/// 一次通知使用的渠道
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct Channels {
+    desktop: bool,
+    bell: bool,
+    flash: bool,
+}
+
+impl NotificationsConfig {
+    /// 按级别与勿扰状态选择渠道
+    fn channels(&self, severity: Severity, focus_mode: bool) -> Channels {
+        match (severity, focus_mode && self.respect_focus_mode) {
+            (_, false) => Channels { desktop: true, ..Channels::default() },
+            (Severity::Normal, true) => Channels::default(),
+            (Severity::Critical, true) => Channels { desktop: true, bell: self.terminal_bell, flash: self.flash_window },
+        }
+    }
+}
+
+/// 进程范围的通知配置（未设置时为默认配置）
+static CONFIG: RwLock<Option<NotificationsConfig>> = RwLock::new(None);
+
+/// 设置进程范围的通知配置（加载与重新加载配置时调用）
+pub fn configure(config: NotificationsConfig) {
+    *CONFIG.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(config);
+}
+
+/// 当前的通知配置
+fn config() -> NotificationsConfig {
+    CONFIG.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone().unwrap_or_default()
+}
+
+/// 发送通知（尽力而为，失败时只记录调试日志）
+///
+/// # 参数
+/// * `severity` - 通知级别
+/// * `title` - 标题
+/// * `body` - 正文（不得包含剪贴板内容）
+pub fn notify(severity: Severity, title: &str, body: &str) {
+    let config = config();
+    let title = title.to_string();
+    let body = body.to_string();
+    let spawned = std::thread::Builder::new().name("notify".to_string()).spawn(move || {
+        let focus_mode = config.respect_focus_mode && focus_mode_active();
+        let channels = config.channels(severity, focus_mode);
+        if channels == Channels::default() {
+            debug!("专注/勿扰模式已开启，省略通知: {}", title);
+            return;
+        }
+        if channels.bell {
+            ring_bell();
+        }
+        if channels.flash {
+            flash_window();
+        }
+        if channels.desktop {
+            notify_desktop(severity, &title, &body);
+        }
+    });
+    if let Err(e) = spawned {
+        debug!("无法启动通知线程: {}", e);
+    }
+}
+
+/// 响终端铃（stderr不是终端时跳过）
+fn ring_bell() {
+    use std::io::{IsTerminal, Write};
+
+    let mut stderr = std::io::stderr();
+    if stderr.is_terminal() {
+        let _ = stderr.write_all(b"\x07");
+        let _ = stderr.flush();
+    }
+}
+
+/// 发送桌面通知
+fn notify_desktop(severity: Severity, title: &str, body: &str) {
+    #[cfg(target_os = "linux")]
+    let result = {
+        let urgency = match severity {
+            Severity::Normal => "--urgency=normal",
+            Severity::Critical => "--urgency=critical",
+        };
+        helpers::output("notify-send", &[urgency, "--app-name=ClipVanish", title, body])
+    };
+
+    #[cfg(target_os = "macos")]
+    let result = {
+        let _ = severity;
+        let quote = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"");
+        let script = format!("display notification \"{}\" with title \"{}\"", quote(body), quote(title));
+        helpers::output("osascript", &["-e", &script])
+    };
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    let result: Result<std::process::Output, String> = {
+        let _ = (severity, title, body);
+        Err("当前平台不支持桌面通知".to_string())
+    };
+
+    match result {
+        Ok(output) if output.status.success() => {}
+        Ok(output) => debug!("桌面通知发送失败，退出码: {:?}", output.status.code()),
+        Err(e) => debug!("桌面通知发送失败: {}", e),
+    }
+}
+
+/// 系统的专注/勿扰模式是否开启（无法判断时视为未开启）
+pub fn focus_mode_active() -> bool {
+    detect_focus_mode().unwrap_or(false)
+}
+
+/// Linux: GNOME关闭横幅通知即为勿扰模式
+#[cfg(target_os = "linux")]
+fn detect_focus_mode() -> Option<bool> {
+    let output = helpers::output("gsettings", &["get", "org.gnome.desktop.notifications", "show-banners"])
+        .ok()
+        .filter(|output| output.status.success())?;
+    Some(String::from_utf8_lossy(&output.stdout).trim() == "false")
+}
+
+/// macOS: 专注模式开启时断言文件中存在断言记录（macOS 12及以上）
+#[cfg(target_os = "macos")]
+fn detect_focus_mode() -> Option<bool> {
+    let home = std::env::var_os("HOME")?;
+    let path = std::path::Path::new(&home).join("Library/DoNotDisturb/DB/Assertions.json");
+    std::fs::read_to_string(path).ok().map(|json| assertions_active(&json))
+}
+
+/// Windows: 用户通知状态不是"接受通知"时视为勿扰（专注助手、全屏应用、演示模式等）
+#[cfg(target_os = "windows")]
+fn detect_focus_mode() -> Option<bool> {
+    use winapi::um::shellapi::{SHQueryUserNotificationState, QUNS_ACCEPTS_NOTIFICATIONS};
+
+    let mut state = 0;
+    let result = unsafe { SHQueryUserNotificationState(&mut state) };
+    (result == 0).then_some(state != QUNS_ACCEPTS_NOTIFICATIONS)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn detect_focus_mode() -> Option<bool> {
+    None
+}
+
+/// 专注模式断言文件中是否存在断言记录
+///
+/// # 参数
+/// * `json` - `Assertions.json` 的内容
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn assertions_active(json: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(json)
+        .ok()
+        .and_then(|value| value.get("data")?.as_array().cloned())
+        .is_some_and(|data| {
+            data.iter().any(|entry| {
+                entry
+                    .get("storeAssertionRecords")
+                    .and_then(serde_json::Value::as_array)
+                    .is_some_and(|records| !records.is_empty())
+            })
+        })
+}
+
+/// Linux: 通过xdotool为终端窗口（`WINDOWID`）设置紧急提示
+#[cfg(target_os = "linux")]
+fn flash_window() {
+    let Ok(window) = std::env::var("WINDOWID") else {
+        debug!("未设置WINDOWID，无法闪烁终端窗口");
+        return;
+    };
+    if let Err(e) = helpers::output("xdotool", &["set_window", "--urgency", "1", &window]) {
+        debug!("闪烁终端窗口失败: {}", e);
+    }
+}
+
+/// Windows: 闪烁控制台窗口与任务栏按钮，直到窗口回到前台
+#[cfg(target_os = "windows")]
+fn flash_window() {
+    use winapi::um::wincon::GetConsoleWindow;
+    use winapi::um::winuser::{FlashWindowEx, FLASHWINFO, FLASHW_ALL, FLASHW_TIMERNOFG};
+
+    let window = unsafe { GetConsoleWindow() };
+    if window.is_null() {
+        return;
+    }
+    let mut info = FLASHWINFO {
+        cbSize: std::mem::size_of::<FLASHWINFO>() as u32,
+        hwnd: window,
+        dwFlags: FLASHW_ALL | FLASHW_TIMERNOFG,
+        uCount: 0,
+        dwTimeout: 0,
+    };
+    unsafe { FlashWindowEx(&mut info) };
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn flash_window() {
+    debug!("当前平台不支持闪烁终端窗口");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channels() {
+        let config = NotificationsConfig::default();
+        let desktop = Channels { desktop: true, ..Channels::default() };
+        assert_eq!(config.channels(Severity::Normal, false), desktop);
+        assert_eq!(config.channels(Severity::Critical, false), desktop);
+        assert_eq!(config.channels(Severity::Normal, true), Channels::default());
+        assert_eq!(config.channels(Severity::Critical, true), Channels { desktop: true, bell: true, flash: true });
+
+        let quiet = NotificationsConfig { flash_window: false, ..config.clone() };
+        assert_eq!(quiet.channels(Severity::Critical, true), Channels { desktop: true, bell: true, flash: false });
+
+        let ignore = NotificationsConfig { respect_focus_mode: false, ..config };
+        assert_eq!(ignore.channels(Severity::Normal, true), desktop);
+    }
+
+    #[test]
+    fn test_assertions_active() {
+        assert!(assertions_active(r#"{"data":[{"storeAssertionRecords":[{"assertionDetails":{"assertionDetailsModeIdentifier":"com.apple.donotdisturb.mode.default"}}]}]}"#));
+        assert!(!assertions_active(r#"{"data":[{"storeAssertionRecords":[]}]}"#));
+        assert!(!assertions_active(r#"{"data":[{}]}"#));
+        assert!(!assertions_active("not json"));
+    }
+}
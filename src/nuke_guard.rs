@@ -0,0 +1,446 @@
+/*!
+ * ClipVanish™ 紧急销毁双人确认模块
+ *
+ * 共享运维工作站上，`nuke` 命令、控制接口、热键、菜单与SIGUSR1信号触发的紧急销毁需要第二因素，避免他人误触或恶意清除
+ * 特点：
+ * - TOTP验证码（RFC 6238，HMAC-SHA1），允许前后各一个时间步的时钟偏差；已使用的验证码不能重复使用；
+ *   连续输错后暂停验证（与控制接口口令相同的失败锁定），计数随状态文件落盘，跨进程生效
+ * - 硬件密钥触摸（`hardware-keys` 特性）：通过 `ykchalresp` 向YubiKey发送登记时的挑战值，响应的SHA-256与配置一致即通过
+ *   （槽位设置了触摸要求时需按下密钥）；未启用该特性的构建忽略配置中的硬件密钥
+ * - 通过与拒绝都记入审计日志（`nuke_authorized` / `nuke_denied`）
+ * - 只约束人为发起的销毁；热键、菜单与信号无法输入验证码，只能通过硬件密钥确认；定时任务到期与自动防护触发的销毁不受影响
+ *
+ * 注意：需求中的第三种因素（经同步通道由第二台设备确认）未实现，本项目没有设备间同步通道
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+#[cfg(feature = "hardware-keys")]
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+use crate::audit;
+use crate::config::Config;
+use crate::crypto::constant_time_eq;
+#[cfg(feature = "hardware-keys")]
+use crate::encoding::encode_hex;
+#[cfg(feature = "hardware-keys")]
+use crate::helpers;
+use crate::lockout::FailureLockout;
+use crate::platform;
+
+/// Base32字母表（RFC 4648）
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// 硬件密钥配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HardwareKeyConfig {
+    /// YubiKey挑战-响应槽位（1或2）
+    #[serde(default = "default_slot")]
+    pub slot: u8,
+    /// 登记时的挑战值（十六进制）
+    pub challenge: String,
+    /// 预期响应的SHA-256（十六进制）
+    pub response_sha256: String,
+}
+
+fn default_slot() -> u8 {
+    2
+}
+
+/// 紧急销毁双人确认配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NukeGuardConfig {
+    /// 是否要求第二因素
+    pub enabled: bool,
+    /// TOTP共享密钥（Base32），由 `guard setup-totp` 生成
+    pub totp_secret: Option<String>,
+    /// TOTP验证码位数
+    pub totp_digits: u32,
+    /// TOTP时间步长（秒）
+    pub totp_step: u64,
+    /// 硬件密钥，由 `guard enroll-key` 登记
+    pub hardware_key: Option<HardwareKeyConfig>,
+}
+
+impl NukeGuardConfig {
+    /// 当前构建可用的硬件密钥（未启用 `hardware-keys` 特性时为None）
+    pub fn active_hardware_key(&self) -> Option<&HardwareKeyConfig> {
+        self.hardware_key.as_ref().filter(|_| cfg!(feature = "hardware-keys"))
+    }
+}
+
+impl Default for NukeGuardConfig {
+    fn default() -> Self {
+        NukeGuardConfig {
+            enabled: false,
+            totp_secret: None,
+            totp_digits: 6,
+            totp_step: 30,
+            hardware_key: None,
+        }
+    }
+}
+
+/// 双人确认错误类型
+#[derive(Debug)]
+pub enum NukeGuardError {
+    /// 未提供第二因素
+    FactorRequired,
+    /// 未配置对应的第二因素
+    NotConfigured(&'static str),
+    /// TOTP共享密钥格式错误
+    InvalidSecret,
+    /// 验证码错误或已过期
+    InvalidCode,
+    /// 验证码已使用过
+    ReusedCode,
+    /// 验证码错误次数过多，暂停验证（剩余秒数）
+    RateLimited(u64),
+    /// 硬件密钥调用失败
+    #[cfg(feature = "hardware-keys")]
+    HardwareKeyError(String),
+    /// 硬件密钥响应不匹配
+    #[cfg(feature = "hardware-keys")]
+    HardwareKeyMismatch,
+    /// 状态文件访问失败
+    IoError(std::io::Error),
+}
+
+impl std::fmt::Display for NukeGuardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NukeGuardError::FactorRequired => write!(f, "紧急销毁需要第二因素（TOTP验证码或硬件密钥）"),
+            NukeGuardError::NotConfigured(factor) => write!(f, "未配置{}", factor),
+            NukeGuardError::InvalidSecret => write!(f, "TOTP共享密钥不是有效的Base32"),
+            NukeGuardError::InvalidCode => write!(f, "验证码错误或已过期"),
+            NukeGuardError::ReusedCode => write!(f, "验证码已使用过，请等待下一个验证码"),
+            NukeGuardError::RateLimited(seconds) => write!(f, "验证码错误次数过多，请在{}秒后重试", seconds),
+            #[cfg(feature = "hardware-keys")]
+            NukeGuardError::HardwareKeyError(e) => write!(f, "硬件密钥调用失败: {}", e),
+            #[cfg(feature = "hardware-keys")]
+            NukeGuardError::HardwareKeyMismatch => write!(f, "硬件密钥响应与登记的不一致"),
+            NukeGuardError::IoError(e) => write!(f, "双人确认状态文件访问失败: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for NukeGuardError {}
+
+/// 通过验证的第二因素
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SecondFactor {
+    /// TOTP验证码
+    Totp,
+    /// 硬件密钥触摸
+    #[cfg(feature = "hardware-keys")]
+    HardwareKey,
+}
+
+impl SecondFactor {
+    /// 审计记录中的名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            SecondFactor::Totp => "totp",
+            #[cfg(feature = "hardware-keys")]
+            SecondFactor::HardwareKey => "hardware_key",
+        }
+    }
+}
+
+/// 持久化的验证状态（防止验证码重放，进程重启后仍有效）
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct GuardState {
+    /// 最近一次使用的TOTP计数
+    last_totp_counter: Option<u64>,
+    /// 连续验证码错误计数
+    #[serde(default)]
+    totp_failures: FailureLockout,
+}
+
+/// 状态文件位置（配置目录下的nuke_guard.json）
+fn state_path() -> Result<PathBuf, NukeGuardError> {
+    Config::get_config_directory()
+        .map(|dir| dir.join("nuke_guard.json"))
+        .map_err(|e| NukeGuardError::IoError(std::io::Error::new(std::io::ErrorKind::NotFound, e.to_string())))
+}
+
+impl GuardState {
+    /// 加载状态（文件不存在或损坏时为初始状态）
+    fn load(path: &Path) -> Self {
+        platform::read_state_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// 保存状态
+    fn save(&self, path: &Path) -> Result<(), NukeGuardError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(NukeGuardError::IoError)?;
+        }
+        let content = serde_json::to_string(self).unwrap_or_default();
+        platform::write_state(path, content.as_bytes()).map_err(NukeGuardError::IoError)
+    }
+}
+
+/// Base32编码（无填充）
+pub fn encode_base32(bytes: &[u8]) -> String {
+    let mut encoded = String::new();
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    encoded
+}
+
+/// Base32解码（不区分大小写，忽略空格、连字符与填充）
+pub fn decode_base32(text: &str) -> Option<Vec<u8>> {
+    let mut decoded = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for c in text.chars().filter(|c| !matches!(c, ' ' | '-' | '=')) {
+        let value = BASE32_ALPHABET.iter().position(|&a| a as char == c.to_ascii_uppercase())? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((buffer >> bits) as u8);
+        }
+    }
+    (!decoded.is_empty()).then_some(decoded)
+}
+
+/// 计算HOTP验证码（RFC 4226）
+///
+/// # 参数
+/// * `secret` - 共享密钥
+/// * `counter` - 计数
+/// * `digits` - 位数
+pub fn hotp(secret: &[u8], counter: u64, digits: u32) -> String {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC接受任意长度的密钥");
+    mac.update(&counter.to_be_bytes());
+    let mac = mac.finalize().into_bytes();
+    let offset = (mac[19] & 0x0f) as usize;
+    let value = u32::from_be_bytes([mac[offset] & 0x7f, mac[offset + 1], mac[offset + 2], mac[offset + 3]]);
+    format!("{:0width$}", value % 10u32.pow(digits), width = digits as usize)
+}
+
+/// 校验TOTP验证码，返回匹配的计数
+///
+/// # 参数
+/// * `config` - 双人确认配置
+/// * `code` - 用户输入的验证码
+/// * `now` - 当前Unix时间（秒）
+/// * `last_counter` - 最近一次使用的计数（不早于它的验证码视为重放）
+fn verify_totp(config: &NukeGuardConfig, code: &str, now: u64, last_counter: Option<u64>) -> Result<u64, NukeGuardError> {
+    let secret = config.totp_secret.as_deref().ok_or(NukeGuardError::NotConfigured("TOTP共享密钥"))?;
+    let secret = Zeroizing::new(decode_base32(secret).ok_or(NukeGuardError::InvalidSecret)?);
+    let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+
+    let current = now / config.totp_step.max(1);
+    let matched = [current.saturating_sub(1), current, current + 1]
+        .into_iter()
+        .find(|&counter| constant_time_eq(hotp(&secret, counter, config.totp_digits).as_bytes(), code.as_bytes()))
+        .ok_or(NukeGuardError::InvalidCode)?;
+    if last_counter.is_some_and(|last| matched <= last) {
+        return Err(NukeGuardError::ReusedCode);
+    }
+    Ok(matched)
+}
+
+/// 向硬件密钥发送挑战值，返回响应的SHA-256（十六进制）
+///
+/// # 参数
+/// * `slot` - 挑战-响应槽位
+/// * `challenge` - 挑战值（十六进制）
+#[cfg(feature = "hardware-keys")]
+fn challenge_response(slot: u8, challenge: &str) -> Result<String, NukeGuardError> {
+    let slot = format!("-{}", slot);
+    let output = helpers::output("ykchalresp", &[&slot, "-x", challenge]).map_err(NukeGuardError::HardwareKeyError)?;
+    if !output.status.success() {
+        return Err(NukeGuardError::HardwareKeyError(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+    let response = Zeroizing::new(String::from_utf8_lossy(&output.stdout).trim().to_lowercase());
+    let mut hasher = Sha256::default();
+    hasher.update(response.as_bytes());
+    Ok(encode_hex(&hasher.finalize()))
+}
+
+/// 验证第二因素：提供了验证码时校验TOTP，否则请求硬件密钥触摸
+///
+/// 结果记入审计日志
+///
+/// # 参数
+/// * `config` - 双人确认配置
+/// * `code` - TOTP验证码
+/// * `source` - 销毁请求来源（用于审计）
+pub fn authorize(config: &NukeGuardConfig, code: Option<&str>, source: &str) -> Result<SecondFactor, NukeGuardError> {
+    let result = match (code, config.active_hardware_key()) {
+        (Some(code), _) => authorize_totp(config, code),
+        #[cfg(feature = "hardware-keys")]
+        (None, Some(key)) => match challenge_response(key.slot, &key.challenge) {
+            Ok(hash) if constant_time_eq(hash.as_bytes(), key.response_sha256.to_lowercase().as_bytes()) => {
+                Ok(SecondFactor::HardwareKey)
+            }
+            Ok(_) => Err(NukeGuardError::HardwareKeyMismatch),
+            Err(e) => Err(e),
+        },
+        (None, _) => Err(NukeGuardError::FactorRequired),
+    };
+
+    match &result {
+        Ok(factor) => audit::record("nuke_authorized", &format!("来源={} 因素={}", source, factor.name())),
+        Err(e) => audit::record("nuke_denied", &format!("来源={} 原因={}", source, e)),
+    }
+    result
+}
+
+/// 校验TOTP验证码并记录已使用的计数与连续错误
+fn authorize_totp(config: &NukeGuardConfig, code: &str) -> Result<SecondFactor, NukeGuardError> {
+    let path = state_path()?;
+    let mut state = GuardState::load(&path);
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+    let result = check_totp(&mut state, config, code, now);
+    if !matches!(result, Err(NukeGuardError::RateLimited(_))) {
+        state.save(&path)?;
+    }
+    result.map(|_| SecondFactor::Totp)
+}
+
+/// 在暂停期外校验验证码，更新状态中的计数与连续错误
+fn check_totp(state: &mut GuardState, config: &NukeGuardConfig, code: &str, now: u64) -> Result<(), NukeGuardError> {
+    let now_secs = i64::try_from(now).unwrap_or(i64::MAX);
+    if let Some(remaining) = state.totp_failures.locked_for(now_secs) {
+        return Err(NukeGuardError::RateLimited(remaining));
+    }
+
+    match verify_totp(config, code, now, state.last_totp_counter) {
+        Ok(counter) => {
+            state.last_totp_counter = Some(counter);
+            state.totp_failures.reset();
+            Ok(())
+        }
+        Err(e) => {
+            if matches!(e, NukeGuardError::InvalidCode | NukeGuardError::ReusedCode) {
+                state.totp_failures.record_failure(now_secs);
+            }
+            Err(e)
+        }
+    }
+}
+
+/// 生成新的TOTP共享密钥（160位，Base32）
+pub fn generate_totp_secret() -> String {
+    let mut secret = Zeroizing::new([0u8; 20]);
+    rand::rngs::OsRng.fill_bytes(secret.as_mut());
+    encode_base32(secret.as_ref())
+}
+
+/// 生成供验证器应用扫描或导入的otpauth链接
+///
+/// # 参数
+/// * `config` - 双人确认配置（位数与步长）
+/// * `secret` - TOTP共享密钥
+pub fn otpauth_uri(config: &NukeGuardConfig, secret: &str) -> String {
+    format!(
+        "otpauth://totp/ClipVanish:nuke?secret={}&issuer=ClipVanish&algorithm=SHA1&digits={}&period={}",
+        secret, config.totp_digits, config.totp_step
+    )
+}
+
+/// 登记硬件密钥：生成随机挑战值并记录响应的SHA-256
+///
+/// # 参数
+/// * `slot` - 挑战-响应槽位
+#[cfg(feature = "hardware-keys")]
+pub fn enroll_hardware_key(slot: u8) -> Result<HardwareKeyConfig, NukeGuardError> {
+    let mut challenge = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut challenge);
+    let challenge = encode_hex(&challenge);
+    let response_sha256 = challenge_response(slot, &challenge)?;
+    Ok(HardwareKeyConfig { slot, challenge, response_sha256 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc_vectors() {
+        // RFC 4226 附录D 与 RFC 6238 附录B（SHA-1）
+        let secret = b"12345678901234567890";
+        assert_eq!(hotp(secret, 0, 6), "755224");
+        assert_eq!(hotp(secret, 9, 6), "520489");
+        assert_eq!(hotp(secret, 59 / 30, 8), "94287082");
+        assert_eq!(hotp(secret, 1111111109 / 30, 8), "07081804");
+
+        let encoded = encode_base32(secret);
+        assert_eq!(encoded, "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+        assert_eq!(decode_base32(&encoded.to_lowercase()).unwrap(), secret);
+        assert!(decode_base32("not base32!").is_none());
+    }
+
+    #[test]
+    fn test_verify_totp_window_and_replay() {
+        let config = NukeGuardConfig {
+            enabled: true,
+            totp_secret: Some("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string()),
+            ..NukeGuardConfig::default()
+        };
+        let secret = decode_base32(config.totp_secret.as_ref().unwrap()).unwrap();
+        let now = 1_700_000_000;
+        let counter = now / 30;
+
+        assert_eq!(verify_totp(&config, &hotp(&secret, counter, 6), now, None).unwrap(), counter);
+        assert_eq!(verify_totp(&config, &hotp(&secret, counter - 1, 6), now, None).unwrap(), counter - 1);
+        assert!(matches!(verify_totp(&config, &hotp(&secret, counter - 2, 6), now, None), Err(NukeGuardError::InvalidCode)));
+        assert!(matches!(verify_totp(&config, &hotp(&secret, counter, 6), now, Some(counter)), Err(NukeGuardError::ReusedCode)));
+        assert!(matches!(verify_totp(&NukeGuardConfig { totp_secret: None, ..config }, "123456", now, None), Err(NukeGuardError::NotConfigured(_))));
+    }
+
+    #[test]
+    fn test_totp_failures_lock_out() {
+        let config = NukeGuardConfig {
+            enabled: true,
+            totp_secret: Some("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string()),
+            ..NukeGuardConfig::default()
+        };
+        let secret = decode_base32(config.totp_secret.as_ref().unwrap()).unwrap();
+        let now = 1_700_000_000;
+        let valid = hotp(&secret, now / 30, 6);
+        let wrong = if valid == "000000" { "111111" } else { "000000" };
+
+        let mut state = GuardState::default();
+        for _ in 0..crate::lockout::MAX_FAILURES {
+            assert!(matches!(check_totp(&mut state, &config, wrong, now), Err(NukeGuardError::InvalidCode)));
+        }
+        // 暂停期内正确的验证码同样被拒绝
+        assert!(matches!(check_totp(&mut state, &config, &valid, now + 10), Err(NukeGuardError::RateLimited(50))));
+        assert_eq!(state.last_totp_counter, None);
+
+        // 暂停期后可以验证，成功后清零
+        let later = now + 60;
+        check_totp(&mut state, &config, &hotp(&secret, later / 30, 6), later).unwrap();
+        assert_eq!(state.totp_failures, FailureLockout::default());
+
+        // 错误计数随状态文件保存
+        state.totp_failures.record_failure(1);
+        let restored: GuardState = serde_json::from_str(&serde_json::to_string(&state).unwrap()).unwrap();
+        assert_eq!(restored.totp_failures, state.totp_failures);
+    }
+}
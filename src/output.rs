@@ -0,0 +1,99 @@
+/*!
+ * ClipVanish™ 输出通道模块
+ *
+ * 区分面向用户的提示信息与面向脚本的机器输出，便于在管道中使用
+ * 特点：
+ * - 横幅、表情状态行等提示信息写入stderr，--silent 时不输出
+ * - stdout只保留机器输出（JSON、get 命令的内容、新令牌）
+ * - --output json 时状态类命令以JSON输出
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 是否抑制提示信息
+static SILENT: AtomicBool = AtomicBool::new(false);
+
+/// 机器输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// 人类可读文本
+    #[default]
+    Text,
+    /// JSON
+    Json,
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputFormat::Text => write!(f, "text"),
+            OutputFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// 设置是否抑制提示信息
+pub fn set_silent(silent: bool) {
+    SILENT.store(silent, Ordering::SeqCst);
+}
+
+/// 是否抑制提示信息
+pub fn is_silent() -> bool {
+    SILENT.load(Ordering::SeqCst)
+}
+
+/// 输出一行提示信息到stderr（--silent 时不输出）
+#[macro_export]
+macro_rules! notice {
+    ($($arg:tt)*) => {
+        if !$crate::output::is_silent() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+/// 输出不换行的提示信息到stderr并立即刷新（用于倒计时、进度条等原地刷新的行）
+#[macro_export]
+macro_rules! notice_inline {
+    ($($arg:tt)*) => {
+        if !$crate::output::is_silent() {
+            eprint!($($arg)*);
+            let _ = std::io::Write::flush(&mut std::io::stderr());
+        }
+    };
+}
+
+pub use crate::{notice, notice_inline};
+
+/// 输出JSON到stdout
+pub fn emit_json(value: &serde_json::Value) {
+    println!("{}", serde_json::to_string_pretty(value).unwrap_or_else(|_| "{}".to_string()));
+}
+
+/// 原样输出内容到stdout（不追加换行，便于管道传递）
+pub fn emit_raw(content: &str) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(content.as_bytes())?;
+    stdout.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::ValueEnum;
+
+    #[test]
+    fn test_output_format() {
+        assert_eq!(OutputFormat::default(), OutputFormat::Text);
+        assert_eq!(OutputFormat::from_str("json", true).unwrap(), OutputFormat::Json);
+        assert_eq!(OutputFormat::Json.to_string(), "json");
+
+        set_silent(true);
+        assert!(is_silent());
+        set_silent(false);
+        assert!(!is_silent());
+    }
+}
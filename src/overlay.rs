@@ -0,0 +1,670 @@
+/*!
+ * ClipVanish™ 倒计时悬浮窗模块
+ *
+ * 在屏幕角落或光标旁显示一个始终置顶的半透明小窗口，实时显示当前受保护条目的剩余时间，
+ * 避免在不知情时粘贴已过期的内容
+ * 特点：
+ * - 由后台线程持有窗口，没有进行中的倒计时或已隐藏时窗口不显示
+ * - 窗口不接收焦点，不出现在任务栏，不影响正在输入的程序
+ * - 只显示剩余时间，从不显示内容
+ * - Linux使用X11（Wayland会话需要XWayland），Windows使用分层置顶窗口，macOS通过osascript创建浮动窗口
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use crate::countdown_display;
+#[cfg(target_os = "linux")]
+use self::linux::Surface;
+#[cfg(target_os = "windows")]
+use self::windows::Surface;
+#[cfg(target_os = "macos")]
+use self::macos::Surface;
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+use self::unsupported::Surface;
+
+/// 刷新间隔
+const REFRESH_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 窗口尺寸（像素）
+const WIDTH: i32 = 96;
+const HEIGHT: i32 = 28;
+
+/// 与屏幕边缘或光标的距离（像素）
+const MARGIN: i32 = 16;
+
+/// 悬浮窗位置
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlayPosition {
+    /// 跟随光标（显示在光标右下方）
+    Cursor,
+    /// 左上角
+    TopLeft,
+    /// 右上角
+    TopRight,
+    /// 左下角
+    BottomLeft,
+    /// 右下角
+    BottomRight,
+}
+
+impl OverlayPosition {
+    /// 计算窗口左上角坐标（屏幕坐标，原点在左上角）
+    ///
+    /// # 参数
+    /// * `screen` - 屏幕宽高
+    /// * `cursor` - 光标位置，无法获取时跟随光标的窗口退回右下角
+    pub fn origin(&self, screen: (i32, i32), cursor: Option<(i32, i32)>) -> (i32, i32) {
+        let right = screen.0 - WIDTH - MARGIN;
+        let bottom = screen.1 - HEIGHT - MARGIN;
+        match (self, cursor) {
+            // 靠近屏幕边缘时保持在屏幕内
+            (OverlayPosition::Cursor, Some((x, y))) => ((x + MARGIN).clamp(0, right.max(0)), (y + MARGIN).clamp(0, bottom.max(0))),
+            (OverlayPosition::TopLeft, _) => (MARGIN, MARGIN),
+            (OverlayPosition::TopRight, _) => (right, MARGIN),
+            (OverlayPosition::BottomLeft, _) => (MARGIN, bottom),
+            (OverlayPosition::BottomRight, _) | (OverlayPosition::Cursor, None) => (right, bottom),
+        }
+    }
+}
+
+/// 悬浮窗配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverlayConfig {
+    /// 服务启动时是否显示悬浮窗（关闭时仍可用热键临时显示）
+    #[serde(default)]
+    pub enabled: bool,
+    /// 显示位置
+    #[serde(default = "default_overlay_position")]
+    pub position: OverlayPosition,
+    /// 不透明度（0到1之间，1为完全不透明）
+    #[serde(default = "default_overlay_opacity")]
+    pub opacity: f64,
+}
+
+fn default_overlay_position() -> OverlayPosition {
+    OverlayPosition::BottomRight
+}
+
+fn default_overlay_opacity() -> f64 {
+    0.85
+}
+
+impl Default for OverlayConfig {
+    fn default() -> Self {
+        OverlayConfig {
+            enabled: false,
+            position: default_overlay_position(),
+            opacity: default_overlay_opacity(),
+        }
+    }
+}
+
+/// 悬浮窗中显示的文字，例如 `12s`、`2m05s`
+///
+/// 不带图标：X11核心字体无法绘制emoji
+///
+/// # 参数
+/// * `remaining` - 剩余时间
+pub fn label(remaining: Duration) -> String {
+    countdown_display::compact(remaining).trim_start_matches("🔒 ").to_string()
+}
+
+/// 调用方与窗口线程共享的状态
+#[derive(Default)]
+struct OverlayState {
+    /// 当前倒计时的截止时间
+    deadline: Mutex<Option<Instant>>,
+    /// 是否显示
+    visible: AtomicBool,
+    /// 窗口线程是否应退出
+    stopped: AtomicBool,
+}
+
+impl OverlayState {
+    /// 此刻应显示的文字，不应显示时为None
+    fn text(&self, now: Instant) -> Option<String> {
+        if !self.visible.load(Ordering::SeqCst) {
+            return None;
+        }
+        let deadline = (*self.deadline.lock().unwrap())?;
+        let remaining = deadline.checked_duration_since(now).filter(|remaining| !remaining.is_zero())?;
+        // 向上取整，最后不足一秒时显示1s而不是0s
+        Some(label(Duration::from_secs(remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0))))
+    }
+}
+
+/// 倒计时悬浮窗
+pub struct Overlay {
+    /// 配置
+    config: OverlayConfig,
+    /// 共享状态
+    state: Arc<OverlayState>,
+    /// 窗口线程（第一次显示时启动）
+    thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Overlay {
+    /// 创建悬浮窗，配置启用时立即显示
+    ///
+    /// # 参数
+    /// * `config` - 悬浮窗配置
+    pub fn new(config: OverlayConfig) -> Self {
+        let overlay = Overlay {
+            config,
+            state: Arc::new(OverlayState::default()),
+            thread: Mutex::new(None),
+        };
+        if overlay.config.enabled {
+            if let Err(e) = overlay.set_visible(true) {
+                warn!("无法显示倒计时悬浮窗: {}", e);
+            }
+        }
+        overlay
+    }
+
+    /// 更新剩余时间（倒计时启动、延长时调用；结束或取消时传入None）
+    ///
+    /// # 参数
+    /// * `remaining` - 剩余时间
+    pub fn set_remaining(&self, remaining: Option<Duration>) {
+        *self.state.deadline.lock().unwrap() = remaining.map(|remaining| Instant::now() + remaining);
+    }
+
+    /// 切换显示/隐藏
+    ///
+    /// # 返回值
+    /// * `Result<bool, String>` - 切换后是否显示，窗口无法创建时返回错误
+    #[cfg_attr(not(feature = "hotkeys"), allow(dead_code))]
+    pub fn toggle(&self) -> Result<bool, String> {
+        let visible = !self.state.visible.load(Ordering::SeqCst);
+        self.set_visible(visible)?;
+        Ok(visible)
+    }
+
+    /// 显示或隐藏（第一次显示时创建窗口）
+    fn set_visible(&self, visible: bool) -> Result<(), String> {
+        if visible {
+            self.ensure_started()?;
+        }
+        self.state.visible.store(visible, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// 启动窗口线程并等待窗口创建完成
+    fn ensure_started(&self) -> Result<(), String> {
+        let mut thread = self.thread.lock().unwrap();
+        if thread.is_some() {
+            return Ok(());
+        }
+
+        let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+        let state = self.state.clone();
+        let config = self.config.clone();
+        let handle = thread::Builder::new()
+            .name("clipvanish-overlay".to_string())
+            .spawn(move || run(state, config, ready_tx))
+            .map_err(|e| e.to_string())?;
+
+        ready_rx
+            .recv()
+            .map_err(|_| "悬浮窗线程意外退出".to_string())??;
+        info!("倒计时悬浮窗已创建");
+        *thread = Some(handle);
+        Ok(())
+    }
+}
+
+impl Drop for Overlay {
+    fn drop(&mut self) {
+        self.state.stopped.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.lock().unwrap().take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// 窗口线程：创建窗口后按刷新间隔更新文字与位置，直到悬浮窗被释放
+fn run(state: Arc<OverlayState>, config: OverlayConfig, ready: std::sync::mpsc::Sender<Result<(), String>>) {
+    let mut surface = match Surface::open(&config) {
+        Ok(surface) => surface,
+        Err(e) => {
+            let _ = ready.send(Err(e));
+            return;
+        }
+    };
+    let _ = ready.send(Ok(()));
+
+    while !state.stopped.load(Ordering::SeqCst) {
+        surface.update(state.text(Instant::now()).as_deref());
+        thread::sleep(REFRESH_INTERVAL);
+    }
+    debug!("倒计时悬浮窗已关闭");
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::{OverlayConfig, OverlayPosition, HEIGHT, WIDTH};
+    use std::os::raw::{c_char, c_int, c_uint, c_ulong};
+    use std::ptr;
+    use x11::xlib;
+
+    /// X11悬浮窗（override-redirect窗口，不受窗口管理器管理，不接收焦点）
+    pub(super) struct Surface {
+        display: *mut xlib::Display,
+        screen: c_int,
+        window: xlib::Window,
+        gc: xlib::GC,
+        position: OverlayPosition,
+        mapped: bool,
+    }
+
+    impl Surface {
+        pub(super) fn open(config: &OverlayConfig) -> Result<Self, String> {
+            unsafe {
+                let display = xlib::XOpenDisplay(ptr::null());
+                if display.is_null() {
+                    return Err("无法连接X11显示（Wayland会话需要XWayland）".to_string());
+                }
+                let screen = xlib::XDefaultScreen(display);
+                let root = xlib::XRootWindow(display, screen);
+
+                let mut attributes: xlib::XSetWindowAttributes = std::mem::zeroed();
+                attributes.override_redirect = xlib::True;
+                attributes.background_pixel = xlib::XBlackPixel(display, screen);
+                let window = xlib::XCreateWindow(
+                    display,
+                    root,
+                    0,
+                    0,
+                    WIDTH as c_uint,
+                    HEIGHT as c_uint,
+                    0,
+                    xlib::CopyFromParent,
+                    xlib::InputOutput as c_uint,
+                    ptr::null_mut(),
+                    xlib::CWOverrideRedirect | xlib::CWBackPixel,
+                    &mut attributes,
+                );
+
+                // 半透明需要合成管理器支持_NET_WM_WINDOW_OPACITY，否则以不透明显示
+                let opacity_atom = xlib::XInternAtom(display, c"_NET_WM_WINDOW_OPACITY".as_ptr(), xlib::False);
+                let opacity = (config.opacity.clamp(0.0, 1.0) * u32::MAX as f64) as c_ulong;
+                xlib::XChangeProperty(
+                    display,
+                    window,
+                    opacity_atom,
+                    xlib::XA_CARDINAL,
+                    32,
+                    xlib::PropModeReplace,
+                    &opacity as *const c_ulong as *const u8,
+                    1,
+                );
+
+                let gc = xlib::XCreateGC(display, window, 0, ptr::null_mut());
+                xlib::XSetForeground(display, gc, xlib::XWhitePixel(display, screen));
+
+                Ok(Surface { display, screen, window, gc, position: config.position, mapped: false })
+            }
+        }
+
+        pub(super) fn update(&mut self, text: Option<&str>) {
+            unsafe {
+                match text {
+                    Some(text) => {
+                        let (x, y) = self.position.origin(self.screen_size(), self.cursor());
+                        xlib::XMoveWindow(self.display, self.window, x, y);
+                        if self.mapped {
+                            xlib::XRaiseWindow(self.display, self.window);
+                        } else {
+                            xlib::XMapRaised(self.display, self.window);
+                            self.mapped = true;
+                        }
+                        xlib::XClearWindow(self.display, self.window);
+                        xlib::XDrawString(
+                            self.display,
+                            self.window,
+                            self.gc,
+                            10,
+                            HEIGHT / 2 + 5,
+                            text.as_ptr() as *const c_char,
+                            text.len() as c_int,
+                        );
+                    }
+                    None if self.mapped => {
+                        xlib::XUnmapWindow(self.display, self.window);
+                        self.mapped = false;
+                    }
+                    None => {}
+                }
+                xlib::XFlush(self.display);
+            }
+        }
+
+        fn screen_size(&self) -> (i32, i32) {
+            unsafe {
+                (xlib::XDisplayWidth(self.display, self.screen), xlib::XDisplayHeight(self.display, self.screen))
+            }
+        }
+
+        fn cursor(&self) -> Option<(i32, i32)> {
+            if self.position != OverlayPosition::Cursor {
+                return None;
+            }
+            unsafe {
+                let root = xlib::XRootWindow(self.display, self.screen);
+                let (mut root_return, mut child_return) = (0, 0);
+                let (mut x, mut y, mut window_x, mut window_y, mut mask) = (0, 0, 0, 0, 0);
+                let found = xlib::XQueryPointer(
+                    self.display,
+                    root,
+                    &mut root_return,
+                    &mut child_return,
+                    &mut x,
+                    &mut y,
+                    &mut window_x,
+                    &mut window_y,
+                    &mut mask,
+                );
+                (found != 0).then_some((x, y))
+            }
+        }
+    }
+
+    impl Drop for Surface {
+        fn drop(&mut self) {
+            unsafe {
+                xlib::XFreeGC(self.display, self.gc);
+                xlib::XDestroyWindow(self.display, self.window);
+                xlib::XCloseDisplay(self.display);
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod windows {
+    use super::{OverlayConfig, OverlayPosition, HEIGHT, WIDTH};
+    use std::cell::RefCell;
+    use std::ptr;
+    use winapi::shared::minwindef::{LPARAM, LRESULT, UINT, WPARAM};
+    use winapi::shared::windef::{HBRUSH, HWND, POINT, RECT};
+    use winapi::um::errhandlingapi::GetLastError;
+    use winapi::um::libloaderapi::GetModuleHandleW;
+    use winapi::um::winuser::{
+        BeginPaint, CreateWindowExW, DefWindowProcW, DestroyWindow, DispatchMessageW, DrawTextW, EndPaint,
+        GetClientRect, GetCursorPos, GetSystemMetrics, InvalidateRect, PeekMessageW, RegisterClassW,
+        SetLayeredWindowAttributes, SetWindowPos, ShowWindow, TranslateMessage, COLOR_WINDOW, DT_CENTER,
+        DT_SINGLELINE, DT_VCENTER, HWND_TOPMOST, LWA_ALPHA, MSG, PAINTSTRUCT, PM_REMOVE, SM_CXSCREEN, SM_CYSCREEN,
+        SWP_NOACTIVATE, SW_HIDE, SW_SHOWNOACTIVATE, WM_PAINT, WNDCLASSW, WS_EX_LAYERED, WS_EX_NOACTIVATE,
+        WS_EX_TOOLWINDOW, WS_EX_TOPMOST, WS_EX_TRANSPARENT, WS_POPUP,
+    };
+
+    /// 窗口类名
+    const CLASS_NAME: &str = "ClipVanishOverlay";
+
+    thread_local! {
+        /// 当前显示的文字（UTF-16，窗口过程只在窗口线程中运行）
+        static TEXT: RefCell<Vec<u16>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// 以0结尾的UTF-16字符串
+    fn wide(text: &str) -> Vec<u16> {
+        text.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+
+    /// 分层置顶窗口（鼠标穿透、不激活、不出现在任务栏）
+    pub(super) struct Surface {
+        window: HWND,
+        position: OverlayPosition,
+        shown: bool,
+    }
+
+    impl Surface {
+        pub(super) fn open(config: &OverlayConfig) -> Result<Self, String> {
+            let class_name = wide(CLASS_NAME);
+            unsafe {
+                let instance = GetModuleHandleW(ptr::null());
+                let mut class: WNDCLASSW = std::mem::zeroed();
+                class.lpfnWndProc = Some(window_proc);
+                class.hInstance = instance;
+                class.lpszClassName = class_name.as_ptr();
+                class.hbrBackground = (COLOR_WINDOW + 1) as usize as HBRUSH;
+                // 重复创建时类已注册，失败可以忽略，由创建窗口的结果判断
+                RegisterClassW(&class);
+
+                let window = CreateWindowExW(
+                    WS_EX_TOPMOST | WS_EX_LAYERED | WS_EX_TRANSPARENT | WS_EX_TOOLWINDOW | WS_EX_NOACTIVATE,
+                    class_name.as_ptr(),
+                    class_name.as_ptr(),
+                    WS_POPUP,
+                    0,
+                    0,
+                    WIDTH,
+                    HEIGHT,
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                    instance,
+                    ptr::null_mut(),
+                );
+                if window.is_null() {
+                    return Err(format!("创建悬浮窗失败，错误码: {}", GetLastError()));
+                }
+                let alpha = (config.opacity.clamp(0.0, 1.0) * 255.0) as u8;
+                SetLayeredWindowAttributes(window, 0, alpha, LWA_ALPHA);
+
+                Ok(Surface { window, position: config.position, shown: false })
+            }
+        }
+
+        pub(super) fn update(&mut self, text: Option<&str>) {
+            unsafe {
+                match text {
+                    Some(text) => {
+                        TEXT.with(|cell| *cell.borrow_mut() = wide(text));
+                        let screen = (GetSystemMetrics(SM_CXSCREEN), GetSystemMetrics(SM_CYSCREEN));
+                        let (x, y) = self.position.origin(screen, self.cursor());
+                        SetWindowPos(self.window, HWND_TOPMOST, x, y, WIDTH, HEIGHT, SWP_NOACTIVATE);
+                        if !self.shown {
+                            ShowWindow(self.window, SW_SHOWNOACTIVATE);
+                            self.shown = true;
+                        }
+                        InvalidateRect(self.window, ptr::null(), 1);
+                    }
+                    None if self.shown => {
+                        ShowWindow(self.window, SW_HIDE);
+                        self.shown = false;
+                    }
+                    None => {}
+                }
+
+                // 处理绘制等消息
+                let mut message: MSG = std::mem::zeroed();
+                while PeekMessageW(&mut message, ptr::null_mut(), 0, 0, PM_REMOVE) != 0 {
+                    TranslateMessage(&message);
+                    DispatchMessageW(&message);
+                }
+            }
+        }
+
+        fn cursor(&self) -> Option<(i32, i32)> {
+            if self.position != OverlayPosition::Cursor {
+                return None;
+            }
+            let mut point = POINT { x: 0, y: 0 };
+            unsafe { (GetCursorPos(&mut point) != 0).then_some((point.x, point.y)) }
+        }
+    }
+
+    impl Drop for Surface {
+        fn drop(&mut self) {
+            unsafe {
+                DestroyWindow(self.window);
+            }
+        }
+    }
+
+    /// 窗口过程：绘制当前文字
+    unsafe extern "system" fn window_proc(window: HWND, message: UINT, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if message != WM_PAINT {
+            return DefWindowProcW(window, message, wparam, lparam);
+        }
+
+        let mut paint: PAINTSTRUCT = std::mem::zeroed();
+        let hdc = BeginPaint(window, &mut paint);
+        let mut rect: RECT = std::mem::zeroed();
+        GetClientRect(window, &mut rect);
+        TEXT.with(|cell| {
+            let text = cell.borrow();
+            if !text.is_empty() {
+                DrawTextW(hdc, text.as_ptr(), -1, &mut rect, DT_CENTER | DT_VCENTER | DT_SINGLELINE);
+            }
+        });
+        EndPaint(window, &paint);
+        0
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use super::{OverlayConfig, OverlayPosition, HEIGHT, MARGIN, WIDTH};
+    use std::io::Write;
+    use std::process::{Child, Stdio};
+    use log::warn;
+    use crate::helpers;
+
+    /// osascript创建的浮动窗口：每行标准输入为要显示的文字，空行隐藏，标准输入关闭时退出
+    pub(super) struct Surface {
+        child: Child,
+        /// 上一次发送的文字（未变化时不重复发送）
+        last: Option<String>,
+    }
+
+    impl Surface {
+        pub(super) fn open(config: &OverlayConfig) -> Result<Self, String> {
+            let mut command = helpers::command("osascript")?;
+            command
+                .args(["-l", "JavaScript", "-e", &script(config)])
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null());
+            let child = helpers::spawn(&mut command).map_err(|e| format!("无法启动osascript: {}", e))?;
+            Ok(Surface { child, last: None })
+        }
+
+        pub(super) fn update(&mut self, text: Option<&str>) {
+            let text = text.unwrap_or_default();
+            if self.last.as_deref() == Some(text) {
+                return;
+            }
+            let Some(stdin) = self.child.stdin.as_mut() else {
+                return;
+            };
+            if let Err(e) = writeln!(stdin, "{}", text).and_then(|_| stdin.flush()) {
+                warn!("更新悬浮窗失败: {}", e);
+            }
+            self.last = Some(text.to_string());
+        }
+    }
+
+    impl Drop for Surface {
+        fn drop(&mut self) {
+            // 关闭标准输入，脚本随之退出
+            drop(self.child.stdin.take());
+            let _ = self.child.wait();
+        }
+    }
+
+    /// 窗口脚本（Cocoa坐标原点在左下角，位置按 `OverlayPosition::origin` 的规则换算）
+    fn script(config: &OverlayConfig) -> String {
+        let position = match config.position {
+            OverlayPosition::Cursor => "cursor",
+            OverlayPosition::TopLeft => "top_left",
+            OverlayPosition::TopRight => "top_right",
+            OverlayPosition::BottomLeft => "bottom_left",
+            OverlayPosition::BottomRight => "bottom_right",
+        };
+        format!(
+            "ObjC.import('Cocoa'); \
+             var W = {width}, H = {height}, M = {margin}, POSITION = '{position}'; \
+             $.NSApplication.sharedApplication.setActivationPolicy($.NSApplicationActivationPolicyAccessory); \
+             var win = $.NSWindow.alloc.initWithContentRectStyleMaskBackingDefer($.NSMakeRect(0, 0, W, H), $.NSWindowStyleMaskBorderless, $.NSBackingStoreBuffered, false); \
+             win.level = $.NSStatusWindowLevel; win.opaque = false; win.alphaValue = {opacity}; win.ignoresMouseEvents = true; \
+             win.backgroundColor = $.NSColor.blackColor; win.collectionBehavior = $.NSWindowCollectionBehaviorCanJoinAllSpaces; \
+             var label = $.NSTextField.labelWithString($('')); label.textColor = $.NSColor.whiteColor; \
+             label.alignment = $.NSTextAlignmentCenter; label.frame = $.NSMakeRect(0, 6, W, H - 12); \
+             win.contentView.addSubview(label); \
+             var input = $.NSFileHandle.fileHandleWithStandardInput; \
+             while (true) {{ \
+               var data = input.availableData; if (data.length == 0) break; \
+               var lines = $.NSString.alloc.initWithDataEncoding(data, $.NSUTF8StringEncoding).js.split('\\n').filter(function (l, i, a) {{ return i < a.length - 1; }}); \
+               var text = lines.length ? lines[lines.length - 1] : ''; \
+               if (text == '') {{ win.orderOut(null); }} else {{ \
+                 var s = $.NSScreen.mainScreen.frame, x = s.size.width - W - M, y = M; \
+                 if (POSITION == 'top_left' || POSITION == 'bottom_left') x = M; \
+                 if (POSITION == 'top_left' || POSITION == 'top_right') y = s.size.height - H - M; \
+                 if (POSITION == 'cursor') {{ var p = $.NSEvent.mouseLocation; x = Math.min(Math.max(p.x + M, 0), x); y = Math.min(Math.max(p.y - H - M, M), s.size.height - H); }} \
+                 label.stringValue = $(text); win.setFrameOrigin($.NSMakePoint(x, y)); win.orderFrontRegardless; \
+               }} \
+               $.NSRunLoop.currentRunLoop.runUntilDate($.NSDate.dateWithTimeIntervalSinceNow(0.01)); \
+             }}",
+            width = WIDTH,
+            height = HEIGHT,
+            margin = MARGIN,
+            position = position,
+            opacity = config.opacity.clamp(0.0, 1.0),
+        )
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+mod unsupported {
+    use super::OverlayConfig;
+
+    pub(super) struct Surface;
+
+    impl Surface {
+        pub(super) fn open(_config: &OverlayConfig) -> Result<Self, String> {
+            Err("当前平台不支持倒计时悬浮窗".to_string())
+        }
+
+        pub(super) fn update(&mut self, _text: Option<&str>) {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_origin_stays_on_screen() {
+        let screen = (1920, 1080);
+        assert_eq!(OverlayPosition::TopLeft.origin(screen, None), (MARGIN, MARGIN));
+        assert_eq!(OverlayPosition::BottomRight.origin(screen, None), (1920 - WIDTH - MARGIN, 1080 - HEIGHT - MARGIN));
+        assert_eq!(OverlayPosition::Cursor.origin(screen, Some((100, 200))), (100 + MARGIN, 200 + MARGIN));
+        // 光标靠近右下角时窗口不超出屏幕；无法获取光标时退回右下角
+        assert_eq!(OverlayPosition::Cursor.origin(screen, Some((1915, 1075))), OverlayPosition::BottomRight.origin(screen, None));
+        assert_eq!(OverlayPosition::Cursor.origin(screen, None), OverlayPosition::BottomRight.origin(screen, None));
+    }
+
+    #[test]
+    fn test_text_follows_deadline_and_visibility() {
+        let state = OverlayState::default();
+        let now = Instant::now();
+        *state.deadline.lock().unwrap() = Some(now + Duration::from_millis(11_500));
+        assert_eq!(state.text(now), None);
+
+        state.visible.store(true, Ordering::SeqCst);
+        assert_eq!(state.text(now).as_deref(), Some("12s"));
+        assert_eq!(state.text(now + Duration::from_secs(12)), None);
+
+        *state.deadline.lock().unwrap() = None;
+        assert_eq!(state.text(now), None);
+        assert_eq!(label(Duration::from_secs(125)), "2m05s");
+    }
+}
@@ -0,0 +1,218 @@
+/*!
+ * ClipVanish™ 配置覆盖模块
+ *
+ * 允许通过环境变量与命令行参数覆盖配置文件中的任意设置，便于容器与脚本使用
+ * 特点：
+ * - 优先级：配置文件 < 环境变量 < 命令行 `--set`，受管策略始终最后合并
+ * - 环境变量 `CLIPVANISH_TIMER__DEFAULT_COUNTDOWN=10` 对应 `timer.default_countdown`
+ * - 按原设置的类型解析值：字符串设置原样使用，其他设置按JSON解析
+ * - 未知设置或无法解析的值直接报错，避免拼写错误被静默忽略
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::sync::OnceLock;
+use serde_json::Value;
+
+/// 环境变量前缀
+const ENV_PREFIX: &str = "CLIPVANISH_";
+
+/// 环境变量中的层级分隔符
+const ENV_SEPARATOR: &str = "__";
+
+/// 命令行 `--set` 覆盖（进程启动时设置一次）
+static CLI_OVERRIDES: OnceLock<Vec<ConfigOverride>> = OnceLock::new();
+
+/// 配置覆盖错误类型
+#[derive(Debug)]
+pub enum OverrideError {
+    /// 覆盖表达式格式错误
+    InvalidFormat(String),
+    /// 设置不存在
+    UnknownSetting(String),
+    /// 值无法解析
+    InvalidValue { key: String, message: String },
+    /// 覆盖后的配置无效
+    InvalidConfig(String),
+}
+
+impl std::fmt::Display for OverrideError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OverrideError::InvalidFormat(text) => write!(f, "覆盖格式错误（应为 key=value）: {}", text),
+            OverrideError::UnknownSetting(key) => write!(f, "未知设置: {}", key),
+            OverrideError::InvalidValue { key, message } => write!(f, "设置 {} 的值无效: {}", key, message),
+            OverrideError::InvalidConfig(msg) => write!(f, "覆盖后的配置无效: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for OverrideError {}
+
+/// 覆盖来源
+#[derive(Debug, Clone, PartialEq)]
+pub enum OverrideSource {
+    /// 环境变量（变量名）
+    Env(String),
+    /// 命令行 `--set`
+    Cli,
+}
+
+impl std::fmt::Display for OverrideSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OverrideSource::Env(name) => write!(f, "环境变量 {}", name),
+            OverrideSource::Cli => write!(f, "--set"),
+        }
+    }
+}
+
+/// 单项配置覆盖
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigOverride {
+    /// 以点分隔的设置路径，如 `timer.default_countdown`
+    pub key: String,
+    /// 原始值
+    pub value: String,
+    /// 来源
+    pub source: OverrideSource,
+}
+
+impl ConfigOverride {
+    /// 解析命令行 `key=value` 覆盖
+    ///
+    /// # 参数
+    /// * `text` - 覆盖表达式
+    pub fn parse_cli(text: &str) -> Result<Self, OverrideError> {
+        let (key, value) = text
+            .split_once('=')
+            .filter(|(key, _)| !key.trim().is_empty())
+            .ok_or_else(|| OverrideError::InvalidFormat(text.to_string()))?;
+
+        Ok(ConfigOverride {
+            key: key.trim().to_string(),
+            value: value.to_string(),
+            source: OverrideSource::Cli,
+        })
+    }
+}
+
+/// 从环境变量收集覆盖（按变量名排序，保证结果稳定）
+///
+/// # 参数
+/// * `vars` - 环境变量（名称, 值）
+pub fn from_env<I: IntoIterator<Item = (String, String)>>(vars: I) -> Vec<ConfigOverride> {
+    let mut overrides: Vec<ConfigOverride> = vars
+        .into_iter()
+        .filter_map(|(name, value)| {
+            let rest = name.strip_prefix(ENV_PREFIX)?;
+            if rest.is_empty() {
+                return None;
+            }
+            let key = rest
+                .split(ENV_SEPARATOR)
+                .map(|part| part.to_lowercase())
+                .collect::<Vec<_>>()
+                .join(".");
+            Some(ConfigOverride { key, value, source: OverrideSource::Env(name) })
+        })
+        .collect();
+    overrides.sort_by(|a, b| a.key.cmp(&b.key));
+    overrides
+}
+
+/// 设置命令行覆盖（只在启动时调用一次）
+///
+/// # 参数
+/// * `overrides` - 命令行覆盖
+pub fn set_cli_overrides(overrides: Vec<ConfigOverride>) {
+    let _ = CLI_OVERRIDES.set(overrides);
+}
+
+/// 当前生效的全部覆盖：先环境变量，后命令行
+pub fn active() -> Vec<ConfigOverride> {
+    let mut overrides = from_env(std::env::vars());
+    overrides.extend(CLI_OVERRIDES.get().cloned().unwrap_or_default());
+    overrides
+}
+
+/// 将覆盖依次应用到JSON形式的配置上
+///
+/// # 参数
+/// * `root` - 完整配置的JSON值
+/// * `overrides` - 覆盖（后面的优先）
+pub fn apply(root: &mut Value, overrides: &[ConfigOverride]) -> Result<(), OverrideError> {
+    for item in overrides {
+        let target = item
+            .key
+            .split('.')
+            .try_fold(&mut *root, |value, part| value.as_object_mut()?.get_mut(part))
+            .ok_or_else(|| OverrideError::UnknownSetting(format!("{}（{}）", item.key, item.source)))?;
+
+        *target = parse_value(target, &item.value).map_err(|message| OverrideError::InvalidValue {
+            key: item.key.clone(),
+            message,
+        })?;
+    }
+    Ok(())
+}
+
+/// 按原设置的类型解析覆盖值
+fn parse_value(current: &Value, raw: &str) -> Result<Value, String> {
+    match current {
+        // 字符串设置原样使用，避免正则表达式等内容需要额外加引号
+        Value::String(_) => Ok(Value::String(raw.to_string())),
+        // 可选设置：能按JSON解析时使用JSON，否则视为字符串
+        Value::Null => Ok(serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))),
+        Value::Object(_) => match serde_json::from_str(raw) {
+            Ok(value @ Value::Object(_)) => Ok(value),
+            _ => Err("该设置是一个配置段，需要JSON对象".to_string()),
+        },
+        _ => serde_json::from_str(raw).map_err(|e| e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_env_names_map_to_setting_paths() {
+        let vars = vec![
+            ("CLIPVANISH_TIMER__DEFAULT_COUNTDOWN".to_string(), "10".to_string()),
+            ("CLIPVANISH_CLEAR_DELAY_SECONDS".to_string(), "5".to_string()),
+            ("HOME".to_string(), "/root".to_string()),
+        ];
+
+        let overrides = from_env(vars);
+        let keys: Vec<&str> = overrides.iter().map(|item| item.key.as_str()).collect();
+        assert_eq!(keys, vec!["clear_delay_seconds", "timer.default_countdown"]);
+        assert!(ConfigOverride::parse_cli("novalue").is_err());
+    }
+
+    #[test]
+    fn test_apply_respects_types_and_order() {
+        let mut config = json!({
+            "timer": { "default_countdown": 30 },
+            "ui": { "log_level": "info" },
+            "security": { "destroy_on_paste": false },
+        });
+        let overrides = vec![
+            ConfigOverride::parse_cli("timer.default_countdown=10").unwrap(),
+            ConfigOverride::parse_cli("ui.log_level=debug").unwrap(),
+            ConfigOverride::parse_cli("security.destroy_on_paste=true").unwrap(),
+            ConfigOverride::parse_cli("timer.default_countdown=15").unwrap(),
+        ];
+
+        apply(&mut config, &overrides).unwrap();
+        assert_eq!(config["timer"]["default_countdown"], json!(15));
+        assert_eq!(config["ui"]["log_level"], json!("debug"));
+        assert_eq!(config["security"]["destroy_on_paste"], json!(true));
+
+        let unknown = ConfigOverride::parse_cli("timer.typo=1").unwrap();
+        assert!(matches!(apply(&mut config, &[unknown]), Err(OverrideError::UnknownSetting(_))));
+        let invalid = ConfigOverride::parse_cli("timer.default_countdown=soon").unwrap();
+        assert!(matches!(apply(&mut config, &[invalid]), Err(OverrideError::InvalidValue { .. })));
+    }
+}
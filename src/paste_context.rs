@@ -0,0 +1,243 @@
+/*!
+ * ClipVanish™ 粘贴上下文检测模块
+ *
+ * 尽力识别粘贴时焦点窗口所在的虚拟桌面（Space）与显示器，用于事件元数据和粘贴策略
+ * 特点：
+ * - 只记录桌面编号/名称与显示器名称，不读取窗口标题
+ * - 检测失败时对应字段为None，不影响主流程
+ * - 可按显示器或虚拟桌面阻止受保护内容的解密粘贴（如共享屏幕所在的外接显示器）
+ *
+ * 平台支持：
+ * - Linux(X11): 桌面编号与显示器均可识别（依赖xdotool、xrandr）
+ * - macOS: 仅识别显示器（Space没有公开接口）
+ * - Windows: 仅识别显示器（虚拟桌面没有稳定的公开接口）
+ *
+ * 作者: ClipVanish Team
+ */
+
+// 粘贴检测依赖键盘钩子，最小构建中只保留策略配置
+#![cfg_attr(not(feature = "keyboard-hooks"), allow(dead_code))]
+
+use serde::{Deserialize, Serialize};
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+use crate::helpers;
+
+/// 粘贴上下文策略配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PasteContextConfig {
+    /// 在这些显示器上粘贴时不解密受保护内容（名称不区分大小写）
+    #[serde(default)]
+    pub blocked_monitors: Vec<String>,
+    /// 在这些虚拟桌面上粘贴时不解密受保护内容
+    #[serde(default)]
+    pub blocked_desktops: Vec<String>,
+}
+
+impl PasteContextConfig {
+    /// 判断给定上下文是否被阻止
+    ///
+    /// # 返回值
+    /// * `Option<String>` - 被阻止时返回原因
+    pub fn blocked_reason(&self, context: &PasteContext) -> Option<String> {
+        let matches = |list: &[String], value: &Option<String>| {
+            value.as_deref().is_some_and(|value| list.iter().any(|item| item.eq_ignore_ascii_case(value)))
+        };
+
+        if matches(&self.blocked_monitors, &context.monitor) {
+            return Some(format!("显示器 {}", context.monitor.as_deref().unwrap_or_default()));
+        }
+        if matches(&self.blocked_desktops, &context.desktop) {
+            return Some(format!("虚拟桌面 {}", context.desktop.as_deref().unwrap_or_default()));
+        }
+        None
+    }
+}
+
+/// 粘贴时焦点窗口所在的上下文
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PasteContext {
+    /// 虚拟桌面/Space（编号或名称）
+    pub desktop: Option<String>,
+    /// 显示器名称
+    pub monitor: Option<String>,
+}
+
+impl PasteContext {
+    /// 检测当前焦点窗口的上下文
+    pub fn current() -> Self {
+        PasteContext {
+            desktop: detect_desktop().map(|s| s.trim().to_string()).filter(|s| !s.is_empty()),
+            monitor: detect_monitor().map(|s| s.trim().to_string()).filter(|s| !s.is_empty()),
+        }
+    }
+}
+
+impl std::fmt::Display for PasteContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "桌面={} 显示器={}",
+            self.desktop.as_deref().unwrap_or("未知"),
+            self.monitor.as_deref().unwrap_or("未知")
+        )
+    }
+}
+
+/// Linux: 通过xdotool获取当前桌面编号
+#[cfg(target_os = "linux")]
+fn detect_desktop() -> Option<String> {
+    command_output("xdotool", &["get_desktop"])
+}
+
+/// Linux: 以焦点窗口中心点所在的xrandr显示器为准
+#[cfg(target_os = "linux")]
+fn detect_monitor() -> Option<String> {
+    let geometry = command_output("xdotool", &["getactivewindow", "getwindowgeometry", "--shell"])?;
+    let value = |key: &str| -> Option<i64> {
+        geometry
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix('=')?.trim().parse().ok())
+    };
+    let x = value("X")? + value("WIDTH").unwrap_or(0) / 2;
+    let y = value("Y")? + value("HEIGHT").unwrap_or(0) / 2;
+
+    let listing = command_output("xrandr", &["--listactivemonitors"])?;
+    monitor_at(&listing, x, y)
+}
+
+/// macOS: Space没有公开接口
+#[cfg(target_os = "macos")]
+fn detect_desktop() -> Option<String> {
+    None
+}
+
+/// macOS: 以前台窗口中心点所在的NSScreen为准
+#[cfg(target_os = "macos")]
+fn detect_monitor() -> Option<String> {
+    const SCRIPT: &str = r#"
+ObjC.import('AppKit');
+var proc = Application('System Events').processes.whose({frontmost: true})[0];
+var win = proc.windows[0];
+var pos = win.position(), size = win.size();
+var cx = pos[0] + size[0] / 2, cy = pos[1] + size[1] / 2;
+var screens = $.NSScreen.screens;
+var primaryHeight = screens.objectAtIndex(0).frame.size.height;
+var name = '';
+for (var i = 0; i < screens.count; i++) {
+    var screen = screens.objectAtIndex(i), frame = screen.frame;
+    var top = primaryHeight - (frame.origin.y + frame.size.height);
+    if (cx >= frame.origin.x && cx < frame.origin.x + frame.size.width && cy >= top && cy < top + frame.size.height) {
+        name = screen.localizedName.js;
+        break;
+    }
+}
+name;
+"#;
+    command_output("osascript", &["-l", "JavaScript", "-e", SCRIPT])
+}
+
+/// Windows: 虚拟桌面没有稳定的公开接口
+#[cfg(target_os = "windows")]
+fn detect_desktop() -> Option<String> {
+    None
+}
+
+/// Windows: 前台窗口所在显示器的设备名（如 \\.\DISPLAY2）
+#[cfg(target_os = "windows")]
+fn detect_monitor() -> Option<String> {
+    use winapi::um::winuser::{
+        GetForegroundWindow, GetMonitorInfoW, MonitorFromWindow, MONITORINFOEXW, MONITOR_DEFAULTTONEAREST,
+    };
+
+    unsafe {
+        let window = GetForegroundWindow();
+        if window.is_null() {
+            return None;
+        }
+
+        let monitor = MonitorFromWindow(window, MONITOR_DEFAULTTONEAREST);
+        if monitor.is_null() {
+            return None;
+        }
+
+        let mut info: MONITORINFOEXW = std::mem::zeroed();
+        info.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+        if GetMonitorInfoW(monitor, &mut info as *mut MONITORINFOEXW as *mut _) == 0 {
+            return None;
+        }
+
+        let len = info.szDevice.iter().position(|&c| c == 0).unwrap_or(info.szDevice.len());
+        Some(String::from_utf16_lossy(&info.szDevice[..len]))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn detect_desktop() -> Option<String> {
+    None
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn detect_monitor() -> Option<String> {
+    None
+}
+
+/// 运行命令并返回标准输出
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = helpers::output(program, args)
+        .ok()
+        .filter(|output| output.status.success())?;
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// 从 `xrandr --listactivemonitors` 的输出中找出包含指定坐标的显示器
+///
+/// 输出格式示例：` 1: +HDMI-1 2560/597x1440/336+1920+0  HDMI-1`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn monitor_at(listing: &str, x: i64, y: i64) -> Option<String> {
+    listing.lines().skip(1).find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let geometry = fields.nth(2)?;
+        let name = fields.last()?;
+
+        // W/mmxH/mm+X+Y
+        let (size, offset) = geometry.split_once('+')?;
+        let (left, top) = offset.split_once('+')?;
+        let (width, height) = size.split_once('x')?;
+        let width: i64 = width.split('/').next()?.parse().ok()?;
+        let height: i64 = height.split('/').next()?.parse().ok()?;
+        let left: i64 = left.parse().ok()?;
+        let top: i64 = top.parse().ok()?;
+
+        (x >= left && x < left + width && y >= top && y < top + height).then(|| name.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monitor_at() {
+        let listing = "Monitors: 2\n 0: +*eDP-1 1920/344x1080/193+0+0  eDP-1\n 1: +HDMI-1 2560/597x1440/336+1920+0  HDMI-1\n";
+        assert_eq!(monitor_at(listing, 100, 100).as_deref(), Some("eDP-1"));
+        assert_eq!(monitor_at(listing, 2500, 700).as_deref(), Some("HDMI-1"));
+        assert_eq!(monitor_at(listing, 5000, 100), None);
+    }
+
+    #[test]
+    fn test_blocked_reason() {
+        let config = PasteContextConfig {
+            blocked_monitors: vec!["hdmi-1".to_string()],
+            blocked_desktops: vec!["3".to_string()],
+        };
+        let external = PasteContext { desktop: Some("0".to_string()), monitor: Some("HDMI-1".to_string()) };
+        let laptop = PasteContext { desktop: Some("0".to_string()), monitor: Some("eDP-1".to_string()) };
+        let presenting = PasteContext { desktop: Some("3".to_string()), monitor: None };
+
+        assert!(config.blocked_reason(&external).is_some());
+        assert!(config.blocked_reason(&laptop).is_none());
+        assert!(config.blocked_reason(&presenting).is_some());
+        assert!(config.blocked_reason(&PasteContext::default()).is_none());
+    }
+}
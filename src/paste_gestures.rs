@@ -0,0 +1,189 @@
+/*!
+ * ClipVanish™ 粘贴手势模块
+ *
+ * 识别Ctrl/Cmd+V以外的粘贴方式，使终端中的粘贴同样触发解密与粘贴即销毁
+ * 特点：
+ * - 终端常用的 Ctrl+Shift+V（macOS为Cmd+Shift+V）与 Shift+Insert，可分别关闭
+ * - X11鼠标中键粘贴读取的是PRIMARY选区而不是剪贴板：中键按下时若PRIMARY选区中是受保护条目的明文，
+ *   视为该条目已被粘贴，清空PRIMARY选区并开始粘贴后的倒计时清除
+ * - 键盘钩子线程读取进程范围的配置，重新加载配置后立即生效
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::fmt;
+use std::sync::RwLock;
+use serde::{Deserialize, Serialize};
+#[cfg(all(target_os = "linux", feature = "keyboard-hooks"))]
+use zeroize::Zeroizing;
+#[cfg(all(target_os = "linux", feature = "keyboard-hooks"))]
+use crate::helpers;
+
+/// 粘贴手势配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PasteGesturesConfig {
+    /// 是否识别 Ctrl+Shift+V（macOS为Cmd+Shift+V）
+    pub ctrl_shift_v: bool,
+    /// 是否识别 Shift+Insert
+    pub shift_insert: bool,
+    /// 是否识别X11鼠标中键粘贴（PRIMARY选区）
+    pub middle_click: bool,
+}
+
+impl Default for PasteGesturesConfig {
+    fn default() -> Self {
+        PasteGesturesConfig {
+            ctrl_shift_v: true,
+            shift_insert: true,
+            middle_click: true,
+        }
+    }
+}
+
+/// 粘贴手势
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PasteGesture {
+    /// Ctrl+V（macOS为Cmd+V）
+    Standard,
+    /// Ctrl+Shift+V（macOS为Cmd+Shift+V）
+    CtrlShiftV,
+    /// Shift+Insert
+    ShiftInsert,
+    /// 鼠标中键（读取PRIMARY选区）
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    MiddleClick,
+}
+
+impl fmt::Display for PasteGesture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let primary = if cfg!(target_os = "macos") { "Cmd" } else { "Ctrl" };
+        match self {
+            PasteGesture::Standard => write!(f, "{}+V", primary),
+            PasteGesture::CtrlShiftV => write!(f, "{}+Shift+V", primary),
+            PasteGesture::ShiftInsert => f.write_str("Shift+Insert"),
+            PasteGesture::MiddleClick => f.write_str("鼠标中键"),
+        }
+    }
+}
+
+/// 按下按键时的修饰键状态
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Modifiers {
+    /// Ctrl（macOS为Cmd）
+    pub primary: bool,
+    /// Shift
+    pub shift: bool,
+    /// Alt
+    pub alt: bool,
+}
+
+/// 可能构成粘贴的输入
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(not(feature = "keyboard-hooks"), allow(dead_code))]
+pub enum Input {
+    /// V键
+    KeyV,
+    /// Insert键
+    Insert,
+    /// 鼠标中键
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    MiddleButton,
+}
+
+impl PasteGesturesConfig {
+    /// 按配置识别粘贴手势
+    ///
+    /// # 参数
+    /// * `modifiers` - 修饰键状态
+    /// * `input` - 按下的按键或鼠标按钮
+    pub fn detect(&self, modifiers: Modifiers, input: Input) -> Option<PasteGesture> {
+        if modifiers.alt {
+            return None;
+        }
+        match (input, modifiers.primary, modifiers.shift) {
+            (Input::KeyV, true, false) => Some(PasteGesture::Standard),
+            (Input::KeyV, true, true) => self.ctrl_shift_v.then_some(PasteGesture::CtrlShiftV),
+            (Input::Insert, false, true) => self.shift_insert.then_some(PasteGesture::ShiftInsert),
+            (Input::MiddleButton, false, false) => self.middle_click.then_some(PasteGesture::MiddleClick),
+            _ => None,
+        }
+    }
+}
+
+/// 进程范围的粘贴手势配置（未设置时为默认配置）
+static CONFIG: RwLock<Option<PasteGesturesConfig>> = RwLock::new(None);
+
+/// 设置进程范围的粘贴手势配置（加载与重新加载配置时调用）
+pub fn configure(config: PasteGesturesConfig) {
+    *CONFIG.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(config);
+}
+
+/// 按当前配置识别粘贴手势（供键盘钩子线程调用）
+///
+/// # 参数
+/// * `modifiers` - 修饰键状态
+/// * `input` - 按下的按键或鼠标按钮
+#[cfg_attr(not(feature = "keyboard-hooks"), allow(dead_code))]
+pub fn detect(modifiers: Modifiers, input: Input) -> Option<PasteGesture> {
+    match &*CONFIG.read().unwrap_or_else(|poisoned| poisoned.into_inner()) {
+        Some(config) => config.detect(modifiers, input),
+        None => PasteGesturesConfig::default().detect(modifiers, input),
+    }
+}
+
+/// Linux: 读取PRIMARY选区的文本（选区为空或无法读取时返回None）
+#[cfg(all(target_os = "linux", feature = "keyboard-hooks"))]
+pub fn read_primary() -> Option<Zeroizing<String>> {
+    let output = helpers::output("xclip", &["-selection", "primary", "-o"])
+        .ok()
+        .filter(|output| output.status.success())?;
+    let text = Zeroizing::new(String::from_utf8(output.stdout).ok()?);
+    (!text.is_empty()).then_some(text)
+}
+
+/// Linux: 以空内容取代PRIMARY选区
+#[cfg(all(target_os = "linux", feature = "keyboard-hooks"))]
+pub fn clear_primary() -> Result<(), String> {
+    let status = helpers::run_with_stdin("xclip", &["-selection", "primary", "-i"], b"")?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("xclip退出码: {:?}", status.code()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        let config = PasteGesturesConfig::default();
+        let ctrl = Modifiers { primary: true, ..Modifiers::default() };
+        let ctrl_shift = Modifiers { shift: true, ..ctrl };
+        let shift = Modifiers { shift: true, ..Modifiers::default() };
+
+        assert_eq!(config.detect(ctrl, Input::KeyV), Some(PasteGesture::Standard));
+        assert_eq!(config.detect(ctrl_shift, Input::KeyV), Some(PasteGesture::CtrlShiftV));
+        assert_eq!(config.detect(shift, Input::Insert), Some(PasteGesture::ShiftInsert));
+        assert_eq!(config.detect(Modifiers::default(), Input::MiddleButton), Some(PasteGesture::MiddleClick));
+
+        // Alt组合、单独的V与Ctrl+Insert（终端中的复制）不是粘贴
+        assert_eq!(config.detect(Modifiers { alt: true, ..ctrl }, Input::KeyV), None);
+        assert_eq!(config.detect(Modifiers::default(), Input::KeyV), None);
+        assert_eq!(config.detect(ctrl, Input::Insert), None);
+        assert_eq!(config.detect(ctrl, Input::MiddleButton), None);
+    }
+
+    #[test]
+    fn test_disabled_gestures() {
+        let config = PasteGesturesConfig { ctrl_shift_v: false, shift_insert: false, middle_click: false };
+        let ctrl = Modifiers { primary: true, ..Modifiers::default() };
+
+        assert_eq!(config.detect(ctrl, Input::KeyV), Some(PasteGesture::Standard));
+        assert_eq!(config.detect(Modifiers { shift: true, ..ctrl }, Input::KeyV), None);
+        assert_eq!(config.detect(Modifiers { shift: true, ..Modifiers::default() }, Input::Insert), None);
+        assert_eq!(config.detect(Modifiers::default(), Input::MiddleButton), None);
+    }
+}
@@ -0,0 +1,221 @@
+/*!
+ * ClipVanish™ 粘贴方式模块
+ *
+ * 按目标程序选择确认后的安全粘贴如何把明文交给目标程序：模拟快捷键、WM_PASTE消息、直接输入文本或只放入剪贴板
+ * 特点：
+ * - 规则在配置中按程序名称定义，第一条匹配目标程序的规则生效，没有匹配时使用默认方式
+ * - 辅助程序（PowerShell、osascript、xdotool）的失败会如实报告，不再被忽略
+ * - Windows上目标程序以更高权限运行时不尝试模拟输入（UIPI会静默丢弃），并给出可行的方式
+ * - 错误信息中附带建议改用的粘贴方式
+ *
+ * 作者: ClipVanish Team
+ */
+
+// 模拟粘贴依赖键盘钩子，最小构建中只保留配置
+#![cfg_attr(not(feature = "keyboard-hooks"), allow(dead_code))]
+
+use std::fmt;
+use log::debug;
+use serde::{Deserialize, Serialize};
+#[cfg(target_os = "windows")]
+use crate::elevation;
+use crate::helpers;
+use crate::paste_transform::app_matches;
+
+/// 粘贴方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PasteStrategy {
+    /// 临时放入剪贴板并模拟粘贴快捷键（Windows: SendKeys，macOS: osascript，Linux: xdotool）
+    #[default]
+    Keystroke,
+    /// 临时放入剪贴板并向焦点控件发送WM_PASTE消息（仅Windows，适用于拦截模拟按键的程序）
+    WmPaste,
+    /// 按当前键盘布局逐字符输入（不经过剪贴板，只支持布局可直接输入的字符）
+    TypeText,
+    /// 只把明文放入剪贴板，由用户手动粘贴（游戏、远程桌面等无法模拟输入的程序）
+    ClipboardOnly,
+}
+
+impl fmt::Display for PasteStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            PasteStrategy::Keystroke => "keystroke",
+            PasteStrategy::WmPaste => "wm_paste",
+            PasteStrategy::TypeText => "type_text",
+            PasteStrategy::ClipboardOnly => "clipboard_only",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// 粘贴方式规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasteStrategyRule {
+    /// 目标程序名称（不区分大小写，`.exe` 后缀可省略）
+    pub apps: Vec<String>,
+    /// 粘贴方式
+    pub strategy: PasteStrategy,
+}
+
+/// 粘贴方式配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PasteStrategyConfig {
+    /// 没有匹配规则时的粘贴方式
+    pub default: PasteStrategy,
+    /// 按程序的规则（第一条匹配目标程序的规则生效）
+    pub rules: Vec<PasteStrategyRule>,
+}
+
+impl PasteStrategyConfig {
+    /// 目标程序使用的粘贴方式（目标无法识别时使用默认方式）
+    ///
+    /// # 参数
+    /// * `target` - 粘贴目标程序名称
+    pub fn for_app(&self, target: Option<&str>) -> PasteStrategy {
+        target
+            .and_then(|target| self.rules.iter().find(|rule| app_matches(&rule.apps, target)))
+            .map_or(self.default, |rule| rule.strategy)
+    }
+}
+
+/// 粘贴错误
+#[derive(Debug)]
+pub enum PasteError {
+    /// 目标程序以更高权限运行，模拟输入会被系统丢弃
+    #[cfg(target_os = "windows")]
+    ElevationMismatch { app: String },
+    /// 当前平台不支持该粘贴方式
+    Unsupported(PasteStrategy),
+    /// 发送粘贴命令或输入文本失败
+    Delivery { strategy: PasteStrategy, message: String },
+    /// 读写剪贴板失败
+    Clipboard(String),
+}
+
+impl fmt::Display for PasteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(target_os = "windows")]
+            PasteError::ElevationMismatch { app } => {
+                write!(f, "{} 以管理员权限运行，系统会丢弃来自普通权限程序的模拟输入；请{}", app, elevation::guidance(app))
+            }
+            PasteError::Unsupported(strategy) => {
+                write!(f, "当前平台不支持粘贴方式 {}；请改用 keystroke 或 clipboard_only", strategy)
+            }
+            PasteError::Delivery { strategy, message } => {
+                let suggestion = match strategy {
+                    PasteStrategy::TypeText => "clipboard_only",
+                    _ => "type_text 或 clipboard_only",
+                };
+                write!(f, "粘贴方式 {} 失败: {}；可在 paste_strategies 中为该程序改用 {}", strategy, message, suggestion)
+            }
+            PasteError::Clipboard(message) => write!(f, "读写剪贴板失败: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for PasteError {}
+
+/// 向焦点窗口发送粘贴命令（内容需已在剪贴板中）
+///
+/// # 参数
+/// * `strategy` - 粘贴方式（只支持 keystroke 与 wm_paste）
+pub fn send_paste(strategy: PasteStrategy) -> Result<(), PasteError> {
+    debug!("发送粘贴命令，方式: {}", strategy);
+    let delivery = |message: String| PasteError::Delivery { strategy, message };
+    match strategy {
+        PasteStrategy::Keystroke => send_keystroke().map_err(delivery),
+        #[cfg(target_os = "windows")]
+        PasteStrategy::WmPaste => send_wm_paste().map_err(delivery),
+        other => Err(PasteError::Unsupported(other)),
+    }
+}
+
+/// 模拟粘贴快捷键，辅助程序失败时返回其错误输出
+fn send_keystroke() -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    let output = helpers::output(
+        "powershell",
+        &["-Command", "Add-Type -AssemblyName System.Windows.Forms; [System.Windows.Forms.SendKeys]::SendWait('^v')"],
+    );
+    #[cfg(target_os = "macos")]
+    let output = helpers::output("osascript", &["-e", "tell application \"System Events\" to keystroke \"v\" using command down"]);
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let output = helpers::output("xdotool", &["key", "ctrl+v"]);
+
+    let output = output?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("辅助程序退出码 {:?}: {}", output.status.code(), stderr.trim()));
+    }
+    Ok(())
+}
+
+/// Windows: 向前台窗口中的焦点控件发送WM_PASTE
+#[cfg(target_os = "windows")]
+fn send_wm_paste() -> Result<(), String> {
+    use std::{mem, ptr};
+    use winapi::um::errhandlingapi::GetLastError;
+    use winapi::um::winuser::{
+        GetForegroundWindow, GetGUIThreadInfo, GetWindowThreadProcessId, SendMessageTimeoutW, GUITHREADINFO,
+        SMTO_ABORTIFHUNG, WM_PASTE,
+    };
+
+    unsafe {
+        let window = GetForegroundWindow();
+        if window.is_null() {
+            return Err("没有前台窗口".to_string());
+        }
+
+        // WM_PASTE需要发给实际获得焦点的控件，而不是顶层窗口
+        let thread = GetWindowThreadProcessId(window, ptr::null_mut());
+        let mut info: GUITHREADINFO = mem::zeroed();
+        info.cbSize = mem::size_of::<GUITHREADINFO>() as u32;
+        let target = if GetGUIThreadInfo(thread, &mut info) != 0 && !info.hwndFocus.is_null() {
+            info.hwndFocus
+        } else {
+            window
+        };
+
+        let mut result = 0;
+        if SendMessageTimeoutW(target, WM_PASTE, 0, 0, SMTO_ABORTIFHUNG, 500, &mut result) == 0 {
+            return Err(format!("发送WM_PASTE失败（错误码 {}）", GetLastError()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_app() {
+        let config: PasteStrategyConfig = serde_json::from_value(serde_json::json!({
+            "rules": [
+                { "apps": ["Taskmgr.exe", "regedit"], "strategy": "clipboard_only" },
+                { "apps": ["slack"], "strategy": "wm_paste" },
+            ]
+        }))
+        .unwrap();
+        assert_eq!(config.for_app(Some("taskmgr")), PasteStrategy::ClipboardOnly);
+        assert_eq!(config.for_app(Some("REGEDIT.EXE")), PasteStrategy::ClipboardOnly);
+        assert_eq!(config.for_app(Some("Slack.exe")), PasteStrategy::WmPaste);
+        assert_eq!(config.for_app(Some("notepad.exe")), PasteStrategy::Keystroke);
+        assert_eq!(config.for_app(None), PasteStrategy::Keystroke);
+    }
+
+    #[test]
+    fn test_error_suggests_strategy() {
+        #[cfg(target_os = "windows")]
+        {
+            let elevated = PasteError::ElevationMismatch { app: "Taskmgr.exe".to_string() };
+            assert!(elevated.to_string().contains("将 Taskmgr.exe 设为 clipboard_only"));
+        }
+        let failed = PasteError::Delivery { strategy: PasteStrategy::Keystroke, message: "未找到xdotool".to_string() };
+        assert!(failed.to_string().ends_with("改用 type_text 或 clipboard_only"));
+        assert!(PasteError::Unsupported(PasteStrategy::WmPaste).to_string().contains("wm_paste"));
+    }
+}
@@ -0,0 +1,152 @@
+/*!
+ * ClipVanish™ 粘贴转换模块
+ *
+ * 按粘贴目标程序在交付明文前转换内容，例如为终端加引号、为单行输入框去掉换行、为REST客户端加 `Bearer ` 前缀
+ * 特点：
+ * - 规则在配置中按程序名称定义，第一条匹配目标程序的规则生效，其转换按顺序执行
+ * - 只作用于粘贴交付的明文（键盘粘贴、延迟渲染与确认后的安全粘贴），剪贴板中的密文与历史记录不受影响
+ * - 目标程序无法识别时不做转换
+ *
+ * 作者: ClipVanish Team
+ */
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+use crate::source_app;
+
+/// 单个转换步骤
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transform {
+    /// 去掉首尾空白
+    Trim,
+    /// 去掉换行（多行内容合并为一行）
+    StripNewlines,
+    /// 按POSIX shell规则加单引号
+    ShellQuote,
+    /// 加双引号，并转义其中的反斜杠与双引号
+    DoubleQuote,
+    /// 添加前缀（内容已以该前缀开头时不重复添加）
+    Prefix(String),
+    /// 添加后缀（内容已以该后缀结尾时不重复添加）
+    Suffix(String),
+}
+
+impl Transform {
+    /// 对内容执行转换
+    ///
+    /// # 参数
+    /// * `content` - 待转换的内容
+    pub fn apply(&self, content: &str) -> String {
+        match self {
+            Transform::Trim => content.trim().to_string(),
+            Transform::StripNewlines => content.chars().filter(|c| !matches!(c, '\r' | '\n')).collect(),
+            Transform::ShellQuote => format!("'{}'", content.replace('\'', r"'\''")),
+            Transform::DoubleQuote => format!("\"{}\"", content.replace('\\', r"\\").replace('"', "\\\"")),
+            Transform::Prefix(prefix) if content.starts_with(prefix.as_str()) => content.to_string(),
+            Transform::Prefix(prefix) => format!("{}{}", prefix, content),
+            Transform::Suffix(suffix) if content.ends_with(suffix.as_str()) => content.to_string(),
+            Transform::Suffix(suffix) => format!("{}{}", content, suffix),
+        }
+    }
+}
+
+/// 粘贴转换规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasteTransformRule {
+    /// 目标程序名称（不区分大小写，`.exe` 后缀可省略）
+    pub apps: Vec<String>,
+    /// 按顺序执行的转换
+    pub transforms: Vec<Transform>,
+}
+
+impl PasteTransformRule {
+    /// 规则是否适用于目标程序
+    fn matches(&self, app: &str) -> bool {
+        app_matches(&self.apps, app)
+    }
+}
+
+/// 程序名称列表是否包含目标程序（不区分大小写，`.exe` 后缀可省略）
+///
+/// # 参数
+/// * `apps` - 配置中的程序名称
+/// * `app` - 目标程序名称
+pub(crate) fn app_matches(apps: &[String], app: &str) -> bool {
+    let normalize = |name: &str| {
+        let name = name.trim().to_lowercase();
+        name.strip_suffix(".exe").map(str::to_string).unwrap_or(name)
+    };
+    let app = normalize(app);
+    apps.iter().any(|candidate| normalize(candidate) == app)
+}
+
+/// 粘贴转换配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PasteTransformConfig {
+    /// 转换规则（第一条匹配目标程序的规则生效）
+    #[serde(default)]
+    pub rules: Vec<PasteTransformRule>,
+}
+
+impl PasteTransformConfig {
+    /// 按目标程序转换粘贴内容（没有匹配的规则时返回None）
+    ///
+    /// # 参数
+    /// * `target` - 粘贴目标程序名称
+    /// * `content` - 解密后的内容
+    pub fn apply(&self, target: Option<&str>, content: &str) -> Option<Zeroizing<String>> {
+        let target = target?;
+        let rule = self.rules.iter().find(|rule| rule.matches(target))?;
+        let transformed = rule
+            .transforms
+            .iter()
+            .fold(Zeroizing::new(content.to_string()), |content, transform| Zeroizing::new(transform.apply(&content)));
+        debug!("已按 {} 的粘贴转换规则处理内容", target);
+        Some(transformed)
+    }
+
+    /// 按当前前台程序转换粘贴内容（没有规则时不检测前台程序）
+    ///
+    /// # 参数
+    /// * `content` - 解密后的内容
+    pub fn apply_to_foreground(&self, content: &str) -> Option<Zeroizing<String>> {
+        if self.rules.is_empty() {
+            return None;
+        }
+        self.apply(source_app::foreground_app().as_deref(), content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transforms() {
+        assert_eq!(Transform::ShellQuote.apply("it's $HOME"), r"'it'\''s $HOME'");
+        assert_eq!(Transform::DoubleQuote.apply(r#"a"b\c"#), r#""a\"b\\c""#);
+        assert_eq!(Transform::StripNewlines.apply("line1\r\nline2\n"), "line1line2");
+        assert_eq!(Transform::Prefix("Bearer ".to_string()).apply("abc"), "Bearer abc");
+        assert_eq!(Transform::Prefix("Bearer ".to_string()).apply("Bearer abc"), "Bearer abc");
+        assert_eq!(Transform::Suffix(";".to_string()).apply("x"), "x;");
+    }
+
+    #[test]
+    fn test_rule_selection() {
+        let config: PasteTransformConfig = serde_json::from_str(
+            r#"{"rules": [
+                {"apps": ["Postman.exe", "Insomnia"], "transforms": ["trim", {"prefix": "Bearer "}]},
+                {"apps": ["bash", "WindowsTerminal"], "transforms": ["strip_newlines", "shell_quote"]}
+            ]}"#,
+        )
+        .unwrap();
+
+        let apply = |target: Option<&str>, content: &str| config.apply(target, content).map(|content| content.to_string());
+        assert_eq!(apply(Some("postman"), " tok\n").as_deref(), Some("Bearer tok"));
+        assert_eq!(apply(Some("WindowsTerminal.exe"), "a\nb").as_deref(), Some("'ab'"));
+        assert_eq!(apply(Some("notepad.exe"), "x"), None);
+        assert_eq!(apply(None, "x"), None);
+    }
+}
@@ -0,0 +1,7 @@
+/*!
+ * macOS平台功能
+ *
+ * 包含只在macOS上可用的系统接口封装
+ */
+
+pub mod pasteboard;
@@ -0,0 +1,189 @@
+/*!
+ * NSPasteboard快照
+ *
+ * 完整保存通用剪贴板中的全部条目与类型，之后原样写回，用于安全粘贴与格式隔离后的恢复
+ * 特点：
+ * - 保留多个条目及每个条目的全部类型（富文本、RTF、文件URL、私有类型等），而不只是纯文本
+ * - 直接调用AppKit，不启动osascript，严格模式下同样可用
+ * - 快照数据保存在内存中，释放时清零
+ * - 应用承诺提供但无法取得数据的类型跳过，不影响其余类型
+ */
+
+use std::ffi::{c_char, c_void, CStr};
+use cocoa::base::{id, nil, BOOL, NO};
+use cocoa::foundation::{NSInteger, NSString, NSUInteger};
+use objc::{class, msg_send, sel, sel_impl};
+use zeroize::Zeroizing;
+
+/// 单个条目的全部类型及数据（按条目中的原始顺序）
+type Item = Vec<(String, Zeroizing<Vec<u8>>)>;
+
+/// 剪贴板快照
+pub struct PasteboardSnapshot {
+    /// 全部条目
+    items: Vec<Item>,
+}
+
+impl PasteboardSnapshot {
+    /// 快照通用剪贴板中的全部条目与类型
+    ///
+    /// # 返回值
+    /// * `Result<PasteboardSnapshot, String>` - 快照（剪贴板为空时没有条目）
+    pub fn capture() -> Result<Self, String> {
+        unsafe {
+            let pool: id = msg_send![class!(NSAutoreleasePool), new];
+            let items = capture_items();
+            let _: () = msg_send![pool, drain];
+            items.map(|items| PasteboardSnapshot { items })
+        }
+    }
+
+    /// 由单个条目构成的快照
+    ///
+    /// # 参数
+    /// * `types` - 类型及数据
+    pub fn single(types: Item) -> Self {
+        PasteboardSnapshot { items: if types.is_empty() { Vec::new() } else { vec![types] } }
+    }
+
+    /// 快照中出现的全部类型（去重，按首次出现的顺序）
+    pub fn types(&self) -> Vec<String> {
+        let mut types: Vec<String> = Vec::new();
+        for (name, _) in self.items.iter().flatten() {
+            if !types.contains(name) {
+                types.push(name.clone());
+            }
+        }
+        types
+    }
+
+    /// 第一个带该类型的条目中的数据
+    ///
+    /// # 参数
+    /// * `name` - 类型（UTI）
+    pub fn data(&self, name: &str) -> Option<&[u8]> {
+        self.items.iter().flatten().find(|(type_name, _)| type_name == name).map(|(_, data)| data.as_slice())
+    }
+
+    /// 清空剪贴板并原样写回快照中的全部条目（快照为空时只清空）
+    ///
+    /// # 返回值
+    /// * `Result<usize, String>` - 写回的类型数量
+    pub fn restore(&self) -> Result<usize, String> {
+        unsafe {
+            let pool: id = msg_send![class!(NSAutoreleasePool), new];
+            let result = restore_items(&self.items);
+            let _: () = msg_send![pool, drain];
+            result
+        }
+    }
+}
+
+/// 读取全部条目（需在自动释放池中调用）
+unsafe fn capture_items() -> Result<Vec<Item>, String> {
+    let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+    let items: id = msg_send![pasteboard, pasteboardItems];
+    if items == nil {
+        return Err("无法读取剪贴板条目".to_string());
+    }
+
+    let count: NSUInteger = msg_send![items, count];
+    let mut captured = Vec::with_capacity(count as usize);
+    for index in 0..count {
+        let item: id = msg_send![items, objectAtIndex: index];
+        let types: id = msg_send![item, types];
+        let type_count: NSUInteger = msg_send![types, count];
+        let mut entries = Vec::with_capacity(type_count as usize);
+        for type_index in 0..type_count {
+            let name: id = msg_send![types, objectAtIndex: type_index];
+            let data: id = msg_send![item, dataForType: name];
+            if data == nil {
+                continue;
+            }
+            entries.push((to_string(name), copy_bytes(data)));
+        }
+        captured.push(entries);
+    }
+    Ok(captured)
+}
+
+/// 写回全部条目（需在自动释放池中调用）
+unsafe fn restore_items(items: &[Item]) -> Result<usize, String> {
+    let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+    let objects: id = msg_send![class!(NSMutableArray), array];
+    let mut written = 0;
+    for types in items {
+        let item: id = msg_send![class!(NSPasteboardItem), new];
+        let item: id = msg_send![item, autorelease];
+        for (name, data) in types {
+            let bytes: id = msg_send![
+                class!(NSData),
+                dataWithBytes: data.as_ptr() as *const c_void
+                length: data.len() as NSUInteger
+            ];
+            let ok: BOOL = msg_send![item, setData: bytes forType: ns_string(name)];
+            if ok != NO {
+                written += 1;
+            }
+        }
+        let _: () = msg_send![objects, addObject: item];
+    }
+
+    let _: NSInteger = msg_send![pasteboard, clearContents];
+    if items.is_empty() {
+        return Ok(0);
+    }
+    let ok: BOOL = msg_send![pasteboard, writeObjects: objects];
+    if ok == NO {
+        return Err("写回剪贴板条目失败".to_string());
+    }
+    Ok(written)
+}
+
+/// 复制NSData中的字节
+unsafe fn copy_bytes(data: id) -> Zeroizing<Vec<u8>> {
+    let length: NSUInteger = msg_send![data, length];
+    let bytes: *const u8 = msg_send![data, bytes];
+    if bytes.is_null() || length == 0 {
+        return Zeroizing::new(Vec::new());
+    }
+    Zeroizing::new(std::slice::from_raw_parts(bytes, length as usize).to_vec())
+}
+
+/// NSString转为Rust字符串
+unsafe fn to_string(string: id) -> String {
+    let utf8: *const c_char = msg_send![string, UTF8String];
+    if utf8.is_null() {
+        return String::new();
+    }
+    CStr::from_ptr(utf8).to_string_lossy().into_owned()
+}
+
+/// 创建自动释放的NSString
+unsafe fn ns_string(text: &str) -> id {
+    let string = NSString::alloc(nil).init_str(text);
+    msg_send![string, autorelease]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(types: &[(&str, &[u8])]) -> Item {
+        types.iter().map(|(name, data)| (name.to_string(), Zeroizing::new(data.to_vec()))).collect()
+    }
+
+    #[test]
+    fn test_types_and_data() {
+        let snapshot = PasteboardSnapshot {
+            items: vec![
+                item(&[("public.utf8-plain-text", b"a"), ("public.rtf", b"{\\rtf1 a}")]),
+                item(&[("public.file-url", b"file:///tmp/a"), ("public.utf8-plain-text", b"/tmp/a")]),
+            ],
+        };
+        assert_eq!(snapshot.types(), vec!["public.utf8-plain-text", "public.rtf", "public.file-url"]);
+        assert_eq!(snapshot.data("public.utf8-plain-text"), Some(&b"a"[..]));
+        assert_eq!(snapshot.data("public.png"), None);
+        assert!(PasteboardSnapshot::single(Vec::new()).types().is_empty());
+    }
+}
@@ -0,0 +1,170 @@
+/*!
+ * ClipVanish™ 平台相关功能模块
+ *
+ * 配置目录中持久化的状态（笔记库、历史记录、取证密钥、令牌等）统一经此读写
+ * 特点：
+ * - Windows上写入前用DPAPI绑定到当前用户再落盘，配置目录被复制到其他计算机或用户后无法解密
+ * - 读取时兼容旧版本留下的明文文件，下次保存时自动改为包装格式
+ * - 状态文件以0600权限创建，不存在先按umask落盘再收紧权限的窗口
+ * - 其他平台原样读写（依靠文件权限保护）；遇到Windows包装的文件时明确报错，而不是当作损坏文件覆盖
+ *
+ * 作者: ClipVanish Team
+ */
+
+#[cfg(windows)]
+pub mod windows;
+#[cfg(target_os = "macos")]
+pub mod macos;
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use zeroize::Zeroizing;
+
+/// 包装文件的格式标记（其后为DPAPI密文）
+const WRAPPED_MAGIC: &[u8] = b"CLIPVANISH-DPAPI-1\n";
+
+/// 写入持久化状态（仅当前用户可读写）
+///
+/// # 参数
+/// * `path` - 状态文件路径
+/// * `contents` - 明文内容
+pub fn write_state<P: AsRef<Path>>(path: P, contents: &[u8]) -> io::Result<()> {
+    write_private(path, &wrap(contents)?)
+}
+
+/// 写入仅当前用户可读写的文件（Unix权限0600）
+///
+/// 文件创建时即为0600，不会先按umask落盘再收紧权限；覆盖已有文件时先收紧权限再写入
+///
+/// # 参数
+/// * `path` - 文件路径
+/// * `contents` - 文件内容
+pub fn write_private<P: AsRef<Path>>(path: P, contents: &[u8]) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    open_private(&mut options, path.as_ref())?.write_all(contents)
+}
+
+/// 新建仅当前用户可读写的文件（Unix权限0600），文件已存在时返回 `AlreadyExists`
+///
+/// 用于生成密钥文件，不会覆盖已有的密钥
+///
+/// # 参数
+/// * `path` - 文件路径
+/// * `contents` - 文件内容
+pub fn create_private<P: AsRef<Path>>(path: P, contents: &[u8]) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    open_private(&mut options, path.as_ref())?.write_all(contents)
+}
+
+/// 以0600权限打开文件
+fn open_private(options: &mut fs::OpenOptions, path: &Path) -> io::Result<fs::File> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        let file = options.mode(0o600).open(path)?;
+        // mode只在创建时生效，已存在的文件需要另行收紧
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        Ok(file)
+    }
+    #[cfg(not(unix))]
+    options.open(path)
+}
+
+/// 读取持久化状态
+///
+/// # 参数
+/// * `path` - 状态文件路径
+///
+/// # 返回值
+/// * `io::Result<String>` - 明文内容；文件不存在时错误类型为 `NotFound`，无法解开包装时为 `InvalidData`
+pub fn read_state_to_string<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    let data = Zeroizing::new(fs::read(path)?);
+    let plain = unwrap(&data)?;
+    String::from_utf8(plain.to_vec()).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// 包装明文
+#[cfg(windows)]
+fn wrap(contents: &[u8]) -> io::Result<Vec<u8>> {
+    let mut wrapped = WRAPPED_MAGIC.to_vec();
+    wrapped.extend_from_slice(&windows::dpapi::protect(contents)?);
+    Ok(wrapped)
+}
+
+/// 包装明文（非Windows平台原样返回）
+#[cfg(not(windows))]
+fn wrap(contents: &[u8]) -> io::Result<Vec<u8>> {
+    Ok(contents.to_vec())
+}
+
+/// 解开包装（旧版本的明文文件原样返回）
+fn unwrap(data: &[u8]) -> io::Result<Zeroizing<Vec<u8>>> {
+    match data.strip_prefix(WRAPPED_MAGIC) {
+        #[cfg(windows)]
+        Some(blob) => windows::dpapi::unprotect(blob),
+        #[cfg(not(windows))]
+        Some(_) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "状态文件已用Windows DPAPI绑定到原计算机的用户，无法在此平台读取",
+        )),
+        None => Ok(Zeroizing::new(data.to_vec())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_state_round_trip_and_legacy_plaintext() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("state.json");
+
+        write_state(&path, b"{\"notes\": []}").unwrap();
+        assert_eq!(read_state_to_string(&path).unwrap(), "{\"notes\": []}");
+        #[cfg(windows)]
+        assert!(fs::read(&path).unwrap().starts_with(WRAPPED_MAGIC));
+
+        // 旧版本写入的明文文件照常读取
+        fs::write(&path, "{\"legacy\": true}").unwrap();
+        assert_eq!(read_state_to_string(&path).unwrap(), "{\"legacy\": true}");
+
+        assert_eq!(read_state_to_string(dir.path().join("missing.json")).unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_private_files_are_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("escrow.key");
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+        create_private(&path, b"first").unwrap();
+        assert_eq!(mode(&path), 0o600);
+        assert_eq!(create_private(&path, b"second").unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read(&path).unwrap(), b"first");
+
+        // 覆盖权限过宽的已有文件时收紧权限
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        write_private(&path, b"second").unwrap();
+        assert_eq!(mode(&path), 0o600);
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_wrapped_state_is_rejected_off_windows() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("forensics.key");
+        let mut data = WRAPPED_MAGIC.to_vec();
+        data.extend_from_slice(&[0x01, 0x00, 0x00, 0x00, 0xd0, 0x8c]);
+        fs::write(&path, data).unwrap();
+
+        assert_eq!(read_state_to_string(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,117 @@
+/*!
+ * DPAPI包装
+ *
+ * 用 `CryptProtectData`/`CryptUnprotectData` 将数据绑定到当前Windows用户
+ * 特点：
+ * - 密钥由系统按用户凭据派生，其他计算机或其他用户无法解密
+ * - 附加应用专属熵，同一用户下的其他程序不能直接调用DPAPI解开
+ * - 禁止弹出任何界面（后台服务中也可使用）
+ * - 系统分配的明文缓冲区在释放前清零
+ */
+
+use std::io;
+use std::ptr;
+use winapi::shared::minwindef::BYTE;
+use winapi::um::dpapi::{CryptProtectData, CryptUnprotectData, CRYPTPROTECT_UI_FORBIDDEN};
+use winapi::um::winbase::LocalFree;
+use winapi::um::wincrypt::DATA_BLOB;
+use zeroize::Zeroizing;
+
+/// 应用专属熵
+const ENTROPY: &[u8] = b"ClipVanish persisted state v1";
+
+/// 将数据绑定到当前用户
+///
+/// # 参数
+/// * `data` - 明文
+///
+/// # 返回值
+/// * `io::Result<Vec<u8>>` - DPAPI密文
+pub fn protect(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut input = blob(data);
+    let mut entropy = blob(ENTROPY);
+    let mut output = DATA_BLOB { cbData: 0, pbData: ptr::null_mut() };
+
+    let ok = unsafe {
+        CryptProtectData(
+            &mut input,
+            ptr::null(),
+            &mut entropy,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            CRYPTPROTECT_UI_FORBIDDEN,
+            &mut output,
+        )
+    };
+    if ok == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(unsafe { take(output, false) }.to_vec())
+}
+
+/// 解开当前用户绑定的数据
+///
+/// # 参数
+/// * `data` - DPAPI密文
+///
+/// # 返回值
+/// * `io::Result<Zeroizing<Vec<u8>>>` - 明文；来自其他计算机或用户时返回 `InvalidData`
+pub fn unprotect(data: &[u8]) -> io::Result<Zeroizing<Vec<u8>>> {
+    let mut input = blob(data);
+    let mut entropy = blob(ENTROPY);
+    let mut output = DATA_BLOB { cbData: 0, pbData: ptr::null_mut() };
+
+    let ok = unsafe {
+        CryptUnprotectData(
+            &mut input,
+            ptr::null_mut(),
+            &mut entropy,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            CRYPTPROTECT_UI_FORBIDDEN,
+            &mut output,
+        )
+    };
+    if ok == 0 {
+        let cause = io::Error::last_os_error();
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("无法用当前用户的DPAPI密钥解密（文件可能来自其他计算机或用户）: {}", cause),
+        ));
+    }
+    Ok(unsafe { take(output, true) })
+}
+
+/// 以只读方式引用数据（DPAPI不会修改输入）
+fn blob(data: &[u8]) -> DATA_BLOB {
+    DATA_BLOB { cbData: data.len() as u32, pbData: data.as_ptr() as *mut BYTE }
+}
+
+/// 复制系统分配的输出缓冲区并释放
+///
+/// # Safety
+/// `output` 必须是DPAPI成功返回的缓冲区
+unsafe fn take(output: DATA_BLOB, sensitive: bool) -> Zeroizing<Vec<u8>> {
+    let data = Zeroizing::new(std::slice::from_raw_parts(output.pbData, output.cbData as usize).to_vec());
+    if sensitive {
+        ptr::write_bytes(output.pbData, 0, output.cbData as usize);
+    }
+    LocalFree(output.pbData as _);
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protect_round_trip() {
+        let wrapped = protect(b"forensics key").unwrap();
+        assert!(!wrapped.windows(b"forensics key".len()).any(|window| window == b"forensics key"));
+        assert_eq!(unprotect(&wrapped).unwrap().as_slice(), b"forensics key");
+
+        let mut tampered = wrapped.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert_eq!(unprotect(&tampered).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
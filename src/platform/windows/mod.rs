@@ -0,0 +1,7 @@
+/*!
+ * Windows平台功能
+ *
+ * 包含只在Windows上可用的系统接口封装
+ */
+
+pub mod dpapi;
@@ -0,0 +1,251 @@
+/*!
+ * ClipVanish™ 受管策略模块
+ *
+ * 企业部署时由管理员下发只读的系统级策略，合并到用户配置之上且不能被用户覆盖
+ * 特点：
+ * - 策略是配置的局部JSON（如 {"security": {"destroy_on_paste": true}}），只强制列出的设置
+ * - 每次加载配置（包括SIGHUP重新加载与重置配置）都会重新应用
+ * - 用户配置与策略不一致时记录冲突并在日志、`config` 中报告
+ * - 策略存在但无法解析或包含未知设置时拒绝启动，避免策略静默失效
+ *
+ * 策略来源：
+ * - Linux/Unix: /etc/clipvanish/policy.json
+ * - macOS: /Library/Managed Preferences/com.clipvanish.policy.plist（配置描述文件下发），其次 /etc/clipvanish/policy.json
+ * - Windows: HKLM\SOFTWARE\Policies\ClipVanish 下的字符串值 Policy（内容为JSON）
+ *
+ * 作者: ClipVanish Team
+ */
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use crate::config::Config;
+
+/// 受管策略错误类型
+#[derive(Debug)]
+pub enum PolicyError {
+    /// 策略格式错误
+    ParseError(String),
+    /// 策略包含配置中不存在的设置
+    UnknownSetting(String),
+    /// 合并后的配置无效
+    InvalidConfig(String),
+}
+
+impl std::fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolicyError::ParseError(msg) => write!(f, "受管策略格式错误: {}", msg),
+            PolicyError::UnknownSetting(path) => write!(f, "受管策略包含未知设置: {}", path),
+            PolicyError::InvalidConfig(msg) => write!(f, "应用受管策略后配置无效: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for PolicyError {}
+
+/// 用户配置与策略的冲突
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyConflict {
+    /// 设置路径（如 security.destroy_on_paste）
+    pub setting: String,
+    /// 用户配置中的值
+    pub user_value: Value,
+    /// 策略强制的值
+    pub enforced_value: Value,
+}
+
+impl std::fmt::Display for PolicyConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: 用户配置 {} → 策略强制 {}", self.setting, self.user_value, self.enforced_value)
+    }
+}
+
+/// 策略应用结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PolicyReport {
+    /// 策略来源
+    pub source: String,
+    /// 被强制的设置路径
+    pub enforced: Vec<String>,
+    /// 与用户配置冲突的设置
+    pub conflicts: Vec<PolicyConflict>,
+}
+
+/// 系统级受管策略
+#[derive(Debug, Clone)]
+pub struct ManagedPolicy {
+    /// 策略来源（文件路径或注册表键）
+    source: String,
+    /// 强制的设置
+    settings: Map<String, Value>,
+}
+
+impl ManagedPolicy {
+    /// 从系统策略来源加载
+    ///
+    /// # 返回值
+    /// * `Result<Option<ManagedPolicy>, PolicyError>` - 未部署策略时返回None
+    pub fn load_system() -> Result<Option<Self>, PolicyError> {
+        match read_system_policy() {
+            Some((source, text)) => Self::parse(&source, &text).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// 解析策略内容
+    ///
+    /// # 参数
+    /// * `source` - 策略来源（用于报告）
+    /// * `text` - 策略JSON
+    pub fn parse(source: &str, text: &str) -> Result<Self, PolicyError> {
+        match serde_json::from_str::<Value>(text) {
+            Ok(Value::Object(settings)) => Ok(ManagedPolicy { source: source.to_string(), settings }),
+            Ok(_) => Err(PolicyError::ParseError("策略必须是JSON对象".to_string())),
+            Err(e) => Err(PolicyError::ParseError(e.to_string())),
+        }
+    }
+
+    /// 策略来源
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// 将策略合并到用户配置之上
+    ///
+    /// # 返回值
+    /// * `Result<(Config, PolicyReport), PolicyError>` - 合并后的配置与冲突报告
+    pub fn apply(&self, config: &Config) -> Result<(Config, PolicyReport), PolicyError> {
+        let mut merged = serde_json::to_value(config).map_err(|e| PolicyError::InvalidConfig(e.to_string()))?;
+        let mut report = PolicyReport { source: self.source.clone(), ..PolicyReport::default() };
+        merge(&mut merged, &self.settings, "", &mut report)?;
+
+        let mut result: Config = serde_json::from_value(merged)
+            .map_err(|e| PolicyError::InvalidConfig(e.to_string()))?;
+        result.validate().map_err(|e| PolicyError::InvalidConfig(e.to_string()))?;
+        result.managed_policy = Some(report.clone());
+        Ok((result, report))
+    }
+}
+
+/// 递归合并，策略中的叶子值覆盖用户配置
+fn merge(target: &mut Value, policy: &Map<String, Value>, prefix: &str, report: &mut PolicyReport) -> Result<(), PolicyError> {
+    for (key, enforced) in policy {
+        let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        let current = target
+            .as_object_mut()
+            .and_then(|object| object.get_mut(key))
+            .ok_or_else(|| PolicyError::UnknownSetting(path.clone()))?;
+
+        match (current.is_object(), enforced) {
+            (true, Value::Object(nested)) => merge(current, nested, &path, report)?,
+            _ => {
+                if current != enforced {
+                    report.conflicts.push(PolicyConflict {
+                        setting: path.clone(),
+                        user_value: current.clone(),
+                        enforced_value: enforced.clone(),
+                    });
+                    *current = enforced.clone();
+                }
+                report.enforced.push(path);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 系统级策略文件路径
+#[cfg(unix)]
+const POLICY_FILE: &str = "/etc/clipvanish/policy.json";
+
+/// Linux/Unix: 读取策略文件
+#[cfg(all(unix, not(target_os = "macos")))]
+fn read_system_policy() -> Option<(String, String)> {
+    read_policy_file(POLICY_FILE)
+}
+
+/// macOS: 优先读取配置描述文件下发的受管偏好设置
+#[cfg(target_os = "macos")]
+fn read_system_policy() -> Option<(String, String)> {
+    const MANAGED_PLIST: &str = "/Library/Managed Preferences/com.clipvanish.policy.plist";
+
+    if std::path::Path::new(MANAGED_PLIST).exists() {
+        let output = std::process::Command::new("plutil")
+            .args(["-convert", "json", "-o", "-", MANAGED_PLIST])
+            .output()
+            .ok()
+            .filter(|output| output.status.success());
+        // 无法转换时返回空内容，由解析阶段报告错误，避免策略静默失效
+        let text = output.map(|output| String::from_utf8_lossy(&output.stdout).to_string()).unwrap_or_default();
+        return Some((MANAGED_PLIST.to_string(), text));
+    }
+
+    read_policy_file(POLICY_FILE)
+}
+
+/// Windows: 读取 HKLM\SOFTWARE\Policies\ClipVanish 的 Policy 值
+#[cfg(target_os = "windows")]
+fn read_system_policy() -> Option<(String, String)> {
+    const POLICY_KEY: &str = r"HKLM\SOFTWARE\Policies\ClipVanish";
+
+    let output = std::process::Command::new("reg")
+        .args(["query", POLICY_KEY, "/v", "Policy"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let text = stdout
+        .lines()
+        .find_map(|line| line.split_once("REG_SZ").map(|(_, value)| value.trim().to_string()))?;
+    Some((format!(r"{}\Policy", POLICY_KEY), text))
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn read_system_policy() -> Option<(String, String)> {
+    None
+}
+
+/// 读取策略文件（不存在时返回None）
+#[cfg(unix)]
+fn read_policy_file(path: &str) -> Option<(String, String)> {
+    std::fs::read_to_string(path).ok().map(|text| (path.to_string(), text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_overrides_user_config() {
+        let mut user = Config::default();
+        user.security.destroy_on_paste = false;
+        user.clear_delay_seconds = 10;
+
+        let policy = ManagedPolicy::parse(
+            "test",
+            r#"{"security": {"destroy_on_paste": true}, "clear_delay_seconds": 10}"#,
+        )
+        .unwrap();
+        let (merged, report) = policy.apply(&user).unwrap();
+
+        assert!(merged.security.destroy_on_paste);
+        assert_eq!(report.enforced, vec!["clear_delay_seconds", "security.destroy_on_paste"]);
+        // 只有值不同的设置算作冲突
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].setting, "security.destroy_on_paste");
+        assert_eq!(merged.managed_policy.unwrap().source, "test");
+    }
+
+    #[test]
+    fn test_invalid_policy_rejected() {
+        let config = Config::default();
+        assert!(ManagedPolicy::parse("test", "[1, 2]").is_err());
+
+        let typo = ManagedPolicy::parse("test", r#"{"security": {"destroy_on_pasta": true}}"#).unwrap();
+        assert!(matches!(typo.apply(&config), Err(PolicyError::UnknownSetting(_))));
+
+        let invalid = ManagedPolicy::parse("test", r#"{"timer": {"min_countdown": 999999}}"#).unwrap();
+        assert!(matches!(invalid.apply(&config), Err(PolicyError::InvalidConfig(_))));
+    }
+}
@@ -0,0 +1,350 @@
+/*!
+ * ClipVanish™ 门户剪贴板模块
+ *
+ * 通过 xdg-desktop-portal 的 RemoteDesktop 与 Clipboard 接口访问剪贴板，供Flatpak/Snap等沙箱环境使用
+ * 特点：
+ * - 运行时协商门户会话（用户在系统对话框中授权一次），进程内所有剪贴板上下文共享同一会话
+ * - 剪贴板数据经门户传递的文件描述符读写，不经过X11，也不启动辅助程序
+ * - 本进程提供的剪贴板内容在被其他应用取代或覆盖时清零
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
+use std::time::Duration;
+use log::{debug, info, warn};
+use zeroize::Zeroizing;
+use crate::dbus::{Connection, Message, MessageKind, Sender, Value};
+
+/// 门户服务名与对象路径
+const PORTAL_DEST: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+
+/// 门户接口
+const REMOTE_DESKTOP: &str = "org.freedesktop.portal.RemoteDesktop";
+const CLIPBOARD: &str = "org.freedesktop.portal.Clipboard";
+const REQUEST: &str = "org.freedesktop.portal.Request";
+const SESSION: &str = "org.freedesktop.portal.Session";
+
+/// 提供与读取的文本格式（按优先级排列）
+const TEXT_MIME_TYPES: &[&str] = &["text/plain;charset=utf-8", "text/plain"];
+
+/// RemoteDesktop设备类型：键盘（剪贴板共享需要至少一种设备）
+const DEVICE_KEYBOARD: u32 = 1;
+
+/// 普通调用的超时
+const CALL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 等待用户在授权对话框中确认的最长时间
+const PROMPT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// 进程内共享的门户会话（授权失败后不再重复弹出对话框）
+static SHARED: OnceLock<Result<Arc<PortalClipboard>, String>> = OnceLock::new();
+
+/// 请求令牌计数
+static NEXT_TOKEN: AtomicU32 = AtomicU32::new(1);
+
+/// 其他应用提供的剪贴板内容状态
+#[derive(Default)]
+struct SelectionState {
+    /// 当前剪贴板提供的文本格式（None表示尚未收到通知）
+    offered_text: Option<Option<String>>,
+    /// 门户会话是否已关闭
+    closed: bool,
+}
+
+/// 门户剪贴板会话
+pub struct PortalClipboard {
+    sender: Arc<Sender>,
+    session: String,
+    /// 等待回复的调用（序号 -> 回复通道）
+    pending: Mutex<HashMap<u32, mpsc::Sender<Message>>>,
+    /// 本进程提供的剪贴板内容
+    selection: Mutex<Option<Zeroizing<String>>>,
+    state: Mutex<SelectionState>,
+}
+
+/// 获取进程内共享的门户会话（首次调用时协商，可能弹出授权对话框）
+pub fn shared() -> Result<Arc<PortalClipboard>, String> {
+    SHARED
+        .get_or_init(|| {
+            info!("正在通过xdg-desktop-portal请求剪贴板访问");
+            PortalClipboard::connect().map_err(|e| format!("门户剪贴板不可用: {}", e))
+        })
+        .clone()
+}
+
+impl PortalClipboard {
+    /// 建立RemoteDesktop会话并启用剪贴板共享
+    fn connect() -> Result<Arc<Self>, String> {
+        let mut connection = Connection::session().map_err(|e| e.to_string())?;
+        for rule in [
+            format!("type='signal',interface='{}',member='Response'", REQUEST),
+            format!("type='signal',interface='{}'", CLIPBOARD),
+            format!("type='signal',interface='{}',member='Closed'", SESSION),
+        ] {
+            connection.add_match(&rule).map_err(|e| e.to_string())?;
+        }
+        connection.set_read_timeout(Some(PROMPT_TIMEOUT)).map_err(|e| e.to_string())?;
+
+        let session_token = next_token();
+        let created = portal_request(
+            &mut connection,
+            "CreateSession",
+            Vec::new(),
+            vec![("session_handle_token", Value::str(&session_token))],
+        )?;
+        let session = created
+            .get("session_handle")
+            .and_then(Value::as_str)
+            .ok_or("门户未返回会话")?
+            .to_string();
+
+        let session_path = Value::Path(session.clone());
+        portal_request(
+            &mut connection,
+            "SelectDevices",
+            vec![session_path.clone()],
+            vec![("types", Value::U32(DEVICE_KEYBOARD))],
+        )?;
+
+        // 剪贴板必须在Start之前请求
+        connection
+            .call(PORTAL_DEST, PORTAL_PATH, CLIPBOARD, "RequestClipboard", &[session_path.clone(), Value::dict(Vec::new())])
+            .map_err(|e| e.to_string())?;
+
+        let started = portal_request(&mut connection, "Start", vec![session_path, Value::str("")], Vec::new())?;
+        if started.get("clipboard_enabled").and_then(Value::as_bool) != Some(true) {
+            return Err("用户未允许剪贴板共享".to_string());
+        }
+
+        connection.set_read_timeout(None).map_err(|e| e.to_string())?;
+        let clipboard = Arc::new(PortalClipboard {
+            sender: connection.sender(),
+            session,
+            pending: Mutex::new(HashMap::new()),
+            selection: Mutex::new(None),
+            state: Mutex::new(SelectionState::default()),
+        });
+
+        let worker = Arc::clone(&clipboard);
+        std::thread::Builder::new()
+            .name("portal-clipboard".to_string())
+            .spawn(move || worker.dispatch(connection))
+            .map_err(|e| e.to_string())?;
+
+        info!("门户剪贴板会话已建立");
+        Ok(clipboard)
+    }
+
+    /// 读取剪贴板文本
+    pub fn get_contents(&self) -> Result<String, String> {
+        if self.state.lock().unwrap().closed {
+            return Err("门户会话已关闭".to_string());
+        }
+        if let Some(content) = self.selection.lock().unwrap().as_ref() {
+            return Ok(content.to_string());
+        }
+
+        let mime_type = match &self.state.lock().unwrap().offered_text {
+            Some(None) => return Ok(String::new()),
+            Some(Some(mime_type)) => mime_type.clone(),
+            None => TEXT_MIME_TYPES[0].to_string(),
+        };
+
+        let reply = self.call(CLIPBOARD, "SelectionRead", vec![Value::Path(self.session.clone()), Value::Str(mime_type)])?;
+        let fd = reply.fds.into_iter().next().ok_or("门户未返回数据通道")?;
+        let mut data = Zeroizing::new(Vec::new());
+        File::from(fd).read_to_end(&mut data).map_err(|e| e.to_string())?;
+        String::from_utf8(data.to_vec()).map_err(|_| "剪贴板内容不是UTF-8文本".to_string())
+    }
+
+    /// 设置剪贴板文本（空内容表示清除本进程提供的内容）
+    pub fn set_contents(&self, content: String) -> Result<(), String> {
+        let mime_types: &[&str] = if content.is_empty() { &[] } else { TEXT_MIME_TYPES };
+        // 旧内容随Zeroizing释放时清零
+        *self.selection.lock().unwrap() = (!content.is_empty()).then(|| Zeroizing::new(content));
+
+        let options = Value::dict(vec![("mime_types", Value::string_array(mime_types))]);
+        self.call(CLIPBOARD, "SetSelection", vec![Value::Path(self.session.clone()), options])?;
+        Ok(())
+    }
+
+    /// 调用门户方法并等待回复
+    fn call(&self, interface: &str, member: &str, body: Vec<Value>) -> Result<Message, String> {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        let serial = self.sender.next_serial();
+        self.pending.lock().unwrap().insert(serial, reply_tx);
+
+        if let Err(e) = self.sender.call(serial, PORTAL_DEST, PORTAL_PATH, interface, member, &body) {
+            self.pending.lock().unwrap().remove(&serial);
+            return Err(e.to_string());
+        }
+
+        let reply = reply_rx.recv_timeout(CALL_TIMEOUT).map_err(|_| {
+            self.pending.lock().unwrap().remove(&serial);
+            format!("门户调用 {} 无响应", member)
+        })?;
+        reply.into_result().map_err(|e| e.to_string())
+    }
+
+    /// 读取线程：分发回复并处理剪贴板信号
+    fn dispatch(self: Arc<Self>, mut connection: Connection) {
+        loop {
+            let message = match connection.read_message() {
+                Ok(message) => message,
+                Err(e) => {
+                    warn!("门户连接已断开: {}", e);
+                    break;
+                }
+            };
+
+            if let Some(serial) = message.reply_serial {
+                if let Some(reply_tx) = self.pending.lock().unwrap().remove(&serial) {
+                    let _ = reply_tx.send(message);
+                }
+                continue;
+            }
+            if message.kind != MessageKind::Signal {
+                continue;
+            }
+
+            match (message.interface.as_deref(), message.member.as_deref()) {
+                (Some(CLIPBOARD), Some("SelectionTransfer")) => {
+                    let mime_type = message.body.get(1).and_then(Value::as_str).unwrap_or_default().to_string();
+                    let Some(serial) = message.body.get(2).and_then(Value::as_u32) else { continue };
+                    // 写入需要等待门户回复，不能在读取线程中进行
+                    let worker = Arc::clone(&self);
+                    std::thread::spawn(move || worker.transfer(&mime_type, serial));
+                }
+                (Some(CLIPBOARD), Some("SelectionOwnerChanged")) => {
+                    let options = message.body.get(1);
+                    let is_owner = options.and_then(|o| o.get("session_is_owner")).and_then(Value::as_bool) == Some(true);
+                    let offered = options
+                        .and_then(|o| o.get("mime_types"))
+                        .and_then(Value::as_array)
+                        .map(|types| types.iter().filter_map(Value::as_str).collect::<Vec<_>>())
+                        .unwrap_or_default();
+
+                    if !is_owner {
+                        // 其他应用取代了本进程提供的内容
+                        *self.selection.lock().unwrap() = None;
+                    }
+                    self.state.lock().unwrap().offered_text = Some(preferred_text_type(&offered));
+                    debug!("剪贴板所有者变化，提供 {} 种格式", offered.len());
+                }
+                (Some(SESSION), Some("Closed")) if message.path.as_deref() == Some(self.session.as_str()) => {
+                    warn!("门户剪贴板会话已被关闭");
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        self.state.lock().unwrap().closed = true;
+        *self.selection.lock().unwrap() = None;
+        // 丢弃等待中的回复通道，调用方立即得到错误
+        self.pending.lock().unwrap().clear();
+    }
+
+    /// 向请求剪贴板内容的应用写入数据
+    fn transfer(&self, mime_type: &str, serial: u32) {
+        let session = Value::Path(self.session.clone());
+        let result = self
+            .call(CLIPBOARD, "SelectionWrite", vec![session.clone(), Value::U32(serial)])
+            .and_then(|reply| {
+                let fd = reply.fds.into_iter().next().ok_or("门户未返回数据通道")?;
+                let content = self.selection.lock().unwrap().clone().unwrap_or_default();
+                File::from(fd).write_all(content.as_bytes()).map_err(|e| e.to_string())
+            });
+
+        if let Err(e) = &result {
+            warn!("向门户写入剪贴板内容（{}）失败: {}", mime_type, e);
+        }
+        let done = vec![session, Value::U32(serial), Value::Bool(result.is_ok())];
+        if let Err(e) = self.call(CLIPBOARD, "SelectionWriteDone", done) {
+            debug!("通知门户写入完成失败: {}", e);
+        }
+    }
+}
+
+/// 生成请求令牌
+fn next_token() -> String {
+    format!("clipvanish{}_{}", std::process::id(), NEXT_TOKEN.fetch_add(1, Ordering::SeqCst))
+}
+
+/// 请求对象路径：/org/freedesktop/portal/desktop/request/<连接名>/<令牌>
+///
+/// # 参数
+/// * `unique_name` - 总线连接名，如 `:1.42`
+/// * `token` - 请求令牌
+fn request_path(unique_name: &str, token: &str) -> String {
+    let sender = unique_name.trim_start_matches(':').replace('.', "_");
+    format!("{}/request/{}/{}", PORTAL_PATH, sender, token)
+}
+
+/// 在提供的格式中选择本程序支持的文本格式
+fn preferred_text_type(offered: &[&str]) -> Option<String> {
+    TEXT_MIME_TYPES
+        .iter()
+        .find(|mime_type| offered.contains(mime_type))
+        .map(|mime_type| mime_type.to_string())
+}
+
+/// 调用返回Request对象的RemoteDesktop方法，并等待Response信号
+///
+/// # 参数
+/// * `connection` - 会话总线连接
+/// * `method` - 方法名
+/// * `args` - 选项之前的参数
+/// * `options` - 选项（自动添加handle_token）
+fn portal_request(
+    connection: &mut Connection,
+    method: &str,
+    mut args: Vec<Value>,
+    mut options: Vec<(&str, Value)>,
+) -> Result<Value, String> {
+    let token = next_token();
+    let expected = request_path(connection.unique_name(), &token);
+    options.push(("handle_token", Value::str(&token)));
+    args.push(Value::dict(options));
+
+    connection
+        .call(PORTAL_DEST, PORTAL_PATH, REMOTE_DESKTOP, method, &args)
+        .map_err(|e| e.to_string())?;
+
+    loop {
+        let message = connection.read_message().map_err(|e| format!("等待门户响应失败: {}", e))?;
+        let is_response = message.kind == MessageKind::Signal
+            && message.member.as_deref() == Some("Response")
+            && message.path.as_deref() == Some(expected.as_str());
+        if !is_response {
+            continue;
+        }
+
+        return match message.body.first().and_then(Value::as_u32) {
+            Some(0) => Ok(message.body.into_iter().nth(1).unwrap_or_else(|| Value::dict(Vec::new()))),
+            Some(1) => Err("用户取消了门户授权".to_string()),
+            _ => Err(format!("门户请求 {} 失败", method)),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_path_and_text_type() {
+        assert_eq!(
+            request_path(":1.42", "clipvanish7"),
+            "/org/freedesktop/portal/desktop/request/1_42/clipvanish7"
+        );
+        assert_eq!(preferred_text_type(&["image/png", "text/plain"]), Some("text/plain".to_string()));
+        assert_eq!(preferred_text_type(&["image/png"]), None);
+    }
+}
@@ -0,0 +1,167 @@
+/*!
+ * ClipVanish™ 交互确认模块
+ *
+ * 统一处理破坏性操作前的确认提示
+ * 特点：
+ * - 检测标准输入是否为终端，非交互环境下默认取消
+ * - 可配置的确认超时，超时默认取消
+ * - 支持 --yes 全局参数跳过所有确认
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::time::Duration;
+
+/// 确认结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfirmOutcome {
+    /// 用户确认（或通过 --yes 预先确认）
+    Confirmed,
+    /// 用户拒绝
+    Declined,
+    /// 等待输入超时
+    TimedOut,
+    /// 标准输入不是终端，无法询问
+    NoTerminal,
+}
+
+impl ConfirmOutcome {
+    /// 是否允许继续执行
+    pub fn is_confirmed(&self) -> bool {
+        *self == ConfirmOutcome::Confirmed
+    }
+}
+
+/// 确认提示
+#[derive(Debug, Clone)]
+pub struct ConfirmPrompt {
+    /// 是否预先确认所有提示（--yes）
+    assume_yes: bool,
+    /// 等待输入的超时时间，为零表示一直等待
+    timeout: Duration,
+}
+
+impl ConfirmPrompt {
+    /// 创建确认提示
+    ///
+    /// # 参数
+    /// * `assume_yes` - 是否预先确认所有提示
+    /// * `timeout` - 等待输入的超时时间，为零表示一直等待
+    pub fn new(assume_yes: bool, timeout: Duration) -> Self {
+        ConfirmPrompt { assume_yes, timeout }
+    }
+
+    /// 询问用户是否继续
+    ///
+    /// # 参数
+    /// * `question` - 提示问题
+    ///
+    /// # 返回值
+    /// * `ConfirmOutcome` - 确认结果
+    pub fn ask(&self, question: &str) -> ConfirmOutcome {
+        if self.assume_yes {
+            eprintln!("   {} (y/N): y [--yes]", question);
+            return ConfirmOutcome::Confirmed;
+        }
+
+        if !io::stdin().is_terminal() {
+            eprintln!("   {} 非交互终端，已默认取消（可使用 --yes 跳过确认）", question);
+            return ConfirmOutcome::NoTerminal;
+        }
+
+        if self.timeout.is_zero() {
+            eprint!("   {} (y/N): ", question);
+        } else {
+            eprint!("   {} (y/N，{}秒后自动取消): ", question, self.timeout.as_secs());
+        }
+        io::stderr().flush().unwrap();
+
+        match read_line_with_timeout(self.timeout) {
+            Some(input) => parse_answer(&input),
+            None => {
+                eprintln!();
+                eprintln!("   ⌛ 等待确认超时，已默认取消");
+                ConfirmOutcome::TimedOut
+            },
+        }
+    }
+}
+
+/// 解析用户输入，仅以y开头视为确认
+fn parse_answer(input: &str) -> ConfirmOutcome {
+    if input.trim().to_lowercase().starts_with('y') {
+        ConfirmOutcome::Confirmed
+    } else {
+        ConfirmOutcome::Declined
+    }
+}
+
+/// 在超时时间内读取一行输入
+///
+/// 先等待标准输入可读再读取，避免超时后遗留阻塞的读取操作吞掉后续输入
+///
+/// # 参数
+/// * `timeout` - 超时时间，为零表示一直等待
+///
+/// # 返回值
+/// * `Option<String>` - 超时或读取失败时返回None
+#[cfg(unix)]
+fn read_line_with_timeout(timeout: Duration) -> Option<String> {
+    if !timeout.is_zero() {
+        let mut fds = libc::pollfd {
+            fd: libc::STDIN_FILENO,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let timeout_ms = timeout.as_millis().min(i32::MAX as u128) as i32;
+        let ready = unsafe { libc::poll(&mut fds, 1, timeout_ms) };
+        if ready <= 0 {
+            return None;
+        }
+    }
+
+    read_line()
+}
+
+/// 在超时时间内读取一行输入
+///
+/// 非Unix平台使用后台线程读取，超时后该线程读取到的输入会被丢弃
+#[cfg(not(unix))]
+fn read_line_with_timeout(timeout: Duration) -> Option<String> {
+    if timeout.is_zero() {
+        return read_line();
+    }
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(read_line());
+    });
+
+    receiver.recv_timeout(timeout).ok().flatten()
+}
+
+/// 从标准输入读取一行（EOF视为空输入）
+fn read_line() -> Option<String> {
+    let mut input = String::new();
+    io::stdin().lock().read_line(&mut input).ok().map(|_| input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_answer() {
+        assert_eq!(parse_answer("y\n"), ConfirmOutcome::Confirmed);
+        assert_eq!(parse_answer("Yes"), ConfirmOutcome::Confirmed);
+        assert_eq!(parse_answer("n"), ConfirmOutcome::Declined);
+        assert_eq!(parse_answer(""), ConfirmOutcome::Declined);
+    }
+
+    #[test]
+    fn test_assume_yes_skips_input() {
+        let prompt = ConfirmPrompt::new(true, Duration::from_secs(1));
+        assert!(prompt.ask("确认执行?").is_confirmed());
+    }
+}
@@ -0,0 +1,291 @@
+/*!
+ * ClipVanish™ 剪贴板格式隔离模块
+ *
+ * 检测到敏感复制时，快照并清除剪贴板中的未知格式（富文本、图片、私有二进制格式等），
+ * 防止这些格式在清除文本后继续残留
+ * 特点：
+ * - 只有纯文本格式被视为已知格式
+ * - 默认不恢复任何被隔离的格式
+ * - 白名单中的格式保存在内存中，受保护内容清除后恢复（Linux与macOS支持恢复）
+ * - macOS上直接读取NSPasteboard，格式名称为UTI（如 `public.rtf`）
+ *
+ * 作者: ClipVanish Team
+ */
+
+use log::{debug, warn};
+use zeroize::Zeroizing;
+#[cfg(target_os = "linux")]
+use crate::helpers;
+#[cfg(target_os = "macos")]
+use crate::platform::macos::pasteboard::PasteboardSnapshot;
+
+/// 被视为纯文本的已知格式（各平台的格式名称）
+const KNOWN_TEXT_FORMATS: &[&str] = &[
+    // X11
+    "TARGETS",
+    "TIMESTAMP",
+    "MULTIPLE",
+    "SAVE_TARGETS",
+    "UTF8_STRING",
+    "STRING",
+    "TEXT",
+    "COMPOUND_TEXT",
+    "text/plain",
+    "text/plain;charset=utf-8",
+    // macOS
+    "public.utf8-plain-text",
+    "public.utf16-plain-text",
+    "public.utf16-external-plain-text",
+    "NSStringPboardType",
+    // Windows
+    "CF_TEXT",
+    "CF_OEMTEXT",
+    "CF_UNICODETEXT",
+    "CF_LOCALE",
+];
+
+/// 判断格式是否为已知的纯文本格式
+///
+/// # 参数
+/// * `format` - 格式名称
+pub fn is_known_text_format(format: &str) -> bool {
+    KNOWN_TEXT_FORMATS.iter().any(|known| known.eq_ignore_ascii_case(format.trim()))
+}
+
+/// 被隔离的单个格式
+struct QuarantinedFormat {
+    /// 格式名称
+    name: String,
+    /// 格式数据（仅白名单格式保存）
+    data: Zeroizing<Vec<u8>>,
+}
+
+/// 隔离快照
+///
+/// 保存敏感复制发生时剪贴板中的未知格式，白名单之外的格式只记录名称
+pub struct QuarantineSnapshot {
+    /// 检测到的全部未知格式名称
+    unknown_formats: Vec<String>,
+    /// 白名单格式及其数据
+    retained: Vec<QuarantinedFormat>,
+}
+
+impl QuarantineSnapshot {
+    /// 快照当前剪贴板中的未知格式
+    ///
+    /// # 参数
+    /// * `allowlist` - 允许在清除后恢复的格式名称
+    ///
+    /// # 返回值
+    /// * `QuarantineSnapshot` - 隔离快照（无法枚举格式时为空）
+    pub fn capture(allowlist: &[String]) -> Self {
+        let formats = match list_formats() {
+            Ok(formats) => formats,
+            Err(e) => {
+                debug!("无法枚举剪贴板格式: {}", e);
+                Vec::new()
+            }
+        };
+
+        let unknown_formats: Vec<String> = formats
+            .into_iter()
+            .filter(|format| !is_known_text_format(format))
+            .collect();
+
+        let retained = unknown_formats
+            .iter()
+            .filter(|format| allowlist.iter().any(|allowed| allowed == *format))
+            .filter_map(|format| match read_format(format) {
+                Ok(data) => Some(QuarantinedFormat {
+                    name: format.clone(),
+                    data: Zeroizing::new(data),
+                }),
+                Err(e) => {
+                    warn!("读取隔离格式 {} 失败: {}", format, e);
+                    None
+                }
+            })
+            .collect();
+
+        QuarantineSnapshot { unknown_formats, retained }
+    }
+
+    /// 被隔离的未知格式名称
+    pub fn unknown_formats(&self) -> &[String] {
+        &self.unknown_formats
+    }
+
+    /// 将白名单格式恢复到剪贴板
+    ///
+    /// # 返回值
+    /// * `usize` - 成功恢复的格式数量
+    pub fn restore(self) -> usize {
+        if self.retained.is_empty() {
+            return 0;
+        }
+        write_formats(self.retained)
+    }
+}
+
+/// 枚举当前剪贴板中的全部格式
+#[cfg(target_os = "linux")]
+fn list_formats() -> Result<Vec<String>, String> {
+    let output = helpers::output("xclip", &["-selection", "clipboard", "-t", "TARGETS", "-o"])?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// 枚举当前剪贴板中的全部格式
+#[cfg(target_os = "macos")]
+fn list_formats() -> Result<Vec<String>, String> {
+    Ok(PasteboardSnapshot::capture()?.types())
+}
+
+/// 枚举当前剪贴板中的全部格式
+#[cfg(target_os = "windows")]
+fn list_formats() -> Result<Vec<String>, String> {
+    use std::ptr;
+    use winapi::um::winuser::{CloseClipboard, EnumClipboardFormats, GetClipboardFormatNameW, OpenClipboard};
+
+    unsafe {
+        if OpenClipboard(ptr::null_mut()) == 0 {
+            return Err("无法打开剪贴板".to_string());
+        }
+
+        let mut formats = Vec::new();
+        let mut format = EnumClipboardFormats(0);
+        while format != 0 {
+            let name = match format {
+                1 => "CF_TEXT".to_string(),
+                7 => "CF_OEMTEXT".to_string(),
+                13 => "CF_UNICODETEXT".to_string(),
+                16 => "CF_LOCALE".to_string(),
+                _ => {
+                    let mut buffer = [0u16; 256];
+                    let len = GetClipboardFormatNameW(format, buffer.as_mut_ptr(), buffer.len() as i32);
+                    if len > 0 {
+                        String::from_utf16_lossy(&buffer[..len as usize])
+                    } else {
+                        format!("CF_{}", format)
+                    }
+                }
+            };
+            formats.push(name);
+            format = EnumClipboardFormats(format);
+        }
+
+        CloseClipboard();
+        Ok(formats)
+    }
+}
+
+/// 枚举当前剪贴板中的全部格式
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn list_formats() -> Result<Vec<String>, String> {
+    Err("当前平台不支持枚举剪贴板格式".to_string())
+}
+
+/// 读取指定格式的数据
+#[cfg(target_os = "linux")]
+fn read_format(format: &str) -> Result<Vec<u8>, String> {
+    let output = helpers::output("xclip", &["-selection", "clipboard", "-t", format, "-o"])?;
+
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// 读取指定格式的数据
+#[cfg(target_os = "macos")]
+fn read_format(format: &str) -> Result<Vec<u8>, String> {
+    PasteboardSnapshot::capture()?
+        .data(format)
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| "剪贴板中已没有该格式".to_string())
+}
+
+/// 读取指定格式的数据
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn read_format(_format: &str) -> Result<Vec<u8>, String> {
+    Err("当前平台不支持保存隔离格式".to_string())
+}
+
+/// 逐个写回格式
+///
+/// # 返回值
+/// * `usize` - 成功恢复的格式数量
+#[cfg(target_os = "linux")]
+fn write_formats(formats: Vec<QuarantinedFormat>) -> usize {
+    let mut restored = 0;
+    for format in &formats {
+        let result = helpers::run_with_stdin("xclip", &["-selection", "clipboard", "-t", &format.name, "-i"], &format.data)
+            .and_then(|status| if status.success() { Ok(()) } else { Err(format!("xclip退出码: {:?}", status.code())) });
+        match result {
+            Ok(()) => restored += 1,
+            Err(e) => warn!("恢复隔离格式 {} 失败: {}", format.name, e),
+        }
+    }
+    restored
+}
+
+/// 将全部格式作为同一个条目写回
+///
+/// # 返回值
+/// * `usize` - 成功恢复的格式数量
+#[cfg(target_os = "macos")]
+fn write_formats(formats: Vec<QuarantinedFormat>) -> usize {
+    let types = formats.into_iter().map(|format| (format.name, format.data)).collect();
+    match PasteboardSnapshot::single(types).restore() {
+        Ok(restored) => restored,
+        Err(e) => {
+            warn!("恢复隔离格式失败: {}", e);
+            0
+        }
+    }
+}
+
+/// 写回格式（当前平台不支持）
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn write_formats(formats: Vec<QuarantinedFormat>) -> usize {
+    for format in &formats {
+        warn!("恢复隔离格式 {} 失败: 当前平台不支持恢复隔离格式", format.name);
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_text_formats() {
+        assert!(is_known_text_format("UTF8_STRING"));
+        assert!(is_known_text_format("text/plain;charset=utf-8"));
+        assert!(is_known_text_format("CF_UNICODETEXT"));
+        // 富文本同样可能包含敏感内容，不视为已知格式
+        assert!(!is_known_text_format("text/html"));
+        assert!(!is_known_text_format("image/png"));
+        assert!(!is_known_text_format("«class RTF »"));
+    }
+
+    #[test]
+    fn test_empty_snapshot_restores_nothing() {
+        let snapshot = QuarantineSnapshot {
+            unknown_formats: vec!["image/png".to_string()],
+            retained: Vec::new(),
+        };
+        assert_eq!(snapshot.unknown_formats().len(), 1);
+        assert_eq!(snapshot.restore(), 0);
+    }
+}
@@ -0,0 +1,216 @@
+/*!
+ * ClipVanish™ 可疑剪贴板读取者检测模块
+ *
+ * 发现每次剪贴板变化后都立即读取剪贴板的进程（剪贴板窃取程序的典型行为）
+ * 特点：
+ * - Windows：轮询 GetClipboardSequenceNumber 发现剪贴板变化，随后在短时间窗口内采样
+ *   GetOpenClipboardWindow 与剪贴板查看器链首（GetClipboardViewer），记录打开过剪贴板的进程
+ * - 按进程统计自首次出现以来读取的变化比例，达到阈值即告警并记录 `suspicious_reader` 审计事件
+ * - 预热期后首次出现的未知读取者在存在受保护条目时可触发紧急销毁（需显式开启）
+ * - 已知的剪贴板工具（如远程桌面的rdpclip.exe）可加入白名单；本进程与刚写入剪贴板的进程不计入
+ * - 以NULL窗口打开剪贴板的程序无法归属到进程，不在检测范围内
+ * - 仅在Windows上编译；配置（`ReaderWatchConfig`）在config模块中，各平台的配置文件格式一致
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use log::warn;
+use crate::audit;
+use crate::clipboard::ClipboardMonitor;
+use crate::config::ReaderWatchConfig;
+use crate::output::notice;
+
+/// 读取者统计
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReaderStats {
+    /// 进程名
+    pub app: String,
+    /// 读取过的变化次数
+    pub reads: u64,
+    /// 自首次出现以来的变化次数
+    pub changes: u64,
+}
+
+/// 一次剪贴板变化的检测结果
+#[derive(Debug, Default, PartialEq)]
+pub struct Observation {
+    /// 本次新达到阈值的可疑读取者
+    pub suspicious: Vec<ReaderStats>,
+    /// 预热期后首次出现的读取者
+    pub unknown: Vec<String>,
+}
+
+/// 单个进程的读取记录
+#[derive(Debug)]
+struct Record {
+    /// 首次出现时的变化序号
+    first_change: u64,
+    /// 读取过的变化次数
+    reads: u64,
+    /// 是否已经告警
+    flagged: bool,
+}
+
+/// 读取者跟踪
+#[derive(Debug, Default)]
+pub struct ReaderTracker {
+    /// 已观察到的剪贴板变化次数
+    changes: u64,
+    /// 按进程名（小写）的读取记录
+    records: HashMap<String, Record>,
+}
+
+impl ReaderTracker {
+    /// 记录一次剪贴板变化及其后读取过剪贴板的进程
+    ///
+    /// # 参数
+    /// * `config` - 检测配置
+    /// * `readers` - 本次变化后打开过剪贴板的进程名
+    pub fn record(&mut self, config: &ReaderWatchConfig, readers: &BTreeSet<String>) -> Observation {
+        self.changes += 1;
+        let mut observation = Observation::default();
+
+        for app in readers.iter().filter(|app| !config.allows(app)) {
+            let changes = self.changes;
+            let record = self.records.entry(app.to_lowercase()).or_insert_with(|| {
+                if changes > config.min_changes {
+                    observation.unknown.push(app.clone());
+                }
+                Record { first_change: changes, reads: 0, flagged: false }
+            });
+            record.reads += 1;
+
+            let seen = self.changes - record.first_change + 1;
+            if !record.flagged && record.reads >= config.min_changes.max(1) && record.reads as f64 >= seen as f64 * config.read_ratio {
+                record.flagged = true;
+                observation.suspicious.push(ReaderStats { app: app.clone(), reads: record.reads, changes: seen });
+            }
+        }
+        observation
+    }
+}
+
+/// 启动检测线程
+///
+/// # 参数
+/// * `config` - 检测配置
+/// * `monitor` - 剪贴板监听器
+pub fn spawn(config: ReaderWatchConfig, monitor: Arc<ClipboardMonitor>) {
+    std::thread::spawn(move || {
+        let mut tracker = ReaderTracker::default();
+        let mut sequence = windows::sequence_number();
+
+        while !monitor.stop_requested() {
+            std::thread::sleep(Duration::from_millis(20));
+            let current = windows::sequence_number();
+            if current == sequence {
+                continue;
+            }
+            sequence = current;
+
+            let readers = windows::sample_readers(Duration::from_millis(config.window_ms));
+            let observation = tracker.record(&config, &readers);
+
+            for stats in &observation.suspicious {
+                warn!("{} 在 {}/{} 次剪贴板变化后立即读取了剪贴板", stats.app, stats.reads, stats.changes);
+                notice!(
+                    "🕵️ 可疑的剪贴板读取者: {}（{}次变化中读取了{}次），请确认它是否为可信的剪贴板工具",
+                    stats.app,
+                    stats.changes,
+                    stats.reads
+                );
+                audit::record("suspicious_reader", &format!("进程={} 读取={}/{}", stats.app, stats.reads, stats.changes));
+            }
+
+            if !config.auto_nuke_unknown || observation.unknown.is_empty() || !monitor.has_protected_item() {
+                continue;
+            }
+            let apps = observation.unknown.join(", ");
+            warn!("受保护条目存在期间出现未知剪贴板读取者 {}，执行紧急销毁", apps);
+            notice!("🚨 未知进程 {} 读取了剪贴板，已紧急销毁受保护内容", apps);
+            audit::record("suspicious_reader", &format!("未知读取者={} 已自动销毁", apps));
+            if let Err(e) = monitor.emergency_nuke() {
+                warn!("自动紧急销毁失败: {}", e);
+            }
+        }
+    });
+}
+
+/// Windows剪贴板接口
+mod windows {
+    use std::collections::BTreeSet;
+    use std::time::{Duration, Instant};
+    use winapi::um::winuser::{GetClipboardOwner, GetClipboardSequenceNumber, GetClipboardViewer, GetOpenClipboardWindow};
+    use crate::source_app::{process_name, window_process};
+
+    /// 采样间隔
+    const SAMPLE_INTERVAL: Duration = Duration::from_millis(2);
+
+    /// 剪贴板序列号（每次内容变化递增）
+    pub fn sequence_number() -> u32 {
+        unsafe { GetClipboardSequenceNumber() }
+    }
+
+    /// 在时间窗口内采样打开过剪贴板的进程（不含本进程与写入者）
+    ///
+    /// # 参数
+    /// * `window` - 采样时长
+    pub fn sample_readers(window: Duration) -> BTreeSet<String> {
+        let own = std::process::id();
+        let writer = window_process(unsafe { GetClipboardOwner() });
+        let mut pids = BTreeSet::new();
+
+        // 旧式查看器链首会在每次变化时收到WM_DRAWCLIPBOARD
+        pids.extend(window_process(unsafe { GetClipboardViewer() }));
+
+        let deadline = Instant::now() + window;
+        while Instant::now() < deadline {
+            pids.extend(window_process(unsafe { GetOpenClipboardWindow() }));
+            std::thread::sleep(SAMPLE_INTERVAL);
+        }
+
+        pids.into_iter()
+            .filter(|&pid| pid != own && Some(pid) != writer)
+            .filter_map(process_name)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn readers(apps: &[&str]) -> BTreeSet<String> {
+        apps.iter().map(|app| app.to_string()).collect()
+    }
+
+    #[test]
+    fn test_flags_reader_of_every_change_once() {
+        let config = ReaderWatchConfig { min_changes: 3, ..ReaderWatchConfig::default() };
+        let mut tracker = ReaderTracker::default();
+
+        // 偶尔读取的进程与白名单中的进程不会被标记
+        let observation = tracker.record(&config, &readers(&["stealer.exe", "notepad.exe", "rdpclip.exe"]));
+        assert_eq!(observation, Observation::default());
+        assert_eq!(tracker.record(&config, &readers(&["stealer.exe"])), Observation::default());
+        let observation = tracker.record(&config, &readers(&["stealer.exe", "RDPCLIP.EXE"]));
+        assert_eq!(observation.suspicious, vec![ReaderStats { app: "stealer.exe".to_string(), reads: 3, changes: 3 }]);
+        assert!(tracker.record(&config, &readers(&["stealer.exe"])).suspicious.is_empty());
+        assert!(tracker.record(&config, &readers(&["notepad.exe"])).suspicious.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_readers_after_warmup() {
+        let config = ReaderWatchConfig { min_changes: 2, ..ReaderWatchConfig::default() };
+        let mut tracker = ReaderTracker::default();
+
+        assert!(tracker.record(&config, &readers(&["ditto.exe"])).unknown.is_empty());
+        assert!(tracker.record(&config, &readers(&[])).unknown.is_empty());
+        let observation = tracker.record(&config, &readers(&["ditto.exe", "new.exe"]));
+        assert_eq!(observation.unknown, vec!["new.exe".to_string()]);
+        assert!(tracker.record(&config, &readers(&["new.exe"])).unknown.is_empty());
+    }
+}
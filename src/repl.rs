@@ -0,0 +1,426 @@
+/*!
+ * ClipVanish™ 交互模式行编辑模块
+ *
+ * 基于rustyline为交互模式提供命令历史、补全与历史搜索
+ * 特点：
+ * - 行内编辑：←/→ 移动光标，Home/End（Ctrl+A/E），Ctrl+U/K/W 删除，Ctrl+C 放弃当前输入，空行上 Ctrl+D 退出
+ * - ↑/↓ 浏览历史，Ctrl+R 增量搜索历史
+ * - Tab 补全命令、子命令、参数以及格式名称、令牌名称、配置备份编号等取值
+ * - 历史只记录命令：含剪贴板内容、笔记内容或凭据的命令（put、note、--code）以及以空格开头的行从不记录
+ * - 可选（ui.repl_history）将历史保存到配置目录，文件权限0600
+ * - 标准输入或标准输出不是终端时退回逐行读取，提示符写入stderr
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use log::debug;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{CompletionType, Context, Editor, Helper};
+use crate::config::Config;
+use crate::formats;
+#[cfg(feature = "api")]
+use crate::ipc_auth::TokenStore;
+
+/// 历史记录保留的最大条数
+const MAX_HISTORY: usize = 1000;
+
+/// 历史文件名（位于配置目录）
+const HISTORY_FILE: &str = "repl_history";
+
+/// 参数中带有剪贴板内容、笔记内容或凭据的命令，不记入历史
+const SENSITIVE_COMMANDS: &[&str] = &["put", "note"];
+
+/// 取值为一次性验证码的参数，所在的命令不记入历史
+const SENSITIVE_FLAGS: &[&str] = &["--code"];
+
+/// 交互命令的补全信息
+struct CommandSpec {
+    /// 命令名称
+    name: &'static str,
+    /// 子命令（只在命令后的第一个位置补全）
+    subcommands: &'static [&'static str],
+    /// 参数
+    flags: &'static [&'static str],
+}
+
+const fn spec(name: &'static str, subcommands: &'static [&'static str], flags: &'static [&'static str]) -> CommandSpec {
+    CommandSpec { name, subcommands, flags }
+}
+
+/// 交互模式支持的命令（与 parse_interactive_command 保持一致）
+const COMMANDS: &[CommandSpec] = &[
+    spec("start", &[], &["--timer", "--daemon"]),
+    spec(
+        "nuke",
+        &[],
+        &["--force", "--code", "--at", "--in", "--cancel", "--clipboard-only", "--history-only", "--keys-only", "--slots", "--older-than"],
+    ),
+    spec("undo", &[], &[]),
+    spec("unprotect", &[], &[]),
+    spec("extend", &[], &["--item"]),
+    spec("learned", &[], &["--reset"]),
+    spec(
+        "rules",
+        &["export", "import", "keygen"],
+        &["--promote", "--name", "--sign-key", "--allow-unsigned", "--trust", "--replace", "--output"],
+    ),
+    spec("get", &[], &[]),
+    spec("put", &[], &[]),
+    spec("note", &["create", "read"], &["--ttl"]),
+    spec("status", &[], &["--tmux"]),
+    spec("health", &[], &[]),
+    spec("stop", &[], &[]),
+    spec("history", &[], &[]),
+    spec("doctor", &[], &[]),
+    spec("report", &[], &["--output"]),
+    spec("export-forensics", &[], &["--verify", "--key", "--public-key"]),
+    spec("config", &["backups", "restore"], &["--reset", "--backup"]),
+    spec("soak", &[], &["--hours"]),
+    spec("redteam", &[], &["--seconds"]),
+    spec("stats", &["report", "share"], &["--html", "--weeks", "--epsilon", "--days", "--output"]),
+    spec("canary", &["generate", "rotate", "list", "check"], &[]),
+    spec("audit", &["export"], &["--from", "--to", "--format", "--output"]),
+    spec("formats", &["list", "dump-schema"], &[]),
+    spec("backup", &["create", "restore"], &["--include-notes"]),
+    spec("escrow", &["keygen", "decrypt"], &["--output", "--private-key"]),
+    spec(
+        "guard",
+        if cfg!(feature = "hardware-keys") { &["setup-totp", "enroll-key"] } else { &["setup-totp"] },
+        if cfg!(feature = "hardware-keys") { &["--slot"] } else { &[] },
+    ),
+    spec("token", &["list", "create", "revoke"], &["--capability"]),
+    spec("help", &[], &[]),
+    spec("exit", &[], &[]),
+];
+
+/// 当前构建可用的命令
+fn commands() -> impl Iterator<Item = &'static CommandSpec> {
+    COMMANDS.iter().filter(|spec| spec.name != "token" || cfg!(feature = "api"))
+}
+
+/// 命令行是否可以记入历史
+///
+/// # 参数
+/// * `line` - 输入的命令行（未去除首尾空白）
+pub fn is_recordable(line: &str) -> bool {
+    if line.starts_with(char::is_whitespace) {
+        return false;
+    }
+    let mut words = line.split_whitespace();
+    match words.next() {
+        None => false,
+        Some(command) if SENSITIVE_COMMANDS.contains(&command) => false,
+        Some(_) => !words.any(|word| SENSITIVE_FLAGS.iter().any(|flag| word == *flag || word.starts_with(&format!("{}=", flag)))),
+    }
+}
+
+/// 补全光标前的文本
+///
+/// # 参数
+/// * `line` - 光标前的文本
+///
+/// # 返回值
+/// * `(usize, Vec<String>)` - 待补全单词的起始位置（字节）与候选项
+pub fn complete(line: &str) -> (usize, Vec<String>) {
+    let start = line.rfind(char::is_whitespace).map(|i| i + line[i..].chars().next().map_or(1, char::len_utf8)).unwrap_or(0);
+    let current = &line[start..];
+    let words: Vec<&str> = line[..start].split_whitespace().collect();
+
+    let mut candidates: Vec<String> = match words.as_slice() {
+        [] => commands().map(|spec| spec.name.to_string()).collect(),
+        [command, rest @ ..] => match commands().find(|spec| spec.name == *command) {
+            None => Vec::new(),
+            Some(spec) => match values(command, rest) {
+                Some(values) => values,
+                None => {
+                    let subcommands = if rest.is_empty() { spec.subcommands } else { &[] };
+                    subcommands
+                        .iter()
+                        .chain(spec.flags.iter())
+                        .filter(|word| !rest.contains(word))
+                        .map(|word| word.to_string())
+                        .collect()
+                }
+            },
+        },
+    };
+    candidates.retain(|candidate| candidate.starts_with(current));
+    candidates.sort();
+    candidates.dedup();
+    (start, candidates)
+}
+
+/// 参数取值的补全（当前位置不是取值时返回None）
+///
+/// # 参数
+/// * `command` - 命令名称
+/// * `rest` - 命令后已输入完整的单词
+fn values(command: &str, rest: &[&str]) -> Option<Vec<String>> {
+    let owned = |values: &[&str]| values.iter().map(|value| value.to_string()).collect();
+    match (command, rest) {
+        ("formats", ["dump-schema"]) => Some(formats::FORMATS.iter().map(|format| format.name.to_string()).collect()),
+        ("token", ["revoke"]) => Some(token_names()),
+        ("config", ["restore", "--backup"]) => Some(backup_numbers()),
+        ("audit", [.., "--format"]) => Some(owned(&["jsonl", "csv"])),
+        ("guard", [.., "--slot"]) => Some(owned(&["1", "2"])),
+        ("token", [.., "--capability"]) => Some(owned(&["status", "control", "content"])),
+        _ => None,
+    }
+}
+
+/// 已创建的控制接口令牌名称
+#[cfg(feature = "api")]
+fn token_names() -> Vec<String> {
+    TokenStore::default_path()
+        .and_then(TokenStore::load)
+        .map(|store| store.entries().map(|(name, _)| name.to_string()).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(not(feature = "api"))]
+fn token_names() -> Vec<String> {
+    Vec::new()
+}
+
+/// 现有配置备份的编号（1为最新）
+fn backup_numbers() -> Vec<String> {
+    Config::list_backups()
+        .map(|backups| (1..=backups.len()).map(|number| number.to_string()).collect())
+        .unwrap_or_default()
+}
+
+/// 命令历史
+#[derive(Debug, Default)]
+pub struct History {
+    /// 历史命令（从旧到新）
+    entries: Vec<String>,
+    /// 保存历史的文件，为None时只在本次会话中保留
+    path: Option<PathBuf>,
+}
+
+impl History {
+    /// 创建历史，指定文件时加载其中的记录
+    ///
+    /// # 参数
+    /// * `path` - 历史文件路径，为None时不保存
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let entries = path
+            .as_deref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|content| content.lines().filter(|line| is_recordable(line)).map(str::to_string).collect::<Vec<_>>())
+            .unwrap_or_default();
+        let skip = entries.len().saturating_sub(MAX_HISTORY);
+        History { entries: entries.into_iter().skip(skip).collect(), path }
+    }
+
+    /// 记录一条命令（不可记录或与上一条相同时忽略）
+    ///
+    /// # 参数
+    /// * `line` - 输入的命令行
+    ///
+    /// # 返回值
+    /// * `bool` - 是否已记录
+    pub fn add(&mut self, line: &str) -> bool {
+        if !is_recordable(line) || self.entries.last().map(String::as_str) == Some(line.trim_end()) {
+            return false;
+        }
+        self.entries.push(line.trim_end().to_string());
+        if self.entries.len() > MAX_HISTORY {
+            self.entries.remove(0);
+        }
+        if let Some(path) = &self.path {
+            if let Err(e) = save(path, &self.entries) {
+                debug!("无法保存命令历史 {:?}: {}", path, e);
+            }
+        }
+        true
+    }
+}
+
+/// 覆盖写入历史文件（仅当前用户可读写）
+fn save(path: &Path, entries: &[String]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut content = entries.join("\n");
+    content.push('\n');
+    options.open(path)?.write_all(content.as_bytes())
+}
+
+/// 交互命令的补全（rustyline辅助功能中只实现补全）
+struct ReplHelper;
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+        let (start, mut candidates) = complete(&line[..pos]);
+        // 唯一的候选项补全后接一个空格，便于继续输入
+        if let [only] = candidates.as_mut_slice() {
+            only.push(' ');
+        }
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+/// 交互模式的行编辑器
+pub struct LineEditor {
+    /// 命令历史（决定哪些命令记录与保存）
+    history: History,
+    /// 终端行编辑器，标准输入输出不是终端或初始化失败时为None
+    editor: Option<Editor<ReplHelper, DefaultHistory>>,
+}
+
+impl LineEditor {
+    /// 创建行编辑器
+    ///
+    /// # 参数
+    /// * `persist` - 是否将历史保存到配置目录（ui.repl_history）
+    pub fn new(persist: bool) -> Self {
+        let path = if persist {
+            Config::get_config_directory().ok().map(|dir| dir.join(HISTORY_FILE))
+        } else {
+            None
+        };
+        let history = History::load(path);
+        let editor = if io::stdin().is_terminal() && io::stdout().is_terminal() {
+            Self::terminal_editor(&history)
+                .map_err(|e| debug!("无法初始化行编辑器，改为逐行读取: {}", e))
+                .ok()
+        } else {
+            None
+        };
+        LineEditor { history, editor }
+    }
+
+    /// 创建rustyline编辑器并载入已有的历史
+    fn terminal_editor(history: &History) -> rustyline::Result<Editor<ReplHelper, DefaultHistory>> {
+        let config = rustyline::Config::builder()
+            .max_history_size(MAX_HISTORY)?
+            .history_ignore_dups(true)?
+            .history_ignore_space(true)
+            .completion_type(CompletionType::List)
+            .build();
+        let mut editor = Editor::with_config(config)?;
+        editor.set_helper(Some(ReplHelper));
+        for entry in &history.entries {
+            editor.add_history_entry(entry.as_str())?;
+        }
+        Ok(editor)
+    }
+
+    /// 记录一条已执行的命令
+    pub fn add_history(&mut self, line: &str) {
+        if !self.history.add(line) {
+            return;
+        }
+        if let Some(editor) = self.editor.as_mut() {
+            if let Err(e) = editor.add_history_entry(line.trim_end()) {
+                debug!("无法记录命令历史: {}", e);
+            }
+        }
+    }
+
+    /// 读取一行输入
+    ///
+    /// # 参数
+    /// * `prompt` - 提示符
+    ///
+    /// # 返回值
+    /// * `io::Result<Option<String>>` - 输入结束时返回None
+    pub fn read_line(&mut self, prompt: &str) -> io::Result<Option<String>> {
+        let Some(editor) = self.editor.as_mut() else {
+            eprint!("{}", prompt);
+            io::stderr().flush()?;
+            let mut input = String::new();
+            return match io::stdin().lock().read_line(&mut input)? {
+                0 => Ok(None),
+                _ => Ok(Some(input)),
+            };
+        };
+
+        loop {
+            match editor.readline(prompt) {
+                Ok(line) => return Ok(Some(line)),
+                // Ctrl+C 放弃当前输入，重新显示提示符
+                Err(ReadlineError::Interrupted) => continue,
+                Err(ReadlineError::Eof) => return Ok(None),
+                Err(ReadlineError::Io(e)) => return Err(e),
+                Err(e) => return Err(io::Error::other(e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_complete() {
+        assert_eq!(complete("ex"), (0, vec!["exit".to_string(), "export-forensics".to_string(), "extend".to_string()]));
+        assert_eq!(complete("formats "), (8, vec!["dump-schema".to_string(), "list".to_string()]));
+        assert_eq!(complete("formats dump-schema ca"), (20, vec!["canaries".to_string()]));
+        assert_eq!(complete("audit export --format c"), (22, vec!["csv".to_string()]));
+        assert_eq!(complete("nuke --force --c"), (13, vec!["--cancel".to_string(), "--clipboard-only".to_string(), "--code".to_string()]));
+        assert_eq!(complete("backup create out.json --"), (23, vec!["--include-notes".to_string()]));
+        assert!(complete("unknown ").1.is_empty());
+    }
+
+    #[test]
+    fn test_history_skips_sensitive_and_persists() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(HISTORY_FILE);
+
+        let mut history = History::load(Some(path.clone()));
+        for line in ["status", "status", "put hunter2", "note read 01HQX.00ff", "nuke --code 123456", " doctor", "extend 2m"] {
+            history.add(line);
+        }
+        assert_eq!(history.entries, vec!["status", "extend 2m"]);
+
+        let reloaded = History::load(Some(path.clone()));
+        assert_eq!(reloaded.entries, history.entries);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "status\nextend 2m\n");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_helper_completes_with_trailing_space() {
+        let history = DefaultHistory::new();
+        let ctx = Context::new(&history);
+        assert_eq!(ReplHelper.complete("status do", 9, &ctx).unwrap(), (7, Vec::<String>::new()));
+        assert_eq!(ReplHelper.complete("do", 2, &ctx).unwrap(), (0, vec!["doctor ".to_string()]));
+        assert_eq!(ReplHelper.complete("formats du", 10, &ctx).unwrap(), (8, vec!["dump-schema ".to_string()]));
+        assert_eq!(ReplHelper.complete("ex", 2, &ctx).unwrap().1.len(), 3);
+    }
+}
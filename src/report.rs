@@ -0,0 +1,429 @@
+/*!
+ * ClipVanish™ 问题报告模块
+ *
+ * 收集脱敏后的诊断信息并打包为单个归档文件，便于附加到Issue中
+ * 特点：
+ * - 按章节收集（版本、配置、日志、诊断、崩溃记录）
+ * - 配置中的敏感字段自动遮蔽
+ * - 日志中引号包裹的内容自动脱敏
+ * - 输出标准tar归档，无需额外依赖
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::fs;
+use std::path::Path;
+use log::debug;
+use crate::build_info::BuildInfo;
+use crate::config::Config;
+use crate::doctor;
+use crate::platform;
+
+/// 日志章节最多包含的行数
+const MAX_LOG_LINES: usize = 200;
+
+/// 遮蔽后的占位文本
+const REDACTED: &str = "[REDACTED]";
+
+/// 被视为敏感信息的配置键名片段
+const SECRET_KEY_MARKERS: &[&str] = &[
+    "pattern",
+    "secret",
+    "token",
+    "password",
+    "passphrase",
+];
+
+/// 报告错误类型
+#[derive(Debug)]
+pub enum ReportError {
+    /// 章节内容收集失败
+    CollectFailed(String),
+    /// 归档写入失败
+    WriteFailed(std::io::Error),
+}
+
+impl std::fmt::Display for ReportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportError::CollectFailed(msg) => write!(f, "报告内容收集失败: {}", msg),
+            ReportError::WriteFailed(e) => write!(f, "报告写入失败: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ReportError {}
+
+/// 报告章节
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportSection {
+    /// 版本与系统信息
+    Version,
+    /// 脱敏后的配置
+    Config,
+    /// 最近日志
+    Logs,
+    /// 环境诊断结果
+    Doctor,
+    /// 最近一次崩溃记录
+    Panic,
+}
+
+impl ReportSection {
+    /// 全部章节（按归档顺序）
+    pub const ALL: [ReportSection; 5] = [
+        ReportSection::Version,
+        ReportSection::Config,
+        ReportSection::Logs,
+        ReportSection::Doctor,
+        ReportSection::Panic,
+    ];
+
+    /// 章节说明（用于征求用户同意）
+    pub fn title(&self) -> &'static str {
+        match self {
+            ReportSection::Version => "版本与操作系统信息",
+            ReportSection::Config => "配置文件（敏感字段已遮蔽）",
+            ReportSection::Logs => "最近日志（引号内容已脱敏）",
+            ReportSection::Doctor => "环境诊断结果",
+            ReportSection::Panic => "最近一次崩溃记录",
+        }
+    }
+
+    /// 章节在归档中的文件名
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            ReportSection::Version => "version.txt",
+            ReportSection::Config => "config.json",
+            ReportSection::Logs => "recent.log",
+            ReportSection::Doctor => "doctor.txt",
+            ReportSection::Panic => "last_panic.txt",
+        }
+    }
+
+    /// 收集章节内容
+    ///
+    /// # 参数
+    /// * `config` - 当前配置
+    ///
+    /// # 返回值
+    /// * `Result<Option<String>, ReportError>` - 章节内容，无可用数据时返回None
+    pub fn collect(&self, config: &Config) -> Result<Option<String>, ReportError> {
+        match self {
+            ReportSection::Version => Ok(Some(version_info())),
+            ReportSection::Config => {
+                let mut value = serde_json::to_value(config)
+                    .map_err(|e| ReportError::CollectFailed(e.to_string()))?;
+                mask_secrets(&mut value);
+                serde_json::to_string_pretty(&value)
+                    .map(Some)
+                    .map_err(|e| ReportError::CollectFailed(e.to_string()))
+            },
+            ReportSection::Logs => {
+                let path = Config::get_log_file_path()
+                    .map_err(|e| ReportError::CollectFailed(e.to_string()))?;
+                Ok(fs::read_to_string(path).ok().map(|content| {
+                    let lines: Vec<&str> = content.lines().collect();
+                    let start = lines.len().saturating_sub(MAX_LOG_LINES);
+                    lines[start..]
+                        .iter()
+                        .map(|line| redact_log_line(line))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                }))
+            },
+            ReportSection::Doctor => {
+                let lines: Vec<String> = doctor::run_checks(config)
+                    .iter()
+                    .map(|check| check.to_string())
+                    .collect();
+                Ok(Some(lines.join("\n")))
+            },
+            ReportSection::Panic => {
+                let path = Config::get_panic_file_path()
+                    .map_err(|e| ReportError::CollectFailed(e.to_string()))?;
+                Ok(fs::read_to_string(path).ok())
+            },
+        }
+    }
+}
+
+/// 版本与系统信息
+fn version_info() -> String {
+    format!(
+        "{}\nOS: {}\nArch: {}\nFamily: {}",
+        BuildInfo::current(),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        std::env::consts::FAMILY,
+    )
+}
+
+/// 遮蔽配置中的敏感字段
+///
+/// 递归处理JSON对象，键名包含敏感片段的字符串值会被替换为占位文本
+///
+/// # 参数
+/// * `value` - 配置的JSON表示
+pub fn mask_secrets(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                let key_lower = key.to_lowercase();
+                let is_secret = SECRET_KEY_MARKERS.iter().any(|marker| key_lower.contains(marker));
+                if is_secret && (child.is_string() || child.is_array()) {
+                    *child = serde_json::Value::String(REDACTED.to_string());
+                } else {
+                    mask_secrets(child);
+                }
+            }
+        },
+        serde_json::Value::Array(items) => {
+            for item in items.iter_mut() {
+                mask_secrets(item);
+            }
+        },
+        _ => {},
+    }
+}
+
+/// 脱敏单行日志，将引号包裹的内容替换为占位文本
+///
+/// 引号未闭合时（例如日志在内容中途被截断），从该引号到行尾都视为敏感内容
+///
+/// # 参数
+/// * `line` - 原始日志行
+///
+/// # 返回值
+/// * `String` - 脱敏后的日志行
+pub fn redact_log_line(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut in_quotes = false;
+
+    for ch in line.chars() {
+        if ch == '"' {
+            if in_quotes {
+                result.push_str(REDACTED);
+            }
+            in_quotes = !in_quotes;
+            result.push(ch);
+        } else if !in_quotes {
+            result.push(ch);
+        }
+    }
+
+    if in_quotes {
+        result.push_str(REDACTED);
+    }
+
+    result
+}
+
+/// 极简tar归档写入器（ustar格式）
+pub struct TarWriter {
+    /// 归档数据
+    buffer: Vec<u8>,
+    /// 文件修改时间（Unix时间戳）
+    mtime: u64,
+}
+
+impl TarWriter {
+    /// 创建新的归档写入器
+    pub fn new() -> Self {
+        let mtime = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        TarWriter {
+            buffer: Vec::new(),
+            mtime,
+        }
+    }
+
+    /// 添加一个文件
+    ///
+    /// # 参数
+    /// * `name` - 归档内文件名（不超过99字节）
+    /// * `data` - 文件内容
+    pub fn append_file(&mut self, name: &str, data: &[u8]) {
+        let mut header = [0u8; 512];
+
+        let name_bytes = name.as_bytes();
+        let name_len = name_bytes.len().min(99);
+        header[..name_len].copy_from_slice(&name_bytes[..name_len]);
+
+        Self::write_octal(&mut header[100..108], 0o600);
+        Self::write_octal(&mut header[108..116], 0);
+        Self::write_octal(&mut header[116..124], 0);
+        Self::write_octal(&mut header[124..136], data.len() as u64);
+        Self::write_octal(&mut header[136..148], self.mtime);
+        header[156] = b'0';
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[263..265].copy_from_slice(b"00");
+
+        // 校验和计算时校验和字段视为空格
+        header[148..156].copy_from_slice(b"        ");
+        let checksum: u64 = header.iter().map(|&b| b as u64).sum();
+        Self::write_octal(&mut header[148..155], checksum);
+        header[155] = b' ';
+
+        self.buffer.extend_from_slice(&header);
+        self.buffer.extend_from_slice(data);
+
+        let padding = (512 - data.len() % 512) % 512;
+        self.buffer.extend(std::iter::repeat_n(0u8, padding));
+    }
+
+    /// 完成归档并返回数据
+    pub fn finish(mut self) -> Vec<u8> {
+        // 归档结尾为两个全零块
+        self.buffer.extend(std::iter::repeat_n(0u8, 1024));
+        self.buffer
+    }
+
+    /// 以零填充的八进制写入字段（最后一字节为NUL）
+    fn write_octal(field: &mut [u8], value: u64) {
+        let width = field.len() - 1;
+        let text = format!("{:0width$o}", value, width = width);
+        let bytes = text.as_bytes();
+        let start = bytes.len().saturating_sub(width);
+        field[..width].copy_from_slice(&bytes[start..]);
+        field[width] = 0;
+    }
+}
+
+impl Default for TarWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 读取TarWriter写出的归档
+///
+/// # 参数
+/// * `data` - 归档数据
+///
+/// # 返回值
+/// * `Option<Vec<(String, Vec<u8>)>>` - 按顺序排列的文件名与内容，格式错误时返回None
+pub fn read_tar(data: &[u8]) -> Option<Vec<(String, Vec<u8>)>> {
+    let mut entries = Vec::new();
+    let mut offset = 0;
+
+    while offset + 512 <= data.len() {
+        let header = &data[offset..offset + 512];
+        if header.iter().all(|&b| b == 0) {
+            return Some(entries);
+        }
+
+        let name_len = header[..100].iter().position(|&b| b == 0).unwrap_or(100);
+        let name = String::from_utf8(header[..name_len].to_vec()).ok()?;
+        let size_field = std::str::from_utf8(&header[124..136]).ok()?;
+        let size = usize::from_str_radix(size_field.trim_matches(|c: char| c == '\0' || c == ' '), 8).ok()?;
+
+        let start = offset + 512;
+        let end = start.checked_add(size)?;
+        entries.push((name, data.get(start..end)?.to_vec()));
+        offset = start + size.div_ceil(512) * 512;
+    }
+
+    None
+}
+
+/// 将已收集的章节写入归档文件
+///
+/// # 参数
+/// * `path` - 输出文件路径
+/// * `sections` - 章节及其内容
+///
+/// # 返回值
+/// * `Result<(), ReportError>` - 操作结果
+pub fn write_report<P: AsRef<Path>>(path: P, sections: &[(ReportSection, String)]) -> Result<(), ReportError> {
+    let mut tar = TarWriter::new();
+    for (section, content) in sections {
+        tar.append_file(section.file_name(), content.as_bytes());
+    }
+
+    platform::write_private(path.as_ref(), &tar.finish()).map_err(ReportError::WriteFailed)?;
+
+    debug!("问题报告已写入: {:?}", path.as_ref());
+    Ok(())
+}
+
+/// 生成默认的报告文件名
+pub fn default_report_file_name() -> String {
+    format!("clipvanish-report-{}.tar", chrono::Local::now().format("%Y%m%d-%H%M%S"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_mask_secrets() {
+        let mut config = Config::default();
+        config.security.sensitive_pattern = "(?i)password".to_string();
+
+        let mut value = serde_json::to_value(&config).unwrap();
+        mask_secrets(&mut value);
+
+        assert_eq!(value["security"]["sensitive_pattern"], REDACTED);
+        assert_eq!(value["sensitive_pattern"], REDACTED);
+        // 非敏感字段保持不变
+        assert_eq!(value["timer"]["default_countdown"], 30);
+    }
+
+    #[test]
+    fn test_redact_log_line() {
+        assert_eq!(
+            redact_log_line("检测到敏感内容复制: \"hunter2\" 长度 7"),
+            "检测到敏感内容复制: \"[REDACTED]\" 长度 7"
+        );
+        assert_eq!(redact_log_line("无引号内容"), "无引号内容");
+
+        // 引号未闭合时到行尾的内容都被遮蔽
+        assert_eq!(
+            redact_log_line("复制内容: \"hunter2 长度 7"),
+            "复制内容: \"[REDACTED]"
+        );
+        assert_eq!(
+            redact_log_line("\"a\" 与 \"b"),
+            "\"[REDACTED]\" 与 \"[REDACTED]"
+        );
+    }
+
+    #[test]
+    fn test_tar_layout() {
+        let mut tar = TarWriter::new();
+        tar.append_file("a.txt", b"hello");
+        let data = tar.finish();
+
+        // 头部 + 一个数据块 + 两个结尾块
+        assert_eq!(data.len(), 512 * 4);
+        assert_eq!(&data[..5], b"a.txt");
+        assert_eq!(&data[257..262], b"ustar");
+        assert_eq!(&data[512..517], b"hello");
+        assert_eq!(read_tar(&data).unwrap(), vec![("a.txt".to_string(), b"hello".to_vec())]);
+        assert!(read_tar(&data[..512]).is_none());
+    }
+
+    #[test]
+    fn test_write_report() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("report.tar");
+
+        let sections = vec![(ReportSection::Version, version_info())];
+        write_report(&path, &sections).unwrap();
+
+        let data = fs::read(&path).unwrap();
+        assert_eq!(data.len() % 512, 0);
+        assert_eq!(&data[..11], b"version.txt");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+    }
+}
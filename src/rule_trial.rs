@@ -0,0 +1,298 @@
+/*!
+ * ClipVanish™ 规则试运行模块
+ *
+ * 新启用或修改过的检测规则先进入"仅警告"阶段：命中时只记录并提示，不拦截，避免误报清除剪贴板
+ * 特点：
+ * - 达到配置的天数或命中次数（先到者为准）后自动转为强制执行，也可随时手动提前转正
+ * - 首次使用时已启用的规则直接视为强制执行，升级不会削弱现有保护
+ * - 规则按配置指纹识别，修改模式或阈值后重新进入试运行
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use crate::config::Config;
+use crate::platform;
+
+/// 规则试运行配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleTrialConfig {
+    /// 新启用或修改的规则是否先进入仅警告阶段
+    #[serde(default = "default_trial_enabled")]
+    pub enabled: bool,
+    /// 仅警告阶段最长持续天数
+    #[serde(default = "default_trial_days")]
+    pub days: u32,
+    /// 仅警告阶段最多命中次数
+    #[serde(default = "default_trial_matches")]
+    pub matches: u32,
+}
+
+fn default_trial_enabled() -> bool {
+    true
+}
+
+fn default_trial_days() -> u32 {
+    7
+}
+
+fn default_trial_matches() -> u32 {
+    20
+}
+
+impl Default for RuleTrialConfig {
+    fn default() -> Self {
+        RuleTrialConfig {
+            enabled: default_trial_enabled(),
+            days: default_trial_days(),
+            matches: default_trial_matches(),
+        }
+    }
+}
+
+/// 规则所处阶段
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RuleStage {
+    /// 仅警告：记录并提示，不拦截
+    Warn,
+    /// 强制执行
+    Enforce,
+}
+
+impl std::fmt::Display for RuleStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuleStage::Warn => write!(f, "仅警告"),
+            RuleStage::Enforce => write!(f, "强制执行"),
+        }
+    }
+}
+
+/// 单条规则的试运行记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleTrial {
+    /// 规则名称（与匹配信息中的规则一致）
+    pub rule: String,
+    /// 规则配置指纹，变化时重新进入试运行
+    pub fingerprint: String,
+    /// 试运行开始时间
+    pub started_at: DateTime<Utc>,
+    /// 仅警告阶段的命中次数
+    pub matches: u32,
+    /// 是否已转为强制执行（手动转正或首次使用时已启用）
+    pub promoted: bool,
+}
+
+impl RuleTrial {
+    /// 当前阶段
+    ///
+    /// # 参数
+    /// * `config` - 试运行配置
+    /// * `now` - 当前时间
+    pub fn stage(&self, config: &RuleTrialConfig, now: DateTime<Utc>) -> RuleStage {
+        let expired = now - self.started_at >= chrono::Duration::days(config.days as i64);
+        if !config.enabled || self.promoted || self.matches >= config.matches || expired {
+            RuleStage::Enforce
+        } else {
+            RuleStage::Warn
+        }
+    }
+}
+
+/// 规则试运行状态
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TrialStore {
+    /// 全部规则记录（规则停用后保留，重新启用相同配置时不会再次试运行）
+    rules: Vec<RuleTrial>,
+}
+
+impl TrialStore {
+    /// 默认存储路径
+    pub fn default_path() -> Option<PathBuf> {
+        Config::get_config_directory()
+            .ok()
+            .map(|dir| dir.join("rule_trials.json"))
+    }
+
+    /// 从文件加载（文件不存在或损坏时返回None）
+    pub fn load<P: AsRef<Path>>(path: P) -> Option<Self> {
+        platform::read_state_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+    }
+
+    /// 保存到文件
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(std::io::Error::from)?;
+        platform::write_state(path, content.as_bytes())
+    }
+
+    /// 登记当前启用的规则
+    ///
+    /// # 参数
+    /// * `active` - 启用的规则及其配置指纹
+    /// * `baseline` - 是否为首次使用（此时的规则直接视为强制执行）
+    /// * `now` - 当前时间
+    ///
+    /// # 返回值
+    /// * `Vec<String>` - 本次进入试运行的规则
+    pub fn sync(&mut self, active: &[(String, String)], baseline: bool, now: DateTime<Utc>) -> Vec<String> {
+        let mut started = Vec::new();
+        for (rule, fingerprint) in active {
+            let fresh = RuleTrial {
+                rule: rule.clone(),
+                fingerprint: fingerprint.clone(),
+                started_at: now,
+                matches: 0,
+                promoted: baseline,
+            };
+            match self.rules.iter_mut().find(|trial| &trial.rule == rule) {
+                Some(trial) if &trial.fingerprint == fingerprint => continue,
+                Some(trial) => *trial = fresh,
+                None => self.rules.push(fresh),
+            }
+            if !baseline {
+                started.push(rule.clone());
+            }
+        }
+        started
+    }
+
+    /// 记录一次命中：规则处于仅警告阶段时累计命中次数并返回记录
+    ///
+    /// # 参数
+    /// * `rule` - 命中的规则
+    /// * `config` - 试运行配置
+    /// * `now` - 当前时间
+    pub fn record_warning(&mut self, rule: &str, config: &RuleTrialConfig, now: DateTime<Utc>) -> Option<&RuleTrial> {
+        let trial = self.rules
+            .iter_mut()
+            .find(|trial| trial.rule == rule && trial.stage(config, now) == RuleStage::Warn)?;
+        trial.matches += 1;
+        Some(trial)
+    }
+
+    /// 将规则转为强制执行
+    ///
+    /// # 返回值
+    /// * `bool` - 规则是否存在
+    pub fn promote(&mut self, rule: &str) -> bool {
+        match self.rules.iter_mut().find(|trial| trial.rule == rule) {
+            Some(trial) => {
+                trial.promoted = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 全部记录
+    pub fn trials(&self) -> &[RuleTrial] {
+        &self.rules
+    }
+}
+
+/// 当前启用的规则及其配置指纹
+pub fn active_rules(config: &Config) -> Vec<(String, String)> {
+    let mut rules = Vec::new();
+    if !config.sensitive_pattern.is_empty() {
+        rules.push(("sensitive_pattern".to_string(), config.sensitive_pattern.clone()));
+    }
+    if config.entropy_rule.enabled {
+        let fingerprint = serde_json::to_string(&config.entropy_rule).unwrap_or_default();
+        rules.push(("entropy_rule".to_string(), fingerprint));
+    }
+    rules
+}
+
+/// 按配置登记启用的规则（服务启动与重新加载配置时调用），失败时仅记录警告
+pub fn sync(config: &Config) {
+    let Some(path) = TrialStore::default_path() else {
+        return;
+    };
+    let existing = TrialStore::load(&path);
+    let baseline = existing.is_none();
+    let mut store = existing.unwrap_or_default();
+
+    for rule in store.sync(&active_rules(config), baseline, Utc::now()) {
+        if config.rule_trial.enabled {
+            info!("规则 {} 为新启用或已修改，进入仅警告阶段（{} 天或 {} 次命中）", rule, config.rule_trial.days, config.rule_trial.matches);
+        }
+    }
+    if let Err(e) = store.save(&path) {
+        warn!("保存规则试运行状态失败: {}", e);
+    }
+}
+
+/// 将规则转为强制执行
+///
+/// # 参数
+/// * `rule` - 规则名称
+///
+/// # 返回值
+/// * `std::io::Result<bool>` - 规则是否存在
+pub fn promote(rule: &str) -> std::io::Result<bool> {
+    let Some(path) = TrialStore::default_path() else {
+        return Ok(false);
+    };
+    let mut store = TrialStore::load(&path).unwrap_or_default();
+    if !store.promote(rule) {
+        return Ok(false);
+    }
+    store.save(&path)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(pattern: &str) -> Vec<(String, String)> {
+        vec![("sensitive_pattern".to_string(), pattern.to_string())]
+    }
+
+    #[test]
+    fn test_new_rules_start_in_warn_stage() {
+        let config = RuleTrialConfig { enabled: true, days: 7, matches: 2 };
+        let now = Utc::now();
+        let mut store = TrialStore::default();
+
+        // 首次使用时已启用的规则直接强制执行
+        assert!(store.sync(&rules("(?i)token"), true, now).is_empty());
+        assert!(store.record_warning("sensitive_pattern", &config, now).is_none());
+
+        // 修改后重新进入仅警告阶段，命中次数用完后转为强制执行
+        assert_eq!(store.sync(&rules("(?i)secret"), false, now), ["sensitive_pattern"]);
+        assert_eq!(store.record_warning("sensitive_pattern", &config, now).unwrap().matches, 1);
+        assert_eq!(store.record_warning("sensitive_pattern", &config, now).unwrap().matches, 2);
+        assert!(store.record_warning("sensitive_pattern", &config, now).is_none());
+
+        // 配置未变时不会重新试运行
+        assert!(store.sync(&rules("(?i)secret"), false, now).is_empty());
+    }
+
+    #[test]
+    fn test_trial_expiry_and_promotion() {
+        let config = RuleTrialConfig { enabled: true, days: 7, matches: 20 };
+        let now = Utc::now();
+        let mut store = TrialStore::default();
+        store.sync(&rules("(?i)token"), false, now);
+
+        let trial = &store.trials()[0];
+        assert_eq!(trial.stage(&config, now), RuleStage::Warn);
+        assert_eq!(trial.stage(&config, now + chrono::Duration::days(7)), RuleStage::Enforce);
+        assert_eq!(trial.stage(&RuleTrialConfig { enabled: false, ..config.clone() }, now), RuleStage::Enforce);
+
+        assert!(store.promote("sensitive_pattern"));
+        assert!(!store.promote("entropy_rule"));
+        assert_eq!(store.trials()[0].stage(&config, now), RuleStage::Enforce);
+    }
+}
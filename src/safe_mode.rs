@@ -0,0 +1,389 @@
+/*!
+ * ClipVanish™ 安全模式模块
+ *
+ * 服务反复崩溃时以安全模式启动：只保留轮询方式的剪贴板保护，停用键盘监听、全局热键等容易出问题的组件，
+ * 并告诉用户最可能引起崩溃的子系统，排查期间剪贴板仍受保护
+ * 特点：
+ * - 崩溃计数文件位于配置目录：panic、看门狗退出与启动失败直接记录；
+ *   上次运行既未正常停止也未留下记录（信号、强制终止、段错误）时，下次启动补记一次
+ * - 按panic位置与调用栈、不健康的检查项推断子系统，多次崩溃中出现最多的子系统即为嫌疑
+ * - 计数窗口内崩溃达到阈值后进入安全模式；正常停止服务时清空计数，下次启动恢复正常模式
+ * - 计数文件只保存时间、子系统与脱敏后的原因，不含剪贴板内容
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::fmt;
+use std::fs;
+use std::sync::Mutex;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use crate::config::{ClipboardAccess, ClipboardConfig, Config, CountdownAnchor, HandoffPolicy, TimerConfig};
+use crate::health::HealthReport;
+use crate::redact::Scrubber;
+
+/// 安全模式配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SafeModeConfig {
+    /// 是否在反复崩溃后以安全模式启动
+    pub enabled: bool,
+    /// 计数窗口内崩溃多少次后进入安全模式
+    pub crash_threshold: u32,
+    /// 计数窗口（分钟），更早的崩溃不再计入
+    pub window_minutes: u64,
+}
+
+impl Default for SafeModeConfig {
+    fn default() -> Self {
+        SafeModeConfig {
+            enabled: true,
+            crash_threshold: 3,
+            window_minutes: 30,
+        }
+    }
+}
+
+/// 可能引起崩溃的子系统
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Subsystem {
+    /// 键盘监听
+    KeyboardHooks,
+    /// 全局热键
+    GlobalHotkeys,
+    /// xdg-desktop-portal剪贴板
+    Portal,
+    /// 剪贴板访问
+    Clipboard,
+    /// 剪贴板监听循环
+    MonitorLoop,
+    /// 自毁倒计时
+    Timers,
+    /// 无法判断
+    Unknown,
+}
+
+/// 按子系统识别panic位置与调用栈的关键字（按顺序匹配）
+const MARKERS: &[(&str, Subsystem)] = &[
+    ("rdev", Subsystem::KeyboardHooks),
+    ("keyboard", Subsystem::KeyboardHooks),
+    ("global_hotkey", Subsystem::GlobalHotkeys),
+    ("global-hotkey", Subsystem::GlobalHotkeys),
+    ("hotkey", Subsystem::GlobalHotkeys),
+    ("portal", Subsystem::Portal),
+    ("ashpd", Subsystem::Portal),
+    ("zbus", Subsystem::Portal),
+    ("arboard", Subsystem::Clipboard),
+    ("clipboard", Subsystem::Clipboard),
+    ("timer", Subsystem::Timers),
+];
+
+impl Subsystem {
+    /// 显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            Subsystem::KeyboardHooks => "键盘监听",
+            Subsystem::GlobalHotkeys => "全局热键",
+            Subsystem::Portal => "xdg-desktop-portal剪贴板",
+            Subsystem::Clipboard => "剪贴板访问",
+            Subsystem::MonitorLoop => "剪贴板监听循环",
+            Subsystem::Timers => "自毁倒计时",
+            Subsystem::Unknown => "无法判断（进程未经panic退出，可能是信号、强制终止或段错误）",
+        }
+    }
+
+    /// 按panic消息（含位置）与调用栈推断子系统
+    ///
+    /// 先看panic位置，再从最内层的栈帧向外找第一个能识别的帧
+    ///
+    /// # 参数
+    /// * `message` - panic消息（含文件与行号）
+    /// * `backtrace` - 调用栈文本
+    pub fn from_panic(message: &str, backtrace: &str) -> Self {
+        std::iter::once(message)
+            .chain(backtrace.lines())
+            .find_map(|line| {
+                let line = line.to_lowercase();
+                MARKERS.iter().find(|(marker, _)| line.contains(marker)).map(|(_, subsystem)| *subsystem)
+            })
+            .unwrap_or(Subsystem::Unknown)
+    }
+
+    /// 按不健康的检查项推断子系统（看门狗退出时使用）
+    pub fn from_health(report: &HealthReport) -> Self {
+        match report.checks.iter().find(|check| !check.healthy).map(|check| check.name.as_str()) {
+            Some("monitor_loop") => Subsystem::MonitorLoop,
+            Some("timers") => Subsystem::Timers,
+            Some("clipboard") => Subsystem::Clipboard,
+            _ => Subsystem::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for Subsystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// 一次崩溃
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrashRecord {
+    /// 崩溃时间
+    pub at: DateTime<Utc>,
+    /// 推断的子系统
+    pub subsystem: Subsystem,
+    /// 脱敏后的原因
+    pub detail: String,
+}
+
+/// 正在运行的服务
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct RunMarker {
+    pid: u32,
+    started_at: DateTime<Utc>,
+}
+
+/// 崩溃计数文件的内容
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct CrashCounter {
+    /// 尚未正常停止的运行
+    running: Option<RunMarker>,
+    /// 近期的崩溃
+    crashes: Vec<CrashRecord>,
+}
+
+impl CrashCounter {
+    /// 开始一次运行：上次运行未正常停止时补记一次崩溃，并丢弃计数窗口以外的记录
+    fn begin(&mut self, config: &SafeModeConfig, pid: u32, now: DateTime<Utc>) {
+        if let Some(previous) = self.running.take() {
+            self.crashes.push(CrashRecord {
+                at: now,
+                subsystem: Subsystem::Unknown,
+                detail: format!("进程 {}（启动于 {}）未正常退出", previous.pid, previous.started_at.to_rfc3339()),
+            });
+        }
+        let window = ChronoDuration::minutes(config.window_minutes.min(i64::MAX as u64) as i64);
+        self.crashes.retain(|crash| now - crash.at < window);
+        self.running = Some(RunMarker { pid, started_at: now });
+    }
+
+    /// 记录一次崩溃，本次运行随之结束
+    fn record(&mut self, subsystem: Subsystem, detail: &str, now: DateTime<Utc>) {
+        self.running = None;
+        self.crashes.push(CrashRecord { at: now, subsystem, detail: detail.to_string() });
+    }
+
+    /// 崩溃次数达到阈值时返回安全模式
+    fn evaluate(&self, config: &SafeModeConfig) -> Option<SafeMode> {
+        if !config.enabled || self.crashes.len() < config.crash_threshold.max(1) as usize {
+            return None;
+        }
+        Some(SafeMode {
+            crashes: self.crashes.len(),
+            window_minutes: config.window_minutes,
+            suspect: self.suspect(),
+        })
+    }
+
+    /// 出现最多的可识别子系统（次数相同时取最近一次），都无法识别时为 `Unknown`
+    fn suspect(&self) -> Subsystem {
+        let identified = || self.crashes.iter().rev().map(|crash| crash.subsystem).filter(|s| *s != Subsystem::Unknown);
+        identified()
+            .max_by_key(|candidate| identified().filter(|s| s == candidate).count())
+            .unwrap_or(Subsystem::Unknown)
+    }
+}
+
+/// 进入安全模式的原因与限制
+#[derive(Debug, Clone, PartialEq)]
+pub struct SafeMode {
+    /// 计数窗口内的崩溃次数
+    pub crashes: usize,
+    /// 计数窗口（分钟）
+    pub window_minutes: u64,
+    /// 最可能引起崩溃的子系统
+    pub suspect: Subsystem,
+}
+
+impl SafeMode {
+    /// 安全模式下倒计时的上限（秒）
+    pub fn countdown_cap() -> u64 {
+        TimerConfig::default().default_countdown
+    }
+
+    /// 按安全模式收紧配置
+    ///
+    /// 停用全局热键与依赖图形环境的附加组件，剪贴板只以轮询方式访问（沙箱内仍需门户），
+    /// 倒计时从复制时开始且不超过默认值，轮询间隔不短于默认值
+    ///
+    /// # 参数
+    /// * `config` - 要收紧的配置
+    pub fn apply(&self, config: &mut Config) {
+        config.hotkeys.enable_global_hotkeys = false;
+        if config.clipboard.access == ClipboardAccess::Portal {
+            config.clipboard.access = ClipboardAccess::Auto;
+        }
+        config.clipboard.delayed_rendering = false;
+        if config.clipboard.handoff_policy == HandoffPolicy::Disable {
+            config.clipboard.handoff_policy = HandoffPolicy::Conceal;
+        }
+        config.clipboard.poll_interval_ms = config.clipboard.poll_interval_ms.max(ClipboardConfig::default().poll_interval_ms);
+        config.overlay.enabled = false;
+        config.reader_watch.enabled = false;
+        config.integrations.clipboard_managers.enabled = false;
+
+        // 粘贴锚点依赖键盘监听，安全模式下从复制时开始倒计时
+        config.timer.anchor = CountdownAnchor::Copy;
+        config.timer.undo_grace_seconds = 0;
+        config.timer.default_countdown = config.timer.default_countdown.min(Self::countdown_cap());
+        config.clear_delay_seconds = config.clear_delay_seconds.min(Self::countdown_cap());
+    }
+}
+
+impl fmt::Display for SafeMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "最近 {} 分钟内崩溃 {} 次，可能原因: {}", self.window_minutes, self.crashes, self.suspect)
+    }
+}
+
+/// 当前进程的安全模式（未进入时为None）
+static ACTIVE: Mutex<Option<SafeMode>> = Mutex::new(None);
+
+/// 读取崩溃计数文件（不存在或无法解析时为空）
+fn load() -> CrashCounter {
+    Config::get_crash_counter_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// 保存崩溃计数文件
+fn save(counter: &CrashCounter) {
+    let result = Config::get_crash_counter_path()
+        .map_err(|e| e.to_string())
+        .and_then(|path| {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let content = serde_json::to_string_pretty(counter).map_err(|e| e.to_string())?;
+            fs::write(&path, content).map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        warn!("保存崩溃计数失败: {}", e);
+    }
+}
+
+/// 服务启动时调用：登记本次运行并判断是否进入安全模式
+///
+/// # 参数
+/// * `config` - 安全模式配置
+///
+/// # 返回值
+/// * `Option<SafeMode>` - 需要以安全模式启动时返回原因
+pub fn begin_run(config: &SafeModeConfig) -> Option<SafeMode> {
+    let mut counter = load();
+    counter.begin(config, std::process::id(), Utc::now());
+    save(&counter);
+
+    let safe_mode = counter.evaluate(config);
+    debug!("崩溃计数: {} 次，安全模式: {}", counter.crashes.len(), safe_mode.is_some());
+    *ACTIVE.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = safe_mode.clone();
+    safe_mode
+}
+
+/// 记录一次崩溃（panic钩子、看门狗退出与启动失败时调用）
+///
+/// # 参数
+/// * `subsystem` - 推断的子系统
+/// * `detail` - 原因（保存前脱敏）
+pub fn record_crash(subsystem: Subsystem, detail: &str) {
+    let mut counter = load();
+    counter.record(subsystem, &Scrubber::new().scrub(detail), Utc::now());
+    save(&counter);
+}
+
+/// 服务正常停止时调用：清空崩溃计数，下次启动恢复正常模式
+pub fn finish_run() {
+    let counter = load();
+    if counter != CrashCounter::default() {
+        save(&CrashCounter::default());
+    }
+}
+
+/// 当前进程的安全模式
+pub fn active() -> Option<SafeMode> {
+    ACTIVE.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+}
+
+/// 处于安全模式时收紧配置（重新加载配置时调用，避免恢复已停用的组件）
+///
+/// # 参数
+/// * `config` - 要收紧的配置
+pub fn restrict(config: &mut Config) {
+    if let Some(safe_mode) = active() {
+        safe_mode.apply(config);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subsystem_from_panic() {
+        let keyboard = "   4: rdev::linux::listen::record_callback\n   5: clipvanish::keyboard::linux::handle_event";
+        assert_eq!(Subsystem::from_panic("called `Option::unwrap()` (/src/lib.rs:10)", keyboard), Subsystem::KeyboardHooks);
+        assert_eq!(Subsystem::from_panic("索引越界 (src/timer.rs:88)", ""), Subsystem::Timers);
+        // 最内层的可识别栈帧优先
+        let paste = "   3: core::panicking::panic\n   4: clipvanish::clipboard::ClipboardMonitor::handle_paste\n   5: clipvanish::keyboard::dispatch_paste";
+        assert_eq!(Subsystem::from_panic("未知panic", paste), Subsystem::Clipboard);
+        assert_eq!(Subsystem::from_panic("未知panic", "   1: std::rt::lang_start"), Subsystem::Unknown);
+    }
+
+    #[test]
+    fn test_crash_counter_enters_safe_mode() {
+        let config = SafeModeConfig::default();
+        let start = Utc::now();
+        let mut counter = CrashCounter::default();
+
+        counter.begin(&config, 1, start);
+        counter.record(Subsystem::KeyboardHooks, "panic", start);
+        // 第二次运行未正常退出，下次启动补记
+        counter.begin(&config, 2, start + ChronoDuration::minutes(1));
+        assert!(counter.evaluate(&config).is_none());
+        counter.begin(&config, 3, start + ChronoDuration::minutes(2));
+        assert_eq!(counter.crashes.len(), 2);
+        counter.record(Subsystem::KeyboardHooks, "panic", start + ChronoDuration::minutes(3));
+
+        counter.begin(&config, 4, start + ChronoDuration::minutes(4));
+        let safe_mode = counter.evaluate(&config).unwrap();
+        assert_eq!(safe_mode.crashes, 3);
+        assert_eq!(safe_mode.suspect, Subsystem::KeyboardHooks);
+
+        // 计数窗口以外的崩溃不再计入
+        counter.running = None;
+        counter.begin(&config, 5, start + ChronoDuration::minutes(40));
+        assert!(counter.evaluate(&config).is_none());
+    }
+
+    #[test]
+    fn test_apply_restricts_config() {
+        let mut config = Config::default();
+        config.timer.anchor = CountdownAnchor::Paste;
+        config.timer.default_countdown = 600;
+        config.clipboard.access = ClipboardAccess::Portal;
+        config.clipboard.poll_interval_ms = 100;
+
+        SafeMode { crashes: 3, window_minutes: 30, suspect: Subsystem::Unknown }.apply(&mut config);
+        assert!(!config.hotkeys.enable_global_hotkeys);
+        assert_eq!(config.timer.anchor, CountdownAnchor::Copy);
+        assert_eq!(config.timer.default_countdown, SafeMode::countdown_cap());
+        assert_eq!(config.clipboard.access, ClipboardAccess::Auto);
+        assert_eq!(config.clipboard.poll_interval_ms, 500);
+    }
+}
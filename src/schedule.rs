@@ -0,0 +1,298 @@
+/*!
+ * ClipVanish™ 定时紧急销毁模块
+ *
+ * 向运行中的服务登记一次未来的紧急销毁，例如把笔记本交给他人之前或在下班时自动结束工作会话
+ * 特点：
+ * - 支持绝对时间（`--at 18:00`、`--at 2024-05-01T18:00:00+08:00`）与相对时间（`--in 2h`、`--in 1h30m`）
+ * - 同一时间只保留一个计划，新的计划取代旧的计划
+ * - 按墙上时钟分段等待，系统休眠唤醒后错过的计划立即执行
+ * - 登记、取消与执行都写入审计日志
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use log::{error, info, warn};
+use serde::Serialize;
+use tokio::task::JoinHandle;
+use crate::audit;
+use crate::clipboard::ClipboardMonitor;
+use crate::duration::{self, DurationError};
+use crate::output::notice;
+use crate::timer::DestructTimer;
+
+/// 重新核对墙上时钟的最长间隔
+const RECHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 定时销毁错误类型
+#[derive(Debug, PartialEq)]
+pub enum ScheduleError {
+    /// 无法解析的时间
+    InvalidTime(String),
+    /// 无法解析的时长
+    InvalidDuration(DurationError),
+    /// 计划时间已经过去
+    InPast(String),
+    /// 未指定时间
+    Missing,
+}
+
+impl std::fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScheduleError::InvalidTime(text) => write!(f, "无法解析的时间: {}（示例: 18:00、2024-05-01 18:00）", text),
+            ScheduleError::InvalidDuration(e) => write!(f, "{}", e),
+            ScheduleError::InPast(text) => write!(f, "计划时间已经过去: {}", text),
+            ScheduleError::Missing => write!(f, "需要指定 --at 或 --in"),
+        }
+    }
+}
+
+impl std::error::Error for ScheduleError {}
+
+/// 已登记的定时销毁
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduledNuke {
+    /// 计划执行时间
+    pub at: DateTime<Local>,
+    /// 登记来源（交互命令、控制接口令牌等）
+    pub requested_by: String,
+}
+
+impl ScheduledNuke {
+    /// 距离执行的剩余时间
+    pub fn remaining(&self) -> Duration {
+        (self.at - Local::now()).to_std().unwrap_or(Duration::ZERO)
+    }
+}
+
+/// 解析 `--at` 或 `--in` 指定的执行时间
+///
+/// # 参数
+/// * `at` - 绝对时间
+/// * `delay` - 相对时长
+///
+/// # 返回值
+/// * `Result<DateTime<Local>, ScheduleError>` - 计划执行时间
+pub fn resolve(at: Option<&str>, delay: Option<&str>) -> Result<DateTime<Local>, ScheduleError> {
+    let now = Local::now();
+    match (at, delay) {
+        (Some(at), _) => parse_at(at, now),
+        (None, Some(text)) => {
+            let delay = duration::parse(text).map_err(ScheduleError::InvalidDuration)?;
+            chrono::Duration::from_std(delay)
+                .ok()
+                .and_then(|delay| now.checked_add_signed(delay))
+                .ok_or_else(|| ScheduleError::InvalidDuration(DurationError::TooLarge(text.to_string())))
+        },
+        (None, None) => Err(ScheduleError::Missing),
+    }
+}
+
+/// 解析绝对时间
+///
+/// 只有时刻（HH:MM或HH:MM:SS）时取下一次到达的该时刻：今天已过则为明天
+///
+/// # 参数
+/// * `text` - 时间文本
+/// * `now` - 当前时间
+pub fn parse_at(text: &str, now: DateTime<Local>) -> Result<DateTime<Local>, ScheduleError> {
+    let text = text.trim();
+
+    if let Ok(at) = DateTime::parse_from_rfc3339(text) {
+        let at = at.with_timezone(&Local);
+        return if at > now { Ok(at) } else { Err(ScheduleError::InPast(text.to_string())) };
+    }
+
+    for format in ["%Y-%m-%d %H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%dT%H:%M:%S"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(text, format) {
+            let at = local_time(naive).ok_or_else(|| ScheduleError::InvalidTime(text.to_string()))?;
+            return if at > now { Ok(at) } else { Err(ScheduleError::InPast(text.to_string())) };
+        }
+    }
+
+    let time = NaiveTime::parse_from_str(text, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(text, "%H:%M:%S"))
+        .map_err(|_| ScheduleError::InvalidTime(text.to_string()))?;
+
+    let today = now.date_naive();
+    let mut at = local_time(today.and_time(time)).ok_or_else(|| ScheduleError::InvalidTime(text.to_string()))?;
+    if at <= now {
+        let tomorrow: NaiveDate = today.succ_opt().ok_or_else(|| ScheduleError::InvalidTime(text.to_string()))?;
+        at = local_time(tomorrow.and_time(time)).ok_or_else(|| ScheduleError::InvalidTime(text.to_string()))?;
+    }
+    Ok(at)
+}
+
+/// 本地时间（夏令时切换造成的重复时刻取较早者）
+fn local_time(naive: NaiveDateTime) -> Option<DateTime<Local>> {
+    Local.from_local_datetime(&naive).earliest()
+}
+
+/// 当前计划及其等待任务
+struct PendingNuke {
+    /// 计划编号（区分被取代的计划）
+    id: u64,
+    /// 计划内容
+    nuke: ScheduledNuke,
+    /// 等待任务
+    task: JoinHandle<()>,
+}
+
+/// 定时销毁调度器
+pub struct NukeScheduler {
+    /// 剪贴板监听器
+    monitor: Arc<ClipboardMonitor>,
+    /// 自毁定时器
+    timer: Arc<Mutex<DestructTimer>>,
+    /// 当前计划
+    pending: Mutex<Option<PendingNuke>>,
+    /// 下一个计划编号
+    next_id: Mutex<u64>,
+}
+
+impl NukeScheduler {
+    /// 创建调度器（需在异步运行时中调用）
+    ///
+    /// # 参数
+    /// * `monitor` - 剪贴板监听器
+    /// * `timer` - 自毁定时器
+    pub fn new(monitor: Arc<ClipboardMonitor>, timer: Arc<Mutex<DestructTimer>>) -> Arc<Self> {
+        Arc::new(NukeScheduler {
+            monitor,
+            timer,
+            pending: Mutex::new(None),
+            next_id: Mutex::new(0),
+        })
+    }
+
+    /// 登记定时销毁，取代已有的计划
+    ///
+    /// # 参数
+    /// * `at` - 计划执行时间
+    /// * `requested_by` - 登记来源
+    ///
+    /// # 返回值
+    /// * `Option<ScheduledNuke>` - 被取代的计划
+    pub fn schedule(self: &Arc<Self>, at: DateTime<Local>, requested_by: &str) -> Option<ScheduledNuke> {
+        let id = {
+            let mut next_id = self.next_id.lock().unwrap();
+            *next_id += 1;
+            *next_id
+        };
+
+        let weak = Arc::downgrade(self);
+        let task = tokio::spawn(Self::wait_and_fire(weak, id, at));
+        let nuke = ScheduledNuke { at, requested_by: requested_by.to_string() };
+
+        let replaced = self.pending.lock().unwrap().replace(PendingNuke { id, nuke, task });
+        let replaced = replaced.map(|previous| {
+            previous.task.abort();
+            previous.nuke
+        });
+
+        info!("已登记定时紧急销毁: {}（来源: {}）", at.format("%Y-%m-%d %H:%M:%S"), requested_by);
+        audit::record("scheduled_nuke", &format!("登记 时间={} 来源={}", at.to_rfc3339(), requested_by));
+        replaced
+    }
+
+    /// 取消当前计划
+    ///
+    /// # 返回值
+    /// * `Option<ScheduledNuke>` - 被取消的计划，没有计划时返回None
+    pub fn cancel(&self, requested_by: &str) -> Option<ScheduledNuke> {
+        let pending = self.pending.lock().unwrap().take()?;
+        pending.task.abort();
+
+        info!("已取消定时紧急销毁: {}", pending.nuke.at.format("%Y-%m-%d %H:%M:%S"));
+        audit::record(
+            "scheduled_nuke",
+            &format!("取消 时间={} 来源={}", pending.nuke.at.to_rfc3339(), requested_by),
+        );
+        Some(pending.nuke)
+    }
+
+    /// 当前计划
+    pub fn pending(&self) -> Option<ScheduledNuke> {
+        self.pending.lock().unwrap().as_ref().map(|pending| pending.nuke.clone())
+    }
+
+    /// 按墙上时钟分段等待，到达计划时间后执行
+    async fn wait_and_fire(scheduler: Weak<Self>, id: u64, at: DateTime<Local>) {
+        loop {
+            let remaining = (at - Local::now()).to_std().unwrap_or(Duration::ZERO);
+            if remaining.is_zero() {
+                break;
+            }
+            tokio::time::sleep(remaining.min(RECHECK_INTERVAL)).await;
+        }
+
+        if let Some(scheduler) = scheduler.upgrade() {
+            scheduler.fire(id);
+        }
+    }
+
+    /// 执行定时销毁（计划已被取代或取消时不执行）
+    fn fire(&self, id: u64) {
+        let nuke = {
+            let mut pending = self.pending.lock().unwrap();
+            if pending.as_ref().map(|pending| pending.id) != Some(id) {
+                return;
+            }
+            pending.take().map(|pending| pending.nuke)
+        };
+        let Some(nuke) = nuke else {
+            return;
+        };
+
+        warn!("定时紧急销毁时间已到，开始执行");
+        notice!("\n⏰ 定时紧急销毁时间已到");
+
+        let result = self.monitor.emergency_nuke();
+        if let Err(e) = self.timer.lock().unwrap().stop_countdown() {
+            warn!("停止倒计时失败: {}", e);
+        }
+
+        match result {
+            Ok(_) => {
+                notice!("💥 定时紧急销毁完成 - 剪贴板与内存中的敏感数据已清除");
+                audit::record("scheduled_nuke", &format!("执行 时间={} 来源={}", nuke.at.to_rfc3339(), nuke.requested_by));
+            },
+            Err(e) => {
+                error!("定时紧急销毁失败: {}", e);
+                audit::record("scheduled_nuke", &format!("执行失败 时间={} 错误={}", nuke.at.to_rfc3339(), e));
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_delay() {
+        let before = Local::now();
+        let at = resolve(None, Some("1h30m")).unwrap();
+        assert!(at - before >= chrono::Duration::seconds(5400) && at - before < chrono::Duration::seconds(5410));
+        assert!(matches!(resolve(None, Some("2x")), Err(ScheduleError::InvalidDuration(DurationError::UnknownUnit { .. }))));
+        assert_eq!(resolve(None, None), Err(ScheduleError::Missing));
+    }
+
+    #[test]
+    fn test_parse_at_rolls_over_to_tomorrow() {
+        let now = Local.with_ymd_and_hms(2024, 5, 1, 19, 0, 0).unwrap();
+
+        let later = parse_at("20:30", now).unwrap();
+        assert_eq!(later, Local.with_ymd_and_hms(2024, 5, 1, 20, 30, 0).unwrap());
+
+        // 今天的18:00已过，取明天
+        let tomorrow = parse_at("18:00", now).unwrap();
+        assert_eq!(tomorrow, Local.with_ymd_and_hms(2024, 5, 2, 18, 0, 0).unwrap());
+
+        assert!(matches!(parse_at("2024-05-01 08:00", now), Err(ScheduleError::InPast(_))));
+        assert!(matches!(parse_at("tea time", now), Err(ScheduleError::InvalidTime(_))));
+    }
+}
@@ -0,0 +1,262 @@
+/*!
+ * ClipVanish™ 会话统计模块
+ *
+ * 统计一次监听会话中的保护与销毁情况，在服务停止时输出摘要
+ * 特点：
+ * - 按清除原因统计销毁条目
+ * - 计算条目平均存活时间
+ * - 记录因错误未能保护的条目
+ * - 统计拦截延迟与超出SLO的次数
+ * - 不记录任何明文内容
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+use crate::clipboard::ClearReason;
+use crate::item_id::ItemId;
+
+/// 会话统计
+#[derive(Debug)]
+pub struct SessionStats {
+    /// 会话开始时间
+    started: Instant,
+    /// 受保护条目数
+    protected: u64,
+    /// 按原因统计的销毁条目数
+    destroyed: BTreeMap<&'static str, u64>,
+    /// 尚未销毁的条目及其保护开始时间
+    live_items: HashMap<ItemId, Instant>,
+    /// 已销毁条目的存活时间总和
+    total_lifetime: Duration,
+    /// 已销毁条目数（用于计算平均存活时间）
+    lifetime_samples: u32,
+    /// 紧急销毁次数
+    nukes: u64,
+    /// 因错误未能保护的条目数
+    unprotected: u64,
+    /// 最大拦截延迟
+    max_latency: Option<Duration>,
+    /// 拦截延迟超出SLO的次数
+    slo_violations: u64,
+}
+
+impl SessionStats {
+    /// 开始新的会话统计
+    pub fn new() -> Self {
+        SessionStats {
+            started: Instant::now(),
+            protected: 0,
+            destroyed: BTreeMap::new(),
+            live_items: HashMap::new(),
+            total_lifetime: Duration::ZERO,
+            lifetime_samples: 0,
+            nukes: 0,
+            unprotected: 0,
+            max_latency: None,
+            slo_violations: 0,
+        }
+    }
+
+    /// 记录条目已受保护
+    ///
+    /// # 参数
+    /// * `item_id` - 条目ID
+    pub fn record_protected(&mut self, item_id: ItemId) {
+        self.protected += 1;
+        self.live_items.insert(item_id, Instant::now());
+    }
+
+    /// 记录清除事件
+    ///
+    /// # 参数
+    /// * `item_id` - 被清除的条目ID（没有条目时为None）
+    /// * `reason` - 清除原因
+    pub fn record_cleared(&mut self, item_id: Option<ItemId>, reason: &ClearReason) {
+        if matches!(reason, ClearReason::EmergencyNuke) {
+            self.nukes += 1;
+        }
+
+        let Some(started) = item_id.and_then(|id| self.live_items.remove(&id)) else {
+            return;
+        };
+
+        *self.destroyed.entry(reason_label(reason)).or_insert(0) += 1;
+        self.total_lifetime += started.elapsed();
+        self.lifetime_samples += 1;
+    }
+
+    /// 记录因错误未能保护的条目
+    pub fn record_unprotected(&mut self) {
+        self.unprotected += 1;
+    }
+
+    /// 记录一次拦截的延迟
+    ///
+    /// # 参数
+    /// * `latency` - 从剪贴板变化到密文替换的时间
+    /// * `slo_violated` - 是否超出SLO
+    pub fn record_interception(&mut self, latency: Duration, slo_violated: bool) {
+        self.max_latency = Some(self.max_latency.map_or(latency, |max| max.max(latency)));
+        if slo_violated {
+            self.slo_violations += 1;
+        }
+    }
+
+    /// 生成会话摘要
+    pub fn summary(&self) -> SessionSummary {
+        SessionSummary {
+            duration: self.started.elapsed(),
+            protected: self.protected,
+            destroyed: self.destroyed.clone(),
+            average_lifetime: (self.lifetime_samples > 0)
+                .then(|| self.total_lifetime / self.lifetime_samples),
+            nukes: self.nukes,
+            unprotected: self.unprotected,
+            still_live: self.live_items.len(),
+            max_latency: self.max_latency,
+            slo_violations: self.slo_violations,
+        }
+    }
+}
+
+impl Default for SessionStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 清除原因的显示名称
+pub fn reason_label(reason: &ClearReason) -> &'static str {
+    match reason {
+        ClearReason::TimerExpired => "倒计时到期",
+        ClearReason::ManualClear => "手动清除",
+        ClearReason::EmergencyNuke => "紧急销毁",
+        ClearReason::Shutdown => "程序退出",
+        ClearReason::Unprotected => "解除保护",
+        ClearReason::Escalated => "延长过多强制销毁",
+    }
+}
+
+/// 会话摘要
+#[derive(Debug, Clone)]
+pub struct SessionSummary {
+    /// 会话时长
+    pub duration: Duration,
+    /// 受保护条目数
+    pub protected: u64,
+    /// 按原因统计的销毁条目数
+    pub destroyed: BTreeMap<&'static str, u64>,
+    /// 平均存活时间（没有已销毁条目时为None）
+    pub average_lifetime: Option<Duration>,
+    /// 紧急销毁次数
+    pub nukes: u64,
+    /// 因错误未能保护的条目数
+    pub unprotected: u64,
+    /// 会话结束时仍未销毁的条目数
+    pub still_live: usize,
+    /// 最大拦截延迟（没有拦截时为None）
+    pub max_latency: Option<Duration>,
+    /// 拦截延迟超出SLO的次数
+    pub slo_violations: u64,
+}
+
+impl SessionSummary {
+    /// 单行形式（用于审计日志）
+    pub fn to_audit_detail(&self) -> String {
+        let destroyed: Vec<String> = self
+            .destroyed
+            .iter()
+            .map(|(reason, count)| format!("{}={}", reason, count))
+            .collect();
+
+        format!(
+            "时长={}秒 保护={} 销毁[{}] 平均存活={} 紧急销毁={} 未保护={} 未销毁={} 最大拦截延迟={} SLO超时={}",
+            self.duration.as_secs(),
+            self.protected,
+            destroyed.join(","),
+            self.average_lifetime
+                .map(|d| format!("{}秒", d.as_secs()))
+                .unwrap_or_else(|| "-".to_string()),
+            self.nukes,
+            self.unprotected,
+            self.still_live,
+            self.max_latency
+                .map(|d| format!("{}ms", d.as_millis()))
+                .unwrap_or_else(|| "-".to_string()),
+            self.slo_violations,
+        )
+    }
+}
+
+impl std::fmt::Display for SessionSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "📊 会话摘要")?;
+        writeln!(f, "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━")?;
+        writeln!(f, "⏱️  会话时长: {}秒", self.duration.as_secs())?;
+        writeln!(f, "🔒 受保护条目: {}", self.protected)?;
+
+        let total_destroyed: u64 = self.destroyed.values().sum();
+        writeln!(f, "🔥 已销毁条目: {}", total_destroyed)?;
+        for (reason, count) in &self.destroyed {
+            writeln!(f, "   {}: {}", reason, count)?;
+        }
+
+        match self.average_lifetime {
+            Some(lifetime) => writeln!(f, "⏳ 平均存活时间: {:.1}秒", lifetime.as_secs_f64())?,
+            None => writeln!(f, "⏳ 平均存活时间: -")?,
+        }
+
+        writeln!(f, "💥 紧急销毁: {} 次", self.nukes)?;
+
+        if let Some(latency) = self.max_latency {
+            writeln!(f, "⚡ 最大拦截延迟: {}ms（超出SLO {} 次）", latency.as_millis(), self.slo_violations)?;
+        }
+
+        if self.unprotected > 0 {
+            write!(f, "⚠️  因错误未能保护: {} 条", self.unprotected)
+        } else {
+            write!(f, "✅ 所有检测到的敏感条目均已受保护")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_counts() {
+        let mut stats = SessionStats::new();
+        let (first, second) = (ItemId::generate(), ItemId::generate());
+        stats.record_protected(first);
+        stats.record_protected(second);
+        stats.record_cleared(Some(first), &ClearReason::TimerExpired);
+        stats.record_cleared(Some(second), &ClearReason::EmergencyNuke);
+        // 没有条目的紧急销毁只计入次数
+        stats.record_cleared(None, &ClearReason::EmergencyNuke);
+        stats.record_unprotected();
+        stats.record_interception(Duration::from_millis(120), false);
+        stats.record_interception(Duration::from_millis(1500), true);
+
+        let summary = stats.summary();
+        assert_eq!(summary.protected, 2);
+        assert_eq!(summary.destroyed.get("倒计时到期"), Some(&1));
+        assert_eq!(summary.destroyed.get("紧急销毁"), Some(&1));
+        assert_eq!(summary.nukes, 2);
+        assert_eq!(summary.unprotected, 1);
+        assert_eq!(summary.still_live, 0);
+        assert!(summary.average_lifetime.is_some());
+        assert_eq!(summary.max_latency, Some(Duration::from_millis(1500)));
+        assert_eq!(summary.slo_violations, 1);
+    }
+
+    #[test]
+    fn test_empty_session_summary() {
+        let summary = SessionStats::new().summary();
+        assert!(summary.average_lifetime.is_none());
+        assert!(summary.to_audit_detail().contains("保护=0"));
+        assert!(summary.to_string().contains("均已受保护"));
+    }
+}
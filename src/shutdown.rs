@@ -0,0 +1,329 @@
+/*!
+ * ClipVanish™ 关闭协调模块
+ *
+ * 服务的各个组件在启动时登记关闭步骤，信号、`stop`、交互模式的 `exit` 与崩溃统一经由同一个协调器按阶段关闭
+ * 特点：
+ * - 阶段固定为：停止接收 → 取消定时器 → 销毁条目 → 擦除密钥 → 释放钩子 → 写入审计日志
+ * - 每个步骤在独立线程上执行并限时等待；卡住或panic的步骤被放弃，后续阶段照常执行
+ * - 整体受 `shutdown.deadline_ms` 限制，超时后其余步骤各只获得很短的宽限时间
+ * - 登记的步骤只执行一次，重复触发（如收到信号后又执行 `exit`）不会重复关闭
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use crate::audit;
+use crate::crash::{self, SupervisedScope};
+
+/// 整体期限耗尽后每个步骤仍可获得的宽限时间
+const STEP_GRACE: Duration = Duration::from_millis(50);
+
+/// 关闭配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShutdownConfig {
+    /// 全部阶段的总期限（毫秒）
+    pub deadline_ms: u64,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        ShutdownConfig { deadline_ms: 3000 }
+    }
+}
+
+impl ShutdownConfig {
+    /// 全部阶段的总期限
+    pub fn deadline(&self) -> Duration {
+        Duration::from_millis(self.deadline_ms)
+    }
+}
+
+/// 关闭阶段（按执行顺序排列）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Phase {
+    /// 停止监听与控制接口，不再接收新内容或命令
+    StopIntake,
+    /// 取消倒计时、定时销毁与后台任务
+    CancelTimers,
+    /// 清除受保护条目、撤销暂存区与历史记录
+    DestroyItems,
+    /// 擦除加密密钥与安全内存
+    WipeKeys,
+    /// 释放键盘钩子并恢复会话期间修改的系统设置
+    ReleaseHooks,
+    /// 写入会话摘要与关闭记录
+    FlushAudit,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Phase::StopIntake => "停止接收",
+            Phase::CancelTimers => "取消定时器",
+            Phase::DestroyItems => "销毁条目",
+            Phase::WipeKeys => "擦除密钥",
+            Phase::ReleaseHooks => "释放钩子",
+            Phase::FlushAudit => "写入审计日志",
+        })
+    }
+}
+
+/// 关闭的触发原因
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trigger {
+    /// 收到终止信号（信号名称）
+    Signal(&'static str),
+    /// `stop` 命令或监听停止
+    Stop,
+    /// 交互模式的 `exit` 命令
+    Exit,
+    /// 程序崩溃
+    Panic,
+    /// 健康检查连续失败
+    Watchdog,
+}
+
+impl fmt::Display for Trigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Trigger::Signal(name) => write!(f, "信号 {}", name),
+            Trigger::Stop => f.write_str("stop"),
+            Trigger::Exit => f.write_str("exit"),
+            Trigger::Panic => f.write_str("崩溃"),
+            Trigger::Watchdog => f.write_str("健康检查"),
+        }
+    }
+}
+
+/// 关闭步骤（在独立线程上执行，不得假定调用者持有的锁可用）
+pub type Step = Box<dyn Fn(Trigger) -> Result<(), String> + Send + Sync>;
+
+/// 单个步骤的结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum StepStatus {
+    /// 完成
+    Done,
+    /// 失败（原因）
+    Failed(String),
+    /// 超过期限，已放弃等待
+    TimedOut,
+}
+
+/// 单个步骤的执行记录
+#[derive(Debug, Clone)]
+pub struct StepOutcome {
+    /// 所属阶段
+    pub phase: Phase,
+    /// 步骤名称
+    pub name: &'static str,
+    /// 用时
+    pub elapsed: Duration,
+    /// 结果
+    pub status: StepStatus,
+}
+
+/// 一次关闭的执行记录
+#[derive(Debug, Clone)]
+pub struct ShutdownReport {
+    /// 触发原因
+    pub trigger: Trigger,
+    /// 各步骤的执行记录（按执行顺序）
+    pub steps: Vec<StepOutcome>,
+    /// 总用时
+    pub elapsed: Duration,
+}
+
+impl ShutdownReport {
+    /// 未完成（失败或超时）的步骤
+    pub fn problems(&self) -> impl Iterator<Item = &StepOutcome> {
+        self.steps.iter().filter(|step| step.status != StepStatus::Done)
+    }
+
+    /// 审计日志中的摘要
+    pub fn to_audit_detail(&self) -> String {
+        let problems: Vec<String> = self
+            .problems()
+            .map(|step| match &step.status {
+                StepStatus::Failed(reason) => format!("{}/{} 失败: {}", step.phase, step.name, reason),
+                _ => format!("{}/{} 超时", step.phase, step.name),
+            })
+            .collect();
+        format!(
+            "触发={} 步骤={} 用时={}ms{}",
+            self.trigger,
+            self.steps.len(),
+            self.elapsed.as_millis(),
+            if problems.is_empty() { String::new() } else { format!(" 未完成: {}", problems.join("; ")) }
+        )
+    }
+}
+
+/// 已登记的步骤
+struct Registered {
+    phase: Phase,
+    name: &'static str,
+    step: Arc<Step>,
+}
+
+/// 关闭协调器
+pub struct Coordinator {
+    /// 已登记、尚未执行的步骤
+    steps: Mutex<Vec<Registered>>,
+    /// 全部阶段的总期限
+    deadline: Mutex<Duration>,
+    /// 是否正在关闭
+    running: AtomicBool,
+}
+
+impl Coordinator {
+    /// 创建协调器
+    ///
+    /// # 参数
+    /// * `deadline` - 全部阶段的总期限
+    pub fn new(deadline: Duration) -> Self {
+        Coordinator { steps: Mutex::new(Vec::new()), deadline: Mutex::new(deadline), running: AtomicBool::new(false) }
+    }
+
+    /// 设置全部阶段的总期限
+    pub fn set_deadline(&self, deadline: Duration) {
+        *self.deadline.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = deadline;
+    }
+
+    /// 登记关闭步骤（同一阶段的步骤按登记顺序执行）
+    ///
+    /// # 参数
+    /// * `phase` - 所属阶段
+    /// * `name` - 步骤名称（用于提示与审计日志）
+    /// * `step` - 步骤
+    pub fn register(&self, phase: Phase, name: &'static str, step: Step) {
+        let mut steps = self.steps.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        steps.push(Registered { phase, name, step: Arc::new(step) });
+    }
+
+    /// 按阶段执行全部已登记的步骤
+    ///
+    /// 会阻塞调用线程直到全部步骤完成或期限耗尽，异步上下文中应经由 `spawn_blocking` 调用
+    ///
+    /// # 参数
+    /// * `trigger` - 触发原因
+    ///
+    /// # 返回值
+    /// * `Option<ShutdownReport>` - 没有已登记的步骤或已在关闭时返回None
+    pub fn run(&self, trigger: Trigger) -> Option<ShutdownReport> {
+        if self.running.swap(true, Ordering::SeqCst) {
+            return None;
+        }
+        let mut steps = std::mem::take(&mut *self.steps.lock().unwrap_or_else(|poisoned| poisoned.into_inner()));
+        let budget = *self.deadline.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        steps.sort_by_key(|registered| registered.phase);
+
+        let report = (!steps.is_empty()).then(|| {
+            info!("开始关闭（触发: {}，{} 个步骤）", trigger, steps.len());
+            let started = Instant::now();
+            let outcomes = steps
+                .into_iter()
+                .map(|registered| {
+                    let wait = (started + budget).saturating_duration_since(Instant::now()).max(STEP_GRACE);
+                    run_step(registered, trigger, wait)
+                })
+                .collect();
+            let report = ShutdownReport { trigger, steps: outcomes, elapsed: started.elapsed() };
+            for problem in report.problems() {
+                warn!("关闭步骤 {}/{} 未完成: {:?}", problem.phase, problem.name, problem.status);
+            }
+            audit::record("shutdown", &report.to_audit_detail());
+            report
+        });
+
+        self.running.store(false, Ordering::SeqCst);
+        report
+    }
+}
+
+/// 在独立线程上执行单个步骤，最多等待 `wait`
+fn run_step(registered: Registered, trigger: Trigger, wait: Duration) -> StepOutcome {
+    let started = Instant::now();
+    let (tx, rx) = std::sync::mpsc::channel();
+    let step = registered.step.clone();
+    let spawned = std::thread::Builder::new().name(format!("shutdown-{}", registered.name)).spawn(move || {
+        // 步骤中的panic交由这里处理，不按致命错误终止进程
+        let _scope = SupervisedScope::enter();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| step(trigger)))
+            .unwrap_or_else(|payload| Err(crash::take_supervised_panic().unwrap_or_else(|| crash::payload_message(payload.as_ref()))));
+        let _ = tx.send(result);
+    });
+
+    let status = match spawned {
+        Err(e) => StepStatus::Failed(format!("无法创建线程: {}", e)),
+        Ok(_) => match rx.recv_timeout(wait) {
+            Ok(Ok(())) => StepStatus::Done,
+            Ok(Err(reason)) => StepStatus::Failed(reason),
+            Err(_) => StepStatus::TimedOut,
+        },
+    };
+    StepOutcome { phase: registered.phase, name: registered.name, elapsed: started.elapsed(), status }
+}
+
+/// 进程唯一的关闭协调器
+pub fn coordinator() -> &'static Coordinator {
+    static COORDINATOR: OnceLock<Coordinator> = OnceLock::new();
+    COORDINATOR.get_or_init(|| Coordinator::new(ShutdownConfig::default().deadline()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording(log: &Arc<Mutex<Vec<&'static str>>>, name: &'static str) -> Step {
+        let log = log.clone();
+        Box::new(move |_| {
+            log.lock().unwrap().push(name);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_phases_run_in_order_once() {
+        let coordinator = Coordinator::new(Duration::from_secs(5));
+        let log = Arc::new(Mutex::new(Vec::new()));
+        coordinator.register(Phase::FlushAudit, "session", recording(&log, "session"));
+        coordinator.register(Phase::WipeKeys, "keys", recording(&log, "keys"));
+        coordinator.register(Phase::StopIntake, "monitor", recording(&log, "monitor"));
+        coordinator.register(Phase::StopIntake, "ipc", recording(&log, "ipc"));
+        coordinator.register(Phase::DestroyItems, "fails", Box::new(|trigger| Err(format!("触发 {}", trigger))));
+
+        let report = coordinator.run(Trigger::Signal("SIGTERM")).unwrap();
+        assert_eq!(*log.lock().unwrap(), vec!["monitor", "ipc", "keys", "session"]);
+        let problems: Vec<_> = report.problems().map(|step| (step.phase, step.status.clone())).collect();
+        assert_eq!(problems, vec![(Phase::DestroyItems, StepStatus::Failed("触发 信号 SIGTERM".to_string()))]);
+        assert!(report.to_audit_detail().contains("销毁条目/fails 失败"));
+
+        // 步骤只执行一次
+        assert!(coordinator.run(Trigger::Exit).is_none());
+    }
+
+    #[test]
+    fn test_hung_step_does_not_block_later_phases() {
+        let coordinator = Coordinator::new(Duration::from_millis(100));
+        let log = Arc::new(Mutex::new(Vec::new()));
+        coordinator.register(Phase::CancelTimers, "hung", Box::new(|_| {
+            std::thread::sleep(Duration::from_secs(5));
+            Ok(())
+        }));
+        coordinator.register(Phase::WipeKeys, "keys", recording(&log, "keys"));
+
+        let started = Instant::now();
+        let report = coordinator.run(Trigger::Stop).unwrap();
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(report.steps[0].status, StepStatus::TimedOut);
+        assert_eq!(report.steps[1].status, StepStatus::Done);
+        assert_eq!(*log.lock().unwrap(), vec!["keys"]);
+    }
+}
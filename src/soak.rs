@@ -0,0 +1,380 @@
+/*!
+ * ClipVanish™ 浸泡测试模块
+ *
+ * 长时间连续运行合成的复制/粘贴/清除循环，验证守护进程的长期稳定性
+ * 特点：
+ * - 使用内存中的测试剪贴板，不读写系统剪贴板
+ * - 每个循环走完整链路：加密存储 → 解密粘贴并重置密钥 → 安全内存擦除 → 倒计时清除
+ * - 定期采样常驻内存、打开的文件描述符与存活的异步任务数
+ * - 记录倒计时完成时间与预期的偏差
+ * - 资源增长或计时偏差超过阈值时立即失败
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use tokio::sync::mpsc;
+use zeroize::Zeroizing;
+use aes_gcm_siv::aead::OsRng;
+use rand::RngCore;
+use crate::crypto::{CryptoEngine, EncryptedData};
+use crate::memory::SecureMemoryBlock;
+use crate::timer::{DestructTimer, TimerEvent};
+
+/// 失败阈值
+#[derive(Debug, Clone)]
+pub struct SoakLimits {
+    /// 常驻内存最大增长（KB）
+    pub max_rss_growth_kb: u64,
+    /// 文件描述符/句柄最大增长
+    pub max_fd_growth: u64,
+    /// 存活异步任务最大增长
+    pub max_task_growth: usize,
+    /// 倒计时完成的最大偏差
+    pub max_timer_drift: Duration,
+}
+
+impl Default for SoakLimits {
+    fn default() -> Self {
+        SoakLimits {
+            max_rss_growth_kb: 20 * 1024,
+            max_fd_growth: 8,
+            max_task_growth: 8,
+            max_timer_drift: Duration::from_millis(500),
+        }
+    }
+}
+
+/// 浸泡测试参数
+#[derive(Debug, Clone)]
+pub struct SoakOptions {
+    /// 总运行时间
+    pub duration: Duration,
+    /// 每个循环的清除倒计时
+    pub countdown: Duration,
+    /// 资源采样间隔（第一次采样作为基线）
+    pub sample_interval: Duration,
+    /// 失败阈值
+    pub limits: SoakLimits,
+}
+
+impl SoakOptions {
+    /// 按运行小时数创建参数
+    pub fn hours(hours: f64) -> Self {
+        let duration = Duration::from_secs_f64(hours.max(0.0) * 3600.0);
+        SoakOptions {
+            duration,
+            countdown: Duration::from_secs(1),
+            // 短时间运行时也至少采样数次
+            sample_interval: (duration / 10).clamp(Duration::from_secs(5), Duration::from_secs(60)),
+            limits: SoakLimits::default(),
+        }
+    }
+}
+
+/// 资源采样
+#[derive(Debug, Clone, Serialize)]
+pub struct ResourceSample {
+    /// 开始后经过的秒数
+    pub elapsed_seconds: u64,
+    /// 已完成的循环数
+    pub cycles: u64,
+    /// 常驻内存（KB，平台不支持时为None）
+    pub rss_kb: Option<u64>,
+    /// 打开的文件描述符/句柄数
+    pub open_fds: Option<u64>,
+    /// 存活的异步任务数
+    pub tasks: usize,
+}
+
+/// 浸泡测试结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SoakReport {
+    /// 完成的循环数
+    pub cycles: u64,
+    /// 失败的循环数
+    pub cycle_errors: u64,
+    /// 最大计时偏差（毫秒）
+    pub max_drift_ms: u64,
+    /// 平均计时偏差（毫秒）
+    pub mean_drift_ms: u64,
+    /// 全部采样
+    pub samples: Vec<ResourceSample>,
+    /// 失败原因
+    pub failures: Vec<String>,
+}
+
+impl SoakReport {
+    /// 是否通过
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// 内存中的测试剪贴板（替代系统剪贴板）
+#[derive(Default)]
+struct TestClipboard {
+    /// 当前内容
+    content: Mutex<Option<Zeroizing<String>>>,
+}
+
+impl TestClipboard {
+    fn set(&self, content: String) {
+        *self.content.lock().unwrap() = Some(Zeroizing::new(content));
+    }
+
+    fn get(&self) -> Option<Zeroizing<String>> {
+        self.content.lock().unwrap().clone()
+    }
+
+    fn clear(&self) {
+        *self.content.lock().unwrap() = None;
+    }
+}
+
+/// 运行浸泡测试
+///
+/// # 参数
+/// * `options` - 测试参数
+/// * `on_sample` - 每次采样后的回调（用于显示进度）
+pub async fn run<F: Fn(&ResourceSample)>(options: &SoakOptions, on_sample: F) -> SoakReport {
+    let mut report = SoakReport::default();
+
+    // 倒计时完成事件通过通道送回测试循环
+    let (completed_tx, mut completed_rx) = mpsc::unbounded_channel::<Instant>();
+    let mut timer = DestructTimer::new();
+    timer.set_callback(Arc::new(move |event| {
+        if let TimerEvent::Completed { timestamp, .. } = event {
+            let _ = completed_tx.send(timestamp);
+        }
+    }));
+    if let Err(e) = timer.start_service().await {
+        report.failures.push(format!("定时器服务启动失败: {}", e));
+        return report;
+    }
+
+    let mut engine = match CryptoEngine::new() {
+        Ok(engine) => engine,
+        Err(e) => {
+            report.failures.push(format!("加密引擎创建失败: {}", e));
+            return report;
+        }
+    };
+    let clipboard = TestClipboard::default();
+
+    let start = Instant::now();
+    let mut next_sample = start;
+    let mut baseline: Option<ResourceSample> = None;
+    let mut total_drift = Duration::ZERO;
+    let mut max_drift = Duration::ZERO;
+
+    while start.elapsed() < options.duration {
+        match run_cycle(&mut engine, &clipboard, &timer, &mut completed_rx, options).await {
+            Ok(drift) => {
+                total_drift += drift;
+                max_drift = max_drift.max(drift);
+                if drift > options.limits.max_timer_drift {
+                    report.failures.push(format!(
+                        "第 {} 个循环计时偏差 {}ms 超过阈值 {}ms",
+                        report.cycles + 1,
+                        drift.as_millis(),
+                        options.limits.max_timer_drift.as_millis()
+                    ));
+                }
+            }
+            Err(e) => {
+                report.cycle_errors += 1;
+                report.failures.push(format!("第 {} 个循环失败: {}", report.cycles + 1, e));
+            }
+        }
+        report.cycles += 1;
+
+        if Instant::now() >= next_sample {
+            let sample = take_sample(start, report.cycles);
+            on_sample(&sample);
+            match &baseline {
+                Some(base) => report.failures.extend(check_growth(base, &sample, &options.limits)),
+                None => baseline = Some(sample.clone()),
+            }
+            report.samples.push(sample);
+            next_sample += options.sample_interval;
+        }
+
+        if !report.failures.is_empty() {
+            break;
+        }
+    }
+
+    let _ = timer.shutdown();
+    if report.cycles > 0 {
+        report.mean_drift_ms = (total_drift / report.cycles as u32).as_millis() as u64;
+    }
+    report.max_drift_ms = max_drift.as_millis() as u64;
+    report
+}
+
+/// 执行一次复制/粘贴/清除循环
+///
+/// # 返回值
+/// * `Result<Duration, String>` - 倒计时完成时间与预期的偏差
+async fn run_cycle(
+    engine: &mut CryptoEngine,
+    clipboard: &TestClipboard,
+    timer: &DestructTimer,
+    completed_rx: &mut mpsc::UnboundedReceiver<Instant>,
+    options: &SoakOptions,
+) -> Result<Duration, String> {
+    // 复制：生成合成的敏感内容并加密存入测试剪贴板
+    let mut random = [0u8; 16];
+    OsRng.fill_bytes(&mut random);
+    let secret = Zeroizing::new(format!(
+        "sk_soak_{}",
+        random.iter().map(|b| format!("{:02x}", b)).collect::<String>()
+    ));
+    let encrypted = engine.encrypt(secret.as_bytes()).map_err(|e| e.to_string())?;
+    clipboard.set(encrypted.to_base64());
+
+    // 粘贴：从测试剪贴板读出密文，解密并重置密钥
+    let stored = clipboard.get().ok_or("测试剪贴板为空")?;
+    let data = EncryptedData::from_base64(&stored).map_err(|e| e.to_string())?;
+    let plaintext = Zeroizing::new(engine.decrypt_and_reset_key(&data).map_err(|e| e.to_string())?);
+    if plaintext.as_slice() != secret.as_bytes() {
+        return Err("解密内容与原文不一致".to_string());
+    }
+
+    // 明文经过安全内存后擦除
+    let mut block = SecureMemoryBlock::allocate(plaintext.len()).map_err(|e| e.to_string())?;
+    let _ = block.lock();
+    block.as_mut_slice().copy_from_slice(&plaintext);
+    block.secure_erase();
+    drop(block);
+
+    // 清除：启动倒计时并等待完成
+    while completed_rx.try_recv().is_ok() {}
+    let started = Instant::now();
+    timer.start_countdown(options.countdown).map_err(|e| e.to_string())?;
+    let wait_limit = options.countdown + options.limits.max_timer_drift * 4;
+    let completed_at = tokio::time::timeout(wait_limit, completed_rx.recv())
+        .await
+        .map_err(|_| format!("倒计时在 {}ms 内未完成", wait_limit.as_millis()))?
+        .ok_or("定时器服务已停止")?;
+    clipboard.clear();
+
+    let actual = completed_at.saturating_duration_since(started);
+    Ok(actual.abs_diff(options.countdown))
+}
+
+/// 采集当前资源使用情况
+fn take_sample(start: Instant, cycles: u64) -> ResourceSample {
+    ResourceSample {
+        elapsed_seconds: start.elapsed().as_secs(),
+        cycles,
+        rss_kb: resident_memory_kb(),
+        open_fds: open_descriptors(),
+        tasks: tokio::runtime::Handle::current().metrics().num_alive_tasks(),
+    }
+}
+
+/// 与基线比较资源增长
+///
+/// # 返回值
+/// * `Vec<String>` - 超过阈值的项目
+fn check_growth(baseline: &ResourceSample, sample: &ResourceSample, limits: &SoakLimits) -> Vec<String> {
+    let mut failures = Vec::new();
+
+    if let (Some(base), Some(now)) = (baseline.rss_kb, sample.rss_kb) {
+        if now.saturating_sub(base) > limits.max_rss_growth_kb {
+            failures.push(format!("常驻内存从 {}KB 增长到 {}KB，疑似内存泄漏", base, now));
+        }
+    }
+    if let (Some(base), Some(now)) = (baseline.open_fds, sample.open_fds) {
+        if now.saturating_sub(base) > limits.max_fd_growth {
+            failures.push(format!("文件描述符从 {} 增长到 {}，疑似句柄泄漏", base, now));
+        }
+    }
+    if sample.tasks.saturating_sub(baseline.tasks) > limits.max_task_growth {
+        failures.push(format!("异步任务从 {} 增长到 {}，疑似任务泄漏", baseline.tasks, sample.tasks));
+    }
+
+    failures
+}
+
+/// Linux: 从 /proc/self/statm 读取常驻内存
+#[cfg(target_os = "linux")]
+fn resident_memory_kb() -> Option<u64> {
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    Some(pages * page_size.max(0) as u64 / 1024)
+}
+
+/// macOS: 通过ps读取常驻内存
+#[cfg(target_os = "macos")]
+fn resident_memory_kb() -> Option<u64> {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "rss=", "-p", &std::process::id().to_string()])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn resident_memory_kb() -> Option<u64> {
+    None
+}
+
+/// Unix: 统计打开的文件描述符
+#[cfg(unix)]
+fn open_descriptors() -> Option<u64> {
+    let dir = if cfg!(target_os = "linux") { "/proc/self/fd" } else { "/dev/fd" };
+    std::fs::read_dir(dir).ok().map(|entries| entries.count() as u64)
+}
+
+/// Windows: 进程句柄数
+#[cfg(target_os = "windows")]
+fn open_descriptors() -> Option<u64> {
+    use winapi::um::processthreadsapi::{GetCurrentProcess, GetProcessHandleCount};
+
+    let mut count = 0u32;
+    let ok = unsafe { GetProcessHandleCount(GetCurrentProcess(), &mut count) };
+    (ok != 0).then_some(count as u64)
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn open_descriptors() -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(rss_kb: u64, open_fds: u64, tasks: usize) -> ResourceSample {
+        ResourceSample { elapsed_seconds: 0, cycles: 0, rss_kb: Some(rss_kb), open_fds: Some(open_fds), tasks }
+    }
+
+    #[test]
+    fn test_growth_detection() {
+        let limits = SoakLimits::default();
+        let baseline = sample(10_000, 10, 3);
+
+        assert!(check_growth(&baseline, &sample(12_000, 12, 4), &limits).is_empty());
+        assert_eq!(check_growth(&baseline, &sample(40_000, 10, 3), &limits).len(), 1);
+        assert_eq!(check_growth(&baseline, &sample(10_000, 50, 30), &limits).len(), 2);
+        // 资源减少不算失败
+        assert!(check_growth(&baseline, &sample(5_000, 5, 1), &limits).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_short_soak_run() {
+        let mut options = SoakOptions::hours(0.0);
+        options.duration = Duration::from_millis(10);
+        let report = run(&options, |_| {}).await;
+
+        assert!(report.passed(), "{:?}", report.failures);
+        assert_eq!(report.cycles, 1);
+        assert_eq!(report.samples.len(), 1);
+    }
+}
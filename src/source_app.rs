@@ -0,0 +1,250 @@
+/*!
+ * ClipVanish™ 来源应用检测模块
+ *
+ * 尽力识别复制操作发生时的前台应用，用于事件元数据；以及接管剪贴板的程序，用于审计
+ * 特点：
+ * - 通常只返回应用/进程名称；窗口标题（可能包含敏感信息）只供登录页面检测在内存中比较，从不记录
+ * - 检测失败时返回None，不影响主流程
+ * - 剪贴板所有者：Windows通过GetClipboardOwner确定；macOS读取 org.nspasteboard.source 声明；
+ *   其他情况以前台应用推测，并在结果中注明
+ *
+ * 作者: ClipVanish Team
+ */
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+use crate::helpers;
+
+/// 剪贴板所有者
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClipboardOwner {
+    /// 应用/进程名称（macOS上可能是Bundle ID）
+    pub app: String,
+    /// 进程ID（已知时）
+    pub pid: Option<u32>,
+    /// 是否由系统接口或应用声明确定（否则为前台应用推测）
+    pub exact: bool,
+}
+
+impl std::fmt::Display for ClipboardOwner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.app)?;
+        if let Some(pid) = self.pid {
+            write!(f, " (pid {})", pid)?;
+        }
+        if !self.exact {
+            write!(f, " [前台应用推测]")?;
+        }
+        Ok(())
+    }
+}
+
+/// 获取当前剪贴板所有者
+///
+/// 无法直接确定时以前台应用推测（通常就是刚刚复制或清空剪贴板的程序）
+///
+/// # 返回值
+/// * `Option<ClipboardOwner>` - 剪贴板所有者，无法确定时返回None
+pub fn clipboard_owner() -> Option<ClipboardOwner> {
+    detect_owner().or_else(|| {
+        foreground_app().map(|app| ClipboardOwner { app, pid: None, exact: false })
+    })
+}
+
+/// 获取当前前台应用名称
+///
+/// # 返回值
+/// * `Option<String>` - 应用名称，无法确定时返回None
+pub fn foreground_app() -> Option<String> {
+    detect().map(|name| name.trim().to_string()).filter(|name| !name.is_empty())
+}
+
+/// 获取前台窗口标题（只用于登录页面检测，不得写入日志或审计记录）
+///
+/// # 返回值
+/// * `Option<String>` - 窗口标题，无法确定时返回None
+pub fn foreground_window_title() -> Option<String> {
+    detect_title().map(|title| title.trim().to_string()).filter(|title| !title.is_empty())
+}
+
+/// Linux: 通过xdotool获取前台窗口标题
+#[cfg(target_os = "linux")]
+fn detect_title() -> Option<String> {
+    let output = helpers::output("xdotool", &["getactivewindow", "getwindowname"])
+        .ok()
+        .filter(|output| output.status.success())?;
+
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// macOS: 通过System Events获取前台进程最前面的窗口标题（需要辅助功能权限）
+#[cfg(target_os = "macos")]
+fn detect_title() -> Option<String> {
+    let output = helpers::output(
+        "osascript",
+        &["-e", "tell application \"System Events\" to get name of front window of (first application process whose frontmost is true)"],
+    )
+    .ok()
+    .filter(|output| output.status.success())?;
+
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Windows: 读取前台窗口的标题
+#[cfg(target_os = "windows")]
+fn detect_title() -> Option<String> {
+    use winapi::um::winuser::{GetForegroundWindow, GetWindowTextW};
+
+    let window = unsafe { GetForegroundWindow() };
+    if window.is_null() {
+        return None;
+    }
+
+    let mut buffer = [0u16; 512];
+    let len = unsafe { GetWindowTextW(window, buffer.as_mut_ptr(), buffer.len() as i32) };
+    (len > 0).then(|| String::from_utf16_lossy(&buffer[..len as usize]))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn detect_title() -> Option<String> {
+    None
+}
+
+/// Linux: 通过xdotool获取前台窗口进程，再读取进程名
+#[cfg(target_os = "linux")]
+fn detect() -> Option<String> {
+    let output = helpers::output("xdotool", &["getactivewindow", "getwindowpid"])
+        .ok()
+        .filter(|output| output.status.success())?;
+
+    let pid: u32 = String::from_utf8_lossy(&output.stdout).trim().parse().ok()?;
+    std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok()
+}
+
+/// macOS: 通过System Events获取前台进程名
+#[cfg(target_os = "macos")]
+fn detect() -> Option<String> {
+    let output = helpers::output(
+        "osascript",
+        &["-e", "tell application \"System Events\" to get name of first application process whose frontmost is true"],
+    )
+    .ok()
+        .filter(|output| output.status.success())?;
+
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Windows: 通过前台窗口所属进程获取可执行文件名
+#[cfg(target_os = "windows")]
+fn detect() -> Option<String> {
+    use winapi::um::winuser::GetForegroundWindow;
+
+    let window = unsafe { GetForegroundWindow() };
+    window_process(window).and_then(process_name)
+}
+
+/// Windows: 剪贴板所有者窗口所属的进程
+#[cfg(target_os = "windows")]
+fn detect_owner() -> Option<ClipboardOwner> {
+    use winapi::um::winuser::GetClipboardOwner;
+
+    // 以NULL窗口打开剪贴板写入的程序没有所有者，交由前台应用推测
+    let window = unsafe { GetClipboardOwner() };
+    let pid = window_process(window)?;
+    Some(ClipboardOwner {
+        app: process_name(pid).unwrap_or_else(|| "未知进程".to_string()),
+        pid: Some(pid),
+        exact: true,
+    })
+}
+
+/// Windows: 窗口所属的进程ID
+#[cfg(target_os = "windows")]
+pub(crate) fn window_process(window: winapi::shared::windef::HWND) -> Option<u32> {
+    use winapi::um::winuser::GetWindowThreadProcessId;
+
+    if window.is_null() {
+        return None;
+    }
+
+    let mut pid = 0u32;
+    unsafe {
+        GetWindowThreadProcessId(window, &mut pid);
+    }
+    Some(pid).filter(|&pid| pid != 0)
+}
+
+/// Windows: 进程的可执行文件名
+#[cfg(target_os = "windows")]
+pub(crate) fn process_name(pid: u32) -> Option<String> {
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::processthreadsapi::OpenProcess;
+    use winapi::um::winbase::QueryFullProcessImageNameW;
+    use winapi::um::winnt::PROCESS_QUERY_LIMITED_INFORMATION;
+
+    unsafe {
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if process.is_null() {
+            return None;
+        }
+
+        let mut buffer = [0u16; 260];
+        let mut len = buffer.len() as u32;
+        let ok = QueryFullProcessImageNameW(process, 0, buffer.as_mut_ptr(), &mut len);
+        CloseHandle(process);
+
+        if ok == 0 {
+            return None;
+        }
+
+        let path = String::from_utf16_lossy(&buffer[..len as usize]);
+        path.rsplit('\\').next().map(str::to_string)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn detect() -> Option<String> {
+    None
+}
+
+/// macOS: 读取复制方按nspasteboard.org约定声明的来源（Bundle ID）
+#[cfg(target_os = "macos")]
+fn detect_owner() -> Option<ClipboardOwner> {
+    const SCRIPT: &str = "ObjC.import('AppKit'); \
+        var source = $.NSPasteboard.generalPasteboard.stringForType('org.nspasteboard.source'); \
+        source.isNil() ? '' : source.js";
+
+    let output = helpers::output("osascript", &["-l", "JavaScript", "-e", SCRIPT])
+        .ok()
+        .filter(|output| output.status.success())?;
+
+    let app = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!app.is_empty()).then_some(ClipboardOwner { app, pid: None, exact: true })
+}
+
+/// Linux: X11选择所有者没有可用的进程信息接口，交由前台应用推测
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn detect_owner() -> Option<ClipboardOwner> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_owner_display_marks_guess() {
+        let exact = ClipboardOwner { app: "notepad.exe".to_string(), pid: Some(42), exact: true };
+        assert_eq!(exact.to_string(), "notepad.exe (pid 42)");
+
+        let guess = ClipboardOwner { app: "firefox".to_string(), pid: None, exact: false };
+        assert_eq!(guess.to_string(), "firefox [前台应用推测]");
+    }
+
+    #[test]
+    fn test_foreground_app_never_empty() {
+        // 无图形环境时返回None，有结果时不应为空字符串
+        if let Some(name) = foreground_app() {
+            assert!(!name.is_empty());
+        }
+    }
+}
@@ -0,0 +1,620 @@
+/*!
+ * ClipVanish™ 使用统计报告模块
+ *
+ * 根据审计日志中的保护与销毁记录生成本地使用报告
+ * 特点：
+ * - 按星期与小时统计敏感内容的复制时段（热力图）
+ * - 统计各检测器的命中次数与平均暴露时间
+ * - 按周汇总趋势，HTML报告为单文件、不引用任何外部资源
+ * - 完全在本地生成，数据来源只有审计日志中的元数据
+ * - 可选的共享统计：按天界定区间，只含固定类别的计数，经拉普拉斯机制加噪满足ε-差分隐私，
+ *   用户自行决定是否发送，帮助调整默认检测器
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Timelike, Utc};
+use rand::Rng;
+use serde::Serialize;
+use crate::audit::AuditEntry;
+use crate::config::Config;
+
+/// 星期名称（周一开始）
+const WEEKDAYS: [&str; 7] = ["周一", "周二", "周三", "周四", "周五", "周六", "周日"];
+
+/// 单周趋势
+#[derive(Debug, Clone, Serialize)]
+pub struct WeekTrend {
+    /// 周一日期（本地时间）
+    pub week_start: NaiveDate,
+    /// 受保护条目数
+    pub protected: u64,
+    /// 平均暴露时间（秒，没有已销毁条目时为None）
+    pub average_exposure_seconds: Option<f64>,
+    /// 拦截延迟超出SLO的次数
+    pub slo_violations: u64,
+}
+
+/// 使用统计报告
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageReport {
+    /// 生成时间
+    pub generated_at: DateTime<Utc>,
+    /// 统计起点（最近7天）
+    pub period_start: DateTime<Utc>,
+    /// 最近7天受保护条目数
+    pub protected: u64,
+    /// 最近7天按星期（周一为0）与小时统计的受保护条目数
+    pub heatmap: [[u64; 24]; 7],
+    /// 最近7天各检测器（规则/检测器）的命中次数
+    pub detectors: BTreeMap<String, u64>,
+    /// 最近7天按原因统计的销毁条目数
+    pub destroyed: BTreeMap<String, u64>,
+    /// 最近7天平均暴露时间（秒）
+    pub average_exposure_seconds: Option<f64>,
+    /// 最近7天拦截延迟超出SLO的次数
+    pub slo_violations: u64,
+    /// 按周趋势（由远及近）
+    pub trend: Vec<WeekTrend>,
+}
+
+/// 一个受保护条目从保护到销毁的过程
+struct Exposure {
+    /// 保护时间
+    protected_at: DateTime<Utc>,
+    /// 命中的类别
+    category: String,
+    /// 销毁时间与原因（仍未销毁时为None）
+    destroyed: Option<(DateTime<Utc>, String)>,
+}
+
+impl Exposure {
+    /// 暴露时长（秒）
+    fn seconds(&self) -> Option<f64> {
+        self.destroyed
+            .as_ref()
+            .map(|(at, _)| (*at - self.protected_at).num_milliseconds().max(0) as f64 / 1000.0)
+    }
+}
+
+/// 从审计记录详情中取出 `键=值` 字段
+fn field<'a>(detail: &'a str, key: &str) -> Option<&'a str> {
+    let start = detail.find(key)? + key.len();
+    let rest = &detail[start..];
+    Some(rest.split_whitespace().next().unwrap_or(rest))
+}
+
+/// 从审计记录详情中取出条目ID（`条目 <ULID>`，旧版本记录为 `条目 #N`）
+pub(crate) fn item_id(detail: &str) -> Option<&str> {
+    field(detail, "条目 ").map(|id| id.trim_start_matches('#')).filter(|id| !id.is_empty())
+}
+
+/// 将审计记录按条目配对为暴露过程
+///
+/// 旧版本的条目ID在每次会话中重新编号，因此遇到会话结束记录时清空未配对的条目
+fn collect_exposures(entries: &[AuditEntry]) -> Vec<Exposure> {
+    let mut exposures = Vec::new();
+    let mut live: HashMap<&str, usize> = HashMap::new();
+
+    for entry in entries {
+        match entry.event.as_str() {
+            "protected" => {
+                let Some(id) = item_id(&entry.detail) else { continue };
+                live.insert(id, exposures.len());
+                exposures.push(Exposure {
+                    protected_at: entry.timestamp,
+                    category: field(&entry.detail, "类别=").unwrap_or("未知").to_string(),
+                    destroyed: None,
+                });
+            }
+            "destroyed" => {
+                let Some(index) = item_id(&entry.detail).and_then(|id| live.remove(id)) else { continue };
+                let reason = field(&entry.detail, "原因=").unwrap_or("未知").to_string();
+                exposures[index].destroyed = Some((entry.timestamp, reason));
+            }
+            "session" => live.clear(),
+            _ => {}
+        }
+    }
+
+    exposures
+}
+
+/// 平均暴露时间（秒）
+fn average_exposure<'a>(exposures: impl Iterator<Item = &'a Exposure>) -> Option<f64> {
+    let samples: Vec<f64> = exposures.filter_map(Exposure::seconds).collect();
+    (!samples.is_empty()).then(|| samples.iter().sum::<f64>() / samples.len() as f64)
+}
+
+impl UsageReport {
+    /// 根据审计记录生成报告
+    ///
+    /// # 参数
+    /// * `entries` - 按时间顺序排列的审计记录
+    /// * `now` - 报告时间
+    /// * `weeks` - 趋势包含的周数（含本周）
+    pub fn from_entries(entries: &[AuditEntry], now: DateTime<Utc>, weeks: u32) -> Self {
+        let exposures = collect_exposures(entries);
+        let period_start = now - Duration::days(7);
+        let recent: Vec<&Exposure> = exposures.iter().filter(|e| e.protected_at >= period_start).collect();
+
+        let mut heatmap = [[0u64; 24]; 7];
+        let mut detectors = BTreeMap::new();
+        let mut destroyed = BTreeMap::new();
+        for exposure in &recent {
+            let local = exposure.protected_at.with_timezone(&Local);
+            heatmap[local.weekday().num_days_from_monday() as usize][local.hour() as usize] += 1;
+            *detectors.entry(exposure.category.clone()).or_insert(0) += 1;
+            if let Some((_, reason)) = &exposure.destroyed {
+                *destroyed.entry(reason.clone()).or_insert(0) += 1;
+            }
+        }
+
+        let slo_events: Vec<DateTime<Utc>> = entries
+            .iter()
+            .filter(|entry| entry.event == "interception_slo")
+            .map(|entry| entry.timestamp)
+            .collect();
+
+        let this_week = week_start(now);
+        let trend = (0..weeks.max(1) as i64)
+            .rev()
+            .map(|offset| {
+                let start = this_week - Duration::weeks(offset);
+                let in_week: Vec<&Exposure> = exposures
+                    .iter()
+                    .filter(|e| week_start(e.protected_at) == start)
+                    .collect();
+                WeekTrend {
+                    week_start: start,
+                    protected: in_week.len() as u64,
+                    average_exposure_seconds: average_exposure(in_week.into_iter()),
+                    slo_violations: slo_events.iter().filter(|&&at| week_start(at) == start).count() as u64,
+                }
+            })
+            .collect();
+
+        UsageReport {
+            generated_at: now,
+            period_start,
+            protected: recent.len() as u64,
+            heatmap,
+            detectors,
+            destroyed,
+            average_exposure_seconds: average_exposure(recent.into_iter()),
+            slo_violations: slo_events.iter().filter(|&&at| at >= period_start).count() as u64,
+            trend,
+        }
+    }
+
+    /// 复制敏感内容最多的时段（星期, 小时）
+    pub fn peak_slot(&self) -> Option<(usize, usize)> {
+        let mut peak = None;
+        let mut peak_count = 0;
+        for (day, hours) in self.heatmap.iter().enumerate() {
+            for (hour, &count) in hours.iter().enumerate() {
+                if count > peak_count {
+                    peak_count = count;
+                    peak = Some((day, hour));
+                }
+            }
+        }
+        peak
+    }
+
+    /// 生成单文件HTML报告（内联样式与SVG，不引用外部资源）
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        let max_cell = self.heatmap.iter().flatten().copied().max().unwrap_or(0).max(1);
+
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>ClipVanish 使用报告</title>\n<style>\n\
+             body{{font-family:sans-serif;margin:2em;color:#222}}\n\
+             table{{border-collapse:collapse}}\n\
+             td,th{{padding:2px 6px;text-align:center;font-size:12px}}\n\
+             .heat td{{width:18px;height:18px;border:1px solid #fff}}\n\
+             .bar{{background:#c0392b;height:12px}}\n\
+             </style>\n</head>\n<body>\n\
+             <h1>🔒 ClipVanish 使用报告</h1>\n<p>统计区间: {} — {}（本地时间）</p>\n",
+            self.period_start.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+            self.generated_at.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+        );
+
+        let _ = write!(
+            html,
+            "<h2>概览</h2>\n<ul>\n<li>受保护条目: {}</li>\n<li>平均暴露时间: {}</li>\n\
+             <li>拦截延迟超出SLO: {} 次</li>\n",
+            self.protected,
+            format_seconds(self.average_exposure_seconds),
+            self.slo_violations,
+        );
+        if let Some((day, hour)) = self.peak_slot() {
+            let _ = writeln!(html, "<li>复制敏感内容最多的时段: {} {:02}:00</li>", WEEKDAYS[day], hour);
+        }
+        html.push_str("</ul>\n");
+
+        html.push_str("<h2>复制时段热力图</h2>\n<table class=\"heat\">\n<tr><th></th>");
+        for hour in 0..24 {
+            let _ = write!(html, "<th>{}</th>", hour);
+        }
+        html.push_str("</tr>\n");
+        for (day, hours) in self.heatmap.iter().enumerate() {
+            let _ = write!(html, "<tr><th>{}</th>", WEEKDAYS[day]);
+            for &count in hours {
+                let alpha = count as f64 / max_cell as f64;
+                let _ = write!(
+                    html,
+                    "<td style=\"background:rgba(192,57,43,{:.2})\" title=\"{}\"></td>",
+                    alpha, count
+                );
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n");
+
+        html.push_str("<h2>检测器命中</h2>\n<table>\n");
+        let max_detector = self.detectors.values().copied().max().unwrap_or(0).max(1);
+        for (category, &count) in &self.detectors {
+            let _ = writeln!(
+                html,
+                "<tr><td style=\"text-align:left\">{}</td><td>{}</td>\
+                 <td style=\"width:200px\"><div class=\"bar\" style=\"width:{}%\"></div></td></tr>",
+                escape_html(category),
+                count,
+                count * 100 / max_detector
+            );
+        }
+        html.push_str("</table>\n");
+
+        if !self.destroyed.is_empty() {
+            html.push_str("<h2>销毁原因</h2>\n<ul>\n");
+            for (reason, count) in &self.destroyed {
+                let _ = writeln!(html, "<li>{}: {}</li>", escape_html(reason), count);
+            }
+            html.push_str("</ul>\n");
+        }
+
+        html.push_str("<h2>每周趋势</h2>\n");
+        html.push_str(&self.trend_svg());
+        html.push_str("\n<table>\n<tr><th>周</th><th>受保护条目</th><th>平均暴露时间</th><th>SLO超时</th></tr>\n");
+        for week in &self.trend {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                week.week_start,
+                week.protected,
+                format_seconds(week.average_exposure_seconds),
+                week.slo_violations
+            );
+        }
+        html.push_str("</table>\n</body>\n</html>\n");
+
+        html
+    }
+
+    /// 每周受保护条目数的折线图
+    fn trend_svg(&self) -> String {
+        const WIDTH: f64 = 480.0;
+        const HEIGHT: f64 = 120.0;
+
+        let max = self.trend.iter().map(|week| week.protected).max().unwrap_or(0).max(1) as f64;
+        let step = if self.trend.len() > 1 { WIDTH / (self.trend.len() - 1) as f64 } else { 0.0 };
+        let points: Vec<String> = self
+            .trend
+            .iter()
+            .enumerate()
+            .map(|(index, week)| {
+                let y = HEIGHT - week.protected as f64 / max * (HEIGHT - 10.0) - 5.0;
+                format!("{:.1},{:.1}", index as f64 * step, y)
+            })
+            .collect();
+
+        format!(
+            "<svg width=\"{}\" height=\"{}\" viewBox=\"-5 0 {} {}\">\
+             <polyline fill=\"none\" stroke=\"#c0392b\" stroke-width=\"2\" points=\"{}\"/></svg>",
+            WIDTH + 10.0,
+            HEIGHT,
+            WIDTH + 10.0,
+            HEIGHT,
+            points.join(" ")
+        )
+    }
+}
+
+impl std::fmt::Display for UsageReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "📊 最近7天使用报告")?;
+        writeln!(f, "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━")?;
+        writeln!(f, "🔒 受保护条目: {}", self.protected)?;
+        writeln!(f, "⏳ 平均暴露时间: {}", format_seconds(self.average_exposure_seconds))?;
+        writeln!(f, "⚡ 拦截延迟超出SLO: {} 次", self.slo_violations)?;
+        if let Some((day, hour)) = self.peak_slot() {
+            writeln!(f, "🕘 复制敏感内容最多的时段: {} {:02}:00", WEEKDAYS[day], hour)?;
+        }
+
+        writeln!(f, "🔍 检测器命中:")?;
+        for (category, count) in &self.detectors {
+            writeln!(f, "   {}: {}", category, count)?;
+        }
+
+        write!(f, "📈 每周趋势:")?;
+        for week in &self.trend {
+            write!(
+                f,
+                "\n   {} 起: {} 条，平均暴露 {}，SLO超时 {} 次",
+                week.week_start,
+                week.protected,
+                format_seconds(week.average_exposure_seconds),
+                week.slo_violations
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// 共享统计的默认隐私预算ε
+pub const DEFAULT_EPSILON: f64 = 1.0;
+
+/// 共享统计允许的最大隐私预算ε（再大噪声已不足以提供有意义的保护）
+pub const MAX_EPSILON: f64 = 10.0;
+
+/// 共享统计的格式标识
+const SHARE_FORMAT: &str = "clipvanish-dp-stats/1";
+
+/// 共享统计中的检测器（规则/检测器），自定义或未知的类别归入 `other`
+const SHARED_DETECTORS: [&str; 5] = [
+    "sensitive_pattern/regex",
+    "sensitive_pattern/substring",
+    "entropy_rule/entropy_hex",
+    "entropy_rule/entropy_base64",
+    "entropy_rule/entropy",
+];
+
+/// 共享统计中的销毁原因，其他原因归入 `other`
+const SHARED_REASONS: [&str; 6] = ["倒计时到期", "手动清除", "紧急销毁", "程序退出", "解除保护", "延长过多强制销毁"];
+
+/// 暴露时间分桶（上限秒数, 名称），超过最后一个上限的归入 `>=30min`
+const EXPOSURE_BUCKETS: [(f64, &str); 5] = [(10.0, "<10s"), (30.0, "10-30s"), (60.0, "30-60s"), (300.0, "1-5min"), (1800.0, "5-30min")];
+
+/// 每个受保护条目最多影响的计数之和（L1敏感度）：检测器、销毁原因、暴露时间三个直方图各计一次
+const SHARE_SENSITIVITY: f64 = 3.0;
+
+/// 可共享的加噪统计
+///
+/// 以单个受保护条目为隐私单位：三个直方图的每个计数加上尺度为 `sensitivity / epsilon` 的拉普拉斯噪声，
+/// 整体满足ε-差分隐私；取整与截断为非负数属于后处理，不影响隐私保证。
+/// 类别集合固定，输出中不含内容、条目ID、自定义规则名或比一天更细的时间
+#[derive(Debug, Clone, Serialize)]
+pub struct SharedStats {
+    /// 格式标识
+    pub format: &'static str,
+    /// 程序版本
+    pub version: &'static str,
+    /// 差分隐私机制
+    pub mechanism: &'static str,
+    /// 隐私预算ε
+    pub epsilon: f64,
+    /// L1敏感度
+    pub sensitivity: f64,
+    /// 统计起始日期（含）
+    pub period_start: NaiveDate,
+    /// 统计结束日期（含）
+    pub period_end: NaiveDate,
+    /// 各检测器的命中次数（加噪）
+    pub detectors: BTreeMap<String, u64>,
+    /// 按原因统计的销毁条目数（加噪）
+    pub destroyed: BTreeMap<String, u64>,
+    /// 按暴露时间分桶的条目数（加噪，仍未销毁的归入 `not_destroyed`）
+    pub exposure: BTreeMap<String, u64>,
+}
+
+impl SharedStats {
+    /// 根据审计记录生成加噪统计
+    ///
+    /// # 参数
+    /// * `entries` - 按时间顺序排列的审计记录
+    /// * `today` - 统计结束日期（本地时间）
+    /// * `days` - 统计的天数（含结束日期）
+    /// * `epsilon` - 隐私预算ε（须先经 `validate_epsilon` 检查）
+    /// * `rng` - 噪声的随机数来源
+    pub fn from_entries(entries: &[AuditEntry], today: NaiveDate, days: u32, epsilon: f64, rng: &mut impl Rng) -> Self {
+        let period_start = today - Duration::days(days.max(1) as i64 - 1);
+        let exposures = collect_exposures(entries);
+
+        let mut detectors: BTreeMap<&str, u64> = SHARED_DETECTORS.iter().chain(["other"].iter()).map(|&name| (name, 0)).collect();
+        let mut destroyed: BTreeMap<&str, u64> = SHARED_REASONS.iter().chain(["other"].iter()).map(|&name| (name, 0)).collect();
+        let mut exposure: BTreeMap<&str, u64> = EXPOSURE_BUCKETS
+            .iter()
+            .map(|&(_, name)| name)
+            .chain([">=30min", "not_destroyed"])
+            .map(|name| (name, 0))
+            .collect();
+
+        for item in &exposures {
+            let date = item.protected_at.with_timezone(&Local).date_naive();
+            if date < period_start || date > today {
+                continue;
+            }
+            let detector = SHARED_DETECTORS.iter().find(|&&name| name == item.category).copied().unwrap_or("other");
+            *detectors.entry(detector).or_insert(0) += 1;
+            if let Some((_, reason)) = &item.destroyed {
+                let reason = SHARED_REASONS.iter().find(|&&name| name == reason).copied().unwrap_or("other");
+                *destroyed.entry(reason).or_insert(0) += 1;
+            }
+            let bucket = match item.seconds() {
+                Some(seconds) => EXPOSURE_BUCKETS.iter().find(|&&(limit, _)| seconds < limit).map_or(">=30min", |&(_, name)| name),
+                None => "not_destroyed",
+            };
+            *exposure.entry(bucket).or_insert(0) += 1;
+        }
+
+        let scale = SHARE_SENSITIVITY / epsilon;
+        let mut noisy = |counts: BTreeMap<&str, u64>| -> BTreeMap<String, u64> {
+            counts
+                .into_iter()
+                .map(|(name, count)| (name.to_string(), (count as f64 + laplace(scale, rng)).round().max(0.0) as u64))
+                .collect()
+        };
+
+        SharedStats {
+            format: SHARE_FORMAT,
+            version: env!("CARGO_PKG_VERSION"),
+            mechanism: "laplace",
+            epsilon,
+            sensitivity: SHARE_SENSITIVITY,
+            period_start,
+            period_end: today,
+            detectors: noisy(detectors),
+            destroyed: noisy(destroyed),
+            exposure: noisy(exposure),
+        }
+    }
+}
+
+/// 检查隐私预算ε是否在允许范围内（0 < ε ≤ `MAX_EPSILON`）
+pub fn validate_epsilon(epsilon: f64) -> Result<(), String> {
+    if epsilon.is_finite() && epsilon > 0.0 && epsilon <= MAX_EPSILON {
+        Ok(())
+    } else {
+        Err(format!("epsilon 必须大于0且不超过{}，当前为 {}", MAX_EPSILON, epsilon))
+    }
+}
+
+/// 从尺度为 `scale` 的拉普拉斯分布采样（逆变换法）
+fn laplace(scale: f64, rng: &mut impl Rng) -> f64 {
+    let u: f64 = rng.gen_range(-0.5..0.5);
+    -scale * u.signum() * (1.0 - 2.0 * u.abs()).ln()
+}
+
+/// 时间点所在周的周一（本地时间）
+fn week_start(time: DateTime<Utc>) -> NaiveDate {
+    let date = time.with_timezone(&Local).date_naive();
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// 格式化秒数
+fn format_seconds(seconds: Option<f64>) -> String {
+    seconds.map_or_else(|| "-".to_string(), |s| format!("{:.1}秒", s))
+}
+
+/// 转义HTML特殊字符
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// 默认的HTML报告路径（配置目录下的reports/stats-日期.html）
+pub fn default_html_path(now: DateTime<Utc>) -> Option<PathBuf> {
+    Config::get_config_directory().ok().map(|dir| {
+        dir.join("reports")
+            .join(format!("stats-{}.html", now.with_timezone(&Local).format("%Y-%m-%d")))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(at: DateTime<Utc>, event: &str, detail: &str) -> AuditEntry {
+        AuditEntry {
+            timestamp: at,
+            event: event.to_string(),
+            detail: detail.to_string(),
+            hash: None,
+        }
+    }
+
+    #[test]
+    fn test_report_pairs_protected_and_destroyed() {
+        let now = Utc::now();
+        let start = now - Duration::hours(1);
+        let entries = vec![
+            entry(start, "protected", "条目 01JAE5S8W1K7V3X9QZ2M4N6P8R 类别=sensitive_pattern/regex 长度=20"),
+            entry(start + Duration::seconds(1), "interception_slo", "条目 01JAE5S8W1K7V3X9QZ2M4N6P8R 延迟=1500ms 阈值=1000ms 检测=1400ms 替换=100ms"),
+            entry(start + Duration::seconds(10), "destroyed", "条目 01JAE5S8W1K7V3X9QZ2M4N6P8R 原因=倒计时到期"),
+            entry(start + Duration::seconds(20), "session", "时长=60秒"),
+            // 旧版本的记录每次会话重新从 #1 编号
+            entry(start + Duration::seconds(30), "protected", "条目 #1 类别=entropy_rule/entropy_hex 长度=40"),
+            entry(start + Duration::seconds(60), "destroyed", "条目 #1 原因=紧急销毁"),
+            // 超出最近7天的记录只计入趋势
+            entry(now - Duration::days(30), "protected", "条目 #9 类别=sensitive_pattern/regex 长度=8"),
+        ];
+
+        let report = UsageReport::from_entries(&entries, now, 8);
+        assert_eq!(report.protected, 2);
+        assert_eq!(report.detectors.get("sensitive_pattern/regex"), Some(&1));
+        assert_eq!(report.detectors.get("entropy_rule/entropy_hex"), Some(&1));
+        assert_eq!(report.destroyed.get("紧急销毁"), Some(&1));
+        assert_eq!(report.average_exposure_seconds, Some(20.0));
+        assert_eq!(report.slo_violations, 1);
+        assert_eq!(report.heatmap.iter().flatten().sum::<u64>(), 2);
+        assert_eq!(report.trend.len(), 8);
+        assert_eq!(report.trend.iter().map(|week| week.protected).sum::<u64>(), 3);
+    }
+
+    #[test]
+    fn test_shared_stats_use_fixed_categories() {
+        use rand::SeedableRng;
+
+        let now = Utc::now();
+        let today = now.with_timezone(&Local).date_naive();
+        let entries = vec![
+            entry(now - Duration::seconds(20), "protected", "条目 #1 类别=my_secret_rule/regex 长度=12"),
+            entry(now, "destroyed", "条目 #1 原因=倒计时到期"),
+            entry(now, "protected", "条目 #2 类别=sensitive_pattern/regex 长度=20"),
+            // 统计区间以外的记录不计入
+            entry(now - Duration::days(60), "protected", "条目 #3 类别=sensitive_pattern/regex 长度=8"),
+        ];
+
+        // ε极大时噪声可以忽略，便于核对计数
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let shared = SharedStats::from_entries(&entries, today, 28, 1e9, &mut rng);
+        assert_eq!(shared.detectors.len(), SHARED_DETECTORS.len() + 1);
+        assert_eq!(shared.detectors["other"], 1);
+        assert_eq!(shared.detectors["sensitive_pattern/regex"], 1);
+        assert_eq!(shared.destroyed["倒计时到期"], 1);
+        assert_eq!(shared.exposure["10-30s"], 1);
+        assert_eq!(shared.exposure["not_destroyed"], 1);
+        assert_eq!(shared.period_end - shared.period_start, Duration::days(27));
+
+        let json = serde_json::to_string(&shared).unwrap();
+        assert!(!json.contains("my_secret_rule"));
+        assert!(!json.contains('T'), "不应包含比一天更细的时间: {}", json);
+    }
+
+    #[test]
+    fn test_laplace_noise_and_epsilon_bounds() {
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let samples: Vec<f64> = (0..20_000).map(|_| laplace(3.0, &mut rng)).collect();
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let mean_abs = samples.iter().map(|x| x.abs()).sum::<f64>() / samples.len() as f64;
+        // 拉普拉斯分布的均值为0，绝对值的期望等于尺度
+        assert!(mean.abs() < 0.1, "均值 {}", mean);
+        assert!((mean_abs - 3.0).abs() < 0.1, "平均绝对值 {}", mean_abs);
+
+        assert!(validate_epsilon(DEFAULT_EPSILON).is_ok());
+        assert!(validate_epsilon(0.0).is_err());
+        assert!(validate_epsilon(f64::NAN).is_err());
+        assert!(validate_epsilon(MAX_EPSILON + 1.0).is_err());
+    }
+
+    #[test]
+    fn test_html_is_self_contained_and_escaped() {
+        let now = Utc::now();
+        let entries = vec![entry(now, "protected", "条目 #1 类别=<script> 长度=1")];
+
+        let html = UsageReport::from_entries(&entries, now, 4).to_html();
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("http"));
+    }
+}
@@ -0,0 +1,325 @@
+/*!
+ * ClipVanish™ 只读状态页模块
+ *
+ * 在本机提供一个自动刷新的极简HTML页面（`http://127.0.0.1:<端口>/`），供挂墙的运维显示屏查看剪贴板保护状态，无需shell访问
+ * 特点：
+ * - 只监听127.0.0.1，只响应 `GET /` 与 `HEAD /`，页面没有任何操作入口
+ * - 显示服务状态、倒计时与最近的审计事件；事件详情经日志脱敏器处理，从不包含剪贴板内容
+ * - 显示的事件可按类型、规则标签与最低严重级别过滤
+ * - 校验Host请求头，防止DNS重绑定后被外部网页读取
+ * - 页面为单文件、不引用任何外部资源，通过 `<meta refresh>` 定时刷新
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use chrono::{DateTime, Local, Utc};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::error::RecvError;
+use crate::audit::{AuditEntry, EventFilter, Subscription};
+use crate::clipboard::ClipboardMonitor;
+use crate::redact::Scrubber;
+use crate::stats::escape_html;
+use crate::timer::DestructTimer;
+
+/// 请求头的最大长度
+const MAX_REQUEST_LEN: usize = 8 * 1024;
+
+/// 读取请求的超时时间
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 状态页配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatusPageConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 监听端口（只监听127.0.0.1）
+    pub port: u16,
+    /// 页面自动刷新间隔（秒）
+    pub refresh_seconds: u64,
+    /// 显示的最近事件条数
+    pub recent_events: usize,
+    /// 显示哪些事件（默认全部）
+    pub event_filter: EventFilter,
+}
+
+impl Default for StatusPageConfig {
+    fn default() -> Self {
+        StatusPageConfig {
+            enabled: false,
+            port: 8787,
+            refresh_seconds: 5,
+            recent_events: 20,
+            event_filter: EventFilter::default(),
+        }
+    }
+}
+
+/// 页面显示的状态快照
+#[derive(Debug)]
+struct Snapshot {
+    /// 快照时间
+    generated_at: DateTime<Utc>,
+    /// 剪贴板中是否有受保护条目
+    protected: bool,
+    /// 保护是否暂停
+    paused: bool,
+    /// 监听循环健康状态（已脱敏）
+    health: String,
+    /// 倒计时剩余时间
+    remaining: Option<Duration>,
+}
+
+/// 生成状态页HTML
+///
+/// # 参数
+/// * `snapshot` - 状态快照
+/// * `events` - 最近的审计事件（由近及远，详情已脱敏）
+/// * `refresh_seconds` - 自动刷新间隔
+fn render(snapshot: &Snapshot, events: &[AuditEntry], refresh_seconds: u64) -> String {
+    let (label, class) = match (snapshot.paused, snapshot.protected) {
+        (true, _) => ("⏸️ 保护已暂停", "paused"),
+        (false, true) => ("🔒 剪贴板中有受保护内容", "protected"),
+        (false, false) => ("✅ 剪贴板干净", "clear"),
+    };
+    let countdown = snapshot.remaining.map_or_else(|| "-".to_string(), DestructTimer::format_duration);
+
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta http-equiv=\"refresh\" content=\"{}\">\n<title>ClipVanish 状态</title>\n<style>\n\
+         body{{font-family:sans-serif;margin:2em;background:#111;color:#eee}}\n\
+         .state{{font-size:3em;padding:.4em .6em;border-radius:.3em}}\n\
+         .clear{{background:#1e6b3a}}.protected{{background:#a8321e}}.paused{{background:#7a6a12}}\n\
+         .countdown{{font-size:2.5em;margin:.5em 0}}\n\
+         table{{border-collapse:collapse;width:100%}}\n\
+         td,th{{padding:4px 8px;text-align:left;border-bottom:1px solid #333}}\n\
+         </style>\n</head>\n<body>\n\
+         <div class=\"state {}\">{}</div>\n\
+         <div class=\"countdown\">⏳ 倒计时: {}</div>\n\
+         <p>监听状态: {} · 更新于 {}</p>\n",
+        refresh_seconds.max(1),
+        class,
+        label,
+        escape_html(&countdown),
+        escape_html(&snapshot.health),
+        snapshot.generated_at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+    );
+
+    html.push_str("<h2>最近事件</h2>\n");
+    if events.is_empty() {
+        html.push_str("<p>服务启动以来没有事件</p>\n");
+    } else {
+        html.push_str("<table>\n<tr><th>时间</th><th>事件</th><th>详情</th></tr>\n");
+        for entry in events {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                entry.timestamp.with_timezone(&Local).format("%H:%M:%S"),
+                escape_html(&entry.event),
+                escape_html(&entry.detail),
+            );
+        }
+        html.push_str("</table>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// 解析请求，返回状态码与是否需要响应体
+///
+/// # 参数
+/// * `request` - 请求行与请求头
+/// * `port` - 监听端口（用于校验Host）
+fn route(request: &str, port: u16) -> (&'static str, bool) {
+    let mut lines = request.lines();
+    let mut parts = lines.next().unwrap_or_default().split_whitespace();
+    let (method, path) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+
+    let host = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("host"))
+        .map(|(_, value)| value.trim().to_ascii_lowercase());
+    let allowed_hosts = [format!("127.0.0.1:{}", port), format!("localhost:{}", port)];
+    if !host.is_some_and(|host| allowed_hosts.contains(&host)) {
+        return ("403 Forbidden", true);
+    }
+
+    match (method, path.split('?').next().unwrap_or_default()) {
+        ("GET", "/") => ("200 OK", true),
+        ("HEAD", "/") => ("200 OK", false),
+        ("GET" | "HEAD", _) => ("404 Not Found", method == "GET"),
+        _ => ("405 Method Not Allowed", true),
+    }
+}
+
+/// 只读状态页服务
+pub struct StatusPage {
+    /// 配置
+    config: StatusPageConfig,
+    /// 剪贴板监听器
+    monitor: Arc<ClipboardMonitor>,
+    /// 自毁定时器
+    timer: Arc<Mutex<DestructTimer>>,
+    /// 最近的审计事件（由近及远，详情已脱敏）
+    events: Mutex<VecDeque<AuditEntry>>,
+    /// 详情脱敏器
+    scrubber: Scrubber,
+}
+
+impl StatusPage {
+    /// 创建状态页服务
+    ///
+    /// # 参数
+    /// * `config` - 状态页配置
+    /// * `monitor` - 剪贴板监听器
+    /// * `timer` - 自毁定时器
+    pub fn new(config: StatusPageConfig, monitor: Arc<ClipboardMonitor>, timer: Arc<Mutex<DestructTimer>>) -> Self {
+        StatusPage { config, monitor, timer, events: Mutex::new(VecDeque::new()), scrubber: Scrubber::new() }
+    }
+
+    /// 绑定监听端口（只监听127.0.0.1）
+    pub async fn bind(&self) -> io::Result<TcpListener> {
+        let listener = TcpListener::bind(("127.0.0.1", self.config.port)).await?;
+        info!("状态页已启动: http://127.0.0.1:{}/", self.config.port);
+        Ok(listener)
+    }
+
+    /// 接受并处理连接，同时读取审计事件总线保留最近的事件，直到任务被取消
+    ///
+    /// # 参数
+    /// * `listener` - 已绑定的监听器
+    /// * `events` - 审计事件订阅
+    pub async fn serve(self: Arc<Self>, listener: TcpListener, mut events: Subscription) {
+        let mut bus_open = true;
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => {
+                        let page = self.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = page.handle_connection(stream).await {
+                                debug!("状态页连接结束: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => warn!("接受状态页连接失败: {}", e),
+                },
+                event = events.recv(), if bus_open => match event {
+                    Ok(entry) => self.remember(entry),
+                    Err(RecvError::Lagged(skipped)) => debug!("状态页跳过了 {} 条审计记录", skipped),
+                    Err(RecvError::Closed) => bus_open = false,
+                },
+            }
+        }
+    }
+
+    /// 保留一条审计事件（详情脱敏后保存）
+    fn remember(&self, mut entry: AuditEntry) {
+        entry.detail = self.scrubber.scrub(&entry.detail).into_owned();
+        let mut recent = self.events.lock().unwrap();
+        recent.push_front(entry);
+        recent.truncate(self.config.recent_events);
+    }
+
+    /// 处理单个请求（响应后关闭连接）
+    async fn handle_connection(&self, mut stream: TcpStream) -> io::Result<()> {
+        let mut request = Vec::new();
+        let mut buffer = [0u8; 1024];
+        let read = tokio::time::timeout(REQUEST_TIMEOUT, async {
+            while !request.windows(4).any(|window| window == b"\r\n\r\n") && request.len() < MAX_REQUEST_LEN {
+                let read = stream.read(&mut buffer).await?;
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buffer[..read]);
+            }
+            io::Result::Ok(())
+        })
+        .await;
+        if !matches!(read, Ok(Ok(()))) {
+            return Ok(());
+        }
+
+        let (status, with_body) = route(&String::from_utf8_lossy(&request), self.config.port);
+        let body = match status {
+            "200 OK" => self.page(),
+            _ => format!("{}\n", status),
+        };
+        let content_type = if status == "200 OK" { "text/html; charset=utf-8" } else { "text/plain; charset=utf-8" };
+        let header = format!(
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\n\
+             Content-Security-Policy: default-src 'none'; style-src 'unsafe-inline'\r\n\
+             X-Content-Type-Options: nosniff\r\nConnection: close\r\n\r\n",
+            status,
+            content_type,
+            body.len()
+        );
+        stream.write_all(header.as_bytes()).await?;
+        if with_body {
+            stream.write_all(body.as_bytes()).await?;
+        }
+        stream.shutdown().await
+    }
+
+    /// 生成当前的状态页
+    fn page(&self) -> String {
+        let snapshot = Snapshot {
+            generated_at: Utc::now(),
+            protected: self.monitor.has_protected_item(),
+            paused: self.monitor.is_paused(),
+            health: self.scrubber.scrub(&self.monitor.get_state().health.to_string()).into_owned(),
+            remaining: self.timer.lock().unwrap().get_remaining_time(),
+        };
+        let events: Vec<AuditEntry> = self.events.lock().unwrap().iter().cloned().collect();
+        render(&snapshot, &events, self.config.refresh_seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route() {
+        let request = |line: &str, host: &str| format!("{}\r\nHost: {}\r\nAccept: */*\r\n\r\n", line, host);
+        assert_eq!(route(&request("GET / HTTP/1.1", "127.0.0.1:8787"), 8787), ("200 OK", true));
+        assert_eq!(route(&request("GET /?kiosk=1 HTTP/1.1", "localhost:8787"), 8787), ("200 OK", true));
+        assert_eq!(route(&request("HEAD / HTTP/1.1", "127.0.0.1:8787"), 8787), ("200 OK", false));
+        assert_eq!(route(&request("GET /favicon.ico HTTP/1.1", "127.0.0.1:8787"), 8787).0, "404 Not Found");
+        assert_eq!(route(&request("POST / HTTP/1.1", "127.0.0.1:8787"), 8787).0, "405 Method Not Allowed");
+        // DNS重绑定：Host为外部域名
+        assert_eq!(route(&request("GET / HTTP/1.1", "attacker.example:8787"), 8787).0, "403 Forbidden");
+        assert_eq!(route("GET / HTTP/1.0\r\n\r\n", 8787).0, "403 Forbidden");
+    }
+
+    #[test]
+    fn test_render_escapes_events() {
+        let snapshot = Snapshot {
+            generated_at: Utc::now(),
+            protected: true,
+            paused: false,
+            health: "正常".to_string(),
+            remaining: Some(Duration::from_secs(42)),
+        };
+        let events = vec![AuditEntry {
+            timestamp: Utc::now(),
+            event: "paste_blocked".to_string(),
+            detail: "<script>alert(1)</script>".to_string(),
+            hash: None,
+        }];
+        let html = render(&snapshot, &events, 0);
+        assert!(html.contains("content=\"1\""));
+        assert!(html.contains("class=\"state protected\""));
+        assert!(html.contains("&lt;script&gt;") && !html.contains("<script>"));
+    }
+}
@@ -0,0 +1,304 @@
+/*!
+ * ClipVanish™ 监听循环监督模块
+ *
+ * 剪贴板轮询循环在独立任务中运行，由监督器负责捕获panic并重启
+ * 特点：
+ * - 循环panic或返回错误时记录原因（写入日志与审计日志），按指数退避重启
+ * - 连续失败达到阈值后标记为降级，`status` 与控制接口可见；此后仍以最长退避间隔继续尝试
+ * - 重启后稳定运行一段时间即恢复为正常，并重置失败计数
+ * - 健康检查发现循环卡住时可请求重启，卡住的循环按一次失败处理
+ * - 循环正常结束（停止服务）或停止已被请求时不再重启
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use log::{error, info, warn};
+use serde::Serialize;
+use tokio::sync::Notify;
+use tokio::time::{sleep, timeout, Instant};
+use crate::audit;
+use crate::crash;
+
+/// 监听循环健康状态
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum MonitorHealth {
+    /// 正常运行
+    Healthy,
+    /// 失败后已重启，尚未稳定
+    Recovering {
+        /// 连续失败次数
+        failures: u32,
+        /// 最近一次失败原因
+        last_error: String,
+    },
+    /// 连续失败次数过多
+    Degraded {
+        /// 连续失败次数
+        failures: u32,
+        /// 最近一次失败原因
+        last_error: String,
+    },
+}
+
+impl std::fmt::Display for MonitorHealth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MonitorHealth::Healthy => write!(f, "正常"),
+            MonitorHealth::Recovering { failures, last_error } => {
+                write!(f, "恢复中（连续失败 {} 次，最近: {}）", failures, last_error)
+            }
+            MonitorHealth::Degraded { failures, last_error } => {
+                write!(f, "降级（连续失败 {} 次，最近: {}）", failures, last_error)
+            }
+        }
+    }
+}
+
+/// 重启策略
+#[derive(Debug, Clone)]
+pub struct RestartPolicy {
+    /// 首次重启前的等待时间
+    pub initial_backoff: Duration,
+    /// 最长等待时间
+    pub max_backoff: Duration,
+    /// 连续失败多少次后标记为降级
+    pub degrade_after: u32,
+    /// 重启后稳定运行多久视为恢复
+    pub stable_after: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        RestartPolicy {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            degrade_after: 5,
+            stable_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// 第 `failures` 次连续失败后的等待时间
+    fn backoff(&self, failures: u32) -> Duration {
+        let factor = 1u32 << failures.saturating_sub(1).min(16);
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+/// 标记轮询期间处于监督之下的Future（panic钩子据此跳过致命处理）
+struct Supervised<F>(Pin<Box<F>>);
+
+impl<F: Future> Future for Supervised<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let _guard = crash::SupervisedScope::enter();
+        self.0.as_mut().poll(cx)
+    }
+}
+
+/// 在监督下运行循环，失败时重启
+///
+/// # 参数
+/// * `policy` - 重启策略
+/// * `run` - 启动一次循环，返回其Future
+/// * `stop_requested` - 是否已请求停止（为true时不再重启）
+/// * `report` - 健康状态变化回调
+/// * `restart` - 收到通知时中止当前循环并按失败重启（由健康检查在循环卡住时发出）
+pub async fn supervise<R, Fut, S, H>(policy: RestartPolicy, mut run: R, stop_requested: S, report: H, restart: Arc<Notify>)
+where
+    R: FnMut() -> Fut,
+    Fut: Future<Output = Result<(), String>> + Send + 'static,
+    S: Fn() -> bool,
+    H: Fn(MonitorHealth),
+{
+    let mut failures = 0u32;
+    report(MonitorHealth::Healthy);
+
+    loop {
+        let mut task = tokio::spawn(Supervised(Box::pin(run())));
+
+        // 失败后重启的循环稳定运行一段时间即视为恢复
+        let mut stable = failures == 0;
+        let outcome = loop {
+            tokio::select! {
+                outcome = &mut task => break outcome,
+                _ = sleep(policy.stable_after), if !stable => {
+                    info!("监听循环已稳定运行，恢复正常");
+                    failures = 0;
+                    stable = true;
+                    report(MonitorHealth::Healthy);
+                }
+                _ = restart.notified() => {
+                    // 卡在阻塞调用中的循环要到下一个await点才会结束，不无限等待
+                    task.abort();
+                    let _ = timeout(Duration::from_secs(1), &mut task).await;
+                    break Ok(Err("健康检查发现监听循环无响应".to_string()));
+                }
+            }
+        };
+
+        let reason = match outcome {
+            Ok(Ok(())) => return,
+            Ok(Err(e)) => e,
+            Err(e) if e.is_panic() => {
+                let payload = e.into_panic();
+                crash::take_supervised_panic().unwrap_or_else(|| crash::payload_message(payload.as_ref()))
+            }
+            // 任务被取消（运行时关闭）
+            Err(_) => return,
+        };
+
+        if stop_requested() {
+            return;
+        }
+
+        failures += 1;
+        let backoff = policy.backoff(failures);
+        error!("监听循环失败（连续第 {} 次），{:?} 后重启: {}", failures, backoff, reason);
+        audit::record("monitor_restart", &format!("监听循环失败（连续第 {} 次）: {}", failures, reason));
+
+        if failures >= policy.degrade_after {
+            if failures == policy.degrade_after {
+                warn!("监听循环连续失败 {} 次，服务进入降级状态", failures);
+                audit::record("monitor_degraded", &format!("监听循环连续失败 {} 次", failures));
+            }
+            report(MonitorHealth::Degraded { failures, last_error: reason });
+        } else {
+            report(MonitorHealth::Recovering { failures, last_error: reason });
+        }
+
+        let resume_at = Instant::now() + backoff;
+        while Instant::now() < resume_at {
+            if stop_requested() {
+                return;
+            }
+            sleep(Duration::from_millis(100).min(resume_at - Instant::now())).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    fn fast_policy() -> RestartPolicy {
+        RestartPolicy {
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(4),
+            degrade_after: 3,
+            stable_after: Duration::from_millis(50),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_panics_are_restarted_until_degraded() {
+        let runs = Arc::new(AtomicU32::new(0));
+        let reports = Arc::new(Mutex::new(Vec::new()));
+
+        let runs_clone = runs.clone();
+        let reports_clone = reports.clone();
+        supervise(
+            fast_policy(),
+            move || {
+                let attempt = runs_clone.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    match attempt {
+                        0..=2 => panic!("轮询失败 {}", attempt),
+                        3 => Err("剪贴板不可用".to_string()),
+                        _ => Ok(()),
+                    }
+                }
+            },
+            || false,
+            move |health| reports_clone.lock().unwrap().push(health),
+            Arc::new(Notify::new()),
+        )
+        .await;
+
+        assert_eq!(runs.load(Ordering::SeqCst), 5);
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports[0], MonitorHealth::Healthy);
+        assert!(matches!(&reports[1], MonitorHealth::Recovering { failures: 1, last_error } if last_error == "轮询失败 0"));
+        assert!(matches!(&reports[3], MonitorHealth::Degraded { failures: 3, .. }));
+        assert!(matches!(&reports[4], MonitorHealth::Degraded { failures: 4, last_error } if last_error == "剪贴板不可用"));
+    }
+
+    #[tokio::test]
+    async fn test_stable_run_recovers_and_stop_prevents_restart() {
+        let runs = Arc::new(AtomicU32::new(0));
+        let reports = Arc::new(Mutex::new(Vec::new()));
+
+        let runs_clone = runs.clone();
+        let stop_runs = runs.clone();
+        let reports_clone = reports.clone();
+        supervise(
+            fast_policy(),
+            move || {
+                let attempt = runs_clone.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if attempt == 1 {
+                        sleep(Duration::from_millis(100)).await;
+                    }
+                    Err(format!("失败 {}", attempt))
+                }
+            },
+            // 第二次运行失败后请求停止
+            move || stop_runs.load(Ordering::SeqCst) >= 2,
+            move |health| reports_clone.lock().unwrap().push(health),
+            Arc::new(Notify::new()),
+        )
+        .await;
+
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+        let reports = reports.lock().unwrap();
+        assert!(matches!(reports[1], MonitorHealth::Recovering { failures: 1, .. }));
+        assert_eq!(reports[2], MonitorHealth::Healthy);
+        assert_eq!(reports.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_restart_request_replaces_stalled_loop() {
+        let runs = Arc::new(AtomicU32::new(0));
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let restart = Arc::new(Notify::new());
+
+        let runs_clone = runs.clone();
+        let reports_clone = reports.clone();
+        let notifier = restart.clone();
+        tokio::spawn(async move {
+            sleep(Duration::from_millis(20)).await;
+            notifier.notify_one();
+        });
+        supervise(
+            fast_policy(),
+            move || {
+                let attempt = runs_clone.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    // 第一次运行卡住，重启后正常结束
+                    if attempt == 0 {
+                        std::future::pending::<()>().await;
+                    }
+                    Ok(())
+                }
+            },
+            || false,
+            move |health| reports_clone.lock().unwrap().push(health),
+            restart,
+        )
+        .await;
+
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+        let reports = reports.lock().unwrap();
+        assert!(matches!(&reports[1], MonitorHealth::Recovering { failures: 1, last_error } if last_error.contains("无响应")));
+    }
+}
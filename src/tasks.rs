@@ -0,0 +1,134 @@
+/*!
+ * ClipVanish™ 后台任务登记模块
+ *
+ * 统一管理剪贴板监听器派生的清除倒计时等后台任务，避免分离的任务无限累积
+ * 特点：
+ * - 按类别登记，同一类别只保留最新的任务，新任务启动时取消被取代的旧任务
+ * - 任务总数不超过类别数
+ * - 可从没有异步运行时的线程（如键盘钩子回调）派发任务
+ * - 停止服务时取消并等待全部任务结束
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use log::debug;
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
+
+/// 后台任务类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TaskSlot {
+    /// 复制后的清除倒计时
+    CopyCleanup,
+    /// 粘贴后的清除倒计时
+    PasteCleanup,
+    /// 撤销宽限期到期后销毁暂存条目
+    UndoExpiry,
+}
+
+/// 后台任务登记表
+pub struct TaskRegistry {
+    /// 派发任务使用的运行时
+    runtime: Mutex<Option<Handle>>,
+    /// 各类别当前的任务
+    tasks: Mutex<HashMap<TaskSlot, JoinHandle<()>>>,
+}
+
+impl TaskRegistry {
+    /// 创建登记表（在异步运行时中创建时自动记录运行时）
+    pub fn new() -> Self {
+        TaskRegistry {
+            runtime: Mutex::new(Handle::try_current().ok()),
+            tasks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 记录当前运行时（监听启动时调用）
+    pub fn attach_current_runtime(&self) {
+        if let Ok(handle) = Handle::try_current() {
+            *self.runtime.lock().unwrap() = Some(handle);
+        }
+    }
+
+    /// 派发任务，并取消同一类别中被取代的旧任务
+    ///
+    /// 没有可用的运行时时（如单元测试）在独立线程中运行，此时任务不受登记表管理
+    pub fn spawn<F>(&self, slot: TaskSlot, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let runtime = self.runtime.lock().unwrap().clone().or_else(|| Handle::try_current().ok());
+        let Some(runtime) = runtime else {
+            debug!("没有可用的异步运行时，{:?} 任务在独立线程中运行", slot);
+            std::thread::spawn(move || {
+                if let Ok(runtime) = tokio::runtime::Builder::new_current_thread().enable_time().build() {
+                    runtime.block_on(future);
+                }
+            });
+            return;
+        };
+
+        let handle = runtime.spawn(future);
+        if let Some(previous) = self.tasks.lock().unwrap().insert(slot, handle) {
+            if !previous.is_finished() {
+                debug!("取消被取代的 {:?} 任务", slot);
+                previous.abort();
+            }
+        }
+    }
+
+    /// 仍在运行的任务数
+    pub fn active_count(&self) -> usize {
+        self.tasks.lock().unwrap().values().filter(|handle| !handle.is_finished()).count()
+    }
+
+    /// 取消全部任务（不等待结束，用于同步上下文）
+    pub fn abort_all(&self) {
+        for (_, handle) in self.tasks.lock().unwrap().drain() {
+            handle.abort();
+        }
+    }
+
+    /// 取消全部任务并等待其结束
+    pub async fn shutdown(&self) {
+        let handles: Vec<_> = self.tasks.lock().unwrap().drain().map(|(_, handle)| handle).collect();
+        for handle in handles {
+            handle.abort();
+            // 被取消的任务返回JoinError::Cancelled，属于预期结果
+            let _ = handle.await;
+        }
+    }
+}
+
+impl Default for TaskRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_superseded_task_cancelled() {
+        let registry = TaskRegistry::new();
+        registry.spawn(TaskSlot::CopyCleanup, tokio::time::sleep(Duration::from_secs(3600)));
+        registry.spawn(TaskSlot::PasteCleanup, tokio::time::sleep(Duration::from_secs(3600)));
+        assert_eq!(registry.active_count(), 2);
+
+        // 同一类别的新任务取代旧任务，总数不增长
+        for _ in 0..100 {
+            registry.spawn(TaskSlot::CopyCleanup, tokio::time::sleep(Duration::from_secs(3600)));
+        }
+        tokio::task::yield_now().await;
+        assert_eq!(registry.active_count(), 2);
+
+        registry.shutdown().await;
+        assert_eq!(registry.active_count(), 0);
+    }
+}
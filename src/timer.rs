@@ -16,6 +16,8 @@ use std::time::{Duration, Instant};
 use tokio::time::{sleep, timeout};
 use tokio::sync::mpsc;
 use log::{info, warn, debug};
+use crate::clock::{self, SharedClock};
+use crate::duration;
 
 /// 定时器状态
 #[derive(Debug, Clone, PartialEq)]
@@ -97,6 +99,8 @@ pub struct DestructTimer {
     command_sender: Option<mpsc::UnboundedSender<TimerCommand>>,
     /// 是否正在运行
     is_running: Arc<Mutex<bool>>,
+    /// 时间源
+    clock: SharedClock,
 }
 
 impl DestructTimer {
@@ -105,11 +109,20 @@ impl DestructTimer {
     /// # 返回值
     /// * `DestructTimer` - 定时器实例
     pub fn new() -> Self {
+        Self::with_clock(clock::monotonic())
+    }
+
+    /// 使用指定时间源创建定时器
+    ///
+    /// # 参数
+    /// * `clock` - 时间源
+    pub fn with_clock(clock: SharedClock) -> Self {
         DestructTimer {
             state: Arc::new(Mutex::new(TimerState::Idle)),
             callback: None,
             command_sender: None,
             is_running: Arc::new(Mutex::new(false)),
+            clock,
         }
     }
     
@@ -141,6 +154,7 @@ impl DestructTimer {
         let state = self.state.clone();
         let callback = self.callback.clone();
         let is_running = self.is_running.clone();
+        let clock = self.clock.clone();
         
         // 标记为运行状态
         *is_running.lock().unwrap() = true;
@@ -163,7 +177,7 @@ impl DestructTimer {
                         {
                             let mut state_guard = state.lock().unwrap();
                             *state_guard = TimerState::Running {
-                                start_time: Instant::now(),
+                                start_time: clock.now(),
                                 total_duration: duration,
                             };
                         }
@@ -172,7 +186,7 @@ impl DestructTimer {
                         if let Some(ref cb) = callback {
                             let event = TimerEvent::Started {
                                 duration,
-                                timestamp: Instant::now(),
+                                timestamp: clock.now(),
                             };
                             cb(event);
                         }
@@ -180,9 +194,10 @@ impl DestructTimer {
                         // 启动新的定时器任务
                         let state_clone = state.clone();
                         let callback_clone = callback.clone();
+                        let clock_clone = clock.clone();
                         
                         current_timer_handle = Some(tokio::spawn(async move {
-                            Self::run_timer(duration, state_clone, callback_clone).await;
+                            Self::run_timer(duration, state_clone, callback_clone, clock_clone).await;
                         }));
                     },
                     
@@ -196,7 +211,7 @@ impl DestructTimer {
                             let remaining = {
                                 let state_guard = state.lock().unwrap();
                                 if let TimerState::Running { start_time, total_duration } = *state_guard {
-                                    let elapsed = start_time.elapsed();
+                                    let elapsed = clock.elapsed(start_time);
                                     if elapsed < total_duration {
                                         total_duration - elapsed
                                     } else {
@@ -217,7 +232,7 @@ impl DestructTimer {
                             if let Some(ref cb) = callback {
                                 let event = TimerEvent::Cancelled {
                                     remaining,
-                                    timestamp: Instant::now(),
+                                    timestamp: clock.now(),
                                 };
                                 cb(event);
                             }
@@ -241,7 +256,7 @@ impl DestructTimer {
                         // 触发重置事件
                         if let Some(ref cb) = callback {
                             let event = TimerEvent::Reset {
-                                timestamp: Instant::now(),
+                                timestamp: clock.now(),
                             };
                             cb(event);
                         }
@@ -333,7 +348,7 @@ impl DestructTimer {
     pub fn get_remaining_time(&self) -> Option<Duration> {
         let state = self.state.lock().unwrap();
         if let TimerState::Running { start_time, total_duration } = *state {
-            let elapsed = start_time.elapsed();
+            let elapsed = self.clock.elapsed(start_time);
             if elapsed < total_duration {
                 Some(total_duration - elapsed)
             } else {
@@ -369,14 +384,15 @@ impl DestructTimer {
         duration: Duration,
         state: Arc<Mutex<TimerState>>,
         callback: Option<TimerCallback>,
+        clock: SharedClock,
     ) {
-        let start_time = Instant::now();
+        let start_time = clock.now();
         let total_seconds = duration.as_secs();
         
         // 倒计时循环，每秒更新一次
         for remaining_seconds in (0..=total_seconds).rev() {
             let remaining = Duration::from_secs(remaining_seconds);
-            let elapsed = start_time.elapsed();
+            let elapsed = clock.elapsed(start_time);
             
             // 检查是否被取消
             {
@@ -392,7 +408,7 @@ impl DestructTimer {
                 let event = TimerEvent::Tick {
                     remaining,
                     elapsed,
-                    timestamp: Instant::now(),
+                    timestamp: clock.now(),
                 };
                 cb(event);
             }
@@ -416,7 +432,7 @@ impl DestructTimer {
         if let Some(ref cb) = callback {
             let event = TimerEvent::Completed {
                 total_duration: duration,
-                timestamp: Instant::now(),
+                timestamp: clock.now(),
             };
             cb(event);
         }
@@ -432,23 +448,13 @@ impl DestructTimer {
     /// # 返回值
     /// * `String` - 格式化后的时间字符串
     pub fn format_duration(duration: Duration) -> String {
-        let total_seconds = duration.as_secs();
-        
-        if total_seconds >= 3600 {
-            // 超过1小时，显示时:分:秒
-            let hours = total_seconds / 3600;
-            let minutes = (total_seconds % 3600) / 60;
-            let seconds = total_seconds % 60;
-            format!("{}:{:02}:{:02}", hours, minutes, seconds)
-        } else if total_seconds >= 60 {
-            // 超过1分钟，显示分:秒
-            let minutes = total_seconds / 60;
-            let seconds = total_seconds % 60;
-            format!("{}:{:02}", minutes, seconds)
-        } else {
-            // 少于1分钟，只显示秒
-            format!("{}s", total_seconds)
-        }
+        duration::format(duration)
+    }
+}
+
+impl Default for DestructTimer {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -469,6 +475,7 @@ mod tests {
     use super::*;
     use std::sync::atomic::{AtomicUsize, Ordering};
     use tokio::time::sleep;
+    use crate::clock::TokioClock;
     
     #[tokio::test]
     async fn test_timer_creation() {
@@ -477,9 +484,9 @@ mod tests {
         assert!(!timer.is_running());
     }
     
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn test_timer_start_and_complete() {
-        let mut timer = DestructTimer::new();
+        let mut timer = DestructTimer::with_clock(Arc::new(TokioClock));
         let event_count = Arc::new(AtomicUsize::new(0));
         let event_count_clone = event_count.clone();
         
@@ -498,8 +505,9 @@ mod tests {
         timer.set_callback(callback);
         timer.start_service().await.unwrap();
         
-        // 启动短时间的倒计时
+        // 启动短时间的倒计时（命令由服务任务异步处理）
         timer.start_countdown(Duration::from_secs(1)).unwrap();
+        tokio::task::yield_now().await;
         assert!(timer.is_running());
         
         // 等待倒计时完成
@@ -512,9 +520,9 @@ mod tests {
         timer.shutdown().unwrap();
     }
     
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn test_timer_cancellation() {
-        let mut timer = DestructTimer::new();
+        let mut timer = DestructTimer::with_clock(Arc::new(TokioClock));
         let cancelled = Arc::new(AtomicUsize::new(0));
         let cancelled_clone = cancelled.clone();
         
@@ -529,6 +537,7 @@ mod tests {
         
         // 启动较长时间的倒计时
         timer.start_countdown(Duration::from_secs(10)).unwrap();
+        tokio::task::yield_now().await;
         assert!(timer.is_running());
         
         // 等待一小段时间后取消
@@ -548,13 +557,13 @@ mod tests {
     #[test]
     fn test_duration_formatting() {
         assert_eq!(DestructTimer::format_duration(Duration::from_secs(30)), "30s");
-        assert_eq!(DestructTimer::format_duration(Duration::from_secs(90)), "1:30");
-        assert_eq!(DestructTimer::format_duration(Duration::from_secs(3661)), "1:01:01");
+        assert_eq!(DestructTimer::format_duration(Duration::from_secs(90)), "1m30s");
+        assert_eq!(DestructTimer::format_duration(Duration::from_secs(3661)), "1h1m1s");
     }
     
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn test_remaining_time() {
-        let mut timer = DestructTimer::new();
+        let mut timer = DestructTimer::with_clock(Arc::new(TokioClock));
         timer.start_service().await.unwrap();
         
         // 启动5秒倒计时
@@ -563,10 +572,9 @@ mod tests {
         // 等待1秒
         sleep(Duration::from_millis(1000)).await;
         
-        // 检查剩余时间
+        // 检查剩余时间（暂停的时间只随等待推进）
         if let Some(remaining) = timer.get_remaining_time() {
-            assert!(remaining.as_secs() <= 4);
-            assert!(remaining.as_secs() >= 3);
+            assert_eq!(remaining, Duration::from_secs(4));
         } else {
             panic!("应该有剩余时间");
         }
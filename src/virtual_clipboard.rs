@@ -0,0 +1,143 @@
+/*!
+ * ClipVanish™ 虚拟剪贴板模块
+ *
+ * 为容器与CI等没有图形环境的场景提供进程内的虚拟剪贴板（`--headless`）
+ * 特点：
+ * - 与系统剪贴板实现同一个 `ClipboardProvider` 接口，监听器、定时器与控制接口无需区分
+ * - 进程内所有剪贴板上下文共享同一份内容，`put`/`get` 在同一进程中可配合使用
+ * - 内容在覆盖或清除时清零
+ * - 同一后端在Flatpak/Snap沙箱内改用xdg-desktop-portal（`portal` 特性），沙箱外才直接访问X11
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::error::Error;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use clipboard::{ClipboardContext, ClipboardProvider};
+use zeroize::Zeroizing;
+use crate::config::ClipboardAccess;
+use crate::confinement;
+#[cfg(all(target_os = "linux", feature = "portal"))]
+use crate::portal_clipboard::{self, PortalClipboard};
+#[cfg(all(target_os = "linux", feature = "portal"))]
+use std::sync::Arc;
+
+/// 是否处于无头模式
+static HEADLESS: AtomicBool = AtomicBool::new(false);
+
+/// 虚拟剪贴板内容（进程内共享）
+static CONTENT: Mutex<Option<Zeroizing<String>>> = Mutex::new(None);
+
+/// 设置是否使用虚拟剪贴板（需在创建任何剪贴板上下文之前调用）
+pub fn set_headless(headless: bool) {
+    HEADLESS.store(headless, Ordering::SeqCst);
+}
+
+/// 是否处于无头模式
+pub fn is_headless() -> bool {
+    HEADLESS.load(Ordering::SeqCst)
+}
+
+/// 按访问方式判断是否使用门户
+///
+/// # 参数
+/// * `access` - 配置的访问方式
+pub fn uses_portal(access: ClipboardAccess) -> bool {
+    match access {
+        ClipboardAccess::Portal => true,
+        ClipboardAccess::Direct => false,
+        ClipboardAccess::Auto => cfg!(all(target_os = "linux", feature = "portal")) && confinement::is_app_sandbox(),
+    }
+}
+
+/// 剪贴板后端：系统剪贴板、门户或进程内虚拟剪贴板
+pub enum ClipboardBackend {
+    /// 系统剪贴板
+    System(ClipboardContext),
+    /// xdg-desktop-portal剪贴板（进程内共享同一会话）
+    #[cfg(all(target_os = "linux", feature = "portal"))]
+    Portal(Arc<PortalClipboard>),
+    /// 进程内虚拟剪贴板
+    Virtual,
+}
+
+impl ClipboardBackend {
+    /// 按访问方式创建剪贴板上下文；使用门户时失败不会回退到X11
+    ///
+    /// # 参数
+    /// * `access` - 配置的访问方式
+    pub fn open(access: ClipboardAccess) -> Result<Self, Box<dyn Error>> {
+        if is_headless() {
+            return Ok(ClipboardBackend::Virtual);
+        }
+
+        if uses_portal(access) {
+            #[cfg(all(target_os = "linux", feature = "portal"))]
+            return portal_clipboard::shared().map(ClipboardBackend::Portal).map_err(Into::into);
+            #[cfg(not(all(target_os = "linux", feature = "portal")))]
+            return Err("当前构建未包含portal特性，无法通过xdg-desktop-portal访问剪贴板".into());
+        }
+
+        ClipboardContext::new()
+            .map(ClipboardBackend::System)
+            .map_err(|e| confinement::annotate(&format!("{}（无图形环境时可使用 --headless）", e)).into())
+    }
+
+    /// 是否可以使用平台原生接口清除（门户与虚拟剪贴板直接写入空内容）
+    pub fn supports_native_clear(&self) -> bool {
+        matches!(self, ClipboardBackend::System(_))
+    }
+}
+
+impl ClipboardProvider for ClipboardBackend {
+    /// 按默认访问方式创建剪贴板上下文，系统剪贴板不可用时提示使用 `--headless`
+    fn new() -> Result<Self, Box<dyn Error>> {
+        Self::open(ClipboardAccess::Auto)
+    }
+
+    fn get_contents(&mut self) -> Result<String, Box<dyn Error>> {
+        match self {
+            ClipboardBackend::System(ctx) => ctx.get_contents(),
+            #[cfg(all(target_os = "linux", feature = "portal"))]
+            ClipboardBackend::Portal(portal) => portal.get_contents().map_err(Into::into),
+            ClipboardBackend::Virtual => Ok(CONTENT
+                .lock()
+                .unwrap()
+                .as_ref()
+                .map(|content| content.to_string())
+                .unwrap_or_default()),
+        }
+    }
+
+    fn set_contents(&mut self, content: String) -> Result<(), Box<dyn Error>> {
+        match self {
+            ClipboardBackend::System(ctx) => ctx.set_contents(content),
+            #[cfg(all(target_os = "linux", feature = "portal"))]
+            ClipboardBackend::Portal(portal) => portal.set_contents(content).map_err(Into::into),
+            ClipboardBackend::Virtual => {
+                // 旧内容随Zeroizing释放时清零
+                *CONTENT.lock().unwrap() = (!content.is_empty()).then(|| Zeroizing::new(content));
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_virtual_contexts_share_content() {
+        // 直接构造虚拟后端，不切换全局模式，避免影响并行运行的其他测试
+        let mut writer = ClipboardBackend::Virtual;
+        let mut reader = ClipboardBackend::Virtual;
+
+        writer.set_contents("pipeline-value".to_string()).unwrap();
+        assert_eq!(reader.get_contents().unwrap(), "pipeline-value");
+
+        writer.set_contents(String::new()).unwrap();
+        assert_eq!(reader.get_contents().unwrap(), "");
+    }
+}
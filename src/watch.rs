@@ -0,0 +1,274 @@
+/*!
+ * ClipVanish™ 实时状态行模块
+ *
+ * 为 `clipvanish watch` 生成持续刷新的单行状态（状态 | 条目存活 | 剩余 | 今日事件），适合放在终端的小窗格中
+ * 特点：
+ * - 只读取服务发布的健康状态文件、倒计时状态文件与审计日志，无需令牌也不连接控制接口
+ * - 审计日志增量读取，只在文件增长时解析新增的行，跨天或日志被替换时重新统计
+ * - 终端中原地刷新；`--plain` 与 `--output json` 每次变化输出一行，便于管道与状态栏使用
+ * - 从不读取或显示剪贴板内容
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::time::Duration;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::Serialize;
+use crate::audit::AuditEntry;
+use crate::countdown_display::Countdown;
+use crate::health::HealthReport;
+
+/// 服务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchState {
+    /// 服务未运行（健康状态文件缺失或过期）
+    Stopped,
+    /// 服务运行中但健康检查未通过
+    Unhealthy,
+    /// 运行中，剪贴板中没有受保护内容
+    Idle,
+    /// 剪贴板中有受保护内容，倒计时进行中
+    Protected,
+}
+
+impl WatchState {
+    /// 带图标的中文名称
+    fn label(&self) -> &'static str {
+        match self {
+            WatchState::Stopped => "🔴 未运行",
+            WatchState::Unhealthy => "🟠 不健康",
+            WatchState::Idle => "🟢 待机",
+            WatchState::Protected => "🔒 保护中",
+        }
+    }
+
+    /// 纯文本名称（与JSON一致）
+    fn name(&self) -> &'static str {
+        match self {
+            WatchState::Stopped => "stopped",
+            WatchState::Unhealthy => "unhealthy",
+            WatchState::Idle => "idle",
+            WatchState::Protected => "protected",
+        }
+    }
+}
+
+/// 一次刷新的状态行
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WatchLine {
+    /// 服务状态
+    pub state: WatchState,
+    /// 当前条目已存活的秒数
+    pub item_age_seconds: Option<u64>,
+    /// 倒计时剩余秒数
+    pub remaining_seconds: Option<u64>,
+    /// 今天（本地时间）的审计事件数
+    pub events_today: usize,
+}
+
+impl WatchLine {
+    /// 根据其他进程发布的状态生成状态行
+    ///
+    /// # 参数
+    /// * `health` - 健康状态文件（缺失时为None）
+    /// * `report_ttl` - 健康状态的有效期
+    /// * `countdown` - 倒计时状态文件（没有倒计时时为None）
+    /// * `events_today` - 今日审计事件数
+    /// * `now` - 当前时间
+    pub fn new(
+        health: Option<&HealthReport>,
+        report_ttl: Duration,
+        countdown: Option<&Countdown>,
+        events_today: usize,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let running = health.filter(|report| (now - report.checked_at).to_std().unwrap_or_default() <= report_ttl);
+        let remaining = countdown
+            .and_then(|countdown| (countdown.deadline - now).to_std().ok())
+            .filter(|remaining| !remaining.is_zero());
+        // 没有启用健康检查时，以进行中的倒计时作为服务运行的依据
+        let state = match (running, remaining) {
+            (Some(report), _) if !report.healthy() => WatchState::Unhealthy,
+            (_, Some(_)) => WatchState::Protected,
+            (Some(_), None) => WatchState::Idle,
+            (None, None) => WatchState::Stopped,
+        };
+        let item_age = remaining
+            .and(countdown)
+            .and_then(|countdown| countdown.item)
+            .map(|item| (now - item.created_at()).num_seconds().max(0) as u64);
+        WatchLine {
+            state,
+            item_age_seconds: item_age,
+            remaining_seconds: remaining.map(|remaining| remaining.as_secs()),
+            events_today,
+        }
+    }
+
+    /// 带图标的状态行
+    pub fn text(&self) -> String {
+        let unknown = || "—".to_string();
+        format!(
+            "{} | 条目 {} | 剩余 {} | 今日 {} 个事件",
+            self.state.label(),
+            self.item_age_seconds.map_or_else(unknown, short_duration),
+            self.remaining_seconds.map_or_else(unknown, short_duration),
+            self.events_today
+        )
+    }
+
+    /// 纯ASCII状态行
+    pub fn plain(&self) -> String {
+        let unknown = || "-".to_string();
+        format!(
+            "{} | age={} | remaining={} | events={}",
+            self.state.name(),
+            self.item_age_seconds.map_or_else(unknown, short_duration),
+            self.remaining_seconds.map_or_else(unknown, short_duration),
+            self.events_today
+        )
+    }
+}
+
+/// 紧凑时长，例如 `12s`、`2m05s`、`1h02m`
+fn short_duration(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+/// 今日审计事件计数器（增量读取审计日志）
+pub struct EventCounter {
+    /// 审计日志路径
+    path: PathBuf,
+    /// 已统计到的字节位置
+    offset: u64,
+    /// 统计的日期（本地时间）
+    date: Option<NaiveDate>,
+    /// 今日事件数
+    count: usize,
+}
+
+impl EventCounter {
+    /// 创建计数器
+    ///
+    /// # 参数
+    /// * `path` - 审计日志路径
+    pub fn new(path: PathBuf) -> Self {
+        EventCounter { path, offset: 0, date: None, count: 0 }
+    }
+
+    /// 读取新增的记录并返回今日事件数（日志不存在时为0）
+    ///
+    /// # 参数
+    /// * `today` - 今天（本地时间）
+    pub fn refresh(&mut self, today: NaiveDate) -> io::Result<usize> {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                *self = EventCounter::new(std::mem::take(&mut self.path));
+                return Ok(0);
+            }
+            Err(e) => return Err(e),
+        };
+        let len = file.metadata()?.len();
+        // 跨天或日志被替换（变短）时从头统计
+        if self.date != Some(today) || len < self.offset {
+            self.offset = 0;
+            self.count = 0;
+            self.date = Some(today);
+        }
+        if len == self.offset {
+            return Ok(self.count);
+        }
+
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut appended = Vec::new();
+        file.take(len - self.offset).read_to_end(&mut appended)?;
+        // 只处理完整的行，正在写入的最后一行留到下次
+        let Some(complete) = appended.iter().rposition(|&byte| byte == b'\n').map(|end| end + 1) else {
+            return Ok(self.count);
+        };
+        self.count += String::from_utf8_lossy(&appended[..complete])
+            .lines()
+            .filter_map(|line| serde_json::from_str::<AuditEntry>(line).ok())
+            .filter(|entry| entry.timestamp.with_timezone(&Local).date_naive() == today)
+            .count();
+        self.offset += complete as u64;
+        Ok(self.count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use crate::health::HealthCheck;
+    use crate::item_id::ItemId;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_watch_line_states() {
+        let now = Utc::now();
+        let ttl = Duration::from_secs(60);
+        let healthy = HealthReport {
+            checked_at: now,
+            pid: 1,
+            checks: vec![HealthCheck { name: "monitor_loop".to_string(), healthy: true, detail: String::new() }],
+        };
+        let item = ItemId::generate();
+        let countdown = Countdown { deadline: now + chrono::Duration::seconds(18), item: Some(item) };
+
+        let stopped = WatchLine::new(None, ttl, None, 0, now);
+        assert_eq!(stopped.state, WatchState::Stopped);
+        assert_eq!(stopped.plain(), "stopped | age=- | remaining=- | events=0");
+
+        assert_eq!(WatchLine::new(Some(&healthy), ttl, None, 3, now).state, WatchState::Idle);
+        let stale = HealthReport { checked_at: now - chrono::Duration::minutes(5), ..healthy.clone() };
+        assert_eq!(WatchLine::new(Some(&stale), ttl, None, 3, now).state, WatchState::Stopped);
+
+        let protected = WatchLine::new(Some(&healthy), ttl, Some(&countdown), 7, now + chrono::Duration::seconds(12));
+        assert_eq!(protected.state, WatchState::Protected);
+        assert_eq!(protected.remaining_seconds, Some(6));
+        assert!(protected.item_age_seconds.is_some_and(|age| (11..=13).contains(&age)));
+        assert!(protected.text().starts_with("🔒 保护中 | 条目 1"));
+
+        // 截止时间已过的状态文件视为没有倒计时
+        let expired = WatchLine::new(Some(&healthy), ttl, Some(&countdown), 7, now + chrono::Duration::minutes(1));
+        assert_eq!((expired.state, expired.item_age_seconds), (WatchState::Idle, None));
+    }
+
+    #[test]
+    fn test_event_counter_incremental() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("audit.log");
+        let today = Local::now().date_naive();
+        let line = |timestamp: DateTime<Utc>| {
+            format!("{}\n", serde_json::json!({ "timestamp": timestamp, "event": "destroyed", "detail": "条目" }))
+        };
+        let mut counter = EventCounter::new(path.clone());
+        assert_eq!(counter.refresh(today).unwrap(), 0);
+
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&path).unwrap();
+        file.write_all(line(Utc::now() - chrono::Duration::days(2)).as_bytes()).unwrap();
+        file.write_all(line(Utc::now()).as_bytes()).unwrap();
+        assert_eq!(counter.refresh(today).unwrap(), 1);
+
+        // 未写完的行不计入，写完后计入
+        let partial = line(Utc::now());
+        let (head, tail) = partial.split_at(10);
+        file.write_all(head.as_bytes()).unwrap();
+        assert_eq!(counter.refresh(today).unwrap(), 1);
+        file.write_all(tail.as_bytes()).unwrap();
+        assert_eq!(counter.refresh(today).unwrap(), 2);
+
+        // 跨天后重新统计
+        assert_eq!(counter.refresh(today.succ_opt().unwrap()).unwrap(), 0);
+    }
+}
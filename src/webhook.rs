@@ -0,0 +1,338 @@
+/*!
+ * ClipVanish™ Webhook模块
+ *
+ * 发生紧急销毁、条目销毁或策略拦截时，向配置的地址推送JSON通知，便于家庭实验室等环境联动自动化
+ * 特点：
+ * - 订阅审计事件总线，只推送端点选择的事件类型；端点还可按规则标签与最低严重级别过滤
+ * - 负载只含事件类型、条目ID与审计详情，详情经日志脱敏器处理，从不包含剪贴板内容
+ * - 每个请求以端点密钥计算HMAC-SHA256签名（`X-ClipVanish-Signature: sha256=<十六进制>`）
+ * - 发送失败按指数退避重试；各端点独立投递，慢端点不影响其他端点
+ * - 通过curl发送，地址与请求头经stdin传入，不出现在进程参数中；`--no-subprocess` 严格模式下不可用
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tokio::sync::broadcast::error::RecvError;
+use crate::audit::{AuditEntry, EventFilter, EventSeverity, Subscription};
+use crate::encoding::encode_hex;
+use crate::helpers;
+use crate::redact::Scrubber;
+
+/// 退避时长上限
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// 可推送的事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    /// 紧急销毁（含部分销毁与定时销毁）
+    Nuke,
+    /// 受保护条目被销毁
+    Destroyed,
+    /// 策略拦截（粘贴被阻止、控制接口或代理套接字拒绝请求）、诱饵令牌触发、可疑剪贴板读取者与未通过双人确认的紧急销毁
+    PolicyViolation,
+}
+
+impl WebhookEvent {
+    /// 全部事件类型
+    pub const ALL: [WebhookEvent; 3] = [WebhookEvent::Nuke, WebhookEvent::Destroyed, WebhookEvent::PolicyViolation];
+
+    /// 会推送的审计事件类型（与 `from_audit` 一致，用于订阅过滤）
+    pub const AUDIT_EVENTS: [&'static str; 8] =
+        ["nuke", "destroyed", "paste_blocked", "ipc_denied", "agent_denied", "canary_tripped", "suspicious_reader", "nuke_denied"];
+
+    /// 审计事件对应的推送类型（不推送的事件返回None）
+    ///
+    /// # 参数
+    /// * `audit_event` - 审计事件类型
+    pub fn from_audit(audit_event: &str) -> Option<Self> {
+        match audit_event {
+            "nuke" => Some(WebhookEvent::Nuke),
+            "destroyed" => Some(WebhookEvent::Destroyed),
+            "paste_blocked" | "ipc_denied" | "agent_denied" | "canary_tripped" | "suspicious_reader" | "nuke_denied" => {
+                Some(WebhookEvent::PolicyViolation)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// 推送端点
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookEndpoint {
+    /// 推送地址（http或https）
+    pub url: String,
+    /// 签名密钥
+    pub secret: String,
+    /// 推送的事件类型（默认全部）
+    #[serde(default = "default_events")]
+    pub events: Vec<WebhookEvent>,
+    /// 只推送带这些规则标签的事件（默认不限制）
+    #[serde(default)]
+    pub rules: Vec<String>,
+    /// 推送的最低严重级别（默认全部）
+    #[serde(default)]
+    pub min_severity: EventSeverity,
+}
+
+impl WebhookEndpoint {
+    /// 端点是否接收该审计记录
+    fn accepts(&self, event: WebhookEvent, entry: &AuditEntry) -> bool {
+        let filter = EventFilter { events: Vec::new(), rules: self.rules.clone(), min_severity: self.min_severity };
+        self.events.contains(&event) && filter.matches(entry)
+    }
+}
+
+fn default_events() -> Vec<WebhookEvent> {
+    WebhookEvent::ALL.to_vec()
+}
+
+/// Webhook配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    /// 推送端点（为空时不启用）
+    pub endpoints: Vec<WebhookEndpoint>,
+    /// 每个通知最多尝试的次数
+    pub max_attempts: u32,
+    /// 第一次重试前的等待时间（秒），之后每次翻倍，最长5分钟
+    pub backoff_seconds: u64,
+    /// 单次请求超时（秒）
+    pub timeout_seconds: u64,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        WebhookConfig {
+            endpoints: Vec::new(),
+            max_attempts: 5,
+            backoff_seconds: 2,
+            timeout_seconds: 10,
+        }
+    }
+}
+
+impl WebhookConfig {
+    /// 第 `attempt` 次失败后的等待时间
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+        Duration::from_secs(self.backoff_seconds.saturating_mul(factor)).min(MAX_BACKOFF)
+    }
+}
+
+/// 推送负载
+#[derive(Debug, Serialize)]
+struct Payload<'a> {
+    /// 事件类型
+    event: WebhookEvent,
+    /// 审计事件类型
+    audit_event: &'a str,
+    /// 审计详情（已脱敏）
+    detail: &'a str,
+    /// 事件时间（UTC）
+    timestamp: DateTime<Utc>,
+}
+
+/// 计算请求签名
+///
+/// # 参数
+/// * `secret` - 端点密钥
+/// * `body` - 请求体
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC接受任意长度的密钥");
+    mac.update(body);
+    format!("sha256={}", encode_hex(&mac.finalize().into_bytes()))
+}
+
+/// 转义为curl配置文件中的带引号字符串
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// 生成传给curl的配置（地址、请求头与请求体）
+fn curl_config(endpoint: &WebhookEndpoint, body: &str) -> String {
+    [
+        format!("url = {}", quote(&endpoint.url)),
+        format!("header = {}", quote("Content-Type: application/json")),
+        format!("header = {}", quote(&format!("X-ClipVanish-Signature: {}", sign(&endpoint.secret, body.as_bytes())))),
+        format!("data-binary = {}", quote(body)),
+    ]
+    .join("\n")
+}
+
+/// Webhook推送器
+pub struct WebhookDispatcher {
+    /// 配置
+    config: Arc<WebhookConfig>,
+    /// 详情脱敏器
+    scrubber: Scrubber,
+}
+
+impl WebhookDispatcher {
+    /// 创建推送器（没有端点时返回None）
+    ///
+    /// # 参数
+    /// * `config` - Webhook配置
+    pub fn new(config: WebhookConfig) -> Option<Self> {
+        if config.endpoints.is_empty() {
+            return None;
+        }
+        Some(WebhookDispatcher { config: Arc::new(config), scrubber: Scrubber::new() })
+    }
+
+    /// 订阅过滤条件：只接收会推送的事件类型，且不低于各端点中最低的严重级别
+    pub fn filter(&self) -> EventFilter {
+        EventFilter {
+            min_severity: self.config.endpoints.iter().map(|endpoint| endpoint.min_severity).min().unwrap_or_default(),
+            ..EventFilter::events(&WebhookEvent::AUDIT_EVENTS)
+        }
+    }
+
+    /// 持续读取审计事件总线并推送，直到总线关闭
+    ///
+    /// # 参数
+    /// * `events` - 审计事件订阅
+    pub async fn run(self, mut events: Subscription) {
+        loop {
+            match events.recv().await {
+                Ok(entry) => self.dispatch(&entry),
+                Err(RecvError::Lagged(skipped)) => warn!("Webhook推送落后，已跳过 {} 条审计记录", skipped),
+                Err(RecvError::Closed) => break,
+            }
+        }
+    }
+
+    /// 为选择了该事件的端点各启动一次投递
+    fn dispatch(&self, entry: &AuditEntry) {
+        let Some(event) = WebhookEvent::from_audit(&entry.event) else {
+            return;
+        };
+        let detail = self.scrubber.scrub(&entry.detail);
+        let payload = Payload { event, audit_event: &entry.event, detail: &detail, timestamp: entry.timestamp };
+        let body = match serde_json::to_string(&payload) {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("序列化Webhook负载失败: {}", e);
+                return;
+            }
+        };
+
+        for endpoint in self.config.endpoints.iter().filter(|endpoint| endpoint.accepts(event, entry)) {
+            let config = self.config.clone();
+            let endpoint = endpoint.clone();
+            let body = body.clone();
+            tokio::spawn(async move { deliver(&config, &endpoint, &body).await });
+        }
+    }
+}
+
+/// 投递一个通知，失败时按退避重试
+async fn deliver(config: &WebhookConfig, endpoint: &WebhookEndpoint, body: &str) {
+    let timeout = config.timeout_seconds.to_string();
+    let curl_config = curl_config(endpoint, body);
+
+    for attempt in 1..=config.max_attempts.max(1) {
+        let timeout = timeout.clone();
+        let curl_config = curl_config.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            helpers::run_with_stdin(
+                "curl",
+                &["-fsS", "--proto", "=http,https", "--max-time", &timeout, "-o", null_device(), "--config", "-"],
+                curl_config.as_bytes(),
+            )
+        })
+        .await;
+
+        let error = match result {
+            Ok(Ok(status)) if status.success() => {
+                debug!("Webhook已推送到 {}", endpoint.url);
+                return;
+            }
+            Ok(Ok(status)) => format!("curl退出码 {:?}", status.code()),
+            // 严格模式或找不到curl时重试没有意义
+            Ok(Err(e)) => {
+                warn!("Webhook推送到 {} 失败: {}", endpoint.url, e);
+                return;
+            }
+            Err(e) => e.to_string(),
+        };
+
+        if attempt == config.max_attempts.max(1) {
+            warn!("Webhook推送到 {} 失败（已尝试 {} 次）: {}", endpoint.url, attempt, error);
+            return;
+        }
+        let wait = config.backoff(attempt);
+        info!("Webhook推送到 {} 失败（第 {} 次）: {}，{}秒后重试", endpoint.url, attempt, error, wait.as_secs());
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// 丢弃响应体的输出路径
+fn null_device() -> &'static str {
+    if cfg!(windows) { "NUL" } else { "/dev/null" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_defaults_and_backoff() {
+        let config: WebhookConfig =
+            serde_json::from_str(r#"{"endpoints":[{"url":"https://example.invalid/hook","secret":"s"}]}"#).unwrap();
+        assert_eq!(config.endpoints[0].events, WebhookEvent::ALL.to_vec());
+        assert_eq!(config.max_attempts, 5);
+
+        assert_eq!(config.backoff(1), Duration::from_secs(2));
+        assert_eq!(config.backoff(3), Duration::from_secs(8));
+        assert_eq!(config.backoff(30), MAX_BACKOFF);
+
+        assert_eq!(WebhookEvent::from_audit("paste_blocked"), Some(WebhookEvent::PolicyViolation));
+        assert_eq!(WebhookEvent::from_audit("observed"), None);
+        for audit_event in WebhookEvent::AUDIT_EVENTS {
+            assert!(WebhookEvent::from_audit(audit_event).is_some(), "{}", audit_event);
+        }
+        assert_eq!(config.endpoints[0].min_severity, EventSeverity::Info);
+    }
+
+    #[test]
+    fn test_signed_curl_config() {
+        let endpoint = WebhookEndpoint {
+            url: "https://example.invalid/hook?id=\"x\"".to_string(),
+            secret: "Jefe".to_string(),
+            events: default_events(),
+            rules: Vec::new(),
+            min_severity: EventSeverity::Info,
+        };
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+
+        let body = r#"{"event":"nuke","detail":"a\\b"}"#;
+        let config = curl_config(&endpoint, body);
+        assert!(config.contains(r#"url = "https://example.invalid/hook?id=\"x\"""#));
+        assert!(config.contains(&format!("X-ClipVanish-Signature: {}", sign("Jefe", body.as_bytes()))));
+        assert!(config.contains(r#"data-binary = "{\"event\":\"nuke\",\"detail\":\"a\\\\b\"}""#));
+    }
+}
@@ -0,0 +1,143 @@
+/*!
+ * ClipVanish™ Windows剪贴板历史模块
+ *
+ * Windows的剪贴板历史（Win+V）会保存每一次复制的内容，受保护条目在粘贴解密后可能被记录
+ * 特点：
+ * - 清空剪贴板历史（Clipboard.ClearHistory）
+ * - 会话期间禁用剪贴板历史，会话结束后恢复原设置
+ * - 非Windows平台上所有操作均为空操作
+ *
+ * 作者: ClipVanish Team
+ */
+
+use std::sync::Mutex;
+use log::warn;
+#[cfg(target_os = "windows")]
+use log::info;
+#[cfg(target_os = "windows")]
+use crate::helpers;
+
+/// 会话开始前的剪贴板历史设置（None表示未修改过）
+static PREVIOUS_STATE: Mutex<Option<Option<bool>>> = Mutex::new(None);
+
+/// 剪贴板历史所在的注册表键
+#[cfg(target_os = "windows")]
+const CLIPBOARD_KEY: &str = r"HKCU\Software\Microsoft\Clipboard";
+
+/// 剪贴板历史开关的注册表值
+#[cfg(target_os = "windows")]
+const HISTORY_VALUE: &str = "EnableClipboardHistory";
+
+/// 清空Windows剪贴板历史
+///
+/// # 返回值
+/// * `Result<(), String>` - 操作结果
+#[cfg(target_os = "windows")]
+pub fn clear_history() -> Result<(), String> {
+    let script = "Add-Type -AssemblyName System.Runtime.WindowsRuntime; \
+        $null = [Windows.ApplicationModel.DataTransfer.Clipboard,Windows.ApplicationModel.DataTransfer,ContentType=WindowsRuntime]; \
+        if (-not [Windows.ApplicationModel.DataTransfer.Clipboard]::ClearHistory()) { exit 1 }";
+
+    let status = helpers::status(helpers::command("powershell")?.args(["-Command", script]))
+        .map_err(|e| e.to_string())?;
+
+    if status.success() {
+        info!("Windows剪贴板历史已清空");
+        Ok(())
+    } else {
+        Err(format!("ClearHistory失败，退出码: {:?}", status.code()))
+    }
+}
+
+/// 读取剪贴板历史开关（值不存在时返回None）
+#[cfg(target_os = "windows")]
+fn history_enabled() -> Result<Option<bool>, String> {
+    let output = helpers::output("reg", &["query", CLIPBOARD_KEY, "/v", HISTORY_VALUE])?;
+
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .lines()
+        .find(|line| line.contains(HISTORY_VALUE))
+        .and_then(|line| line.split_whitespace().last())
+        .map(|value| value != "0x0"))
+}
+
+/// 设置剪贴板历史开关
+#[cfg(target_os = "windows")]
+fn set_history_enabled(enabled: Option<bool>) -> Result<(), String> {
+    let status = match enabled {
+        Some(enabled) => helpers::status(helpers::command("reg")?.args([
+            "add", CLIPBOARD_KEY, "/v", HISTORY_VALUE,
+            "/t", "REG_DWORD", "/d", if enabled { "1" } else { "0" }, "/f",
+        ])),
+        None => helpers::status(helpers::command("reg")?.args(["delete", CLIPBOARD_KEY, "/v", HISTORY_VALUE, "/f"])),
+    }
+    .map_err(|e| e.to_string())?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("reg退出码: {:?}", status.code()))
+    }
+}
+
+/// 会话期间禁用剪贴板历史，并记录原设置以便恢复
+///
+/// # 返回值
+/// * `Result<(), String>` - 操作结果
+#[cfg(target_os = "windows")]
+pub fn suppress_for_session() -> Result<(), String> {
+    let mut previous = PREVIOUS_STATE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if previous.is_some() {
+        return Ok(());
+    }
+
+    let current = history_enabled()?;
+    set_history_enabled(Some(false))?;
+    *previous = Some(current);
+    info!("本次会话已禁用Windows剪贴板历史");
+    Ok(())
+}
+
+/// 会话期间禁用剪贴板历史
+#[cfg(not(target_os = "windows"))]
+pub fn suppress_for_session() -> Result<(), String> {
+    Ok(())
+}
+
+/// 恢复会话开始前的剪贴板历史设置（未修改过时不做任何处理）
+pub fn restore_after_session() {
+    let mut previous = PREVIOUS_STATE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let Some(state) = previous.take() else {
+        return;
+    };
+
+    #[cfg(target_os = "windows")]
+    {
+        match set_history_enabled(state) {
+            Ok(()) => info!("已恢复Windows剪贴板历史设置"),
+            Err(e) => warn!("恢复Windows剪贴板历史设置失败: {}", e),
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = state;
+        warn!("当前平台不应存在剪贴板历史修改记录");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_without_suppression_is_noop() {
+        restore_after_session();
+        assert!(PREVIOUS_STATE.lock().unwrap().is_none());
+    }
+}